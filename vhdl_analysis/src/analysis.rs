@@ -0,0 +1,71 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this file,
+// You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) 2019, Olof Kraigher olof.kraigher@gmail.com
+
+#[macro_use]
+mod analyze;
+mod assignment;
+mod association;
+mod attribute_catalog;
+mod call_graph;
+mod cancellation;
+mod case_choices;
+mod component;
+mod concurrent;
+mod configuration;
+mod constant_value;
+mod dead_generate;
+mod declarative;
+mod design_unit;
+mod drivers;
+mod elaborate;
+mod expand_instance;
+mod expression;
+mod fanout;
+mod formal_region;
+mod instantiation;
+mod latch;
+mod literals;
+mod lock;
+mod named_entity;
+mod names;
+mod open_port;
+mod overloaded;
+mod package_instance;
+mod parameter_mode;
+mod port_order;
+mod port_width;
+mod purity;
+mod range;
+mod region;
+mod reset_value;
+mod root;
+mod search;
+mod semantic;
+mod sensitivity_list;
+mod sequential;
+mod standard;
+mod static_expression;
+mod staticness;
+mod synthesis;
+mod target;
+mod unused;
+mod visibility;
+
+#[cfg(test)]
+mod tests;
+
+pub use self::attribute_catalog::{AttributePrefix, PredefinedAttribute, PREDEFINED_ATTRIBUTES};
+pub use self::call_graph::{Call, CallGraph};
+pub use self::cancellation::CancellationToken;
+pub use self::constant_value::ConstantValue;
+pub use self::elaborate::{ElaboratedInstance, Elaboration};
+pub use self::fanout::{ArchitectureFanout, SignalFanout};
+pub use self::instantiation::InstantiationForm;
+pub use self::root::{CompletionItem, DesignRoot, EntHierarchy, EntPositions, ItemAt, Partial};
+pub use named_entity::{
+    AnyEnt, AnyEntKind, Concurrent, Design, EntRef, EntityId, Object, ObjectInterface, Overloaded,
+    Related, Sequential, Type,
+};
@@ -10,7 +10,7 @@ use super::root::*;
 use crate::ast::*;
 use crate::data::*;
 use fnv::FnvHashSet;
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
 use std::ops::Deref;
 
 #[derive(Debug, PartialEq, Eq)]
@@ -155,6 +155,13 @@ pub(super) struct AnalyzeContext<'a> {
     uses: RefCell<FnvHashSet<UnitId>>,
     missing_unit: RefCell<FnvHashSet<(Symbol, Symbol, Option<Symbol>)>>,
     uses_library_all: RefCell<FnvHashSet<Symbol>>,
+
+    // The positions of the references to the work library through the work alias
+    work_references: RefCell<Vec<SrcPos>>,
+
+    // Set while analyzing the statements of a process with a sensitivity list
+    // which must not contain wait statements
+    pub(super) in_sensitive_process: Cell<bool>,
 }
 
 impl<'a> AnalyzeContext<'a> {
@@ -178,6 +185,8 @@ impl<'a> AnalyzeContext<'a> {
             uses: RefCell::new(FnvHashSet::default()),
             missing_unit: RefCell::new(FnvHashSet::default()),
             uses_library_all: RefCell::new(FnvHashSet::default()),
+            work_references: RefCell::new(Vec::new()),
+            in_sensitive_process: Cell::new(false),
         }
     }
 
@@ -189,10 +198,29 @@ impl<'a> AnalyzeContext<'a> {
         self.get_library(self.current_unit.library_name()).unwrap()
     }
 
+    /// Record a reference to a library that is made through the work alias
+    pub(super) fn record_work_reference(&self, pos: &SrcPos, designator: &Designator, ent: EntRef) {
+        if matches!(ent.kind(), AnyEntKind::Library)
+            && matches!(designator, Designator::Identifier(sym) if sym == &self.work_sym)
+        {
+            self.work_references.borrow_mut().push(pos.clone());
+        }
+    }
+
+    /// The positions of the references to the work library through the work alias
+    pub(super) fn take_work_references(&self) -> Vec<SrcPos> {
+        self.work_references.take()
+    }
+
     pub fn current_unit_id(&self) -> &UnitId {
         &self.current_unit
     }
 
+    /// The analysis has been cancelled and the remaining walk may be skipped
+    pub fn is_cancelled(&self) -> bool {
+        self.root.cancellation.is_cancelled()
+    }
+
     fn make_use_of(&self, use_pos: Option<&SrcPos>, unit_id: &UnitId) -> FatalResult {
         // Check local cache before taking lock
         if self.uses.borrow_mut().insert(unit_id.clone()) {
@@ -378,12 +406,31 @@ impl<'a> AnalyzeContext<'a> {
             }
         }
 
-        Err(AnalysisError::NotFatal(Diagnostic::error(
+        let mut diagnostic = Diagnostic::error(
             pos,
             format!(
                 "No architecture '{architecture_name}' for entity '{library_name}.{entity_name}'"
             ),
-        )))
+        );
+        for (name, decl_pos) in self.root.architectures_of(library_name, entity_name) {
+            diagnostic.add_related(decl_pos, format!("Architecture '{name}' declared here"));
+        }
+        Err(AnalysisError::NotFatal(diagnostic))
+    }
+
+    /// The analyzed architecture such that a configuration can refer to its statements
+    pub(super) fn get_architecture_unit(
+        &self,
+        library_name: &Symbol,
+        pos: &SrcPos,
+        entity_name: &Symbol,
+        architecture_name: &Symbol,
+    ) -> FatalResult<Option<UnitReadGuard<'a>>> {
+        if let Some(unit) = self.get_secondary_unit(library_name, entity_name, architecture_name) {
+            Ok(Some(self.get_analysis(Some(pos), unit)?))
+        } else {
+            Ok(None)
+        }
     }
 
     pub fn lookup_in_library(
@@ -415,10 +462,25 @@ impl<'a> AnalyzeContext<'a> {
             }
         }
 
-        Err(AnalysisError::NotFatal(Diagnostic::error(
+        let mut diagnostic = Diagnostic::error(
             pos,
             format!("No primary unit '{primary_name}' within library '{library_name}'"),
-        )))
+        );
+        // The unit is typically expected in the library that work denotes for another file
+        if library_name == self.work_library_name() {
+            if let Designator::Identifier(ref primary_name) = primary_name {
+                for (other_library, decl_pos) in self.root.libraries_with_primary_unit(primary_name)
+                {
+                    diagnostic.add_related(
+                        decl_pos,
+                        format!(
+                            "Found within library '{other_library}' while work denotes library '{library_name}' that this file is compiled into"
+                        ),
+                    );
+                }
+            }
+        }
+        Err(AnalysisError::NotFatal(diagnostic))
     }
 
     // Returns None when analyzing the standard package itsel
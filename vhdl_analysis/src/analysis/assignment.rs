@@ -8,6 +8,7 @@
 use super::analyze::*;
 use super::named_entity::*;
 use super::region::*;
+use super::target::may_be_guarded;
 use super::target::AssignmentType;
 use crate::ast::*;
 use crate::data::*;
@@ -52,6 +53,9 @@ impl<'a> AnalyzeContext<'a> {
                     self.analyze_expression_for_target(scope, ttyp, item, diagnostics)?;
                     self.choice_with_ttyp(scope, ctyp, choices, diagnostics)?;
                 }
+                if let Some(ctyp) = ctyp {
+                    self.check_case_choices(ctyp, expression, alternatives, diagnostics);
+                }
             }
         }
         Ok(())
@@ -65,10 +69,18 @@ impl<'a> AnalyzeContext<'a> {
         rhs: &mut AssignmentRightHand<Waveform>,
         diagnostics: &mut dyn DiagnosticHandler,
     ) -> FatalResult {
-        let ttyp = as_fatal(self.resolve_target(scope, target, assignment_type, diagnostics))?;
+        let object_name =
+            as_fatal(self.resolve_target_object(scope, target, assignment_type, diagnostics))?;
+        let ttyp = object_name
+            .as_ref()
+            .map(|object_name| object_name.type_mark());
+        let allows_null = object_name
+            .as_ref()
+            .map(|object_name| may_be_guarded(&object_name.base))
+            .unwrap_or(true);
         match rhs {
             AssignmentRightHand::Simple(wavf) => {
-                self.analyze_waveform(scope, ttyp, wavf, diagnostics)?;
+                self.analyze_waveform(scope, ttyp, allows_null, wavf, diagnostics)?;
             }
             AssignmentRightHand::Conditional(conditionals) => {
                 let Conditionals {
@@ -77,11 +89,11 @@ impl<'a> AnalyzeContext<'a> {
                 } = conditionals;
                 for conditional in conditionals {
                     let Conditional { condition, item } = conditional;
-                    self.analyze_waveform(scope, ttyp, item, diagnostics)?;
+                    self.analyze_waveform(scope, ttyp, allows_null, item, diagnostics)?;
                     self.boolean_expr(scope, condition, diagnostics)?;
                 }
                 if let Some(wavf) = else_item {
-                    self.analyze_waveform(scope, ttyp, wavf, diagnostics)?;
+                    self.analyze_waveform(scope, ttyp, allows_null, wavf, diagnostics)?;
                 }
             }
             AssignmentRightHand::Selected(selection) => {
@@ -91,9 +103,12 @@ impl<'a> AnalyzeContext<'a> {
                 } = selection;
                 let ctyp = as_fatal(self.expr_unambiguous_type(scope, expression, diagnostics))?;
                 for Alternative { choices, item } in alternatives.iter_mut() {
-                    self.analyze_waveform(scope, ttyp, item, diagnostics)?;
+                    self.analyze_waveform(scope, ttyp, allows_null, item, diagnostics)?;
                     self.choice_with_ttyp(scope, ctyp, choices, diagnostics)?;
                 }
+                if let Some(ctyp) = ctyp {
+                    self.check_case_choices(ctyp, expression, alternatives, diagnostics);
+                }
             }
         }
         Ok(())
@@ -103,6 +118,7 @@ impl<'a> AnalyzeContext<'a> {
         &self,
         scope: &Scope<'a>,
         ttyp: Option<TypeEnt<'a>>,
+        allows_null: bool,
         wavf: &mut Waveform,
         diagnostics: &mut dyn DiagnosticHandler,
     ) -> FatalResult {
@@ -110,7 +126,17 @@ impl<'a> AnalyzeContext<'a> {
             Waveform::Elements(ref mut elems) => {
                 for elem in elems.iter_mut() {
                     let WaveformElement { value, after } = elem;
-                    self.analyze_expression_for_target(scope, ttyp, value, diagnostics)?;
+                    if let Expression::Literal(Literal::Null) = value.item {
+                        // A null waveform element is a null transaction and not a value of the target type
+                        if !allows_null {
+                            diagnostics.error(
+                                &value.pos,
+                                "Null transaction is only allowed when the target is a guarded signal",
+                            );
+                        }
+                    } else {
+                        self.analyze_expression_for_target(scope, ttyp, value, diagnostics)?;
+                    }
                     if let Some(expr) = after {
                         self.expr_with_ttyp(scope, self.time(), expr, diagnostics)?;
                     }
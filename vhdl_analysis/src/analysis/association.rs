@@ -1,6 +1,6 @@
 #![allow(clippy::only_used_in_recursion)]
 
-use fnv::FnvHashSet;
+use fnv::{FnvHashMap, FnvHashSet};
 
 // This Source Code Form is subject to the terms of the Mozilla Public
 // License, v. 2.0. If a copy of the MPL was not distributed with this file,
@@ -8,13 +8,18 @@ use fnv::FnvHashSet;
 //
 // Copyright (c) 2018, Olof Kraigher olof.kraigher@gmail.com
 use super::analyze::*;
+use super::component::capitalize;
+use super::concurrent::FindSignal;
 use super::formal_region::FormalRegion;
 use super::formal_region::InterfaceEnt;
 use super::named_entity::*;
 use super::names::ResolvedName;
 use super::region::*;
+use super::semantic::SemanticDiagnostics;
+use crate::ast::search::*;
 use crate::ast::*;
 use crate::data::*;
+use crate::VHDLStandard;
 
 #[derive(Copy, Clone)]
 pub enum ResolvedFormal<'a> {
@@ -58,6 +63,15 @@ impl<'a> ResolvedFormal<'a> {
         }
     }
 
+    /// The interface object of the formal
+    pub fn interface(&self) -> InterfaceEnt<'a> {
+        *match self {
+            ResolvedFormal::Basic(_, ent) => ent,
+            ResolvedFormal::Selected(_, ent, _) => ent,
+            ResolvedFormal::Converted(_, ent, _) => ent,
+        }
+    }
+
     // The position of the formal in the formal region
     fn idx(&self) -> usize {
         *match self {
@@ -256,10 +270,15 @@ impl<'a> AnalyzeContext<'a> {
 
         let mut missing = false;
         let mut associated_indexes: FnvHashSet<usize> = Default::default();
+        // The first association of each formal and whether it was associated as a whole
+        let mut first_associations: FnvHashMap<usize, (SrcPos, bool)> = Default::default();
+        // The record elements of each formal associated in parts, None when a part is not an element
+        let mut element_associations: FnvHashMap<usize, Option<FnvHashSet<Designator>>> =
+            Default::default();
         let mut extra_associations: Vec<SrcPos> = Default::default();
 
         for (idx, AssociationElement { formal, actual }) in elems.iter_mut().enumerate() {
-            if let Some(ref mut formal) = formal {
+            let (pos, resolved, element) = if let Some(ref mut formal) = formal {
                 // Call by name using formal
                 match self.resolve_formal(
                     formal_region,
@@ -271,28 +290,85 @@ impl<'a> AnalyzeContext<'a> {
                     Err(err) => {
                         missing = true;
                         diagnostics.push(err.into_non_fatal()?);
+                        continue;
                     }
-                    Ok(formal) => {
-                        associated_indexes.insert(formal.idx());
-                        result.push(formal);
-                    }
+                    Ok(resolved) => (&formal.pos, resolved, record_element(&formal.item)),
                 }
             } else if let Some(formal) = formal_region.nth(idx) {
-                associated_indexes.insert(idx);
-                result.push(ResolvedFormal::Basic(idx, formal));
+                (&actual.pos, ResolvedFormal::Basic(idx, formal), None)
             } else {
                 extra_associations.push(actual.pos.clone());
+                continue;
             };
+
+            // A formal may be associated in parts but only once as a whole
+            let whole = matches!(
+                resolved,
+                ResolvedFormal::Basic(..) | ResolvedFormal::Converted(..)
+            );
+            if !whole {
+                let elements = element_associations
+                    .entry(resolved.idx())
+                    .or_insert_with(|| Some(FnvHashSet::default()));
+                match (elements.as_mut(), element) {
+                    (Some(elements), Some(element)) => {
+                        elements.insert(element);
+                    }
+                    _ => *elements = None,
+                }
+            }
+            if let Some((prev_pos, prev_whole)) = first_associations.get(&resolved.idx()) {
+                if whole || *prev_whole {
+                    missing = true;
+                    let mut diagnostic = Diagnostic::error(
+                        pos,
+                        format!(
+                            "{} is associated more than once",
+                            capitalize(&resolved.interface().describe())
+                        ),
+                    );
+                    diagnostic.add_related(prev_pos, "Previously associated here");
+                    diagnostics.push(diagnostic);
+                }
+            } else {
+                first_associations.insert(resolved.idx(), (pos.clone(), whole));
+            }
+
+            associated_indexes.insert(resolved.idx());
+            result.push(resolved);
+        }
+
+        // Partial associations must together associate every element of a record formal
+        for (idx, formal) in formal_region.iter().enumerate() {
+            let elements = if let Some(Some(elements)) = element_associations.get(&idx) {
+                elements
+            } else {
+                continue;
+            };
+            if let Type::Record(ref region) = formal.type_mark().base().kind() {
+                for elem in region.iter() {
+                    if !elements.contains(elem.designator()) {
+                        missing = true;
+                        let mut diagnostic = Diagnostic::error(
+                            error_pos,
+                            format!(
+                                "No association of element '{}' of {}",
+                                elem.designator(),
+                                formal.describe()
+                            ),
+                        );
+                        if let Some(decl_pos) = formal.decl_pos() {
+                            diagnostic.add_related(decl_pos, "Defined here");
+                        }
+                        diagnostics.push(diagnostic);
+                    }
+                }
+            }
         }
 
         let mut not_associated = Vec::new();
         for (idx, formal) in formal_region.iter().enumerate() {
-            if !(associated_indexes.contains(&idx)
-                // Default may be unconnected
-                || formal.has_default()
-                // Output ports are allowed to be unconnected
-                || (formal_region.typ == InterfaceType::Port && formal.is_out_or_inout_signal()))
-            {
+            if !(associated_indexes.contains(&idx) || may_be_unassociated(formal_region, formal)) {
                 not_associated.push(idx);
             }
         }
@@ -350,8 +426,27 @@ impl<'a> AnalyzeContext<'a> {
                             expr,
                             diagnostics,
                         )?;
+                        if formal_region.typ == InterfaceType::Port {
+                            self.check_port_actual_class(formal.interface(), actual, diagnostics);
+                        }
+                    }
+                    ActualPart::Open => {
+                        if let ResolvedFormal::Basic(_, formal) = formal {
+                            if !may_be_unassociated(formal_region, *formal) {
+                                let mut diagnostic = Diagnostic::error(
+                                    &actual.pos,
+                                    format!(
+                                        "{} without a default value cannot be left open",
+                                        formal.describe()
+                                    ),
+                                );
+                                if let Some(decl_pos) = formal.decl_pos() {
+                                    diagnostic.add_related(decl_pos, "Defined here");
+                                }
+                                diagnostics.push(diagnostic);
+                            }
+                        }
                     }
-                    ActualPart::Open => {}
                 }
             }
         }
@@ -359,6 +454,148 @@ impl<'a> AnalyzeContext<'a> {
     }
 }
 
+impl<'a> AnalyzeContext<'a> {
+    /// A port of mode out, inout or buffer must be associated with a signal
+    /// An input port may only be associated with an expression that reads signals from VHDL-2008
+    fn check_port_actual_class(
+        &self,
+        formal: InterfaceEnt<'a>,
+        actual: &mut WithPos<ActualPart>,
+        diagnostics: &mut dyn DiagnosticHandler,
+    ) {
+        let object = match formal.kind() {
+            AnyEntKind::Object(object) if object.class == ObjectClass::Signal => object,
+            _ => return,
+        };
+
+        let expr = match actual.item {
+            ActualPart::Expression(ref expr) => expr,
+            ActualPart::Open => return,
+        };
+
+        match object.mode() {
+            Some(Mode::Out | Mode::InOut | Mode::Buffer) => {
+                let is_signal = match expr {
+                    Expression::Name(name) => self.denotes_signal(name),
+                    _ => Some(false),
+                };
+
+                if is_signal == Some(false) {
+                    let mut diagnostic = Diagnostic::error(
+                        &actual.pos,
+                        format!(
+                            "{} must be associated with a signal",
+                            capitalize(&formal.describe())
+                        ),
+                    );
+                    if let Some(decl_pos) = formal.decl_pos() {
+                        diagnostic.add_related(decl_pos, "Defined here");
+                    }
+                    diagnostics.push(diagnostic);
+                }
+            }
+            Some(Mode::In) if self.root.standard < VHDLStandard::VHDL2008 => {
+                if matches!(expr, Expression::Name(_)) {
+                    return;
+                }
+
+                let mut searcher = FindSignal {
+                    context: self,
+                    found: false,
+                };
+                let _ = actual.search(&mut searcher);
+                if searcher.found {
+                    diagnostics.error(
+                        &actual.pos,
+                        format!(
+                            "{} may only be associated with a signal or a globally static expression before VHDL-2008",
+                            capitalize(&formal.describe())
+                        ),
+                    );
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Some(true) if the name denotes a signal or a conversion of a signal,
+    /// Some(false) if it denotes another object or a value
+    /// and None if it cannot be determined
+    fn denotes_signal(&self, name: &Name) -> Option<bool> {
+        match name {
+            Name::Designator(designator) => self.is_signal_ent(designator.reference?),
+            Name::Selected(prefix, suffix) => {
+                // The suffix is either an object within a package or an element of a record
+                match suffix.item.reference.and_then(|id| self.is_signal_ent(id)) {
+                    Some(is_signal) => Some(is_signal),
+                    None => self.denotes_signal(&prefix.item),
+                }
+            }
+            Name::Slice(prefix, _) => self.denotes_signal(&prefix.item),
+            Name::CallOrIndexed(call) => {
+                let is_conversion = call.name.item.get_suffix_reference().is_some_and(|id| {
+                    matches!(
+                        self.arena.get(id).kind(),
+                        AnyEntKind::Overloaded(_) | AnyEntKind::Type(_)
+                    )
+                });
+
+                if is_conversion {
+                    match call.parameters.as_slice() {
+                        [AssociationElement {
+                            formal: None,
+                            actual:
+                                WithPos {
+                                    item: ActualPart::Expression(Expression::Name(name)),
+                                    ..
+                                },
+                        }] => self.denotes_signal(name),
+                        _ => Some(false),
+                    }
+                } else {
+                    self.denotes_signal(&call.name.item)
+                }
+            }
+            // Attributes may denote implicit signals such as 'delayed
+            Name::Attribute(_) => None,
+            Name::External(external) => Some(external.class == ExternalObjectClass::Signal),
+            // The object designated by an access value is never a signal
+            Name::SelectedAll(_) => Some(false),
+        }
+    }
+
+    fn is_signal_ent(&self, id: EntityId) -> Option<bool> {
+        match self.arena.get(id).kind() {
+            AnyEntKind::Object(object) => Some(object.class == ObjectClass::Signal),
+            AnyEntKind::ObjectAlias { base_object, .. } => {
+                Some(base_object.class() == ObjectClass::Signal)
+            }
+            AnyEntKind::ExternalAlias { class, .. } => Some(*class == ExternalObjectClass::Signal),
+            AnyEntKind::DeferredConstant(_) => Some(false),
+            AnyEntKind::Overloaded(_) => Some(false),
+            _ => None,
+        }
+    }
+}
+
+/// A formal may be left open or unassociated when its value is given by a default
+/// The record element of a formal such as `elem` in `rec.elem`
+fn record_element(name: &Name) -> Option<Designator> {
+    if let Name::Selected(prefix, suffix) = name {
+        if let Name::Designator(_) = prefix.item {
+            return Some(suffix.item.designator().clone());
+        }
+    }
+    None
+}
+
+fn may_be_unassociated(formal_region: &FormalRegion, formal: InterfaceEnt) -> bool {
+    // Default may be unconnected
+    formal.has_default()
+        // Output ports are allowed to be unconnected
+        || (formal_region.typ == InterfaceType::Port && formal.is_out_or_inout_signal())
+}
+
 fn to_formal_conversion_argument(
     parameters: &mut [AssociationElement],
 ) -> Option<(&SrcPos, &mut WithRef<Designator>)> {
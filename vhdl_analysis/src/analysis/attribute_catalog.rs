@@ -0,0 +1,397 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this file,
+// You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) 2023, Olof Kraigher olof.kraigher@gmail.com
+
+//! The predefined attributes of LRM 16.2 with the kinds of prefix they apply to
+//! Used to complete and document the attribute designator after the tick
+
+use super::named_entity::*;
+use crate::ast::search::*;
+use crate::ast::*;
+use crate::data::*;
+
+/// The classification of the prefix of an attribute name
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum AttributePrefix {
+    /// A scalar type or subtype
+    ScalarType,
+    /// A discrete or physical type or subtype
+    DiscreteType,
+    /// An array type or an object of an array type
+    Array,
+    /// An object of any type
+    Object,
+    /// A signal
+    Signal,
+    /// Any named entity
+    Named,
+}
+
+#[derive(PartialEq, Eq, Debug)]
+pub struct PredefinedAttribute {
+    /// The attribute designator such as `last_value`
+    pub name: &'static str,
+    pub prefix: AttributePrefix,
+    /// The form of the attribute name such as `S'delayed(t)`
+    pub signature: &'static str,
+    pub documentation: &'static str,
+}
+
+macro_rules! attribute {
+    ($prefix:ident, $name:literal, $signature:literal, $documentation:literal) => {
+        PredefinedAttribute {
+            name: $name,
+            prefix: AttributePrefix::$prefix,
+            signature: $signature,
+            documentation: $documentation,
+        }
+    };
+}
+
+/// The attributes of each prefix classification, an attribute such as `left` which applies
+/// to several kinds of prefix has one entry for each
+pub const PREDEFINED_ATTRIBUTES: &[PredefinedAttribute] = &[
+    attribute!(ScalarType, "left", "T'left", "The left bound of T."),
+    attribute!(ScalarType, "right", "T'right", "The right bound of T."),
+    attribute!(ScalarType, "high", "T'high", "The upper bound of T."),
+    attribute!(ScalarType, "low", "T'low", "The lower bound of T."),
+    attribute!(
+        ScalarType,
+        "ascending",
+        "T'ascending",
+        "True if T is defined with an ascending range, false otherwise."
+    ),
+    attribute!(
+        ScalarType,
+        "descending",
+        "T'descending",
+        "True if T is defined with a descending range, false otherwise."
+    ),
+    attribute!(
+        ScalarType,
+        "image",
+        "T'image(x)",
+        "The string representation of the value x of type T."
+    ),
+    attribute!(
+        ScalarType,
+        "value",
+        "T'value(x)",
+        "The value of type T represented by the string x."
+    ),
+    attribute!(
+        DiscreteType,
+        "pos",
+        "T'pos(x)",
+        "The position number of the value x within the base type of T."
+    ),
+    attribute!(
+        DiscreteType,
+        "val",
+        "T'val(x)",
+        "The value of the base type of T at the position number x."
+    ),
+    attribute!(
+        DiscreteType,
+        "succ",
+        "T'succ(x)",
+        "The value at the position one greater than that of x."
+    ),
+    attribute!(
+        DiscreteType,
+        "pred",
+        "T'pred(x)",
+        "The value at the position one less than that of x."
+    ),
+    attribute!(
+        DiscreteType,
+        "leftof",
+        "T'leftof(x)",
+        "The value to the left of x in the range of T."
+    ),
+    attribute!(
+        DiscreteType,
+        "rightof",
+        "T'rightof(x)",
+        "The value to the right of x in the range of T."
+    ),
+    attribute!(
+        Array,
+        "left",
+        "A'left[(n)]",
+        "The left bound of the nth index range of A."
+    ),
+    attribute!(
+        Array,
+        "right",
+        "A'right[(n)]",
+        "The right bound of the nth index range of A."
+    ),
+    attribute!(
+        Array,
+        "high",
+        "A'high[(n)]",
+        "The upper bound of the nth index range of A."
+    ),
+    attribute!(
+        Array,
+        "low",
+        "A'low[(n)]",
+        "The lower bound of the nth index range of A."
+    ),
+    attribute!(Array, "range", "A'range[(n)]", "The nth index range of A."),
+    attribute!(
+        Array,
+        "reverse_range",
+        "A'reverse_range[(n)]",
+        "The nth index range of A with the direction reversed."
+    ),
+    attribute!(
+        Array,
+        "length",
+        "A'length[(n)]",
+        "The number of values in the nth index range of A."
+    ),
+    attribute!(
+        Array,
+        "ascending",
+        "A'ascending[(n)]",
+        "True if the nth index range of A is ascending, false otherwise."
+    ),
+    attribute!(
+        Array,
+        "descending",
+        "A'descending[(n)]",
+        "True if the nth index range of A is descending, false otherwise."
+    ),
+    attribute!(
+        Array,
+        "element",
+        "A'element",
+        "The subtype of the elements of A."
+    ),
+    attribute!(
+        Object,
+        "subtype",
+        "O'subtype",
+        "The subtype of the object O."
+    ),
+    attribute!(
+        Signal,
+        "delayed",
+        "S'delayed[(t)]",
+        "A signal equivalent to S delayed t units of time."
+    ),
+    attribute!(
+        Signal,
+        "stable",
+        "S'stable[(t)]",
+        "A signal that is true when no event has occurred on S for t units of time."
+    ),
+    attribute!(
+        Signal,
+        "quiet",
+        "S'quiet[(t)]",
+        "A signal that is true when S has been quiet for t units of time."
+    ),
+    attribute!(
+        Signal,
+        "transaction",
+        "S'transaction",
+        "A signal of type bit that toggles in each simulation cycle in which S is active."
+    ),
+    attribute!(
+        Signal,
+        "event",
+        "S'event",
+        "True if an event has occurred on S in the current simulation cycle."
+    ),
+    attribute!(
+        Signal,
+        "active",
+        "S'active",
+        "True if S is active during the current simulation cycle."
+    ),
+    attribute!(
+        Signal,
+        "last_event",
+        "S'last_event",
+        "The amount of time that has elapsed since the last event on S."
+    ),
+    attribute!(
+        Signal,
+        "last_active",
+        "S'last_active",
+        "The amount of time that has elapsed since S was last active."
+    ),
+    attribute!(
+        Signal,
+        "last_value",
+        "S'last_value",
+        "The value of S immediately before the last change of S."
+    ),
+    attribute!(
+        Signal,
+        "driving",
+        "S'driving",
+        "False if the driver of S in the current process is disconnected, true otherwise."
+    ),
+    attribute!(
+        Signal,
+        "driving_value",
+        "S'driving_value",
+        "The value of the driver of S in the current process."
+    ),
+    attribute!(
+        Named,
+        "simple_name",
+        "E'simple_name",
+        "The simple name of the named entity E."
+    ),
+    attribute!(
+        Named,
+        "instance_name",
+        "E'instance_name",
+        "The hierarchical path of E including the names of instantiated design entities."
+    ),
+    attribute!(
+        Named,
+        "path_name",
+        "E'path_name",
+        "The hierarchical path of E excluding the names of instantiated design entities."
+    ),
+];
+
+/// The kinds of prefix that the named entity is an instance of
+pub(crate) fn prefix_classes(ent: EntRef) -> Vec<AttributePrefix> {
+    let mut classes = Vec::new();
+
+    let (type_mark, is_type) = match ent.actual_kind() {
+        AnyEntKind::Type(_) => (TypeEnt::from_any(ent.as_actual()), true),
+        AnyEntKind::Object(object) => {
+            if object.class == ObjectClass::Signal {
+                classes.push(AttributePrefix::Signal);
+            }
+            (Some(object.subtype.type_mark()), false)
+        }
+        AnyEntKind::ObjectAlias {
+            base_object,
+            type_mark,
+        } => {
+            if base_object.class() == ObjectClass::Signal {
+                classes.push(AttributePrefix::Signal);
+            }
+            (Some(*type_mark), false)
+        }
+        AnyEntKind::ExternalAlias { class, type_mark } => {
+            if *class == ExternalObjectClass::Signal {
+                classes.push(AttributePrefix::Signal);
+            }
+            (Some(*type_mark), false)
+        }
+        _ => (None, false),
+    };
+
+    if let Some(type_mark) = type_mark {
+        let base = type_mark.base();
+        if base.array_type().is_some() {
+            classes.push(AttributePrefix::Array);
+        } else if is_type && base.is_scalar() {
+            classes.push(AttributePrefix::ScalarType);
+            if base.is_discrete() {
+                classes.push(AttributePrefix::DiscreteType);
+            }
+        }
+        if !is_type {
+            classes.push(AttributePrefix::Object);
+        }
+    }
+
+    classes.push(AttributePrefix::Named);
+    classes
+}
+
+/// The attributes applicable to a prefix of the classes, the first entry of each name
+pub(crate) fn applicable_attributes(
+    classes: &[AttributePrefix],
+) -> Vec<&'static PredefinedAttribute> {
+    let mut attributes: Vec<&'static PredefinedAttribute> = Vec::new();
+    for attribute in PREDEFINED_ATTRIBUTES.iter() {
+        if classes.contains(&attribute.prefix)
+            && !attributes.iter().any(|other| other.name == attribute.name)
+        {
+            attributes.push(attribute);
+        }
+    }
+    attributes
+}
+
+/// The predefined attribute with the name, preferring the entry of the prefix
+pub(crate) fn find_attribute(
+    name: &str,
+    classes: &[AttributePrefix],
+) -> Option<&'static PredefinedAttribute> {
+    let mut candidates = PREDEFINED_ATTRIBUTES
+        .iter()
+        .filter(|attribute| attribute.name.eq_ignore_ascii_case(name));
+    let first = candidates.clone().next();
+    candidates
+        .find(|attribute| classes.contains(&attribute.prefix))
+        .or(first)
+}
+
+/// Find the designator of a predefined attribute at the cursor and the prefix of the attribute
+pub(crate) struct AttributeAtCursor {
+    source: Source,
+    cursor: Position,
+    pub result: Option<(AttributeDesignator, Option<EntityId>)>,
+}
+
+impl AttributeAtCursor {
+    pub fn new(source: &Source, cursor: Position) -> AttributeAtCursor {
+        AttributeAtCursor {
+            source: source.clone(),
+            cursor,
+            result: None,
+        }
+    }
+
+    fn is_inside(&self, pos: &SrcPos) -> bool {
+        pos.start() <= self.cursor && self.cursor <= pos.end()
+    }
+}
+
+impl Searcher for AttributeAtCursor {
+    fn search_with_pos(&mut self, pos: &SrcPos) -> SearchState {
+        if self.is_inside(pos) {
+            NotFinished
+        } else {
+            Finished(NotFound)
+        }
+    }
+
+    fn search_attribute(&mut self, attr: &AttributeName) -> SearchState {
+        if !self.is_inside(&attr.attr.pos) {
+            return NotFinished;
+        }
+        // User defined attributes are declared entities
+        if matches!(attr.attr.item, AttributeDesignator::Ident(_)) {
+            return Finished(NotFound);
+        }
+        self.result = Some((
+            attr.attr.item.clone(),
+            attr.name.item.get_suffix_reference(),
+        ));
+        Finished(Found)
+    }
+
+    fn search_source(&mut self, source: &Source) -> SearchState {
+        if source == &self.source {
+            NotFinished
+        } else {
+            Finished(NotFound)
+        }
+    }
+}
@@ -0,0 +1,468 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this file,
+// You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) 2023, Olof Kraigher olof.kraigher@gmail.com
+
+//! The graph of calls between subprograms built from the resolved references in their bodies
+
+use super::component::capitalize;
+use super::named_entity::*;
+use super::root::DesignRoot;
+use crate::ast::search::*;
+use crate::ast::*;
+use crate::data::*;
+use fnv::{FnvHashMap, FnvHashSet};
+
+/// A call of a subprogram from the body of another subprogram
+#[derive(Clone)]
+pub struct Call<'a> {
+    pub callee: EntRef<'a>,
+    pub pos: SrcPos,
+}
+
+/// A call of a subprogram from the value of a constant declaration
+struct ConstantCall<'a> {
+    constant: EntRef<'a>,
+    call: Call<'a>,
+}
+
+/// A call from a process or another concurrent statement, the caller is the label of the
+/// statement or the design unit when the statement has no label
+struct StatementCall<'a> {
+    caller: EntRef<'a>,
+    call: Call<'a>,
+}
+
+#[derive(Default)]
+pub struct CallGraph<'a> {
+    // Subprograms in the order they were found
+    subprograms: Vec<EntRef<'a>>,
+    calls: FnvHashMap<EntityId, Vec<Call<'a>>>,
+    constant_calls: Vec<ConstantCall<'a>>,
+    statement_calls: Vec<StatementCall<'a>>,
+}
+
+impl<'a> CallGraph<'a> {
+    /// All subprograms with a body or that are called from one
+    /// A subprogram with a separate declaration is represented by the declaration
+    pub fn subprograms(&self) -> impl Iterator<Item = EntRef<'a>> + '_ {
+        self.subprograms.iter().copied()
+    }
+
+    /// The calls from the body of a subprogram in the order they appear
+    pub fn calls_from(&self, subprogram: EntRef) -> &[Call<'a>] {
+        self.calls
+            .get(&subprogram.declaration().id())
+            .map(|calls| calls.as_slice())
+            .unwrap_or_default()
+    }
+
+    /// The callers of a subprogram with the positions of their calls in the order they were found
+    /// A caller is a subprogram, a labeled concurrent statement or the design unit
+    /// of an unlabeled concurrent statement
+    pub fn incoming_calls(&self, subprogram: EntRef) -> Vec<(EntRef<'a>, Vec<SrcPos>)> {
+        let target = subprogram.declaration().id();
+        let from_subprograms = self.subprograms.iter().flat_map(|caller| {
+            self.calls_from(caller)
+                .iter()
+                .map(move |call| (*caller, call))
+        });
+        let from_statements = self
+            .statement_calls
+            .iter()
+            .map(|statement_call| (statement_call.caller, &statement_call.call));
+
+        group_by_ent(
+            from_subprograms
+                .chain(from_statements)
+                .filter(|(_, call)| call.callee.id() == target)
+                .map(|(caller, call)| (caller, call.pos.clone())),
+        )
+    }
+
+    /// The subprograms called from the body of a subprogram with the positions of the calls
+    pub fn outgoing_calls(&self, subprogram: EntRef) -> Vec<(EntRef<'a>, Vec<SrcPos>)> {
+        group_by_ent(
+            self.calls_from(subprogram)
+                .iter()
+                .map(|call| (call.callee, call.pos.clone())),
+        )
+    }
+
+    /// A call from the body of the subprogram that leads back to the subprogram itself
+    pub fn recursive_call(&self, subprogram: EntRef) -> Option<&Call<'a>> {
+        let target = subprogram.declaration().id();
+        self.calls_from(subprogram).iter().find(|call| {
+            let mut visited = FnvHashSet::default();
+            self.reaches(call.callee.id(), target, &mut visited)
+        })
+    }
+
+    fn reaches(
+        &self,
+        from: EntityId,
+        target: EntityId,
+        visited: &mut FnvHashSet<EntityId>,
+    ) -> bool {
+        if from == target {
+            return true;
+        }
+        if !visited.insert(from) {
+            return false;
+        }
+        self.calls.get(&from).is_some_and(|calls| {
+            calls
+                .iter()
+                .any(|call| self.reaches(call.callee.id(), target, visited))
+        })
+    }
+
+    /// The first recursive subprogram that can be called from the subprogram including itself
+    fn reachable_recursion(&self, subprogram: EntRef<'a>) -> Option<(EntRef<'a>, &Call<'a>)> {
+        let mut visited = FnvHashSet::default();
+        let mut queue = vec![subprogram];
+
+        while !queue.is_empty() {
+            let ent = queue.remove(0);
+            if !visited.insert(ent.id()) {
+                continue;
+            }
+            if let Some(call) = self.recursive_call(ent) {
+                return Some((ent, call));
+            }
+            queue.extend(self.calls_from(ent).iter().map(|call| call.callee));
+        }
+        None
+    }
+
+    /// Warn when the value of a constant is computed by a recursive subprogram
+    /// which is typically not supported by synthesis tools
+    pub(super) fn check_recursion(&self, diagnostics: &mut dyn DiagnosticHandler) {
+        for ConstantCall { constant, call } in self.constant_calls.iter() {
+            if let Some((recursive, recursive_call)) = self.reachable_recursion(call.callee) {
+                let message = if recursive.id() == call.callee.id() {
+                    format!(
+                        "Recursive {} '{}' is used in the value of constant '{}'",
+                        subprogram_kind(recursive),
+                        recursive.designator(),
+                        constant.designator()
+                    )
+                } else {
+                    format!(
+                        "{} '{}' used in the value of constant '{}' calls recursive {} '{}'",
+                        capitalize(subprogram_kind(call.callee)),
+                        call.callee.designator(),
+                        constant.designator(),
+                        subprogram_kind(recursive),
+                        recursive.designator()
+                    )
+                };
+
+                diagnostics.push(
+                    Diagnostic::warning(&call.pos, message)
+                        .related(&recursive_call.pos, "Recursion through this call"),
+                );
+            }
+        }
+    }
+
+    /// Export the call graph in the DOT format of Graphviz
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph calls {\n");
+        for (name, callees) in self.sorted_edges() {
+            dot.push_str(&format!("  {};\n", quoted(&name)));
+            for callee in callees {
+                dot.push_str(&format!("  {} -> {};\n", quoted(&name), quoted(&callee)));
+            }
+        }
+        dot.push('}');
+        dot
+    }
+
+    /// Export the call graph as a JSON array of subprograms with the subprograms they call
+    pub fn to_json(&self) -> String {
+        let subprograms: Vec<_> = self
+            .sorted_edges()
+            .into_iter()
+            .map(|(name, callees)| {
+                let callees: Vec<_> = callees.iter().map(|callee| quoted(callee)).collect();
+                format!(
+                    "{{\"name\": {}, \"calls\": [{}]}}",
+                    quoted(&name),
+                    callees.join(", ")
+                )
+            })
+            .collect();
+        format!("[{}]", subprograms.join(", "))
+    }
+
+    /// The name of each subprogram with the unique names of the subprograms it calls, sorted by name
+    fn sorted_edges(&self) -> Vec<(String, Vec<String>)> {
+        let mut edges: Vec<_> = self
+            .subprograms
+            .iter()
+            .map(|subprogram| {
+                let mut callees: Vec<_> = self
+                    .calls_from(subprogram)
+                    .iter()
+                    .map(|call| subprogram_name(call.callee))
+                    .collect();
+                callees.sort();
+                callees.dedup();
+                (subprogram_name(subprogram), callees)
+            })
+            .collect();
+        edges.sort();
+        edges
+    }
+}
+
+pub(super) struct CallGraphBuilder<'a> {
+    root: &'a DesignRoot,
+    graph: CallGraph<'a>,
+    known: FnvHashSet<EntityId>,
+}
+
+impl<'a> CallGraphBuilder<'a> {
+    pub fn new(root: &'a DesignRoot) -> Self {
+        CallGraphBuilder {
+            root,
+            graph: CallGraph::default(),
+            known: FnvHashSet::default(),
+        }
+    }
+
+    pub fn finish(self) -> CallGraph<'a> {
+        self.graph
+    }
+
+    fn add_subprogram(&mut self, ent: EntRef<'a>) {
+        if self.known.insert(ent.id()) {
+            self.graph.subprograms.push(ent);
+        }
+    }
+
+    fn calls(&mut self, search: &mut impl Search) -> Vec<Call<'a>> {
+        let calls = self.find_calls(search);
+        for call in calls.iter() {
+            self.add_subprogram(call.callee);
+        }
+        calls
+    }
+
+    fn find_calls(&self, search: &mut impl Search) -> Vec<Call<'a>> {
+        let mut searcher = FindCalls {
+            root: self.root,
+            calls: Vec::new(),
+        };
+        let _ = search.search(&mut searcher);
+        searcher.calls
+    }
+
+    /// Calls from concurrent statements are not part of the graph of calls between subprograms
+    fn add_statement_calls(&mut self, caller: Option<EntRef<'a>>, calls: Vec<Call<'a>>) {
+        if let Some(caller) = caller {
+            self.graph
+                .statement_calls
+                .extend(calls.into_iter().map(|call| StatementCall { caller, call }));
+        }
+    }
+
+    fn label_or(
+        &self,
+        label: &WithDecl<Option<Ident>>,
+        unit: Option<EntRef<'a>>,
+    ) -> Option<EntRef<'a>> {
+        label.decl.map(|id| self.root.get_ent(id)).or(unit)
+    }
+
+    pub fn design_unit(&mut self, unit: &mut AnyDesignUnit) {
+        match unit {
+            AnyDesignUnit::Primary(AnyPrimaryUnit::Entity(entity)) => {
+                let unit = entity.ident.decl.map(|id| self.root.get_ent(id));
+                self.declarations(&mut entity.decl);
+                self.concurrent_statements(unit, &mut entity.statements);
+            }
+            AnyDesignUnit::Primary(AnyPrimaryUnit::Package(package)) => {
+                self.declarations(&mut package.decl);
+            }
+            AnyDesignUnit::Secondary(AnySecondaryUnit::Architecture(architecture)) => {
+                let unit = architecture.ident.decl.map(|id| self.root.get_ent(id));
+                self.declarations(&mut architecture.decl);
+                self.concurrent_statements(unit, &mut architecture.statements);
+            }
+            AnyDesignUnit::Secondary(AnySecondaryUnit::PackageBody(body)) => {
+                self.declarations(&mut body.decl);
+            }
+            AnyDesignUnit::Primary(
+                AnyPrimaryUnit::PackageInstance(..)
+                | AnyPrimaryUnit::Configuration(..)
+                | AnyPrimaryUnit::Context(..),
+            ) => {}
+        }
+    }
+
+    fn declarations(&mut self, decls: &mut [Declaration]) {
+        for decl in decls.iter_mut() {
+            match decl {
+                Declaration::SubprogramBody(body) => self.subprogram_body(body),
+                Declaration::Type(TypeDeclaration {
+                    def: TypeDefinition::ProtectedBody(body),
+                    ..
+                }) => self.declarations(&mut body.decl),
+                Declaration::Object(object) if object.class == ObjectClass::Constant => {
+                    if let (Some(id), Some(expr)) = (object.ident.decl, object.expression.as_mut())
+                    {
+                        let constant = self.root.get_ent(id);
+                        for call in self.calls(expr) {
+                            self.graph
+                                .constant_calls
+                                .push(ConstantCall { constant, call });
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn subprogram_body(&mut self, body: &mut SubprogramBody) {
+        if let Some(id) = body.specification.reference_mut() {
+            let subprogram = self.root.get_ent(*id).declaration();
+            self.add_subprogram(subprogram);
+
+            // Nested subprograms have their own calls
+            let mut calls = Vec::new();
+            for decl in body.declarations.iter_mut() {
+                if !matches!(decl, Declaration::SubprogramBody(..)) {
+                    calls.extend(self.calls(decl));
+                }
+            }
+            calls.extend(self.calls(&mut body.statements));
+            self.graph
+                .calls
+                .entry(subprogram.id())
+                .or_default()
+                .extend(calls);
+        }
+
+        self.declarations(&mut body.declarations);
+    }
+
+    /// The unit is the caller of the statements without a label
+    fn concurrent_statements(
+        &mut self,
+        unit: Option<EntRef<'a>>,
+        statements: &mut [LabeledConcurrentStatement],
+    ) {
+        for statement in statements.iter_mut() {
+            let caller = self.label_or(&statement.label, unit);
+            match statement.statement.item {
+                ConcurrentStatement::Block(ref mut block) => {
+                    self.declarations(&mut block.decl);
+                    self.concurrent_statements(caller, &mut block.statements);
+                }
+                ConcurrentStatement::Process(ref mut process) => {
+                    let mut calls = Vec::new();
+                    for decl in process.decl.iter_mut() {
+                        if !matches!(decl, Declaration::SubprogramBody(..)) {
+                            calls.extend(self.find_calls(decl));
+                        }
+                    }
+                    calls.extend(self.find_calls(&mut process.statements));
+                    self.add_statement_calls(caller, calls);
+                    self.declarations(&mut process.decl);
+                }
+                ConcurrentStatement::ForGenerate(ref mut gen) => {
+                    self.generate_body(caller, &mut gen.body);
+                }
+                ConcurrentStatement::IfGenerate(ref mut gen) => {
+                    for cond in gen.conds.conditionals.iter_mut() {
+                        self.generate_body(caller, &mut cond.item);
+                    }
+                    if let Some(ref mut body) = gen.conds.else_item {
+                        self.generate_body(caller, body);
+                    }
+                }
+                ConcurrentStatement::CaseGenerate(ref mut gen) => {
+                    for alternative in gen.sels.alternatives.iter_mut() {
+                        self.generate_body(caller, &mut alternative.item);
+                    }
+                }
+                ConcurrentStatement::ProcedureCall(_)
+                | ConcurrentStatement::Assert(_)
+                | ConcurrentStatement::Assignment(_)
+                | ConcurrentStatement::Instance(_) => {
+                    let calls = self.find_calls(statement);
+                    self.add_statement_calls(caller, calls);
+                }
+            }
+        }
+    }
+
+    fn generate_body(&mut self, caller: Option<EntRef<'a>>, body: &mut GenerateBody) {
+        if let Some(ref mut decl) = body.decl {
+            self.declarations(decl);
+        }
+        self.concurrent_statements(caller, &mut body.statements);
+    }
+}
+
+/// Find the references to explicitly declared subprograms
+struct FindCalls<'a> {
+    root: &'a DesignRoot,
+    calls: Vec<Call<'a>>,
+}
+
+impl<'a> Searcher for FindCalls<'a> {
+    fn search_pos_with_ref(&mut self, pos: &SrcPos, reference: &mut Reference) -> SearchState {
+        if let Some(id) = reference {
+            let ent = self.root.get_ent(*id);
+            if ent.is_explicit() && (ent.is_subprogram() || ent.is_subprogram_decl()) {
+                self.calls.push(Call {
+                    callee: ent.declaration(),
+                    pos: pos.clone(),
+                });
+            }
+        }
+        SearchState::NotFinished
+    }
+}
+
+/// Group the positions by entity in the order the entities are first found
+fn group_by_ent<'a>(
+    items: impl Iterator<Item = (EntRef<'a>, SrcPos)>,
+) -> Vec<(EntRef<'a>, Vec<SrcPos>)> {
+    let mut groups: Vec<(EntRef<'a>, Vec<SrcPos>)> = Vec::new();
+    for (ent, pos) in items {
+        match groups.iter_mut().find(|(group, _)| group.id() == ent.id()) {
+            Some((_, positions)) => positions.push(pos),
+            None => groups.push((ent, vec![pos])),
+        }
+    }
+    groups
+}
+
+fn subprogram_kind(ent: EntRef) -> &'static str {
+    if ent
+        .signature()
+        .is_some_and(|signature| signature.return_type().is_some())
+    {
+        "function"
+    } else {
+        "procedure"
+    }
+}
+
+fn subprogram_name(ent: EntRef) -> String {
+    let signature = ent
+        .signature()
+        .map(|signature| signature.describe())
+        .unwrap_or_default();
+    format!("{}{signature}", ent.path_name())
+}
+
+fn quoted(name: &str) -> String {
+    format!("\"{}\"", name.replace('\\', "\\\\").replace('"', "\\\""))
+}
@@ -0,0 +1,190 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this file,
+// You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) 2023, Olof Kraigher olof.kraigher@gmail.com
+
+//! Check the choices of case statements and selected assignments
+//! Choices are compared by their position within the discrete type of the expression
+
+use super::analyze::*;
+use super::named_entity::*;
+use super::static_expression::static_integer;
+use crate::ast;
+use crate::ast::*;
+use crate::data::*;
+
+/// The missing enumeration literals listed before the rest are summarized
+const MAX_LISTED_MISSING: usize = 10;
+
+impl<'a> AnalyzeContext<'a> {
+    /// Report duplicate and overlapping choices as well as the literals of an enumeration type
+    /// that are not covered by any choice when there is no `others` choice
+    pub(super) fn check_case_choices<T>(
+        &self,
+        ctyp: TypeEnt<'a>,
+        expression: &WithPos<Expression>,
+        alternatives: &[Alternative<T>],
+        diagnostics: &mut dyn DiagnosticHandler,
+    ) {
+        let base_type = ctyp.base_type();
+        let literals: Option<Vec<&Designator>> = match base_type.kind() {
+            Type::Enum(_) => Some(
+                base_type
+                    .implicits
+                    .iter()
+                    .filter(|ent| {
+                        matches!(
+                            ent.kind(),
+                            AnyEntKind::Overloaded(Overloaded::EnumLiteral(_))
+                        )
+                    })
+                    .map(|ent| ent.designator())
+                    .collect(),
+            ),
+            Type::Integer | Type::Universal(UniversalType::Integer) => None,
+            _ => return,
+        };
+        let choice_type = ChoiceType {
+            literals: literals.as_deref(),
+        };
+
+        let mut has_others = false;
+        let mut all_known = true;
+        let mut previous: Vec<((i64, i64), &SrcPos)> = Vec::new();
+        for choice in alternatives.iter().flat_map(|alt| alt.choices.iter()) {
+            let (low, high) = match choice.item {
+                Choice::Others => {
+                    has_others = true;
+                    continue;
+                }
+                Choice::Expression(ref expr) => {
+                    if let Some(value) = choice_type.value_of(expr) {
+                        (value, value)
+                    } else {
+                        all_known = false;
+                        continue;
+                    }
+                }
+                Choice::DiscreteRange(ref drange) => {
+                    if let Some(interval) = choice_type.interval_of(drange) {
+                        interval
+                    } else {
+                        all_known = false;
+                        continue;
+                    }
+                }
+            };
+
+            if let Some(((prev_low, prev_high), prev_pos)) = previous
+                .iter()
+                .find(|((prev_low, prev_high), _)| low <= *prev_high && *prev_low <= high)
+            {
+                let message = if low == high && prev_low == prev_high {
+                    format!("Duplicate choice {}", choice_type.describe(low))
+                } else {
+                    "Choice overlaps with a previous choice".to_owned()
+                };
+                diagnostics.push(
+                    Diagnostic::error(&choice.pos, message).related(*prev_pos, "Previous choice"),
+                );
+            }
+            previous.push(((low, high), &choice.pos));
+        }
+
+        // The range of a subtype is not known so only the full type is checked for coverage
+        let literals = if let Some(literals) = literals {
+            literals
+        } else {
+            return;
+        };
+        if has_others || !all_known || matches!(ctyp.kind(), Type::Subtype(_)) {
+            return;
+        }
+
+        let missing: Vec<&Designator> = literals
+            .iter()
+            .enumerate()
+            .filter(|(idx, _)| {
+                let idx = *idx as i64;
+                !previous
+                    .iter()
+                    .any(|((low, high), _)| *low <= idx && idx <= *high)
+            })
+            .map(|(_, literal)| *literal)
+            .collect();
+
+        if !missing.is_empty() {
+            let mut listed = missing
+                .iter()
+                .take(MAX_LISTED_MISSING)
+                .map(|literal| literal.to_string())
+                .collect::<Vec<_>>()
+                .join(", ");
+            if missing.len() > MAX_LISTED_MISSING {
+                listed.push_str(&format!(" and {} more", missing.len() - MAX_LISTED_MISSING));
+            }
+            diagnostics.error(
+                &expression.pos,
+                format!("Missing choices for {}: {}", ctyp.describe(), listed),
+            );
+        }
+    }
+}
+
+/// The enumeration literals in declaration order or None for an integer type
+struct ChoiceType<'t> {
+    literals: Option<&'t [&'t Designator]>,
+}
+
+impl<'t> ChoiceType<'t> {
+    /// The position of a locally static choice
+    fn value_of(&self, expr: &Expression) -> Option<i64> {
+        let literals = if let Some(literals) = self.literals {
+            literals
+        } else {
+            return static_integer(expr);
+        };
+
+        let designator = match expr {
+            Expression::Name(name) => match name.as_ref() {
+                Name::Designator(designator) => designator.item.clone(),
+                _ => return None,
+            },
+            Expression::Literal(Literal::Character(chr)) => Designator::Character(*chr),
+            Expression::Qualified(qexpr) => return self.value_of(&qexpr.expr.item),
+            _ => return None,
+        };
+        literals
+            .iter()
+            .position(|literal| **literal == designator)
+            .map(|idx| idx as i64)
+    }
+
+    /// The lowest and highest positions of a non-null range
+    fn interval_of(&self, drange: &DiscreteRange) -> Option<(i64, i64)> {
+        if let DiscreteRange::Range(ast::Range::Range(RangeConstraint {
+            direction,
+            left_expr,
+            right_expr,
+        })) = drange
+        {
+            let left = self.value_of(&left_expr.item)?;
+            let right = self.value_of(&right_expr.item)?;
+            let (low, high) = match direction {
+                Direction::Ascending => (left, right),
+                Direction::Descending => (right, left),
+            };
+            (low <= high).then_some((low, high))
+        } else {
+            None
+        }
+    }
+
+    fn describe(&self, value: i64) -> String {
+        match self.literals {
+            Some(literals) => literals[value as usize].to_string(),
+            None => value.to_string(),
+        }
+    }
+}
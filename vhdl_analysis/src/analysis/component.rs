@@ -0,0 +1,294 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this file,
+// You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) 2023, Olof Kraigher olof.kraigher@gmail.com
+
+//! Check that a component declaration agrees with the entity of the same name
+//! and with the entity that each of its instances is bound to by default
+
+use super::formal_region::{FormalRegion, InterfaceEnt};
+use super::named_entity::*;
+use crate::ast::*;
+use crate::data::*;
+
+/// Compare the generics and ports of a component with those of an entity
+/// and report any drift as warnings on the component
+pub(crate) fn component_entity_mismatches(component: EntRef, entity: EntRef) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+
+    let (component_region, entity_region) = match (component.kind(), entity.kind()) {
+        (
+            AnyEntKind::Component(component_region),
+            AnyEntKind::Design(Design::Entity(_, entity_region)),
+        ) => (component_region, entity_region),
+        _ => return diagnostics,
+    };
+
+    let (component_generics, component_ports) = component_region.to_entity_formal();
+    let (entity_generics, entity_ports) = entity_region.to_entity_formal();
+
+    let mut checker = MismatchChecker {
+        component,
+        entity,
+        diagnostics: &mut diagnostics,
+    };
+    checker.compare("generic", &component_generics, &entity_generics);
+    checker.compare("port", &component_ports, &entity_ports);
+
+    diagnostics
+}
+
+struct MismatchChecker<'a, 'd> {
+    component: EntRef<'a>,
+    entity: EntRef<'a>,
+    diagnostics: &'d mut Vec<Diagnostic>,
+}
+
+impl<'a, 'd> MismatchChecker<'a, 'd> {
+    fn warning(
+        &mut self,
+        pos: Option<&SrcPos>,
+        msg: String,
+        related: Option<&SrcPos>,
+        related_msg: &str,
+    ) {
+        if let Some(pos) = pos.or_else(|| self.component.decl_pos()) {
+            self.diagnostics
+                .push(Diagnostic::warning(pos, msg).opt_related(related, related_msg));
+        }
+    }
+
+    fn compare(&mut self, kind: &str, component: &FormalRegion<'a>, entity: &FormalRegion<'a>) {
+        for entity_iface in entity.iter() {
+            if let Some(component_iface) = find(component, &entity_iface) {
+                self.compare_interface(kind, component_iface, entity_iface);
+            } else {
+                self.warning(
+                    self.component.decl_pos(),
+                    format!(
+                        "Missing {kind} '{}' of {} in {}",
+                        entity_iface.designator(),
+                        self.entity.describe(),
+                        self.component.describe()
+                    ),
+                    entity_iface.decl_pos(),
+                    &format!("{kind} '{}' declared here", entity_iface.designator()),
+                );
+            }
+        }
+
+        for component_iface in component.iter() {
+            if find(entity, &component_iface).is_none() {
+                self.warning(
+                    component_iface.decl_pos(),
+                    format!(
+                        "{} '{}' of {} does not exist in {}",
+                        capitalize(kind),
+                        component_iface.designator(),
+                        self.component.describe(),
+                        self.entity.describe()
+                    ),
+                    self.entity.decl_pos(),
+                    "Entity declared here",
+                );
+            }
+        }
+
+        let component_order: Vec<_> = component
+            .iter()
+            .filter(|iface| find(entity, iface).is_some())
+            .map(|iface| iface.designator().clone())
+            .collect();
+        let entity_order: Vec<_> = entity
+            .iter()
+            .filter(|iface| find(component, iface).is_some())
+            .map(|iface| iface.designator().clone())
+            .collect();
+
+        if component_order != entity_order {
+            self.warning(
+                self.component.decl_pos(),
+                format!(
+                    "The {kind}s of {} are not declared in the same order as in {}",
+                    self.component.describe(),
+                    self.entity.describe()
+                ),
+                self.entity.decl_pos(),
+                "Entity declared here",
+            );
+        }
+    }
+
+    fn compare_interface(
+        &mut self,
+        kind: &str,
+        component_iface: InterfaceEnt<'a>,
+        entity_iface: InterfaceEnt<'a>,
+    ) {
+        let name = component_iface.designator();
+        let related_msg = format!("{kind} '{name}' declared here");
+
+        if component_iface.base() != entity_iface.base() {
+            self.warning(
+                component_iface.decl_pos(),
+                format!(
+                    "{} '{name}' has {} in {} but {} in {}",
+                    capitalize(kind),
+                    component_iface.type_mark().describe(),
+                    self.component.describe(),
+                    entity_iface.type_mark().describe(),
+                    self.entity.describe()
+                ),
+                entity_iface.decl_pos(),
+                &related_msg,
+            );
+        }
+
+        let component_mode = mode_of(component_iface);
+        let entity_mode = mode_of(entity_iface);
+        if let (Some(component_mode), Some(entity_mode)) = (component_mode, entity_mode) {
+            if component_mode != entity_mode {
+                self.warning(
+                    component_iface.decl_pos(),
+                    format!(
+                        "{} '{name}' has mode '{component_mode}' in {} but mode '{entity_mode}' in {}",
+                        capitalize(kind),
+                        self.component.describe(),
+                        self.entity.describe()
+                    ),
+                    entity_iface.decl_pos(),
+                    &related_msg,
+                );
+            }
+        }
+
+        if component_iface.has_default() != entity_iface.has_default() {
+            let (with, without) = if component_iface.has_default() {
+                (self.component, self.entity)
+            } else {
+                (self.entity, self.component)
+            };
+            self.warning(
+                component_iface.decl_pos(),
+                format!(
+                    "{} '{name}' has a default value in {} but not in {}",
+                    capitalize(kind),
+                    with.describe(),
+                    without.describe()
+                ),
+                entity_iface.decl_pos(),
+                &related_msg,
+            );
+        }
+    }
+}
+
+fn find<'a>(region: &FormalRegion<'a>, iface: &InterfaceEnt) -> Option<InterfaceEnt<'a>> {
+    region
+        .iter()
+        .find(|other| other.designator() == iface.designator())
+}
+
+fn mode_of(iface: InterfaceEnt) -> Option<crate::ast::Mode> {
+    if let AnyEntKind::Object(object) = iface.kind() {
+        object.mode()
+    } else {
+        None
+    }
+}
+
+/// The components of the instances that are not bound by a configuration specification
+/// Such instances are bound by default to the entity of the same name in the library
+/// of the instantiating design unit
+pub(super) fn default_bound_components(
+    decl: &[Declaration],
+    statements: &[LabeledConcurrentStatement],
+    components: &mut Vec<EntityId>,
+) {
+    let specs: Vec<_> = decl
+        .iter()
+        .filter_map(|decl| match decl {
+            Declaration::Configuration(spec) => Some(&spec.spec),
+            _ => None,
+        })
+        .collect();
+
+    for statement in statements {
+        match statement.statement.item {
+            ConcurrentStatement::Instance(InstantiationStatement {
+                unit: InstantiatedUnit::Component(ref name),
+                ..
+            }) => {
+                let component = if let Some(component) = selected_reference(&name.item) {
+                    component
+                } else {
+                    continue;
+                };
+                let configured = specs.iter().any(|spec| match spec.instantiation_list {
+                    InstantiationList::Labels(ref labels) => {
+                        statement.label.tree.as_ref().is_some_and(|label| {
+                            labels.iter().any(|other| other.item.item == label.item)
+                        })
+                    }
+                    InstantiationList::Others | InstantiationList::All => {
+                        selected_reference(&spec.component_name.item) == Some(component)
+                    }
+                });
+                if !configured {
+                    components.push(component);
+                }
+            }
+            ConcurrentStatement::Block(ref block) => {
+                default_bound_components(&block.decl, &block.statements, components);
+            }
+            ConcurrentStatement::ForGenerate(ref gen) => {
+                default_bound_components(
+                    gen.body.decl.as_deref().unwrap_or_default(),
+                    &gen.body.statements,
+                    components,
+                );
+            }
+            ConcurrentStatement::IfGenerate(ref gen) => {
+                for body in gen
+                    .conds
+                    .conditionals
+                    .iter()
+                    .map(|cond| &cond.item)
+                    .chain(gen.conds.else_item.iter())
+                {
+                    default_bound_components(
+                        body.decl.as_deref().unwrap_or_default(),
+                        &body.statements,
+                        components,
+                    );
+                }
+            }
+            ConcurrentStatement::CaseGenerate(ref gen) => {
+                for alternative in gen.sels.alternatives.iter() {
+                    default_bound_components(
+                        alternative.item.decl.as_deref().unwrap_or_default(),
+                        &alternative.item.statements,
+                        components,
+                    );
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn selected_reference(name: &SelectedName) -> Option<EntityId> {
+    match name {
+        SelectedName::Designator(designator) => designator.reference,
+        SelectedName::Selected(_, designator) => designator.item.reference,
+    }
+}
+
+pub(super) fn capitalize(kind: &str) -> String {
+    let mut chars = kind.chars();
+    chars
+        .next()
+        .map(|first| first.to_uppercase().chain(chars).collect())
+        .unwrap_or_default()
+}
@@ -7,12 +7,16 @@
 // These fields are better explicit than .. since we are forced to consider if new fields should be searched
 #![allow(clippy::unneeded_field_pattern)]
 
+use super::formal_region::{FormalRegion, GpkgInterfaceEnt, GpkgRegion, InterfaceEnt};
 use super::named_entity::*;
 use super::*;
+use crate::ast::search::*;
 use crate::ast::*;
 use crate::data::*;
 use analyze::*;
+use fnv::FnvHashMap;
 use region::*;
+use staticness::Staticness;
 use target::AssignmentType;
 
 impl<'a> AnalyzeContext<'a> {
@@ -24,6 +28,10 @@ impl<'a> AnalyzeContext<'a> {
         diagnostics: &mut dyn DiagnosticHandler,
     ) -> FatalResult {
         for statement in statements.iter_mut() {
+            if self.is_cancelled() {
+                return Ok(());
+            }
+
             let parent = if let Some(id) = statement.label.decl {
                 self.arena.get(id)
             } else {
@@ -82,16 +90,81 @@ impl<'a> AnalyzeContext<'a> {
                     self.boolean_expr(scope, guard_condition, diagnostics)?;
                 }
                 let nested = scope.nested();
-                if let Some(ref mut list) = block.header.generic_clause {
-                    self.analyze_interface_list(&nested, parent, list, diagnostics)?;
-                }
-                if let Some(ref mut list) = block.header.generic_map {
+                let BlockHeader {
+                    generic_clause,
+                    generic_map,
+                    port_clause,
+                    port_map,
+                } = &mut block.header;
+
+                // The generic and port maps of a block are associated with
+                // the generic and port clauses of the same block header
+                let block_pos = if let Some(ref label) = statement.label.tree {
+                    &label.pos
+                } else {
+                    &statement.statement.pos
+                };
+
+                // The actual types of interface type generics
+                let mut mapping = FnvHashMap::default();
+                if let Some(ref mut list) = generic_clause {
+                    let generics =
+                        self.analyze_interface_list(&nested, parent, list, diagnostics)?;
+                    let generic_map = generic_map.as_deref_mut().unwrap_or(&mut []);
+                    let gpkg_region = GpkgRegion::new(
+                        generics
+                            .iter()
+                            .filter_map(|ent| GpkgInterfaceEnt::from_any(ent))
+                            .collect(),
+                    );
+                    if gpkg_region.has_type() {
+                        if let Some(type_mapping) = as_fatal(self.package_generic_map(
+                            scope,
+                            gpkg_region,
+                            generic_map,
+                            diagnostics,
+                        ))? {
+                            mapping = type_mapping;
+                        }
+                    } else {
+                        let generic_region = FormalRegion::new_with(
+                            InterfaceType::Generic,
+                            generics
+                                .into_iter()
+                                .filter_map(InterfaceEnt::from_any)
+                                .collect(),
+                        );
+                        self.analyze_assoc_elems_with_formal_region(
+                            block_pos,
+                            &generic_region,
+                            scope,
+                            generic_map,
+                            diagnostics,
+                        )?;
+                    }
+                } else if let Some(ref mut list) = generic_map {
                     self.analyze_assoc_elems(scope, list, diagnostics)?;
                 }
-                if let Some(ref mut list) = block.header.port_clause {
-                    self.analyze_interface_list(&nested, parent, list, diagnostics)?;
-                }
-                if let Some(ref mut list) = block.header.port_map {
+
+                if let Some(ref mut list) = port_clause {
+                    let mut port_region = self.analyze_formal_list(
+                        &nested,
+                        parent,
+                        InterfaceType::Port,
+                        list,
+                        diagnostics,
+                    )?;
+                    if !mapping.is_empty() {
+                        port_region = self.instantiate_ports(&mapping, &port_region);
+                    }
+                    self.analyze_assoc_elems_with_formal_region(
+                        block_pos,
+                        &port_region,
+                        scope,
+                        port_map.as_deref_mut().unwrap_or(&mut []),
+                        diagnostics,
+                    )?;
+                } else if let Some(ref mut list) = port_map {
                     self.analyze_assoc_elems(scope, list, diagnostics)?;
                 }
 
@@ -123,7 +196,13 @@ impl<'a> AnalyzeContext<'a> {
                 let nested = scope.nested();
                 self.define_labels_for_sequential_part(scope, parent, statements, diagnostics)?;
                 self.analyze_declarative_part(&nested, parent, decl, diagnostics)?;
-                self.analyze_sequential_part(&nested, parent, statements, diagnostics)?;
+                self.in_sensitive_process.set(sensitivity_list.is_some());
+                let result = self.analyze_sequential_part(&nested, parent, statements, diagnostics);
+                self.in_sensitive_process.set(false);
+                result?;
+                if let Some(SensitivityList::Names(names)) = sensitivity_list {
+                    self.check_sensitivity_list_completeness(names, statements, diagnostics);
+                }
             }
             ConcurrentStatement::ForGenerate(ref mut gen) => {
                 let ForGenerateStatement {
@@ -133,6 +212,13 @@ impl<'a> AnalyzeContext<'a> {
                     end_label_pos: _,
                 } = gen;
                 let typ = as_fatal(self.drange_type(scope, discrete_range, diagnostics))?;
+                self.check_staticness(
+                    &discrete_range.pos(),
+                    self.drange_staticness(discrete_range),
+                    Staticness::Global,
+                    "Range of a for generate statement",
+                    diagnostics,
+                );
                 let nested = scope.nested();
                 nested.add(
                     index_name.define(self.arena, parent, AnyEntKind::LoopParameter(typ)),
@@ -148,6 +234,13 @@ impl<'a> AnalyzeContext<'a> {
                 for conditional in conditionals.iter_mut() {
                     let Conditional { condition, item } = conditional;
                     self.boolean_expr(scope, condition, diagnostics)?;
+                    self.check_staticness(
+                        &condition.pos,
+                        self.expression_staticness(&condition.item),
+                        Staticness::Global,
+                        "Condition of an if generate statement",
+                        diagnostics,
+                    );
                     let nested = scope.nested();
                     self.analyze_generate_body(&nested, parent, item, diagnostics)?;
                 }
@@ -157,7 +250,20 @@ impl<'a> AnalyzeContext<'a> {
                 }
             }
             ConcurrentStatement::CaseGenerate(ref mut gen) => {
-                for alternative in gen.sels.alternatives.iter_mut() {
+                let Selection {
+                    expression,
+                    alternatives,
+                } = &mut gen.sels;
+                let ctyp = as_fatal(self.expr_unambiguous_type(scope, expression, diagnostics))?;
+                self.check_staticness(
+                    &expression.pos,
+                    self.expression_staticness(&expression.item),
+                    Staticness::Global,
+                    "Expression of a case generate statement",
+                    diagnostics,
+                );
+                for alternative in alternatives.iter_mut() {
+                    self.choice_with_ttyp(scope, ctyp, &mut alternative.choices, diagnostics)?;
                     let nested = scope.nested();
                     self.analyze_generate_body(
                         &nested,
@@ -166,6 +272,9 @@ impl<'a> AnalyzeContext<'a> {
                         diagnostics,
                     )?;
                 }
+                if let Some(ctyp) = ctyp {
+                    self.check_case_choices(ctyp, expression, alternatives, diagnostics);
+                }
             }
             ConcurrentStatement::Instance(ref mut instance) => {
                 self.analyze_instance(scope, instance, diagnostics)?;
@@ -173,6 +282,12 @@ impl<'a> AnalyzeContext<'a> {
             ConcurrentStatement::Assignment(ref mut assign) => {
                 // @TODO more delaymechanism
                 let ConcurrentSignalAssignment { target, rhs, .. } = assign;
+                if let AssignmentRightHand::Simple(Waveform::Unaffected) = rhs {
+                    diagnostics.error(
+                        &statement.statement.pos,
+                        "'unaffected' is not allowed in a concurrent simple signal assignment",
+                    );
+                }
                 self.analyze_waveform_assignment(
                     scope,
                     target,
@@ -184,6 +299,7 @@ impl<'a> AnalyzeContext<'a> {
             ConcurrentStatement::ProcedureCall(ref mut pcall) => {
                 let ConcurrentProcedureCall { call, .. } = pcall;
                 self.analyze_procedure_call(scope, call, diagnostics)?;
+                self.check_implied_sensitivity(scope, call, diagnostics)?;
             }
             ConcurrentStatement::Assert(ref mut assert) => {
                 let ConcurrentAssertStatement {
@@ -243,6 +359,36 @@ impl<'a> AnalyzeContext<'a> {
         Ok(())
     }
 
+    /// The generic and port maps of an instance of an entity or a component
+    /// The types of the ports are the actuals of interface type generics
+    fn analyze_instance_maps(
+        &self,
+        scope: &Scope<'a>,
+        pos: &SrcPos,
+        region: &'a Region<'a>,
+        generic_map: &mut [AssociationElement],
+        port_map: &mut [AssociationElement],
+        diagnostics: &mut dyn DiagnosticHandler,
+    ) -> FatalResult {
+        let (generic_region, port_region) = region.to_entity_formal();
+        let port_region =
+            match as_fatal(self.generic_type_instance(scope, region, generic_map, diagnostics))? {
+                Some(Some(instantiated)) => instantiated,
+                Some(None) => {
+                    self.analyze_assoc_elems_with_formal_region(
+                        pos,
+                        &generic_region,
+                        scope,
+                        generic_map,
+                        diagnostics,
+                    )?;
+                    port_region
+                }
+                None => port_region,
+            };
+        self.analyze_assoc_elems_with_formal_region(pos, &port_region, scope, port_map, diagnostics)
+    }
+
     fn analyze_instance(
         &self,
         scope: &Scope<'a>,
@@ -282,19 +428,11 @@ impl<'a> AnalyzeContext<'a> {
                                     }
                                 }
 
-                                let (generic_region, port_region) = ent_region.to_entity_formal();
-
-                                self.analyze_assoc_elems_with_formal_region(
-                                    &entity_name.pos,
-                                    &generic_region,
+                                self.analyze_instance_maps(
                                     scope,
-                                    &mut instance.generic_map,
-                                    diagnostics,
-                                )?;
-                                self.analyze_assoc_elems_with_formal_region(
                                     &entity_name.pos,
-                                    &port_region,
-                                    scope,
+                                    ent_region,
+                                    &mut instance.generic_map,
                                     &mut instance.port_map,
                                     diagnostics,
                                 )?;
@@ -321,18 +459,11 @@ impl<'a> AnalyzeContext<'a> {
                             )?;
 
                             if let AnyEntKind::Component(ent_region) = ent.kind() {
-                                let (generic_region, port_region) = ent_region.to_entity_formal();
-                                self.analyze_assoc_elems_with_formal_region(
-                                    &component_name.pos,
-                                    &generic_region,
+                                self.analyze_instance_maps(
                                     scope,
-                                    &mut instance.generic_map,
-                                    diagnostics,
-                                )?;
-                                self.analyze_assoc_elems_with_formal_region(
                                     &component_name.pos,
-                                    &port_region,
-                                    scope,
+                                    ent_region,
+                                    &mut instance.generic_map,
                                     &mut instance.port_map,
                                     diagnostics,
                                 )?;
@@ -374,6 +505,73 @@ impl<'a> AnalyzeContext<'a> {
         Ok(())
     }
 
+    /// A concurrent procedure call is equivalent to a process that calls the procedure and then
+    /// waits on the signals associated with formals of mode in or inout (LRM 11.4)
+    /// Without such signals the procedure is only called once
+    fn check_implied_sensitivity(
+        &self,
+        scope: &Scope<'a>,
+        call: &mut WithPos<CallOrIndexed>,
+        diagnostics: &mut dyn DiagnosticHandler,
+    ) -> FatalResult {
+        let procedure = if let Some(ent) = call
+            .item
+            .name
+            .item
+            .get_suffix_reference()
+            .and_then(|id| OverloadedEnt::from_any(self.arena.get(id)))
+            .filter(|ent| ent.is_procedure())
+        {
+            ent
+        } else {
+            return Ok(());
+        };
+
+        // A procedure that drives signals without being sensitive to any signal is expected
+        // to wait by itself such as a clock generator
+        if procedure
+            .formals()
+            .iter()
+            .any(|formal| formal.is_out_or_inout_signal())
+        {
+            return Ok(());
+        }
+
+        let formals = if let Some(formals) = as_fatal(self.resolve_association_formals(
+            &call.pos,
+            procedure.formals(),
+            scope,
+            &mut call.item.parameters,
+            &mut NullDiagnostics,
+        ))? {
+            formals
+        } else {
+            return Ok(());
+        };
+
+        for (assoc, formal) in call.item.parameters.iter_mut().zip(formals.iter()) {
+            if is_in_or_inout(formal.interface()) {
+                let mut searcher = FindSignal {
+                    context: self,
+                    found: false,
+                };
+                let _ = assoc.actual.search(&mut searcher);
+                if searcher.found {
+                    return Ok(());
+                }
+            }
+        }
+
+        diagnostics.warning(
+            &call.pos,
+            format!(
+                "Concurrent call to {} is only executed once since no signal is associated with a formal of mode in or inout",
+                procedure.describe()
+            ),
+        );
+        Ok(())
+    }
+
     pub fn sensitivity_list_check(
         &self,
         scope: &Scope<'a>,
@@ -411,3 +609,30 @@ impl<'a> AnalyzeContext<'a> {
         Ok(())
     }
 }
+
+fn is_in_or_inout(formal: InterfaceEnt) -> bool {
+    match formal.kind() {
+        AnyEntKind::Object(object) => matches!(object.mode(), Some(Mode::In | Mode::InOut)),
+        _ => false,
+    }
+}
+
+/// Find if an expression reads a signal
+pub(super) struct FindSignal<'c, 'a> {
+    pub(super) context: &'c AnalyzeContext<'a>,
+    pub(super) found: bool,
+}
+
+impl<'c, 'a> Searcher for FindSignal<'c, 'a> {
+    fn search_pos_with_ref(&mut self, _pos: &SrcPos, reference: &mut Reference) -> SearchState {
+        if let Some(id) = reference {
+            if let AnyEntKind::Object(object) = self.context.arena.get(*id).kind() {
+                if object.class == ObjectClass::Signal {
+                    self.found = true;
+                    return SearchState::Finished(SearchResult::Found);
+                }
+            }
+        }
+        SearchState::NotFinished
+    }
+}
@@ -0,0 +1,474 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this file,
+// You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) 2023, Olof Kraigher olof.kraigher@gmail.com
+
+//! Analysis of the block configurations of configuration declarations
+//! The labels of block and component configurations denote the block, generate
+//! and instance statements of the configured architecture
+
+use super::analyze::*;
+use super::named_entity::*;
+use super::region::*;
+use crate::ast::*;
+use crate::data::*;
+use std::ops::Deref;
+
+impl<'a> AnalyzeContext<'a> {
+    /// Analyze a block configuration of an architecture of the entity
+    pub(super) fn analyze_block_configuration(
+        &self,
+        scope: &Scope<'a>,
+        entity: DesignEnt<'a>,
+        block_config: &mut BlockConfiguration,
+        diagnostics: &mut dyn DiagnosticHandler,
+    ) -> FatalResult {
+        let (library_name, entity_name) = match (entity.library_name(), entity.designator()) {
+            (Some(library_name), Designator::Identifier(entity_name)) => {
+                (library_name, entity_name)
+            }
+            _ => return Ok(()),
+        };
+
+        let block_spec = &mut block_config.block_spec;
+        let architecture_name = match block_spec.item {
+            Name::Designator(ref mut designator) => match designator.item {
+                Designator::Identifier(ref name) => name.clone(),
+                _ => return Ok(()),
+            },
+            _ => {
+                diagnostics.error(
+                    &block_spec.pos,
+                    format!(
+                        "Expected the name of an architecture of {}",
+                        entity.describe()
+                    ),
+                );
+                return Ok(());
+            }
+        };
+
+        match self.get_architecture(
+            library_name,
+            &block_spec.pos,
+            entity_name,
+            &architecture_name,
+        ) {
+            Ok(architecture) => block_spec.set_unique_reference(&architecture),
+            Err(err) => {
+                err.add_to(diagnostics)?;
+                return Ok(());
+            }
+        }
+
+        let unit = if let Some(unit) = self.get_architecture_unit(
+            library_name,
+            &block_spec.pos,
+            entity_name,
+            &architecture_name,
+        )? {
+            unit
+        } else {
+            return Ok(());
+        };
+
+        if let AnyDesignUnit::Secondary(AnySecondaryUnit::Architecture(ref architecture)) =
+            unit.deref()
+        {
+            let statements: Vec<_> = architecture.statements.iter().collect();
+            self.analyze_configuration_items(
+                scope,
+                &statements,
+                &mut block_config.items,
+                diagnostics,
+            )?;
+        }
+        Ok(())
+    }
+
+    fn analyze_configuration_items(
+        &self,
+        scope: &Scope<'a>,
+        statements: &[&LabeledConcurrentStatement],
+        items: &mut [ConfigurationItem],
+        diagnostics: &mut dyn DiagnosticHandler,
+    ) -> FatalResult {
+        for item in items.iter_mut() {
+            match item {
+                ConfigurationItem::Block(ref mut block_config) => {
+                    self.analyze_nested_block_configuration(
+                        scope,
+                        statements,
+                        block_config,
+                        diagnostics,
+                    )?;
+                }
+                ConfigurationItem::Component(ref mut component_config) => {
+                    self.analyze_component_configuration(
+                        scope,
+                        statements,
+                        component_config,
+                        diagnostics,
+                    )?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// A block configuration of a block statement or of a generate statement where
+    /// `gen(alternative)` selects an alternative of an if or case generate statement
+    fn analyze_nested_block_configuration(
+        &self,
+        scope: &Scope<'a>,
+        statements: &[&LabeledConcurrentStatement],
+        block_config: &mut BlockConfiguration,
+        diagnostics: &mut dyn DiagnosticHandler,
+    ) -> FatalResult {
+        let block_spec = &mut block_config.block_spec;
+        let (label, alternative) = match block_spec.item {
+            Name::Designator(_) => (&mut block_spec.item, None),
+            Name::CallOrIndexed(ref mut call) => {
+                let alternative = match call.parameters.as_mut_slice() {
+                    [AssociationElement {
+                        formal: None,
+                        actual:
+                            WithPos {
+                                item: ActualPart::Expression(Expression::Name(name)),
+                                ..
+                            },
+                    }] if matches!(name.as_ref(), Name::Designator(_)) => Some(name.as_mut()),
+                    _ => None,
+                };
+                (&mut call.name.item, alternative)
+            }
+            Name::Slice(ref mut prefix, _) => (&mut prefix.item, None),
+            _ => return Ok(()),
+        };
+
+        let label_name = if let Name::Designator(WithRef {
+            item: Designator::Identifier(ref name),
+            ..
+        }) = label
+        {
+            name.clone()
+        } else {
+            return Ok(());
+        };
+
+        let statement = if let Some(statement) = find_labeled(statements, &label_name) {
+            statement
+        } else {
+            diagnostics.error(
+                &block_spec.pos,
+                format!("No block or generate statement with label '{label_name}'"),
+            );
+            return Ok(());
+        };
+        if let Some(id) = statement.label.decl {
+            label.set_unique_reference(self.arena.get(id));
+        }
+
+        let bodies: Vec<&GenerateBody> = match statement.statement.item {
+            ConcurrentStatement::Block(ref block) => {
+                let statements: Vec<_> = block.statements.iter().collect();
+                return self.analyze_configuration_items(
+                    scope,
+                    &statements,
+                    &mut block_config.items,
+                    diagnostics,
+                );
+            }
+            ConcurrentStatement::ForGenerate(ref gen) => vec![&gen.body],
+            ConcurrentStatement::IfGenerate(ref gen) => gen
+                .conds
+                .conditionals
+                .iter()
+                .map(|cond| &cond.item)
+                .chain(gen.conds.else_item.iter())
+                .collect(),
+            ConcurrentStatement::CaseGenerate(ref gen) => gen
+                .sels
+                .alternatives
+                .iter()
+                .map(|alternative| &alternative.item)
+                .collect(),
+            _ => {
+                diagnostics.error(
+                    &block_spec.pos,
+                    format!(
+                        "Expected block or generate label, got {}",
+                        self.describe_label(statement)
+                    ),
+                );
+                return Ok(());
+            }
+        };
+
+        // The index of a for generate statement is not an alternative
+        let alternative_body = alternative.and_then(|name| {
+            let body = bodies.iter().find(|body| {
+                body.alternative_label
+                    .as_ref()
+                    .is_some_and(|alternative_label| {
+                        matches!(name, Name::Designator(WithRef {
+                        item: Designator::Identifier(ref alternative_name),
+                        ..
+                    }) if alternative_name == &alternative_label.tree.item)
+                    })
+            })?;
+            if let Some(id) = body.alternative_label.as_ref().and_then(|label| label.decl) {
+                name.set_unique_reference(self.arena.get(id));
+            }
+            Some(*body)
+        });
+
+        let statements: Vec<_> = if let Some(body) = alternative_body {
+            body.statements.iter().collect()
+        } else {
+            bodies
+                .iter()
+                .flat_map(|body| body.statements.iter())
+                .collect()
+        };
+        self.analyze_configuration_items(scope, &statements, &mut block_config.items, diagnostics)
+    }
+
+    fn analyze_component_configuration(
+        &self,
+        scope: &Scope<'a>,
+        statements: &[&LabeledConcurrentStatement],
+        component_config: &mut ComponentConfiguration,
+        diagnostics: &mut dyn DiagnosticHandler,
+    ) -> FatalResult {
+        let ComponentConfiguration {
+            spec,
+            bind_ind,
+            vunit_bind_inds,
+            block_config,
+        } = component_config;
+        let component_name = selected_designator(&spec.component_name.item).clone();
+
+        // The instances of the component
+        let mut instances = Vec::new();
+        match spec.instantiation_list {
+            InstantiationList::Labels(ref mut labels) => {
+                for label in labels.iter_mut() {
+                    let statement =
+                        if let Some(statement) = find_labeled(statements, &label.item.item) {
+                            statement
+                        } else {
+                            diagnostics.error(
+                                &label.item.pos,
+                                format!("No component instance with label '{}'", label.item.item),
+                            );
+                            continue;
+                        };
+                    if let Some(id) = statement.label.decl {
+                        label.set_unique_reference(self.arena.get(id));
+                    }
+
+                    match component_instance_of(statement) {
+                        Some(name) if selected_designator(name) == &component_name => {
+                            instances.push(name);
+                        }
+                        Some(_) => {
+                            diagnostics.error(
+                                &label.item.pos,
+                                format!(
+                                    "Instance '{}' is not an instance of component '{}'",
+                                    label.item.item, component_name
+                                ),
+                            );
+                        }
+                        None => {
+                            diagnostics.error(
+                                &label.item.pos,
+                                format!(
+                                    "Expected component instance label, got {}",
+                                    self.describe_label(statement)
+                                ),
+                            );
+                        }
+                    }
+                }
+            }
+            InstantiationList::Others | InstantiationList::All => {
+                instances.extend(
+                    statements
+                        .iter()
+                        .filter_map(|statement| component_instance_of(statement))
+                        .filter(|name| selected_designator(name) == &component_name),
+                );
+            }
+        }
+
+        if let Some(id) = instances.first().and_then(|name| selected_reference(name)) {
+            *selected_reference_mut(&mut spec.component_name.item) = Some(id);
+        }
+
+        let mut is_open = false;
+        let mut bound_entity = None;
+        match bind_ind
+            .as_mut()
+            .and_then(|bind_ind| bind_ind.entity_aspect.as_mut())
+        {
+            Some(EntityAspect::Entity(ref mut entity_name, ref mut architecture_name)) => {
+                match self.resolve_entity_aspect(scope, entity_name, architecture_name, diagnostics)
+                {
+                    Ok(entity) => bound_entity = Some(entity),
+                    Err(err) => err.add_to(diagnostics)?,
+                }
+            }
+            Some(EntityAspect::Configuration(ref mut config_name)) => {
+                if let Err(err) = self.resolve_configuration_aspect(scope, config_name) {
+                    err.add_to(diagnostics)?;
+                }
+            }
+            Some(EntityAspect::Open) => {
+                is_open = true;
+            }
+            None => {
+                // The default binding is the entity of the same name as the component
+                if block_config.is_some() {
+                    bound_entity = self
+                        .lookup_in_library(
+                            self.work_library_name(),
+                            &spec.component_name.pos,
+                            &component_name,
+                        )
+                        .ok()
+                        .filter(|design| matches!(design.kind(), Design::Entity(..)));
+                }
+            }
+        }
+
+        if is_open {
+            if let Some(vunit) = vunit_bind_inds
+                .iter()
+                .flat_map(|vunit_bind_ind| vunit_bind_ind.vunit_list.iter())
+                .next()
+            {
+                diagnostics.error(
+                    &vunit.pos,
+                    "Verification units cannot be bound to an open component instance",
+                );
+            }
+        }
+
+        if let (Some(entity), Some(block_config)) = (bound_entity, block_config) {
+            self.analyze_block_configuration(scope, entity, block_config, diagnostics)?;
+        }
+        Ok(())
+    }
+
+    fn describe_label(&self, statement: &LabeledConcurrentStatement) -> String {
+        match (statement.label.decl, &statement.label.tree) {
+            (Some(id), _) => self.arena.get(id).describe(),
+            (None, Some(label)) => format!("'{}'", label.item),
+            (None, None) => "statement".to_owned(),
+        }
+    }
+
+    fn resolve_entity_aspect(
+        &self,
+        scope: &Scope<'a>,
+        entity_name: &mut WithPos<SelectedName>,
+        architecture_name: &mut Option<WithRef<Ident>>,
+        diagnostics: &mut dyn DiagnosticHandler,
+    ) -> AnalysisResult<DesignEnt<'a>> {
+        let expected = "entity";
+        let entities = self.resolve_selected_name(scope, entity_name)?;
+        let ent = self.resolve_non_overloaded(entities, entity_name.suffix_pos(), expected)?;
+        let entity = DesignEnt::from_any(ent)
+            .filter(|design| matches!(design.kind(), Design::Entity(..)))
+            .ok_or_else(|| {
+                AnalysisError::NotFatal(ent.kind_error(entity_name.suffix_pos(), expected))
+            })?;
+
+        if let (Some(library_name), Designator::Identifier(entity_ident), Some(architecture_name)) = (
+            entity.library_name(),
+            entity.designator(),
+            architecture_name,
+        ) {
+            match self.get_architecture(
+                library_name,
+                &architecture_name.item.pos,
+                entity_ident,
+                &architecture_name.item.item,
+            ) {
+                Ok(architecture) => architecture_name.set_unique_reference(&architecture),
+                Err(err) => diagnostics.push(err.into_non_fatal()?),
+            }
+        }
+        Ok(entity)
+    }
+
+    fn resolve_configuration_aspect(
+        &self,
+        scope: &Scope<'a>,
+        config_name: &mut WithPos<SelectedName>,
+    ) -> AnalysisResult<()> {
+        let expected = "configuration";
+        let entities = self.resolve_selected_name(scope, config_name)?;
+        let ent = self.resolve_non_overloaded(entities, config_name.suffix_pos(), expected)?;
+        if matches!(ent.kind(), AnyEntKind::Design(Design::Configuration)) {
+            Ok(())
+        } else {
+            Err(AnalysisError::NotFatal(
+                ent.kind_error(config_name.suffix_pos(), expected),
+            ))
+        }
+    }
+}
+
+fn find_labeled<'s>(
+    statements: &[&'s LabeledConcurrentStatement],
+    label: &Symbol,
+) -> Option<&'s LabeledConcurrentStatement> {
+    statements
+        .iter()
+        .find(|statement| {
+            statement
+                .label
+                .tree
+                .as_ref()
+                .is_some_and(|ident| &ident.item == label)
+        })
+        .copied()
+}
+
+/// The component name of a component instance
+fn component_instance_of(statement: &LabeledConcurrentStatement) -> Option<&SelectedName> {
+    if let ConcurrentStatement::Instance(InstantiationStatement {
+        unit: InstantiatedUnit::Component(ref name),
+        ..
+    }) = statement.statement.item
+    {
+        Some(&name.item)
+    } else {
+        None
+    }
+}
+
+fn selected_designator(name: &SelectedName) -> &Designator {
+    match name {
+        SelectedName::Designator(designator) => &designator.item,
+        SelectedName::Selected(_, designator) => &designator.item.item,
+    }
+}
+
+fn selected_reference(name: &SelectedName) -> Option<EntityId> {
+    match name {
+        SelectedName::Designator(designator) => designator.reference,
+        SelectedName::Selected(_, designator) => designator.item.reference,
+    }
+}
+
+fn selected_reference_mut(name: &mut SelectedName) -> &mut Reference {
+    match name {
+        SelectedName::Designator(designator) => &mut designator.reference,
+        SelectedName::Selected(_, designator) => &mut designator.item.reference,
+    }
+}
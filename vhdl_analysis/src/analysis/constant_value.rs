@@ -0,0 +1,1111 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this file,
+// You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) 2023, Olof Kraigher olof.kraigher@gmail.com
+
+//! Evaluate package level constants built from literals, aggregates, predefined operators
+//! and other constants such as configuration tables, without elaborating the design
+
+use super::named_entity::*;
+use super::root::DesignRoot;
+use super::static_expression::bit_string_to_string;
+use crate::ast;
+use crate::ast::search::*;
+use crate::ast::*;
+use crate::config::GenericValue;
+use crate::data::*;
+use fnv::{FnvHashMap, FnvHashSet};
+use std::fmt;
+
+/// Arrays larger than this are not evaluated
+const MAX_ELEMENTS: i64 = 1 << 16;
+
+/// The value of a constant
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub enum ConstantValue {
+    Integer(i64),
+    /// A physical value in multiples of the primary unit such as `fs` for `time`
+    Physical(i64, Designator),
+    Character(u8),
+    /// An enumeration literal such as `true`
+    Literal(Designator),
+    /// The elements from left to right, the index of the left element is not known
+    /// for arrays indexed by enumeration types
+    Array {
+        left: Option<i64>,
+        ascending: bool,
+        elements: Vec<ConstantValue>,
+    },
+    Record(Vec<(Designator, ConstantValue)>),
+}
+
+impl ConstantValue {
+    pub(super) fn integer(&self) -> Option<i64> {
+        if let ConstantValue::Integer(value) = self {
+            Some(*value)
+        } else {
+            None
+        }
+    }
+
+    pub(super) fn boolean(&self) -> Option<bool> {
+        if let ConstantValue::Literal(Designator::Identifier(name)) = self {
+            let name = name.name_utf8();
+            if name.eq_ignore_ascii_case("true") {
+                Some(true)
+            } else if name.eq_ignore_ascii_case("false") {
+                Some(false)
+            } else {
+                None
+            }
+        } else {
+            None
+        }
+    }
+}
+
+fn boolean_value(root: &DesignRoot, value: bool) -> ConstantValue {
+    ConstantValue::Literal(Designator::Identifier(root.symbol_utf8(if value {
+        "true"
+    } else {
+        "false"
+    })))
+}
+
+/// The index of the right element of an array
+fn right_index(left: i64, ascending: bool, len: usize) -> i64 {
+    if ascending {
+        left + len as i64 - 1
+    } else {
+        left - len as i64 + 1
+    }
+}
+
+fn direction(ascending: bool) -> &'static str {
+    if ascending {
+        "to"
+    } else {
+        "downto"
+    }
+}
+
+impl fmt::Display for ConstantValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConstantValue::Integer(value) => write!(f, "{value}"),
+            ConstantValue::Physical(value, unit) => write!(f, "{value} {unit}"),
+            ConstantValue::Character(chr) => write!(f, "'{}'", *chr as char),
+            ConstantValue::Literal(designator) => write!(f, "{designator}"),
+            ConstantValue::Array { elements, .. } => {
+                let is_string = !elements.is_empty()
+                    && elements
+                        .iter()
+                        .all(|elem| matches!(elem, ConstantValue::Character(_)));
+                if is_string {
+                    write!(f, "\"")?;
+                    for elem in elements.iter() {
+                        if let ConstantValue::Character(chr) = elem {
+                            write!(f, "{}", *chr as char)?;
+                        }
+                    }
+                    write!(f, "\"")
+                } else {
+                    let elements: Vec<_> = elements.iter().map(|elem| elem.to_string()).collect();
+                    write!(f, "({})", elements.join(", "))
+                }
+            }
+            ConstantValue::Record(elements) => {
+                let elements: Vec<_> = elements
+                    .iter()
+                    .map(|(name, value)| format!("{name} => {value}"))
+                    .collect();
+                write!(f, "({})", elements.join(", "))
+            }
+        }
+    }
+}
+
+/// What is known about a type to evaluate an aggregate of it
+#[derive(Debug, Clone)]
+enum Shape {
+    Scalar,
+    /// A one-dimensional array where the right index is not known when it is unconstrained
+    Array {
+        left: Option<i64>,
+        ascending: bool,
+        right: Option<i64>,
+        element: Box<Shape>,
+    },
+    Record(Vec<(Designator, Shape)>),
+}
+
+/// The values of package level constants
+#[derive(Default, Clone)]
+pub(crate) struct ConstantValues {
+    values: FnvHashMap<EntityId, ConstantValue>,
+    /// Constants whose value is computed rather than written as a literal
+    computed: FnvHashSet<EntityId>,
+    shapes: FnvHashMap<EntityId, Shape>,
+    /// The value of physical units in multiples of the primary unit of their type
+    units: FnvHashMap<EntityId, (i64, Designator)>,
+}
+
+impl ConstantValues {
+    /// The value of a constant that is not written as a literal in its declaration
+    pub fn computed_value(&self, id: EntityId) -> Option<&ConstantValue> {
+        if self.computed.contains(&id) {
+            self.values.get(&id)
+        } else {
+            None
+        }
+    }
+
+    /// The number of evaluated constants and types
+    pub fn len(&self) -> usize {
+        self.values.len() + self.shapes.len() + self.units.len()
+    }
+
+    /// Evaluate a static expression from literals, predefined operators and evaluated constants
+    pub fn evaluate(&self, root: &DesignRoot, expr: &Expression) -> Option<ConstantValue> {
+        self.eval(root, expr, &Shape::Scalar)
+    }
+
+    /// Evaluate the types and constants of a package declaration
+    pub fn declarations(&mut self, root: &DesignRoot, decls: &[Declaration]) {
+        for decl in decls.iter() {
+            match decl {
+                Declaration::Type(TypeDeclaration {
+                    def: TypeDefinition::Physical(physical),
+                    ..
+                }) => self.physical_units(physical),
+                Declaration::Type(type_decl) => {
+                    if let Some(id) = type_decl.ident.decl {
+                        if let Some(shape) = self.type_shape(root, &type_decl.def) {
+                            self.shapes.insert(id, shape);
+                        }
+                    }
+                }
+                Declaration::Object(object) if object.class == ObjectClass::Constant => {
+                    if let (Some(id), Some(expr)) = (object.ident.decl, &object.expression) {
+                        if self.values.contains_key(&id) {
+                            continue;
+                        }
+                        let shape = self.subtype_shape(root, &object.subtype_indication);
+                        if let Some(value) = self.eval(root, &expr.item, &shape) {
+                            self.values.insert(id, value);
+                            if !matches!(expr.item, Expression::Literal(_)) {
+                                self.computed.insert(id);
+                            }
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Evaluate the generics of a top level entity from the generics file or their defaults
+    /// together with the declarations of the entity
+    pub fn top_level_entity(
+        &mut self,
+        root: &DesignRoot,
+        entity: &EntityDeclaration,
+        overrides: &FnvHashMap<String, GenericValue>,
+    ) {
+        for generic in entity.generic_clause.iter().flatten() {
+            if let InterfaceDeclaration::Object(object) = generic {
+                let id = if let Some(id) = object.ident.decl {
+                    id
+                } else {
+                    continue;
+                };
+                if self.values.contains_key(&id) {
+                    continue;
+                }
+
+                let shape = self.subtype_shape(root, &object.subtype_indication);
+                let name = object.ident.tree.item.name_utf8().to_lowercase();
+                let value = if let Some(value) = overrides.get(&name) {
+                    Some(generic_value(root, value, &shape))
+                } else if let Some(ref expr) = object.expression {
+                    self.eval(root, &expr.item, &shape)
+                } else {
+                    None
+                };
+
+                if let Some(value) = value {
+                    self.values.insert(id, value);
+                    // The value on hover shows which generics are overridden
+                    self.computed.insert(id);
+                }
+            }
+        }
+        self.declarations(root, &entity.decl);
+    }
+
+    /// Evaluate the generics of an instance of an entity from their actuals or their defaults
+    /// together with the declarations of the entity, replacing the values of other instances
+    pub fn entity_instance(
+        &mut self,
+        root: &DesignRoot,
+        entity: &EntityDeclaration,
+        actuals: &FnvHashMap<Symbol, ConstantValue>,
+    ) {
+        for generic in entity.generic_clause.iter().flatten() {
+            if let InterfaceDeclaration::Object(object) = generic {
+                let id = if let Some(id) = object.ident.decl {
+                    id
+                } else {
+                    continue;
+                };
+                self.values.remove(&id);
+
+                let value = if let Some(value) = actuals.get(&object.ident.tree.item) {
+                    Some(value.clone())
+                } else if let Some(ref expr) = object.expression {
+                    let shape = self.subtype_shape(root, &object.subtype_indication);
+                    self.eval(root, &expr.item, &shape)
+                } else {
+                    None
+                };
+
+                if let Some(value) = value {
+                    self.values.insert(id, value);
+                    self.computed.insert(id);
+                }
+            }
+        }
+        self.local_declarations(root, &entity.decl);
+    }
+
+    /// Evaluate the actuals of a generic map in the instantiating design unit
+    /// Positional actuals are associated with the generics of the entity in order
+    pub fn generic_map_actuals(
+        &self,
+        root: &DesignRoot,
+        entity: &EntityDeclaration,
+        generic_map: &[AssociationElement],
+        actuals: &mut FnvHashMap<Symbol, ConstantValue>,
+    ) {
+        let generics: Vec<_> = entity.generic_clause.iter().flatten().collect();
+        for (idx, assoc) in generic_map.iter().enumerate() {
+            let name = match assoc.formal {
+                Some(ref formal) => match formal.item {
+                    Name::Designator(ref designator) => match designator.item {
+                        Designator::Identifier(ref name) => name.clone(),
+                        _ => continue,
+                    },
+                    _ => continue,
+                },
+                None => match generics.get(idx) {
+                    Some(InterfaceDeclaration::Object(object)) => object.ident.tree.item.clone(),
+                    _ => continue,
+                },
+            };
+            if let ActualPart::Expression(ref expr) = assoc.actual.item {
+                if let Some(value) = self.evaluate(root, expr) {
+                    actuals.insert(name, value);
+                }
+            }
+        }
+    }
+
+    /// Evaluate declarations which have different values in each instance of a design unit
+    pub fn local_declarations(&mut self, root: &DesignRoot, decls: &[Declaration]) {
+        for decl in decls.iter() {
+            if let Declaration::Object(object) = decl {
+                if let Some(id) = object.ident.decl {
+                    self.values.remove(&id);
+                }
+            }
+        }
+        self.declarations(root, decls);
+    }
+
+    /// The value of a generic of a top level entity from the generics file
+    pub fn override_value(
+        &self,
+        root: &DesignRoot,
+        object: &InterfaceObjectDeclaration,
+        value: &GenericValue,
+    ) -> ConstantValue {
+        let shape = self.subtype_shape(root, &object.subtype_indication);
+        generic_value(root, value, &shape)
+    }
+
+    /// Set the value of a constant such as the parameter of a generate statement
+    pub fn set_value(&mut self, id: EntityId, value: ConstantValue) {
+        self.values.insert(id, value);
+    }
+
+    /// The left bound, the direction and the right bound of an integer range
+    pub fn evaluate_range(
+        &self,
+        root: &DesignRoot,
+        range: &DiscreteRange,
+    ) -> Option<(i64, bool, i64)> {
+        self.range_bounds(root, range)
+    }
+
+    /// The number of elements of a constrained one dimensional array subtype
+    pub fn subtype_length(&self, root: &DesignRoot, subtype: &SubtypeIndication) -> Option<i64> {
+        match self.subtype_shape(root, subtype) {
+            Shape::Array {
+                left: Some(left),
+                ascending,
+                right: Some(right),
+                ..
+            } => Some(
+                if ascending {
+                    right - left + 1
+                } else {
+                    left - right + 1
+                }
+                .max(0),
+            ),
+            _ => None,
+        }
+    }
+
+    fn physical_units(&mut self, physical: &PhysicalTypeDeclaration) {
+        let primary = if let Some(id) = physical.primary_unit.decl {
+            id
+        } else {
+            return;
+        };
+        let primary_unit = Designator::Identifier(physical.primary_unit.tree.item.clone());
+        self.units.insert(primary, (1, primary_unit.clone()));
+
+        // Secondary units are defined in terms of the units before them
+        for (ident, literal) in physical.secondary_units.iter() {
+            if let (Some(id), Some(ConstantValue::Physical(value, _))) =
+                (ident.decl, self.physical_literal(literal))
+            {
+                self.units.insert(id, (value, primary_unit.clone()));
+            }
+        }
+    }
+
+    fn physical_literal(&self, literal: &PhysicalLiteral) -> Option<ConstantValue> {
+        let (scale, unit) = self.units.get(&literal.unit.reference?)?;
+        let value = match literal.value {
+            AbstractLiteral::Integer(value) => i64::try_from(value).ok()?.checked_mul(*scale)?,
+            AbstractLiteral::Real(value) => {
+                let value = (value * *scale as f64).round();
+                if value.abs() < i64::MAX as f64 {
+                    value as i64
+                } else {
+                    return None;
+                }
+            }
+        };
+        Some(ConstantValue::Physical(value, unit.clone()))
+    }
+
+    fn type_shape(&self, root: &DesignRoot, def: &TypeDefinition) -> Option<Shape> {
+        match def {
+            TypeDefinition::Array(indexes, element) => {
+                let element = Box::new(self.subtype_shape(root, element));
+                match indexes.as_slice() {
+                    [ArrayIndex::IndexSubtypeDefintion(type_mark)] => Some(Shape::Array {
+                        left: index_subtype_left(root, &type_mark.item),
+                        ascending: true,
+                        right: None,
+                        element,
+                    }),
+                    [ArrayIndex::Discrete(range)] => {
+                        let (left, ascending, right) = match self.range_bounds(root, range) {
+                            Some((left, ascending, right)) => (Some(left), ascending, Some(right)),
+                            None => (None, true, None),
+                        };
+                        Some(Shape::Array {
+                            left,
+                            ascending,
+                            right,
+                            element,
+                        })
+                    }
+                    _ => None,
+                }
+            }
+            TypeDefinition::Record(elements) => Some(Shape::Record(
+                elements
+                    .iter()
+                    .map(|elem| {
+                        (
+                            Designator::Identifier(elem.ident.tree.item.clone()),
+                            self.subtype_shape(root, &elem.subtype),
+                        )
+                    })
+                    .collect(),
+            )),
+            TypeDefinition::Subtype(subtype) => Some(self.subtype_shape(root, subtype)),
+            _ => None,
+        }
+    }
+
+    fn type_mark_shape(&self, type_mark: &TypeMark) -> Shape {
+        if type_mark.attr.is_some() {
+            return Shape::Scalar;
+        }
+        selected_reference(&type_mark.name.item)
+            .and_then(|id| self.shapes.get(&id))
+            .cloned()
+            .unwrap_or(Shape::Scalar)
+    }
+
+    fn subtype_shape(&self, root: &DesignRoot, subtype: &SubtypeIndication) -> Shape {
+        let shape = self.type_mark_shape(&subtype.type_mark.item);
+        match (shape, &subtype.constraint) {
+            (
+                Shape::Array { element, .. },
+                Some(WithPos {
+                    item: SubtypeConstraint::Array(ranges, _),
+                    ..
+                }),
+            ) => {
+                if let [range] = ranges.as_slice() {
+                    if let Some((left, ascending, right)) = self.range_bounds(root, range) {
+                        return Shape::Array {
+                            left: Some(left),
+                            ascending,
+                            right: Some(right),
+                            element,
+                        };
+                    }
+                }
+                Shape::Array {
+                    left: None,
+                    ascending: true,
+                    right: None,
+                    element,
+                }
+            }
+            (shape, _) => shape,
+        }
+    }
+
+    fn range_bounds(&self, root: &DesignRoot, range: &DiscreteRange) -> Option<(i64, bool, i64)> {
+        let constraint = match range {
+            DiscreteRange::Range(ast::Range::Range(constraint)) => constraint,
+            DiscreteRange::Discrete(_, Some(ast::Range::Range(constraint))) => constraint,
+            _ => return None,
+        };
+        let left = self.eval_integer(root, &constraint.left_expr.item)?;
+        let right = self.eval_integer(root, &constraint.right_expr.item)?;
+        Some((left, constraint.direction == Direction::Ascending, right))
+    }
+
+    fn eval_integer(&self, root: &DesignRoot, expr: &Expression) -> Option<i64> {
+        self.eval(root, expr, &Shape::Scalar)?.integer()
+    }
+
+    fn eval(&self, root: &DesignRoot, expr: &Expression, shape: &Shape) -> Option<ConstantValue> {
+        match expr {
+            Expression::Literal(literal) => match literal {
+                Literal::AbstractLiteral(AbstractLiteral::Integer(value)) => {
+                    i64::try_from(*value).ok().map(ConstantValue::Integer)
+                }
+                Literal::Character(chr) => Some(ConstantValue::Character(*chr)),
+                Literal::String(value) => string_value(&value.bytes, shape),
+                Literal::BitString(value) => {
+                    string_value(&bit_string_to_string(value).ok()?.bytes, shape)
+                }
+                Literal::Physical(literal) => self.physical_literal(literal),
+                _ => None,
+            },
+            Expression::Name(name) => self.eval_name(root, name),
+            Expression::Qualified(qexpr) => {
+                let shape = self.type_mark_shape(&qexpr.type_mark.item);
+                self.eval(root, &qexpr.expr.item, &shape)
+            }
+            Expression::Unary(op, operand) => {
+                let operand = self.eval(root, &operand.item, shape)?;
+                unary_operation(root, op.item.item, operand)
+            }
+            Expression::Binary(op, left, right) => {
+                let left = self.eval(root, &left.item, shape)?;
+                let right = self.eval(root, &right.item, shape)?;
+                let value = binary_operation(root, op.item.item, left, right)?;
+                // The bounds of a concatenation are those of the declared subtype when known
+                match (value, shape) {
+                    (
+                        ConstantValue::Array {
+                            left: None,
+                            elements,
+                            ..
+                        },
+                        Shape::Array {
+                            left, ascending, ..
+                        },
+                    ) => Some(ConstantValue::Array {
+                        left: *left,
+                        ascending: *ascending,
+                        elements,
+                    }),
+                    (value, _) => Some(value),
+                }
+            }
+            Expression::Aggregate(assocs) => match shape {
+                Shape::Array {
+                    left,
+                    ascending,
+                    right,
+                    element,
+                } => self.eval_array_aggregate(root, assocs, *left, *ascending, *right, element),
+                Shape::Record(elements) => self.eval_record_aggregate(root, assocs, elements),
+                Shape::Scalar => None,
+            },
+            _ => None,
+        }
+    }
+
+    fn eval_name(&self, root: &DesignRoot, name: &Name) -> Option<ConstantValue> {
+        match name {
+            Name::Designator(designator) => self.named_value(root, designator.reference?),
+            Name::Selected(prefix, suffix) => {
+                if let Some(value) = suffix
+                    .item
+                    .reference
+                    .and_then(|id| self.named_value(root, id))
+                {
+                    return Some(value);
+                }
+                if let ConstantValue::Record(elements) = self.eval_name(root, &prefix.item)? {
+                    elements
+                        .into_iter()
+                        .find(|(name, _)| name == &suffix.item.item)
+                        .map(|(_, value)| value)
+                } else {
+                    None
+                }
+            }
+            Name::CallOrIndexed(call) => {
+                let index = match call.parameters.as_slice() {
+                    [AssociationElement {
+                        formal: None,
+                        actual:
+                            WithPos {
+                                item: ActualPart::Expression(expr),
+                                ..
+                            },
+                    }] => self.eval_integer(root, expr)?,
+                    _ => return None,
+                };
+                if let ConstantValue::Array {
+                    left: Some(left),
+                    ascending,
+                    elements,
+                } = self.eval_name(root, &call.name.item)?
+                {
+                    let offset = if ascending {
+                        index.checked_sub(left)?
+                    } else {
+                        left.checked_sub(index)?
+                    };
+                    elements.get(usize::try_from(offset).ok()?).cloned()
+                } else {
+                    None
+                }
+            }
+            _ => None,
+        }
+    }
+
+    fn named_value(&self, root: &DesignRoot, id: EntityId) -> Option<ConstantValue> {
+        if let Some(value) = self.values.get(&id) {
+            return Some(value.clone());
+        }
+        let ent = root.get_ent(id);
+        if matches!(
+            ent.kind(),
+            AnyEntKind::Overloaded(Overloaded::EnumLiteral(..))
+        ) {
+            Some(ConstantValue::Literal(ent.designator().clone()))
+        } else {
+            None
+        }
+    }
+
+    fn eval_array_aggregate(
+        &self,
+        root: &DesignRoot,
+        assocs: &[ElementAssociation],
+        left: Option<i64>,
+        ascending: bool,
+        right: Option<i64>,
+        element: &Shape,
+    ) -> Option<ConstantValue> {
+        let mut positional = Vec::new();
+        let mut named = Vec::new();
+        let mut others = None;
+
+        for assoc in assocs.iter() {
+            match assoc {
+                ElementAssociation::Positional(expr) => {
+                    positional.push(self.eval(root, &expr.item, element)?);
+                }
+                ElementAssociation::Named(choices, expr) => {
+                    let value = self.eval(root, &expr.item, element)?;
+                    for choice in choices.iter() {
+                        match choice.item {
+                            Choice::Expression(ref index) => {
+                                named.push((self.eval_integer(root, index)?, value.clone()));
+                            }
+                            Choice::DiscreteRange(ref range) => {
+                                let (from, ascending, to) = self.range_bounds(root, range)?;
+                                let (low, high) = if ascending { (from, to) } else { (to, from) };
+                                if high - low > MAX_ELEMENTS {
+                                    return None;
+                                }
+                                for index in low..=high {
+                                    named.push((index, value.clone()));
+                                }
+                            }
+                            Choice::Others => others = Some(value.clone()),
+                        }
+                    }
+                }
+            }
+        }
+
+        if !positional.is_empty() && !named.is_empty() {
+            return None;
+        }
+
+        let bounds = match (left, right) {
+            (Some(left), Some(right)) => Some((left, right)),
+            _ => None,
+        };
+        let len = |left: i64, right: i64| {
+            let len = if ascending {
+                right - left
+            } else {
+                left - right
+            } + 1;
+            if len > MAX_ELEMENTS {
+                None
+            } else {
+                usize::try_from(len.max(0)).ok()
+            }
+        };
+
+        if named.is_empty() {
+            let len = match bounds {
+                Some((left, right)) => len(left, right)?,
+                None if others.is_none() => positional.len(),
+                None => return None,
+            };
+            if positional.len() > len {
+                return None;
+            }
+            while positional.len() < len {
+                positional.push(others.clone()?);
+            }
+            return Some(ConstantValue::Array {
+                left,
+                ascending,
+                elements: positional,
+            });
+        }
+
+        let (left, right) = match bounds {
+            Some(bounds) => bounds,
+            None if others.is_none() => {
+                let low = named.iter().map(|(index, _)| *index).min()?;
+                let high = named.iter().map(|(index, _)| *index).max()?;
+                if ascending {
+                    (low, high)
+                } else {
+                    (high, low)
+                }
+            }
+            None => return None,
+        };
+        let mut elements: Vec<Option<ConstantValue>> = vec![None; len(left, right)?];
+        for (index, value) in named {
+            let offset = if ascending {
+                index - left
+            } else {
+                left - index
+            };
+            *elements.get_mut(usize::try_from(offset).ok()?)? = Some(value);
+        }
+        let elements = elements
+            .into_iter()
+            .map(|elem| elem.or_else(|| others.clone()))
+            .collect::<Option<Vec<_>>>()?;
+
+        Some(ConstantValue::Array {
+            left: Some(left),
+            ascending,
+            elements,
+        })
+    }
+
+    fn eval_record_aggregate(
+        &self,
+        root: &DesignRoot,
+        assocs: &[ElementAssociation],
+        fields: &[(Designator, Shape)],
+    ) -> Option<ConstantValue> {
+        let mut values: Vec<Option<ConstantValue>> = vec![None; fields.len()];
+        let mut others = None;
+
+        for (idx, assoc) in assocs.iter().enumerate() {
+            match assoc {
+                ElementAssociation::Positional(expr) => {
+                    let (_, shape) = fields.get(idx)?;
+                    values[idx] = Some(self.eval(root, &expr.item, shape)?);
+                }
+                ElementAssociation::Named(choices, expr) => {
+                    for choice in choices.iter() {
+                        match choice.item {
+                            Choice::Expression(Expression::Name(ref name)) => {
+                                let designator = if let Name::Designator(designator) = name.as_ref()
+                                {
+                                    &designator.item
+                                } else {
+                                    return None;
+                                };
+                                let idx = fields.iter().position(|(name, _)| name == designator)?;
+                                values[idx] = Some(self.eval(root, &expr.item, &fields[idx].1)?);
+                            }
+                            Choice::Others => others = Some(expr),
+                            _ => return None,
+                        }
+                    }
+                }
+            }
+        }
+
+        let mut elements = Vec::with_capacity(fields.len());
+        for ((name, shape), value) in fields.iter().zip(values) {
+            let value = match value {
+                Some(value) => value,
+                None => self.eval(root, &others?.item, shape)?,
+            };
+            elements.push((name.clone(), value));
+        }
+        Some(ConstantValue::Record(elements))
+    }
+
+    /// Report indexes of constant arrays that are outside of their range
+    pub fn check_indexes(&self, root: &DesignRoot) -> Vec<Diagnostic> {
+        let mut checker = IndexChecker {
+            root,
+            values: self,
+            diagnostics: Vec::new(),
+        };
+        let _ = root.search(&mut checker);
+        checker.diagnostics
+    }
+}
+
+fn unary_operation(
+    root: &DesignRoot,
+    op: Operator,
+    operand: ConstantValue,
+) -> Option<ConstantValue> {
+    match operand {
+        ConstantValue::Integer(value) => match op {
+            Operator::Plus => Some(value),
+            Operator::Minus => value.checked_neg(),
+            Operator::Abs => value.checked_abs(),
+            _ => None,
+        }
+        .map(ConstantValue::Integer),
+        ConstantValue::Physical(value, unit) => match op {
+            Operator::Plus => Some(value),
+            Operator::Minus => value.checked_neg(),
+            Operator::Abs => value.checked_abs(),
+            _ => None,
+        }
+        .map(|value| ConstantValue::Physical(value, unit)),
+        operand => {
+            let value = operand.boolean()?;
+            if op == Operator::Not {
+                Some(boolean_value(root, !value))
+            } else {
+                None
+            }
+        }
+    }
+}
+
+fn integer_operation(op: Operator, left: i64, right: i64) -> Option<i64> {
+    match op {
+        Operator::Plus => left.checked_add(right),
+        Operator::Minus => left.checked_sub(right),
+        Operator::Times => left.checked_mul(right),
+        Operator::Div => left.checked_div(right),
+        Operator::Mod => left.checked_rem_euclid(right).map(|rem| {
+            if rem != 0 && right < 0 {
+                rem + right
+            } else {
+                rem
+            }
+        }),
+        Operator::Rem => left.checked_rem(right),
+        Operator::Pow => u32::try_from(right)
+            .ok()
+            .and_then(|right| left.checked_pow(right)),
+        _ => None,
+    }
+}
+
+/// The result of a relational operator or None if it is not a relational operator
+fn compare<T: PartialOrd>(op: Operator, left: &T, right: &T) -> Option<bool> {
+    match op {
+        Operator::EQ => Some(left == right),
+        Operator::NE => Some(left != right),
+        Operator::LT => Some(left < right),
+        Operator::LTE => Some(left <= right),
+        Operator::GT => Some(left > right),
+        Operator::GTE => Some(left >= right),
+        _ => None,
+    }
+}
+
+fn binary_operation(
+    root: &DesignRoot,
+    op: Operator,
+    left: ConstantValue,
+    right: ConstantValue,
+) -> Option<ConstantValue> {
+    if op == Operator::Concat {
+        return concatenate(left, right);
+    }
+
+    match (left, right) {
+        (ConstantValue::Integer(left), ConstantValue::Integer(right)) => {
+            if let Some(result) = compare(op, &left, &right) {
+                Some(boolean_value(root, result))
+            } else {
+                integer_operation(op, left, right).map(ConstantValue::Integer)
+            }
+        }
+        (ConstantValue::Physical(left, unit), ConstantValue::Physical(right, _)) => {
+            if let Some(result) = compare(op, &left, &right) {
+                return Some(boolean_value(root, result));
+            }
+            match op {
+                Operator::Plus | Operator::Minus => integer_operation(op, left, right)
+                    .map(|value| ConstantValue::Physical(value, unit)),
+                Operator::Div => left.checked_div(right).map(ConstantValue::Integer),
+                _ => None,
+            }
+        }
+        (ConstantValue::Physical(left, unit), ConstantValue::Integer(right)) => match op {
+            Operator::Times | Operator::Div => {
+                integer_operation(op, left, right).map(|value| ConstantValue::Physical(value, unit))
+            }
+            _ => None,
+        },
+        (ConstantValue::Integer(left), ConstantValue::Physical(right, unit)) => match op {
+            Operator::Times => left
+                .checked_mul(right)
+                .map(|value| ConstantValue::Physical(value, unit)),
+            _ => None,
+        },
+        (ConstantValue::Character(left), ConstantValue::Character(right)) => {
+            compare(op, &left, &right).map(|result| boolean_value(root, result))
+        }
+        (left, right) => {
+            if let (Some(left), Some(right)) = (left.boolean(), right.boolean()) {
+                let result = match op {
+                    Operator::And => left && right,
+                    Operator::Or => left || right,
+                    Operator::Xor => left != right,
+                    Operator::Nand => !(left && right),
+                    Operator::Nor => !(left || right),
+                    Operator::Xnor => left == right,
+                    _ => compare(op, &left, &right)?,
+                };
+                Some(boolean_value(root, result))
+            } else {
+                // Other values such as arrays and records are only compared for equality
+                match op {
+                    Operator::EQ => Some(boolean_value(root, equals(&left, &right))),
+                    Operator::NE => Some(boolean_value(root, !equals(&left, &right))),
+                    _ => None,
+                }
+            }
+        }
+    }
+}
+
+/// Arrays are equal when their elements are equal regardless of their bounds
+fn equals(left: &ConstantValue, right: &ConstantValue) -> bool {
+    match (left, right) {
+        (
+            ConstantValue::Array { elements: left, .. },
+            ConstantValue::Array {
+                elements: right, ..
+            },
+        ) => {
+            left.len() == right.len()
+                && left
+                    .iter()
+                    .zip(right.iter())
+                    .all(|(left, right)| equals(left, right))
+        }
+        (ConstantValue::Record(left), ConstantValue::Record(right)) => {
+            left.len() == right.len()
+                && left
+                    .iter()
+                    .zip(right.iter())
+                    .all(|((_, left), (_, right))| equals(left, right))
+        }
+        (left, right) => left == right,
+    }
+}
+
+/// Concatenate arrays and elements, the left index of the result is the left index
+/// of the left operand when it is an array
+fn concatenate(left: ConstantValue, right: ConstantValue) -> Option<ConstantValue> {
+    let (left_index, ascending, mut elements) = match left {
+        ConstantValue::Array {
+            left,
+            ascending,
+            elements,
+        } => (left, ascending, elements),
+        element => (None, true, vec![element]),
+    };
+    match right {
+        ConstantValue::Array {
+            elements: right, ..
+        } => elements.extend(right),
+        element => elements.push(element),
+    }
+    if elements.len() as i64 > MAX_ELEMENTS {
+        return None;
+    }
+    Some(ConstantValue::Array {
+        left: left_index,
+        ascending,
+        elements,
+    })
+}
+
+/// The bounds of a string are unknown when the shape is not known such as for an
+/// operand of a relational operator
+fn string_value(bytes: &[u8], shape: &Shape) -> Option<ConstantValue> {
+    let (left, ascending) = match shape {
+        Shape::Array {
+            left, ascending, ..
+        } => (*left, *ascending),
+        Shape::Scalar => (None, true),
+        Shape::Record(_) => return None,
+    };
+    Some(ConstantValue::Array {
+        left,
+        ascending,
+        elements: bytes
+            .iter()
+            .map(|chr| ConstantValue::Character(*chr))
+            .collect(),
+    })
+}
+
+fn generic_value(root: &DesignRoot, value: &GenericValue, shape: &Shape) -> ConstantValue {
+    match value {
+        GenericValue::Integer(value) => ConstantValue::Integer(*value),
+        GenericValue::Boolean(value) => boolean_value(root, *value),
+        GenericValue::String(value) => {
+            let elements = value.bytes().map(ConstantValue::Character).collect();
+            let (left, ascending) = if let Shape::Array {
+                left, ascending, ..
+            } = shape
+            {
+                (left.or(Some(1)), *ascending)
+            } else {
+                (Some(1), true)
+            };
+            ConstantValue::Array {
+                left,
+                ascending,
+                elements,
+            }
+        }
+    }
+}
+
+fn selected_reference(name: &SelectedName) -> Option<EntityId> {
+    match name {
+        SelectedName::Designator(designator) => designator.reference,
+        SelectedName::Selected(_, designator) => designator.item.reference,
+    }
+}
+
+/// The left index of an unconstrained array indexed by `natural range <>` or `positive range <>`
+fn index_subtype_left(root: &DesignRoot, type_mark: &TypeMark) -> Option<i64> {
+    let ent = root.get_ent(selected_reference(&type_mark.name.item)?);
+    if let Designator::Identifier(name) = ent.designator() {
+        let name = name.name_utf8();
+        if name.eq_ignore_ascii_case("natural") {
+            return Some(0);
+        } else if name.eq_ignore_ascii_case("positive") {
+            return Some(1);
+        }
+    }
+    None
+}
+
+struct IndexChecker<'a> {
+    root: &'a DesignRoot,
+    values: &'a ConstantValues,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl<'a> Searcher for IndexChecker<'a> {
+    fn search_call_or_indexed(&mut self, call: &CallOrIndexed) -> SearchState {
+        let id = match call.name.item {
+            Name::Designator(ref designator) => designator.reference,
+            Name::Selected(_, ref designator) => designator.item.reference,
+            _ => None,
+        };
+        let array = id.and_then(|id| Some((id, self.values.values.get(&id)?)));
+
+        if let (
+            Some((
+                id,
+                ConstantValue::Array {
+                    left: Some(left),
+                    ascending,
+                    elements,
+                },
+            )),
+            [AssociationElement {
+                formal: None,
+                actual:
+                    WithPos {
+                        item: ActualPart::Expression(expr),
+                        pos,
+                    },
+            }],
+        ) = (array, call.parameters.as_slice())
+        {
+            if let Some(index) = self.values.eval_integer(self.root, expr) {
+                let right = right_index(*left, *ascending, elements.len());
+                let (low, high) = if *ascending {
+                    (*left, right)
+                } else {
+                    (right, *left)
+                };
+                if index < low || index > high {
+                    self.diagnostics.push(Diagnostic::error(
+                        pos,
+                        format!(
+                            "Index {index} is outside of the range {left} {} {right} of constant '{}'",
+                            direction(*ascending),
+                            self.root.get_ent(id).designator()
+                        ),
+                    ));
+                }
+            }
+        }
+        NotFinished
+    }
+}
@@ -0,0 +1,99 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this file,
+// You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) 2023, Olof Kraigher olof.kraigher@gmail.com
+
+//! Find the branches of if generate statements that can never be selected since their
+//! conditions evaluate to false, such as code for another vendor guarded by a generic
+
+use super::root::DesignRoot;
+use crate::ast::*;
+use crate::data::*;
+
+/// The spans of the branches of if generate statements that are not selected
+/// with the evaluated constants, from their condition or else keyword to the next branch
+pub(super) fn dead_generate_branches(
+    root: &DesignRoot,
+    statements: &[LabeledConcurrentStatement],
+    result: &mut Vec<SrcPos>,
+) {
+    for statement in statements.iter() {
+        match statement.statement.item {
+            ConcurrentStatement::Block(ref block) => {
+                dead_generate_branches(root, &block.statements, result);
+            }
+            ConcurrentStatement::ForGenerate(ref gen) => {
+                dead_generate_branches(root, &gen.body.statements, result);
+            }
+            ConcurrentStatement::IfGenerate(ref gen) => {
+                if_generate(root, gen, &statement.statement.pos, result);
+            }
+            ConcurrentStatement::CaseGenerate(ref gen) => {
+                for alternative in gen.sels.alternatives.iter() {
+                    dead_generate_branches(root, &alternative.item.statements, result);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn if_generate(
+    root: &DesignRoot,
+    gen: &IfGenerateStatement,
+    pos: &SrcPos,
+    result: &mut Vec<SrcPos>,
+) {
+    let conditionals = &gen.conds.conditionals;
+    // Each branch extends to the condition of the next branch, the else keyword or the end
+    let end_of = |idx: usize| -> Position {
+        match conditionals.get(idx + 1) {
+            Some(next) => next.condition.pos.start(),
+            None => gen
+                .else_pos
+                .as_ref()
+                .map(|else_pos| else_pos.start())
+                .unwrap_or_else(|| pos.end()),
+        }
+    };
+
+    let mut selected = false;
+    for (idx, conditional) in conditionals.iter().enumerate() {
+        let value = if selected {
+            Some(false)
+        } else {
+            root.evaluate_expression(&conditional.condition.item)
+                .and_then(|value| value.boolean())
+        };
+        match value {
+            Some(true) => {
+                selected = true;
+                dead_generate_branches(root, &conditional.item.statements, result);
+            }
+            Some(false) => {
+                let start = conditional.condition.pos.start();
+                result.push(pos.source.pos(start, end_of(idx)));
+            }
+            // The branches that follow may be selected or not
+            None => {
+                for conditional in conditionals[idx..].iter() {
+                    dead_generate_branches(root, &conditional.item.statements, result);
+                }
+                if let Some(ref else_item) = gen.conds.else_item {
+                    dead_generate_branches(root, &else_item.statements, result);
+                }
+                return;
+            }
+        }
+    }
+
+    if let Some(ref else_item) = gen.conds.else_item {
+        match gen.else_pos {
+            Some(ref else_pos) if selected => {
+                result.push(pos.source.pos(else_pos.start(), pos.end()));
+            }
+            _ => dead_generate_branches(root, &else_item.statements, result),
+        }
+    }
+}
@@ -5,17 +5,21 @@
 // Copyright (c) 2019, Olof Kraigher olof.kraigher@gmail.com
 
 use super::formal_region::FormalRegion;
+use super::formal_region::InterfaceEnt;
 use super::formal_region::RecordRegion;
 use super::named_entity::*;
 use super::names::*;
+use super::semantic::SemanticDiagnostics;
 use super::*;
 use crate::ast;
 use crate::ast::*;
+use crate::config::VHDLStandard;
 use crate::data::*;
 use analyze::*;
 use fnv::FnvHashMap;
 use named_entity::Signature;
 use region::*;
+use staticness::Staticness;
 use std::collections::hash_map::Entry;
 
 impl<'a> AnalyzeContext<'a> {
@@ -29,6 +33,10 @@ impl<'a> AnalyzeContext<'a> {
         let mut incomplete_types: FnvHashMap<Symbol, (EntRef<'a>, SrcPos)> = FnvHashMap::default();
 
         for i in 0..declarations.len() {
+            if self.is_cancelled() {
+                return Ok(());
+            }
+
             // Handle incomplete types
 
             let (decl, remaining) = declarations[i..].split_first_mut().unwrap();
@@ -108,6 +116,12 @@ impl<'a> AnalyzeContext<'a> {
                     self.analyze_declaration(scope, parent, &mut declarations[i], diagnostics)?;
                 }
             }
+
+            if let Some((id, span)) = declaration_span(&declarations[i]) {
+                unsafe {
+                    self.arena.get(id).set_decl_span(span.clone());
+                }
+            }
         }
         Ok(())
     }
@@ -154,7 +168,7 @@ impl<'a> AnalyzeContext<'a> {
                             class,
                             type_mark: oname.type_mark(),
                         },
-                        ObjectBase::DeferredConstant(_) => {
+                        ObjectBase::DeferredConstant(_) | ObjectBase::ImplicitSignal(_) => {
                             // @TODO handle
                             return Err(EvalError::Unknown);
                         }
@@ -274,6 +288,17 @@ impl<'a> AnalyzeContext<'a> {
 
                 match subtype {
                     Ok(subtype) => {
+                        if object_decl.class == ObjectClass::Signal {
+                            if let Some(diagnostic) = Diagnostic::illegal_signal_type(
+                                &object_decl.subtype_indication.type_mark.pos,
+                                "Signal",
+                                &object_decl.ident.tree,
+                                subtype.type_mark(),
+                            ) {
+                                diagnostics.push(diagnostic);
+                            }
+                        }
+
                         let kind = if object_decl.class == ObjectClass::Constant
                             && object_decl.expression.is_none()
                         {
@@ -283,6 +308,7 @@ impl<'a> AnalyzeContext<'a> {
                                 class: object_decl.class,
                                 iface: None,
                                 has_default: object_decl.expression.is_some(),
+                                signal_kind: object_decl.signal_kind,
                                 subtype,
                             })
                         };
@@ -513,6 +539,9 @@ impl<'a> AnalyzeContext<'a> {
                     &mut body.statements,
                     diagnostics,
                 )?;
+                self.check_return_paths(subpgm_ent.into(), &body.statements, diagnostics);
+                self.check_function_purity(subpgm_ent, body, diagnostics);
+                self.check_parameter_modes(subpgm_ent, body, diagnostics);
             }
             Declaration::SubprogramDeclaration(ref mut subdecl) => {
                 match self.subprogram_declaration(
@@ -782,6 +811,16 @@ impl<'a> AnalyzeContext<'a> {
                         self.resolve_subtype_indication(scope, &mut elem_decl.subtype, diagnostics);
                     match subtype {
                         Ok(subtype) => {
+                            if self.is_illegal_element_type(subtype.type_mark()) {
+                                diagnostics.error(
+                                    &elem_decl.subtype.type_mark.pos,
+                                    format!(
+                                        "Record element '{}' cannot be of {}",
+                                        elem_decl.ident.tree.item,
+                                        subtype.base_type().describe()
+                                    ),
+                                );
+                            }
                             let elem = self.arena.define(
                                 &mut elem_decl.ident,
                                 type_ent.into(),
@@ -856,6 +895,16 @@ impl<'a> AnalyzeContext<'a> {
                         }
                     };
 
+                if self.is_illegal_element_type(elem_type) {
+                    diagnostics.error(
+                        &subtype_indication.type_mark.pos,
+                        format!(
+                            "Array element cannot be of {}",
+                            elem_type.base_type().describe()
+                        ),
+                    );
+                }
+
                 let is_1d = indexes.len() == 1;
                 let array_ent = TypeEnt::define_with_opt_id(
                     self.arena,
@@ -901,6 +950,13 @@ impl<'a> AnalyzeContext<'a> {
                     &mut physical.range,
                     diagnostics,
                 )?;
+                self.check_staticness(
+                    &physical.range.pos(),
+                    self.range_staticness(&physical.range),
+                    Staticness::Local,
+                    "Range of a type definition",
+                    diagnostics,
+                );
 
                 let phys_type = TypeEnt::define_with_opt_id(
                     self.arena,
@@ -964,6 +1020,13 @@ impl<'a> AnalyzeContext<'a> {
 
             TypeDefinition::Numeric(ref mut range) => {
                 self.range_unknown_typ(scope, range, diagnostics)?;
+                self.check_staticness(
+                    &range.pos(),
+                    self.range_staticness(range),
+                    Staticness::Local,
+                    "Range of a type definition",
+                    diagnostics,
+                );
 
                 let universal_type = if let Some(range_typ) =
                     as_fatal(self.range_type(scope, range, diagnostics))?
@@ -1034,7 +1097,7 @@ impl<'a> AnalyzeContext<'a> {
     }
 
     /// The matching operators such as ?= are defined for 1d arrays of bit and std_ulogic element type
-    fn has_matching_op(&self, typ: TypeEnt<'a>) -> bool {
+    pub fn has_matching_op(&self, typ: TypeEnt<'a>) -> bool {
         if self.is_std_logic_1164 {
             // Within the std_logic_1164 we do not have efficient access to the types
             typ.designator() == &Designator::Identifier(self.root.symbol_utf8("std_ulogic"))
@@ -1135,6 +1198,25 @@ impl<'a> AnalyzeContext<'a> {
                 }
 
                 let subtype = subtype?;
+                if object_decl.list_type == InterfaceType::Port {
+                    self.check_port_constraint(&object_decl.subtype_indication, diagnostics);
+                }
+                if object_decl.class == ObjectClass::Signal {
+                    let prefix = if object_decl.list_type == InterfaceType::Port {
+                        "Port"
+                    } else {
+                        "Signal parameter"
+                    };
+                    if let Some(diagnostic) = Diagnostic::illegal_signal_type(
+                        &object_decl.subtype_indication.type_mark.pos,
+                        prefix,
+                        &object_decl.ident.tree,
+                        subtype.type_mark(),
+                    ) {
+                        diagnostics.push(diagnostic);
+                    }
+                }
+
                 self.arena.define(
                     &mut object_decl.ident,
                     parent,
@@ -1145,6 +1227,7 @@ impl<'a> AnalyzeContext<'a> {
                             object_decl.mode,
                         )),
                         subtype,
+                        signal_kind: object_decl.signal_kind,
                         has_default: object_decl.expression.is_some(),
                     }),
                 )
@@ -1202,18 +1285,20 @@ impl<'a> AnalyzeContext<'a> {
         parent: EntRef<'a>,
         declarations: &mut [InterfaceDeclaration],
         diagnostics: &mut dyn DiagnosticHandler,
-    ) -> FatalResult {
+    ) -> FatalResult<Vec<EntRef<'a>>> {
+        let mut ents = Vec::with_capacity(declarations.len());
         for decl in declarations.iter_mut() {
             match self.analyze_interface_declaration(scope, parent, decl, diagnostics) {
                 Ok(ent) => {
                     scope.add(ent, diagnostics);
+                    ents.push(ent);
                 }
                 Err(err) => {
                     err.add_to(diagnostics)?;
                 }
             }
         }
-        Ok(())
+        Ok(ents)
     }
 
     pub fn analyze_parameter_list(
@@ -1223,20 +1308,33 @@ impl<'a> AnalyzeContext<'a> {
         declarations: &mut [InterfaceDeclaration],
         diagnostics: &mut dyn DiagnosticHandler,
     ) -> FatalResult<FormalRegion<'a>> {
-        let mut params = FormalRegion::new(InterfaceType::Parameter);
+        self.analyze_formal_list(
+            scope,
+            parent,
+            InterfaceType::Parameter,
+            declarations,
+            diagnostics,
+        )
+    }
 
-        for decl in declarations.iter_mut() {
-            match self.analyze_interface_declaration(scope, parent, decl, diagnostics) {
-                Ok(ent) => {
-                    scope.add(ent, diagnostics);
-                    params.add(ent);
-                }
-                Err(err) => {
-                    err.add_to(diagnostics)?;
-                }
-            }
-        }
-        Ok(params)
+    /// Analyze an interface list and return the formal region of the declared interface objects
+    /// in declaration order
+    pub fn analyze_formal_list(
+        &self,
+        scope: &Scope<'a>,
+        parent: EntRef<'a>,
+        typ: InterfaceType,
+        declarations: &mut [InterfaceDeclaration],
+        diagnostics: &mut dyn DiagnosticHandler,
+    ) -> FatalResult<FormalRegion<'a>> {
+        let ents = self.analyze_interface_list(scope, parent, declarations, diagnostics)?;
+        // Interface types, subprograms and packages are not associated like objects
+        Ok(FormalRegion::new_with(
+            typ,
+            ents.into_iter()
+                .filter_map(InterfaceEnt::from_any)
+                .collect(),
+        ))
     }
 
     fn analyze_array_index(
@@ -1376,9 +1474,9 @@ impl<'a> AnalyzeContext<'a> {
     ) -> AnalysisResult<Subtype<'a>> {
         // @TODO more
         let SubtypeIndication {
+            resolution,
             type_mark,
             constraint,
-            ..
         } = subtype_indication;
 
         let base_type = self.resolve_type_mark(scope, type_mark)?;
@@ -1393,7 +1491,11 @@ impl<'a> AnalyzeContext<'a> {
             )?;
         }
 
-        Ok(Subtype::new(base_type))
+        if matches!(resolution, ResolutionIndication::Unresolved) {
+            Ok(Subtype::new(base_type))
+        } else {
+            Ok(Subtype::with_resolution(base_type))
+        }
     }
 
     pub fn analyze_subtype_indication(
@@ -1437,7 +1539,9 @@ impl<'a> AnalyzeContext<'a> {
                     diagnostics,
                 );
                 let return_type = self.resolve_type_mark(scope, &mut fun.return_type);
-                Signature::new(params?, Some(return_type?))
+                let mut signature = Signature::new(params?, Some(return_type?));
+                signature.impure = !fun.pure;
+                signature
             }
             SubprogramDeclaration::Procedure(procedure) => {
                 let params = self.analyze_parameter_list(
@@ -1468,6 +1572,36 @@ impl<'a> AnalyzeContext<'a> {
         subprogram.set_decl_id(ent.id());
         Ok((subpgm_region, OverloadedEnt::from_any(ent).unwrap()))
     }
+
+    /// Composite types may not have elements of file type
+    /// or of protected type before VHDL-2019 (LRM 5.3.2.1, 5.3.3)
+    fn is_illegal_element_type(&self, typ: TypeEnt) -> bool {
+        match typ.base_type().kind() {
+            Type::File => true,
+            Type::Protected(..) => self.root.standard < VHDLStandard::VHDL2019,
+            _ => false,
+        }
+    }
+}
+
+/// The entity defined by a declaration and the span of the whole declaration
+fn declaration_span(decl: &Declaration) -> Option<(EntityId, &SrcPos)> {
+    match decl {
+        Declaration::Object(object_decl) => Some((object_decl.ident.decl?, &object_decl.pos)),
+        Declaration::Type(type_decl) => Some((type_decl.ident.decl?, &type_decl.pos)),
+        Declaration::SubprogramDeclaration(specification)
+        | Declaration::SubprogramBody(SubprogramBody { specification, .. }) => {
+            match specification {
+                SubprogramDeclaration::Procedure(procedure) => {
+                    Some((procedure.designator.decl?, &procedure.pos))
+                }
+                SubprogramDeclaration::Function(function) => {
+                    Some((function.designator.decl?, &function.pos))
+                }
+            }
+        }
+        _ => None,
+    }
 }
 
 fn find_full_type_definition<'a>(
@@ -1491,7 +1625,44 @@ fn find_full_type_definition<'a>(
     None
 }
 
-impl Diagnostic {
+/// Find a subelement of a signal that is of access, file or protected type (LRM 6.4.2.3)
+/// Returns the suffix of the subelement name and its type
+fn find_illegal_signal_subelement(typ: TypeEnt) -> Option<(String, TypeEnt)> {
+    let base_type = typ.base_type();
+    match base_type.kind() {
+        Type::Access(..) | Type::File | Type::Protected(..) => Some((String::new(), base_type)),
+        Type::Array { elem_type, .. } => find_illegal_signal_subelement(*elem_type)
+            .map(|(suffix, typ)| (format!("'element{suffix}"), typ)),
+        Type::Record(elems) => elems.iter().find_map(|elem| {
+            find_illegal_signal_subelement(elem.type_mark())
+                .map(|(suffix, typ)| (format!(".{}{suffix}", elem.designator()), typ))
+        }),
+        _ => None,
+    }
+}
+
+/// Diagnostics about aliases and signal declarations
+trait DeclarationDiagnostics {
+    fn no_overloaded_with_signature(
+        pos: &SrcPos,
+        des: &Designator,
+        overloaded: &OverloadedName,
+    ) -> Diagnostic;
+
+    fn should_not_have_signature(prefix: &str, pos: impl AsRef<SrcPos>) -> Diagnostic;
+
+    fn signature_required(pos: impl AsRef<SrcPos>) -> Diagnostic;
+
+    /// Signals may not contain values that are not copied by value
+    fn illegal_signal_type(
+        pos: impl AsRef<SrcPos>,
+        prefix: &str,
+        ident: &Ident,
+        typ: TypeEnt,
+    ) -> Option<Diagnostic>;
+}
+
+impl DeclarationDiagnostics for Diagnostic {
     fn no_overloaded_with_signature(
         pos: &SrcPos,
         des: &Designator,
@@ -1521,4 +1692,26 @@ impl Diagnostic {
             "Signature required for alias of subprogram and enum literals",
         )
     }
+
+    fn illegal_signal_type(
+        pos: impl AsRef<SrcPos>,
+        prefix: &str,
+        ident: &Ident,
+        typ: TypeEnt,
+    ) -> Option<Diagnostic> {
+        let (suffix, typ) = find_illegal_signal_subelement(typ)?;
+        Some(Diagnostic::error(
+            pos,
+            if suffix.is_empty() {
+                format!("{prefix} '{}' cannot be of {}", ident.item, typ.describe())
+            } else {
+                format!(
+                    "{prefix} '{}' cannot have subelement '{}{suffix}' of {}",
+                    ident.item,
+                    ident.item,
+                    typ.describe()
+                )
+            },
+        ))
+    }
 }
@@ -5,6 +5,8 @@
 // Copyright (c) 2019, Olof Kraigher olof.kraigher@gmail.com
 
 use super::named_entity::*;
+use super::reset_value::check_reset_values;
+use super::semantic::SemanticDiagnostics;
 use super::visibility::Visibility;
 use super::*;
 use crate::ast::*;
@@ -98,6 +100,12 @@ impl<'a> AnalyzeContext<'a> {
 
         match self.lookup_entity_for_configuration(&root_region, unit) {
             Ok(named_entity) => {
+                self.analyze_block_configuration(
+                    &root_region,
+                    named_entity,
+                    &mut unit.block_config,
+                    diagnostics,
+                )?;
                 if let Some(primary_pos) = named_entity.decl_pos() {
                     let secondary_pos = unit.pos();
                     if primary_pos.source == secondary_pos.source
@@ -274,6 +282,9 @@ impl<'a> AnalyzeContext<'a> {
         self.define_labels_for_concurrent_part(&scope, arch, &mut unit.statements, diagnostics)?;
         self.analyze_declarative_part(&scope, arch, &mut unit.decl, diagnostics)?;
         self.analyze_concurrent_part(&scope, arch, &mut unit.statements, diagnostics)?;
+        self.check_multiple_drivers(&unit.statements, diagnostics);
+        self.check_latches(&unit.statements, diagnostics);
+        check_reset_values(&unit.decl, &unit.statements, diagnostics);
         scope.close(diagnostics);
         Ok(())
     }
@@ -455,6 +466,9 @@ impl<'a> AnalyzeContext<'a> {
             Name::Designator(designator) => {
                 let visible = scope.lookup(&name.pos, designator.designator())?;
                 designator.set_reference(&visible);
+                if let NamedEntities::Single(ent) = visible {
+                    self.record_work_reference(&name.pos, &designator.item, ent);
+                }
                 Ok(UsedNames::Single(visible))
             }
 
@@ -480,6 +494,11 @@ impl<'a> AnalyzeContext<'a> {
                     for library_name in name_list.iter_mut() {
                         if self.work_sym == library_name.item.item {
                             library_name.set_unique_reference(self.work_library());
+                            self.record_work_reference(
+                                &library_name.item.pos,
+                                &self.work_sym.clone().into(),
+                                self.work_library(),
+                            );
                             diagnostics.push(Diagnostic::hint(
                                 &library_name.item,
                                 "Library clause not necessary for current working library",
@@ -0,0 +1,342 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this file,
+// You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) 2023, Olof Kraigher olof.kraigher@gmail.com
+
+//! Find the processes and concurrent assignments of an architecture that drive each signal
+//! A signal of an unresolved type may only have a single driver. Drivers of different
+//! elements or statically known indexes of a signal do not conflict.
+
+use super::analyze::*;
+use super::named_entity::*;
+use super::static_expression::static_integer;
+use crate::ast::*;
+use crate::data::*;
+
+/// A part of a signal selected by the target of an assignment
+#[derive(Clone, PartialEq, Eq, Debug)]
+enum Part {
+    Element(Designator),
+    /// The lowest and highest index
+    Indexes(i64, i64),
+    /// The part of the object denoted by an alias
+    Alias(EntityId),
+    /// An index or range which is not locally static
+    Unknown,
+}
+
+impl Part {
+    /// Parts which are not known to overlap are assumed not to
+    fn overlaps(&self, other: &Part) -> bool {
+        match (self, other) {
+            (Part::Element(left), Part::Element(right)) => left == right,
+            (Part::Indexes(left_low, left_high), Part::Indexes(right_low, right_high)) => {
+                left_low <= right_high && right_low <= left_high
+            }
+            (Part::Alias(left), Part::Alias(right)) => left == right,
+            _ => false,
+        }
+    }
+}
+
+/// A signal driven by a statement
+struct Driven {
+    signal: EntityId,
+    path: Vec<Part>,
+    pos: SrcPos,
+}
+
+impl Driven {
+    fn overlaps(&self, other: &Driven) -> bool {
+        self.signal == other.signal
+            && self
+                .path
+                .iter()
+                .zip(other.path.iter())
+                .all(|(left, right)| left.overlaps(right))
+    }
+}
+
+/// The signals driven by a process or concurrent assignment
+struct Driver {
+    /// The if and case generate statements enclosing the driver and the index of the alternative
+    alternatives: Vec<(usize, usize)>,
+    driven: Vec<Driven>,
+}
+
+impl Driver {
+    /// Drivers within different alternatives of a generate statement are never both elaborated
+    fn is_exclusive(&self, other: &Driver) -> bool {
+        self.alternatives.iter().any(|(generate, alternative)| {
+            other
+                .alternatives
+                .iter()
+                .any(|(other_generate, other_alternative)| {
+                    generate == other_generate && alternative != other_alternative
+                })
+        })
+    }
+}
+
+impl<'a> AnalyzeContext<'a> {
+    /// Report signals of unresolved types which are driven by more than one
+    /// process or concurrent assignment of the architecture
+    pub(super) fn check_multiple_drivers(
+        &self,
+        statements: &[LabeledConcurrentStatement],
+        diagnostics: &mut dyn DiagnosticHandler,
+    ) {
+        let mut finder = DriverFinder {
+            context: self,
+            drivers: Vec::new(),
+            alternatives: Vec::new(),
+            num_generates: 0,
+        };
+        finder.concurrent_statements(statements);
+        let drivers = finder.drivers;
+
+        for (idx, driver) in drivers.iter().enumerate() {
+            for driven in driver.driven.iter() {
+                let previous = drivers[..idx]
+                    .iter()
+                    .filter(|previous| !previous.is_exclusive(driver))
+                    .flat_map(|previous| previous.driven.iter())
+                    .find(|previous| previous.overlaps(driven));
+
+                if let Some(previous) = previous {
+                    diagnostics.push(
+                        Diagnostic::error(
+                            &driven.pos,
+                            format!(
+                                "Signal '{}' of an unresolved type has multiple drivers",
+                                self.arena.get(driven.signal).designator()
+                            ),
+                        )
+                        .related(&previous.pos, "Also driven here"),
+                    );
+                }
+            }
+        }
+    }
+
+    /// The signal and the part of it denoted by the name of a target
+    fn driven_part(&self, name: &Name) -> Option<(EntityId, Vec<Part>)> {
+        match name {
+            Name::Designator(designator) => self.driven_object(designator.reference?),
+            Name::Selected(prefix, suffix) => {
+                if let Some(driven) = suffix.item.reference.and_then(|id| self.driven_object(id)) {
+                    // A signal selected from a package
+                    return Some(driven);
+                }
+                let (signal, mut path) = self.driven_part(&prefix.item)?;
+                path.push(Part::Element(suffix.item.item.clone()));
+                Some((signal, path))
+            }
+            Name::CallOrIndexed(call) => {
+                let (signal, mut path) = self.driven_part(&call.name.item)?;
+                let index = match call.parameters.as_slice() {
+                    [AssociationElement {
+                        formal: None,
+                        actual:
+                            WithPos {
+                                item: ActualPart::Expression(expr),
+                                ..
+                            },
+                    }] => static_integer(expr),
+                    _ => None,
+                };
+                path.push(index.map_or(Part::Unknown, |index| Part::Indexes(index, index)));
+                Some((signal, path))
+            }
+            Name::Slice(prefix, drange) => {
+                let (signal, mut path) = self.driven_part(&prefix.item)?;
+                path.push(static_indexes(drange).unwrap_or(Part::Unknown));
+                Some((signal, path))
+            }
+            _ => None,
+        }
+    }
+
+    /// A signal of an unresolved type or an alias of such a signal
+    fn driven_object(&self, id: EntityId) -> Option<(EntityId, Vec<Part>)> {
+        let ent = self.arena.get(id);
+        match ent.kind() {
+            AnyEntKind::Object(object)
+                if object.class == ObjectClass::Signal && !object.subtype.is_resolved() =>
+            {
+                Some((id, Vec::new()))
+            }
+            AnyEntKind::ObjectAlias { base_object, .. } => {
+                let (signal, _) = self.driven_object(base_object.id())?;
+                Some((signal, vec![Part::Alias(id)]))
+            }
+            _ => None,
+        }
+    }
+}
+
+struct DriverFinder<'c, 'a> {
+    context: &'c AnalyzeContext<'a>,
+    drivers: Vec<Driver>,
+    alternatives: Vec<(usize, usize)>,
+    num_generates: usize,
+}
+
+impl<'c, 'a> DriverFinder<'c, 'a> {
+    fn concurrent_statements(&mut self, statements: &[LabeledConcurrentStatement]) {
+        for statement in statements.iter() {
+            match statement.statement.item {
+                ConcurrentStatement::Process(ref process) => {
+                    let mut targets = FindDrivenSignals {
+                        context: self.context,
+                        driven: Vec::new(),
+                    };
+                    targets.sequential_statements(&process.statements);
+                    self.add_driver(targets.driven);
+                }
+                ConcurrentStatement::Assignment(ref assign) => {
+                    let mut targets = FindDrivenSignals {
+                        context: self.context,
+                        driven: Vec::new(),
+                    };
+                    targets.target(&assign.target.item, &assign.target.pos);
+                    self.add_driver(targets.driven);
+                }
+                ConcurrentStatement::Block(ref block) => {
+                    self.concurrent_statements(&block.statements);
+                }
+                ConcurrentStatement::ForGenerate(ref gen) => {
+                    self.concurrent_statements(&gen.body.statements);
+                }
+                ConcurrentStatement::IfGenerate(ref gen) => {
+                    let bodies = gen
+                        .conds
+                        .conditionals
+                        .iter()
+                        .map(|cond| &cond.item)
+                        .chain(gen.conds.else_item.iter());
+                    self.generate_alternatives(bodies);
+                }
+                ConcurrentStatement::CaseGenerate(ref gen) => {
+                    let bodies = gen
+                        .sels
+                        .alternatives
+                        .iter()
+                        .map(|alternative| &alternative.item);
+                    self.generate_alternatives(bodies);
+                }
+                ConcurrentStatement::ProcedureCall(_)
+                | ConcurrentStatement::Assert(_)
+                | ConcurrentStatement::Instance(_) => {}
+            }
+        }
+    }
+
+    fn add_driver(&mut self, driven: Vec<Driven>) {
+        if !driven.is_empty() {
+            self.drivers.push(Driver {
+                alternatives: self.alternatives.clone(),
+                driven,
+            });
+        }
+    }
+
+    fn generate_alternatives<'b>(&mut self, bodies: impl Iterator<Item = &'b GenerateBody>) {
+        let generate = self.num_generates;
+        self.num_generates += 1;
+        for (alternative, body) in bodies.enumerate() {
+            self.alternatives.push((generate, alternative));
+            self.concurrent_statements(&body.statements);
+            self.alternatives.pop();
+        }
+    }
+}
+
+fn static_indexes(drange: &DiscreteRange) -> Option<Part> {
+    if let DiscreteRange::Range(crate::ast::Range::Range(constraint)) = drange {
+        let left = static_integer(&constraint.left_expr.item)?;
+        let right = static_integer(&constraint.right_expr.item)?;
+        let (low, high) = match constraint.direction {
+            Direction::Ascending => (left, right),
+            Direction::Descending => (right, left),
+        };
+        Some(Part::Indexes(low, high))
+    } else {
+        None
+    }
+}
+
+/// Find the signals driven by the targets of signal assignments
+struct FindDrivenSignals<'c, 'a> {
+    context: &'c AnalyzeContext<'a>,
+    driven: Vec<Driven>,
+}
+
+impl<'c, 'a> FindDrivenSignals<'c, 'a> {
+    fn sequential_statements(&mut self, statements: &[LabeledSequentialStatement]) {
+        for statement in statements.iter() {
+            match statement.statement.item {
+                SequentialStatement::SignalAssignment(ref assign) => {
+                    self.target(&assign.target.item, &assign.target.pos);
+                }
+                SequentialStatement::SignalForceAssignment(ref assign) => {
+                    self.target(&assign.target.item, &assign.target.pos);
+                }
+                SequentialStatement::SignalReleaseAssignment(ref assign) => {
+                    self.target(&assign.target.item, &assign.target.pos);
+                }
+                SequentialStatement::If(ref ifstmt) => {
+                    for cond in ifstmt.conds.conditionals.iter() {
+                        self.sequential_statements(&cond.item);
+                    }
+                    if let Some(ref else_item) = ifstmt.conds.else_item {
+                        self.sequential_statements(else_item);
+                    }
+                }
+                SequentialStatement::Case(ref case_stmt) => {
+                    for alternative in case_stmt.alternatives.iter() {
+                        self.sequential_statements(&alternative.item);
+                    }
+                }
+                SequentialStatement::Loop(ref loop_stmt) => {
+                    self.sequential_statements(&loop_stmt.statements);
+                }
+                SequentialStatement::Wait(_)
+                | SequentialStatement::Assert(_)
+                | SequentialStatement::Report(_)
+                | SequentialStatement::VariableAssignment(_)
+                | SequentialStatement::ProcedureCall(_)
+                | SequentialStatement::Next(_)
+                | SequentialStatement::Exit(_)
+                | SequentialStatement::Return(_)
+                | SequentialStatement::Null => {}
+            }
+        }
+    }
+
+    fn target(&mut self, target: &Target, pos: &SrcPos) {
+        match target {
+            Target::Name(name) => {
+                if let Some((signal, path)) = self.context.driven_part(name) {
+                    self.driven.push(Driven {
+                        signal,
+                        path,
+                        pos: pos.clone(),
+                    });
+                }
+            }
+            Target::Aggregate(assocs) => {
+                for assoc in assocs.iter() {
+                    let expr = match assoc {
+                        ElementAssociation::Positional(expr)
+                        | ElementAssociation::Named(_, expr) => expr,
+                    };
+                    if let Expression::Name(ref name) = expr.item {
+                        self.target(&Target::Name(name.as_ref().clone()), &expr.pos);
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,1030 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this file,
+// You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) 2023, Olof Kraigher olof.kraigher@gmail.com
+
+//! Elaborate the hierarchy of instances below a top level entity
+//! The generics of each instance are evaluated from the actuals of its instantiation
+//! such that generate statements can be expanded with the values of that instance
+//! The signals of each instance are nets that are connected to the ports of its children
+
+use super::constant_value::{ConstantValue, ConstantValues};
+use super::root::DesignRoot;
+use crate::ast::*;
+use crate::data::*;
+use fnv::FnvHashMap;
+use std::ops::Deref;
+
+/// Instances nested deeper than this are assumed to be an unbounded recursion
+const MAX_DEPTH: usize = 64;
+
+/// For generate statements with more iterations than this are not expanded
+const MAX_ITERATIONS: i64 = 1 << 12;
+
+/// Nets are numbered from 2 since 0 and 1 are reserved for constants in the netlist format of yosys
+const FIRST_NET: usize = 2;
+
+/// An instance of an entity within the elaborated design
+#[derive(Debug, Clone)]
+pub struct ElaboratedInstance {
+    /// The path from the parent instance through blocks and generate statements
+    /// such as `gen(1).inst`, the name of the entity for the top level
+    pub name: String,
+    pub library_name: Symbol,
+    pub entity_name: Symbol,
+    /// None when the entity has no architecture
+    pub architecture_name: Option<Symbol>,
+    /// The generics whose value is known in declaration order
+    pub generics: Vec<(Symbol, ConstantValue)>,
+    /// The instantiation statement or the entity declaration of the top level
+    pub pos: SrcPos,
+    pub ports: Vec<ElaboratedPort>,
+    /// The signals declared in the architecture, blocks and generate statements
+    pub signals: Vec<ElaboratedSignal>,
+    pub children: Vec<ElaboratedInstance>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ElaboratedPort {
+    pub name: Symbol,
+    pub mode: Mode,
+    /// The nets of the actuals in the port map, one for each association of a part of the port
+    /// A port which is not associated with a signal is connected to a net of its own
+    pub nets: Vec<usize>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ElaboratedSignal {
+    /// The path through blocks and generate statements such as `gen(1).sig`
+    pub name: String,
+    pub net: usize,
+}
+
+pub struct Elaboration {
+    pub top: ElaboratedInstance,
+    /// Generate statements which could not be evaluated and instances which could not be bound
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+impl ElaboratedInstance {
+    fn write_text(&self, depth: usize, text: &mut String) {
+        let generics: Vec<_> = self
+            .generics
+            .iter()
+            .map(|(name, value)| format!("{name} => {value}"))
+            .collect();
+        text.push_str(&format!(
+            "{}{} : {}.{}",
+            "  ".repeat(depth),
+            self.name,
+            self.library_name,
+            self.entity_name
+        ));
+        if let Some(ref architecture_name) = self.architecture_name {
+            text.push_str(&format!("({architecture_name})"));
+        }
+        if !generics.is_empty() {
+            text.push_str(&format!(" generic map ({})", generics.join(", ")));
+        }
+        text.push_str(&format!(
+            " at {}:{}\n",
+            self.pos.file_name().to_string_lossy(),
+            self.pos.start().line + 1
+        ));
+        for child in self.children.iter() {
+            child.write_text(depth + 1, text);
+        }
+    }
+
+    fn to_json(&self) -> String {
+        let generics: Vec<_> = self
+            .generics
+            .iter()
+            .map(|(name, value)| {
+                format!(
+                    "{}: {}",
+                    quoted(&name.name_utf8()),
+                    quoted(&value.to_string())
+                )
+            })
+            .collect();
+        let children: Vec<_> = self.children.iter().map(|child| child.to_json()).collect();
+        format!(
+            "{{\"name\": {}, \"library\": {}, \"entity\": {}, \"architecture\": {}, \
+             \"generics\": {{{}}}, \"file\": {}, \"line\": {}, \"children\": [{}]}}",
+            quoted(&self.name),
+            quoted(&self.library_name.name_utf8()),
+            quoted(&self.entity_name.name_utf8()),
+            self.architecture_name
+                .as_ref()
+                .map(|name| quoted(&name.name_utf8()))
+                .unwrap_or_else(|| "null".to_owned()),
+            generics.join(", "),
+            quoted(&self.pos.file_name().to_string_lossy()),
+            self.pos.start().line + 1,
+            children.join(", ")
+        )
+    }
+}
+
+impl ElaboratedInstance {
+    /// Add a module for this instance and a module for each instance below it
+    fn write_modules(&self, module_name: &str, is_top: bool, modules: &mut Vec<String>) {
+        let ports: Vec<_> = self
+            .ports
+            .iter()
+            .map(|port| {
+                format!(
+                    "{}: {{\"direction\": {}, \"bits\": {}}}",
+                    quoted(&port.name.name_utf8()),
+                    quoted(direction(port.mode)),
+                    bits(&port.nets)
+                )
+            })
+            .collect();
+
+        let cells: Vec<_> = self
+            .children
+            .iter()
+            .map(|child| {
+                let parameters: Vec<_> = child
+                    .generics
+                    .iter()
+                    .map(|(name, value)| {
+                        format!(
+                            "{}: {}",
+                            quoted(&name.name_utf8()),
+                            quoted(&value.to_string())
+                        )
+                    })
+                    .collect();
+                let port_directions: Vec<_> = child
+                    .ports
+                    .iter()
+                    .map(|port| {
+                        format!(
+                            "{}: {}",
+                            quoted(&port.name.name_utf8()),
+                            quoted(direction(port.mode))
+                        )
+                    })
+                    .collect();
+                let connections: Vec<_> = child
+                    .ports
+                    .iter()
+                    .map(|port| format!("{}: {}", quoted(&port.name.name_utf8()), bits(&port.nets)))
+                    .collect();
+                format!(
+                    "{}: {{\"type\": {}, \"parameters\": {{{}}}, \"port_directions\": {{{}}}, \
+                     \"connections\": {{{}}}}}",
+                    quoted(&child.name),
+                    quoted(&format!("{module_name}.{}", child.name)),
+                    parameters.join(", "),
+                    port_directions.join(", "),
+                    connections.join(", ")
+                )
+            })
+            .collect();
+
+        let netnames: Vec<_> = self
+            .ports
+            .iter()
+            .map(|port| (port.name.name_utf8(), bits(&port.nets)))
+            .chain(
+                self.signals
+                    .iter()
+                    .map(|signal| (signal.name.clone(), bits(&[signal.net]))),
+            )
+            .map(|(name, bits)| format!("{}: {{\"bits\": {bits}}}", quoted(&name)))
+            .collect();
+
+        modules.push(format!(
+            "{}: {{\"attributes\": {{{}}}, \"ports\": {{{}}}, \"cells\": {{{}}}, \
+             \"netnames\": {{{}}}}}",
+            quoted(module_name),
+            if is_top { "\"top\": 1" } else { "" },
+            ports.join(", "),
+            cells.join(", "),
+            netnames.join(", ")
+        ));
+
+        for child in self.children.iter() {
+            child.write_modules(&format!("{module_name}.{}", child.name), false, modules);
+        }
+    }
+}
+
+impl Elaboration {
+    /// Export the elaborated design as a netlist in the JSON format of yosys with a module
+    /// for each instance such that it can be drawn by tools such as netlistsvg
+    pub fn to_netlist_json(&self) -> String {
+        let mut modules = Vec::new();
+        self.top.write_modules(&self.top.name, true, &mut modules);
+        format!(
+            "{{\"creator\": \"vhdl_lang\", \"modules\": {{{}}}}}",
+            modules.join(", ")
+        )
+    }
+
+    /// Export the instance hierarchy as one indented line per instance
+    pub fn to_text(&self) -> String {
+        let mut text = String::new();
+        self.top.write_text(0, &mut text);
+        text
+    }
+
+    /// Export the instance hierarchy as a JSON object of the top level with nested children
+    pub fn to_json(&self) -> String {
+        self.top.to_json()
+    }
+}
+
+impl DesignRoot {
+    /// Elaborate the design below a top level entity written as `lib.ent(arch)`
+    /// where the library and the architecture are optional
+    pub fn elaborate(&self, top: &str) -> Result<Elaboration, String> {
+        let (library_name, entity_name, architecture_name) = self.find_top(top)?;
+        let overrides = self
+            .generic_overrides
+            .get(&library_name.name_utf8(), &entity_name.name_utf8());
+
+        let mut elaborator = Elaborator {
+            root: self,
+            diagnostics: Vec::new(),
+            depth: 0,
+            next_net: FIRST_NET,
+        };
+        let top = elaborator
+            .instance(
+                Instantiation {
+                    name: entity_name.name_utf8(),
+                    library_name: &library_name,
+                    entity_name: &entity_name,
+                    architecture_name: architecture_name.as_ref(),
+                    pos: None,
+                },
+                |entity, values, actuals| {
+                    for generic in entity.generic_clause.iter().flatten() {
+                        if let InterfaceDeclaration::Object(object) = generic {
+                            let name = object.ident.tree.item.clone();
+                            if let Some(value) = overrides.and_then(|overrides| {
+                                overrides.get(&name.name_utf8().to_lowercase())
+                            }) {
+                                actuals
+                                    .generics
+                                    .insert(name, values.override_value(self, object, value));
+                            }
+                        }
+                    }
+                },
+            )
+            .ok_or_else(|| format!("Could not elaborate '{top}'"))?;
+
+        Ok(Elaboration {
+            top,
+            diagnostics: elaborator.diagnostics,
+        })
+    }
+
+    /// The library, the entity and the architecture of the top level
+    /// An entity without a library must only exist in a single library
+    fn find_top(&self, top: &str) -> Result<(Symbol, Symbol, Option<Symbol>), String> {
+        let (name, architecture_name) = match top.trim().strip_suffix(')') {
+            Some(name) => {
+                let (name, architecture_name) = name
+                    .split_once('(')
+                    .ok_or_else(|| format!("Invalid top level '{top}'"))?;
+                (name, Some(self.symbol_utf8(architecture_name.trim())))
+            }
+            None => (top.trim(), None),
+        };
+
+        let (library_name, entity_name) = match name.split_once('.') {
+            Some((library_name, entity_name)) => {
+                let library_name = self.symbol_utf8(library_name);
+                let entity_name = self.symbol_utf8(entity_name);
+                if !self.is_entity(&library_name, &entity_name) {
+                    return Err(format!(
+                        "No entity '{entity_name}' in library '{library_name}'"
+                    ));
+                }
+                (library_name, entity_name)
+            }
+            None => {
+                let entity_name = self.symbol_utf8(name);
+                let mut library_names: Vec<_> = self
+                    .library_names()
+                    .filter(|library_name| self.is_entity(library_name, &entity_name))
+                    .cloned()
+                    .collect();
+                library_names.sort_by_key(|library_name| library_name.name_utf8());
+                match library_names.as_slice() {
+                    [] => return Err(format!("No entity '{entity_name}'")),
+                    [library_name] => (library_name.clone(), entity_name),
+                    _ => {
+                        let library_names: Vec<_> = library_names
+                            .iter()
+                            .map(|library_name| library_name.to_string())
+                            .collect();
+                        return Err(format!(
+                            "Entity '{entity_name}' exists in libraries {}",
+                            library_names.join(", ")
+                        ));
+                    }
+                }
+            }
+        };
+
+        Ok((library_name, entity_name, architecture_name))
+    }
+
+    fn is_entity(&self, library_name: &Symbol, entity_name: &Symbol) -> bool {
+        self.get_design_entity(library_name, entity_name).is_some()
+    }
+}
+
+/// The entity and architecture bound to an instance
+struct Instantiation<'n> {
+    name: String,
+    library_name: &'n Symbol,
+    entity_name: &'n Symbol,
+    architecture_name: Option<&'n Symbol>,
+    pos: Option<SrcPos>,
+}
+
+/// The actuals of an instance evaluated in the instantiating design unit
+struct Actuals {
+    generics: FnvHashMap<Symbol, ConstantValue>,
+    /// The nets of the actuals of each port by the name of the port
+    ports: FnvHashMap<Symbol, Vec<usize>>,
+    next_net: usize,
+}
+
+impl Actuals {
+    fn new_net(&mut self) -> usize {
+        self.next_net += 1;
+        self.next_net - 1
+    }
+}
+
+/// The values of the constants and the nets of the signals visible in a declarative region
+#[derive(Clone)]
+struct Environment {
+    values: ConstantValues,
+    nets: FnvHashMap<EntityId, usize>,
+}
+
+impl Environment {
+    /// The net of the signal denoted by a name or of the signal of which it denotes a part
+    fn signal_net(&self, name: &Name) -> Option<usize> {
+        match name {
+            Name::Designator(designator) => self.nets.get(&designator.reference?).copied(),
+            Name::Selected(prefix, suffix) => suffix
+                .item
+                .reference
+                .and_then(|id| self.nets.get(&id).copied())
+                .or_else(|| self.signal_net(&prefix.item)),
+            Name::Slice(prefix, _) => self.signal_net(&prefix.item),
+            Name::CallOrIndexed(call) => self.signal_net(&call.name.item),
+            _ => None,
+        }
+    }
+}
+
+/// The instances and signals of an architecture
+#[derive(Default)]
+struct Contents {
+    children: Vec<ElaboratedInstance>,
+    signals: Vec<ElaboratedSignal>,
+}
+
+struct Elaborator<'r> {
+    root: &'r DesignRoot,
+    diagnostics: Vec<Diagnostic>,
+    depth: usize,
+    next_net: usize,
+}
+
+impl<'r> Elaborator<'r> {
+    /// Elaborate an instance of an entity with the actuals given by the closure
+    fn instance(
+        &mut self,
+        inst: Instantiation,
+        instance_actuals: impl FnOnce(&EntityDeclaration, &ConstantValues, &mut Actuals),
+    ) -> Option<ElaboratedInstance> {
+        let units = self.root.get_library_units(inst.library_name)?;
+        let unit = units
+            .get(&UnitKey::Primary(inst.entity_name.clone()))?
+            .unit
+            .expect_analyzed();
+        let entity = if let AnyDesignUnit::Primary(AnyPrimaryUnit::Entity(entity)) = unit.deref() {
+            entity
+        } else {
+            return None;
+        };
+        let pos = inst.pos.unwrap_or_else(|| entity.ident.tree.pos.clone());
+
+        if self.depth >= MAX_DEPTH {
+            self.diagnostics.push(Diagnostic::warning(
+                &pos,
+                format!("Instances are nested deeper than {MAX_DEPTH} levels"),
+            ));
+            return None;
+        }
+
+        let mut values = self.root.constant_values.clone();
+        let mut actuals = Actuals {
+            generics: FnvHashMap::default(),
+            ports: FnvHashMap::default(),
+            next_net: self.next_net,
+        };
+        instance_actuals(entity, &values, &mut actuals);
+        self.next_net = actuals.next_net;
+        values.entity_instance(self.root, entity, &actuals.generics);
+
+        let generics = entity
+            .generic_clause
+            .iter()
+            .flatten()
+            .filter_map(|generic| match generic {
+                InterfaceDeclaration::Object(object) => {
+                    let value = values.computed_value(object.ident.decl?)?;
+                    Some((object.ident.tree.item.clone(), value.clone()))
+                }
+                _ => None,
+            })
+            .collect();
+
+        let mut env = Environment {
+            values,
+            nets: FnvHashMap::default(),
+        };
+        let mut ports = Vec::new();
+        for port in entity.port_clause.iter().flatten() {
+            if let InterfaceDeclaration::Object(object) = port {
+                let name = object.ident.tree.item.clone();
+                let nets = match actuals.ports.remove(&name) {
+                    Some(nets) => nets,
+                    None => vec![self.new_net()],
+                };
+                if let Some(id) = object.ident.decl {
+                    env.nets.insert(id, nets[0]);
+                }
+                ports.push(ElaboratedPort {
+                    name,
+                    mode: object.mode,
+                    nets,
+                });
+            }
+        }
+
+        let mut contents = Contents::default();
+        self.declare_signals(&mut env, &entity.decl, "", &mut contents);
+
+        let architecture_name = match inst.architecture_name {
+            Some(architecture_name) => Some(architecture_name.clone()),
+            None => self.default_architecture(inst.library_name, inst.entity_name),
+        };
+
+        if let Some(ref architecture_name) = architecture_name {
+            let unit = units.get(&UnitKey::Secondary(
+                inst.entity_name.clone(),
+                architecture_name.clone(),
+            ));
+            if let Some(unit) = unit {
+                if let AnyDesignUnit::Secondary(AnySecondaryUnit::Architecture(ref body)) =
+                    *unit.unit.expect_analyzed()
+                {
+                    env.values.local_declarations(self.root, &body.decl);
+                    self.declare_signals(&mut env, &body.decl, "", &mut contents);
+                    self.depth += 1;
+                    self.statements(
+                        &env,
+                        inst.library_name,
+                        &[],
+                        &body.decl,
+                        &body.statements,
+                        "",
+                        &mut contents,
+                    );
+                    self.depth -= 1;
+                }
+            } else {
+                self.diagnostics.push(Diagnostic::warning(
+                    &pos,
+                    format!(
+                        "No architecture '{architecture_name}' of entity '{}.{}'",
+                        inst.library_name, inst.entity_name
+                    ),
+                ));
+            }
+        }
+
+        Some(ElaboratedInstance {
+            name: inst.name,
+            library_name: inst.library_name.clone(),
+            entity_name: inst.entity_name.clone(),
+            architecture_name,
+            generics,
+            pos,
+            ports,
+            signals: contents.signals,
+            children: contents.children,
+        })
+    }
+
+    fn new_net(&mut self) -> usize {
+        self.next_net += 1;
+        self.next_net - 1
+    }
+
+    /// Connect the signals of a declarative part to new nets
+    fn declare_signals(
+        &mut self,
+        env: &mut Environment,
+        decl: &[Declaration],
+        prefix: &str,
+        contents: &mut Contents,
+    ) {
+        for decl in decl.iter() {
+            if let Declaration::Object(object) = decl {
+                if object.class == ObjectClass::Signal {
+                    let net = self.new_net();
+                    if let Some(id) = object.ident.decl {
+                        env.nets.insert(id, net);
+                    }
+                    contents.signals.push(ElaboratedSignal {
+                        name: format!("{prefix}{}", object.ident.tree.item),
+                        net,
+                    });
+                }
+            }
+        }
+    }
+
+    /// The architecture which is last in source order as if the files were analyzed in order
+    fn default_architecture(&self, library_name: &Symbol, entity_name: &Symbol) -> Option<Symbol> {
+        self.root
+            .architectures_of(library_name, entity_name)
+            .into_iter()
+            .max_by(|(_, left), (_, right)| {
+                (left.file_name(), left.start()).cmp(&(right.file_name(), right.start()))
+            })
+            .map(|(name, _)| name)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn statements(
+        &mut self,
+        env: &Environment,
+        library_name: &Symbol,
+        specs: &[&ConfigurationSpecification],
+        decl: &[Declaration],
+        statements: &[LabeledConcurrentStatement],
+        prefix: &str,
+        contents: &mut Contents,
+    ) {
+        let mut specs = specs.to_vec();
+        specs.extend(decl.iter().filter_map(|decl| match decl {
+            Declaration::Configuration(spec) => Some(spec),
+            _ => None,
+        }));
+
+        for statement in statements.iter() {
+            let label = statement
+                .label
+                .tree
+                .as_ref()
+                .map(|label| label.item.clone());
+            let path = format!(
+                "{prefix}{}",
+                label
+                    .as_ref()
+                    .map(|label| label.name_utf8())
+                    .unwrap_or_default()
+            );
+            let pos = &statement.statement.pos;
+
+            match statement.statement.item {
+                ConcurrentStatement::Block(ref block) => {
+                    let prefix = format!("{path}.");
+                    let mut env = env.clone();
+                    env.values.local_declarations(self.root, &block.decl);
+                    self.declare_signals(&mut env, &block.decl, &prefix, contents);
+                    self.statements(
+                        &env,
+                        library_name,
+                        &specs,
+                        &block.decl,
+                        &block.statements,
+                        &prefix,
+                        contents,
+                    );
+                }
+                ConcurrentStatement::ForGenerate(ref gen) => {
+                    let (left, ascending, right) =
+                        match env.values.evaluate_range(self.root, &gen.discrete_range) {
+                            Some(bounds) => bounds,
+                            None => {
+                                self.diagnostics.push(Diagnostic::warning(
+                                    pos,
+                                    "Could not evaluate the range of the generate statement",
+                                ));
+                                continue;
+                            }
+                        };
+                    let (low, high) = if ascending {
+                        (left, right)
+                    } else {
+                        (right, left)
+                    };
+                    if high.saturating_sub(low) >= MAX_ITERATIONS {
+                        self.diagnostics.push(Diagnostic::warning(
+                            pos,
+                            format!("Generate statements with more than {MAX_ITERATIONS} iterations are not expanded"),
+                        ));
+                        continue;
+                    }
+
+                    let indexes: Vec<i64> = if ascending {
+                        (low..=high).collect()
+                    } else {
+                        (low..=high).rev().collect()
+                    };
+                    for index in indexes {
+                        let mut env = env.clone();
+                        if let Some(id) = gen.index_name.decl {
+                            env.values.set_value(id, ConstantValue::Integer(index));
+                        }
+                        self.generate_body(
+                            &env,
+                            library_name,
+                            &specs,
+                            &gen.body,
+                            &format!("{path}({index})."),
+                            contents,
+                        );
+                    }
+                }
+                ConcurrentStatement::IfGenerate(ref gen) => {
+                    let mut selected = gen.conds.else_item.as_ref();
+                    for conditional in gen.conds.conditionals.iter() {
+                        match env
+                            .values
+                            .evaluate(self.root, &conditional.condition.item)
+                            .and_then(|value| value.boolean())
+                        {
+                            Some(true) => {
+                                selected = Some(&conditional.item);
+                                break;
+                            }
+                            Some(false) => {}
+                            None => {
+                                self.diagnostics.push(Diagnostic::warning(
+                                    &conditional.condition.pos,
+                                    "Could not evaluate the condition of the generate statement",
+                                ));
+                                selected = None;
+                                break;
+                            }
+                        }
+                    }
+                    if let Some(body) = selected {
+                        self.generate_body(
+                            env,
+                            library_name,
+                            &specs,
+                            body,
+                            &format!("{path}."),
+                            contents,
+                        );
+                    }
+                }
+                ConcurrentStatement::CaseGenerate(ref gen) => {
+                    let value = match env.values.evaluate(self.root, &gen.sels.expression.item) {
+                        Some(value) => value,
+                        None => {
+                            self.diagnostics.push(Diagnostic::warning(
+                                &gen.sels.expression.pos,
+                                "Could not evaluate the expression of the generate statement",
+                            ));
+                            continue;
+                        }
+                    };
+                    let mut selected = None;
+                    for alternative in gen.sels.alternatives.iter() {
+                        match self.matches_choices(&env.values, &alternative.choices, &value) {
+                            Some(true) => {
+                                selected = Some(&alternative.item);
+                                break;
+                            }
+                            Some(false) => {}
+                            None => {
+                                self.diagnostics.push(Diagnostic::warning(
+                                    pos,
+                                    "Could not evaluate the choices of the generate statement",
+                                ));
+                                break;
+                            }
+                        }
+                    }
+                    if let Some(body) = selected {
+                        self.generate_body(
+                            env,
+                            library_name,
+                            &specs,
+                            body,
+                            &format!("{path}."),
+                            contents,
+                        );
+                    }
+                }
+                ConcurrentStatement::Instance(ref instance) => {
+                    let binding = match instance.unit {
+                        InstantiatedUnit::Entity(ref name, ref architecture_name) => {
+                            match self.entity_of(&name.item) {
+                                Some((library_name, entity_name)) => Binding::Entity(
+                                    library_name,
+                                    entity_name,
+                                    architecture_name
+                                        .as_ref()
+                                        .map(|name| name.item.item.clone()),
+                                ),
+                                None => continue,
+                            }
+                        }
+                        InstantiatedUnit::Component(ref name) => {
+                            self.component_binding(&specs, label.as_ref(), library_name, name)
+                        }
+                        InstantiatedUnit::Configuration(_) => Binding::Unbound(
+                            "Instances of configurations are not elaborated".to_owned(),
+                        ),
+                    };
+
+                    match binding {
+                        Binding::Entity(library_name, entity_name, architecture_name) => {
+                            let child = self.instance(
+                                Instantiation {
+                                    name: path,
+                                    library_name: &library_name,
+                                    entity_name: &entity_name,
+                                    architecture_name: architecture_name.as_ref(),
+                                    pos: Some(pos.clone()),
+                                },
+                                |entity, _, actuals| {
+                                    env.values.generic_map_actuals(
+                                        self.root,
+                                        entity,
+                                        &instance.generic_map,
+                                        &mut actuals.generics,
+                                    );
+                                    port_map_actuals(env, entity, &instance.port_map, actuals);
+                                },
+                            );
+                            contents.children.extend(child);
+                        }
+                        Binding::Open => {}
+                        Binding::Unbound(message) => {
+                            self.diagnostics.push(Diagnostic::warning(pos, message));
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn generate_body(
+        &mut self,
+        env: &Environment,
+        library_name: &Symbol,
+        specs: &[&ConfigurationSpecification],
+        body: &GenerateBody,
+        prefix: &str,
+        contents: &mut Contents,
+    ) {
+        let decl = body.decl.as_deref().unwrap_or_default();
+        let mut env = env.clone();
+        env.values.local_declarations(self.root, decl);
+        self.declare_signals(&mut env, decl, prefix, contents);
+        self.statements(
+            &env,
+            library_name,
+            specs,
+            decl,
+            &body.statements,
+            prefix,
+            contents,
+        );
+    }
+
+    /// Some(true) if a choice matches the value and None if a choice could not be evaluated
+    fn matches_choices(
+        &self,
+        values: &ConstantValues,
+        choices: &[WithPos<Choice>],
+        value: &ConstantValue,
+    ) -> Option<bool> {
+        for choice in choices.iter() {
+            let matches = match choice.item {
+                Choice::Expression(ref expr) => values.evaluate(self.root, expr)? == *value,
+                Choice::DiscreteRange(ref drange) => {
+                    let (left, ascending, right) = values.evaluate_range(self.root, drange)?;
+                    let value = value.integer()?;
+                    if ascending {
+                        left <= value && value <= right
+                    } else {
+                        right <= value && value <= left
+                    }
+                }
+                Choice::Others => true,
+            };
+            if matches {
+                return Some(true);
+            }
+        }
+        Some(false)
+    }
+
+    /// The library and the name of the entity denoted by a name
+    fn entity_of(&self, name: &SelectedName) -> Option<(Symbol, Symbol)> {
+        let ent = self.root.get_ent(selected_reference(name)?);
+        match ent.designator() {
+            Designator::Identifier(entity_name) => {
+                Some((ent.library_name()?.clone(), entity_name.clone()))
+            }
+            _ => None,
+        }
+    }
+
+    /// The entity denoted by an unresolved name such as `work.ent` since the names of
+    /// configuration specifications within an architecture are not resolved by the analysis
+    fn entity_named(&self, library_name: &Symbol, name: &SelectedName) -> Option<(Symbol, Symbol)> {
+        let (prefix, entity_name) = match name {
+            SelectedName::Selected(prefix, designator) => match designator.item.item {
+                Designator::Identifier(ref entity_name) => (&prefix.item, entity_name),
+                _ => return None,
+            },
+            SelectedName::Designator(_) => return None,
+        };
+        let library_name = match prefix {
+            SelectedName::Designator(designator) => match designator.item {
+                Designator::Identifier(ref name) if name.name_utf8() == "work" => {
+                    library_name.clone()
+                }
+                Designator::Identifier(ref name) => name.clone(),
+                _ => return None,
+            },
+            SelectedName::Selected(..) => return None,
+        };
+        if self.root.is_entity(&library_name, entity_name) {
+            Some((library_name, entity_name.clone()))
+        } else {
+            None
+        }
+    }
+
+    /// The entity bound to a component instance by a configuration specification or by default
+    /// to the entity of the same name in the library of the instantiating design unit
+    fn component_binding(
+        &self,
+        specs: &[&ConfigurationSpecification],
+        label: Option<&Symbol>,
+        library_name: &Symbol,
+        name: &WithPos<SelectedName>,
+    ) -> Binding {
+        let component = if let Some(component) = selected_reference(&name.item) {
+            component
+        } else {
+            return Binding::Open;
+        };
+
+        let spec = specs
+            .iter()
+            .find(|spec| match spec.spec.instantiation_list {
+                InstantiationList::Labels(ref labels) => {
+                    label.is_some_and(|label| labels.iter().any(|other| &other.item.item == label))
+                }
+                InstantiationList::Others | InstantiationList::All => {
+                    selected_designator(&spec.spec.component_name.item)
+                        == selected_designator(&name.item)
+                }
+            });
+
+        match spec.and_then(|spec| spec.bind_ind.entity_aspect.as_ref()) {
+            Some(EntityAspect::Entity(name, architecture_name)) => match self
+                .entity_of(&name.item)
+                .or_else(|| self.entity_named(library_name, &name.item))
+            {
+                Some((library_name, entity_name)) => Binding::Entity(
+                    library_name,
+                    entity_name,
+                    architecture_name
+                        .as_ref()
+                        .map(|name| name.item.item.clone()),
+                ),
+                None => Binding::Open,
+            },
+            Some(EntityAspect::Configuration(_)) => {
+                Binding::Unbound("Bindings to configurations are not elaborated".to_owned())
+            }
+            Some(EntityAspect::Open) => Binding::Open,
+            None => {
+                let component = self.root.get_ent(component);
+                match component.designator() {
+                    Designator::Identifier(entity_name)
+                        if self.root.is_entity(library_name, entity_name) =>
+                    {
+                        Binding::Entity(library_name.clone(), entity_name.clone(), None)
+                    }
+                    designator => Binding::Unbound(format!(
+                        "No entity '{designator}' in library '{library_name}' is bound to the instance"
+                    )),
+                }
+            }
+        }
+    }
+}
+
+enum Binding {
+    /// The library, the entity and the optional architecture
+    Entity(Symbol, Symbol, Option<Symbol>),
+    /// The instance is left unbound on purpose
+    Open,
+    Unbound(String),
+}
+
+/// Connect the ports of an instance to the nets of the actuals in the port map
+/// An actual that is not a signal, such as an expression, is a net of its own
+fn port_map_actuals(
+    env: &Environment,
+    entity: &EntityDeclaration,
+    port_map: &[AssociationElement],
+    actuals: &mut Actuals,
+) {
+    let ports: Vec<_> = entity.port_clause.iter().flatten().collect();
+    for (idx, assoc) in port_map.iter().enumerate() {
+        let name = match assoc.formal {
+            Some(ref formal) => match base_designator(&formal.item) {
+                Some(Designator::Identifier(name)) => name.clone(),
+                _ => continue,
+            },
+            None => match ports.get(idx) {
+                Some(InterfaceDeclaration::Object(object)) => object.ident.tree.item.clone(),
+                _ => continue,
+            },
+        };
+        let net = match assoc.actual.item {
+            ActualPart::Expression(Expression::Name(ref name)) => env.signal_net(name),
+            ActualPart::Expression(_) => None,
+            ActualPart::Open => continue,
+        };
+        let net = match net {
+            Some(net) => net,
+            None => actuals.new_net(),
+        };
+        actuals.ports.entry(name).or_default().push(net);
+    }
+}
+
+/// The designator of a formal such as `port` in `port(0)`
+fn base_designator(name: &Name) -> Option<&Designator> {
+    match name {
+        Name::Designator(designator) => Some(&designator.item),
+        Name::Selected(prefix, _) | Name::Slice(prefix, _) => base_designator(&prefix.item),
+        Name::CallOrIndexed(call) => base_designator(&call.name.item),
+        _ => None,
+    }
+}
+
+fn direction(mode: Mode) -> &'static str {
+    match mode {
+        Mode::In => "input",
+        Mode::Out | Mode::Buffer => "output",
+        Mode::InOut | Mode::Linkage => "inout",
+    }
+}
+
+fn bits(nets: &[usize]) -> String {
+    let nets: Vec<_> = nets.iter().map(|net| net.to_string()).collect();
+    format!("[{}]", nets.join(", "))
+}
+
+fn selected_designator(name: &SelectedName) -> &Designator {
+    match name {
+        SelectedName::Designator(designator) => &designator.item,
+        SelectedName::Selected(_, designator) => &designator.item.item,
+    }
+}
+
+fn selected_reference(name: &SelectedName) -> Option<EntityId> {
+    match name {
+        SelectedName::Designator(designator) => designator.reference,
+        SelectedName::Selected(_, designator) => designator.item.reference,
+    }
+}
+
+fn quoted(name: &str) -> String {
+    format!("\"{}\"", name.replace('\\', "\\\\").replace('"', "\\\""))
+}
@@ -0,0 +1,194 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this file,
+// You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) 2023, Olof Kraigher olof.kraigher@gmail.com
+
+//! Render the declarations provided by an instance of a generic package
+//! with the actuals of the generic map substituted for the generics
+
+use fnv::FnvHashMap;
+
+use super::named_entity::*;
+use super::root::DesignRoot;
+use super::search::FormatDeclaration;
+use crate::ast::search::*;
+use crate::ast::*;
+use crate::data::*;
+
+pub(super) fn expand_package_instance(root: &DesignRoot, instance: EntRef) -> Option<String> {
+    let region = if let AnyEntKind::Design(Design::PackageInstance(region)) = instance.kind() {
+        region
+    } else {
+        return None;
+    };
+
+    let mut searcher = FindPackageInstantiation {
+        instance: instance.id(),
+        result: None,
+    };
+    let _ = root.search_library(instance.library_name()?, &mut searcher);
+    let (package_name, generic_map) = searcher.result?;
+
+    let package = root.get_ent(selected_reference(&package_name)?);
+    let generics =
+        if let AnyEntKind::Design(Design::UninstPackage(_, package_region)) = package.kind() {
+            package_region.to_package_generic().0
+        } else {
+            return None;
+        };
+
+    let mut actuals = FnvHashMap::default();
+    for (idx, assoc) in generic_map.iter().enumerate() {
+        let formal = match assoc.formal {
+            Some(WithPos {
+                item: Name::Designator(ref des),
+                ..
+            }) => des.item.to_string(),
+            Some(_) => continue,
+            None => {
+                if let Some(generic) = generics.nth(idx) {
+                    generic.designator().to_string()
+                } else {
+                    continue;
+                }
+            }
+        };
+
+        if let ActualPart::Expression(ref expr) = assoc.actual.item {
+            let actual = match expr {
+                Expression::Name(_) | Expression::Literal(_) => expr.to_string(),
+                _ => format!("({expr})"),
+            };
+            actuals.insert(formal.to_lowercase(), actual);
+        }
+    }
+
+    let mut ents: Vec<EntRef> = region
+        .immediates()
+        .filter(|ent| {
+            // Literals are part of the declaration of their type
+            !matches!(
+                ent.kind(),
+                AnyEntKind::Overloaded(Overloaded::EnumLiteral(_)) | AnyEntKind::PhysicalLiteral(_)
+            )
+        })
+        .collect();
+    ents.sort_by_key(|ent| ent.decl_pos().map(|pos| pos.range().start));
+
+    let mut text = format!(
+        "-- Expanded from generic package {package_name}\npackage {} is\n",
+        instance.designator()
+    );
+    for ent in ents {
+        let uninst = if let Related::InstanceOf(uninst) = ent.related {
+            uninst
+        } else {
+            ent
+        };
+
+        let mut searcher = FormatDeclaration::new(uninst);
+        if let Some(library_name) = uninst.library_name() {
+            let _ = root.search_library(library_name, &mut searcher);
+        }
+        if let Some(declaration) = searcher.result {
+            for line in substitute(&declaration, &actuals).lines() {
+                text.push_str(&format!("  {line}\n"));
+            }
+        }
+    }
+    text.push_str("end package;");
+    Some(text)
+}
+
+/// The entity denoted by the last designator of a selected name
+fn selected_reference(name: &SelectedName) -> Reference {
+    match name {
+        SelectedName::Designator(des) => des.reference,
+        SelectedName::Selected(_, des) => des.item.reference,
+    }
+}
+
+/// Replace the identifiers that name a generic by the text of its actual
+/// Literals, comments, selected suffixes and attribute designators are kept as is
+fn substitute(text: &str, actuals: &FnvHashMap<String, String>) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    let mut result = String::with_capacity(text.len());
+    // The last character that is not whitespace
+    let mut prev: Option<char> = None;
+    let mut idx = 0;
+
+    while idx < chars.len() {
+        let chr = chars[idx];
+        let start = idx;
+
+        if chr == '"' {
+            idx += 1;
+            while idx < chars.len() && chars[idx] != '"' {
+                idx += 1;
+            }
+            idx = (idx + 1).min(chars.len());
+        } else if chr == '-' && chars.get(idx + 1) == Some(&'-') {
+            while idx < chars.len() && chars[idx] != '\n' {
+                idx += 1;
+            }
+        } else if chr == '\''
+            && !prev.is_some_and(|prev| prev.is_alphanumeric() || prev == ')')
+            && chars.get(idx + 2) == Some(&'\'')
+        {
+            // A character literal rather than the tick of an attribute name
+            idx += 3;
+        } else if chr.is_alphanumeric() {
+            let is_number = chr.is_ascii_digit();
+            while idx < chars.len()
+                && (chars[idx].is_alphanumeric()
+                    || chars[idx] == '_'
+                    || (is_number && matches!(chars[idx], '#' | '.')))
+            {
+                idx += 1;
+            }
+
+            let word: String = chars[start..idx].iter().collect();
+            let is_bit_string = chars.get(idx) == Some(&'"');
+            let is_suffix = matches!(prev, Some('.') | Some('\''));
+            if !is_number && !is_bit_string && !is_suffix {
+                if let Some(actual) = actuals.get(&word.to_lowercase()) {
+                    result.push_str(actual);
+                    prev = actual.chars().last();
+                    continue;
+                }
+            }
+        } else {
+            idx += 1;
+        }
+
+        result.extend(&chars[start..idx]);
+        if let Some(last) = chars[start..idx].iter().rev().find(|c| !c.is_whitespace()) {
+            prev = Some(*last);
+        }
+    }
+    result
+}
+
+/// Find the name and generic map of a package instantiation
+struct FindPackageInstantiation {
+    instance: EntityId,
+    result: Option<(SelectedName, Vec<AssociationElement>)>,
+}
+
+impl Searcher for FindPackageInstantiation {
+    fn search_decl(&mut self, decl: FoundDeclaration) -> SearchState {
+        match decl {
+            FoundDeclaration::PackageInstance(instance)
+                if instance.ident.decl == Some(self.instance) =>
+            {
+                self.result = Some((
+                    instance.package_name.item.clone(),
+                    instance.generic_map.clone().unwrap_or_default(),
+                ));
+                Finished(Found)
+            }
+            _ => NotFinished,
+        }
+    }
+}
@@ -15,7 +15,9 @@ use super::overloaded::Disambiguated;
 use super::overloaded::DisambiguatedType;
 use super::overloaded::ResolvedCall;
 use super::region::*;
+use super::semantic::SemanticDiagnostics;
 use crate::ast::*;
+use crate::config::VHDLStandard;
 use crate::data::*;
 
 #[derive(Debug, PartialEq, Eq)]
@@ -127,15 +129,23 @@ impl<'c, 'a> TypeMatcher<'c, 'a> {
         actual_types: &[Option<ExpressionType<'a>>],
         candidates: &mut Vec<ResolvedCall<'a>>,
     ) {
-        candidates.retain(|resolved| {
-            actual_types.iter().enumerate().all(|(idx, actual_type)| {
-                if let Some(actual_type) = actual_type {
-                    self.is_possible(actual_type, resolved.formals[idx].type_mark().base())
-                } else {
-                    true
-                }
+        candidates.retain(|resolved| self.assoc_type_mismatch(actual_types, resolved).is_none())
+    }
+
+    /// The index of the first actual that cannot match the type of its formal
+    pub fn assoc_type_mismatch(
+        &self,
+        actual_types: &[Option<ExpressionType<'a>>],
+        resolved: &ResolvedCall<'a>,
+    ) -> Option<usize> {
+        actual_types
+            .iter()
+            .enumerate()
+            .position(|(idx, actual_type)| {
+                actual_type.as_ref().is_some_and(|actual_type| {
+                    !self.is_possible(actual_type, resolved.formals[idx].type_mark().base())
+                })
             })
-        })
     }
 
     pub fn disambiguate_op_by_return_type(
@@ -226,18 +236,34 @@ impl<'a> AnalyzeContext<'a> {
         expr: &mut WithPos<Expression>,
         diagnostics: &mut dyn DiagnosticHandler,
     ) -> EvalResult<TypeEnt<'a>> {
-        match self.expr_type(scope, expr, diagnostics)? {
-            ExpressionType::Unambiguous(typ) => Ok(typ),
-            ExpressionType::Ambiguous(_)
-            | ExpressionType::String
-            | ExpressionType::Null
-            | ExpressionType::Aggregate => {
-                diagnostics.error(
-                &expr.pos,
-                "Ambiguous expression. You can use a qualified expression type'(expr) to disambiguate.",
-            );
-                Err(EvalError::Unknown)
-            }
+        let types = self.expr_type(scope, expr, diagnostics)?;
+        let candidates = match types {
+            ExpressionType::Unambiguous(typ) => return Ok(typ),
+            ExpressionType::Ambiguous(types) => types.into_iter().collect(),
+            ExpressionType::String => self.string_literal_types(scope, &expr.pos),
+            ExpressionType::Null | ExpressionType::Aggregate => Vec::new(),
+        };
+
+        let mut diag = Diagnostic::error(
+            &expr.pos,
+            "Ambiguous expression. You can use a qualified expression type'(expr) to disambiguate.",
+        );
+        diag.add_type_candididates("Might be", candidates);
+        diagnostics.push(diag);
+        Err(EvalError::Unknown)
+    }
+
+    /// The visible types that a string literal could have
+    /// Every such one-dimensional array type has an implicit "=" operator visible along with it
+    fn string_literal_types(&self, scope: &Scope<'a>, pos: &SrcPos) -> Vec<BaseType<'a>> {
+        if let Ok(overloaded) = self.lookup_operator(scope, pos, Operator::EQ, 2) {
+            overloaded
+                .iter()
+                .filter_map(|ent| ent.nth_base(0))
+                .filter(|typ| typ.is_compatible_with_string_literal())
+                .collect()
+        } else {
+            Vec::new()
         }
     }
 
@@ -397,10 +423,59 @@ impl<'a> AnalyzeContext<'a> {
         }
 
         if candidates.is_empty() {
-            diagnostics.error(
-                &op.pos,
-                format!("Found no match for {}", designator.describe()),
-            );
+            // Report at the operand if it is the only one that prevents a match
+            let near_matches: Vec<_> = overloaded
+                .iter()
+                .filter_map(|ent| {
+                    let mismatches: Vec<_> = operand_types
+                        .iter()
+                        .enumerate()
+                        .filter(|(idx, typ)| {
+                            !self
+                                .implicit_matcher()
+                                .is_possible(typ, ent.nth_base(*idx).unwrap())
+                        })
+                        .map(|(idx, _)| idx)
+                        .collect();
+
+                    if let [idx] = mismatches.as_slice() {
+                        Some((*ent, *idx))
+                    } else {
+                        None
+                    }
+                })
+                .collect();
+
+            if let [(ent, _)] = near_matches.as_slice() {
+                // Type check the operands against the only near match to report the mismatch
+                self.check_op(scope, op, *ent, exprs, diagnostics)?;
+            } else if let Some((_, idx)) = near_matches
+                .first()
+                .filter(|(_, idx)| near_matches.iter().all(|(_, other)| other == idx))
+            {
+                diagnostics.error(
+                    &exprs[*idx].pos,
+                    format!(
+                        "{} is not a valid operand of {}",
+                        operand_types[*idx].describe(),
+                        designator.describe()
+                    ),
+                );
+            } else if let Some((idx, chr, typ)) = character_not_in_enum(exprs, &operand_types) {
+                diagnostics.error(
+                    &exprs[idx].pos,
+                    format!(
+                        "Character literal '{}' is not a literal of {}",
+                        chr as char,
+                        typ.describe()
+                    ),
+                );
+            } else {
+                diagnostics.error(
+                    &op.pos,
+                    format!("Found no match for {}", designator.describe()),
+                );
+            }
 
             Err(EvalError::Unknown)
         } else if candidates.len() == 1 {
@@ -464,6 +539,10 @@ impl<'a> AnalyzeContext<'a> {
         expr: &mut Expression,
         diagnostics: &mut dyn DiagnosticHandler,
     ) -> EvalResult<ExpressionType<'a>> {
+        if self.is_cancelled() {
+            return Err(EvalError::Unknown);
+        }
+
         match expr {
             Expression::Binary(ref mut op, ref mut left, ref mut right) => {
                 self.operator_type(scope, op, &mut [left.as_mut(), right.as_mut()], diagnostics)
@@ -627,8 +706,12 @@ impl<'a> AnalyzeContext<'a> {
         self.expr_pos_with_ttyp(scope, target_type, &expr.pos, &mut expr.item, diagnostics)
     }
 
+    /// The types with an implicit condition conversion using the ?? operator, which is only
+    /// applied from VHDL-2008
     fn implicit_bool_types(&self, scope: &Scope<'a>, pos: &SrcPos) -> FnvHashSet<BaseType<'a>> {
-        if let Ok(NamedEntities::Overloaded(overloaded)) =
+        if self.root.standard < VHDLStandard::VHDL2008 {
+            FnvHashSet::default()
+        } else if let Ok(NamedEntities::Overloaded(overloaded)) =
             scope.lookup(pos, &Designator::OperatorSymbol(Operator::QueQue))
         {
             overloaded
@@ -685,8 +768,19 @@ impl<'a> AnalyzeContext<'a> {
             match types {
                 ExpressionType::Unambiguous(typ) => {
                     if typ.base() != self.boolean().base() {
-                        let implicit_bools = self.implicit_bool_types(scope, &expr.pos);
-                        if !implicit_bools.contains(&typ.base()) {
+                        if self.root.standard < VHDLStandard::VHDL2008 {
+                            diagnostics.error(
+                                &expr.pos,
+                                format!(
+                                    "{} cannot be implictly converted to {}. Implicit condition conversion requires VHDL-2008.",
+                                    typ.describe(),
+                                    self.boolean().describe()
+                                ),
+                            );
+                        } else if !self
+                            .implicit_bool_types(scope, &expr.pos)
+                            .contains(&typ.base())
+                        {
                             diagnostics.error(
                                 &expr.pos,
                                 format!(
@@ -710,7 +804,8 @@ impl<'a> AnalyzeContext<'a> {
 
                         match implicit_bool_types.len().cmp(&1) {
                             std::cmp::Ordering::Equal => {
-                                let typ: TypeEnt = types.into_iter().next().unwrap().into();
+                                let typ: TypeEnt =
+                                    implicit_bool_types.into_iter().next().unwrap().into();
                                 self.expr_with_ttyp(scope, typ, expr, diagnostics)?;
                             }
                             std::cmp::Ordering::Greater => {
@@ -758,6 +853,10 @@ impl<'a> AnalyzeContext<'a> {
         expr: &mut Expression,
         diagnostics: &mut dyn DiagnosticHandler,
     ) -> FatalResult {
+        if self.is_cancelled() {
+            return Ok(());
+        }
+
         let target_base = target_type.base_type();
         match expr {
             Expression::Literal(ref mut lit) => self.analyze_literal_with_target_type(
@@ -1050,13 +1149,17 @@ impl<'a> AnalyzeContext<'a> {
                             }
                         }
                     } else {
-                        if let (Some(first), Some(last)) = (choices.first(), choices.last()) {
+                        let typ = self.record_multiple_choices(
+                            record_type,
+                            elems,
+                            choices,
+                            &mut associated,
+                            diagnostics,
+                        );
+                        if typ.is_none() {
                             is_ok_so_far = false;
-                            let pos = first.pos.combine(&last.pos);
-                            diagnostics
-                                .error(&pos, "Record aggregate choice must be a simple name");
                         }
-                        None
+                        typ
                     };
 
                     if let Some(typ) = typ {
@@ -1120,6 +1223,81 @@ impl<'a> AnalyzeContext<'a> {
         Ok(())
     }
 
+    /// Associate the elements of a choice such as `valid | ready => '0'`
+    /// Returns the type of the elements when they are all of the same type
+    fn record_multiple_choices<'c>(
+        &self,
+        record_type: TypeEnt<'a>,
+        elems: &RecordRegion<'a>,
+        choices: &'c mut [WithPos<Choice>],
+        associated: &mut RecordAssociations<'c>,
+        diagnostics: &mut dyn DiagnosticHandler,
+    ) -> Option<BaseType<'a>> {
+        let (first, last) = (choices.first()?, choices.last()?);
+        let pos = first.pos.combine(&last.pos);
+
+        let mut choice_elems = Vec::with_capacity(choices.len());
+        for choice in choices.iter_mut() {
+            let simple_name = if let Choice::Expression(ref mut choice_expr) = choice.item {
+                as_name_mut(choice_expr).and_then(as_simple_name_mut)
+            } else {
+                None
+            };
+
+            if let Some(simple_name) = simple_name {
+                if let Some(elem) = elems.lookup(&simple_name.item) {
+                    simple_name.set_unique_reference(&elem);
+                    choice_elems.push(elem);
+                } else {
+                    diagnostics.push(Diagnostic::no_declaration_within(
+                        &record_type,
+                        &choice.pos,
+                        &simple_name.item,
+                    ));
+                    return None;
+                }
+            } else {
+                diagnostics.error(&pos, "Record aggregate choice must be a simple name");
+                return None;
+            }
+        }
+
+        let choices: &'c [WithPos<Choice>] = choices;
+        for (elem, choice) in choice_elems.iter().zip(choices.iter()) {
+            associated.associate(elem, &choice.pos, diagnostics);
+        }
+
+        let base_type = choice_elems.first()?.type_mark().base();
+        if choice_elems
+            .iter()
+            .all(|elem| elem.type_mark().base() == base_type)
+        {
+            Some(base_type)
+        } else {
+            let mut diag = Diagnostic::error(
+                &pos,
+                format!(
+                    "Elements of record '{}' associated together are not of the same type",
+                    record_type.designator()
+                ),
+            );
+            for elem in choice_elems.iter() {
+                if let Some(decl_pos) = elem.decl_pos() {
+                    diag.add_related(
+                        decl_pos,
+                        format!(
+                            "Element '{}' has {}",
+                            elem.designator(),
+                            elem.type_mark().describe()
+                        ),
+                    );
+                }
+            }
+            diagnostics.push(diag);
+            None
+        }
+    }
+
     pub fn array_assoc_elem(
         &self,
         scope: &Scope<'a>,
@@ -1238,7 +1416,16 @@ impl<'a> AnalyzeContext<'a> {
     }
 }
 
-impl Diagnostic {
+/// Diagnostics about operator calls with more than one candidate
+trait OperatorDiagnostics {
+    fn ambiguous_op<'a>(
+        pos: &SrcPos,
+        op: Operator,
+        candidates: impl IntoIterator<Item = OverloadedEnt<'a>>,
+    ) -> Diagnostic;
+}
+
+impl OperatorDiagnostics for Diagnostic {
     fn ambiguous_op<'a>(
         pos: &SrcPos,
         op: Operator,
@@ -1285,6 +1472,29 @@ impl<'a> RecordAssociations<'a> {
     }
 }
 
+/// Find a character literal operand that is not a literal of the enumeration type of the other operand
+fn character_not_in_enum<'a>(
+    exprs: &[&mut WithPos<Expression>],
+    operand_types: &[ExpressionType<'a>],
+) -> Option<(usize, u8, TypeEnt<'a>)> {
+    if let ([lexpr, rexpr], [ltyp, rtyp]) = (exprs, operand_types) {
+        for (idx, expr, other_typ) in [(0, lexpr, rtyp), (1, rexpr, ltyp)] {
+            if let (
+                Expression::Literal(Literal::Character(chr)),
+                ExpressionType::Unambiguous(typ),
+            ) = (&expr.item, other_typ)
+            {
+                if let Type::Enum(literals) = typ.base().kind() {
+                    if !literals.contains(&Designator::Character(*chr)) {
+                        return Some((idx, *chr, *typ));
+                    }
+                }
+            }
+        }
+    }
+    None
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
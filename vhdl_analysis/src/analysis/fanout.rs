@@ -0,0 +1,321 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this file,
+// You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) 2023, Olof Kraigher olof.kraigher@gmail.com
+
+//! Count the processes, concurrent statements and instances of an architecture that drive
+//! and that read each signal, a signal with many loads or drivers is hard to follow
+
+use super::named_entity::*;
+use super::root::DesignRoot;
+use crate::ast::search::*;
+use crate::ast::*;
+use crate::data::*;
+
+/// The number of statements of an architecture that drive and that read a signal
+#[derive(Clone)]
+pub struct SignalFanout<'a> {
+    pub signal: EntRef<'a>,
+    /// The processes, concurrent assignments and instances that drive the signal
+    pub drivers: usize,
+    /// The processes, concurrent statements and instances that read the signal
+    pub loads: usize,
+}
+
+/// The signals driven or read within an architecture in the order they are first used
+pub struct ArchitectureFanout<'a> {
+    pub architecture: EntRef<'a>,
+    pub signals: Vec<SignalFanout<'a>>,
+}
+
+impl<'a> ArchitectureFanout<'a> {
+    pub fn of_signal(&self, signal: EntRef) -> Option<&SignalFanout<'a>> {
+        self.signals
+            .iter()
+            .find(|fanout| fanout.signal.id() == signal.id())
+    }
+}
+
+/// The largest number of loads and drivers of a signal that is not reported
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug)]
+pub(super) struct FanoutLimits {
+    pub max_loads: Option<usize>,
+    pub max_drivers: Option<usize>,
+}
+
+impl FanoutLimits {
+    pub fn is_enabled(&self) -> bool {
+        self.max_loads.is_some() || self.max_drivers.is_some()
+    }
+
+    /// Warn about the signals of the architecture with more loads or drivers than the limits
+    pub fn check(&self, fanout: &ArchitectureFanout, diagnostics: &mut dyn DiagnosticHandler) {
+        for signal in fanout.signals.iter() {
+            let decl_pos = if let Some(decl_pos) = signal.signal.decl_pos() {
+                decl_pos
+            } else {
+                continue;
+            };
+
+            let exceeded = [
+                (self.max_loads, signal.loads, "loads", "signal-fanout"),
+                (
+                    self.max_drivers,
+                    signal.drivers,
+                    "drivers",
+                    "signal-drivers",
+                ),
+            ];
+            for (limit, count, what, code) in exceeded {
+                if let Some(limit) = limit.filter(|limit| count > *limit) {
+                    diagnostics.push(
+                        Diagnostic::warning(
+                            decl_pos,
+                            format!(
+                                "Signal '{}' has {count} {what} within {} which is more than the limit of {limit}",
+                                signal.signal.designator(),
+                                fanout.architecture.describe()
+                            ),
+                        )
+                        .with_code(code),
+                    );
+                }
+            }
+        }
+    }
+}
+
+pub(super) fn architecture_fanout<'a>(
+    root: &'a DesignRoot,
+    architecture: &mut ArchitectureBody,
+) -> Option<ArchitectureFanout<'a>> {
+    let architecture_ent = root.get_ent(architecture.ident.decl?);
+    let mut counter = FanoutCounter {
+        root,
+        signals: Vec::new(),
+    };
+    counter.concurrent_statements(&mut architecture.statements);
+    Some(ArchitectureFanout {
+        architecture: architecture_ent,
+        signals: counter.signals,
+    })
+}
+
+struct FanoutCounter<'a> {
+    root: &'a DesignRoot,
+    signals: Vec<SignalFanout<'a>>,
+}
+
+impl<'a> FanoutCounter<'a> {
+    fn concurrent_statements(&mut self, statements: &mut [LabeledConcurrentStatement]) {
+        for statement in statements.iter_mut() {
+            match statement.statement.item {
+                ConcurrentStatement::Block(ref mut block) => {
+                    self.concurrent_statements(&mut block.statements);
+                }
+                ConcurrentStatement::ForGenerate(ref mut gen) => {
+                    self.concurrent_statements(&mut gen.body.statements);
+                }
+                ConcurrentStatement::IfGenerate(ref mut gen) => {
+                    for conditional in gen.conds.conditionals.iter_mut() {
+                        self.concurrent_statements(&mut conditional.item.statements);
+                    }
+                    if let Some(ref mut else_item) = gen.conds.else_item {
+                        self.concurrent_statements(&mut else_item.statements);
+                    }
+                }
+                ConcurrentStatement::CaseGenerate(ref mut gen) => {
+                    for alternative in gen.sels.alternatives.iter_mut() {
+                        self.concurrent_statements(&mut alternative.item.statements);
+                    }
+                }
+                ConcurrentStatement::Instance(ref mut instance) => self.instance(instance),
+                _ => {
+                    let mut uses = FindSignalUses::new(self.root);
+                    let _ = statement.search(&mut uses);
+                    self.add(&uses.driven, &uses.reads);
+                }
+            }
+        }
+    }
+
+    /// The actuals of ports of mode in are read and those of mode out are driven by the instance
+    fn instance(&mut self, instance: &mut InstantiationStatement) {
+        let ports = self.ports_of(&instance.unit);
+        let mut driven = Vec::new();
+        let mut reads = Vec::new();
+
+        for (idx, assoc) in instance.port_map.iter_mut().enumerate() {
+            let port = match assoc.formal {
+                Some(ref formal) => base_reference(&formal.item).map(|id| self.root.get_ent(id)),
+                None => ports.get(idx).copied(),
+            };
+            let mode = port.and_then(|port| match port.kind() {
+                AnyEntKind::Object(object) => object.mode(),
+                _ => None,
+            });
+
+            let mut uses = FindSignalUses::new(self.root);
+            let _ = assoc.actual.search(&mut uses);
+            for id in uses.reads {
+                if matches!(
+                    mode,
+                    Some(Mode::Out | Mode::Buffer | Mode::InOut | Mode::Linkage)
+                ) && !driven.contains(&id)
+                {
+                    driven.push(id);
+                }
+                if !matches!(mode, Some(Mode::Out | Mode::Buffer)) && !reads.contains(&id) {
+                    reads.push(id);
+                }
+            }
+        }
+        self.add(&driven, &reads);
+    }
+
+    fn ports_of(&self, unit: &InstantiatedUnit) -> Vec<EntRef<'a>> {
+        let id = match unit {
+            InstantiatedUnit::Entity(ref name, _) | InstantiatedUnit::Component(ref name) => {
+                selected_reference(&name.item)
+            }
+            InstantiatedUnit::Configuration(_) => None,
+        };
+        match id.map(|id| self.root.get_ent(id).kind()) {
+            Some(AnyEntKind::Design(Design::Entity(_, region)) | AnyEntKind::Component(region)) => {
+                let (_, ports) = region.to_entity_formal();
+                ports.iter().map(|port| port.inner()).collect()
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    fn add(&mut self, driven: &[EntityId], reads: &[EntityId]) {
+        for id in driven.iter() {
+            self.fanout(*id).drivers += 1;
+        }
+        for id in reads.iter() {
+            self.fanout(*id).loads += 1;
+        }
+    }
+
+    fn fanout(&mut self, id: EntityId) -> &mut SignalFanout<'a> {
+        let idx = match self
+            .signals
+            .iter()
+            .position(|fanout| fanout.signal.id() == id)
+        {
+            Some(idx) => idx,
+            None => {
+                self.signals.push(SignalFanout {
+                    signal: self.root.get_ent(id),
+                    drivers: 0,
+                    loads: 0,
+                });
+                self.signals.len() - 1
+            }
+        };
+        &mut self.signals[idx]
+    }
+}
+
+/// Find the signals driven by the targets of assignments and the signals read by a statement
+/// An alias of a signal is counted as the signal itself
+struct FindSignalUses<'a> {
+    root: &'a DesignRoot,
+    driven: Vec<EntityId>,
+    reads: Vec<EntityId>,
+}
+
+impl<'a> FindSignalUses<'a> {
+    fn new(root: &'a DesignRoot) -> Self {
+        Self {
+            root,
+            driven: Vec::new(),
+            reads: Vec::new(),
+        }
+    }
+
+    fn signal(&self, id: EntityId) -> Option<EntityId> {
+        match self.root.get_ent(id).kind() {
+            AnyEntKind::Object(object) if object.class == ObjectClass::Signal => Some(id),
+            AnyEntKind::ObjectAlias { base_object, .. } => self.signal(base_object.id()),
+            _ => None,
+        }
+    }
+
+    fn target(&mut self, target: &Target) {
+        match target {
+            Target::Name(name) => self.driven_name(name),
+            Target::Aggregate(assocs) => {
+                for assoc in assocs.iter() {
+                    let expr = match assoc {
+                        ElementAssociation::Positional(expr)
+                        | ElementAssociation::Named(_, expr) => expr,
+                    };
+                    if let Expression::Name(ref name) = expr.item {
+                        self.driven_name(name);
+                    }
+                }
+            }
+        }
+    }
+
+    fn driven_name(&mut self, name: &Name) {
+        if let Some(id) = self.driven_signal(name) {
+            if !self.driven.contains(&id) {
+                self.driven.push(id);
+            }
+        }
+    }
+
+    /// The signal denoted by the prefix of a target such as `sig` in `sig(0).elem`
+    fn driven_signal(&self, name: &Name) -> Option<EntityId> {
+        match name {
+            Name::Designator(designator) => self.signal(designator.reference?),
+            Name::Selected(prefix, suffix) => suffix
+                .item
+                .reference
+                .and_then(|id| self.signal(id))
+                // A signal selected from a package
+                .or_else(|| self.driven_signal(&prefix.item)),
+            Name::CallOrIndexed(call) => self.driven_signal(&call.name.item),
+            Name::Slice(prefix, _) => self.driven_signal(&prefix.item),
+            _ => None,
+        }
+    }
+}
+
+impl<'a> Searcher for FindSignalUses<'a> {
+    fn search_pos_with_ref(&mut self, _pos: &SrcPos, reference: &mut Reference) -> SearchState {
+        if let Some(id) = reference.and_then(|id| self.signal(id)) {
+            if !self.reads.contains(&id) {
+                self.reads.push(id);
+            }
+        }
+        NotFinished
+    }
+
+    fn search_target(&mut self, target: &WithPos<Target>) -> SearchState {
+        self.target(&target.item);
+        Finished(NotFound)
+    }
+}
+
+/// The port denoted by a formal such as `port` in `port(0)`
+fn base_reference(name: &Name) -> Option<EntityId> {
+    match name {
+        Name::Designator(designator) => designator.reference,
+        Name::Selected(prefix, _) => base_reference(&prefix.item),
+        Name::CallOrIndexed(call) => base_reference(&call.name.item),
+        Name::Slice(prefix, _) => base_reference(&prefix.item),
+        _ => None,
+    }
+}
+
+fn selected_reference(name: &SelectedName) -> Option<EntityId> {
+    match name {
+        SelectedName::Designator(designator) => designator.reference,
+        SelectedName::Selected(_, designator) => designator.item.reference,
+    }
+}
@@ -300,4 +300,10 @@ impl<'a> GpkgRegion<'a> {
     pub fn nth(&self, idx: usize) -> Option<GpkgInterfaceEnt<'a>> {
         self.entities.get(idx).cloned()
     }
+
+    pub fn has_type(&self) -> bool {
+        self.entities
+            .iter()
+            .any(|ent| matches!(ent, GpkgInterfaceEnt::Type(_)))
+    }
 }
@@ -0,0 +1,180 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this file,
+// You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) 2023, Olof Kraigher olof.kraigher@gmail.com
+
+//! Generate the text of an instance of an entity with all generics and ports associated by name
+
+use super::named_entity::*;
+use super::root::DesignRoot;
+use crate::ast::search::*;
+use crate::ast::*;
+
+/// How the entity is instantiated
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum InstantiationForm {
+    /// A direct instantiation such as `ent_inst : entity work.ent`
+    Entity,
+    /// A component declaration followed by an instantiation such as `ent_inst : component ent`
+    Component,
+}
+
+pub(super) fn instantiation_text(
+    root: &DesignRoot,
+    entity: EntRef,
+    form: InstantiationForm,
+) -> Option<String> {
+    instantiation(root, entity, form, false)
+}
+
+/// The instantiation text as a snippet where the label and the actuals are placeholders
+/// that an editor steps through
+pub(super) fn instantiation_snippet(
+    root: &DesignRoot,
+    entity: EntRef,
+    form: InstantiationForm,
+) -> Option<String> {
+    instantiation(root, entity, form, true)
+}
+
+fn instantiation(
+    root: &DesignRoot,
+    entity: EntRef,
+    form: InstantiationForm,
+    snippet: bool,
+) -> Option<String> {
+    let library_name = entity.library_name()?;
+    if !matches!(entity.kind(), AnyEntKind::Design(Design::Entity(..))) {
+        return None;
+    }
+
+    let mut searcher = FindEntityInterface {
+        entity: entity.id(),
+        result: None,
+    };
+    let _ = root.search_library(library_name, &mut searcher);
+    let (ident, generics, ports) = searcher.result?;
+
+    let name = ident.tree.item.clone();
+    let label = if snippet {
+        format!("${{1:{}}}", escape_snippet(&format!("{name}_inst")))
+    } else {
+        format!("{name}_inst")
+    };
+    // The label is the first placeholder
+    let mut placeholders = 2..;
+
+    let mut text = String::new();
+    match form {
+        InstantiationForm::Entity => {
+            text.push_str(&format!("{label} : entity {library_name}.{name}"));
+        }
+        InstantiationForm::Component => {
+            let component = ComponentDeclaration {
+                pos: ident.tree.pos.clone(),
+                ident,
+                generic_list: generics.clone(),
+                port_list: ports.clone(),
+                end_ident_pos: None,
+            };
+            let component = if snippet {
+                escape_snippet(&component.to_string())
+            } else {
+                component.to_string()
+            };
+            text.push_str(&format!("{component}\n\n{label} : component {name}"));
+        }
+    }
+
+    if !generics.is_empty() {
+        text.push_str(&format!(
+            "\n  generic map ({}\n  )",
+            associations(&generics, snippet, &mut placeholders)
+        ));
+    }
+    if !ports.is_empty() {
+        text.push_str(&format!(
+            "\n  port map ({}\n  )",
+            associations(&ports, snippet, &mut placeholders)
+        ));
+    }
+    text.push(';');
+    if snippet {
+        text.push_str("$0");
+    }
+    Some(text)
+}
+
+/// Associate each interface element with an actual of the same name, one per line
+/// The actuals of a snippet are numbered placeholders
+fn associations(
+    interface: &[InterfaceDeclaration],
+    snippet: bool,
+    placeholders: &mut impl Iterator<Item = usize>,
+) -> String {
+    let names: Vec<String> = interface.iter().map(interface_name).collect();
+    let width = names.iter().map(|name| name.len()).max().unwrap_or(0);
+    let lines: Vec<String> = names
+        .iter()
+        .map(|name| {
+            if snippet {
+                let formal = escape_snippet(name);
+                // Escaping adds characters that are not shown, which the padding must include
+                let width = width + formal.len() - name.len();
+                let idx = placeholders.next().unwrap_or_default();
+                format!("\n    {formal:width$} => ${{{idx}:{formal}}}")
+            } else {
+                format!("\n    {name:width$} => {name}")
+            }
+        })
+        .collect();
+    lines.join(",")
+}
+
+fn escape_snippet(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for chr in text.chars() {
+        if matches!(chr, '$' | '}' | '\\') {
+            escaped.push('\\');
+        }
+        escaped.push(chr);
+    }
+    escaped
+}
+
+fn interface_name(decl: &InterfaceDeclaration) -> String {
+    match decl {
+        InterfaceDeclaration::Object(object) => object.ident.tree.item.to_string(),
+        InterfaceDeclaration::File(file) => file.ident.tree.item.to_string(),
+        InterfaceDeclaration::Type(ident) => ident.tree.item.to_string(),
+        InterfaceDeclaration::Subprogram(decl, _) => decl.subpgm_designator().item.to_string(),
+        InterfaceDeclaration::Package(package) => package.ident.tree.item.to_string(),
+    }
+}
+
+/// Find the identifier, generics and ports of an entity declaration
+struct FindEntityInterface {
+    entity: EntityId,
+    result: Option<(
+        WithDecl<Ident>,
+        Vec<InterfaceDeclaration>,
+        Vec<InterfaceDeclaration>,
+    )>,
+}
+
+impl Searcher for FindEntityInterface {
+    fn search_decl(&mut self, decl: FoundDeclaration) -> SearchState {
+        match decl {
+            FoundDeclaration::Entity(entity) if entity.ident.decl == Some(self.entity) => {
+                self.result = Some((
+                    entity.ident.clone(),
+                    entity.generic_clause.clone().unwrap_or_default(),
+                    entity.port_clause.clone().unwrap_or_default(),
+                ));
+                Finished(Found)
+            }
+            _ => NotFinished,
+        }
+    }
+}
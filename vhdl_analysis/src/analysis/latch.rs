@@ -0,0 +1,207 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this file,
+// You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) 2023, Olof Kraigher olof.kraigher@gmail.com
+
+//! Warn when a combinational process assigns a signal in some but not all branches
+//! The signal keeps its previous value in the other branches which infers a latch in synthesis
+
+use super::analyze::*;
+use super::named_entity::*;
+use super::reset_value::is_clock_edge;
+use crate::ast::*;
+use crate::data::*;
+use fnv::FnvHashSet;
+
+impl<'a> AnalyzeContext<'a> {
+    /// Report signals which are not assigned in every path through a
+    /// process with a sensitivity list and without a clock edge
+    pub(super) fn check_latches(
+        &self,
+        statements: &[LabeledConcurrentStatement],
+        diagnostics: &mut dyn DiagnosticHandler,
+    ) {
+        for statement in statements {
+            match statement.statement.item {
+                ConcurrentStatement::Process(ref process)
+                    if process.sensitivity_list.is_some()
+                        && !has_clock_edge(&process.statements) =>
+                {
+                    self.check_process_latches(&process.statements, diagnostics);
+                }
+                ConcurrentStatement::Block(ref block) => {
+                    self.check_latches(&block.statements, diagnostics);
+                }
+                ConcurrentStatement::ForGenerate(ref gen) => {
+                    self.check_latches(&gen.body.statements, diagnostics);
+                }
+                ConcurrentStatement::IfGenerate(ref gen) => {
+                    for body in gen
+                        .conds
+                        .conditionals
+                        .iter()
+                        .map(|cond| &cond.item)
+                        .chain(gen.conds.else_item.iter())
+                    {
+                        self.check_latches(&body.statements, diagnostics);
+                    }
+                }
+                ConcurrentStatement::CaseGenerate(ref gen) => {
+                    for alternative in gen.sels.alternatives.iter() {
+                        self.check_latches(&alternative.item.statements, diagnostics);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn check_process_latches(
+        &self,
+        statements: &[LabeledSequentialStatement],
+        diagnostics: &mut dyn DiagnosticHandler,
+    ) {
+        let definite = self.definitely_assigned(statements);
+        let mut assignments = Vec::new();
+        self.assigned_signals(statements, &mut assignments);
+
+        let mut reported = FnvHashSet::default();
+        for (signal, pos) in assignments {
+            if !definite.contains(&signal) && reported.insert(signal) {
+                diagnostics.push(Diagnostic::warning(
+                    pos,
+                    format!(
+                        "Signal '{}' is not assigned in all branches of a process without a clock edge which infers a latch",
+                        self.arena.get(signal).designator()
+                    ),
+                ));
+            }
+        }
+    }
+
+    /// The signal assigned as a whole by a sequential statement
+    /// Assignments of parts of a signal are ignored since the parts are not tracked
+    fn assigned_signal(&self, statement: &SequentialStatement) -> Option<EntityId> {
+        if let SequentialStatement::SignalAssignment(assignment) = statement {
+            if let Target::Name(Name::Designator(designator)) = &assignment.target.item {
+                let id = designator.reference?;
+                if let AnyEntKind::Object(object) = self.arena.get(id).kind() {
+                    if object.class == ObjectClass::Signal {
+                        return Some(id);
+                    }
+                }
+            }
+        }
+        None
+    }
+
+    /// The signals assigned in every path through the statements
+    fn definitely_assigned(
+        &self,
+        statements: &[LabeledSequentialStatement],
+    ) -> FnvHashSet<EntityId> {
+        let mut assigned = FnvHashSet::default();
+        for statement in statements {
+            match statement.statement.item {
+                SequentialStatement::If(ref ifstmt) => {
+                    if let Some(ref else_item) = ifstmt.conds.else_item {
+                        let branches = ifstmt
+                            .conds
+                            .conditionals
+                            .iter()
+                            .map(|cond| cond.item.as_slice())
+                            .chain(std::iter::once(else_item.as_slice()));
+                        assigned.extend(self.assigned_in_all(branches));
+                    }
+                }
+                SequentialStatement::Case(ref case) => {
+                    let branches = case.alternatives.iter().map(|alt| alt.item.as_slice());
+                    assigned.extend(self.assigned_in_all(branches));
+                }
+                // A loop may run zero times
+                SequentialStatement::Loop(_) => {}
+                ref statement => assigned.extend(self.assigned_signal(statement)),
+            }
+        }
+        assigned
+    }
+
+    fn assigned_in_all<'s>(
+        &self,
+        mut branches: impl Iterator<Item = &'s [LabeledSequentialStatement]>,
+    ) -> FnvHashSet<EntityId> {
+        let mut assigned = if let Some(first) = branches.next() {
+            self.definitely_assigned(first)
+        } else {
+            return FnvHashSet::default();
+        };
+        for branch in branches {
+            let other = self.definitely_assigned(branch);
+            assigned.retain(|signal| other.contains(signal));
+        }
+        assigned
+    }
+
+    /// All signals assigned by the statements and the position of the assignment
+    fn assigned_signals<'s>(
+        &self,
+        statements: &'s [LabeledSequentialStatement],
+        assignments: &mut Vec<(EntityId, &'s SrcPos)>,
+    ) {
+        for statement in statements {
+            match statement.statement.item {
+                SequentialStatement::If(ref ifstmt) => {
+                    for branch in ifstmt
+                        .conds
+                        .conditionals
+                        .iter()
+                        .map(|cond| &cond.item)
+                        .chain(ifstmt.conds.else_item.iter())
+                    {
+                        self.assigned_signals(branch, assignments);
+                    }
+                }
+                SequentialStatement::Case(ref case) => {
+                    for alternative in case.alternatives.iter() {
+                        self.assigned_signals(&alternative.item, assignments);
+                    }
+                }
+                SequentialStatement::Loop(ref loop_stmt) => {
+                    self.assigned_signals(&loop_stmt.statements, assignments);
+                }
+                ref item => {
+                    if let Some(signal) = self.assigned_signal(item) {
+                        assignments.push((signal, &statement.statement.pos));
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// True if any condition of the statements is a clock edge such as `rising_edge(clk)`
+fn has_clock_edge(statements: &[LabeledSequentialStatement]) -> bool {
+    statements
+        .iter()
+        .any(|statement| match statement.statement.item {
+            SequentialStatement::If(ref ifstmt) => {
+                ifstmt
+                    .conds
+                    .conditionals
+                    .iter()
+                    .any(|cond| is_clock_edge(&cond.condition.item) || has_clock_edge(&cond.item))
+                    || ifstmt
+                        .conds
+                        .else_item
+                        .as_ref()
+                        .is_some_and(|else_item| has_clock_edge(else_item))
+            }
+            SequentialStatement::Case(ref case) => case
+                .alternatives
+                .iter()
+                .any(|alternative| has_clock_edge(&alternative.item)),
+            SequentialStatement::Loop(ref loop_stmt) => has_clock_edge(&loop_stmt.statements),
+            _ => false,
+        })
+}
@@ -8,6 +8,7 @@ use fnv::FnvHashSet;
 use super::analyze::*;
 use super::named_entity::*;
 use super::region::*;
+use super::semantic::SemanticDiagnostics;
 use crate::analysis::static_expression::{bit_string_to_string, BitStringConversionError};
 use crate::ast::*;
 use crate::data::*;
@@ -8,8 +8,9 @@ use super::formal_region::FormalRegion;
 use super::region::Region;
 use crate::ast::ExternalObjectClass;
 use crate::ast::{
-    AnyPrimaryUnit, Designator, HasIdent, Ident, ObjectClass, SubprogramDeclaration, WithDecl,
+    ConcurrentStatement, Designator, HasIdent, Ident, ObjectClass, SequentialStatement, WithDecl,
 };
+pub use crate::ast::{EntityId, HasEntityId};
 use crate::data::*;
 
 mod types;
@@ -25,7 +26,7 @@ mod design;
 pub use design::{Design, DesignEnt};
 
 mod arena;
-pub use arena::{Arena, ArenaId, EntityId, FinalArena};
+pub use arena::{Arena, ArenaId, FinalArena};
 
 pub enum AnyEntKind<'a> {
     ExternalAlias {
@@ -134,6 +135,7 @@ impl<'a> std::fmt::Debug for AnyEnt<'a> {
             designator,
             kind,
             decl_pos,
+            decl_span,
         } = self;
 
         let mut s = f.debug_struct(stringify!(AnyEnt));
@@ -144,6 +146,7 @@ impl<'a> std::fmt::Debug for AnyEnt<'a> {
         s.field(stringify!(designator), designator);
         s.field(stringify!(kind), kind);
         s.field(stringify!(decl_pos), decl_pos);
+        s.field(stringify!(decl_span), decl_span);
         s.finish()
     }
 }
@@ -173,6 +176,8 @@ pub struct AnyEnt<'a> {
     pub designator: Designator,
     pub kind: AnyEntKind<'a>,
     pub decl_pos: Option<SrcPos>,
+    /// The span of the whole declaration such as `signal foo : natural;`
+    pub decl_span: Option<SrcPos>,
 }
 
 impl Arena {
@@ -293,6 +298,10 @@ impl<'a> AnyEnt<'a> {
         self.decl_pos.as_ref()
     }
 
+    pub fn decl_span(&self) -> Option<&SrcPos> {
+        self.decl_span.as_ref()
+    }
+
     pub fn parent_in_same_source(&self) -> Option<EntRef<'a>> {
         let source = self.decl_pos()?.source();
         let mut ent = self;
@@ -427,6 +436,13 @@ impl<'a> AnyEnt<'a> {
             self.unsafe_ref_mut().related = Related::DeclaredBy(ent);
         }
     }
+
+    // Used to set the span of the declaration once the entity has been defined
+    pub(crate) unsafe fn set_decl_span(&self, span: SrcPos) {
+        unsafe {
+            self.unsafe_ref_mut().decl_span = Some(span);
+        }
+    }
 }
 
 impl<'a> std::cmp::PartialEq for AnyEnt<'a> {
@@ -443,19 +459,18 @@ impl<'a> std::hash::Hash for AnyEnt<'a> {
 
 impl<'a> Eq for AnyEnt<'a> {}
 
-/// This trait is implemented for Ast-nodes which declare named entities
-pub trait HasEntityId {
-    fn ent_id(&self) -> Option<EntityId>;
-}
-
-impl HasEntityId for AnyPrimaryUnit {
-    fn ent_id(&self) -> Option<EntityId> {
-        delegate_primary!(self, unit, unit.ident.decl)
-    }
+/// Declare the named entity of the identifier or designator of a declaration
+pub trait Define {
+    fn define<'a>(
+        &mut self,
+        arena: &'a Arena,
+        parent: EntRef<'a>,
+        kind: AnyEntKind<'a>,
+    ) -> EntRef<'a>;
 }
 
-impl WithDecl<Ident> {
-    pub fn define<'a>(
+impl Define for WithDecl<Ident> {
+    fn define<'a>(
         &mut self,
         arena: &'a Arena,
         parent: EntRef<'a>,
@@ -472,8 +487,8 @@ impl WithDecl<Ident> {
     }
 }
 
-impl WithDecl<WithPos<Designator>> {
-    pub fn define<'a>(
+impl Define for WithDecl<WithPos<Designator>> {
+    fn define<'a>(
         &mut self,
         arena: &'a Arena,
         parent: EntRef<'a>,
@@ -485,15 +500,6 @@ impl WithDecl<WithPos<Designator>> {
     }
 }
 
-impl SubprogramDeclaration {
-    pub fn set_decl_id(&mut self, id: EntityId) {
-        match self {
-            SubprogramDeclaration::Function(f) => f.designator.decl = Some(id),
-            SubprogramDeclaration::Procedure(p) => p.designator.decl = Some(id),
-        }
-    }
-}
-
 #[derive(Copy, Clone, Debug)]
 pub enum Concurrent {
     Block,
@@ -529,3 +535,56 @@ impl Sequential {
         }
     }
 }
+
+/// The kind of a statement that may be labeled
+pub trait LabelType {
+    type Label;
+
+    fn label_typ(&self) -> Option<Self::Label>;
+
+    fn can_have_label(&self) -> bool {
+        self.label_typ().is_some()
+    }
+}
+
+impl LabelType for ConcurrentStatement {
+    type Label = Concurrent;
+
+    fn label_typ(&self) -> Option<Concurrent> {
+        use ConcurrentStatement::*;
+        match self {
+            ProcedureCall(_) => None,
+            Block(_) => Some(Concurrent::Block),
+            Process(_) => Some(Concurrent::Process),
+            Assert(_) => None,
+            Assignment(_) => None,
+            Instance(_) => Some(Concurrent::Instance),
+            ForGenerate(_) | IfGenerate(_) | CaseGenerate(_) => Some(Concurrent::Generate),
+        }
+    }
+}
+
+impl LabelType for SequentialStatement {
+    type Label = Sequential;
+
+    fn label_typ(&self) -> Option<Sequential> {
+        use SequentialStatement::*;
+        match self {
+            Wait(_) => None,
+            Assert(_) => None,
+            Report(_) => None,
+            VariableAssignment(_) => None,
+            SignalAssignment(_) => None,
+            SignalForceAssignment(_) => None,
+            SignalReleaseAssignment(_) => None,
+            ProcedureCall(_) => None,
+            If(_) => Some(Sequential::If),
+            Case(_) => Some(Sequential::Case),
+            Loop(_) => Some(Sequential::Loop),
+            Next(_) => None,
+            Exit(_) => None,
+            Return(_) => None,
+            Null => None,
+        }
+    }
+}
@@ -6,13 +6,15 @@
 
 use pinned_vec::PinnedVec;
 use std::cell::RefCell;
+use std::collections::hash_map::Entry;
 use std::sync::atomic::AtomicU32;
+use std::sync::atomic::AtomicU64;
 use std::sync::atomic::Ordering;
 use std::sync::Arc;
 
 use fnv::FnvHashMap;
 
-use crate::ast::Designator;
+use crate::ast::{Designator, EntityId};
 use crate::SrcPos;
 
 use super::AnyEnt;
@@ -36,11 +38,16 @@ impl Default for ArenaId {
 #[derive(Copy, Clone, PartialEq, Eq, Debug)]
 pub struct LocalId(u32);
 
+/// Counts the local arenas such that a later analysis of a design unit
+/// which keeps its ArenaId can be told apart from an earlier one
+static GCOUNTER: AtomicU64 = AtomicU64::new(0);
+
 /// Arena allocators used to store named entities
 
 /// Local arena used for single design unit in a separate thread
 struct LocalArena {
     pub id: ArenaId,
+    generation: u64,
     items: PinnedVec<AnyEnt<'static>>,
 }
 
@@ -48,6 +55,7 @@ impl LocalArena {
     pub fn new(id: ArenaId) -> Self {
         Self {
             id,
+            generation: GCOUNTER.fetch_add(1, Ordering::Relaxed),
             items: PinnedVec::new(),
         }
     }
@@ -101,6 +109,8 @@ impl LocalArena {
 #[derive(Clone, Default)]
 pub struct FinalArena {
     refs: FnvHashMap<u32, Arc<LocalArena>>,
+    /// Earlier analyses of arenas in refs which entities may still point into
+    retained: Vec<Arc<LocalArena>>,
 }
 
 impl<'a> FinalArena {
@@ -111,14 +121,29 @@ impl<'a> FinalArena {
         }
     }
 
+    /// Link the arenas of another final arena, a later analysis of the same arena replaces an earlier one
     pub fn link(&mut self, referenced: &FinalArena) {
+        self.retained.extend(referenced.retained.iter().cloned());
         for (id, arena) in referenced.refs.iter() {
-            self.refs.entry(*id).or_insert_with(|| arena.clone());
+            match self.refs.entry(*id) {
+                Entry::Occupied(mut entry) => {
+                    if entry.get().generation < arena.generation {
+                        let previous = entry.insert(arena.clone());
+                        self.retained.push(previous);
+                    } else if entry.get().generation > arena.generation {
+                        self.retained.push(arena.clone());
+                    }
+                }
+                Entry::Vacant(entry) => {
+                    entry.insert(arena.clone());
+                }
+            }
         }
     }
 
     pub fn clear(&mut self) {
         self.refs.clear();
+        self.retained.clear();
     }
 }
 
@@ -165,6 +190,7 @@ impl Arena {
             designator,
             kind,
             decl_pos,
+            decl_span: None,
         };
 
         unsafe {
@@ -195,6 +221,7 @@ impl Arena {
                 designator,
                 kind,
                 decl_pos,
+                decl_span: None,
             };
             &*eref as EntRef<'a>
         }
@@ -250,29 +277,23 @@ impl Arena {
     }
 }
 
-#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
-pub struct EntityId {
-    id: usize,
+/// The id of the arena and the id within the arena of a named entity
+trait ArenaEntityId {
+    fn new_arena(arena_id: ArenaId, id: LocalId) -> Self;
+    fn arena_id(&self) -> ArenaId;
+    fn local_id(&self) -> LocalId;
 }
 
-impl EntityId {
-    // Using 64-bits we can create 5 * 10**9 ids per second for 100 years before wrapping
-    #[allow(clippy::new_without_default)]
-    pub fn undefined() -> Self {
-        EntityId { id: usize::MAX }
-    }
-
-    pub(crate) fn new_arena(arena_id: ArenaId, id: LocalId) -> Self {
-        EntityId {
-            id: ((arena_id.0 as usize) << u32::BITS) | (id.0 as usize),
-        }
+impl ArenaEntityId for EntityId {
+    fn new_arena(arena_id: ArenaId, id: LocalId) -> Self {
+        EntityId::from_raw(((arena_id.0 as usize) << u32::BITS) | (id.0 as usize))
     }
 
-    pub fn arena_id(&self) -> ArenaId {
-        ArenaId((self.id >> u32::BITS) as u32)
+    fn arena_id(&self) -> ArenaId {
+        ArenaId((self.raw() >> u32::BITS) as u32)
     }
 
     fn local_id(&self) -> LocalId {
-        LocalId((self.id & (u32::MAX as usize)) as u32)
+        LocalId((self.raw() & (u32::MAX as usize)) as u32)
     }
 }
@@ -11,6 +11,7 @@ use super::AnyEntKind;
 use super::EntRef;
 use crate::analysis::region::NamedEntities;
 use crate::analysis::region::Region;
+use crate::analysis::semantic::SemanticDiagnostics;
 use crate::analysis::visibility::Visibility;
 use crate::ast::Designator;
 use crate::ast::HasDesignator;
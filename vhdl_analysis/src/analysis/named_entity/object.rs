@@ -10,6 +10,7 @@ use crate::analysis::AnyEntKind;
 use crate::ast::InterfaceType;
 use crate::ast::Mode;
 use crate::ast::ObjectClass;
+use crate::ast::SignalKind;
 
 use super::AnyEnt;
 use super::EntRef;
@@ -123,6 +124,7 @@ pub struct Object<'a> {
     pub class: ObjectClass,
     pub iface: Option<ObjectInterface>,
     pub subtype: Subtype<'a>,
+    pub signal_kind: Option<SignalKind>,
     pub has_default: bool,
 }
 
@@ -132,6 +134,17 @@ impl<'a> Object<'a> {
             class: ObjectClass::Constant,
             iface: Some(ObjectInterface::Parameter(Mode::In)),
             subtype,
+            signal_kind: None,
+            has_default: false,
+        }
+    }
+
+    pub(crate) fn variable_out_param(subtype: Subtype<'a>) -> Object<'a> {
+        Object {
+            class: ObjectClass::Variable,
+            iface: Some(ObjectInterface::Parameter(Mode::Out)),
+            subtype,
+            signal_kind: None,
             has_default: false,
         }
     }
@@ -156,17 +169,10 @@ impl<'a> Object<'a> {
     pub fn mode(&self) -> Option<Mode> {
         self.iface.map(|i| i.mode())
     }
-}
 
-impl ObjectClass {
-    pub fn describe(&self) -> &str {
-        use ObjectClass::*;
-        match self {
-            Constant => "constant",
-            Variable => "variable",
-            Signal => "signal",
-            SharedVariable => "shared variable",
-        }
+    /// A signal declared with a signal kind of register or bus
+    pub fn is_guarded(&self) -> bool {
+        self.signal_kind.is_some()
     }
 }
 
@@ -52,6 +52,8 @@ pub struct Signature<'a> {
     /// Vector of InterfaceObject or InterfaceFile
     pub(crate) formals: FormalRegion<'a>,
     pub(crate) return_type: Option<TypeEnt<'a>>,
+    /// Functions are pure unless declared impure
+    pub(crate) impure: bool,
 }
 
 impl<'a> Signature<'a> {
@@ -59,9 +61,15 @@ impl<'a> Signature<'a> {
         Signature {
             formals,
             return_type: return_type.as_ref().map(TypeEnt::to_owned),
+            impure: false,
         }
     }
 
+    /// True for functions declared impure
+    pub fn is_impure(&self) -> bool {
+        self.impure
+    }
+
     pub fn key(&self) -> SignatureKey<'a> {
         let formals = self.formals.iter().map(|formal| formal.base()).collect();
         let return_type = self.return_type.as_ref().map(|ent| ent.base());
@@ -10,6 +10,7 @@ use crate::analysis::named_entity::{AnyEnt, AnyEntKind, EntityId};
 
 use crate::analysis::formal_region::{RecordElement, RecordRegion};
 use crate::analysis::region::{NamedEntities, OverloadedName, Region};
+use crate::analysis::semantic::SemanticDiagnostics;
 use crate::ast::WithDecl;
 use crate::ast::{Designator, WithRef};
 use crate::ast::{HasDesignator, Ident};
@@ -153,6 +154,19 @@ impl<'a> TypeEnt<'a> {
         BaseType::from(*self)
     }
 
+    pub fn is_resolved(&self) -> bool {
+        match self.kind() {
+            Type::Subtype(subtype) => subtype.is_resolved(),
+            Type::Alias(typ) => typ.is_resolved(),
+            Type::Array { elem_type, .. } => elem_type.is_resolved(),
+            Type::Record(region) => region.iter().any(|elem| match elem.kind() {
+                AnyEntKind::ElementDeclaration(subtype) => subtype.is_resolved(),
+                _ => false,
+            }),
+            _ => false,
+        }
+    }
+
     pub fn accessed_type(&self) -> Option<TypeEnt<'a>> {
         self.base().accessed_type()
     }
@@ -433,11 +447,29 @@ impl<'a> Deref for BaseType<'a> {
 #[derive(Clone, Copy)]
 pub struct Subtype<'a> {
     pub(crate) type_mark: TypeEnt<'a>,
+    /// The subtype indication has a resolution function
+    pub(crate) has_resolution: bool,
 }
 
 impl<'a> Subtype<'a> {
     pub fn new(type_mark: TypeEnt<'a>) -> Subtype<'a> {
-        Subtype { type_mark }
+        Subtype {
+            type_mark,
+            has_resolution: false,
+        }
+    }
+
+    pub fn with_resolution(type_mark: TypeEnt<'a>) -> Subtype<'a> {
+        Subtype {
+            type_mark,
+            has_resolution: true,
+        }
+    }
+
+    /// A signal of a resolved subtype may have several drivers
+    /// A composite is considered resolved when any of its elements is resolved
+    pub fn is_resolved(&self) -> bool {
+        self.has_resolution || self.type_mark.is_resolved()
     }
 
     pub fn type_mark(&self) -> TypeEnt<'a> {
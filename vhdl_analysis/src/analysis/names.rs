@@ -13,6 +13,8 @@ use super::overloaded::Disambiguated;
 use super::overloaded::DisambiguatedType;
 use super::overloaded::SubprogramKind;
 use super::region::*;
+use super::semantic::SemanticDiagnostics;
+use super::static_expression::static_integer;
 use crate::ast::*;
 use crate::data::*;
 
@@ -22,6 +24,8 @@ pub enum ObjectBase<'a> {
     ObjectAlias(ObjectEnt<'a>, EntRef<'a>),
     DeferredConstant(EntRef<'a>),
     ExternalName(ExternalObjectClass),
+    /// The signal implicitly declared by a signal attribute such as `'delayed`
+    ImplicitSignal(SignalAttribute),
 }
 
 impl<'a> ObjectBase<'a> {
@@ -31,6 +35,7 @@ impl<'a> ObjectBase<'a> {
             ObjectBase::ObjectAlias(object, _) => object.mode(),
             ObjectBase::DeferredConstant(..) => None,
             ObjectBase::ExternalName(_) => None,
+            ObjectBase::ImplicitSignal(_) => None,
         }
     }
 
@@ -40,6 +45,7 @@ impl<'a> ObjectBase<'a> {
             ObjectBase::ObjectAlias(object, _) => object.class(),
             ObjectBase::DeferredConstant(..) => ObjectClass::Constant,
             ObjectBase::ExternalName(class) => (*class).into(),
+            ObjectBase::ImplicitSignal(_) => ObjectClass::Signal,
         }
     }
 
@@ -62,6 +68,7 @@ impl<'a> ObjectBase<'a> {
                 format!("deferred constant '{}'", ent.designator())
             }
             ObjectBase::ExternalName(..) => "external name".to_owned(),
+            ObjectBase::ImplicitSignal(attr) => format!("implicit signal '{attr}"),
             ObjectBase::Object(obj) => obj.describe_name(),
             ObjectBase::ObjectAlias(_, alias) => {
                 format!("alias '{}' of {}", alias.designator(), self.class())
@@ -75,6 +82,7 @@ impl<'a> ObjectBase<'a> {
             ObjectBase::ObjectAlias(obj, _) => obj.kind().is_port(),
             ObjectBase::DeferredConstant(_) => false,
             ObjectBase::ExternalName(_) => false,
+            ObjectBase::ImplicitSignal(_) => false,
         }
     }
 }
@@ -317,6 +325,43 @@ impl<'a> ResolvedName<'a> {
         Err(EvalError::Unknown)
     }
 }
+/// The attributes that denote a signal implicitly declared by the attribute (LRM 16.2.4)
+fn is_implicit_signal(attr: SignalAttribute) -> bool {
+    matches!(
+        attr,
+        SignalAttribute::Delayed
+            | SignalAttribute::Stable
+            | SignalAttribute::Quiet
+            | SignalAttribute::Transaction
+    )
+}
+
+/// Implicit signals may not be created from a formal signal parameter of a subprogram
+/// since the subprogram has no lifetime in which the signal could be updated
+fn check_not_signal_parameter(
+    prefix_pos: &SrcPos,
+    prefix: &ResolvedName,
+    attr: &AttributeSuffix,
+    diagnostics: &mut dyn DiagnosticHandler,
+) {
+    if let ResolvedName::ObjectName(ObjectName {
+        base: ObjectBase::Object(obj) | ObjectBase::ObjectAlias(obj, _),
+        ..
+    }) = prefix
+    {
+        if obj.object().is_param() {
+            diagnostics.error(
+                prefix_pos,
+                format!(
+                    "'{} attribute is not allowed on formal signal parameter '{}' of a subprogram",
+                    attr.attr,
+                    obj.designator()
+                ),
+            );
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct AttributeSuffix<'a> {
     pub signature: &'a mut Option<WithPos<crate::ast::Signature>>,
@@ -378,6 +423,23 @@ fn could_be_indexed_name(assocs: &[AssociationElement]) -> bool {
         .all(|assoc| assoc.formal.is_none() && !matches!(assoc.actual.item, ActualPart::Open))
 }
 
+/// The function of an overloaded name such as `f` in `f(0)` when it is the only
+/// function with that name and it has no parameters so the suffix must index its result
+fn indexed_parameterless_function<'a>(
+    overloaded: &OverloadedName<'a>,
+    suffix: &Suffix,
+) -> Option<OverloadedEnt<'a>> {
+    if let Suffix::CallOrIndexed(ref assocs) = suffix {
+        if overloaded.len() == 1 && could_be_indexed_name(assocs) {
+            let ent = overloaded.first();
+            if ent.is_function() && ent.formals().is_empty() {
+                return Some(ent);
+            }
+        }
+    }
+    None
+}
+
 pub fn as_type_conversion(assocs: &mut [AssociationElement]) -> Option<(&SrcPos, &mut Expression)> {
     if assocs.len() == 1 && could_be_indexed_name(assocs) {
         if let ActualPart::Expression(ref mut expr) = assocs[0].actual.item {
@@ -667,27 +729,36 @@ impl<'a> AnalyzeContext<'a> {
         }
     }
 
-    // Resolve an index used in an array attribute such as arr_t'left(0) to an index type
+    // Resolve an index used in an array attribute such as arr_t'left(2) to an index type
     pub(crate) fn array_index_expression_in_attribute(
         &self,
+        scope: &Scope<'a>,
         indexes: &[Option<BaseType<'a>>],
         mut expr: Option<&mut WithPos<Expression>>,
         diagnostics: &mut dyn DiagnosticHandler,
     ) -> EvalResult<BaseType<'a>> {
         let idx = if let Some(expr) = expr.as_mut() {
-            if let Expression::Literal(Literal::AbstractLiteral(AbstractLiteral::Integer(idx))) =
-                expr.item
-            {
-                idx as usize
+            if let Some(idx) = static_integer(&expr.item) {
+                idx
             } else {
-                diagnostics.error(&expr.pos, "Expected an integer literal");
-                return Err(EvalError::Unknown);
+                // The dimension depends on constants or generics and cannot be known here
+                // Only the first dimension is valid for a one-dimensional array though
+                self.integer_expr(scope, expr, diagnostics)?;
+                return match indexes {
+                    [Some(idx_typ)] => Ok(*idx_typ),
+                    _ => Err(EvalError::Unknown),
+                };
             }
         } else {
             1
         };
 
-        if let Some(idx_typ) = indexes.get(idx - 1) {
+        let idx_typ = usize::try_from(idx)
+            .ok()
+            .and_then(|idx| idx.checked_sub(1))
+            .and_then(|idx| indexes.get(idx));
+
+        if let Some(idx_typ) = idx_typ {
             if let Some(idx_typ) = idx_typ {
                 Ok(*idx_typ)
             } else {
@@ -722,6 +793,7 @@ impl<'a> AnalyzeContext<'a> {
 
                 if let Some((_, indexes)) = typ.array_type() {
                     self.array_index_expression_in_attribute(
+                        scope,
                         indexes,
                         attr.expr.as_mut().map(|expr| expr.as_mut()),
                         diagnostics,
@@ -739,7 +811,13 @@ impl<'a> AnalyzeContext<'a> {
             AttributeDesignator::Ascending | AttributeDesignator::Descending => {
                 let typ = prefix.as_type_of_attr_prefix(prefix_pos, attr, diagnostics)?;
 
-                if typ.array_type().is_some() {
+                if let Some((_, indexes)) = typ.array_type() {
+                    as_fatal(self.array_index_expression_in_attribute(
+                        scope,
+                        indexes,
+                        attr.expr.as_mut().map(|expr| expr.as_mut()),
+                        diagnostics,
+                    ))?;
                     Ok(self.boolean().base())
                 } else if typ.is_scalar() {
                     check_no_attr_argument(attr, diagnostics);
@@ -834,7 +912,13 @@ impl<'a> AnalyzeContext<'a> {
             AttributeDesignator::Length => {
                 let typ = prefix.as_type_of_attr_prefix(prefix_pos, attr, diagnostics)?;
 
-                if typ.array_type().is_some() {
+                if let Some((_, indexes)) = typ.array_type() {
+                    as_fatal(self.array_index_expression_in_attribute(
+                        scope,
+                        indexes,
+                        attr.expr.as_mut().map(|expr| expr.as_mut()),
+                        diagnostics,
+                    ))?;
                     Ok(self.universal_integer())
                 } else {
                     diagnostics.push(Diagnostic::cannot_be_prefix_of_attribute(
@@ -852,6 +936,9 @@ impl<'a> AnalyzeContext<'a> {
 
             AttributeDesignator::Signal(sattr) => {
                 let typ = prefix.as_type_of_signal_attr_prefix(prefix_pos, attr, diagnostics)?;
+                if is_implicit_signal(sattr) {
+                    check_not_signal_parameter(prefix_pos, prefix, attr, diagnostics);
+                }
                 let expr = attr.expr.as_mut().map(|expr| expr.as_mut());
                 match sattr {
                     SignalAttribute::Delayed => {
@@ -919,6 +1006,42 @@ impl<'a> AnalyzeContext<'a> {
         }
     }
 
+    /// The type denoted by obj'subtype, obj'element or typ'element
+    fn type_attribute_suffix(
+        &self,
+        prefix_pos: &SrcPos,
+        prefix: &ResolvedName<'a>,
+        tattr: TypeAttribute,
+        attr: &AttributeSuffix,
+        diagnostics: &mut dyn DiagnosticHandler,
+    ) -> EvalResult<TypeEnt<'a>> {
+        let typ = match prefix {
+            ResolvedName::ObjectName(oname) => oname.type_mark(),
+            ResolvedName::Type(typ) if tattr == TypeAttribute::Element => *typ,
+            _ => {
+                diagnostics.push(Diagnostic::cannot_be_prefix_of_attribute(
+                    prefix_pos, prefix, attr,
+                ));
+                return Err(EvalError::Unknown);
+            }
+        };
+
+        match tattr {
+            TypeAttribute::Subtype => Ok(typ),
+            TypeAttribute::Element => {
+                if let Some((elem_type, _)) = typ.array_type() {
+                    Ok(elem_type)
+                } else {
+                    diagnostics.error(
+                        prefix_pos,
+                        format!("array type expected for '{tattr} attribute"),
+                    );
+                    Err(EvalError::Unknown)
+                }
+            }
+        }
+    }
+
     pub fn name_resolve(
         &self,
         scope: &Scope<'a>,
@@ -947,6 +1070,7 @@ impl<'a> AnalyzeContext<'a> {
                 return Ok(match name {
                     NamedEntities::Single(ent) => {
                         designator.set_unique_reference(ent);
+                        self.record_work_reference(name_pos, &designator.item, ent);
 
                         catch_diagnostic(
                             ResolvedName::from_scope_not_overloaded(ent)
@@ -1019,6 +1143,7 @@ impl<'a> AnalyzeContext<'a> {
                             }
                         }
                         Disambiguated::Unambiguous(ent) => {
+                            prefix.set_unique_reference(&ent);
                             if let Some(typ) = ent.return_type() {
                                 resolved =
                                     ResolvedName::Expression(DisambiguatedType::Unambiguous(typ));
@@ -1037,8 +1162,28 @@ impl<'a> AnalyzeContext<'a> {
 
         // Attributes for non-types not handled yet
         if let Suffix::Attribute(ref mut attr) = suffix {
+            if let AttributeDesignator::Type(tattr) = attr.attr.item {
+                let typ =
+                    self.type_attribute_suffix(&prefix.pos, &resolved, tattr, attr, diagnostics)?;
+                // The type of an attribute with an argument such as arr'element(value) converts the argument
+                if let Some(ref mut expr) = attr.expr {
+                    self.check_type_conversion(scope, typ, &expr.pos, &mut expr.item, diagnostics)?;
+                    return Ok(ResolvedName::Expression(DisambiguatedType::Unambiguous(
+                        typ,
+                    )));
+                }
+                return Ok(ResolvedName::Type(typ));
+            }
             let typ =
                 self.attribute_suffix(name_pos, &prefix.pos, scope, &resolved, attr, diagnostics)?;
+            if let AttributeDesignator::Signal(sattr) = attr.attr.item {
+                if is_implicit_signal(sattr) {
+                    return Ok(ResolvedName::ObjectName(ObjectName {
+                        base: ObjectBase::ImplicitSignal(sattr),
+                        type_mark: Some(typ.into()),
+                    }));
+                }
+            }
             return Ok(ResolvedName::Expression(DisambiguatedType::Unambiguous(
                 typ.into(),
             )));
@@ -1046,9 +1191,41 @@ impl<'a> AnalyzeContext<'a> {
 
         match resolved {
             ResolvedName::Overloaded(ref des, ref overloaded) => {
-                if let Suffix::CallOrIndexed(ref mut assocs) = suffix {
-                    // @TODO could be overloaded with no arguments that is indexed
-
+                if let Some(ent) = indexed_parameterless_function(overloaded, &suffix) {
+                    // A function without parameters that is called without arguments
+                    // and whose result is indexed or sliced such as f(0)
+                    prefix.set_unique_reference(&ent);
+                    let return_type = ent.return_type().unwrap();
+                    match catch_analysis_err(
+                        self.resolve_typed_suffix(
+                            scope,
+                            &prefix.pos,
+                            name_pos,
+                            return_type,
+                            &mut suffix,
+                            diagnostics,
+                        ),
+                        diagnostics,
+                    )? {
+                        Some(TypeOrMethod::Type(typ)) => {
+                            resolved =
+                                ResolvedName::Expression(DisambiguatedType::Unambiguous(typ));
+                        }
+                        Some(TypeOrMethod::Method(des, name)) => {
+                            resolved = ResolvedName::Overloaded(des, name);
+                        }
+                        None => {
+                            diagnostics.push(Diagnostic::cannot_be_prefix(
+                                &prefix.pos,
+                                ResolvedName::Expression(DisambiguatedType::Unambiguous(
+                                    return_type,
+                                )),
+                                suffix,
+                            ));
+                            return Err(EvalError::Unknown);
+                        }
+                    }
+                } else if let Suffix::CallOrIndexed(ref mut assocs) = suffix {
                     // @TODO lookup already set reference to get O(N) instead of O(N^2) when disambiguating deeply nested ambiguous calls
                     if let Some(id) = prefix.item.get_suffix_reference() {
                         if let Some(ent) = OverloadedEnt::from_any(self.arena.get(id)) {
@@ -1523,6 +1700,9 @@ impl<'a> AnalyzeContext<'a> {
             SelectedName::Designator(ref mut designator) => {
                 let visible = scope.lookup(&name.pos, designator.designator())?;
                 designator.set_reference(&visible);
+                if let NamedEntities::Single(ent) = visible {
+                    self.record_work_reference(&name.pos, &designator.item, ent);
+                }
                 Ok(visible)
             }
         }
@@ -1537,7 +1717,28 @@ fn plural(singular: &'static str, plural: &'static str, count: usize) -> &'stati
     }
 }
 
-impl Diagnostic {
+/// Diagnostics about names that cannot be resolved with their suffix
+trait NameDiagnostics {
+    fn cannot_be_prefix(prefix_pos: &SrcPos, resolved: ResolvedName, suffix: Suffix) -> Diagnostic;
+
+    fn cannot_be_prefix_of_attribute(
+        prefix_pos: &SrcPos,
+        resolved: &ResolvedName,
+        attr: &AttributeSuffix,
+    ) -> Diagnostic;
+
+    fn dimension_mismatch(
+        pos: &SrcPos,
+        base_type: TypeEnt,
+        got: usize,
+        expected: usize,
+    ) -> Diagnostic;
+
+    /// An internal logic error that we want to show to the user to get bug reports
+    fn unreachable(pos: &SrcPos, expected: &str) -> Diagnostic;
+}
+
+impl NameDiagnostics for Diagnostic {
     fn cannot_be_prefix(prefix_pos: &SrcPos, resolved: ResolvedName, suffix: Suffix) -> Diagnostic {
         let suffix_desc = match suffix {
             Suffix::Selected(_) => "selected",
@@ -1604,22 +1805,9 @@ impl Diagnostic {
         diag
     }
 
-    /// An internal logic error that we want to show to the user to get bug reports
     fn unreachable(pos: &SrcPos, expected: &str) -> Diagnostic {
         Diagnostic::warning(pos, format!("Internal error, unreachable code {expected}"))
     }
-
-    pub fn ambiguous_call<'a>(
-        call_name: &WithPos<Designator>,
-        candidates: impl IntoIterator<Item = OverloadedEnt<'a>>,
-    ) -> Diagnostic {
-        let mut diag = Diagnostic::error(
-            &call_name.pos,
-            format!("Ambiguous call to {}", call_name.item.describe()),
-        );
-        diag.add_subprogram_candidates("Might be", candidates);
-        diag
-    }
 }
 
 fn check_no_attr_argument(suffix: &AttributeSuffix, diagnostics: &mut dyn DiagnosticHandler) {
@@ -2191,6 +2379,14 @@ type arr_t is array (integer range 0 to 3, character range 'a' to 'c') of intege
         );
 
         let code = test.snippet("arr_t'left(1+1)");
+        assert_eq!(
+            test.name_resolve(&code, None, &mut NoDiagnostics),
+            Ok(ResolvedName::Expression(DisambiguatedType::Unambiguous(
+                test.lookup_type("character")
+            )))
+        );
+
+        let code = test.snippet("arr_t'left(0)");
         let mut diagnostics = Vec::new();
         assert_eq!(
             test.name_resolve(&code, None, &mut diagnostics),
@@ -2199,10 +2395,48 @@ type arr_t is array (integer range 0 to 3, character range 'a' to 'c') of intege
         check_diagnostics(
             diagnostics,
             vec![Diagnostic::error(
-                code.s1("1+1"),
-                "Expected an integer literal",
+                code.s1("0"),
+                "Index 0 out of range for array with 2 dimensions, expected 1 to 2",
             )],
-        )
+        );
+    }
+
+    #[test]
+    fn array_attribute_with_non_static_dimension() {
+        let test = TestSetup::new();
+        test.declarative_part(
+            "
+type arr_t is array (integer range 0 to 3, character range 'a' to 'c') of integer;
+type vec_t is array (natural range 0 to 3) of integer;
+constant dim : natural := 2;
+        ",
+        );
+
+        // The dimension is not known without evaluating the constant
+        let code = test.snippet("arr_t'left(dim)");
+        assert_eq!(
+            test.name_resolve(&code, None, &mut NoDiagnostics),
+            Err(EvalError::Unknown)
+        );
+
+        let code = test.snippet("vec_t'left(dim)");
+        assert_eq!(
+            test.name_resolve(&code, None, &mut NoDiagnostics),
+            Ok(ResolvedName::Expression(DisambiguatedType::Unambiguous(
+                test.lookup_type("integer")
+            )))
+        );
+
+        let code = test.snippet("arr_t'left(true)");
+        let mut diagnostics = Vec::new();
+        let _ = test.name_resolve(&code, None, &mut diagnostics);
+        check_diagnostics(
+            diagnostics,
+            vec![Diagnostic::error(
+                code.s1("true"),
+                "Expected integer type, got type 'BOOLEAN'",
+            )],
+        );
     }
 
     #[test]
@@ -2439,41 +2673,46 @@ signal thesig : integer;
         let code = test.snippet("thesig'delayed(0 ns)");
         assert_eq!(
             test.name_resolve(&code, None, &mut NoDiagnostics),
-            Ok(ResolvedName::Expression(DisambiguatedType::Unambiguous(
-                test.ctx().integer()
-            )))
+            Ok(ResolvedName::ObjectName(ObjectName {
+                base: ObjectBase::ImplicitSignal(SignalAttribute::Delayed),
+                type_mark: Some(test.ctx().integer()),
+            }))
         );
 
         let code = test.snippet("thesig'delayed");
         assert_eq!(
             test.name_resolve(&code, None, &mut NoDiagnostics),
-            Ok(ResolvedName::Expression(DisambiguatedType::Unambiguous(
-                test.ctx().integer()
-            )))
+            Ok(ResolvedName::ObjectName(ObjectName {
+                base: ObjectBase::ImplicitSignal(SignalAttribute::Delayed),
+                type_mark: Some(test.ctx().integer()),
+            }))
         );
 
         let code = test.snippet("thesig'stable(0 ns)");
         assert_eq!(
             test.name_resolve(&code, None, &mut NoDiagnostics),
-            Ok(ResolvedName::Expression(DisambiguatedType::Unambiguous(
-                test.ctx().boolean()
-            )))
+            Ok(ResolvedName::ObjectName(ObjectName {
+                base: ObjectBase::ImplicitSignal(SignalAttribute::Stable),
+                type_mark: Some(test.ctx().boolean()),
+            }))
         );
 
         let code = test.snippet("thesig'quiet(0 ns)");
         assert_eq!(
             test.name_resolve(&code, None, &mut NoDiagnostics),
-            Ok(ResolvedName::Expression(DisambiguatedType::Unambiguous(
-                test.ctx().boolean()
-            )))
+            Ok(ResolvedName::ObjectName(ObjectName {
+                base: ObjectBase::ImplicitSignal(SignalAttribute::Quiet),
+                type_mark: Some(test.ctx().boolean()),
+            }))
         );
 
         let code = test.snippet("thesig'transaction");
         assert_eq!(
             test.name_resolve(&code, None, &mut NoDiagnostics),
-            Ok(ResolvedName::Expression(DisambiguatedType::Unambiguous(
-                test.ctx().bit()
-            )))
+            Ok(ResolvedName::ObjectName(ObjectName {
+                base: ObjectBase::ImplicitSignal(SignalAttribute::Transaction),
+                type_mark: Some(test.ctx().bit()),
+            }))
         );
 
         let code = test.snippet("thesig'event");
@@ -2588,6 +2827,34 @@ variable thevar : integer_vector(0 to 1);
         ",
         );
         let code = test.snippet("thevar'subtype");
+        assert_matches!(
+            test.name_resolve(&code, None, &mut NoDiagnostics),
+            Ok(ResolvedName::Type(typ)) if typ == test.lookup_type("integer_vector")
+        );
+    }
+
+    #[test]
+    fn element_attribute() {
+        let test = TestSetup::new();
+        test.declarative_part(
+            "
+variable thevar : integer_vector(0 to 1);
+        ",
+        );
+        assert_matches!(
+            test.name_resolve(&test.snippet("thevar'element"), None, &mut NoDiagnostics),
+            Ok(ResolvedName::Type(typ)) if typ == test.lookup_type("integer")
+        );
+        assert_matches!(
+            test.name_resolve(&test.snippet("integer_vector'element"), None, &mut NoDiagnostics),
+            Ok(ResolvedName::Type(typ)) if typ == test.lookup_type("integer")
+        );
+        assert_matches!(
+            test.name_resolve(&test.snippet("thevar'element(1.0)"), None, &mut NoDiagnostics),
+            Ok(ResolvedName::Expression(DisambiguatedType::Unambiguous(typ))) if typ == test.lookup_type("integer")
+        );
+
+        let code = test.snippet("thevar(0)'element");
         let mut diagnostics = Vec::new();
         assert_eq!(
             test.name_resolve(&code, None, &mut diagnostics),
@@ -2596,8 +2863,26 @@ variable thevar : integer_vector(0 to 1);
         check_diagnostics(
             diagnostics,
             vec![Diagnostic::error(
-                code,
-                "Type cannot be used as an expression",
+                code.s1("thevar(0)"),
+                "array type expected for 'element attribute",
+            )],
+        )
+    }
+
+    #[test]
+    fn subtype_attribute_of_type() {
+        let test = TestSetup::new();
+        let code = test.snippet("integer'subtype");
+        let mut diagnostics = Vec::new();
+        assert_eq!(
+            test.name_resolve(&code, None, &mut diagnostics),
+            Err(EvalError::Unknown)
+        );
+        check_diagnostics(
+            diagnostics,
+            vec![Diagnostic::error(
+                code.s1("integer"),
+                "integer type 'INTEGER' cannot be the the prefix of 'subtype attribute",
             )],
         )
     }
@@ -0,0 +1,202 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this file,
+// You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) 2023, Olof Kraigher olof.kraigher@gmail.com
+
+//! Find output ports that are left open in an instance while the architecture
+//! of the instantiated entity reads their value
+
+use super::named_entity::*;
+use super::region::NamedEntities;
+use super::root::DesignRoot;
+use crate::ast::search::*;
+use crate::ast::*;
+use crate::data::*;
+use fnv::FnvHashMap;
+
+/// An instance that leaves output ports open
+struct OpenPorts {
+    unit: InstanceOf,
+    /// The formals and the positions of their open actuals
+    ports: Vec<(Designator, SrcPos)>,
+}
+
+enum InstanceOf {
+    Entity(EntityId),
+    Component(EntityId),
+}
+
+#[derive(Default)]
+pub(super) struct CheckOpenOutputPorts {
+    instances: Vec<OpenPorts>,
+}
+
+impl CheckOpenOutputPorts {
+    pub fn design_unit(&mut self, unit: &AnyDesignUnit) {
+        if let AnyDesignUnit::Secondary(AnySecondaryUnit::Architecture(architecture)) = unit {
+            self.concurrent_statements(&architecture.statements);
+        }
+    }
+
+    fn concurrent_statements(&mut self, statements: &[LabeledConcurrentStatement]) {
+        for statement in statements.iter() {
+            match statement.statement.item {
+                ConcurrentStatement::Instance(ref instance) => self.instance(instance),
+                ConcurrentStatement::Block(ref block) => {
+                    self.concurrent_statements(&block.statements);
+                }
+                ConcurrentStatement::ForGenerate(ref gen) => {
+                    self.concurrent_statements(&gen.body.statements);
+                }
+                ConcurrentStatement::IfGenerate(ref gen) => {
+                    for body in gen
+                        .conds
+                        .conditionals
+                        .iter()
+                        .map(|cond| &cond.item)
+                        .chain(gen.conds.else_item.iter())
+                    {
+                        self.concurrent_statements(&body.statements);
+                    }
+                }
+                ConcurrentStatement::CaseGenerate(ref gen) => {
+                    for alternative in gen.sels.alternatives.iter() {
+                        self.concurrent_statements(&alternative.item.statements);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn instance(&mut self, instance: &InstantiationStatement) {
+        let unit = match instance.unit {
+            InstantiatedUnit::Entity(ref name, _) => {
+                selected_reference(&name.item).map(InstanceOf::Entity)
+            }
+            InstantiatedUnit::Component(ref name) => {
+                selected_reference(&name.item).map(InstanceOf::Component)
+            }
+            InstantiatedUnit::Configuration(_) => None,
+        };
+        let unit = if let Some(unit) = unit {
+            unit
+        } else {
+            return;
+        };
+
+        let ports: Vec<_> = instance
+            .port_map
+            .iter()
+            .filter_map(|assoc| match (&assoc.formal, &assoc.actual.item) {
+                (Some(formal), ActualPart::Open) => {
+                    if let Name::Designator(designator) = &formal.item {
+                        Some((designator.item.clone(), assoc.actual.pos.clone()))
+                    } else {
+                        None
+                    }
+                }
+                _ => None,
+            })
+            .collect();
+
+        if !ports.is_empty() {
+            self.instances.push(OpenPorts { unit, ports });
+        }
+    }
+
+    /// Hint about output ports that are left open while the entity reads their value
+    pub fn finish(self, root: &DesignRoot, diagnostics: &mut dyn DiagnosticHandler) {
+        if self.instances.is_empty() {
+            return;
+        }
+        let mut reads = FindReads::default();
+        let _ = root.search(&mut reads);
+
+        for OpenPorts { unit, ports } in self.instances {
+            let entity = match unit {
+                InstanceOf::Entity(id) => root.get_ent(id),
+                InstanceOf::Component(id) => {
+                    // Default binding to the entity of the same name
+                    let component = root.get_ent(id);
+                    if let (Some(library_name), Designator::Identifier(ident)) =
+                        (component.library_name(), component.designator())
+                    {
+                        if let Some(entity) = root.get_design_entity(library_name, ident) {
+                            entity.into()
+                        } else {
+                            continue;
+                        }
+                    } else {
+                        continue;
+                    }
+                }
+            };
+            let region = if let AnyEntKind::Design(Design::Entity(_, region)) = entity.kind() {
+                region
+            } else {
+                continue;
+            };
+
+            for (formal, pos) in ports {
+                let port =
+                    if let Some(NamedEntities::Single(port)) = region.lookup_immediate(&formal) {
+                        port
+                    } else {
+                        continue;
+                    };
+                let is_output = matches!(port.kind(), AnyEntKind::Object(object)
+                    if object.mode() == Some(Mode::Out));
+                if !is_output {
+                    continue;
+                }
+                if let Some(read_pos) = reads.reads.get(&port.id()) {
+                    diagnostics.push(
+                        Diagnostic::hint(
+                            &pos,
+                            format!(
+                                "Output port '{}' is left open but its value is used within {}",
+                                port.designator(),
+                                entity.describe()
+                            ),
+                        )
+                        .related(read_pos, "Value used here"),
+                    );
+                }
+            }
+        }
+    }
+}
+
+/// Find the first position where each object is read
+/// Assignment targets and associations are not considered reads since they may
+/// only write the object
+#[derive(Default)]
+struct FindReads {
+    reads: FnvHashMap<EntityId, SrcPos>,
+}
+
+impl Searcher for FindReads {
+    fn search_pos_with_ref(&mut self, pos: &SrcPos, reference: &mut Reference) -> SearchState {
+        if let Some(id) = reference {
+            self.reads.entry(*id).or_insert_with(|| pos.clone());
+        }
+        NotFinished
+    }
+
+    fn search_target(&mut self, _target: &WithPos<Target>) -> SearchState {
+        Finished(NotFound)
+    }
+
+    fn search_association(&mut self, _assoc: &AssociationElement) -> SearchState {
+        Finished(NotFound)
+    }
+}
+
+fn selected_reference(name: &SelectedName) -> Option<EntityId> {
+    match name {
+        SelectedName::Designator(designator) => designator.reference,
+        SelectedName::Selected(_, designator) => designator.item.reference,
+    }
+}
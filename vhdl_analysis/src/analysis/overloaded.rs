@@ -12,6 +12,7 @@ use super::expression::ExpressionType;
 use super::formal_region::InterfaceEnt;
 use super::named_entity::*;
 use super::region::*;
+use super::semantic::SemanticDiagnostics;
 use crate::ast::search::clear_references;
 use crate::ast::*;
 use crate::data::*;
@@ -149,6 +150,60 @@ impl<'a> Candidates<'a> {
     }
 }
 
+/// The reasons the candidates of a call were rejected
+/// Only recorded when enabled since formatting the reasons is not free
+struct Rejections<'a> {
+    enabled: bool,
+    rejected: Vec<(OverloadedEnt<'a>, String)>,
+}
+
+impl<'a> Rejections<'a> {
+    fn reject(&mut self, ent: OverloadedEnt<'a>, reason: impl FnOnce() -> String) {
+        if self.enabled {
+            self.rejected.push((ent, reason()));
+        }
+    }
+
+    /// Reject the candidates that were not kept by a step of the disambiguation
+    fn reject_removed(
+        &mut self,
+        before: &[impl AsRef<OverloadedEnt<'a>>],
+        after: &[impl AsRef<OverloadedEnt<'a>>],
+        reason: impl Fn(OverloadedEnt<'a>) -> String,
+    ) {
+        for ent in before.iter().map(|ent| *ent.as_ref()) {
+            if !after.iter().any(|kept| kept.as_ref().id() == ent.id()) {
+                self.reject(ent, || reason(ent));
+            }
+        }
+    }
+
+    /// The error when no candidate remains
+    /// Explains why each candidate was rejected when enabled, otherwise only lists the candidates of the last step
+    fn could_not_resolve(
+        self,
+        name: &WithPos<Designator>,
+        last: impl IntoIterator<Item = OverloadedEnt<'a>>,
+    ) -> Diagnostic {
+        if !self.enabled {
+            return Diagnostic::could_not_resolve(name, last);
+        }
+
+        let mut diag = Diagnostic::error(
+            &name.pos,
+            format!("Could not resolve call to '{}'", name.designator()),
+        );
+        let mut rejected = self.rejected;
+        rejected.sort_by(|(x, _), (y, _)| x.decl_pos().cmp(&y.decl_pos()));
+        for (ent, reason) in rejected {
+            if let Some(decl_pos) = ent.decl_pos() {
+                diag.add_related(decl_pos, format!("Rejected {}, {}", ent.describe(), reason));
+            }
+        }
+        diag
+    }
+}
+
 impl<'a> Disambiguated<'a> {
     pub fn into_type(self) -> DisambiguatedType<'a> {
         match self {
@@ -216,20 +271,35 @@ impl<'a> AnalyzeContext<'a> {
         call_pos: &SrcPos,
         candidates: &[OverloadedEnt<'a>],
         assocs: &mut [AssociationElement],
+        rejections: &mut Rejections<'a>,
     ) -> EvalResult<Vec<ResolvedCall<'a>>> {
         let mut result = Vec::with_capacity(candidates.len());
         for ent in candidates.iter() {
+            let mut errors = Vec::new();
+            let diagnostics: &mut dyn DiagnosticHandler = if rejections.enabled {
+                &mut errors
+            } else {
+                &mut NullDiagnostics
+            };
+
             if let Some(resolved) = as_fatal(self.resolve_association_formals(
                 call_pos,
                 ent.formals(),
                 scope,
                 assocs,
-                &mut NullDiagnostics,
+                diagnostics,
             ))? {
                 result.push(ResolvedCall {
                     subpgm: *ent,
                     formals: resolved,
                 });
+            } else {
+                rejections.reject(*ent, || {
+                    errors
+                        .first()
+                        .map(|err| decapitalize(&err.message))
+                        .unwrap_or_else(|| "the actuals do not match the formals".to_owned())
+                });
             }
 
             for elem in assocs.iter_mut() {
@@ -281,8 +351,20 @@ impl<'a> AnalyzeContext<'a> {
             return Ok(Disambiguated::Unambiguous(ent));
         }
 
+        let mut rejections = Rejections {
+            enabled: self.root.explain_overloads,
+            rejected: Vec::new(),
+        };
+
         let mut ok_kind = all_overloaded.clone();
         Self::disambiguate_by_kind(&mut ok_kind, kind);
+        rejections.reject_removed(&all_overloaded, &ok_kind, |ent| {
+            if ent.is_procedure() {
+                "a procedure cannot be used as a function".to_owned()
+            } else {
+                "a function cannot be called as a procedure".to_owned()
+            }
+        });
 
         // Does not need disambiguation
         if ok_kind.len() == 1 {
@@ -290,11 +372,12 @@ impl<'a> AnalyzeContext<'a> {
             self.check_call(scope, call_pos, ent, assocs, diagnostics)?;
             return Ok(Disambiguated::Unambiguous(ent));
         } else if ok_kind.is_empty() {
-            diagnostics.push(Diagnostic::could_not_resolve(call_name, all_overloaded));
+            diagnostics.push(rejections.could_not_resolve(call_name, all_overloaded));
             return Err(EvalError::Unknown);
         }
 
-        let ok_formals = self.disambiguate_by_assoc_formals(scope, call_pos, &ok_kind, assocs)?;
+        let ok_formals =
+            self.disambiguate_by_assoc_formals(scope, call_pos, &ok_kind, assocs, &mut rejections)?;
 
         // Only one candidate matched actual/formal profile
         if ok_formals.len() == 1 {
@@ -303,7 +386,7 @@ impl<'a> AnalyzeContext<'a> {
             return Ok(Disambiguated::Unambiguous(ent));
         } else if ok_formals.is_empty() {
             // No candidate matched actual/formal profile
-            diagnostics.push(Diagnostic::could_not_resolve(call_name, ok_kind));
+            diagnostics.push(rejections.could_not_resolve(call_name, ok_kind));
             return Err(EvalError::Unknown);
         }
 
@@ -312,13 +395,33 @@ impl<'a> AnalyzeContext<'a> {
         let mut ok_assoc_types = ok_formals.clone();
         self.implicit_matcher()
             .disambiguate_by_assoc_types(&actual_types, &mut ok_assoc_types);
+        if rejections.enabled {
+            for resolved in ok_formals.iter() {
+                if let Some(idx) = self
+                    .implicit_matcher()
+                    .assoc_type_mismatch(&actual_types, resolved)
+                {
+                    rejections.reject(resolved.subpgm, || {
+                        format!(
+                            "{} does not match {} of argument {}",
+                            actual_types[idx]
+                                .as_ref()
+                                .map(|typ| typ.describe())
+                                .unwrap_or_default(),
+                            resolved.formals[idx].type_mark().describe(),
+                            idx + 1
+                        )
+                    });
+                }
+            }
+        }
 
         if ok_assoc_types.len() == 1 {
             let ent = ok_assoc_types[0].subpgm;
             self.check_call(scope, call_pos, ent, assocs, diagnostics)?;
             return Ok(Disambiguated::Unambiguous(ent));
         } else if ok_assoc_types.is_empty() {
-            diagnostics.push(Diagnostic::could_not_resolve(
+            diagnostics.push(rejections.could_not_resolve(
                 call_name,
                 ok_formals.into_iter().map(|resolved| resolved.subpgm),
             ));
@@ -329,6 +432,15 @@ impl<'a> AnalyzeContext<'a> {
             let mut ok_return_type = ok_assoc_types.clone();
             self.implicit_matcher()
                 .disambiguate_op_by_return_type(&mut ok_return_type, rtyp);
+            rejections.reject_removed(&ok_assoc_types, &ok_return_type, |ent| {
+                format!(
+                    "return {} does not match {}",
+                    ent.return_type()
+                        .map(|typ| typ.describe())
+                        .unwrap_or_default(),
+                    rtyp.map(|typ| typ.describe()).unwrap_or_default()
+                )
+            });
 
             // Only one candidate matches type profile, check it
             if ok_return_type.len() == 1 {
@@ -336,7 +448,7 @@ impl<'a> AnalyzeContext<'a> {
                 self.check_call(scope, call_pos, ent, assocs, diagnostics)?;
                 return Ok(Disambiguated::Unambiguous(ent));
             } else if ok_return_type.is_empty() {
-                diagnostics.push(Diagnostic::could_not_resolve(
+                diagnostics.push(rejections.could_not_resolve(
                     call_name,
                     ok_assoc_types.into_iter().map(|resolved| resolved.subpgm),
                 ));
@@ -398,7 +510,15 @@ impl<'a> AnalyzeContext<'a> {
     }
 }
 
-impl Diagnostic {
+/// Diagnostics about overloaded calls without a candidate
+trait OverloadDiagnostics {
+    fn could_not_resolve<'a>(
+        name: &WithPos<Designator>,
+        rejected: impl IntoIterator<Item = OverloadedEnt<'a>>,
+    ) -> Self;
+}
+
+impl OverloadDiagnostics for Diagnostic {
     fn could_not_resolve<'a>(
         name: &WithPos<Designator>,
         rejected: impl IntoIterator<Item = OverloadedEnt<'a>>,
@@ -412,6 +532,14 @@ impl Diagnostic {
     }
 }
 
+fn decapitalize(message: &str) -> String {
+    let mut chars = message.chars();
+    match chars.next() {
+        Some(first) => first.to_lowercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
 #[cfg(test)]
 mod tests {
 
@@ -648,6 +776,58 @@ function myfun(arg1 : character) return character;
         );
     }
 
+    #[test]
+    fn explains_rejected_candidates() {
+        let test = TestSetup::new().explain_overloads();
+        let decl = test.declarative_part(
+            "
+procedure myfun(arg1 : integer);
+function myfun(arg1 : character) return integer;
+function myfun(arg1, arg2 : integer) return integer;
+function myfun(arg1 : integer) return character;
+function myfun(arg1 : integer) return boolean;
+        ",
+        );
+        let fcall = test.snippet("myfun(0)");
+        let mut diagnostics = Vec::new();
+
+        assert_eq!(
+            test.disambiguate(&fcall, Some(test.lookup_type("integer")), &mut diagnostics),
+            None,
+        );
+
+        check_diagnostics(
+            diagnostics,
+            vec![
+                Diagnostic::error(fcall.s1("myfun"), "Could not resolve call to 'myfun'")
+                    .related(
+                        decl.s1("myfun"),
+                        "Rejected procedure myfun[INTEGER], a procedure cannot be used as a function",
+                    )
+                    .related(
+                        decl.s("myfun", 2),
+                        "Rejected function myfun[CHARACTER return INTEGER], \
+                         expression with type universal_integer does not match type 'CHARACTER' of argument 1",
+                    )
+                    .related(
+                        decl.s("myfun", 3),
+                        "Rejected function myfun[INTEGER, INTEGER return INTEGER], \
+                         no association of parameter 'arg2'",
+                    )
+                    .related(
+                        decl.s("myfun", 4),
+                        "Rejected function myfun[INTEGER return CHARACTER], \
+                         return type 'CHARACTER' does not match integer type 'INTEGER'",
+                    )
+                    .related(
+                        decl.s("myfun", 5),
+                        "Rejected function myfun[INTEGER return BOOLEAN], \
+                         return type 'BOOLEAN' does not match integer type 'INTEGER'",
+                    ),
+            ],
+        )
+    }
+
     #[test]
     fn disambiguates_target_type() {
         let test = TestSetup::new();
@@ -24,6 +24,7 @@ use super::named_entity::Type;
 use super::named_entity::TypeEnt;
 use super::names::ResolvedName;
 use super::region::*;
+use super::semantic::SemanticDiagnostics;
 use super::AnyEntKind;
 use super::EntRef;
 use super::EntityId;
@@ -31,6 +32,7 @@ use super::Related;
 use crate::ast::ActualPart;
 use crate::ast::AssociationElement;
 use crate::ast::Expression;
+use crate::ast::InterfaceType;
 use crate::ast::Literal;
 use crate::ast::Name;
 use crate::ast::Operator;
@@ -40,7 +42,7 @@ use crate::Diagnostic;
 use crate::NullDiagnostics;
 
 impl<'a> AnalyzeContext<'a> {
-    fn package_generic_map(
+    pub(super) fn package_generic_map(
         &self,
         scope: &Scope<'a>,
         generics: GpkgRegion<'a>,
@@ -256,6 +258,42 @@ impl<'a> AnalyzeContext<'a> {
         }
     }
 
+    /// The generic map of an entity or a component with interface type generics is analyzed
+    /// like the generic map of a package instance, returns the ports with the actual types
+    /// substituted for the interface types or None without interface type generics
+    pub fn generic_type_instance(
+        &self,
+        scope: &Scope<'a>,
+        region: &'a Region<'a>,
+        generic_map: &mut [AssociationElement],
+        diagnostics: &mut dyn DiagnosticHandler,
+    ) -> EvalResult<Option<FormalRegion<'a>>> {
+        let (generics, _) = region.to_package_generic();
+        if !generics.has_type() {
+            return Ok(None);
+        }
+
+        let mapping = self.package_generic_map(scope, generics, generic_map, diagnostics)?;
+        let (_, ports) = region.to_entity_formal();
+        Ok(Some(self.instantiate_ports(&mapping, &ports)))
+    }
+
+    /// The ports with the interface type generics replaced by their actual types
+    pub(super) fn instantiate_ports(
+        &self,
+        mapping: &FnvHashMap<EntityId, TypeEnt<'a>>,
+        ports: &FormalRegion<'a>,
+    ) -> FormalRegion<'a> {
+        let mut instantiated = FormalRegion::new(InterfaceType::Port);
+        for port in ports.iter() {
+            match self.instantiate(None, mapping, port.inner()) {
+                Ok(inst) => instantiated.add(inst),
+                Err(_) => instantiated.add(port.inner()),
+            }
+        }
+        instantiated
+    }
+
     fn instantiate(
         &self,
         parent: Option<EntRef<'a>>,
@@ -401,6 +439,7 @@ impl<'a> AnalyzeContext<'a> {
         let Signature {
             formals,
             return_type,
+            impure,
         } = signature;
 
         let FormalRegion {
@@ -427,6 +466,7 @@ impl<'a> AnalyzeContext<'a> {
                 entities: inst_entities,
             },
             return_type: return_type.map(|typ| self.map_type_ent(mapping, typ)),
+            impure: *impure,
         })
     }
 
@@ -523,6 +563,7 @@ impl<'a> AnalyzeContext<'a> {
             class,
             iface,
             subtype,
+            signal_kind,
             has_default,
         } = obj;
 
@@ -530,6 +571,7 @@ impl<'a> AnalyzeContext<'a> {
             class: *class,
             iface: *iface,
             subtype: self.map_subtype(mapping, *subtype)?,
+            signal_kind: *signal_kind,
             has_default: *has_default,
         })
     }
@@ -547,10 +589,14 @@ impl<'a> AnalyzeContext<'a> {
         mapping: &FnvHashMap<EntityId, TypeEnt<'a>>,
         subtype: Subtype<'a>,
     ) -> Result<Subtype<'a>, String> {
-        let Subtype { type_mark } = subtype;
+        let Subtype {
+            type_mark,
+            has_resolution,
+        } = subtype;
 
         Ok(Subtype {
             type_mark: self.map_type_ent(mapping, type_mark),
+            has_resolution,
         })
     }
 }
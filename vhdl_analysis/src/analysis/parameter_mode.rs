@@ -0,0 +1,213 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this file,
+// You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) 2023, Olof Kraigher olof.kraigher@gmail.com
+
+//! Check how the parameters of a subprogram are used within its body
+//! Parameters of mode in may not be associated with formals that are written,
+//! parameters of mode out may not be read before VHDL-2008 and a signal parameter
+//! may not be associated with a variable formal or vice-versa
+
+use fnv::FnvHashSet;
+
+use super::analyze::*;
+use super::formal_region::InterfaceEnt;
+use super::named_entity::*;
+use crate::ast::search::*;
+use crate::ast::*;
+use crate::config::VHDLStandard;
+use crate::data::*;
+
+impl<'a> AnalyzeContext<'a> {
+    pub(super) fn check_parameter_modes(
+        &self,
+        subprogram: OverloadedEnt<'a>,
+        body: &mut SubprogramBody,
+        diagnostics: &mut dyn DiagnosticHandler,
+    ) {
+        let mut searcher = CheckParameterModes {
+            context: self,
+            subprogram,
+            not_read: FnvHashSet::default(),
+            diagnostics,
+        };
+        let _ = body.declarations.search(&mut searcher);
+        let _ = body.statements.search(&mut searcher);
+    }
+}
+
+/// The position and reference of the object prefix of a name
+fn base_name<'n>(pos: &'n SrcPos, name: &'n Name) -> Option<(&'n SrcPos, Reference)> {
+    match name {
+        Name::Designator(designator) => Some((pos, designator.reference)),
+        Name::Selected(prefix, _) | Name::Slice(prefix, _) => base_name(&prefix.pos, &prefix.item),
+        Name::CallOrIndexed(call) => base_name(&call.name.pos, &call.name.item),
+        _ => None,
+    }
+}
+
+struct CheckParameterModes<'c, 'a, 'd> {
+    context: &'c AnalyzeContext<'a>,
+    subprogram: OverloadedEnt<'a>,
+    /// The positions of parameter names that are written rather than read
+    not_read: FnvHashSet<SrcPos>,
+    diagnostics: &'d mut dyn DiagnosticHandler,
+}
+
+impl<'c, 'a, 'd> CheckParameterModes<'c, 'a, 'd> {
+    /// A parameter of the subprogram being checked
+    fn parameter(&self, reference: Reference) -> Option<(EntRef<'a>, &'a Object<'a>)> {
+        let ent = self.context.arena.get(reference?);
+        match ent.kind() {
+            AnyEntKind::Object(
+                object @ Object {
+                    iface: Some(ObjectInterface::Parameter(_)),
+                    ..
+                },
+            ) if ent
+                .parent
+                .is_some_and(|parent| parent.id() == self.subprogram.id()) =>
+            {
+                Some((ent, object))
+            }
+            _ => None,
+        }
+    }
+
+    fn not_read(&mut self, pos: &SrcPos, name: &Name) {
+        if let Some((pos, reference)) = base_name(pos, name) {
+            if self.parameter(reference).is_some() {
+                self.not_read.insert(pos.clone());
+            }
+        }
+    }
+
+    /// Check the actual associated with a formal of a procedure call
+    fn actual(&mut self, formal: InterfaceEnt<'a>, actual: &WithPos<ActualPart>) {
+        let formal_object = if let AnyEntKind::Object(object) = formal.kind() {
+            object
+        } else {
+            return;
+        };
+        let name = if let ActualPart::Expression(Expression::Name(ref name)) = actual.item {
+            name
+        } else {
+            return;
+        };
+        let (pos, reference) = if let Some(base) = base_name(&actual.pos, name) {
+            base
+        } else {
+            return;
+        };
+        let (ent, object) = if let Some(parameter) = self.parameter(reference) {
+            parameter
+        } else {
+            return;
+        };
+
+        let written_mode = match formal_object.mode() {
+            Some(mode @ (Mode::Out | Mode::InOut | Mode::Buffer)) => Some(mode),
+            _ => None,
+        };
+        if written_mode.is_some() {
+            self.not_read.insert(pos.clone());
+        }
+
+        if let (Some(mode), Some(Mode::In)) = (written_mode, object.mode()) {
+            self.diagnostics.error(
+                pos,
+                format!(
+                    "Cannot associate parameter '{}' of mode in with formal '{}' of mode {}",
+                    ent.designator(),
+                    formal.designator(),
+                    mode
+                ),
+            );
+        } else if matches!(
+            (object.class, formal_object.class),
+            (ObjectClass::Signal, ObjectClass::Variable)
+                | (ObjectClass::Variable, ObjectClass::Signal)
+        ) {
+            self.diagnostics.error(
+                pos,
+                format!(
+                    "Cannot associate {} parameter '{}' with {} formal '{}'",
+                    object.class,
+                    ent.designator(),
+                    formal_object.class,
+                    formal.designator()
+                ),
+            );
+        }
+    }
+}
+
+impl<'c, 'a, 'd> Searcher for CheckParameterModes<'c, 'a, 'd> {
+    fn search_target(&mut self, target: &WithPos<Target>) -> SearchState {
+        if let Target::Name(ref name) = target.item {
+            self.not_read(&target.pos, name);
+        }
+        NotFinished
+    }
+
+    fn search_attribute(&mut self, attr: &AttributeName) -> SearchState {
+        // Attributes such as 'length do not read the value of the prefix
+        self.not_read(&attr.name.pos, &attr.name.item);
+        NotFinished
+    }
+
+    fn search_call_or_indexed(&mut self, call: &CallOrIndexed) -> SearchState {
+        let reference = if let Some((_, reference)) = base_name(&call.name.pos, &call.name.item) {
+            reference
+        } else {
+            return NotFinished;
+        };
+        let procedure = if let Some(ent) =
+            reference.and_then(|id| OverloadedEnt::from_any(self.context.arena.get(id)))
+        {
+            ent
+        } else {
+            return NotFinished;
+        };
+        if !procedure.is_procedure() {
+            return NotFinished;
+        }
+
+        for (idx, assoc) in call.parameters.iter().enumerate() {
+            let formal = match assoc.formal {
+                Some(WithPos {
+                    item: Name::Designator(ref designator),
+                    ..
+                }) => designator
+                    .reference
+                    .and_then(|id| InterfaceEnt::from_any(self.context.arena.get(id))),
+                Some(_) => None,
+                None => procedure.formals().nth(idx),
+            };
+            if let Some(formal) = formal {
+                self.actual(formal, &assoc.actual);
+            }
+        }
+        NotFinished
+    }
+
+    fn search_pos_with_ref(&mut self, pos: &SrcPos, reference: &mut Reference) -> SearchState {
+        if self.context.root.standard >= VHDLStandard::VHDL2008 || self.not_read.contains(pos) {
+            return NotFinished;
+        }
+
+        if let Some((ent, object)) = self.parameter(*reference) {
+            if object.mode() == Some(Mode::Out) {
+                self.diagnostics.error(
+                    pos,
+                    format!(
+                        "Cannot read parameter '{}' of mode out before VHDL-2008",
+                        ent.designator()
+                    ),
+                );
+            }
+        }
+        NotFinished
+    }
+}
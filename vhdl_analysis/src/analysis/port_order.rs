@@ -0,0 +1,199 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this file,
+// You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) 2023, Olof Kraigher olof.kraigher@gmail.com
+
+//! Reorder the ports of an entity while preserving the behavior of its instances
+
+use super::named_entity::*;
+use super::root::DesignRoot;
+use crate::ast::search::*;
+use crate::ast::*;
+use crate::data::*;
+use crate::syntax::{port_list_text, InterfaceDeclarationText};
+
+/// Rewrite the port list of an entity in a new order
+/// The new order lists the index of each port in the current order
+/// None when the order is not a reordering of the ports
+pub(super) fn reorder_port_list(
+    root: &DesignRoot,
+    entity: EntRef,
+    order: &[usize],
+) -> Option<Vec<TextEdit>> {
+    let library_name = entity.library_name()?;
+    if !matches!(entity.kind(), AnyEntKind::Design(Design::Entity(..))) {
+        return None;
+    }
+
+    let mut searcher = FindEntityPorts {
+        entity: entity.id(),
+        result: None,
+    };
+    let _ = root.search_library(library_name, &mut searcher);
+    let (entity_start, ports) = searcher.result?;
+
+    let mut sorted = order.to_vec();
+    sorted.sort_unstable();
+    if !sorted.iter().copied().eq(0..ports.len()) {
+        return None;
+    }
+    if order.iter().copied().eq(0..ports.len()) {
+        // Already in this order
+        return None;
+    }
+
+    if !ports
+        .iter()
+        .all(|port| matches!(port, InterfaceDeclaration::Object(..)))
+    {
+        return None;
+    }
+
+    let list = port_list_text(&root.symbols, &entity_start.source, entity_start.start())?;
+    // The declaration and the index within its identifiers of each port
+    let mut declaration_of = Vec::with_capacity(ports.len());
+    for (decl_idx, declaration) in list.declarations.iter().enumerate() {
+        for ident_idx in 0..declaration.idents.len() {
+            declaration_of.push((decl_idx, ident_idx));
+        }
+    }
+    if declaration_of.len() != ports.len() {
+        return None;
+    }
+
+    let indent: String = list
+        .pos
+        .source
+        .contents()
+        .get_line(list.pos.start().line as usize)
+        .unwrap_or_default()
+        .chars()
+        .take_while(|chr| *chr == ' ' || *chr == '\t')
+        .collect();
+
+    // The source text of each declaration is moved with its comments, a declaration of
+    // several ports such as `clk, rst : in bit` is only split when they are separated
+    let mut reordered: Vec<Reordered> = Vec::new();
+    let mut idx = 0;
+    while idx < order.len() {
+        let (decl_idx, ident_idx) = declaration_of[order[idx]];
+        let declaration = &list.declarations[decl_idx];
+        let count = declaration.idents.len();
+        let whole = ident_idx == 0
+            && (0..count).all(|offset| {
+                order.get(idx + offset).map(|port| declaration_of[*port])
+                    == Some((decl_idx, offset))
+            });
+        if whole {
+            reordered.push(Reordered {
+                declaration,
+                text: declaration.text.clone(),
+                leading: true,
+                trailing: true,
+            });
+            idx += count;
+        } else {
+            reordered.push(Reordered {
+                declaration,
+                text: format!("{} {}", declaration.idents[ident_idx], declaration.subtype),
+                leading: ident_idx == 0,
+                trailing: ident_idx + 1 == count,
+            });
+            idx += 1;
+        }
+    }
+
+    let mut lines = Vec::new();
+    for (idx, item) in reordered.iter().enumerate() {
+        if item.leading {
+            for comment in item.declaration.leading_comments.iter() {
+                lines.push(format!("{indent}  {comment}"));
+            }
+        }
+        let separator = if idx + 1 < reordered.len() { ";" } else { "" };
+        let mut line = format!("{indent}  {}{separator}", item.text);
+        let mut trailing = if item.trailing {
+            item.declaration.trailing_comments.iter()
+        } else {
+            [].iter()
+        };
+        if let Some(comment) = trailing.next() {
+            line.push(' ');
+            line.push_str(comment);
+        }
+        lines.push(line);
+        // A comment to the end of the line cannot be followed by another on the same line
+        for comment in trailing {
+            lines.push(format!("{indent}  {comment}"));
+        }
+    }
+    for comment in list.closing_comments.iter() {
+        lines.push(format!("{indent}  {comment}"));
+    }
+    let opening = match list.opening_comment {
+        Some(ref comment) => format!("( {comment}"),
+        None => "(".to_owned(),
+    };
+
+    Some(vec![TextEdit::replace(
+        &list.pos,
+        format!("{opening}\n{}\n{indent})", lines.join("\n")),
+    )])
+}
+
+/// A declaration of the reordered port list
+struct Reordered<'a> {
+    declaration: &'a InterfaceDeclarationText,
+    text: String,
+    /// If the comments before the declaration are kept with this part of it
+    leading: bool,
+    /// If the comments after the declaration are kept with this part of it
+    trailing: bool,
+}
+
+/// Group the ports of an entity by mode in the order in, out, inout, buffer and linkage
+/// keeping the order of the ports with the same mode
+pub(super) fn ports_grouped_by_mode(root: &DesignRoot, entity: EntRef) -> Option<Vec<usize>> {
+    let library_name = entity.library_name()?;
+    let mut searcher = FindEntityPorts {
+        entity: entity.id(),
+        result: None,
+    };
+    let _ = root.search_library(library_name, &mut searcher);
+    let (_, ports) = searcher.result?;
+
+    let mut order: Vec<usize> = (0..ports.len()).collect();
+    order.sort_by_key(|idx| match &ports[*idx] {
+        InterfaceDeclaration::Object(object) => match object.mode {
+            Mode::In => 0,
+            Mode::Out => 1,
+            Mode::InOut => 2,
+            Mode::Buffer => 3,
+            Mode::Linkage => 4,
+        },
+        _ => 5,
+    });
+    Some(order)
+}
+
+/// Find the position of an entity declaration and its ports
+struct FindEntityPorts {
+    entity: EntityId,
+    result: Option<(SrcPos, Vec<InterfaceDeclaration>)>,
+}
+
+impl Searcher for FindEntityPorts {
+    fn search_decl(&mut self, decl: FoundDeclaration) -> SearchState {
+        match decl {
+            FoundDeclaration::Entity(entity) if entity.ident.decl == Some(self.entity) => {
+                self.result = Some((
+                    entity.ident.tree.pos.clone(),
+                    entity.port_clause.clone().unwrap_or_default(),
+                ));
+                Finished(Found)
+            }
+            _ => NotFinished,
+        }
+    }
+}
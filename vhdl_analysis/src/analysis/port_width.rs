@@ -0,0 +1,210 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this file,
+// You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) 2023, Olof Kraigher olof.kraigher@gmail.com
+
+//! Check that the signals associated with the ports of an entity instance have the width
+//! of the port within that instance. The width of a port is evaluated with the values of
+//! the generic map of the instance rather than with the defaults of the generics
+
+use super::constant_value::ConstantValues;
+use super::root::DesignRoot;
+use crate::ast::*;
+use crate::data::*;
+use fnv::FnvHashMap;
+use std::ops::Deref;
+
+pub(super) struct CheckPortWidths<'a> {
+    root: &'a DesignRoot,
+    diagnostics: Vec<Diagnostic>,
+}
+
+/// The signals and the entity instances of an architecture
+#[derive(Default)]
+struct Contents<'u> {
+    signals: FnvHashMap<EntityId, &'u SubtypeIndication>,
+    instances: Vec<&'u InstantiationStatement>,
+}
+
+impl<'u> Contents<'u> {
+    fn declarations(&mut self, decls: &'u [Declaration]) {
+        for decl in decls.iter() {
+            if let Declaration::Object(object) = decl {
+                if let (ObjectClass::Signal, Some(id)) = (object.class, object.ident.decl) {
+                    self.signals.insert(id, &object.subtype_indication);
+                }
+            }
+        }
+    }
+
+    fn concurrent_statements(&mut self, statements: &'u [LabeledConcurrentStatement]) {
+        for statement in statements.iter() {
+            match statement.statement.item {
+                ConcurrentStatement::Instance(ref instance) => self.instances.push(instance),
+                ConcurrentStatement::Block(ref block) => {
+                    self.declarations(&block.decl);
+                    self.concurrent_statements(&block.statements);
+                }
+                ConcurrentStatement::ForGenerate(ref gen) => self.generate_body(&gen.body),
+                ConcurrentStatement::IfGenerate(ref gen) => {
+                    for body in gen
+                        .conds
+                        .conditionals
+                        .iter()
+                        .map(|cond| &cond.item)
+                        .chain(gen.conds.else_item.iter())
+                    {
+                        self.generate_body(body);
+                    }
+                }
+                ConcurrentStatement::CaseGenerate(ref gen) => {
+                    for alternative in gen.sels.alternatives.iter() {
+                        self.generate_body(&alternative.item);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn generate_body(&mut self, body: &'u GenerateBody) {
+        if let Some(ref decl) = body.decl {
+            self.declarations(decl);
+        }
+        self.concurrent_statements(&body.statements);
+    }
+}
+
+impl<'a> CheckPortWidths<'a> {
+    pub fn new(root: &'a DesignRoot) -> Self {
+        Self {
+            root,
+            diagnostics: Vec::new(),
+        }
+    }
+
+    pub fn design_unit(&mut self, unit: &AnyDesignUnit) {
+        let architecture =
+            if let AnyDesignUnit::Secondary(AnySecondaryUnit::Architecture(architecture)) = unit {
+                architecture
+            } else {
+                return;
+            };
+
+        let mut contents = Contents::default();
+        contents.declarations(&architecture.decl);
+        contents.concurrent_statements(&architecture.statements);
+        if contents.instances.is_empty() {
+            return;
+        }
+
+        let mut values = self.root.constant_values.clone();
+        values.local_declarations(self.root, &architecture.decl);
+        for instance in contents.instances.iter() {
+            self.instance(&values, &contents.signals, instance);
+        }
+    }
+
+    fn instance(
+        &mut self,
+        values: &ConstantValues,
+        signals: &FnvHashMap<EntityId, &SubtypeIndication>,
+        instance: &InstantiationStatement,
+    ) {
+        let id = match instance.unit {
+            InstantiatedUnit::Entity(ref name, _) => selected_reference(&name.item),
+            _ => None,
+        };
+        let ent = if let Some(id) = id {
+            self.root.get_ent(id)
+        } else {
+            return;
+        };
+        let (library_name, entity_name) = match (ent.library_name(), ent.designator()) {
+            (Some(library_name), Designator::Identifier(entity_name)) => {
+                (library_name, entity_name)
+            }
+            _ => return,
+        };
+        let unit = if let Some(unit) = self
+            .root
+            .get_library_units(library_name)
+            .and_then(|units| units.get(&UnitKey::Primary(entity_name.clone())))
+        {
+            unit.unit.expect_analyzed()
+        } else {
+            return;
+        };
+        let entity = if let AnyDesignUnit::Primary(AnyPrimaryUnit::Entity(entity)) = unit.deref() {
+            entity
+        } else {
+            return;
+        };
+
+        // The values of the generics within this instance
+        let mut actuals = FnvHashMap::default();
+        values.generic_map_actuals(self.root, entity, &instance.generic_map, &mut actuals);
+        let mut instance_values = values.clone();
+        instance_values.entity_instance(self.root, entity, &actuals);
+
+        let ports: Vec<_> = entity
+            .port_clause
+            .iter()
+            .flatten()
+            .filter_map(|port| match port {
+                InterfaceDeclaration::Object(object) => Some(object),
+                _ => None,
+            })
+            .collect();
+        for (idx, assoc) in instance.port_map.iter().enumerate() {
+            let port = match assoc.formal {
+                Some(ref formal) => match formal.item {
+                    Name::Designator(ref designator) => ports.iter().find(|port| {
+                        designator.item == Designator::Identifier(port.ident.tree.item.clone())
+                    }),
+                    _ => None,
+                },
+                None => ports.get(idx),
+            };
+            let signal = match assoc.actual.item {
+                ActualPart::Expression(Expression::Name(ref name)) => match name.deref() {
+                    Name::Designator(designator) => designator
+                        .reference
+                        .and_then(|id| Some((id, *signals.get(&id)?))),
+                    _ => None,
+                },
+                _ => None,
+            };
+
+            if let (Some(port), Some((signal, subtype))) = (port, signal) {
+                let port_width =
+                    instance_values.subtype_length(self.root, &port.subtype_indication);
+                let signal_width = values.subtype_length(self.root, subtype);
+                if let (Some(port_width), Some(signal_width)) = (port_width, signal_width) {
+                    if port_width != signal_width {
+                        self.diagnostics.push(Diagnostic::error(
+                            &assoc.actual.pos,
+                            format!(
+                                "Signal '{}' of width {signal_width} is associated with port '{}' of width {port_width}",
+                                self.root.get_ent(signal).designator(),
+                                port.ident.tree.item
+                            ),
+                        ));
+                    }
+                }
+            }
+        }
+    }
+
+    pub fn finish(self, diagnostics: &mut dyn DiagnosticHandler) {
+        diagnostics.append(self.diagnostics);
+    }
+}
+
+fn selected_reference(name: &SelectedName) -> Option<EntityId> {
+    match name {
+        SelectedName::Designator(designator) => designator.reference,
+        SelectedName::Selected(_, designator) => designator.item.reference,
+    }
+}
@@ -0,0 +1,145 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this file,
+// You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) 2023, Olof Kraigher olof.kraigher@gmail.com
+
+//! Check that pure functions do not refer to objects declared outside of them
+//! and do not call impure functions
+
+use super::analyze::*;
+use super::named_entity::*;
+use crate::ast::search::*;
+use crate::ast::*;
+use crate::data::*;
+
+impl<'a> AnalyzeContext<'a> {
+    /// Report the impure actions of the body of a pure function
+    pub(super) fn check_function_purity(
+        &self,
+        function: OverloadedEnt<'a>,
+        body: &mut SubprogramBody,
+        diagnostics: &mut dyn DiagnosticHandler,
+    ) {
+        if !function.is_function() || function.signature().is_impure() {
+            return;
+        }
+
+        // The methods of a protected type operate on the variables of the protected type
+        if function
+            .parent
+            .is_some_and(|parent| parent.is_protected_type_body())
+        {
+            return;
+        }
+
+        let mut searcher = FindImpureReferences {
+            context: self,
+            function,
+            diagnostics,
+        };
+        let _ = body.declarations.search(&mut searcher);
+        let _ = body.statements.search(&mut searcher);
+    }
+}
+
+/// Objects other than constants, as well as files and external names
+fn is_impure_object(ent: EntRef) -> bool {
+    match ent.kind() {
+        AnyEntKind::Object(object) => object.class != ObjectClass::Constant,
+        AnyEntKind::ObjectAlias { base_object, .. } => is_impure_object(base_object.ent),
+        AnyEntKind::ExternalAlias { .. } | AnyEntKind::File(_) => true,
+        _ => false,
+    }
+}
+
+/// True if the entity is declared within the declarative region of the function
+fn is_declared_within(ent: EntRef, function: EntRef) -> bool {
+    let mut parent = ent.parent;
+    while let Some(ent) = parent {
+        if ent.id() == function.id() {
+            return true;
+        }
+        parent = ent.parent;
+    }
+    false
+}
+
+/// The formal of a named association refers to a parameter of the called subprogram
+fn is_formal_of_callee(ent: EntRef, function: EntRef) -> bool {
+    if !matches!(
+        ent.kind(),
+        AnyEntKind::Object(Object {
+            iface: Some(ObjectInterface::Parameter(_)),
+            ..
+        })
+    ) {
+        return false;
+    }
+
+    // Parameters of the enclosing subprograms are not formals
+    let subprogram = ent.parent;
+    let mut parent = Some(function);
+    while let Some(ent) = parent {
+        if subprogram.is_some_and(|subprogram| subprogram.id() == ent.id()) {
+            return false;
+        }
+        parent = ent.parent;
+    }
+    true
+}
+
+struct FindImpureReferences<'c, 'a, 'd> {
+    context: &'c AnalyzeContext<'a>,
+    function: OverloadedEnt<'a>,
+    diagnostics: &'d mut dyn DiagnosticHandler,
+}
+
+impl<'c, 'a, 'd> Searcher for FindImpureReferences<'c, 'a, 'd> {
+    fn search_pos_with_ref(&mut self, pos: &SrcPos, reference: &mut Reference) -> SearchState {
+        let ent = if let Some(id) = reference {
+            self.context.arena.get(*id)
+        } else {
+            return NotFinished;
+        };
+
+        if let AnyEntKind::Overloaded(overloaded) = ent.kind() {
+            if overloaded.signature().is_impure() {
+                self.diagnostics.error(
+                    pos,
+                    format!(
+                        "Pure function '{}' cannot call impure function '{}'",
+                        self.function.designator(),
+                        ent.designator()
+                    ),
+                );
+            }
+        } else if is_impure_object(ent) {
+            let declared = if let AnyEntKind::ObjectAlias { base_object, .. } = ent.kind() {
+                base_object.ent
+            } else {
+                ent
+            };
+            if !is_declared_within(declared, self.function.into())
+                && !is_formal_of_callee(declared, self.function.into())
+            {
+                self.diagnostics.error(
+                    pos,
+                    format!(
+                        "Pure function '{}' cannot reference {} declared outside of it",
+                        self.function.designator(),
+                        describe_object(ent)
+                    ),
+                );
+            }
+        }
+        NotFinished
+    }
+}
+
+fn describe_object(ent: EntRef) -> String {
+    match ent.kind() {
+        AnyEntKind::Object(object) => format!("{} '{}'", object.class, ent.designator()),
+        _ => format!("{} '{}'", ent.kind().describe(), ent.designator()),
+    }
+}
@@ -12,6 +12,7 @@ use super::names::ResolvedName;
 use super::overloaded::Disambiguated;
 use super::overloaded::DisambiguatedType;
 use super::region::*;
+use super::semantic::SemanticDiagnostics;
 use crate::ast::Range;
 use crate::ast::*;
 use crate::data::*;
@@ -117,12 +118,12 @@ impl<'a> AnalyzeContext<'a> {
         };
 
         if let Some((_, indexes)) = typ.array_type() {
-            if let Some(first) = indexes.first().unwrap() {
-                Ok(*first)
-            } else {
-                // There was probably an error in the type definition of this signal/variable
-                Err(EvalError::Unknown)
-            }
+            self.array_index_expression_in_attribute(
+                scope,
+                indexes,
+                attr.expr.as_mut().map(|expr| expr.as_mut()),
+                diagnostics,
+            )
         } else {
             diagnostics.error(
                 &attr.name.pos,
@@ -287,9 +288,11 @@ impl<'a> AnalyzeContext<'a> {
                     attr, // Parser ensures this must be 'range or we would not end up here
                 } = name.as_mut();
 
+                let mut prefix_desc = None;
                 let prefix_typ = as_fatal(
                     self.name_resolve(scope, &name.pos, &mut name.item, diagnostics)
                         .and_then(|prefix| {
+                            prefix_desc = Some(prefix.describe());
                             prefix.as_type_of_attr_prefix(
                                 &name.pos,
                                 &AttributeSuffix {
@@ -313,6 +316,7 @@ impl<'a> AnalyzeContext<'a> {
                     if let Some((_, indexes)) = prefix_typ.array_type() {
                         if let Some(index_typ) =
                             as_fatal(self.array_index_expression_in_attribute(
+                                scope,
                                 indexes,
                                 expr.as_mut().map(|expr| expr.as_mut()),
                                 diagnostics,
@@ -326,6 +330,14 @@ impl<'a> AnalyzeContext<'a> {
                                 ))
                             }
                         }
+                    } else {
+                        diagnostics.error(
+                            &name.pos,
+                            format!(
+                                "{} cannot be prefix of range attribute, array type or object is required",
+                                prefix_desc.unwrap_or_else(|| prefix_typ.describe())
+                            ),
+                        );
                     }
                 }
             }
@@ -644,10 +644,17 @@ impl<'a> Region<'a> {
         for ent in self.entities.values() {
             if let AnyEntKind::Type(Type::Protected(_, has_body)) = ent.first_kind() {
                 if !has_body {
-                    ent.first().error(
-                        diagnostics,
-                        format!("Missing body for protected type '{}'", ent.designator()),
-                    );
+                    let ent = ent.first();
+                    if let Some(decl_pos) = ent.decl_pos() {
+                        let mut diagnostic = Diagnostic::error(
+                            decl_pos,
+                            format!("Missing body for protected type '{}'", ent.designator()),
+                        );
+                        if let Some(decl_span) = ent.decl_span() {
+                            diagnostic.add_related(decl_span, "Declared here");
+                        }
+                        diagnostics.push(diagnostic);
+                    }
                 }
             }
         }
@@ -0,0 +1,360 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this file,
+// You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) 2023, Olof Kraigher olof.kraigher@gmail.com
+
+//! Warn when a registered signal is reset to another value than its initial value
+//! Simulation starts from the initial value while synthesis typically only honors the reset value
+//! Also find registered signals with an initial value that are never reset
+
+use super::named_entity::EntityId;
+use super::static_expression::bit_string_to_string;
+use crate::ast::*;
+use crate::data::*;
+use fnv::{FnvHashMap, FnvHashSet};
+
+/// A value that can be compared without evaluating expressions
+#[derive(PartialEq, Eq, Debug)]
+enum StaticValue {
+    Character(u8),
+    Integer(u64),
+    /// The characters of a string or the bits of a bit string
+    String(Vec<u8>),
+    /// An aggregate with only an others choice such as `(others => '0')`
+    Others(Box<StaticValue>),
+    /// A name such as an enumeration literal or a constant
+    Named(EntityId),
+}
+
+fn static_value(expr: &Expression) -> Option<StaticValue> {
+    match expr {
+        Expression::Literal(Literal::Character(chr)) => Some(StaticValue::Character(*chr)),
+        Expression::Literal(Literal::AbstractLiteral(AbstractLiteral::Integer(value))) => {
+            Some(StaticValue::Integer(*value))
+        }
+        Expression::Literal(Literal::String(value)) => {
+            Some(StaticValue::String(value.bytes.clone()))
+        }
+        Expression::Literal(Literal::BitString(value)) => bit_string_to_string(value)
+            .ok()
+            .map(|value| StaticValue::String(value.bytes)),
+        Expression::Aggregate(assocs) => match assocs.as_slice() {
+            [ElementAssociation::Named(choices, expr)] if matches!(choices.as_slice(), [choice] if choice.item == Choice::Others) => {
+                static_value(&expr.item).map(|value| StaticValue::Others(Box::new(value)))
+            }
+            _ => None,
+        },
+        Expression::Name(name) => match name.as_ref() {
+            Name::Designator(designator) => designator.reference.map(StaticValue::Named),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Only values that are known to differ are reported
+fn values_differ(left: &StaticValue, right: &StaticValue) -> bool {
+    match (left, right) {
+        (StaticValue::Others(others), StaticValue::String(string))
+        | (StaticValue::String(string), StaticValue::Others(others)) => {
+            if let StaticValue::Character(chr) = others.as_ref() {
+                string.iter().any(|byte| byte != chr)
+            } else {
+                false
+            }
+        }
+        (StaticValue::Others(left), StaticValue::Others(right)) => values_differ(left, right),
+        (StaticValue::Character(_), StaticValue::Character(_))
+        | (StaticValue::Integer(_), StaticValue::Integer(_))
+        | (StaticValue::String(_), StaticValue::String(_))
+        | (StaticValue::Named(_), StaticValue::Named(_)) => left != right,
+        _ => false,
+    }
+}
+
+fn is_edge_function(name: &Name) -> bool {
+    if let Name::Designator(designator) = name {
+        if let Designator::Identifier(sym) = &designator.item {
+            let name = sym.name_utf8();
+            return name.eq_ignore_ascii_case("rising_edge")
+                || name.eq_ignore_ascii_case("falling_edge");
+        }
+    }
+    false
+}
+
+fn is_event_attribute(expr: &Expression) -> bool {
+    if let Expression::Name(name) = expr {
+        if let Name::Attribute(attr) = name.as_ref() {
+            return attr.attr.item == AttributeDesignator::Signal(SignalAttribute::Event);
+        }
+    }
+    false
+}
+
+/// A condition such as `rising_edge(clk)` or `clk'event and clk = '1'`
+pub(super) fn is_clock_edge(expr: &Expression) -> bool {
+    match expr {
+        Expression::Name(name) => match name.as_ref() {
+            Name::CallOrIndexed(call) => is_edge_function(&call.name.item),
+            _ => false,
+        },
+        Expression::Binary(op, left, right) if op.item.item == Operator::And => {
+            is_event_attribute(&left.item) || is_event_attribute(&right.item)
+        }
+        _ => false,
+    }
+}
+
+/// The signal and the value of a simple assignment such as `sig <= '0'`
+fn simple_assignment(statement: &SequentialStatement) -> Option<(EntityId, &WithPos<Expression>)> {
+    if let SequentialStatement::SignalAssignment(assignment) = statement {
+        if let (
+            Target::Name(Name::Designator(designator)),
+            AssignmentRightHand::Simple(Waveform::Elements(elements)),
+        ) = (&assignment.target.item, &assignment.rhs)
+        {
+            if let [WaveformElement { value, after: None }] = elements.as_slice() {
+                return Some((designator.reference?, value));
+            }
+        }
+    }
+    None
+}
+
+/// A branch that only assigns static values to signals
+fn is_reset_branch(statements: &[LabeledSequentialStatement]) -> bool {
+    !statements.is_empty()
+        && statements.iter().all(|statement| {
+            simple_assignment(&statement.statement.item)
+                .is_some_and(|(_, value)| static_value(&value.item).is_some())
+        })
+}
+
+/// Find the clocked branch of a clocked process and its reset branch if any
+/// Either an asynchronous reset as in `if rst = '1' then ... elsif rising_edge(clk) then`
+/// or a synchronous reset as in `if rising_edge(clk) then if rst = '1' then ... else`
+fn clocked_branch(
+    statements: &[LabeledSequentialStatement],
+) -> Option<(
+    &[LabeledSequentialStatement],
+    Option<&[LabeledSequentialStatement]>,
+)> {
+    for statement in statements {
+        if let SequentialStatement::If(ref ifstmt) = statement.statement.item {
+            match ifstmt.conds.conditionals.as_slice() {
+                [reset, clocked] if is_clock_edge(&clocked.condition.item) => {
+                    let reset = Some(reset.item.as_slice()).filter(|reset| is_reset_branch(reset));
+                    return Some((&clocked.item, reset));
+                }
+                [clocked] if is_clock_edge(&clocked.condition.item) => {
+                    if let [inner] = clocked.item.as_slice() {
+                        if let SequentialStatement::If(ref inner) = inner.statement.item {
+                            if let ([reset], Some(_)) =
+                                (inner.conds.conditionals.as_slice(), &inner.conds.else_item)
+                            {
+                                if is_reset_branch(&reset.item) {
+                                    return Some((&clocked.item, Some(&reset.item)));
+                                }
+                            }
+                        }
+                    }
+                    return Some((&clocked.item, None));
+                }
+                _ => {}
+            }
+        }
+    }
+    None
+}
+
+fn initial_values(decl: &[Declaration]) -> InitialValues<'_> {
+    let mut initial_values = FnvHashMap::default();
+    for decl in decl {
+        if let Declaration::Object(object) = decl {
+            if let (ObjectClass::Signal, Some(id), Some(expr)) =
+                (object.class, object.ident.decl, &object.expression)
+            {
+                initial_values.insert(id, (&object.ident.tree, expr));
+            }
+        }
+    }
+    initial_values
+}
+
+/// Warn when a signal with an initial value is reset to another value in a clocked process
+pub(super) fn check_reset_values(
+    decl: &[Declaration],
+    statements: &[LabeledConcurrentStatement],
+    diagnostics: &mut dyn DiagnosticHandler,
+) {
+    let initial_values = initial_values(decl);
+    if initial_values.is_empty() {
+        return;
+    }
+
+    let mut processes = Vec::new();
+    find_processes(statements, &mut processes);
+    for process in processes {
+        if let Some((_, Some(branch))) = clocked_branch(&process.statements) {
+            check_reset_branch(&initial_values, branch, diagnostics);
+        }
+    }
+}
+
+/// Signals with an initial value that are assigned in a clocked process without being reset
+/// Synthesis tools for ASICs ignore initial values so these registers start in an unknown state
+/// Returns the name and initial value of each signal and the position where it is first assigned
+pub(super) fn registers_without_reset<'s>(
+    decl: &'s [Declaration],
+    statements: &'s [LabeledConcurrentStatement],
+) -> Vec<(&'s Ident, &'s WithPos<Expression>, &'s SrcPos)> {
+    let initial_values = initial_values(decl);
+    if initial_values.is_empty() {
+        return Vec::new();
+    }
+
+    let mut processes = Vec::new();
+    find_processes(statements, &mut processes);
+    let mut assigned = Vec::new();
+    let mut reset = FnvHashSet::default();
+    for process in processes {
+        if let Some((clocked, reset_branch)) = clocked_branch(&process.statements) {
+            assigned_signals(clocked, &mut assigned);
+            reset.extend(
+                reset_branch
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|statement| simple_assignment(&statement.statement.item))
+                    .map(|(id, _)| id),
+            );
+        }
+    }
+
+    let mut reported = FnvHashSet::default();
+    assigned
+        .into_iter()
+        .filter(|(id, _)| !reset.contains(id) && reported.insert(*id))
+        .filter_map(|(id, pos)| {
+            let (ident, initial) = initial_values.get(&id)?;
+            Some((*ident, *initial, pos))
+        })
+        .collect()
+}
+
+type InitialValues<'s> = FnvHashMap<EntityId, (&'s Ident, &'s WithPos<Expression>)>;
+
+/// The processes of a concurrent part including those within blocks and generate statements
+fn find_processes<'s>(
+    statements: &'s [LabeledConcurrentStatement],
+    processes: &mut Vec<&'s ProcessStatement>,
+) {
+    for statement in statements {
+        match statement.statement.item {
+            ConcurrentStatement::Process(ref process) => {
+                processes.push(process);
+            }
+            ConcurrentStatement::Block(ref block) => {
+                find_processes(&block.statements, processes);
+            }
+            ConcurrentStatement::ForGenerate(ref gen) => {
+                find_processes(&gen.body.statements, processes);
+            }
+            ConcurrentStatement::IfGenerate(ref gen) => {
+                for body in gen
+                    .conds
+                    .conditionals
+                    .iter()
+                    .map(|cond| &cond.item)
+                    .chain(gen.conds.else_item.iter())
+                {
+                    find_processes(&body.statements, processes);
+                }
+            }
+            ConcurrentStatement::CaseGenerate(ref gen) => {
+                for alternative in gen.sels.alternatives.iter() {
+                    find_processes(&alternative.item.statements, processes);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// The signals assigned by the statements and the position of each assignment
+fn assigned_signals<'s>(
+    statements: &'s [LabeledSequentialStatement],
+    assigned: &mut Vec<(EntityId, &'s SrcPos)>,
+) {
+    for statement in statements {
+        match statement.statement.item {
+            SequentialStatement::If(ref ifstmt) => {
+                for branch in ifstmt
+                    .conds
+                    .conditionals
+                    .iter()
+                    .map(|cond| &cond.item)
+                    .chain(ifstmt.conds.else_item.iter())
+                {
+                    assigned_signals(branch, assigned);
+                }
+            }
+            SequentialStatement::Case(ref case) => {
+                for alternative in case.alternatives.iter() {
+                    assigned_signals(&alternative.item, assigned);
+                }
+            }
+            SequentialStatement::Loop(ref loop_stmt) => {
+                assigned_signals(&loop_stmt.statements, assigned);
+            }
+            SequentialStatement::SignalAssignment(ref assignment) => {
+                if let Target::Name(ref name) = assignment.target.item {
+                    if let Some(id) = target_signal(name) {
+                        assigned.push((id, &assignment.target.pos));
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// The signal denoted by the prefix of a target such as `sig` in `sig(0).elem`
+fn target_signal(name: &Name) -> Option<EntityId> {
+    match name {
+        Name::Designator(designator) => designator.reference,
+        Name::Selected(prefix, _) | Name::Slice(prefix, _) => target_signal(&prefix.item),
+        Name::CallOrIndexed(call) => target_signal(&call.name.item),
+        _ => None,
+    }
+}
+
+fn check_reset_branch(
+    initial_values: &InitialValues,
+    branch: &[LabeledSequentialStatement],
+    diagnostics: &mut dyn DiagnosticHandler,
+) {
+    for statement in branch {
+        if let Some((id, value)) = simple_assignment(&statement.statement.item) {
+            if let Some((ident, initial)) = initial_values.get(&id) {
+                if let (Some(reset_value), Some(initial_value)) =
+                    (static_value(&value.item), static_value(&initial.item))
+                {
+                    if values_differ(&reset_value, &initial_value) {
+                        diagnostics.push(
+                            Diagnostic::warning(
+                                &value.pos,
+                                format!(
+                                    "Reset value of signal '{}' differs from its initial value",
+                                    ident.item
+                                ),
+                            )
+                            .related(&initial.pos, "Initial value declared here"),
+                        );
+                    }
+                }
+            }
+        }
+    }
+}
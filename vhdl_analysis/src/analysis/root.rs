@@ -0,0 +1,2466 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this file,
+// You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) 2018, Olof Kraigher olof.kraigher@gmail.com
+
+use super::analyze::*;
+use super::attribute_catalog::*;
+use super::call_graph::{CallGraph, CallGraphBuilder};
+use super::cancellation::CancellationToken;
+use super::component::{component_entity_mismatches, default_bound_components};
+use super::constant_value::{ConstantValue, ConstantValues};
+use super::dead_generate::dead_generate_branches;
+use super::expand_instance::expand_package_instance;
+use super::fanout::{architecture_fanout, ArchitectureFanout, FanoutLimits, SignalFanout};
+use super::instantiation::{instantiation_snippet, instantiation_text, InstantiationForm};
+use super::lock::*;
+use super::named_entity::*;
+use super::open_port::CheckOpenOutputPorts;
+use super::port_order::{ports_grouped_by_mode, reorder_port_list};
+use super::port_width::CheckPortWidths;
+use super::region::NamedEntities;
+use super::region::Region;
+use super::region::Scope;
+use super::search::*;
+use super::sequential::{enclosing_loop_labels, LoopAtCursor};
+use super::standard::StandardTypes;
+use super::standard::UniversalTypes;
+use super::synthesis::CheckSynthesis;
+use super::unused::FindUnused;
+use super::visibility::Visibility;
+
+use crate::ast::search::*;
+use crate::ast::*;
+use crate::config::{GenericOverrides, VHDLStandard};
+use crate::data::*;
+use crate::syntax::{ArgumentPosition, ClauseKind, CompletionContext, Symbols};
+use fnv::{FnvHashMap, FnvHashSet};
+use parking_lot::RwLock;
+use std::collections::hash_map::Entry;
+use std::ops::Deref;
+use std::sync::Arc;
+use std::time::Instant;
+
+/// A design unit with design unit data
+pub(super) struct AnalysisData {
+    pub diagnostics: Vec<Diagnostic>,
+    pub has_circular_dependency: bool,
+    // The analysis was stopped early and must be done again
+    pub is_cancelled: bool,
+    // The positions of the references to the work library through the work alias
+    pub work_references: Vec<SrcPos>,
+    pub arena: FinalArena,
+}
+
+pub(super) type UnitReadGuard<'a> = ReadGuard<'a, AnyDesignUnit, AnalysisData>;
+pub(super) type UnitWriteGuard<'a> = WriteGuard<'a, AnyDesignUnit, AnalysisData>;
+
+/// Wraps the AST of a [design unit](../../ast/enum.AnyDesignUnit.html) in a thread-safe
+/// r/w-lock for analysis.
+pub(super) struct LockedUnit {
+    ident: Ident,
+    arena_id: ArenaId,
+    unit_id: UnitId,
+    /// The fingerprint of the tokens of the design unit when known
+    fingerprint: Option<u64>,
+    pub unit: AnalysisLock<AnyDesignUnit, AnalysisData>,
+}
+
+impl HasUnitId for LockedUnit {
+    fn unit_id(&self) -> &UnitId {
+        &self.unit_id
+    }
+}
+
+impl LockedUnit {
+    fn new(library_name: &Symbol, unit: AnyDesignUnit, fingerprint: Option<u64>) -> LockedUnit {
+        let unit_id = match unit {
+            AnyDesignUnit::Primary(ref unit) => {
+                UnitId::primary(library_name, PrimaryKind::kind_of(unit), unit.name())
+            }
+            AnyDesignUnit::Secondary(ref unit) => UnitId::secondary(
+                library_name,
+                SecondaryKind::kind_of(unit),
+                unit.primary_name(),
+                unit.name(),
+            ),
+        };
+
+        LockedUnit {
+            ident: unit.ident().clone(),
+            arena_id: ArenaId::default(),
+            unit_id,
+            fingerprint,
+            unit: AnalysisLock::new(unit),
+        }
+    }
+}
+
+impl HasIdent for LockedUnit {
+    fn ident(&self) -> &Ident {
+        &self.ident
+    }
+}
+
+/// Represents a VHDL library containing zero or more design units.
+///
+/// This struct also keeps track of which source file contained which design units.
+struct Library {
+    name: Symbol,
+
+    /// Arena is only used to store the AnyEnt for the library itself
+    /// as there is no other good place to store it
+    arena: FinalArena,
+    id: EntityId,
+
+    units: FnvHashMap<UnitKey, LockedUnit>,
+    units_by_source: FnvHashMap<Source, FnvHashSet<UnitId>>,
+
+    /// Units removed since last analysis.
+    removed: FnvHashSet<UnitId>,
+    /// Units added since last analysis.
+    added: FnvHashSet<UnitId>,
+    /// The fingerprints and arenas of analyzed units removed since last analysis.
+    removed_fingerprints: FnvHashMap<UnitId, (u64, ArenaId)>,
+    /// Units added since last analysis with the same tokens as the analyzed unit they replace.
+    /// Users of these units keep their analysis since only whitespace and comments differ.
+    unchanged: FnvHashSet<UnitId>,
+
+    /// Design units which were not added since they were duplicates.
+    /// They need to be kept for later refresh which might make them not duplicates.
+    duplicates: Vec<(SrcPos, LockedUnit)>,
+}
+
+impl Library {
+    fn new(name: Symbol) -> Library {
+        let arena = Arena::new(ArenaId::default());
+
+        let ent = arena.alloc(
+            Designator::Identifier(name.clone()),
+            None,
+            Related::None,
+            AnyEntKind::Library,
+            None,
+        );
+
+        Library {
+            name,
+            id: ent.id(),
+            arena: arena.finalize(),
+            units: FnvHashMap::default(),
+            units_by_source: FnvHashMap::default(),
+            added: FnvHashSet::default(),
+            removed: FnvHashSet::default(),
+            removed_fingerprints: FnvHashMap::default(),
+            unchanged: FnvHashSet::default(),
+            duplicates: Vec::new(),
+        }
+    }
+
+    pub fn name(&self) -> &Symbol {
+        &self.name
+    }
+
+    /// The architectures of an entity including duplicates of the same name
+    fn architectures_of(&self, entity_name: &Symbol) -> Vec<(Symbol, SrcPos)> {
+        let mut architectures: Vec<_> = self
+            .units
+            .values()
+            .chain(self.duplicates.iter().map(|(_, unit)| unit))
+            .filter(|unit| unit.secondary_kind() == Some(SecondaryKind::Architecture))
+            .filter_map(|unit| match unit.key() {
+                UnitKey::Secondary(primary_name, name) if primary_name == entity_name => {
+                    Some((name.clone(), unit.ident().pos().clone()))
+                }
+                _ => None,
+            })
+            .collect();
+        // The analyzed architecture is listed before its duplicates
+        architectures.sort_by_key(|(name, _)| name.name_utf8());
+        architectures
+    }
+
+    fn add_design_unit(&mut self, mut unit: LockedUnit) {
+        let unit_id = unit.unit_id().clone();
+        match self.units.entry(unit.key().clone()) {
+            Entry::Occupied(entry) => {
+                self.duplicates
+                    .push((entry.get().ident().pos.clone(), unit));
+            }
+            Entry::Vacant(entry) => {
+                // Keep the arena such that references of the users to the replaced unit remain valid
+                if let (Some(fingerprint), Some((removed_fingerprint, arena_id))) =
+                    (unit.fingerprint, self.removed_fingerprints.get(&unit_id))
+                {
+                    if fingerprint == *removed_fingerprint {
+                        unit.arena_id = *arena_id;
+                        self.unchanged.insert(unit_id.clone());
+                    }
+                }
+                self.added.insert(unit_id);
+                match self.units_by_source.entry(unit.source().clone()) {
+                    Entry::Occupied(mut entry) => {
+                        entry.get_mut().insert(unit.unit_id().clone());
+                    }
+                    Entry::Vacant(entry) => {
+                        let mut set = FnvHashSet::default();
+                        set.insert(unit.unit_id().clone());
+                        entry.insert(set);
+                    }
+                }
+                entry.insert(unit);
+            }
+        }
+    }
+
+    fn add_design_file(&mut self, design_file: DesignFile) {
+        let mut fingerprints = design_file.unit_fingerprints.into_iter();
+        for design_unit in design_file.design_units {
+            let fingerprint = fingerprints.next();
+            self.add_design_unit(LockedUnit::new(self.name(), design_unit, fingerprint));
+        }
+    }
+
+    /// Refresh library after removing or adding new design units.
+    fn refresh(&mut self, diagnostics: &mut dyn DiagnosticHandler) {
+        self.append_duplicate_diagnostics(diagnostics);
+    }
+
+    fn append_duplicate_diagnostics(&self, diagnostics: &mut dyn DiagnosticHandler) {
+        for (prev_pos, unit) in self.duplicates.iter() {
+            let diagnostic = match unit.key() {
+                UnitKey::Primary(ref primary_name) => Diagnostic::error(
+                    unit.pos(),
+                    format!(
+                        "A primary unit has already been declared with name '{}' in library '{}'",
+                        primary_name, &self.name
+                    ),
+                ),
+                UnitKey::Secondary(ref primary_name, ref name) => match unit.kind() {
+                    AnyKind::Secondary(SecondaryKind::Architecture) => Diagnostic::error(
+                        unit.ident(),
+                        format!("Duplicate architecture '{name}' of entity '{primary_name}'",),
+                    ),
+                    AnyKind::Secondary(SecondaryKind::PackageBody) => Diagnostic::error(
+                        unit.pos(),
+                        format!("Duplicate package body of package '{primary_name}'"),
+                    ),
+                    AnyKind::Primary(_) => {
+                        unreachable!();
+                    }
+                },
+            };
+
+            let diagnostic = diagnostic.related(prev_pos, "Previously defined here");
+            diagnostics.push(diagnostic);
+        }
+    }
+
+    /// Remove all design units defined in source.
+    /// This is used for incremental analysis where only a single source file is updated.
+    fn remove_source(&mut self, source: &Source) {
+        let removed = &mut self.removed;
+        let removed_fingerprints = &mut self.removed_fingerprints;
+        let unchanged = &mut self.unchanged;
+        self.units.retain(|_, value| {
+            if value.source() != source {
+                true
+            } else {
+                let unit_id = value.unit_id();
+                // Compare with the last analyzed unit when removed several times before analysis
+                if let (Some(fingerprint), Some(_)) = (value.fingerprint, value.unit.get()) {
+                    removed_fingerprints
+                        .entry(unit_id.clone())
+                        .or_insert((fingerprint, value.arena_id));
+                }
+                unchanged.remove(unit_id);
+                removed.insert(unit_id.clone());
+                false
+            }
+        });
+        self.units_by_source.remove(source);
+        self.duplicates
+            .retain(|(_, value)| value.source() != source);
+
+        // Try to add duplicates that were duplicated by a design unit in the removed file
+        let num_duplicates = self.duplicates.len();
+        let duplicates =
+            std::mem::replace(&mut self.duplicates, Vec::with_capacity(num_duplicates));
+        for (prev_pos, design_unit) in duplicates.into_iter() {
+            if prev_pos.source() == source {
+                self.add_design_unit(design_unit);
+            } else {
+                self.duplicates.push((prev_pos, design_unit));
+            }
+        }
+    }
+
+    /// Iterate over units in the order they appear in the file.
+    /// Ensures diagnostics do not have to be sorted later.
+    fn sorted_unit_ids(&self) -> Vec<UnitId> {
+        // @TODO insert sort when adding instead
+        let mut result = Vec::new();
+
+        for unit_ids in self.units_by_source.values() {
+            let mut unit_ids: Vec<UnitId> = unit_ids.clone().into_iter().collect();
+            unit_ids.sort_by_key(|unit_id| {
+                self.units
+                    .get(unit_id.key())
+                    .unwrap()
+                    .ident()
+                    .pos
+                    .range()
+                    .start
+            });
+            result.append(&mut unit_ids);
+        }
+        result
+    }
+
+    fn get_unit(&self, key: &UnitKey) -> Option<&LockedUnit> {
+        self.units.get(key)
+    }
+}
+
+/// Contains the entire design state.
+///
+/// Besides all loaded libraries and design units, `DesignRoot` also keeps track of
+/// dependencies between design units.
+pub struct DesignRoot {
+    pub(super) symbols: Arc<Symbols>,
+    pub(super) standard_pkg_id: Option<EntityId>,
+    pub(super) standard_arena: Option<FinalArena>,
+    pub(super) universal: Option<UniversalTypes>,
+    pub(super) standard_types: Option<StandardTypes>,
+    pub(super) std_ulogic: Option<EntityId>,
+    pub(super) standard: VHDLStandard,
+    // Warn when recursive subprograms are used to compute constant values
+    warn_recursion: bool,
+    // Explain why each candidate of an unresolved call was rejected
+    pub(super) explain_overloads: bool,
+    // The severity of local declarations that are never used, not reported when None
+    unused_declarations: Option<Severity>,
+    // Libraries whose code is checked for constructs that are not synthesizable
+    synthesis_libraries: FnvHashSet<Symbol>,
+    // The severity of diagnostics within if generate branches that are never selected,
+    // kept when None and not reported when Some(None)
+    dead_generate: Option<Option<Severity>>,
+    // Warn about signals with more loads or drivers within an architecture
+    fanout_limits: FanoutLimits,
+    libraries: FnvHashMap<Symbol, Library>,
+    // Values of package level constants evaluated after analysis
+    pub(super) constant_values: ConstantValues,
+    // Generic values of top level entities used when evaluating constants
+    pub(super) generic_overrides: GenericOverrides,
+    // Checked by the design units being analyzed to stop early when cancelled
+    pub(super) cancellation: CancellationToken,
+
+    // Arena storage of all declaration in the design
+    pub(super) arenas: FinalArena,
+
+    // Dependency tracking for incremental analysis.
+    // user  =>  set(users)
+    users_of: RwLock<FnvHashMap<UnitId, FnvHashSet<UnitId>>>,
+
+    // missing unit name  =>  set(affected)
+    #[allow(clippy::type_complexity)]
+    missing_unit: RwLock<FnvHashMap<(Symbol, Symbol, Option<Symbol>), FnvHashSet<UnitId>>>,
+
+    // Tracks which units have a "use library.all;" clause.
+    // library name  =>  set(affected)
+    users_of_library_all: RwLock<FnvHashMap<Symbol, FnvHashSet<UnitId>>>,
+}
+
+impl DesignRoot {
+    pub fn new(symbols: Arc<Symbols>) -> DesignRoot {
+        DesignRoot {
+            universal: None,
+            standard_pkg_id: None,
+            standard_arena: None,
+            standard_types: None,
+            std_ulogic: None,
+            standard: VHDLStandard::default(),
+            warn_recursion: true,
+            explain_overloads: false,
+            unused_declarations: None,
+            synthesis_libraries: FnvHashSet::default(),
+            dead_generate: None,
+            fanout_limits: FanoutLimits::default(),
+            symbols,
+            arenas: FinalArena::default(),
+            libraries: FnvHashMap::default(),
+            constant_values: ConstantValues::default(),
+            generic_overrides: GenericOverrides::default(),
+            cancellation: CancellationToken::default(),
+            users_of: RwLock::new(FnvHashMap::default()),
+            missing_unit: RwLock::new(FnvHashMap::default()),
+            users_of_library_all: RwLock::new(FnvHashMap::default()),
+        }
+    }
+
+    /// Set the VHDL standard to analyze against
+    pub fn set_standard(&mut self, standard: VHDLStandard) {
+        self.standard = standard;
+    }
+
+    /// Set the generic values of top level entities, constants of these entities and their
+    /// architectures are evaluated with these values
+    pub fn set_generic_overrides(&mut self, generic_overrides: GenericOverrides) {
+        self.generic_overrides = generic_overrides;
+    }
+
+    pub fn set_warn_recursion(&mut self, warn_recursion: bool) {
+        self.warn_recursion = warn_recursion;
+    }
+
+    pub fn set_explain_overloads(&mut self, explain_overloads: bool) {
+        self.explain_overloads = explain_overloads;
+    }
+
+    /// Report local declarations that are never used with the severity
+    pub fn set_unused_declarations(&mut self, severity: Option<Severity>) {
+        self.unused_declarations = severity;
+    }
+
+    /// Warn about constructs that are not synthesizable within the libraries
+    pub fn set_synthesis_libraries(&mut self, libraries: FnvHashSet<Symbol>) {
+        self.synthesis_libraries = libraries;
+    }
+
+    /// Report diagnostics within if generate branches whose conditions evaluate to false
+    /// with the severity, or not at all when the severity is None
+    pub fn set_dead_generate(&mut self, severity: Option<Severity>) {
+        self.dead_generate = Some(severity);
+    }
+
+    /// Warn about signals with more loads or drivers within an architecture than the limits
+    pub fn set_fanout_limits(&mut self, max_loads: Option<usize>, max_drivers: Option<usize>) {
+        self.fanout_limits = FanoutLimits {
+            max_loads,
+            max_drivers,
+        };
+    }
+
+    /// Create library if it does not exist or return existing
+    fn get_or_create_library(&mut self, name: Symbol) -> &mut Library {
+        match self.libraries.entry(name) {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => {
+                let name = entry.key().clone();
+                let library = Library::new(name);
+                entry.insert(library)
+            }
+        }
+    }
+
+    pub fn ensure_library(&mut self, name: Symbol) {
+        self.get_or_create_library(name);
+    }
+
+    /// The names of the architectures of an entity and the positions where they are declared
+    /// Architectures that duplicate the name of another architecture are also included
+    pub fn architectures_of(
+        &self,
+        library_name: &Symbol,
+        entity_name: &Symbol,
+    ) -> Vec<(Symbol, SrcPos)> {
+        self.libraries
+            .get(library_name)
+            .map(|library| library.architectures_of(entity_name))
+            .unwrap_or_default()
+    }
+
+    /// The libraries with a primary unit of a name and the position of its identifier
+    pub(super) fn libraries_with_primary_unit(
+        &self,
+        primary_name: &Symbol,
+    ) -> Vec<(Symbol, SrcPos)> {
+        let key = UnitKey::Primary(primary_name.clone());
+        let mut result: Vec<_> = self
+            .libraries
+            .values()
+            .filter_map(|library| {
+                let unit = library.units.get(&key)?;
+                Some((library.name.clone(), unit.ident().pos().clone()))
+            })
+            .collect();
+        result.sort_by_key(|(name, _)| name.name_utf8());
+        result
+    }
+
+    pub(super) fn library_names(&self) -> impl Iterator<Item = &Symbol> {
+        self.libraries.keys()
+    }
+
+    pub(super) fn get_library_units(
+        &self,
+        library_name: &Symbol,
+    ) -> Option<&FnvHashMap<UnitKey, LockedUnit>> {
+        self.libraries
+            .get(library_name)
+            .map(|library| &library.units)
+    }
+
+    pub(crate) fn get_design_entity<'a>(
+        &'a self,
+        library_name: &Symbol,
+        ident: &Symbol,
+    ) -> Option<DesignEnt<'a>> {
+        let units = self.get_library_units(library_name)?;
+        let unit = units.get(&UnitKey::Primary(ident.clone()))?;
+        let data = self.get_analysis(unit);
+
+        if let AnyDesignUnit::Primary(primary) = data.deref() {
+            if let Some(id) = primary.ent_id() {
+                let design = DesignEnt::from_any(self.arenas.get(id))?;
+                if matches!(design.kind(), Design::Entity(..)) {
+                    return Some(design);
+                }
+            }
+        }
+        None
+    }
+
+    /// Get a named entity corresponding to the library
+    pub(super) fn get_library_arena(
+        &self,
+        library_name: &Symbol,
+    ) -> Option<(&FinalArena, EntityId)> {
+        self.libraries
+            .get(library_name)
+            .map(|library| (&library.arena, library.id))
+    }
+
+    pub fn add_design_file(&mut self, library_name: Symbol, design_file: DesignFile) {
+        self.get_or_create_library(library_name)
+            .add_design_file(design_file);
+    }
+
+    pub fn remove_source(&mut self, library_name: Symbol, source: &Source) {
+        self.get_or_create_library(library_name)
+            .remove_source(source);
+    }
+
+    /// Search for reference at position
+    /// Character offset on a line in a document (zero-based). Assuming that the line is
+    /// represented as a string, the `character` value represents the gap between the
+    /// `character` and `character + 1`.
+    ///
+    /// If the character value is greater than the line length it defaults back to the
+    /// line length.
+    pub fn item_at_cursor<'a>(
+        &'a self,
+        source: &Source,
+        cursor: Position,
+    ) -> Option<(SrcPos, EntRef<'a>)> {
+        let item = self.item_at(source, cursor)?;
+        Some((item.pos, item.ent?))
+    }
+
+    /// The declaration or name at the cursor, also when it was not resolved
+    pub fn item_at<'a>(&'a self, source: &Source, cursor: Position) -> Option<ItemAt<'a>> {
+        let mut searcher = ItemAtCursor::new(source, cursor);
+        let _ = self.search(&mut searcher);
+        let (pos, kind, reference) = searcher.result?;
+        Some(ItemAt {
+            pos,
+            kind,
+            ent: reference.map(|id| self.get_ent(id)),
+        })
+    }
+
+    /// The resolved declarations and names of a source ordered by their position
+    pub fn items_in<'a>(&'a self, source: &Source) -> Vec<ItemAt<'a>> {
+        let mut searcher = FindAllItems::new(source);
+        let _ = self.search(&mut searcher);
+        let mut items = searcher.items;
+        items.sort_by_key(|(pos, ..)| pos.start());
+        items.dedup_by_key(|(pos, ..)| pos.range());
+        items
+            .into_iter()
+            .map(|(pos, kind, id)| ItemAt {
+                pos,
+                kind,
+                ent: Some(self.get_ent(id)),
+            })
+            .collect()
+    }
+
+    /// The formals associated with the positional actuals of calls and instances in a source
+    pub fn positional_formals<'a>(&'a self, source: &Source) -> Vec<(SrcPos, EntRef<'a>)> {
+        let mut searcher = FindPositionalActuals::new(source);
+        let _ = self.search(&mut searcher);
+        self.formals_of_actuals(searcher)
+    }
+
+    /// Edits that change the positional associations of the instance at the cursor into
+    /// named associations, the actuals are kept as written
+    pub fn named_associations(&self, source: &Source, cursor: Position) -> Option<Vec<TextEdit>> {
+        let edits =
+            self.named_association_edits(FindPositionalActuals::instance_at(source, cursor));
+        if edits.is_empty() {
+            None
+        } else {
+            Some(edits)
+        }
+    }
+
+    /// Edits that name the formal of each positional actual found by the searcher
+    fn named_association_edits(&self, mut searcher: FindPositionalActuals) -> Vec<TextEdit> {
+        let _ = self.search(&mut searcher);
+        self.formals_of_actuals(searcher)
+            .into_iter()
+            .map(|(pos, formal)| {
+                TextEdit::replace(
+                    pos.pos_at_beginning(),
+                    format!("{} => ", formal.designator()),
+                )
+            })
+            .collect()
+    }
+
+    fn formals_of_actuals<'a>(
+        &'a self,
+        searcher: FindPositionalActuals,
+    ) -> Vec<(SrcPos, EntRef<'a>)> {
+        let mut result = Vec::new();
+        for (id, typ, actuals) in searcher.result {
+            let ent = self.get_ent(id);
+            let formals: Vec<EntRef<'a>> = match (ent.kind(), typ) {
+                (AnyEntKind::Overloaded(_), InterfaceType::Parameter) => {
+                    match OverloadedEnt::from_any(ent) {
+                        Some(overloaded) => overloaded
+                            .formals()
+                            .iter()
+                            .map(|formal| formal.inner())
+                            .collect(),
+                        None => continue,
+                    }
+                }
+                (
+                    AnyEntKind::Design(Design::Entity(_, region)) | AnyEntKind::Component(region),
+                    InterfaceType::Generic | InterfaceType::Port,
+                ) => {
+                    let (generics, ports) = region.to_entity_formal();
+                    if typ == InterfaceType::Generic {
+                        generics.iter().map(|formal| formal.inner()).collect()
+                    } else {
+                        ports.iter().map(|formal| formal.inner()).collect()
+                    }
+                }
+                _ => continue,
+            };
+            result.extend(actuals.into_iter().zip(formals));
+        }
+        result.sort_by_key(|(pos, _)| pos.start());
+        result
+    }
+
+    pub fn search_reference<'a>(&'a self, source: &Source, cursor: Position) -> Option<EntRef<'a>> {
+        let (_, ent) = self.item_at_cursor(source, cursor)?;
+        Some(ent)
+    }
+
+    /// The predefined attribute whose designator is at the cursor
+    pub fn predefined_attribute_at(
+        &self,
+        source: &Source,
+        cursor: Position,
+    ) -> Option<&'static PredefinedAttribute> {
+        let mut searcher = AttributeAtCursor::new(source, cursor);
+        let _ = self.search(&mut searcher);
+        let (designator, prefix) = searcher.result?;
+        let classes = prefix
+            .map(|id| prefix_classes(self.get_ent(id)))
+            .unwrap_or_default();
+        find_attribute(&designator.to_string(), &classes)
+    }
+
+    pub fn find_definition_of<'a>(&'a self, decl: EntRef<'a>) -> Option<EntRef<'a>> {
+        if decl.is_protected_type()
+            || decl.is_subprogram_decl()
+            || decl.kind().is_deferred_constant()
+            || matches!(
+                decl.kind(),
+                AnyEntKind::Design(Design::Package(..) | Design::UninstPackage(..))
+            )
+        {
+            let mut searcher = FindEnt::new(self, |ent| ent.is_declared_by(decl));
+            let _ = self.search(&mut searcher);
+
+            Some(searcher.result.unwrap_or(decl))
+        } else {
+            // The definition is the same as the declaration
+            Some(decl)
+        }
+    }
+
+    /// The positions of the declaration and the definition of a named entity
+    /// None for an implicit entity without a position
+    pub fn positions_of(&self, ent: EntRef) -> Option<EntPositions> {
+        let decl = ent.declaration();
+        let definition = self.find_definition_of(decl).unwrap_or(decl);
+        Some(EntPositions {
+            declaration: decl.decl_pos()?.clone(),
+            definition: definition.decl_pos()?.clone(),
+        })
+    }
+
+    pub fn find_implementation<'a>(&'a self, ent: EntRef<'a>) -> Vec<EntRef<'a>> {
+        if let Designator::Identifier(ident) = ent.designator() {
+            if let Some(library_name) = ent.library_name() {
+                match ent.kind() {
+                    // Find entity with same name as component in the library
+                    AnyEntKind::Component(_) => {
+                        if let Some(design) = self.get_design_entity(library_name, ident) {
+                            return vec![design.into()];
+                        }
+                    }
+                    // Find all components with same name as entity in the library
+                    AnyEntKind::Design(Design::Entity(..)) => {
+                        let mut searcher = FindAllEnt::new(self, |ent| {
+                            matches!(ent.kind(), AnyEntKind::Component(_))
+                                && matches!(
+                                    ent.designator(),
+                                    Designator::Identifier(comp_ident) if comp_ident == ident
+                                )
+                        });
+
+                        let _ = self.search_library(library_name, &mut searcher);
+                        return searcher.result;
+                    }
+                    _ => {}
+                }
+            }
+        }
+        Vec::default()
+    }
+
+    /// The labels of the loops that enclose the cursor from the innermost to the outermost
+    pub fn enclosing_loop_labels(&self, source: &Source, cursor: Position) -> Vec<EntRef<'_>> {
+        let mut searcher = LoopAtCursor::new(source, cursor);
+        let _ = self.search(&mut searcher);
+        searcher
+            .result
+            .map(|id| enclosing_loop_labels(self.get_ent(id)))
+            .unwrap_or_default()
+    }
+
+    /// List the names that complete the name being typed in a library clause, use clause,
+    /// context reference, argument of a call, attribute name or loop label, sorted by name
+    pub fn list_completion_options(&self, context: &CompletionContext) -> Vec<CompletionItem<'_>> {
+        let mut options = Vec::new();
+
+        match (context.clause, context.prefix.as_slice()) {
+            (ClauseKind::Attribute, _) => {
+                // Offer all attributes when the prefix is not known
+                let classes = context
+                    .attribute_prefix
+                    .as_ref()
+                    .and_then(|prefix| self.search_reference(prefix.source(), prefix.start()))
+                    .map(prefix_classes)
+                    .unwrap_or_else(|| {
+                        PREDEFINED_ATTRIBUTES
+                            .iter()
+                            .map(|attr| attr.prefix)
+                            .collect()
+                    });
+                options.extend(
+                    applicable_attributes(&classes)
+                        .into_iter()
+                        .map(CompletionItem::Attribute),
+                );
+            }
+            (ClauseKind::Argument, _) => {
+                if let Some(ref argument) = context.argument {
+                    options.extend(
+                        self.enum_literals_of_argument(argument)
+                            .into_iter()
+                            .map(CompletionItem::Simple),
+                    );
+                }
+            }
+            (ClauseKind::LoopLabel | ClauseKind::EndLoopLabel, _) => {
+                if let Some(ref keyword) = context.loop_keyword {
+                    let labels = self.enclosing_loop_labels(keyword.source(), keyword.start());
+                    if context.clause == ClauseKind::EndLoopLabel {
+                        // Only the innermost loop is closed by the end label
+                        options.extend(labels.into_iter().take(1).map(CompletionItem::Simple));
+                    } else {
+                        options.extend(labels.into_iter().map(CompletionItem::Simple));
+                    }
+                }
+            }
+            (_, []) => {
+                for library in self.libraries.values() {
+                    options.push(CompletionItem::Simple(self.arenas.get(library.id)));
+                }
+            }
+            (ClauseKind::Use, [library_name]) => {
+                for ent in self.primary_units(library_name) {
+                    if matches!(
+                        ent.kind(),
+                        AnyEntKind::Design(Design::Package(..) | Design::PackageInstance(..))
+                    ) {
+                        options.push(CompletionItem::Simple(ent));
+                        options.push(CompletionItem::AllOf(ent));
+                    }
+                }
+            }
+            (ClauseKind::Context, [library_name]) => {
+                for ent in self.primary_units(library_name) {
+                    if matches!(ent.kind(), AnyEntKind::Design(Design::Context(..))) {
+                        options.push(CompletionItem::Simple(ent));
+                    }
+                }
+            }
+            (ClauseKind::Use, [library_name, unit_name]) => {
+                let region = self
+                    .primary_units(library_name)
+                    .into_iter()
+                    .filter(|ent| matches!(ent.designator(), Designator::Identifier(name) if name == unit_name))
+                    .find_map(|ent| match ent.kind() {
+                        AnyEntKind::Design(Design::Package(_, region))
+                        | AnyEntKind::Design(Design::PackageInstance(region)) => Some(region),
+                        _ => None,
+                    });
+
+                if let Some(region) = region {
+                    options.push(CompletionItem::All);
+                    for ent in region.immediates() {
+                        if matches!(ent.designator(), Designator::Identifier(_)) {
+                            options.push(CompletionItem::Simple(ent));
+                        }
+                    }
+                }
+            }
+            _ => {}
+        }
+
+        let partial = context.partial.to_lowercase();
+        options.retain(|option| option.name().to_lowercase().starts_with(&partial));
+        options.sort_by_key(|option| option.name().to_lowercase());
+        options
+    }
+
+    /// The enumeration literals of the type of the argument in any subprogram with the name
+    /// including the implicitly declared subprograms of types such as `file_open`
+    fn enum_literals_of_argument(&self, argument: &ArgumentPosition) -> Vec<EntRef<'_>> {
+        let mut literals: Vec<EntRef> = Vec::new();
+        let designator = Designator::Identifier(argument.subprogram.clone());
+
+        for ent in self.public_symbols() {
+            for candidate in std::iter::once(ent).chain(ent.implicits.iter().copied()) {
+                if candidate.designator() != &designator {
+                    continue;
+                }
+                let subprogram = if let Some(subprogram) = OverloadedEnt::from_any(candidate) {
+                    subprogram
+                } else {
+                    continue;
+                };
+                let formal = if let Some(ref name) = argument.formal {
+                    subprogram
+                        .formals()
+                        .iter()
+                        .find(|formal| formal.designator() == &Designator::Identifier(name.clone()))
+                } else {
+                    subprogram.formals().nth(argument.index)
+                };
+                let typ = if let Some(formal) = formal {
+                    formal.base_type()
+                } else {
+                    continue;
+                };
+                if !matches!(typ.kind(), Type::Enum(..)) {
+                    continue;
+                }
+                for literal in typ.implicits.iter() {
+                    if matches!(
+                        literal.kind(),
+                        AnyEntKind::Overloaded(Overloaded::EnumLiteral(..))
+                    ) && !literals.iter().any(|other| other.id() == literal.id())
+                    {
+                        literals.push(literal);
+                    }
+                }
+            }
+        }
+        literals
+    }
+
+    fn primary_units(&self, library_name: &Symbol) -> Vec<EntRef<'_>> {
+        let mut result = Vec::new();
+        if let Some(units) = self.get_library_units(library_name) {
+            for unit in units.values() {
+                if matches!(unit.kind(), AnyKind::Primary(_)) {
+                    let data = self.get_analysis(unit);
+                    if let AnyDesignUnit::Primary(primary) = data.deref() {
+                        if let Some(id) = primary.ent_id() {
+                            result.push(self.arenas.get(id));
+                        }
+                    }
+                }
+            }
+        }
+        result
+    }
+
+    #[cfg(test)]
+    pub fn search_reference_pos(&self, source: &Source, cursor: Position) -> Option<SrcPos> {
+        self.search_reference(source, cursor)
+            .and_then(|ent| ent.decl_pos().cloned())
+    }
+    /// Search for the declaration at decl_pos and format it
+    pub fn format_declaration(&self, ent: &AnyEnt) -> Option<String> {
+        if let AnyEntKind::Library = ent.kind() {
+            Some(format!("library {};", ent.designator()))
+        } else {
+            let ent = if let Related::InstanceOf(ent) = ent.related {
+                ent
+            } else {
+                ent
+            };
+
+            let mut searcher = FormatDeclaration::new(ent);
+            let _ = self.search(&mut searcher);
+            let mut result = searcher.result?;
+            if let Some(value) = self.constant_value(ent) {
+                result.push_str(&format!("\n-- Value: {value}"));
+            }
+            Some(result)
+        }
+    }
+
+    /// Search for all references to the declaration at decl_pos
+    pub fn find_all_references(&self, ent: EntRef) -> Vec<SrcPos> {
+        let mut searcher = FindAllReferences::new(self, ent);
+        let _ = self.search(&mut searcher);
+        searcher.references
+    }
+
+    /// Search for all references to the declaration until the deadline has passed
+    pub fn find_all_references_until(
+        &self,
+        ent: EntRef,
+        deadline: Instant,
+    ) -> Partial<Vec<SrcPos>> {
+        let mut searcher = FindAllReferences::new(self, ent);
+        let incomplete = !self.search_until(&mut searcher, deadline);
+        Partial {
+            result: searcher.references,
+            incomplete,
+        }
+    }
+
+    /// The entity and the components bound to it by default binding together with `ent`
+    /// These share the name of `ent` and must be renamed with it
+    pub fn bound_by_default<'a>(&'a self, ent: EntRef<'a>) -> Vec<EntRef<'a>> {
+        let bindings = self.default_bindings();
+        let entity = match ent.kind() {
+            AnyEntKind::Design(Design::Entity(..)) => ent,
+            AnyEntKind::Component(_) => {
+                match bindings
+                    .iter()
+                    .find(|(component, _)| component.id() == ent.id())
+                {
+                    Some((_, entity)) => *entity,
+                    None => return Vec::new(),
+                }
+            }
+            _ => return Vec::new(),
+        };
+
+        let mut result = Vec::new();
+        if entity.id() != ent.id() {
+            result.push(entity);
+        }
+        for (component, other) in bindings {
+            if other.id() == entity.id() && component.id() != ent.id() {
+                result.push(component);
+            }
+        }
+        result
+    }
+
+    /// Search for all references to a library by its name
+    /// References through the work library alias are excluded since they do not contain the name
+    pub fn find_library_references(&self, library_name: &Symbol) -> Option<Vec<SrcPos>> {
+        let library = self.libraries.get(library_name)?;
+        let ent = self.arenas.get(library.id);
+
+        let references = self.find_all_references(ent);
+        let mut sources: Vec<&Source> = Vec::new();
+        for pos in references.iter() {
+            if !sources.contains(&&pos.source) {
+                sources.push(&pos.source);
+            }
+        }
+        let work_references = self.work_references(sources.into_iter());
+        Some(
+            references
+                .into_iter()
+                .filter(|pos| !work_references.contains(pos))
+                .collect(),
+        )
+    }
+
+    /// The positions of the references to libraries through the work alias
+    /// within the design units of the sources
+    fn work_references<'s>(&self, sources: impl Iterator<Item = &'s Source>) -> Vec<SrcPos> {
+        let mut result = Vec::new();
+        for source in sources {
+            for library in self.libraries.values() {
+                for unit_id in library.units_by_source.get(source).into_iter().flatten() {
+                    if let Some(unit) = library.units.get(unit_id.key()) {
+                        let data = self.get_analysis(unit);
+                        result.extend(data.result().work_references.iter().cloned());
+                    }
+                }
+            }
+        }
+        result
+    }
+
+    /// The libraries that the work library alias at the cursor denotes, which are the
+    /// libraries that the source is compiled into, empty when the cursor is not on work
+    pub fn work_libraries_at(&self, source: &Source, cursor: Position) -> Vec<Symbol> {
+        match self.item_at_cursor(source, cursor) {
+            Some((pos, ent))
+                if matches!(ent.kind(), AnyEntKind::Library)
+                    && self.work_references(std::iter::once(source)).contains(&pos) =>
+            {
+                let mut result: Vec<_> = self
+                    .libraries
+                    .values()
+                    .filter(|library| {
+                        library
+                            .units_by_source
+                            .get(source)
+                            .is_some_and(|units| !units.is_empty())
+                    })
+                    .map(|library| library.name.clone())
+                    .collect();
+                result.sort_by_key(|name| name.name_utf8());
+                result
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    pub fn public_symbols<'a>(&'a self) -> Box<dyn Iterator<Item = EntRef<'a>> + 'a> {
+        Box::new(self.libraries.values().flat_map(|library| {
+            std::iter::once(self.arenas.get(library.id)).chain(library.units.values().flat_map(
+                |unit| -> Box<dyn Iterator<Item = EntRef<'a>>> {
+                    if matches!(unit.kind(), AnyKind::Primary(_)) {
+                        let data = self.get_analysis(unit);
+                        if let AnyDesignUnit::Primary(primary) = data.deref() {
+                            if let Some(id) = primary.ent_id() {
+                                let ent = self.arenas.get(id);
+                                return Box::new(std::iter::once(ent).chain(public_symbols(ent)));
+                            }
+                        }
+                    } else if matches!(unit.kind(), AnyKind::Secondary(SecondaryKind::Architecture))
+                    {
+                        let data = self.get_analysis(unit);
+                        if let AnyDesignUnit::Secondary(AnySecondaryUnit::Architecture(arch)) =
+                            data.deref()
+                        {
+                            if let Some(id) = arch.ident.decl {
+                                let ent = self.arenas.get(id);
+                                return Box::new(std::iter::once(ent));
+                            }
+                        }
+                    } else if matches!(unit.kind(), AnyKind::Secondary(SecondaryKind::PackageBody))
+                    {
+                        let data = self.get_analysis(unit);
+                        if let AnyDesignUnit::Secondary(AnySecondaryUnit::PackageBody(body)) =
+                            data.deref()
+                        {
+                            if let Some(id) = body.ident.decl {
+                                let ent = self.arenas.get(id);
+                                return Box::new(std::iter::once(ent));
+                            }
+                        }
+                    }
+                    Box::new(std::iter::empty())
+                },
+            ))
+        }))
+    }
+
+    pub fn document_symbols<'a>(
+        &'a self,
+        library_name: &Symbol,
+        source: &Source,
+    ) -> Vec<EntHierarchy<'a>> {
+        let mut searcher = FindAllEnt::new(self, |ent| ent.is_explicit());
+
+        if let Some(library) = self.libraries.get(library_name) {
+            if let Some(unit_ids) = library.units_by_source.get(source) {
+                for unit_id in unit_ids {
+                    let unit = library.units.get(unit_id.key()).unwrap();
+                    let _ = unit.unit.write().search(&mut searcher);
+                }
+            }
+        }
+
+        searcher.result.sort_by_key(|ent| ent.decl_pos());
+        EntHierarchy::from_vec(searcher.result)
+    }
+
+    pub fn find_all_unresolved(&self) -> (usize, Vec<SrcPos>) {
+        let mut searcher = FindAllUnresolved::default();
+        let _ = self.search(&mut searcher);
+        (searcher.count, searcher.unresolved)
+    }
+
+    #[cfg(test)]
+    pub fn find_all_references_pos(&self, decl_pos: &SrcPos) -> Vec<SrcPos> {
+        if let Some(ent) = self.search_reference(decl_pos.source(), decl_pos.start()) {
+            self.find_all_references(ent)
+        } else {
+            Vec::new()
+        }
+    }
+
+    #[cfg(test)]
+    pub fn find_standard_pkg(&self) -> &AnyEnt {
+        let std_lib = self.libraries.get(&self.symbol_utf8("std")).unwrap();
+        let unit = std_lib
+            .get_unit(&UnitKey::Primary(self.symbol_utf8("standard")))
+            .unwrap();
+
+        if let AnyPrimaryUnit::Package(pkg) = unit.unit.write().as_primary_mut().unwrap() {
+            self.get_ent(pkg.ident.decl.unwrap())
+        } else {
+            panic!("Not a package");
+        }
+    }
+
+    #[cfg(test)]
+    pub fn find_standard_symbol(&self, name: &str) -> &AnyEnt {
+        if let AnyEntKind::Design(Design::Package(_, region)) = self.find_standard_pkg().kind() {
+            region
+                .lookup_immediate(&Designator::Identifier(self.symbol_utf8(name)))
+                .unwrap()
+                .as_non_overloaded()
+                .unwrap()
+        } else {
+            panic!("Not a package");
+        }
+    }
+
+    pub fn search(&self, searcher: &mut impl Searcher) -> SearchResult {
+        for library in self.libraries.values() {
+            for unit_id in library.sorted_unit_ids() {
+                let unit = library.units.get(unit_id.key()).unwrap();
+                return_if_found!(unit.unit.write().search(searcher));
+            }
+        }
+        NotFound
+    }
+
+    /// Search all design units but stop before the next unit once the deadline has passed
+    /// Returns false when the search was stopped by the deadline
+    pub fn search_until(&self, searcher: &mut impl Searcher, deadline: Instant) -> bool {
+        for library in self.libraries.values() {
+            for unit_id in library.sorted_unit_ids() {
+                if Instant::now() >= deadline {
+                    return false;
+                }
+                let unit = library.units.get(unit_id.key()).unwrap();
+                if let Found = unit.unit.write().search(searcher) {
+                    return true;
+                }
+            }
+        }
+        true
+    }
+
+    pub fn search_library(
+        &self,
+        library_name: &Symbol,
+        searcher: &mut impl Searcher,
+    ) -> SearchResult {
+        if let Some(library) = self.libraries.get(library_name) {
+            for unit_id in library.sorted_unit_ids() {
+                let unit = library.units.get(unit_id.key()).unwrap();
+                return_if_found!(unit.unit.write().search(searcher));
+            }
+        }
+        NotFound
+    }
+
+    pub fn symbol_utf8(&self, name: &str) -> Symbol {
+        self.symbols.symtab().insert_utf8(name)
+    }
+
+    fn analyze_unit(&self, arena_id: ArenaId, unit_id: &UnitId, unit: &mut UnitWriteGuard) {
+        // All units reference the standard arena
+        // @TODO keep the same ArenaId when re-using unit
+        let arena = Arena::new(arena_id);
+        let context = AnalyzeContext::new(self, unit_id, &arena);
+        use std::ops::DerefMut;
+
+        let mut diagnostics = Vec::new();
+        let mut has_circular_dependency = false;
+
+        // Ensure no remaining references from previous analysis
+        clear_references(unit.deref_mut());
+
+        let result = match unit.deref_mut() {
+            AnyDesignUnit::Primary(unit) => {
+                if let Err(err) = context.analyze_primary_unit(unit, &mut diagnostics) {
+                    has_circular_dependency = true;
+                    err.push_into(&mut diagnostics);
+                };
+
+                AnalysisData {
+                    work_references: context.take_work_references(),
+                    arena: arena.finalize(),
+                    diagnostics,
+                    has_circular_dependency,
+                    is_cancelled: self.cancellation.is_cancelled(),
+                }
+            }
+
+            AnyDesignUnit::Secondary(unit) => {
+                let mut diagnostics = Vec::new();
+
+                if let Err(err) = context.analyze_secondary_unit(unit, &mut diagnostics) {
+                    has_circular_dependency = true;
+                    err.push_into(&mut diagnostics);
+                };
+
+                AnalysisData {
+                    work_references: context.take_work_references(),
+                    arena: arena.finalize(),
+                    diagnostics,
+                    has_circular_dependency,
+                    is_cancelled: self.cancellation.is_cancelled(),
+                }
+            }
+        };
+
+        unit.finish(result);
+    }
+
+    pub(super) fn get_analysis<'a>(&self, locked_unit: &'a LockedUnit) -> UnitReadGuard<'a> {
+        match locked_unit.unit.entry() {
+            AnalysisEntry::Vacant(mut unit) => {
+                self.analyze_unit(locked_unit.arena_id, locked_unit.unit_id(), &mut unit);
+                unit.downgrade()
+            }
+            AnalysisEntry::Occupied(unit) => unit,
+        }
+    }
+
+    pub(super) fn get_unit<'a>(&'a self, unit_id: &UnitId) -> Option<&'a LockedUnit> {
+        self.libraries
+            .get(unit_id.library_name())
+            .and_then(|library| library.units.get(unit_id.key()))
+    }
+
+    fn reset_affected(&self, mut affected: FnvHashSet<UnitId>) {
+        // Reset analysis state of all design units
+        for unit_id in affected.drain() {
+            if let Some(unit) = self.get_unit(&unit_id) {
+                unit.unit.reset();
+            }
+        }
+    }
+
+    /// Register a direct dependency between two library units
+    pub(super) fn make_use_of(
+        &self,
+        use_pos: Option<&SrcPos>,
+        user: &UnitId,
+        unit_id: &UnitId,
+    ) -> FatalResult {
+        let mut users_of = self.users_of.write();
+        match users_of.entry(unit_id.clone()) {
+            Entry::Occupied(mut entry) => {
+                entry.get_mut().insert(user.clone());
+            }
+            Entry::Vacant(entry) => {
+                let mut set = FnvHashSet::default();
+                set.insert(user.clone());
+                entry.insert(set);
+            }
+        }
+
+        let mut affected = FnvHashSet::default();
+        affected.insert(user.clone());
+        let all_affected = get_all_affected(&users_of, affected);
+
+        if all_affected.contains(unit_id) {
+            Err(CircularDependencyError::new(use_pos))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Register a dependency of library unit for everything within library since .all was used
+    pub(super) fn make_use_of_library_all(&self, user: &UnitId, library_name: &Symbol) {
+        match self
+            .users_of_library_all
+            .write()
+            .entry(library_name.clone())
+        {
+            Entry::Occupied(mut entry) => {
+                entry.get_mut().insert(user.clone());
+            }
+            Entry::Vacant(entry) => {
+                let mut set = FnvHashSet::default();
+                set.insert(user.clone());
+                entry.insert(set);
+            }
+        }
+    }
+
+    /// Make use of a missing unit name. The library unit will be sensitive to adding such a unit in the future.
+    pub(super) fn make_use_of_missing_unit(
+        &self,
+        user: &UnitId,
+        library_name: &Symbol,
+        primary_name: &Symbol,
+        secondary_name: Option<&Symbol>,
+    ) {
+        let mut missing_unit = self.missing_unit.write();
+        let key = (
+            library_name.clone(),
+            primary_name.clone(),
+            secondary_name.cloned(),
+        );
+        match missing_unit.entry(key) {
+            Entry::Occupied(mut entry) => {
+                entry.get_mut().insert(user.clone());
+            }
+            Entry::Vacant(entry) => {
+                let mut set = FnvHashSet::default();
+                set.insert(user.clone());
+                entry.insert(set);
+            }
+        }
+    }
+
+    /// Resets the analysis state of all design units which need to be re-analyzed
+    /// because another design unit has been added or removed.
+    fn reset(&mut self) {
+        let mut removed = FnvHashSet::default();
+        let mut added = FnvHashSet::default();
+        let mut unchanged = FnvHashSet::default();
+
+        for library in self.libraries.values_mut() {
+            for unit_id in library.added.drain() {
+                added.insert(unit_id);
+            }
+            for unit_id in library.removed.drain() {
+                removed.insert(unit_id);
+            }
+            for unit_id in library.unchanged.drain() {
+                unchanged.insert(unit_id);
+            }
+            library.removed_fingerprints.clear();
+        }
+
+        // Units which only differ in whitespace and comments are analyzed again but do not affect their users
+        let mut affected: FnvHashSet<_> = added
+            .union(&removed)
+            .filter(|unit_id| !unchanged.contains(*unit_id))
+            .cloned()
+            .collect();
+        let changed: FnvHashSet<_> = removed.intersection(&added).cloned().collect();
+        removed = removed.difference(&changed).cloned().collect();
+        added = added.difference(&changed).cloned().collect();
+
+        let users_of = self.users_of.read();
+        let users_of_library_all = self.users_of_library_all.read();
+
+        // Add affected users which do 'use library.all'
+        for unit_id in removed.iter().chain(added.iter()) {
+            if let Some(library_all_affected) = users_of_library_all.get(unit_id.library_name()) {
+                for user in library_all_affected.iter() {
+                    affected.insert(user.clone());
+                }
+            }
+        }
+        let missing_unit = self.missing_unit.read();
+        for ((library_name, primary_name, secondary_name), unit_ids) in missing_unit.iter() {
+            let was_added = added.iter().any(|added_id| {
+                added_id.library_name() == library_name
+                    && added_id.primary_name() == primary_name
+                    && added_id.secondary_name() == secondary_name.as_ref()
+            });
+
+            if was_added {
+                for unit_id in unit_ids.iter() {
+                    affected.insert(unit_id.clone());
+                }
+            }
+        }
+
+        // Affect packages which have got body removed or added
+        // Since a package without body may not have deferred constants
+        for unit_id in added.iter().chain(removed.iter()) {
+            if let AnyKind::Secondary(SecondaryKind::PackageBody) = unit_id.kind() {
+                affected.insert(UnitId::package(
+                    unit_id.library_name(),
+                    unit_id.primary_name(),
+                ));
+            }
+        }
+
+        self.reset_affected(get_all_affected(&users_of, affected));
+        drop(users_of);
+        drop(users_of_library_all);
+        drop(missing_unit);
+
+        let mut users_of = self.users_of.write();
+        let mut users_of_library_all = self.users_of_library_all.write();
+        let mut missing_unit = self.missing_unit.write();
+
+        // Clean-up after removed units
+        for removed_unit in removed.iter() {
+            users_of.remove(removed_unit);
+            if let Some(library_all_affected) =
+                users_of_library_all.get_mut(removed_unit.library_name())
+            {
+                library_all_affected.remove(removed_unit);
+            }
+
+            missing_unit.retain(|_, unit_ids| {
+                unit_ids.remove(removed_unit);
+                !unit_ids.is_empty()
+            });
+        }
+    }
+
+    fn analyze_standard_package(&mut self) {
+        // Analyze standard package first if it exits
+        let std_lib_name = self.symbol_utf8("std");
+        if let Some(standard_units) = self
+            .libraries
+            .get(&std_lib_name)
+            .map(|library| &library.units)
+        {
+            if let Some(locked_unit) =
+                standard_units.get(&UnitKey::Primary(self.symbol_utf8("standard")))
+            {
+                if let AnalysisEntry::Vacant(mut unit) = locked_unit.unit.entry() {
+                    // Clear to ensure the analysis of standard package does not believe it has the standard package
+                    let arena = Arena::new_std();
+                    self.standard_pkg_id = None;
+                    self.standard_arena = None;
+
+                    let std_package =
+                        if let Some(AnyPrimaryUnit::Package(pkg)) = unit.as_primary_mut() {
+                            assert!(pkg.context_clause.is_empty());
+                            assert!(pkg.generic_clause.is_none());
+                            pkg
+                        } else {
+                            panic!("Expected standard package is primary unit");
+                        };
+                    // Ensure no remaining references from previous analysis
+                    clear_references(std_package);
+
+                    let standard_pkg = {
+                        let (lib_arena, id) = self.get_library_arena(&std_lib_name).unwrap();
+                        arena.link(lib_arena);
+                        let std_lib = arena.get(id);
+
+                        arena.explicit(
+                            self.symbol_utf8("standard"),
+                            std_lib,
+                            // Will be overwritten below
+                            AnyEntKind::Design(Design::Package(
+                                Visibility::default(),
+                                Region::default(),
+                            )),
+                            Some(std_package.ident.pos()),
+                        )
+                    };
+
+                    std_package.ident.decl = Some(standard_pkg.id());
+
+                    let universal =
+                        UniversalTypes::new(&arena, standard_pkg, self.symbols.as_ref());
+                    self.universal = Some(universal);
+
+                    // Reserve space in the arena for the standard types
+                    self.standard_types = Some(StandardTypes::new(
+                        &arena,
+                        standard_pkg,
+                        &mut std_package.decl,
+                    ));
+
+                    let context = AnalyzeContext::new(self, locked_unit.unit_id(), &arena);
+
+                    let mut diagnostics = Vec::new();
+                    let root_scope = Scope::default();
+                    let scope = root_scope.nested().in_package_declaration();
+
+                    {
+                        for ent in context.universal_implicits(
+                            UniversalType::Integer,
+                            context.universal_integer().into(),
+                        ) {
+                            unsafe {
+                                arena.add_implicit(universal.integer, ent);
+                            };
+                            scope.add(ent, &mut diagnostics);
+                        }
+
+                        for ent in context.universal_implicits(
+                            UniversalType::Real,
+                            context.universal_real().into(),
+                        ) {
+                            unsafe {
+                                arena.add_implicit(universal.real, ent);
+                            };
+                            scope.add(ent, &mut diagnostics);
+                        }
+                    }
+
+                    for decl in std_package.decl.iter_mut() {
+                        if let Declaration::Type(ref mut type_decl) = decl {
+                            context
+                                .analyze_type_declaration(
+                                    &scope,
+                                    standard_pkg,
+                                    type_decl,
+                                    type_decl.ident.decl, // Set by standard types
+                                    &mut diagnostics,
+                                )
+                                .unwrap();
+                        } else {
+                            context
+                                .analyze_declaration(&scope, standard_pkg, decl, &mut diagnostics)
+                                .unwrap();
+                        }
+                    }
+                    scope.close(&mut diagnostics);
+
+                    let mut region = scope.into_region();
+
+                    context.end_of_package_implicits(&mut region, &mut diagnostics);
+                    let visibility = root_scope.into_visibility();
+
+                    let kind = AnyEntKind::Design(Design::Package(visibility, region));
+                    unsafe {
+                        standard_pkg.set_kind(kind);
+                    }
+
+                    self.standard_pkg_id = Some(standard_pkg.id());
+                    let arena = arena.finalize();
+                    self.standard_arena = Some(arena.clone());
+
+                    let result = AnalysisData {
+                        arena,
+                        diagnostics,
+                        has_circular_dependency: false,
+                        is_cancelled: false,
+                        work_references: Vec::new(),
+                    };
+
+                    unit.finish(result);
+                }
+            }
+        }
+    }
+
+    /// Analyze ieee std_logic_1164 package library sequentially
+    /// The matching operators such as ?= are implicitly defined for arrays with std_ulogic element
+    /// So these types are blessed by the lanaguage and we need global access to them
+    fn analyze_std_logic_1164(&mut self) {
+        if let Some(lib) = self.libraries.get(&self.symbol_utf8("ieee")) {
+            if let Some(unit) = lib.get_unit(&UnitKey::Primary(self.symbol_utf8("std_logic_1164")))
+            {
+                let data = self.get_analysis(unit);
+                let std_logic_arena = &data.result().arena;
+                if let AnyDesignUnit::Primary(primary) = data.deref() {
+                    if let Some(ent) = primary.ent_id() {
+                        let AnyEntKind::Design(Design::Package(_, ref region)) = std_logic_arena.get(ent).kind() else {
+                            unreachable!()
+                        };
+
+                        if let Some(NamedEntities::Single(ent)) = region.lookup_immediate(
+                            &Designator::Identifier(self.symbol_utf8("std_ulogic")),
+                        ) {
+                            self.std_ulogic = Some(ent.id());
+                        }
+
+                        self.arenas.link(&data.result().arena);
+                    }
+                }
+            }
+        }
+    }
+
+    #[cfg(test)]
+    pub fn analyze(&mut self, diagnostics: &mut dyn DiagnosticHandler) {
+        self.analyze_cancellable(&CancellationToken::default(), diagnostics);
+    }
+
+    /// Analyze the design until the token is cancelled, returns false when cancelled
+    /// in which case nothing is added to the diagnostics and the design units which
+    /// were not completely analyzed are analyzed again by the next call
+    pub fn analyze_cancellable(
+        &mut self,
+        token: &CancellationToken,
+        diagnostics: &mut dyn DiagnosticHandler,
+    ) -> bool {
+        self.reset();
+
+        for library in self.libraries.values_mut() {
+            library.refresh(diagnostics);
+        }
+
+        // Rebuild declaration arenas of named entities
+        self.arenas.clear();
+
+        // Analyze standard package first sequentially since everything else in the
+        // language depends on it and we want to save a reference to all types there
+        self.analyze_standard_package();
+        if let Some(std_arena) = self.standard_arena.as_ref() {
+            // @TODO some project.rs unit tests do not have the standard package
+            self.arenas.link(std_arena);
+        }
+
+        self.analyze_std_logic_1164();
+
+        use rayon::prelude::*;
+        // @TODO run in parallel
+        let mut units: Vec<_> = Vec::new();
+        for library in self.libraries.values() {
+            for unit in library.units.values() {
+                units.push(unit);
+            }
+        }
+
+        // The standard packages above are always analyzed completely
+        self.cancellation = token.clone();
+
+        // @TODO compute the best order to process the units in parallel
+        units.par_iter().for_each(|unit| {
+            self.get_analysis(unit);
+        });
+
+        self.cancellation = CancellationToken::default();
+        let is_cancelled = token.is_cancelled();
+        if is_cancelled {
+            for library in self.libraries.values() {
+                for unit in library.units.values() {
+                    let was_cancelled = unit
+                        .unit
+                        .get()
+                        .map(|data| data.result().is_cancelled)
+                        .unwrap_or(false);
+                    if was_cancelled {
+                        unit.unit.reset();
+                    }
+                }
+            }
+        }
+
+        for library in self.libraries.values() {
+            self.arenas.link(&library.arena);
+            for unit in library.units.values() {
+                if let Some(result) = unit.unit.get() {
+                    self.arenas.link(&result.result().arena);
+                }
+            }
+        }
+
+        if is_cancelled {
+            return false;
+        }
+
+        self.constant_values = self.evaluate_constants();
+        let dead_branches = if self.dead_generate.is_some() {
+            self.dead_generate_branches()
+        } else {
+            Vec::new()
+        };
+
+        // Emit diagnostics sorted within a file
+        for library in self.libraries.values() {
+            for unit_id in library.sorted_unit_ids() {
+                let unit = library.units.get(unit_id.key()).unwrap();
+                let unit_diagnostics = unit.unit.expect_analyzed().result().diagnostics.clone();
+                match self.dead_generate {
+                    Some(severity) if !dead_branches.is_empty() => {
+                        for mut diagnostic in unit_diagnostics {
+                            if !dead_branches
+                                .iter()
+                                .any(|span| contains(span, &diagnostic.pos))
+                            {
+                                diagnostics.push(diagnostic);
+                            } else if let Some(severity) = severity {
+                                diagnostic.severity = severity;
+                                diagnostics.push(diagnostic);
+                            }
+                        }
+                    }
+                    _ => diagnostics.append(unit_diagnostics),
+                }
+            }
+        }
+
+        self.check_components_against_entities(diagnostics);
+        self.check_open_output_ports(diagnostics);
+        self.check_port_widths(diagnostics);
+        if self.fanout_limits.is_enabled() {
+            for fanout in self.signal_fanout() {
+                self.fanout_limits.check(&fanout, diagnostics);
+            }
+        }
+        if let Some(severity) = self.unused_declarations {
+            let mut unused = FindUnused::default();
+            let _ = self.search(&mut unused);
+            unused.finish(self, severity, diagnostics);
+        }
+        for library_name in self.synthesis_libraries.iter() {
+            let mut synthesis = CheckSynthesis::new(self, Severity::Warning, diagnostics);
+            let _ = self.search_library(library_name, &mut synthesis);
+        }
+
+        diagnostics.append(self.constant_values.check_indexes(self));
+
+        if self.warn_recursion {
+            self.call_graph().check_recursion(diagnostics);
+        }
+        true
+    }
+
+    /// The branches of if generate statements within architectures that are never selected
+    fn dead_generate_branches(&self) -> Vec<SrcPos> {
+        let mut result = Vec::new();
+        for library in self.libraries.values() {
+            for unit in library.units.values() {
+                if let AnyDesignUnit::Secondary(AnySecondaryUnit::Architecture(ref architecture)) =
+                    *unit.unit.expect_analyzed()
+                {
+                    dead_generate_branches(self, &architecture.statements, &mut result);
+                }
+            }
+        }
+        result
+    }
+
+    /// Evaluate the constants of all packages and of the top level entities in the
+    /// generic overrides
+    /// Units are evaluated repeatedly since constants may be built from constants of
+    /// packages that are evaluated later
+    fn evaluate_constants(&self) -> ConstantValues {
+        let mut values = ConstantValues::default();
+        loop {
+            let len = values.len();
+            for library in self.libraries.values() {
+                for unit_id in library.sorted_unit_ids() {
+                    let unit = library.units.get(unit_id.key()).unwrap();
+                    let unit = unit.unit.expect_analyzed();
+                    match unit.deref() {
+                        AnyDesignUnit::Primary(AnyPrimaryUnit::Package(ref package)) => {
+                            values.declarations(self, &package.decl);
+                        }
+                        AnyDesignUnit::Primary(AnyPrimaryUnit::Entity(ref entity)) => {
+                            if let Some(overrides) = self
+                                .generic_overrides
+                                .get(&library.name().name_utf8(), &entity.name().name_utf8())
+                            {
+                                values.top_level_entity(self, entity, overrides);
+                            }
+                        }
+                        AnyDesignUnit::Secondary(AnySecondaryUnit::Architecture(
+                            ref architecture,
+                        )) => {
+                            let entity_name = architecture.entity_name.item.item.name_utf8();
+                            if self
+                                .generic_overrides
+                                .get(&library.name().name_utf8(), &entity_name)
+                                .is_some()
+                            {
+                                values.declarations(self, &architecture.decl);
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+            }
+            if values.len() == len {
+                return values;
+            }
+        }
+    }
+
+    /// The value of a package level constant that is computed from other values
+    pub fn constant_value(&self, ent: EntRef) -> Option<&ConstantValue> {
+        self.constant_values.computed_value(ent.id())
+    }
+
+    /// Evaluate a static expression from literals, predefined operators and the evaluated
+    /// constants such that other analyses can check ranges, lengths and choices
+    pub fn evaluate_expression(&self, expr: &Expression) -> Option<ConstantValue> {
+        self.constant_values.evaluate(self, expr)
+    }
+
+    /// The source files of each library ordered such that the files with the design units
+    /// that a file depends on precede it, files within a circular dependency come last
+    pub fn compile_order(&self) -> Vec<(Symbol, Source)> {
+        let mut libraries: Vec<_> = self.libraries.values().collect();
+        libraries.sort_by_key(|library| library.name().name_utf8());
+
+        let mut files: Vec<(Symbol, Source)> = Vec::new();
+        let mut file_of_unit: FnvHashMap<UnitId, usize> = FnvHashMap::default();
+        for library in libraries {
+            let mut sources: Vec<_> = library.units_by_source.iter().collect();
+            sources.sort_by(|(x, _), (y, _)| x.file_name().cmp(y.file_name()));
+            for (source, unit_ids) in sources {
+                for unit_id in unit_ids.iter() {
+                    file_of_unit.insert(unit_id.clone(), files.len());
+                }
+                files.push((library.name().clone(), source.clone()));
+            }
+        }
+
+        let mut depends_on: Vec<FnvHashSet<usize>> = vec![FnvHashSet::default(); files.len()];
+        for (unit_id, users) in self.users_of.read().iter() {
+            if let Some(used) = file_of_unit.get(unit_id) {
+                for user in users.iter().filter_map(|user| file_of_unit.get(user)) {
+                    if user != used {
+                        depends_on[*user].insert(*used);
+                    }
+                }
+            }
+        }
+
+        let mut order = Vec::with_capacity(files.len());
+        let mut done = vec![false; files.len()];
+        while order.len() < files.len() {
+            let next = (0..files.len())
+                .find(|idx| !done[*idx] && depends_on[*idx].iter().all(|dep| done[*dep]))
+                // Break a circular dependency by the first remaining file
+                .or_else(|| (0..files.len()).find(|idx| !done[*idx]))
+                .unwrap();
+            done[next] = true;
+            order.push(files[next].clone());
+        }
+        order
+    }
+
+    /// Run the lints for unused declarations and for constructs that are not synthesizable
+    /// over all analyzed design units with the severities, a lint is not run when None
+    pub fn run_lints(
+        &self,
+        unused: Option<Severity>,
+        synthesis: Option<Severity>,
+    ) -> Vec<Diagnostic> {
+        let mut diagnostics = Vec::new();
+        if let Some(severity) = unused {
+            let mut unused = FindUnused::default();
+            let _ = self.search(&mut unused);
+            unused.finish(self, severity, &mut diagnostics);
+        }
+        if let Some(severity) = synthesis {
+            let mut synthesis = CheckSynthesis::new(self, severity, &mut diagnostics);
+            let _ = self.search(&mut synthesis);
+        }
+        diagnostics
+    }
+
+    /// The number of drivers and loads of the signals used within each architecture
+    pub fn signal_fanout(&self) -> Vec<ArchitectureFanout<'_>> {
+        self.architecture_fanout(|_| true)
+    }
+
+    /// The number of drivers and loads of a signal within the architectures where it is visible,
+    /// which are the architectures of the entity that declares it
+    pub fn fanout_of<'a>(&'a self, signal: EntRef) -> Vec<(EntRef<'a>, SignalFanout<'a>)> {
+        let mut design = signal.parent;
+        while let Some(ent) = design {
+            if matches!(
+                ent.kind(),
+                AnyEntKind::Design(Design::Entity(..) | Design::Architecture(..))
+            ) {
+                break;
+            }
+            design = ent.parent;
+        }
+        let design = if let Some(design) = design {
+            design
+        } else {
+            return Vec::new();
+        };
+
+        self.architecture_fanout(|architecture| {
+            architecture.ident.decl == Some(design.id())
+                || architecture.entity_name.reference == Some(design.id())
+        })
+        .into_iter()
+        .filter_map(|fanout| {
+            let signal = fanout.of_signal(signal)?.clone();
+            Some((fanout.architecture, signal))
+        })
+        .collect()
+    }
+
+    fn architecture_fanout(
+        &self,
+        include: impl Fn(&ArchitectureBody) -> bool,
+    ) -> Vec<ArchitectureFanout<'_>> {
+        let mut result = Vec::new();
+        for library in self.libraries.values() {
+            for unit_id in library.sorted_unit_ids() {
+                let unit = library.units.get(unit_id.key()).unwrap();
+                if let AnyDesignUnit::Secondary(AnySecondaryUnit::Architecture(
+                    ref mut architecture,
+                )) = *unit.unit.write()
+                {
+                    if include(architecture) {
+                        result.extend(architecture_fanout(self, architecture));
+                    }
+                }
+            }
+        }
+        result
+    }
+
+    /// Build the graph of calls between subprograms from the analyzed design
+    pub fn call_graph(&self) -> CallGraph<'_> {
+        let mut builder = CallGraphBuilder::new(self);
+        for library in self.libraries.values() {
+            for unit_id in library.sorted_unit_ids() {
+                let unit = library.units.get(unit_id.key()).unwrap();
+                builder.design_unit(&mut unit.unit.write());
+            }
+        }
+        builder.finish()
+    }
+
+    /// Reorder the ports of an entity where the order lists the index of each port in the current order
+    /// Positional port map associations of instances of the entity are changed into named associations
+    /// Returns None when the order does not change the ports
+    pub fn reorder_ports(&self, entity: EntRef, order: &[usize]) -> Option<Vec<TextEdit>> {
+        let mut edits = reorder_port_list(self, entity, order)?;
+        edits
+            .extend(self.named_association_edits(FindPositionalActuals::port_maps_of(entity.id())));
+        Some(edits)
+    }
+
+    /// Group the ports of an entity by mode, see `reorder_ports`
+    pub fn group_ports_by_mode(&self, entity: EntRef) -> Option<Vec<TextEdit>> {
+        let order = ports_grouped_by_mode(self, entity)?;
+        self.reorder_ports(entity, &order)
+    }
+
+    /// The text of an instance of the entity with all generics and ports associated by name
+    /// The component form includes a component declaration for the entity
+    pub fn instantiation_text(&self, entity: EntRef, form: InstantiationForm) -> Option<String> {
+        instantiation_text(self, entity, form)
+    }
+
+    /// The instantiation text as a snippet with placeholders for the label and the actuals
+    pub fn instantiation_snippet(&self, entity: EntRef, form: InstantiationForm) -> Option<String> {
+        instantiation_snippet(self, entity, form)
+    }
+
+    /// The declarations of an instance of a generic package with the actuals
+    /// of the generic map substituted for the generics
+    pub fn expand_package_instance(&self, instance: EntRef) -> Option<String> {
+        expand_package_instance(self, instance)
+    }
+
+    /// Report component declarations whose generics or ports have drifted
+    /// from the entity of the same name in the same library
+    fn check_components_against_entities(&self, diagnostics: &mut dyn DiagnosticHandler) {
+        for (component, entity) in self.default_bindings() {
+            diagnostics.append(component_entity_mismatches(component, entity));
+        }
+    }
+
+    /// The components together with the entity they are bound to by default binding
+    fn default_bindings(&self) -> Vec<(EntRef<'_>, EntRef<'_>)> {
+        let mut bindings = Vec::new();
+        let mut searcher =
+            FindAllEnt::new(self, |ent| matches!(ent.kind(), AnyEntKind::Component(_)));
+        let _ = self.search(&mut searcher);
+
+        let mut checked = FnvHashSet::default();
+        for component in searcher.result {
+            if let (Some(library_name), Designator::Identifier(ident)) =
+                (component.library_name(), component.designator())
+            {
+                if let Some(entity) = self.get_design_entity(library_name, ident) {
+                    checked.insert((component.id(), entity.id()));
+                    bindings.push((component, entity.into()));
+                }
+            }
+        }
+
+        // A component declared in another library such as in a package of components
+        // is bound by default to the entity in the library of the instance
+        for (library_name, library) in self.libraries.iter() {
+            for unit_id in library.sorted_unit_ids() {
+                let unit = library.units.get(unit_id.key()).unwrap();
+                let mut components = Vec::new();
+                if let AnyDesignUnit::Secondary(AnySecondaryUnit::Architecture(ref body)) =
+                    *unit.unit.expect_analyzed()
+                {
+                    default_bound_components(&body.decl, &body.statements, &mut components);
+                }
+
+                for component in components {
+                    let component = self.get_ent(component);
+                    if let Designator::Identifier(ident) = component.designator() {
+                        if let Some(entity) = self.get_design_entity(library_name, ident) {
+                            if checked.insert((component.id(), entity.id())) {
+                                bindings.push((component, entity.into()));
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        bindings
+    }
+
+    fn check_open_output_ports(&self, diagnostics: &mut dyn DiagnosticHandler) {
+        let mut check = CheckOpenOutputPorts::default();
+        for library in self.libraries.values() {
+            for unit_id in library.sorted_unit_ids() {
+                let unit = library.units.get(unit_id.key()).unwrap();
+                check.design_unit(&unit.unit.expect_analyzed());
+            }
+        }
+        check.finish(self, diagnostics);
+    }
+
+    fn check_port_widths(&self, diagnostics: &mut dyn DiagnosticHandler) {
+        let mut check = CheckPortWidths::new(self);
+        for library in self.libraries.values() {
+            for unit_id in library.sorted_unit_ids() {
+                let unit = library.units.get(unit_id.key()).unwrap();
+                check.design_unit(&unit.unit.expect_analyzed());
+            }
+        }
+        check.finish(diagnostics);
+    }
+
+    /// Rewrite a component declaration with the generics and ports of the entity
+    /// of the same name. Returns the span of the component declaration together
+    /// with its replacement, or None when the component already agrees with the entity
+    pub fn sync_component_with_entity(&self, component: EntRef) -> Option<(SrcPos, String)> {
+        let library_name = component.library_name()?;
+        let ident = match (component.kind(), component.designator()) {
+            (AnyEntKind::Component(_), Designator::Identifier(ident)) => ident,
+            _ => return None,
+        };
+        let entity = self.get_design_entity(library_name, ident)?;
+
+        if component_entity_mismatches(component, entity.into()).is_empty() {
+            return None;
+        }
+
+        let mut searcher = FindComponentAndEntity {
+            component: component.id(),
+            entity: entity.id(),
+            component_decl: None,
+            entity_interface: None,
+        };
+        let _ = self.search_library(library_name, &mut searcher);
+        let component_decl = searcher.component_decl?;
+        let (generic_list, port_list) = searcher.entity_interface?;
+
+        let synced = ComponentDeclaration {
+            ident: component_decl.ident,
+            generic_list,
+            port_list,
+            end_ident_pos: None,
+            pos: component_decl.pos.clone(),
+        };
+
+        // Indent continuation lines to the column of the component keyword
+        let indent = " ".repeat(component_decl.pos.start().character as usize);
+        let text = synced.to_string().replace('\n', &format!("\n{indent}"));
+        Some((component_decl.pos, text))
+    }
+
+    /// Get the named entity
+    pub fn get_ent(&self, id: EntityId) -> &AnyEnt {
+        self.arenas.get(id)
+    }
+}
+
+/// Find the syntax of a component declaration and the interface lists of an entity
+struct FindComponentAndEntity {
+    component: EntityId,
+    entity: EntityId,
+    component_decl: Option<ComponentDeclaration>,
+    entity_interface: Option<(Vec<InterfaceDeclaration>, Vec<InterfaceDeclaration>)>,
+}
+
+impl Searcher for FindComponentAndEntity {
+    fn search_decl(&mut self, decl: FoundDeclaration) -> SearchState {
+        match decl {
+            FoundDeclaration::Component(component)
+                if component.ident.decl == Some(self.component) =>
+            {
+                self.component_decl = Some(component.clone());
+            }
+            FoundDeclaration::Entity(entity) if entity.ident.decl == Some(self.entity) => {
+                self.entity_interface = Some((
+                    entity.generic_clause.clone().unwrap_or_default(),
+                    entity.port_clause.clone().unwrap_or_default(),
+                ));
+            }
+            _ => {}
+        }
+
+        if self.component_decl.is_some() && self.entity_interface.is_some() {
+            SearchState::Finished(SearchResult::Found)
+        } else {
+            SearchState::NotFinished
+        }
+    }
+}
+
+/// True if pos is within the span
+fn contains(span: &SrcPos, pos: &SrcPos) -> bool {
+    span.source == pos.source && span.start() <= pos.start() && pos.end() <= span.end()
+}
+
+fn get_all_affected(
+    users_of: &FnvHashMap<UnitId, FnvHashSet<UnitId>>,
+    mut affected: FnvHashSet<UnitId>,
+) -> FnvHashSet<UnitId> {
+    let mut all_affected = FnvHashSet::default();
+    let mut next_affected = FnvHashSet::default();
+
+    while !affected.is_empty() {
+        for user in affected.drain() {
+            all_affected.insert(user.clone());
+
+            if let Some(users) = users_of.get(&user) {
+                for new_user in users.iter() {
+                    if all_affected.insert(new_user.clone()) {
+                        next_affected.insert(new_user.clone());
+                    }
+                }
+            }
+        }
+
+        affected = std::mem::replace(&mut next_affected, affected);
+    }
+    all_affected
+}
+
+/// A name that completes the name being typed at the cursor
+#[derive(Clone, Copy, Debug)]
+pub enum CompletionItem<'a> {
+    Simple(EntRef<'a>),
+    /// A package name followed by `.all` such as `numeric_std.all`
+    AllOf(EntRef<'a>),
+    /// The `all` suffix of a use clause
+    All,
+    /// A predefined attribute after the tick of an attribute name
+    Attribute(&'static PredefinedAttribute),
+}
+
+impl<'a> CompletionItem<'a> {
+    /// The text to insert for the completion
+    pub fn name(&self) -> String {
+        match self {
+            CompletionItem::Simple(ent) => ent.designator().to_string(),
+            CompletionItem::AllOf(ent) => format!("{}.all", ent.designator()),
+            CompletionItem::All => "all".to_owned(),
+            CompletionItem::Attribute(attribute) => attribute.name.to_owned(),
+        }
+    }
+}
+
+/// The result of a search that may have been stopped early by a deadline
+#[derive(PartialEq, Eq, Debug, Clone, Default)]
+pub struct Partial<T> {
+    pub result: T,
+    /// The deadline passed before the search was complete
+    pub incomplete: bool,
+}
+
+/// The token at a position with the kind of its syntax node and the entity it denotes
+#[derive(Debug, Clone)]
+pub struct ItemAt<'a> {
+    pub pos: SrcPos,
+    pub kind: ItemKind,
+    /// None for a name that could not be resolved
+    pub ent: Option<EntRef<'a>>,
+}
+
+/// Where a named entity is declared and where it is defined. The positions differ for
+/// a subprogram, package or protected type with a separate body and a deferred constant.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EntPositions {
+    pub declaration: SrcPos,
+    pub definition: SrcPos,
+}
+
+pub struct EntHierarchy<'a> {
+    pub ent: EntRef<'a>,
+    pub children: Vec<EntHierarchy<'a>>,
+}
+
+impl<'a> EntHierarchy<'a> {
+    fn from_vec(mut symbols: Vec<EntRef<'a>>) -> Vec<EntHierarchy<'a>> {
+        let mut by_parent: FnvHashMap<EntityId, Vec<EntRef>> = Default::default();
+
+        symbols.retain(|ent| {
+            if let Some(parent) = ent.parent_in_same_source() {
+                by_parent.entry(parent.id()).or_default().push(ent);
+                false
+            } else {
+                true
+            }
+        });
+        symbols
+            .into_iter()
+            .map(|ent| Self::from_ent(ent, &by_parent))
+            .collect()
+    }
+
+    fn from_ent(
+        ent: EntRef<'a>,
+        by_parent: &FnvHashMap<EntityId, Vec<EntRef<'a>>>,
+    ) -> EntHierarchy<'a> {
+        EntHierarchy {
+            ent,
+            children: if let Some(children) = by_parent.get(&ent.id()) {
+                children
+                    .iter()
+                    .map(|ent| Self::from_ent(ent, by_parent))
+                    .collect()
+            } else {
+                Vec::new()
+            },
+        }
+    }
+
+    pub fn into_flat(self) -> Vec<EntRef<'a>> {
+        std::iter::once(self.ent)
+            .chain(
+                self.children
+                    .into_iter()
+                    .flat_map(|ent| ent.into_flat().into_iter()),
+            )
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::syntax::test::{check_diagnostics, Code};
+
+    fn new_library_with_diagnostics(code: &Code, name: &str) -> (Library, Vec<Diagnostic>) {
+        let mut diagnostics = Vec::new();
+        let mut library = Library::new(code.symbol(name));
+        library.add_design_file(code.design_file());
+        library.refresh(&mut diagnostics);
+        (library, diagnostics)
+    }
+
+    #[test]
+    fn error_on_duplicate_package_body() {
+        let code = Code::new(
+            "
+package pkg is
+end package;
+
+package body pkg is
+end package body;
+
+package body pkg is
+end package body;
+",
+        );
+        let (library, diagnostics) = new_library_with_diagnostics(&code, "libname");
+
+        assert_eq!(library.units.len(), 2);
+        assert_eq!(library.duplicates.len(), 1);
+
+        check_diagnostics(
+            diagnostics,
+            vec![
+                Diagnostic::error(code.s("pkg", 3), "Duplicate package body of package 'pkg'")
+                    .related(code.s("pkg", 2), "Previously defined here"),
+            ],
+        );
+    }
+
+    #[test]
+    fn error_on_duplicate_primary_unit() {
+        let code = Code::new(
+            "
+package pkg is
+end package;
+
+entity pkg is
+end entity;
+
+entity entname is
+end entity;
+
+package entname is
+end package;
+
+configuration pkg of entname is
+  for rtl
+  end for;
+end configuration;
+
+package pkg is new gpkg generic map (const => foo);
+",
+        );
+        let (library, diagnostics) = new_library_with_diagnostics(&code, "libname");
+
+        assert_eq!(library.units.len(), 2);
+        assert_eq!(library.duplicates.len(), 4);
+        check_diagnostics(
+            diagnostics,
+            vec![
+                Diagnostic::error(
+                    code.s("pkg", 2),
+                    "A primary unit has already been declared with name 'pkg' in library 'libname'"
+                ).related(code.s("pkg", 1), "Previously defined here"),
+                Diagnostic::error(
+                    code.s("entname", 2),
+                    "A primary unit has already been declared with name 'entname' in library 'libname'"
+                ).related(code.s("entname", 1), "Previously defined here"),
+                Diagnostic::error(
+                    code.s("pkg", 3),
+                    "A primary unit has already been declared with name 'pkg' in library 'libname'"
+                ).related(code.s("pkg", 1), "Previously defined here"),
+                Diagnostic::error(
+                    code.s("pkg", 4),
+                    "A primary unit has already been declared with name 'pkg' in library 'libname'"
+                ).related(code.s("pkg", 1), "Previously defined here"),
+            ]
+        );
+    }
+
+    #[test]
+    fn error_on_duplicate_architecture() {
+        let code = Code::new(
+            "
+entity ent is
+end ent;
+
+architecture rtl of ent is
+begin
+end architecture;
+
+architecture rtl of ent is
+begin
+end architecture;
+",
+        );
+        let (library, diagnostics) = new_library_with_diagnostics(&code, "libname");
+
+        assert_eq!(library.units.len(), 2);
+        assert_eq!(library.duplicates.len(), 1);
+        check_diagnostics(
+            diagnostics,
+            vec![Diagnostic::error(
+                code.s("rtl", 2),
+                "Duplicate architecture 'rtl' of entity 'ent'",
+            )
+            .related(code.s("rtl", 1), "Previously defined here")],
+        );
+    }
+
+    #[test]
+    fn architectures_of_entity_include_duplicates() {
+        let code = Code::new(
+            "
+entity ent is
+end ent;
+
+architecture rtl of ent is
+begin
+end architecture;
+
+architecture beh of ent is
+begin
+end architecture;
+
+architecture rtl of ent is
+begin
+end architecture;
+",
+        );
+        let (library, _) = new_library_with_diagnostics(&code, "libname");
+
+        assert_eq!(
+            library.architectures_of(&code.symbol("ent")),
+            vec![
+                (code.symbol("beh"), code.s1("beh").pos()),
+                (code.symbol("rtl"), code.s("rtl", 1).pos()),
+                (code.symbol("rtl"), code.s("rtl", 2).pos()),
+            ]
+        );
+        assert_eq!(library.architectures_of(&code.symbol("missing")), vec![]);
+    }
+
+    #[test]
+    fn error_on_duplicate_configuration() {
+        let code = Code::new(
+            "
+entity ent is
+end entity;
+
+configuration cfg of ent is
+  for rtl
+  end for;
+end configuration;
+
+configuration cfg of work.ent is
+  for rtl
+  end for;
+end configuration;
+",
+        );
+        let (library, diagnostics) = new_library_with_diagnostics(&code, "libname");
+
+        check_diagnostics(
+            diagnostics,
+            vec![Diagnostic::error(
+                code.s("cfg", 2),
+                "A primary unit has already been declared with name 'cfg' in library 'libname'",
+            )
+            .related(code.s1("cfg"), "Previously defined here")],
+        );
+        assert_eq!(library.units.len(), 2);
+        assert_eq!(library.duplicates.len(), 1);
+    }
+}
+
+fn public_symbols<'a>(ent: EntRef<'a>) -> Box<dyn Iterator<Item = EntRef<'a>> + 'a> {
+    match ent.kind() {
+        AnyEntKind::Design(d) => match d {
+            Design::Entity(_, region)
+            | Design::Package(_, region)
+            | Design::UninstPackage(_, region) => Box::new(
+                region
+                    .immediates()
+                    .flat_map(|ent| std::iter::once(ent).chain(public_symbols(ent))),
+            ),
+            _ => Box::new(std::iter::empty()),
+        },
+        AnyEntKind::Type(t) => match t {
+            Type::Protected(region, is_body) if !is_body => Box::new(region.immediates()),
+            _ => Box::new(std::iter::empty()),
+        },
+        _ => Box::new(std::iter::empty()),
+    }
+}
@@ -0,0 +1,201 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this file,
+// You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) 2019, Olof Kraigher olof.kraigher@gmail.com
+
+//! Searchers which resolve the references of the syntax tree using the analyzed design
+
+use super::named_entity::{EntRef, Related};
+use super::root::DesignRoot;
+use crate::ast::search::*;
+use crate::ast::*;
+use crate::data::*;
+
+// Search for reference to declaration/definition at cursor
+pub struct FindEnt<'a, T: Fn(EntRef<'a>) -> bool> {
+    root: &'a DesignRoot,
+    cond: T,
+    pub result: Option<EntRef<'a>>,
+}
+
+impl<'a, T: Fn(EntRef<'a>) -> bool> FindEnt<'a, T> {
+    pub fn new(root: &'a DesignRoot, cond: T) -> FindEnt<'a, T> {
+        FindEnt {
+            root,
+            cond,
+            result: None,
+        }
+    }
+}
+
+impl<'a, T: Fn(EntRef<'a>) -> bool> Searcher for FindEnt<'a, T> {
+    fn search_decl(&mut self, decl: FoundDeclaration) -> SearchState {
+        if let Some(id) = decl.ent_id() {
+            let ent = self.root.get_ent(id);
+            if (self.cond)(ent) {
+                self.result = Some(ent);
+                return SearchState::Finished(SearchResult::Found);
+            }
+        }
+
+        SearchState::NotFinished
+    }
+}
+
+pub struct FindAllEnt<'a, T: FnMut(EntRef<'a>) -> bool> {
+    root: &'a DesignRoot,
+    cond: T,
+    pub result: Vec<EntRef<'a>>,
+}
+
+impl<'a, T: FnMut(EntRef<'a>) -> bool> FindAllEnt<'a, T> {
+    pub fn new(root: &'a DesignRoot, cond: T) -> FindAllEnt<'a, T> {
+        FindAllEnt {
+            root,
+            cond,
+            result: Vec::default(),
+        }
+    }
+}
+
+impl<'a, T: FnMut(EntRef<'a>) -> bool> Searcher for FindAllEnt<'a, T> {
+    fn search_decl(&mut self, decl: FoundDeclaration) -> SearchState {
+        if let Some(id) = decl.ent_id() {
+            let ent = self.root.get_ent(id);
+            if (self.cond)(ent) {
+                self.result.push(ent);
+            }
+        }
+
+        SearchState::NotFinished
+    }
+}
+
+// Search for a declaration/definition and format it
+pub struct FormatDeclaration<'a> {
+    ent: EntRef<'a>,
+    pub result: Option<String>,
+}
+
+impl<'a> FormatDeclaration<'a> {
+    pub fn new(ent: EntRef<'a>) -> FormatDeclaration<'a> {
+        FormatDeclaration { ent, result: None }
+    }
+}
+
+impl<'a> Searcher for FormatDeclaration<'a> {
+    fn search_decl(&mut self, decl: FoundDeclaration) -> SearchState {
+        let id = if let Some(id) = decl.ent_id() {
+            id
+        } else {
+            return NotFinished;
+        };
+
+        if is_implicit_of(self.ent, id) {
+            // Implicit
+            self.result = Some(format!(
+                "-- {}\n\n-- Implicitly defined by:\n{}\n",
+                self.ent.describe(),
+                decl,
+            ));
+            return Finished(Found);
+        } else if self.ent.id() == id {
+            // Explicit
+            self.result = Some(decl.to_string());
+            return Finished(Found);
+        }
+        NotFinished
+    }
+}
+
+// Search for all references to declaration/definition
+pub struct FindAllReferences<'a> {
+    root: &'a DesignRoot,
+    ent: EntRef<'a>,
+    pub references: Vec<SrcPos>,
+}
+
+fn is_instance_of(ent: EntRef, other: EntRef) -> bool {
+    if let Related::InstanceOf(ent) = ent.related {
+        if ent.id() == other.id() {
+            return true;
+        }
+
+        if is_instance_of(ent, other) {
+            return true;
+        }
+    }
+
+    false
+}
+
+fn is_declared_by(ent: EntRef, other: EntRef) -> bool {
+    if let Related::DeclaredBy(ent) = ent.related {
+        if ent.id() == other.id() {
+            return true;
+        }
+    }
+
+    false
+}
+
+fn is_implicit_of(ent: EntRef, id: EntityId) -> bool {
+    match ent.related {
+        Related::ImplicitOf(ent) => ent.id() == id,
+        Related::InstanceOf(ent) => is_implicit_of(ent, id),
+        Related::None => false,
+        Related::DeclaredBy(_) => false,
+    }
+}
+
+fn is_reference(ent: EntRef, other: EntRef) -> bool {
+    if ent.id() == other.id() {
+        return true;
+    }
+
+    if is_instance_of(ent, other) || is_instance_of(other, ent) {
+        return true;
+    }
+
+    if is_declared_by(ent, other) || is_declared_by(other, ent) {
+        return true;
+    }
+
+    false
+}
+impl<'a> FindAllReferences<'a> {
+    pub fn new(root: &'a DesignRoot, ent: EntRef<'a>) -> FindAllReferences<'a> {
+        FindAllReferences {
+            root,
+            ent,
+            references: Vec::new(),
+        }
+    }
+}
+
+impl<'a> Searcher for FindAllReferences<'a> {
+    fn search_decl(&mut self, decl: FoundDeclaration) -> SearchState {
+        if let Some(id) = decl.ent_id() {
+            let other = self.root.get_ent(id);
+
+            if is_reference(self.ent, other) {
+                self.references.push(decl.pos().clone());
+                if let Some(pos) = decl.end_ident_pos() {
+                    self.references.push(pos.clone());
+                }
+            }
+        }
+        NotFinished
+    }
+
+    fn search_pos_with_ref(&mut self, pos: &SrcPos, reference: &mut Reference) -> SearchState {
+        if let Some(id) = reference.as_ref() {
+            let other = self.root.get_ent(*id);
+            if is_reference(self.ent, other) {
+                self.references.push(pos.clone());
+            }
+        };
+        NotFinished
+    }
+}
@@ -9,6 +9,7 @@ use super::names::ResolvedName;
 use super::overloaded::Disambiguated;
 use super::overloaded::SubprogramKind;
 use super::region::*;
+use super::staticness::Staticness;
 use crate::ast::*;
 use crate::data::*;
 
@@ -117,6 +118,13 @@ impl<'a> AnalyzeContext<'a> {
                     } else {
                         self.expr_pos_unknown_ttyp(scope, &choice.pos, expr, diagnostics)?;
                     }
+                    self.check_staticness(
+                        &choice.pos,
+                        self.expression_staticness(expr),
+                        Staticness::Local,
+                        "Choice",
+                        diagnostics,
+                    );
                 }
                 Choice::DiscreteRange(ref mut drange) => {
                     if let Some(ttyp) = ttyp {
@@ -124,6 +132,13 @@ impl<'a> AnalyzeContext<'a> {
                     } else {
                         self.drange_unknown_type(scope, drange, diagnostics)?;
                     }
+                    self.check_staticness(
+                        &choice.pos,
+                        self.drange_staticness(drange),
+                        Staticness::Local,
+                        "Choice",
+                        diagnostics,
+                    );
                 }
                 Choice::Others => {}
             }
@@ -239,8 +254,38 @@ impl<'a> AnalyzeContext<'a> {
     }
 }
 
-impl Diagnostic {
-    pub fn add_subprogram_candidates<'a>(
+/// Diagnostics that refer to named entities, shared by the analysis of all kinds of code
+pub(crate) trait SemanticDiagnostics {
+    fn add_subprogram_candidates<'a>(
+        &mut self,
+        prefix: &str,
+        candidates: impl IntoIterator<Item = OverloadedEnt<'a>>,
+    );
+
+    fn add_type_candididates<'a>(
+        &mut self,
+        prefix: &str,
+        candidates: impl IntoIterator<Item = BaseType<'a>>,
+    );
+
+    fn type_mismatch(pos: &SrcPos, desc: &str, expected_type: TypeEnt) -> Diagnostic;
+
+    fn invalid_selected_name_prefix(named_entity: &AnyEnt, prefix: &SrcPos) -> Diagnostic;
+
+    fn no_declaration_within(
+        named_entity: &AnyEnt,
+        pos: &SrcPos,
+        suffix: &Designator,
+    ) -> Diagnostic;
+
+    fn ambiguous_call<'a>(
+        call_name: &WithPos<Designator>,
+        candidates: impl IntoIterator<Item = OverloadedEnt<'a>>,
+    ) -> Diagnostic;
+}
+
+impl SemanticDiagnostics for Diagnostic {
+    fn add_subprogram_candidates<'a>(
         &mut self,
         prefix: &str,
         candidates: impl IntoIterator<Item = OverloadedEnt<'a>>,
@@ -255,7 +300,7 @@ impl Diagnostic {
         }
     }
 
-    pub fn add_type_candididates<'a>(
+    fn add_type_candididates<'a>(
         &mut self,
         prefix: &str,
         candidates: impl IntoIterator<Item = BaseType<'a>>,
@@ -269,33 +314,15 @@ impl Diagnostic {
             }
         }
     }
-}
 
-impl<'a> AnyEnt<'a> {
-    pub(super) fn kind_error(&self, pos: &SrcPos, expected: &str) -> Diagnostic {
-        let mut error = Diagnostic::error(
-            pos,
-            format!("Expected {}, got {}", expected, self.describe()),
-        );
-        if let Some(decl_pos) = self.decl_pos() {
-            error.add_related(decl_pos, "Defined here");
-        }
-        error
-    }
-}
-
-impl Diagnostic {
-    pub(super) fn type_mismatch(pos: &SrcPos, desc: &str, expected_type: TypeEnt) -> Diagnostic {
+    fn type_mismatch(pos: &SrcPos, desc: &str, expected_type: TypeEnt) -> Diagnostic {
         Diagnostic::error(
             pos,
             format!("{} does not match {}", desc, expected_type.describe()),
         )
     }
 
-    pub(super) fn invalid_selected_name_prefix(
-        named_entity: &AnyEnt,
-        prefix: &SrcPos,
-    ) -> Diagnostic {
+    fn invalid_selected_name_prefix(named_entity: &AnyEnt, prefix: &SrcPos) -> Diagnostic {
         Diagnostic::error(
             prefix,
             capitalize(&format!(
@@ -305,7 +332,7 @@ impl Diagnostic {
         )
     }
 
-    pub(super) fn no_declaration_within(
+    fn no_declaration_within(
         named_entity: &AnyEnt,
         pos: &SrcPos,
         suffix: &Designator,
@@ -319,4 +346,29 @@ impl Diagnostic {
             ),
         )
     }
+
+    fn ambiguous_call<'a>(
+        call_name: &WithPos<Designator>,
+        candidates: impl IntoIterator<Item = OverloadedEnt<'a>>,
+    ) -> Diagnostic {
+        let mut diag = Diagnostic::error(
+            &call_name.pos,
+            format!("Ambiguous call to {}", call_name.item.describe()),
+        );
+        diag.add_subprogram_candidates("Might be", candidates);
+        diag
+    }
+}
+
+impl<'a> AnyEnt<'a> {
+    pub(super) fn kind_error(&self, pos: &SrcPos, expected: &str) -> Diagnostic {
+        let mut error = Diagnostic::error(
+            pos,
+            format!("Expected {}, got {}", expected, self.describe()),
+        );
+        if let Some(decl_pos) = self.decl_pos() {
+            error.add_related(decl_pos, "Defined here");
+        }
+        error
+    }
 }
@@ -0,0 +1,229 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this file,
+// You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) 2023, Olof Kraigher olof.kraigher@gmail.com
+
+//! Compare the signals read by a process with its sensitivity list
+//! Signals read within the clock edge branch of a clocked process need not be in the sensitivity list
+
+use super::analyze::*;
+use super::named_entity::*;
+use super::reset_value::is_clock_edge;
+use crate::ast::search::*;
+use crate::ast::*;
+use crate::config::VHDLStandard;
+use crate::data::*;
+
+impl<'a> AnalyzeContext<'a> {
+    /// Warn about signals that are read but missing from the sensitivity list
+    /// and hint about signals of the sensitivity list that are never read
+    /// Missing signals are fixed by adding them or, since VHDL-2008, by `process (all)`
+    pub fn check_sensitivity_list_completeness(
+        &self,
+        names: &mut [WithPos<Name>],
+        statements: &mut [LabeledSequentialStatement],
+        diagnostics: &mut dyn DiagnosticHandler,
+    ) {
+        if statements.is_empty() {
+            // A placeholder process
+            return;
+        }
+
+        let mut sensitive = Vec::with_capacity(names.len());
+        for name in names.iter_mut() {
+            let mut reads = FindSignalReads::new(self);
+            let _ = name.search(&mut reads);
+            if let Some((id, _)) = reads.reads.first() {
+                sensitive.push((*id, name.pos.clone()));
+            } else {
+                // The sensitivity list has errors
+                return;
+            }
+        }
+
+        let mut reads = FindSignalReads::new(self);
+        reads.sequential_part(statements);
+
+        let list_pos = names
+            .first()
+            .zip(names.last())
+            .map(|(first, last)| first.pos.clone().combine_into(&last.pos));
+
+        for (id, pos) in reads.reads.iter() {
+            if !sensitive.iter().any(|(sensitive_id, _)| sensitive_id == id) {
+                let designator = self.arena.get(*id).designator();
+                let mut diagnostic = Diagnostic::warning(
+                    pos,
+                    format!(
+                        "Signal '{designator}' is read within the process but is missing from the sensitivity list"
+                    ),
+                );
+                if let Some(ref list_pos) = list_pos {
+                    diagnostic = diagnostic.unsafe_fix(
+                        format!("Add '{designator}' to the sensitivity list"),
+                        vec![TextEdit::replace(
+                            list_pos.pos_at_end(),
+                            format!(", {designator}"),
+                        )],
+                    );
+                    if self.root.standard >= VHDLStandard::VHDL2008 {
+                        diagnostic = diagnostic.unsafe_fix(
+                            "Replace the sensitivity list with 'all'",
+                            vec![TextEdit::replace(list_pos, "all")],
+                        );
+                    }
+                }
+                diagnostics.push(diagnostic);
+            }
+        }
+
+        for (id, pos) in sensitive.iter() {
+            if !reads.is_read(*id) {
+                diagnostics.hint(
+                    pos,
+                    format!(
+                        "Signal '{}' is in the sensitivity list but is never read within the process",
+                        self.arena.get(*id).designator()
+                    ),
+                );
+            }
+        }
+    }
+}
+
+/// Find the signals read by sequential statements and the position of the first read of each
+/// Assignment targets, actuals of formals of mode out and prefixes of attributes
+/// that are not signal attributes such as `sig'length` do not read the value
+struct FindSignalReads<'c, 'a> {
+    context: &'c AnalyzeContext<'a>,
+    reads: Vec<(EntityId, SrcPos)>,
+    /// Signals only read within the clock edge branch of a clocked process
+    clocked_reads: Vec<EntityId>,
+    excluded: Vec<SrcPos>,
+}
+
+impl<'c, 'a> FindSignalReads<'c, 'a> {
+    fn new(context: &'c AnalyzeContext<'a>) -> Self {
+        Self {
+            context,
+            reads: Vec::new(),
+            clocked_reads: Vec::new(),
+            excluded: Vec::new(),
+        }
+    }
+
+    fn is_read(&self, id: EntityId) -> bool {
+        self.reads.iter().any(|(read_id, _)| *read_id == id) || self.clocked_reads.contains(&id)
+    }
+
+    fn is_excluded(&self, pos: &SrcPos) -> bool {
+        self.excluded.iter().any(|excluded| {
+            excluded.source == pos.source
+                && excluded.start() <= pos.start()
+                && pos.end() <= excluded.end()
+        })
+    }
+
+    fn sequential_part(&mut self, statements: &mut [LabeledSequentialStatement]) {
+        for statement in statements.iter_mut() {
+            match statement.statement.item {
+                SequentialStatement::If(ref mut ifstmt) => {
+                    for conditional in ifstmt.conds.conditionals.iter_mut() {
+                        let _ = conditional.condition.search(self);
+                        if is_clock_edge(&conditional.condition.item) {
+                            let required = std::mem::take(&mut self.reads);
+                            self.sequential_part(&mut conditional.item);
+                            let clocked = std::mem::replace(&mut self.reads, required);
+                            self.clocked_reads
+                                .extend(clocked.into_iter().map(|(id, _)| id));
+                        } else {
+                            self.sequential_part(&mut conditional.item);
+                        }
+                    }
+                    if let Some(ref mut else_item) = ifstmt.conds.else_item {
+                        self.sequential_part(else_item);
+                    }
+                }
+                SequentialStatement::Case(ref mut case_stmt) => {
+                    let _ = case_stmt.expression.search(self);
+                    for alternative in case_stmt.alternatives.iter_mut() {
+                        self.sequential_part(&mut alternative.item);
+                    }
+                }
+                SequentialStatement::Loop(ref mut loop_stmt) => {
+                    match loop_stmt.iteration_scheme {
+                        Some(IterationScheme::For(_, ref mut drange)) => {
+                            let _ = drange.search(self);
+                        }
+                        Some(IterationScheme::While(ref mut condition)) => {
+                            let _ = condition.search(self);
+                        }
+                        None => {}
+                    }
+                    self.sequential_part(&mut loop_stmt.statements);
+                }
+                _ => {
+                    let _ = statement.search(self);
+                }
+            }
+        }
+    }
+}
+
+impl<'c, 'a> Searcher for FindSignalReads<'c, 'a> {
+    fn search_pos_with_ref(&mut self, pos: &SrcPos, reference: &mut Reference) -> SearchState {
+        if let Some(id) = reference {
+            if let AnyEntKind::Object(object) = self.context.arena.get(*id).kind() {
+                if object.class == ObjectClass::Signal
+                    && !self.reads.iter().any(|(read_id, _)| read_id == id)
+                    && !self.is_excluded(pos)
+                {
+                    self.reads.push((*id, pos.clone()));
+                }
+            }
+        }
+        NotFinished
+    }
+
+    fn search_target(&mut self, _target: &WithPos<Target>) -> SearchState {
+        Finished(NotFound)
+    }
+
+    fn search_attribute(&mut self, attr: &AttributeName) -> SearchState {
+        if !matches!(attr.attr.item, AttributeDesignator::Signal(_)) {
+            self.excluded.push(attr.name.pos.clone());
+        }
+        NotFinished
+    }
+
+    fn search_call_or_indexed(&mut self, call: &CallOrIndexed) -> SearchState {
+        let subprogram = call
+            .name
+            .item
+            .get_suffix_reference()
+            .and_then(|id| OverloadedEnt::from_any(self.context.arena.get(id)));
+
+        if let Some(subprogram) = subprogram {
+            for (idx, assoc) in call.parameters.iter().enumerate() {
+                let formal = if let Some(ref formal) = assoc.formal {
+                    formal
+                        .item
+                        .get_suffix_reference()
+                        .map(|id| self.context.arena.get(id))
+                } else {
+                    subprogram.formals().nth(idx).map(|formal| formal.inner())
+                };
+
+                let is_output = formal.is_some_and(|formal| {
+                    matches!(formal.kind(), AnyEntKind::Object(object)
+                        if object.mode() == Some(Mode::Out))
+                });
+                if is_output {
+                    self.excluded.push(assoc.actual.pos.clone());
+                }
+            }
+        }
+        NotFinished
+    }
+}
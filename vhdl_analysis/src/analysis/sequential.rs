@@ -7,8 +7,9 @@
 // These fields are better explicit than .. since we are forced to consider if new fields should be searched
 #![allow(clippy::unneeded_field_pattern)]
 
-use super::named_entity::TypeEnt;
+use super::named_entity::{LabelType, TypeEnt};
 use super::*;
+use crate::ast::search::*;
 use crate::ast::*;
 use crate::data::*;
 use analyze::*;
@@ -142,6 +143,12 @@ impl<'a> AnalyzeContext<'a> {
                     condition_clause,
                     timeout_clause,
                 } = wait_stmt;
+                if self.in_sensitive_process.get() {
+                    diagnostics.error(
+                        &statement.statement.pos,
+                        "Wait statement is not allowed in a process with a sensitivity list",
+                    );
+                }
                 self.sensitivity_list_check(scope, sensitivity_clause, diagnostics)?;
                 if let Some(expr) = condition_clause {
                     self.boolean_expr(scope, expr, diagnostics)?;
@@ -227,17 +234,23 @@ impl<'a> AnalyzeContext<'a> {
             }
             SequentialStatement::Case(ref mut case_stmt) => {
                 let CaseStatement {
-                    is_matching: _,
+                    is_matching,
                     expression,
                     alternatives,
                     end_label_pos: _,
                 } = case_stmt;
                 let ctyp = as_fatal(self.expr_unambiguous_type(scope, expression, diagnostics))?;
+                if *is_matching {
+                    self.check_matching_case(ctyp, expression, alternatives, diagnostics);
+                }
                 for alternative in alternatives.iter_mut() {
                     let Alternative { choices, item } = alternative;
                     self.choice_with_ttyp(scope, ctyp, choices, diagnostics)?;
                     self.analyze_sequential_part(scope, parent, item, diagnostics)?;
                 }
+                if let (Some(ctyp), false) = (ctyp, *is_matching) {
+                    self.check_case_choices(ctyp, expression, alternatives, diagnostics);
+                }
             }
             SequentialStatement::Loop(ref mut loop_stmt) => {
                 let LoopStatement {
@@ -315,6 +328,67 @@ impl<'a> AnalyzeContext<'a> {
         Ok(())
     }
 
+    /// LRM 10.9 Additional rules for the matching case statement
+    fn check_matching_case(
+        &self,
+        ctyp: Option<TypeEnt<'a>>,
+        expression: &WithPos<Expression>,
+        alternatives: &[Alternative<Vec<LabeledSequentialStatement>>],
+        diagnostics: &mut dyn DiagnosticHandler,
+    ) {
+        if let Some(ctyp) = ctyp {
+            let elem_type = if let Some((elem_type, indexes)) = ctyp.array_type() {
+                if indexes.len() == 1 {
+                    Some(elem_type)
+                } else {
+                    None
+                }
+            } else {
+                Some(ctyp)
+            };
+
+            if !elem_type.is_some_and(|typ| self.has_matching_op(typ.base_type())) {
+                diagnostics.error(
+                    &expression.pos,
+                    format!(
+                        "Expression of a matching case statement must be of type BIT, STD_ULOGIC or a one-dimensional array of these, got {}",
+                        ctyp.describe()
+                    ),
+                );
+            }
+        }
+
+        if literal_pattern(&expression.item).is_some_and(|pattern| pattern.contains(&b'-')) {
+            diagnostics.error(
+                &expression.pos,
+                "Expression of a matching case statement may not contain '-'",
+            );
+        }
+
+        let mut previous: Vec<(Vec<u8>, &SrcPos)> = Vec::new();
+        for alternative in alternatives.iter() {
+            for choice in alternative.choices.iter() {
+                if let Choice::Expression(ref expr) = choice.item {
+                    if let Some(pattern) = literal_pattern(expr) {
+                        if let Some((_, prev_pos)) = previous
+                            .iter()
+                            .find(|(prev, _)| patterns_overlap(prev, &pattern))
+                        {
+                            diagnostics.push(
+                                Diagnostic::error(
+                                    &choice.pos,
+                                    "Choice overlaps with a previous choice of the matching case statement",
+                                )
+                                .related(*prev_pos, "Previous choice"),
+                            );
+                        }
+                        previous.push((pattern, &choice.pos));
+                    }
+                }
+            }
+        }
+    }
+
     fn check_loop_label(
         &self,
         scope: &Scope<'a>,
@@ -363,6 +437,10 @@ impl<'a> AnalyzeContext<'a> {
         diagnostics: &mut dyn DiagnosticHandler,
     ) -> FatalResult {
         for statement in statements.iter_mut() {
+            if self.is_cancelled() {
+                return Ok(());
+            }
+
             let parent = if let Some(id) = statement.label.decl {
                 self.arena.get(id)
             } else {
@@ -372,8 +450,149 @@ impl<'a> AnalyzeContext<'a> {
             self.analyze_sequential_statement(scope, parent, statement, diagnostics)?;
         }
 
+        check_reachability(statements, diagnostics);
         Ok(())
     }
+
+    /// Warn when the end of the statements of a function body can be reached
+    /// without executing a return statement
+    pub fn check_return_paths(
+        &self,
+        parent: EntRef<'a>,
+        statements: &[LabeledSequentialStatement],
+        diagnostics: &mut dyn DiagnosticHandler,
+    ) {
+        if !matches!(SequentialRoot::from(parent), SequentialRoot::Function(_)) {
+            return;
+        }
+
+        if statements.iter().all(completes_normally) {
+            if let Some(pos) = parent.decl_pos() {
+                diagnostics.warning(
+                    pos,
+                    format!(
+                        "Function '{}' can reach the end without returning a value",
+                        parent.designator()
+                    ),
+                );
+            }
+        }
+    }
+}
+
+/// Warn about the first statement following a statement that unconditionally transfers control
+fn check_reachability(
+    statements: &[LabeledSequentialStatement],
+    diagnostics: &mut dyn DiagnosticHandler,
+) {
+    if let Some(idx) = statements.iter().position(transfers_control) {
+        if let Some(unreachable) = statements.get(idx + 1) {
+            diagnostics.push(
+                Diagnostic::warning(&unreachable.statement.pos, "Unreachable statement").related(
+                    &statements[idx].statement.pos,
+                    "Execution does not continue after this statement",
+                ),
+            );
+        }
+    }
+}
+
+/// A return, exit or next statement without condition or an if or case statement
+/// where each alternative contains such a statement
+fn transfers_control(statement: &LabeledSequentialStatement) -> bool {
+    match statement.statement.item {
+        SequentialStatement::Return(_)
+        | SequentialStatement::Exit(ExitStatement {
+            condition: None, ..
+        })
+        | SequentialStatement::Next(NextStatement {
+            condition: None, ..
+        }) => true,
+        SequentialStatement::If(ref ifstmt) => {
+            ifstmt.conds.else_item.as_ref().is_some_and(|else_item| {
+                ifstmt
+                    .conds
+                    .conditionals
+                    .iter()
+                    .map(|cond| &cond.item)
+                    .chain(std::iter::once(else_item))
+                    .all(|statements| statements.iter().any(transfers_control))
+            })
+        }
+        SequentialStatement::Case(ref case_stmt) => case_stmt
+            .alternatives
+            .iter()
+            .all(|alternative| alternative.item.iter().any(transfers_control)),
+        _ => false,
+    }
+}
+
+/// True if execution may continue after the statement
+fn completes_normally(statement: &LabeledSequentialStatement) -> bool {
+    match statement.statement.item {
+        SequentialStatement::Return(_) => false,
+        SequentialStatement::If(ref ifstmt) => {
+            if let Some(ref else_item) = ifstmt.conds.else_item {
+                ifstmt
+                    .conds
+                    .conditionals
+                    .iter()
+                    .map(|cond| &cond.item)
+                    .chain(std::iter::once(else_item))
+                    .any(|statements| statements.iter().all(completes_normally))
+            } else {
+                true
+            }
+        }
+        // The choices of a case statement must cover all values
+        SequentialStatement::Case(ref case_stmt) => case_stmt
+            .alternatives
+            .iter()
+            .any(|alternative| alternative.item.iter().all(completes_normally)),
+        SequentialStatement::Loop(ref loop_stmt) => {
+            // A while or for loop may run zero times
+            loop_stmt.iteration_scheme.is_some()
+                || exits_loop(statement.label.decl, &loop_stmt.statements, 0)
+        }
+        _ => true,
+    }
+}
+
+/// True if an exit statement leaves the loop with the label, `depth` is the number
+/// of loops between the statements and the loop
+fn exits_loop(
+    label: Option<EntityId>,
+    statements: &[LabeledSequentialStatement],
+    depth: usize,
+) -> bool {
+    statements
+        .iter()
+        .any(|statement| match statement.statement.item {
+            SequentialStatement::Exit(ref exit_stmt) => match exit_stmt.loop_label {
+                Some(ref loop_label) => label.is_some() && loop_label.reference == label,
+                None => depth == 0,
+            },
+            SequentialStatement::If(ref ifstmt) => {
+                ifstmt
+                    .conds
+                    .conditionals
+                    .iter()
+                    .any(|cond| exits_loop(label, &cond.item, depth))
+                    || ifstmt
+                        .conds
+                        .else_item
+                        .as_ref()
+                        .is_some_and(|else_item| exits_loop(label, else_item, depth))
+            }
+            SequentialStatement::Case(ref case_stmt) => case_stmt
+                .alternatives
+                .iter()
+                .any(|alternative| exits_loop(label, &alternative.item, depth)),
+            SequentialStatement::Loop(ref loop_stmt) => {
+                exits_loop(label, &loop_stmt.statements, depth + 1)
+            }
+            _ => false,
+        })
 }
 
 enum SequentialRoot<'a> {
@@ -382,6 +601,67 @@ enum SequentialRoot<'a> {
     Function(TypeEnt<'a>),
 }
 
+/// The labels of the loops enclosing a sequential statement from the innermost
+/// to the outermost, loops without a label are skipped
+pub(super) fn enclosing_loop_labels(ent: EntRef) -> Vec<EntRef> {
+    let mut labels = Vec::new();
+    let mut ent = Some(ent);
+    while let Some(current) = ent {
+        match current.kind() {
+            AnyEntKind::Sequential(Some(Sequential::Loop)) => {
+                if matches!(current.designator(), Designator::Identifier(_)) {
+                    labels.push(current);
+                }
+            }
+            AnyEntKind::Sequential(_) => {}
+            _ => break,
+        }
+        ent = current.parent;
+    }
+    labels
+}
+
+/// Find the innermost loop statement that contains the cursor
+pub(super) struct LoopAtCursor {
+    source: Source,
+    cursor: Position,
+    pub result: Option<EntityId>,
+}
+
+impl LoopAtCursor {
+    pub fn new(source: &Source, cursor: Position) -> LoopAtCursor {
+        LoopAtCursor {
+            source: source.clone(),
+            cursor,
+            result: None,
+        }
+    }
+}
+
+impl Searcher for LoopAtCursor {
+    fn search_sequential_statement(
+        &mut self,
+        statement: &LabeledSequentialStatement,
+    ) -> SearchState {
+        let pos = &statement.statement.pos;
+        if pos.start() <= self.cursor && self.cursor <= pos.end() {
+            if let SequentialStatement::Loop(_) = statement.statement.item {
+                // Nested loops are searched after the enclosing loop
+                self.result = statement.label.decl;
+            }
+        }
+        NotFinished
+    }
+
+    fn search_source(&mut self, source: &Source) -> SearchState {
+        if source == &self.source {
+            NotFinished
+        } else {
+            Finished(NotFound)
+        }
+    }
+}
+
 fn find_outer_loop(ent: EntRef, label: Option<&Symbol>) -> bool {
     match ent.kind() {
         AnyEntKind::Sequential(Some(Sequential::Loop)) => {
@@ -406,6 +686,34 @@ fn find_outer_loop(ent: EntRef, label: Option<&Symbol>) -> bool {
     }
 }
 
+/// The characters of a, possibly qualified, character or string literal
+fn literal_pattern(expr: &Expression) -> Option<Vec<u8>> {
+    match expr {
+        Expression::Literal(Literal::Character(chr)) => Some(vec![*chr]),
+        Expression::Literal(Literal::String(string)) => Some(string.bytes.clone()),
+        Expression::Qualified(qexpr) => literal_pattern(&qexpr.expr.item),
+        _ => None,
+    }
+}
+
+/// Two choices overlap when some value is matched by both, '-' matches any value
+/// and the weak values 'L' and 'H' match '0' and '1'
+fn patterns_overlap(left: &[u8], right: &[u8]) -> bool {
+    fn strong(chr: u8) -> u8 {
+        match chr {
+            b'L' => b'0',
+            b'H' => b'1',
+            chr => chr,
+        }
+    }
+
+    left.len() == right.len()
+        && left
+            .iter()
+            .zip(right.iter())
+            .all(|(&l, &r)| l == b'-' || r == b'-' || strong(l) == strong(r))
+}
+
 impl<'a> From<EntRef<'a>> for SequentialRoot<'a> {
     fn from(value: EntRef<'a>) -> Self {
         match value.kind() {
@@ -409,7 +409,9 @@ impl<'a> AnalyzeContext<'a> {
                 [
                     (
                         self.ident("Status"),
-                        AnyEntKind::Object(Object::const_param(Subtype::new(file_open_status))),
+                        AnyEntKind::Object(Object::variable_out_param(Subtype::new(
+                            file_open_status,
+                        ))),
                     ),
                     (self.ident("F"), AnyEntKind::InterfaceFile(file_type)),
                     (
@@ -448,12 +450,7 @@ impl<'a> AnalyzeContext<'a> {
                     (self.ident("F"), AnyEntKind::InterfaceFile(file_type)),
                     (
                         self.ident("VALUE"),
-                        AnyEntKind::Object(Object {
-                            class: ObjectClass::Variable,
-                            iface: Some(ObjectInterface::Parameter(Mode::Out)),
-                            subtype: Subtype::new(type_mark),
-                            has_default: false,
-                        }),
+                        AnyEntKind::Object(Object::variable_out_param(Subtype::new(type_mark))),
                     ),
                 ],
                 None,
@@ -536,6 +533,7 @@ impl<'a> AnalyzeContext<'a> {
                     class: ObjectClass::Variable,
                     iface: Some(ObjectInterface::Parameter(Mode::InOut)),
                     subtype: Subtype::new(type_ent.to_owned()),
+                    signal_kind: None,
                     has_default: false,
                 }),
             )],
@@ -1,5 +1,5 @@
 use crate::analysis::static_expression::BitStringConversionError::EmptySignedExpansion;
-use crate::ast::{BaseSpecifier, BitString};
+use crate::ast::{AbstractLiteral, BaseSpecifier, BitString, Expression, Literal, Operator};
 use crate::Latin1String;
 use itertools::Itertools;
 use std::cmp::Ordering;
@@ -97,83 +97,6 @@ fn test_decimal_to_binary() {
     }
 }
 
-impl BaseSpecifier {
-    /// Returns whether this base specifier represents a signed value
-    /// (i.e. `SX` for signed hexadecimal) or an unsigned value
-    /// (i.e. `UX` or `X` for unsigned hexadecimal)
-    pub fn is_signed(&self) -> bool {
-        match self {
-            BaseSpecifier::SX | BaseSpecifier::SO | BaseSpecifier::SB => true,
-            BaseSpecifier::B
-            | BaseSpecifier::UB
-            | BaseSpecifier::O
-            | BaseSpecifier::UO
-            | BaseSpecifier::X
-            | BaseSpecifier::UX
-            | BaseSpecifier::D => false,
-        }
-    }
-
-    /// Get the digits that are obtained by replacing `byte` with the
-    /// appropriate sequence of characters as defined in the standard (section 15.8).
-    ///
-    /// # Special Cases
-    /// If the base specifier is `D`, i.e. decimal, return the byte itself (wrapped as array)
-    ///
-    /// # Example
-    /// ```
-    /// use vhdl_lang::ast::BaseSpecifier;
-    ///
-    /// let digits: Vec<u8> = BaseSpecifier::UX.get_extended_digits(b'C');
-    /// assert_eq!(digits, Vec::from("1100"));
-    ///
-    /// let digits: Vec<u8> = BaseSpecifier::O.get_extended_digits(b'F');
-    /// assert_eq!(digits, Vec::from("FFF"))
-    /// ```
-    pub fn get_extended_digits(&self, byte: u8) -> Vec<u8> {
-        match self {
-            // For O, UO and SO, the values 1-7 are replaced.
-            // All other values are left as-is.
-            BaseSpecifier::O | BaseSpecifier::UO | BaseSpecifier::SO => match byte {
-                b'0' => Vec::from("000"),
-                b'1' => Vec::from("001"),
-                b'2' => Vec::from("010"),
-                b'3' => Vec::from("011"),
-                b'4' => Vec::from("100"),
-                b'5' => Vec::from("101"),
-                b'6' => Vec::from("110"),
-                b'7' => Vec::from("111"),
-                _ => vec![byte; 3],
-            },
-            // For U, UX and SX, the values 1-9 and A-F are replaced.
-            // All other values are left as-is.
-            BaseSpecifier::X | BaseSpecifier::UX | BaseSpecifier::SX => match byte {
-                b'0' => Vec::from("0000"),
-                b'1' => Vec::from("0001"),
-                b'2' => Vec::from("0010"),
-                b'3' => Vec::from("0011"),
-                b'4' => Vec::from("0100"),
-                b'5' => Vec::from("0101"),
-                b'6' => Vec::from("0110"),
-                b'7' => Vec::from("0111"),
-                b'8' => Vec::from("1000"),
-                b'9' => Vec::from("1001"),
-                b'A' | b'a' => Vec::from("1010"),
-                b'B' | b'b' => Vec::from("1011"),
-                b'C' | b'c' => Vec::from("1100"),
-                b'D' | b'd' => Vec::from("1101"),
-                b'E' | b'e' => Vec::from("1110"),
-                b'F' | b'f' => Vec::from("1111"),
-                _ => vec![byte; 4],
-            },
-            // Binary values are simply the values left as they are.
-            BaseSpecifier::B | BaseSpecifier::UB | BaseSpecifier::SB | BaseSpecifier::D => {
-                vec![byte]
-            }
-        }
-    }
-}
-
 /// Represents errors that occur when converting a bit string to a regular string
 #[derive(PartialEq, Eq, Clone, Debug)]
 pub(crate) enum BitStringConversionError {
@@ -295,19 +218,51 @@ pub(crate) fn bit_string_to_string(
     }
 }
 
+/// Evaluates an integer expression of literals and arithmetic operators such as `1+1`
+/// Returns `None` when the expression depends on names or cannot be evaluated
+pub(crate) fn static_integer(expr: &Expression) -> Option<i64> {
+    match expr {
+        Expression::Literal(Literal::AbstractLiteral(AbstractLiteral::Integer(value))) => {
+            i64::try_from(*value).ok()
+        }
+        Expression::Unary(op, operand) => {
+            let operand = static_integer(&operand.item)?;
+            match op.item.item {
+                Operator::Plus => Some(operand),
+                Operator::Minus => operand.checked_neg(),
+                Operator::Abs => operand.checked_abs(),
+                _ => None,
+            }
+        }
+        Expression::Binary(op, left, right) => {
+            let left = static_integer(&left.item)?;
+            let right = static_integer(&right.item)?;
+            match op.item.item {
+                Operator::Plus => left.checked_add(right),
+                Operator::Minus => left.checked_sub(right),
+                Operator::Times => left.checked_mul(right),
+                Operator::Div => left.checked_div(right),
+                Operator::Pow => u32::try_from(right)
+                    .ok()
+                    .and_then(|right| left.checked_pow(right)),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
 #[cfg(test)]
 mod test_mod {
     use crate::analysis::static_expression::{bit_string_to_string, BitStringConversionError};
     use crate::ast::{BaseSpecifier, BitString};
     use crate::Latin1String;
 
-    impl BitString {
-        fn new(length: Option<u32>, base: BaseSpecifier, value: &str) -> BitString {
-            BitString {
-                length,
-                base,
-                value: Latin1String::from_utf8_unchecked(value),
-            }
+    fn bit_string(length: Option<u32>, base: BaseSpecifier, value: &str) -> BitString {
+        BitString {
+            length,
+            base,
+            value: Latin1String::from_utf8_unchecked(value),
         }
     }
 
@@ -327,7 +282,7 @@ mod test_mod {
         ];
         for base_specifier in all_base_specifiers {
             assert_eq!(
-                bit_string_to_string(&BitString::new(None, base_specifier, "")).unwrap(),
+                bit_string_to_string(&bit_string(None, base_specifier, "")).unwrap(),
                 Latin1String::empty()
             )
         }
@@ -336,17 +291,17 @@ mod test_mod {
     #[test]
     fn test_illegal_decimal_character() {
         assert_eq!(
-            bit_string_to_string(&BitString::new(None, BaseSpecifier::D, "12AFFE")),
+            bit_string_to_string(&bit_string(None, BaseSpecifier::D, "12AFFE")),
             Err(BitStringConversionError::IllegalDecimalCharacter(2))
         );
 
         assert_eq!(
-            bit_string_to_string(&BitString::new(None, BaseSpecifier::D, "?")),
+            bit_string_to_string(&bit_string(None, BaseSpecifier::D, "?")),
             Err(BitStringConversionError::IllegalDecimalCharacter(0))
         );
 
         assert_eq!(
-            bit_string_to_string(&BitString::new(None, BaseSpecifier::D, "78234+")),
+            bit_string_to_string(&bit_string(None, BaseSpecifier::D, "78234+")),
             Err(BitStringConversionError::IllegalDecimalCharacter(5))
         );
     }
@@ -354,19 +309,19 @@ mod test_mod {
     #[test]
     fn test_decimal_conversion() {
         let test_cases = [
-            (BitString::new(None, BaseSpecifier::D, ""), ""),
-            (BitString::new(None, BaseSpecifier::D, "0"), "0"),
-            (BitString::new(None, BaseSpecifier::D, "00"), "0"),
-            (BitString::new(None, BaseSpecifier::D, "000"), "0"),
-            (BitString::new(None, BaseSpecifier::D, "1"), "1"),
-            (BitString::new(None, BaseSpecifier::D, "01"), "1"),
-            (BitString::new(None, BaseSpecifier::D, "10"), "1010"),
+            (bit_string(None, BaseSpecifier::D, ""), ""),
+            (bit_string(None, BaseSpecifier::D, "0"), "0"),
+            (bit_string(None, BaseSpecifier::D, "00"), "0"),
+            (bit_string(None, BaseSpecifier::D, "000"), "0"),
+            (bit_string(None, BaseSpecifier::D, "1"), "1"),
+            (bit_string(None, BaseSpecifier::D, "01"), "1"),
+            (bit_string(None, BaseSpecifier::D, "10"), "1010"),
             (
-                BitString::new(None, BaseSpecifier::D, "164824"),
+                bit_string(None, BaseSpecifier::D, "164824"),
                 "101000001111011000",
             ),
             (
-                BitString::new(None, BaseSpecifier::D, "123456781234567812345678"),
+                bit_string(None, BaseSpecifier::D, "123456781234567812345678"),
                 "11010001001001001101100000011011011101100011101100101101101011110111101001110",
             ),
         ];
@@ -382,7 +337,7 @@ mod test_mod {
     #[test]
     fn test_illegal_truncate_position() {
         assert_eq!(
-            bit_string_to_string(&BitString::new(Some(8), BaseSpecifier::SX, "0FF")),
+            bit_string_to_string(&bit_string(Some(8), BaseSpecifier::SX, "0FF")),
             Err(BitStringConversionError::IllegalTruncate(
                 7,
                 Latin1String::new(b"000011111111")
@@ -390,7 +345,7 @@ mod test_mod {
         );
 
         assert_eq!(
-            bit_string_to_string(&BitString::new(Some(8), BaseSpecifier::SX, "1FF")),
+            bit_string_to_string(&bit_string(Some(8), BaseSpecifier::SX, "1FF")),
             Err(BitStringConversionError::IllegalTruncate(
                 8,
                 Latin1String::new(b"000111111111")
@@ -398,7 +353,7 @@ mod test_mod {
         );
 
         assert_eq!(
-            bit_string_to_string(&BitString::new(Some(8), BaseSpecifier::SX, "3FF")),
+            bit_string_to_string(&bit_string(Some(8), BaseSpecifier::SX, "3FF")),
             Err(BitStringConversionError::IllegalTruncate(
                 9,
                 Latin1String::new(b"001111111111")
@@ -411,57 +366,48 @@ mod test_mod {
     fn spec_examples() {
         let test_cases = [
             (
-                BitString::new(None, BaseSpecifier::B, "1111_1111_1111"),
-                "111111111111",
-            ),
-            (
-                BitString::new(None, BaseSpecifier::X, "FFF"),
+                bit_string(None, BaseSpecifier::B, "1111_1111_1111"),
                 "111111111111",
             ),
-            (BitString::new(None, BaseSpecifier::O, "777"), "111111111"),
-            (
-                BitString::new(None, BaseSpecifier::X, "777"),
-                "011101110111",
-            ),
-            (
-                BitString::new(None, BaseSpecifier::B, "XXXX_01LH"),
-                "XXXX01LH",
-            ),
-            (BitString::new(None, BaseSpecifier::UO, "27"), "010111"),
-            // (BitString::new(None, BaseSpecifier::UO, "2C"), "011CCC"), // TODO: is this an error in the spec?
-            (BitString::new(None, BaseSpecifier::SX, "3W"), "0011WWWW"),
-            (BitString::new(None, BaseSpecifier::D, "35"), "100011"),
+            (bit_string(None, BaseSpecifier::X, "FFF"), "111111111111"),
+            (bit_string(None, BaseSpecifier::O, "777"), "111111111"),
+            (bit_string(None, BaseSpecifier::X, "777"), "011101110111"),
+            (bit_string(None, BaseSpecifier::B, "XXXX_01LH"), "XXXX01LH"),
+            (bit_string(None, BaseSpecifier::UO, "27"), "010111"),
+            // (bit_string(None, BaseSpecifier::UO, "2C"), "011CCC"), // TODO: is this an error in the spec?
+            (bit_string(None, BaseSpecifier::SX, "3W"), "0011WWWW"),
+            (bit_string(None, BaseSpecifier::D, "35"), "100011"),
             (
-                BitString::new(Some(12), BaseSpecifier::UB, "X1"),
+                bit_string(Some(12), BaseSpecifier::UB, "X1"),
                 "0000000000X1",
             ),
             (
-                BitString::new(Some(12), BaseSpecifier::SB, "X1"),
+                bit_string(Some(12), BaseSpecifier::SB, "X1"),
                 "XXXXXXXXXXX1",
             ),
             (
-                BitString::new(Some(12), BaseSpecifier::UX, "F-"),
+                bit_string(Some(12), BaseSpecifier::UX, "F-"),
                 "00001111----",
             ),
             (
-                BitString::new(Some(12), BaseSpecifier::SX, "F-"),
+                bit_string(Some(12), BaseSpecifier::SX, "F-"),
                 "11111111----",
             ),
             (
-                BitString::new(Some(12), BaseSpecifier::UX, "000WWW"),
+                bit_string(Some(12), BaseSpecifier::UX, "000WWW"),
                 "WWWWWWWWWWWW",
             ),
             (
-                BitString::new(Some(12), BaseSpecifier::SX, "FFFC00"),
+                bit_string(Some(12), BaseSpecifier::SX, "FFFC00"),
                 "110000000000",
             ),
         ];
 
         let error_cases = [
-            BitString::new(Some(8), BaseSpecifier::D, "511"),
-            BitString::new(Some(8), BaseSpecifier::UO, "477"),
-            BitString::new(Some(8), BaseSpecifier::SX, "0FF"),
-            BitString::new(Some(8), BaseSpecifier::SX, "FXX"),
+            bit_string(Some(8), BaseSpecifier::D, "511"),
+            bit_string(Some(8), BaseSpecifier::UO, "477"),
+            bit_string(Some(8), BaseSpecifier::SX, "0FF"),
+            bit_string(Some(8), BaseSpecifier::SX, "FXX"),
         ];
 
         for bit_string in error_cases {
@@ -0,0 +1,298 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this file,
+// You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) 2023, Olof Kraigher olof.kraigher@gmail.com
+
+//! Classify analyzed expressions as locally static, globally static or not static (LRM 9.4)
+//! A name which could not be resolved or a construct which is not classified precisely
+//! is assumed to be locally static such that no false errors are reported
+
+use super::analyze::*;
+use super::named_entity::*;
+use crate::ast;
+use crate::ast::*;
+use crate::data::*;
+
+/// Ordered from least to most static such that the staticness of an expression
+/// is the minimum of the staticness of its parts
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub(super) enum Staticness {
+    NotStatic,
+    Global,
+    Local,
+}
+
+use Staticness::*;
+
+/// The staticness of an expression together with the entity which limits it, if any
+#[derive(Clone, Copy)]
+pub(super) struct Classified<'a> {
+    staticness: Staticness,
+    culprit: Option<EntRef<'a>>,
+}
+
+impl<'a> Classified<'a> {
+    fn of(staticness: Staticness, ent: EntRef<'a>) -> Self {
+        Self {
+            staticness,
+            culprit: (staticness < Local).then_some(ent),
+        }
+    }
+
+    /// The least static of the two, keeping the first culprit on ties
+    fn min(self, other: Self) -> Self {
+        if other.staticness < self.staticness {
+            other
+        } else {
+            self
+        }
+    }
+}
+
+impl<'a> From<Staticness> for Classified<'a> {
+    fn from(staticness: Staticness) -> Self {
+        Self {
+            staticness,
+            culprit: None,
+        }
+    }
+}
+
+impl<'a> AnalyzeContext<'a> {
+    pub(super) fn expression_staticness(&self, expr: &Expression) -> Classified<'a> {
+        match expr {
+            Expression::Binary(op, left, right) => self
+                .operator_staticness(op)
+                .min(self.expression_staticness(&left.item))
+                .min(self.expression_staticness(&right.item)),
+            Expression::Unary(op, operand) => self
+                .operator_staticness(op)
+                .min(self.expression_staticness(&operand.item)),
+            Expression::Aggregate(assocs) => assocs
+                .iter()
+                .map(|assoc| match assoc {
+                    ElementAssociation::Positional(expr) => self.expression_staticness(&expr.item),
+                    ElementAssociation::Named(choices, expr) => self
+                        .choices_staticness(choices)
+                        .min(self.expression_staticness(&expr.item)),
+                })
+                .fold(Local.into(), Classified::min),
+            Expression::Qualified(qexpr) => self.expression_staticness(&qexpr.expr.item),
+            Expression::Name(name) => self.name_staticness(name),
+            Expression::Literal(_) => Local.into(),
+            Expression::New(_) => NotStatic.into(),
+        }
+    }
+
+    pub(super) fn drange_staticness(&self, drange: &DiscreteRange) -> Classified<'a> {
+        match drange {
+            DiscreteRange::Discrete(_, Some(range)) | DiscreteRange::Range(range) => {
+                self.range_staticness(range)
+            }
+            DiscreteRange::Discrete(_, None) => Local.into(),
+        }
+    }
+
+    pub(super) fn range_staticness(&self, range: &ast::Range) -> Classified<'a> {
+        match range {
+            ast::Range::Range(constraint) => self
+                .expression_staticness(&constraint.left_expr.item)
+                .min(self.expression_staticness(&constraint.right_expr.item)),
+            // The range of the subtype of the prefix
+            ast::Range::Attribute(_) => Local.into(),
+        }
+    }
+
+    fn choices_staticness(&self, choices: &[WithPos<Choice>]) -> Classified<'a> {
+        choices
+            .iter()
+            .map(|choice| match choice.item {
+                Choice::Expression(ref expr) => self.expression_staticness(expr),
+                Choice::DiscreteRange(ref drange) => self.drange_staticness(drange),
+                Choice::Others => Local.into(),
+            })
+            .fold(Local.into(), Classified::min)
+    }
+
+    fn operator_staticness(&self, op: &WithPos<WithRef<Operator>>) -> Classified<'a> {
+        match op.item.reference {
+            Some(id) => self.function_part(self.arena.get(id)),
+            None => Local.into(),
+        }
+    }
+
+    fn name_staticness(&self, name: &Name) -> Classified<'a> {
+        match name {
+            Name::Designator(designator) => match designator.reference {
+                Some(id) => self.ent_part(self.arena.get(id)),
+                None => Local.into(),
+            },
+            Name::Selected(prefix, suffix) => match suffix.item.reference {
+                Some(id) => {
+                    let ent = self.arena.get(id);
+                    if matches!(ent.kind(), AnyEntKind::ElementDeclaration(_)) {
+                        self.name_staticness(&prefix.item)
+                    } else {
+                        self.ent_part(ent)
+                    }
+                }
+                None => self.name_staticness(&prefix.item),
+            },
+            Name::SelectedAll(_) | Name::External(_) => NotStatic.into(),
+            Name::Slice(prefix, drange) => self
+                .name_staticness(&prefix.item)
+                .min(self.drange_staticness(drange)),
+            Name::Attribute(attr) => {
+                let staticness: Classified<'a> = match attr.attr.item {
+                    AttributeDesignator::Signal(_) => NotStatic,
+                    AttributeDesignator::InstanceName | AttributeDesignator::PathName => Global,
+                    // The value of other attributes depends on the subtype of the prefix
+                    _ => Local,
+                }
+                .into();
+                match attr.expr {
+                    Some(ref expr) => staticness.min(self.expression_staticness(&expr.item)),
+                    None => staticness,
+                }
+            }
+            Name::CallOrIndexed(call) => {
+                let prefix = match call.name.item.get_suffix_reference() {
+                    Some(id) => {
+                        let ent = self.arena.get(id);
+                        match ent.kind() {
+                            AnyEntKind::Overloaded(_) => self.function_part(ent),
+                            // A type conversion
+                            AnyEntKind::Type(_) => Local.into(),
+                            _ => self.name_staticness(&call.name.item),
+                        }
+                    }
+                    None => self.name_staticness(&call.name.item),
+                };
+                call.parameters
+                    .iter()
+                    .map(|assoc| match assoc.actual.item {
+                        ActualPart::Expression(ref expr) => self.expression_staticness(expr),
+                        ActualPart::Open => Local.into(),
+                    })
+                    .fold(prefix, Classified::min)
+            }
+        }
+    }
+
+    fn ent_part(&self, ent: EntRef<'a>) -> Classified<'a> {
+        Classified::of(self.ent_staticness(ent), ent)
+    }
+
+    fn function_part(&self, ent: EntRef<'a>) -> Classified<'a> {
+        Classified::of(self.function_staticness(ent), ent)
+    }
+
+    fn ent_staticness(&self, ent: EntRef<'a>) -> Staticness {
+        match ent.kind() {
+            AnyEntKind::Object(object) => match (object.class, object.iface) {
+                (ObjectClass::Constant, Some(ObjectInterface::Generic)) => Global,
+                (ObjectClass::Constant, Some(_)) => NotStatic,
+                // The value of a constant without a default is given by a deferred declaration
+                (ObjectClass::Constant, None) if object.has_default => Local,
+                (ObjectClass::Constant, None) => Global,
+                _ => NotStatic,
+            },
+            AnyEntKind::ObjectAlias { base_object, .. } => self.ent_staticness(base_object.ent),
+            AnyEntKind::DeferredConstant(_) | AnyEntKind::LoopParameter(_) => Global,
+            AnyEntKind::Overloaded(_) => self.function_staticness(ent),
+            AnyEntKind::File(_)
+            | AnyEntKind::InterfaceFile(_)
+            | AnyEntKind::ExternalAlias { .. } => NotStatic,
+            _ => Local,
+        }
+    }
+
+    /// A call of a predefined function or of a function of an IEEE package with locally
+    /// static arguments is locally static, of a pure function it is globally static
+    fn function_staticness(&self, ent: EntRef<'a>) -> Staticness {
+        match ent.kind() {
+            AnyEntKind::Overloaded(Overloaded::Alias(aliased)) => {
+                self.function_staticness((*aliased).into())
+            }
+            AnyEntKind::Overloaded(Overloaded::EnumLiteral(_)) => Local,
+            AnyEntKind::Overloaded(
+                Overloaded::Subprogram(signature) | Overloaded::SubprogramDecl(signature),
+            ) => {
+                if signature.is_impure() {
+                    NotStatic
+                } else if ent.is_implicit()
+                    || ent
+                        .library_name()
+                        .is_some_and(|library| library.name_utf8() == "ieee")
+                {
+                    Local
+                } else {
+                    Global
+                }
+            }
+            AnyEntKind::Overloaded(Overloaded::InterfaceSubprogram(signature)) => {
+                if signature.is_impure() {
+                    NotStatic
+                } else {
+                    Global
+                }
+            }
+            _ => Local,
+        }
+    }
+
+    /// Report an expression which is not static enough for where it is used,
+    /// naming the part of it which is not when known
+    pub(super) fn check_staticness(
+        &self,
+        pos: &SrcPos,
+        classified: Classified<'a>,
+        required: Staticness,
+        what: &str,
+        diagnostics: &mut dyn DiagnosticHandler,
+    ) {
+        if classified.staticness < required {
+            let required = match required {
+                Local => "locally static",
+                _ => "globally static",
+            };
+            match classified.culprit {
+                Some(ent) => diagnostics.error(
+                    pos,
+                    format!(
+                        "{what} must be {required}, {} '{}' is not",
+                        ent.kind().describe(),
+                        ent.designator()
+                    ),
+                ),
+                None => diagnostics.error(pos, format!("{what} must be {required}")),
+            }
+        }
+    }
+
+    /// The index constraints of the subtype of a port must be globally static
+    pub(super) fn check_port_constraint(
+        &self,
+        subtype_indication: &SubtypeIndication,
+        diagnostics: &mut dyn DiagnosticHandler,
+    ) {
+        let mut constraint = subtype_indication.constraint.as_ref();
+        while let Some(WithPos {
+            item: SubtypeConstraint::Array(dranges, element),
+            ..
+        }) = constraint
+        {
+            for drange in dranges.iter() {
+                self.check_staticness(
+                    &drange.pos(),
+                    self.drange_staticness(drange),
+                    Global,
+                    "Index constraint of a port",
+                    diagnostics,
+                );
+            }
+            constraint = element.as_deref();
+        }
+    }
+}
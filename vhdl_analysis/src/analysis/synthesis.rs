@@ -0,0 +1,203 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this file,
+// You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) 2023, Olof Kraigher olof.kraigher@gmail.com
+
+//! Find constructs that are not synthesizable within libraries that are configured for synthesis
+//! Test benches are typically placed in other libraries where these constructs are fine
+
+use super::named_entity::*;
+use super::reset_value::registers_without_reset;
+use super::root::DesignRoot;
+use crate::ast::search::*;
+use crate::ast::*;
+use crate::data::*;
+
+/// The diagnostic codes which allow clients to filter each rule separately
+const WAIT_FOR: &str = "synthesis-wait-for";
+const INITIAL_VALUE: &str = "synthesis-initial-value";
+const SHARED_VARIABLE: &str = "synthesis-shared-variable";
+const ACCESS_TYPE: &str = "synthesis-access-type";
+const FILE: &str = "synthesis-file";
+const DIVISION: &str = "synthesis-division";
+const METALOGICAL: &str = "synthesis-metalogical-comparison";
+
+pub(super) struct CheckSynthesis<'a> {
+    root: &'a DesignRoot,
+    severity: Severity,
+    diagnostics: &'a mut dyn DiagnosticHandler,
+}
+
+impl<'a> CheckSynthesis<'a> {
+    pub fn new(
+        root: &'a DesignRoot,
+        severity: Severity,
+        diagnostics: &'a mut dyn DiagnosticHandler,
+    ) -> Self {
+        CheckSynthesis {
+            root,
+            severity,
+            diagnostics,
+        }
+    }
+
+    /// Returns true if the operator was resolved to an operator of std_ulogic
+    fn is_std_ulogic_op(&self, op: &WithPos<WithRef<Operator>>) -> bool {
+        op.item
+            .reference
+            .and_then(|id| OverloadedEnt::from_any(self.root.get_ent(id)))
+            .and_then(|ent| ent.nth_base(0))
+            .is_some_and(|base| Some(base.id()) == self.root.std_ulogic)
+    }
+
+    /// Warn about comparing a std_ulogic value with a metalogical value such as 'X'
+    /// Such comparisons are always false in synthesis, or always true for /=
+    fn metalogical_comparison(&mut self, pos: &SrcPos, expr: &Expression) {
+        if let Expression::Binary(op, left, right) = expr {
+            if matches!(op.item.item, Operator::EQ | Operator::NE) && self.is_std_ulogic_op(op) {
+                for operand in [left, right] {
+                    if let Expression::Literal(Literal::Character(chr)) = operand.item {
+                        if is_metalogical(chr) {
+                            self.push(
+                                pos,
+                                format!(
+                                    "Comparison with metalogical value '{}' is always {} in synthesis",
+                                    chr as char,
+                                    op.item.item == Operator::NE
+                                ),
+                                METALOGICAL,
+                            );
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    fn push(&mut self, pos: &SrcPos, msg: String, code: &'static str) {
+        self.diagnostics
+            .push(Diagnostic::new(pos, msg, self.severity).with_code(code));
+    }
+}
+
+impl<'a> Searcher for CheckSynthesis<'a> {
+    fn search_decl(&mut self, decl: FoundDeclaration) -> SearchState {
+        match decl {
+            FoundDeclaration::Object(object) if object.class == ObjectClass::SharedVariable => {
+                self.push(
+                    object.ident.tree.pos(),
+                    format!(
+                        "Shared variable '{}' is not synthesizable",
+                        object.ident.tree.item
+                    ),
+                    SHARED_VARIABLE,
+                );
+            }
+            FoundDeclaration::Type(type_decl) => match type_decl.def {
+                TypeDefinition::Access(_) => self.push(
+                    type_decl.ident.tree.pos(),
+                    format!(
+                        "Access type '{}' is not synthesizable",
+                        type_decl.ident.tree.item
+                    ),
+                    ACCESS_TYPE,
+                ),
+                TypeDefinition::File(_) => self.push(
+                    type_decl.ident.tree.pos(),
+                    format!(
+                        "File type '{}' is not synthesizable",
+                        type_decl.ident.tree.item
+                    ),
+                    FILE,
+                ),
+                _ => {}
+            },
+            FoundDeclaration::File(file) => {
+                self.push(
+                    file.ident.tree.pos(),
+                    format!("File '{}' is not synthesizable", file.ident.tree.item),
+                    FILE,
+                );
+            }
+            FoundDeclaration::Architecture(body) => {
+                for (ident, initial, assign_pos) in
+                    registers_without_reset(&body.decl, &body.statements)
+                {
+                    self.diagnostics.push(
+                        Diagnostic::new(
+                            &initial.pos,
+                            format!(
+                                "Register '{}' relies on its initial value since it is never reset",
+                                ident.item
+                            ),
+                            self.severity,
+                        )
+                        .related(assign_pos, "Assigned in a clocked process here")
+                        .with_code(INITIAL_VALUE),
+                    );
+                }
+            }
+            _ => {}
+        }
+        NotFinished
+    }
+
+    fn search_sequential_statement(
+        &mut self,
+        statement: &LabeledSequentialStatement,
+    ) -> SearchState {
+        match statement.statement.item {
+            SequentialStatement::Wait(ref wait_stmt) if wait_stmt.timeout_clause.is_some() => {
+                self.push(
+                    &statement.statement.pos,
+                    "Wait statement with a timeout is not synthesizable".to_owned(),
+                    WAIT_FOR,
+                );
+            }
+            // Assertions are ignored by synthesis
+            SequentialStatement::Assert(_) => return Finished(NotFound),
+            _ => {}
+        }
+        NotFinished
+    }
+
+    fn search_concurrent_statement(
+        &mut self,
+        statement: &LabeledConcurrentStatement,
+    ) -> SearchState {
+        if let ConcurrentStatement::Assert(_) = statement.statement.item {
+            return Finished(NotFound);
+        }
+        NotFinished
+    }
+
+    fn search_expression(&mut self, pos: &SrcPos, expr: &Expression) -> SearchState {
+        self.metalogical_comparison(pos, expr);
+        if let Expression::Binary(op, _, right) = expr {
+            if matches!(op.item.item, Operator::Div | Operator::Mod | Operator::Rem) {
+                if let Expression::Literal(Literal::AbstractLiteral(AbstractLiteral::Integer(
+                    value,
+                ))) = right.item
+                {
+                    if !value.is_power_of_two() {
+                        self.push(
+                            pos,
+                            format!(
+                                "Operator '{}' by {} which is not a power of two is not synthesizable",
+                                op.item.item, value
+                            ),
+                            DIVISION,
+                        );
+                    }
+                }
+            }
+        }
+        NotFinished
+    }
+}
+
+/// The metalogical values of std_ulogic as defined by IEEE 1076.6
+fn is_metalogical(chr: u8) -> bool {
+    matches!(chr, b'U' | b'X' | b'W' | b'-')
+}
@@ -19,6 +19,18 @@ impl<'a> AnalyzeContext<'a> {
         assignment_type: AssignmentType,
         diagnostics: &mut dyn DiagnosticHandler,
     ) -> EvalResult<TypeEnt<'a>> {
+        self.resolve_target_object(scope, target, assignment_type, diagnostics)
+            .map(|object_name| object_name.type_mark())
+    }
+
+    /// Resolve the object denoted by an assignment target
+    pub fn resolve_target_object(
+        &self,
+        scope: &Scope<'a>,
+        target: &mut WithPos<Target>,
+        assignment_type: AssignmentType,
+        diagnostics: &mut dyn DiagnosticHandler,
+    ) -> EvalResult<ObjectName<'a>> {
         match target.item {
             Target::Name(ref mut name) => {
                 self.resolve_target_name(scope, name, &target.pos, assignment_type, diagnostics)
@@ -37,7 +49,7 @@ impl<'a> AnalyzeContext<'a> {
         target_pos: &SrcPos,
         assignment_type: AssignmentType,
         diagnostics: &mut dyn DiagnosticHandler,
-    ) -> EvalResult<TypeEnt<'a>> {
+    ) -> EvalResult<ObjectName<'a>> {
         let object_name = self.resolve_object_name(
             scope,
             target_pos,
@@ -45,7 +57,9 @@ impl<'a> AnalyzeContext<'a> {
             "may not be the target of an assignment",
             diagnostics,
         )?;
-        if !is_valid_assignment_target(&object_name.base) {
+        if let Some(diagnostic) = read_only_object_error(&object_name.base, target_pos) {
+            diagnostics.push(diagnostic);
+        } else if !is_valid_assignment_target(&object_name.base) {
             diagnostics.push(Diagnostic::error(
                 target_pos,
                 format!(
@@ -63,7 +77,7 @@ impl<'a> AnalyzeContext<'a> {
                 ),
             ));
         }
-        Ok(object_name.type_mark())
+        Ok(object_name)
     }
 }
 
@@ -86,7 +100,40 @@ impl AssignmentType {
 
 /// Check that the assignment target is a writable object and not constant or input only
 fn is_valid_assignment_target(base: &ObjectBase) -> bool {
-    base.class() != ObjectClass::Constant && !matches!(base.mode(), Some(Mode::In))
+    base.class() != ObjectClass::Constant
+        && !matches!(base.mode(), Some(Mode::In))
+        && !matches!(base, ObjectBase::ImplicitSignal(_))
+}
+
+/// An input port, generic or constant which is assigned, the entity owning a port or
+/// generic is named since they are only visible within its architectures
+fn read_only_object_error(base: &ObjectBase, target_pos: &SrcPos) -> Option<Diagnostic> {
+    let obj = if let ObjectBase::Object(obj) = base {
+        obj
+    } else {
+        return None;
+    };
+    let object = obj.object();
+    let description = match object.iface {
+        Some(ObjectInterface::Port(Mode::In)) => format!("input port '{}'", obj.designator()),
+        Some(ObjectInterface::Generic) => format!("generic '{}'", obj.designator()),
+        None if object.class == ObjectClass::Constant => {
+            format!("constant '{}'", obj.designator())
+        }
+        _ => return None,
+    };
+    let description = match obj.ent.parent {
+        Some(parent) if matches!(parent.kind(), AnyEntKind::Design(Design::Entity(..))) => {
+            format!("{description} of entity '{}'", parent.designator())
+        }
+        _ => description,
+    };
+
+    let mut diagnostic = Diagnostic::error(target_pos, format!("Cannot assign to {description}"));
+    if let Some(decl_pos) = obj.decl_pos() {
+        diagnostic.add_related(decl_pos, "Defined here");
+    }
+    Some(diagnostic)
 }
 
 // Check that a signal is not the target of a variable assignment and vice-versa
@@ -99,3 +146,13 @@ fn is_valid_assignment_type(base: &ObjectBase, assignment_type: AssignmentType)
         }
     }
 }
+
+/// Check if the target may be a guarded signal and thus the target of a null transaction
+pub fn may_be_guarded(base: &ObjectBase) -> bool {
+    match base {
+        ObjectBase::Object(obj) | ObjectBase::ObjectAlias(obj, _) => obj.object().is_guarded(),
+        ObjectBase::DeferredConstant(_) => false,
+        ObjectBase::ExternalName(_) => true,
+        ObjectBase::ImplicitSignal(_) => false,
+    }
+}
@@ -61,7 +61,7 @@ architecture a of ent is
 begin
   main : process
   begin
-    foo'stable := 1;
+    foo'stable := true;
   end process;
 end architecture;
 ",
@@ -69,7 +69,7 @@ end architecture;
 
     let expected = vec![Diagnostic::error(
         code.s("foo'stable", 1),
-        "Expression may not be the target of an assignment",
+        "implicit signal 'stable may not be the target of an assignment",
     )];
 
     let diagnostics = builder.analyze();
@@ -159,10 +159,8 @@ end architecture;
     );
 
     let expected = vec![
-        Diagnostic::error(
-            code.s("foo1", 3),
-            "constant 'foo1' may not be the target of an assignment",
-        ),
+        Diagnostic::error(code.s("foo1", 3), "Cannot assign to constant 'foo1'")
+            .related(code.s1("foo1"), "Defined here"),
         Diagnostic::error(
             code.s("foo2", 2),
             "alias 'foo2' of constant may not be the target of an assignment",
@@ -263,6 +261,54 @@ end architecture;
     check_no_diagnostics(&diagnostics);
 }
 
+#[test]
+fn input_port_and_generic_of_entity_may_not_be_assignment_target() {
+    let mut builder = LibraryBuilder::new();
+    let code = builder.code(
+        "libname",
+        "
+entity ent is
+  generic (width : natural := 8);
+  port (din, en : in bit; dout : out bit);
+end entity;
+
+architecture a of ent is
+begin
+  din <= '0';
+  dout <= din;
+
+  main : process
+  begin
+    en <= '1';
+    width := 4;
+    wait;
+  end process;
+end architecture;
+",
+    );
+
+    let expected = vec![
+        Diagnostic::error(
+            code.s("din", 2),
+            "Cannot assign to input port 'din' of entity 'ent'",
+        )
+        .related(code.s1("din"), "Defined here"),
+        Diagnostic::error(
+            code.s1("en <=").s1("en"),
+            "Cannot assign to input port 'en' of entity 'ent'",
+        )
+        .related(code.sa("din, ", "en"), "Defined here"),
+        Diagnostic::error(
+            code.s("width", 2),
+            "Cannot assign to generic 'width' of entity 'ent'",
+        )
+        .related(code.s1("width"), "Defined here"),
+    ];
+
+    let diagnostics = builder.analyze();
+    check_diagnostics(diagnostics, expected);
+}
+
 #[test]
 fn interface_constant_may_not_be_assignment_target() {
     let mut builder = LibraryBuilder::new();
@@ -580,3 +626,82 @@ end package body Test;",
         )],
     )
 }
+
+#[test]
+fn unaffected_waveform() {
+    let mut builder = LibraryBuilder::new();
+    let code = builder.code(
+        "libname",
+        "
+entity ent is
+end entity;
+
+architecture a of ent is
+  signal foo, bar, baz, qux : natural;
+  signal cond : boolean;
+begin
+  foo <= unaffected;
+  bar <= 0 when cond else unaffected;
+  with cond select baz <= unaffected when true, 1 when false;
+
+  main : process
+  begin
+    qux <= unaffected;
+    qux <= 0 when cond else unaffected;
+    wait;
+  end process;
+end architecture;
+",
+    );
+
+    let diagnostics = builder.analyze();
+    check_diagnostics(
+        diagnostics,
+        vec![Diagnostic::error(
+            code.s1("foo <= unaffected;"),
+            "'unaffected' is not allowed in a concurrent simple signal assignment",
+        )],
+    );
+}
+
+#[test]
+fn null_transaction_requires_guarded_signal() {
+    let mut builder = LibraryBuilder::new();
+    let code = builder.code(
+        "libname",
+        "
+entity ent is
+  port (oport : out natural bus);
+end entity;
+
+architecture a of ent is
+  signal reg : natural register;
+  signal plain : natural;
+begin
+  main : process
+  begin
+    reg <= null;
+    oport <= 1, null after 1 ns;
+    plain <= 1, null after 2 ns;
+    reg <= null after false;
+    wait;
+  end process;
+end architecture;
+",
+    );
+
+    let diagnostics = builder.analyze();
+    check_diagnostics(
+        diagnostics,
+        vec![
+            Diagnostic::error(
+                code.s1("null after 2 ns").s1("null"),
+                "Null transaction is only allowed when the target is a guarded signal",
+            ),
+            Diagnostic::error(
+                code.s1("false"),
+                "'false' does not match physical type 'TIME'",
+            ),
+        ],
+    );
+}
@@ -0,0 +1,1027 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// Lic// License, v. 2.0. If a copy of the MPL was not distributed with this file,
+// This Source Code Form is subject to the terms of the Mozilla Public
+// You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) 2022, Olof Kraigher olof.kraigher@gmail.com
+use super::*;
+use crate::config::VHDLStandard;
+use pretty_assertions::assert_eq;
+
+#[test]
+fn missing_port_name() {
+    let mut builder = LibraryBuilder::new();
+    let code = builder.code(
+        "libname",
+        "
+entity ent_inst is
+end entity;
+
+architecture a of ent_inst is
+begin
+end architecture;
+
+entity ent is
+end entity;
+
+architecture a of ent is
+   signal sig : boolean;
+begin
+   ent: entity work.ent_inst
+      port map (missing => sig);
+end architecture;
+        ",
+    );
+
+    let diagnostics = builder.analyze();
+    check_diagnostics(
+        diagnostics,
+        vec![Diagnostic::error(
+            code.s1("missing"),
+            "No declaration of 'missing'",
+        )],
+    );
+}
+
+#[test]
+fn missing_generic_name() {
+    let mut builder = LibraryBuilder::new();
+    let code = builder.code(
+        "libname",
+        "
+entity ent_inst is
+end entity;
+
+architecture a of ent_inst is
+begin
+end architecture;
+
+entity ent is
+end entity;
+
+architecture a of ent is
+   signal sig : boolean;
+begin
+   ent: entity work.ent_inst
+      generic map (missing => sig);
+end architecture;
+        ",
+    );
+
+    let diagnostics = builder.analyze();
+    check_diagnostics(
+        diagnostics,
+        vec![Diagnostic::error(
+            code.s1("missing"),
+            "No declaration of 'missing'",
+        )],
+    );
+}
+
+#[test]
+fn resolve_port_name() {
+    let mut builder = LibraryBuilder::new();
+    let code = builder.code(
+        "libname",
+        "
+entity ent_inst is
+    port (
+        theport : in boolean 
+    );
+end entity;
+
+architecture a of ent_inst is
+begin
+end architecture;
+
+entity ent is
+end entity;
+
+architecture a of ent is
+   signal sig : boolean;
+begin
+   ent: entity work.ent_inst
+      port map (theport => sig);
+end architecture;
+        ",
+    );
+
+    let (root, diagnostics) = builder.get_analyzed_root();
+    check_no_diagnostics(&diagnostics);
+
+    assert_eq!(
+        root.search_reference_pos(code.source(), code.s("theport", 2).end()),
+        Some(code.s1("theport").pos())
+    );
+}
+
+#[test]
+fn resolve_generic_name() {
+    let mut builder = LibraryBuilder::new();
+    let code = builder.code(
+        "libname",
+        "
+entity ent_inst is
+    generic (
+        thegeneric : boolean 
+    );
+end entity;
+
+architecture a of ent_inst is
+begin
+end architecture;
+
+entity ent is
+end entity;
+
+architecture a of ent is
+begin
+   ent: entity work.ent_inst
+      generic map (thegeneric => false);
+end architecture;
+        ",
+    );
+
+    let (root, diagnostics) = builder.get_analyzed_root();
+    check_no_diagnostics(&diagnostics);
+
+    assert_eq!(
+        root.search_reference_pos(code.source(), code.s("thegeneric", 2).end()),
+        Some(code.s1("thegeneric").pos())
+    );
+}
+
+#[test]
+fn does_not_mixup_ports_and_generics() {
+    let mut builder = LibraryBuilder::new();
+    let code = builder.code(
+        "libname",
+        "
+entity ent_inst is
+    generic (
+        thegeneric : boolean 
+    );
+    port (
+        theport : in boolean 
+    );
+end entity;
+
+architecture a of ent_inst is
+begin
+end architecture;
+
+entity ent is
+end entity;
+
+architecture a of ent is
+   signal sig : boolean;
+begin
+   ent: entity work.ent_inst
+       generic map (theport => sig)
+       port map (thegeneric => 0);
+end architecture;
+        ",
+    );
+
+    let diagnostics = builder.analyze();
+    check_diagnostics(
+        diagnostics,
+        vec![
+            Diagnostic::error(code.s("theport", 2), "No declaration of 'theport'"),
+            Diagnostic::error(
+                code.s1("work.ent_inst"),
+                "No association of port 'theport' : in",
+            )
+            .related(code.s1("theport"), "Defined here"),
+            Diagnostic::error(code.s("thegeneric", 2), "No declaration of 'thegeneric'"),
+            Diagnostic::error(
+                code.s1("work.ent_inst"),
+                "No association of generic 'thegeneric'",
+            )
+            .related(code.s1("thegeneric"), "Defined here"),
+        ],
+    );
+}
+
+#[test]
+fn resolve_port_and_surrounding_name() {
+    let mut builder = LibraryBuilder::new();
+    let code = builder.code(
+        "libname",
+        "
+entity ent_inst is
+    port (
+        theport : in integer_vector(0 to 0) 
+    );
+end entity;
+
+architecture a of ent_inst is
+begin
+end architecture;
+
+entity ent is
+end entity;
+
+architecture a of ent is
+    constant const0 : natural := 0;
+begin
+   ent: entity work.ent_inst
+      port map (theport(const0) => 0);
+end architecture;
+        ",
+    );
+
+    let (root, diagnostics) = builder.get_analyzed_root();
+    check_no_diagnostics(&diagnostics);
+
+    assert_eq!(
+        root.search_reference_pos(code.source(), code.s("theport", 2).end()),
+        Some(code.s1("theport").pos())
+    );
+
+    assert_eq!(
+        root.search_reference_pos(code.source(), code.s("const0", 2).end()),
+        Some(code.s1("const0").pos())
+    );
+}
+
+#[test]
+fn function_conversion_of_port_name() {
+    let mut builder = LibraryBuilder::new();
+    let code = builder.code(
+        "libname",
+        "
+entity ent is
+    port (
+        theport: out natural
+    );
+end entity;
+
+architecture a of ent is
+begin
+end architecture;
+
+entity ent2 is
+end entity;
+
+architecture a of ent2 is    
+    function fun1(arg : natural) return natural is
+    begin
+        return arg;
+    end function;
+
+    signal sig : natural;
+begin
+    inst: entity work.ent
+        port map (
+        fun1(theport) => sig);
+end architecture;
+        ",
+    );
+
+    let (root, diagnostics) = builder.get_analyzed_root();
+    check_no_diagnostics(&diagnostics);
+
+    assert_eq!(
+        root.search_reference_pos(code.source(), code.s("fun1", 2).end()),
+        Some(code.s1("fun1").pos())
+    );
+
+    assert_eq!(
+        root.search_reference_pos(code.source(), code.s("theport", 2).end()),
+        Some(code.s1("theport").pos())
+    );
+}
+
+#[test]
+fn type_conversion_of_port_name() {
+    let mut builder = LibraryBuilder::new();
+    let code = builder.code(
+        "libname",
+        "
+entity ent is
+    port (
+        theport: out natural
+    );
+end entity;
+
+architecture a of ent is
+begin
+end architecture;
+
+entity ent2 is
+end entity;
+
+architecture a of ent2 is
+    signal sig : real;
+begin
+    inst: entity work.ent
+        port map (
+        real(theport) => sig);
+end architecture;
+        ",
+    );
+
+    let (root, diagnostics) = builder.get_analyzed_root();
+    check_no_diagnostics(&diagnostics);
+
+    assert_eq!(
+        root.search_reference_pos(code.source(), code.s("theport", 2).end()),
+        Some(code.s1("theport").pos())
+    );
+}
+
+#[test]
+fn function_conversion_of_port_name_must_be_single_argument() {
+    let mut builder = LibraryBuilder::new();
+    let code = builder.code(
+        "libname",
+        "
+entity ent is
+    port (
+        theport: out natural
+    );
+end entity;
+
+architecture a of ent is
+begin
+end architecture;
+
+entity ent2 is
+end entity;
+
+architecture a of ent2 is    
+    function fun1(arg : natural; arg2: natural) return natural is
+    begin
+        return arg;
+    end function;
+
+    signal sig : natural;
+begin
+    inst: entity work.ent
+        port map (
+        fun1(theport, 2) => sig);
+end architecture;
+        ",
+    );
+
+    let diagnostics = builder.analyze();
+    check_diagnostics(
+        diagnostics,
+        vec![Diagnostic::error(
+            code.s1("fun1(theport, 2)"),
+            "Invalid formal conversion",
+        )],
+    );
+}
+
+#[test]
+fn function_conversion_of_port_name_must_not_have_its_own_formal() {
+    let mut builder = LibraryBuilder::new();
+    let code = builder.code(
+        "libname",
+        "
+entity ent is
+    port (
+        theport: out natural
+    );
+end entity;
+
+architecture a of ent is
+begin
+end architecture;
+
+entity ent2 is
+end entity;
+
+architecture a of ent2 is    
+    function fun1(arg : natural) return natural is
+    begin
+        return arg;
+    end function;
+
+    signal sig : natural;
+begin
+    inst: entity work.ent
+        port map (
+        fun1(arg => theport) => sig);
+end architecture;
+        ",
+    );
+
+    let diagnostics = builder.analyze();
+    check_diagnostics(
+        diagnostics,
+        vec![Diagnostic::error(
+            code.s1("fun1(arg => theport)"),
+            "Invalid formal conversion",
+        )],
+    );
+}
+
+#[test]
+fn output_ports_may_be_left_open() {
+    let mut builder = LibraryBuilder::new();
+    let code = builder.code(
+        "libname",
+        "
+entity ent2 is
+port (
+    signal inport: in natural;
+    signal outport: out natural  );
+end entity;
+
+architecture a of ent2 is
+begin
+end architecture;
+
+entity ent is
+end entity;
+
+architecture a of ent is
+    signal sig : natural;
+begin
+    inst: entity work.ent2
+        port map (
+        inport => sig
+        );
+end architecture;
+    ",
+    );
+
+    let (root, diagnostics) = builder.get_analyzed_root();
+    check_no_diagnostics(&diagnostics);
+    // Still resolves references when missing output port
+    assert!(root
+        .search_reference(code.source(), code.s1("inport => sig").s1("sig").start())
+        .is_some())
+}
+
+#[test]
+fn block_maps_associate_with_block_header() {
+    let mut builder = LibraryBuilder::new();
+    let code = builder.code(
+        "libname",
+        "
+entity ent is
+end entity;
+
+architecture a of ent is
+    signal sig : natural;
+begin
+    blk: block is
+        generic (
+            gen : natural
+        );
+        generic map (missing => 0);
+        port (
+            inport : in natural
+        );
+        port map (gen => sig);
+    begin
+    end block;
+end architecture;
+    ",
+    );
+
+    let diagnostics = builder.analyze();
+    check_diagnostics(
+        diagnostics,
+        vec![
+            Diagnostic::error(code.s1("missing"), "No declaration of 'missing'"),
+            Diagnostic::error(code.s1("blk"), "No association of generic 'gen'")
+                .related(code.s1("gen :").s1("gen"), "Defined here"),
+            Diagnostic::error(code.s1("gen => sig").s1("gen"), "No declaration of 'gen'"),
+            Diagnostic::error(code.s1("blk"), "No association of port 'inport' : in")
+                .related(code.s1("inport"), "Defined here"),
+        ],
+    );
+}
+
+#[test]
+fn block_map_actuals_are_checked_against_block_formals() {
+    let mut builder = LibraryBuilder::new();
+    let code = builder.code(
+        "libname",
+        "
+entity ent is
+end entity;
+
+architecture a of ent is
+    signal sig : natural;
+begin
+    blk: block is
+        generic (
+            gen : natural;
+            gen_default : natural := 0
+        );
+        generic map (gen => false);
+        port (
+            inport : in natural;
+            outport : out natural
+        );
+        port map (inport => sig);
+    begin
+        outport <= gen + gen_default + inport;
+    end block;
+end architecture;
+    ",
+    );
+
+    let (root, diagnostics) = builder.get_analyzed_root();
+    check_diagnostics(
+        diagnostics,
+        vec![Diagnostic::error(
+            code.s1("false"),
+            "'false' does not match subtype 'NATURAL'",
+        )],
+    );
+
+    // The formals of the block maps reference the block header declarations
+    assert_eq!(
+        root.search_reference_pos(code.source(), code.s1("inport => sig").start()),
+        Some(code.s1("inport").pos())
+    );
+}
+
+#[test]
+fn block_maps_with_type_generic() {
+    let mut builder = LibraryBuilder::new();
+    let code = builder.code(
+        "libname",
+        "
+entity ent is
+end entity;
+
+architecture a of ent is
+    signal sig : integer;
+begin
+    blk: block is
+        generic (
+            type T;
+            gen : natural
+        );
+        generic map (T => integer, gen => 1);
+        port (
+            inport : in T
+        );
+        port map (inport => sig);
+    begin
+    end block;
+end architecture;
+    ",
+    );
+
+    let (root, diagnostics) = builder.get_analyzed_root();
+    check_no_diagnostics(&diagnostics);
+
+    assert_eq!(
+        root.search_reference_pos(code.source(), code.s1("T => integer").start()),
+        Some(code.s1("type T").s1("T").pos())
+    );
+    assert_eq!(
+        root.search_reference_pos(code.source(), code.s1("gen => 1").start()),
+        Some(code.s1("gen :").s1("gen").pos())
+    );
+}
+
+#[test]
+fn open_formal_without_default() {
+    let mut builder = LibraryBuilder::new();
+    let code = builder.code(
+        "libname",
+        "
+entity ent is
+  port (
+    a : in bit;
+    b : in bit := '0';
+    o : out bit);
+end entity;
+
+architecture a of ent is
+begin
+  o <= a and b;
+end architecture;
+
+entity top is
+end entity;
+
+architecture a of top is
+  signal s : bit;
+begin
+  inst: entity work.ent
+    port map (a => open, b => open, o => open);
+end architecture;
+",
+    );
+
+    let diagnostics = builder.analyze();
+    check_diagnostics(
+        diagnostics,
+        vec![Diagnostic::error(
+            code.s1("a => open").s1("open"),
+            "port 'a' : in without a default value cannot be left open",
+        )
+        .related(code.s1("a : in bit").s1("a"), "Defined here")],
+    );
+}
+
+#[test]
+fn open_output_port_that_is_read() {
+    let mut builder = LibraryBuilder::new();
+    let code = builder.code(
+        "libname",
+        "
+entity leaf is
+  port (
+    clk : in bit;
+    count : out natural;
+    wrapped : out boolean);
+end entity;
+
+architecture a of leaf is
+begin
+  process (clk)
+  begin
+    if clk'event and clk = '1' then
+      count <= count + 1;
+      wrapped <= true;
+    end if;
+  end process;
+end architecture;
+
+entity top is
+end entity;
+
+architecture a of top is
+  signal clk : bit;
+
+  component leaf is
+    port (
+      clk : in bit;
+      count : out natural;
+      wrapped : out boolean);
+  end component;
+begin
+  inst: component leaf
+    port map (clk => clk, count => open, wrapped => open);
+end architecture;
+",
+    );
+
+    let diagnostics = builder.analyze();
+    check_diagnostics(
+        diagnostics,
+        vec![Diagnostic::hint(
+            code.s1("count => open").s1("open"),
+            "Output port 'count' is left open but its value is used within entity 'leaf'",
+        )
+        .related(code.s1("count + 1").s1("count"), "Value used here")],
+    );
+}
+
+#[test]
+fn missing_port_map_of_required_input() {
+    let mut builder = LibraryBuilder::new();
+    let code = builder.code(
+        "libname",
+        "
+entity ent_inst is
+  port (
+    clk : in bit;
+    dout : out bit);
+end entity;
+
+architecture a of ent_inst is
+begin
+end architecture;
+
+entity ent is
+end entity;
+
+architecture a of ent is
+begin
+  inst: entity work.ent_inst;
+end architecture;
+",
+    );
+
+    let diagnostics = builder.analyze();
+    check_diagnostics(
+        diagnostics,
+        vec![Diagnostic::error(
+            code.s1("work.ent_inst"),
+            "No association of port 'clk' : in",
+        )
+        .related(code.s1("clk"), "Defined here")],
+    );
+}
+
+#[test]
+fn formal_associated_more_than_once() {
+    let mut builder = LibraryBuilder::new();
+    let code = builder.code(
+        "libname",
+        "
+entity ent_inst is
+  port (
+    a : in bit;
+    b : in bit_vector(0 to 1);
+    c : in bit_vector(0 to 1));
+end entity;
+
+architecture a of ent_inst is
+begin
+end architecture;
+
+entity ent is
+end entity;
+
+architecture a of ent is
+  signal sig : bit;
+  signal vec : bit_vector(0 to 1);
+begin
+  inst: entity work.ent_inst
+    port map (sig, a => sig, b(0) => sig, b(1) => sig, c => vec, c(0) => sig);
+end architecture;
+",
+    );
+
+    let diagnostics = builder.analyze();
+    check_diagnostics(
+        diagnostics,
+        vec![
+            Diagnostic::error(
+                code.s1("a => sig").s1("a"),
+                "Port 'a' : in is associated more than once",
+            )
+            .related(code.s1("(sig, a").s1("sig"), "Previously associated here"),
+            Diagnostic::error(
+                code.s1("c(0)"),
+                "Port 'c' : in is associated more than once",
+            )
+            .related(code.s1("c => vec").s1("c"), "Previously associated here"),
+        ],
+    );
+}
+
+#[test]
+fn partial_associations_must_cover_record_formal() {
+    let mut builder = LibraryBuilder::new();
+    let code = builder.code(
+        "libname",
+        "
+package pkg is
+  type rec_t is record
+    first : bit;
+    second : bit;
+    third : bit;
+  end record;
+end package;
+
+use work.pkg.all;
+
+entity ent_inst is
+  port (
+    complete : in rec_t;
+    incomplete : in rec_t);
+end entity;
+
+architecture a of ent_inst is
+begin
+end architecture;
+
+use work.pkg.all;
+
+entity ent is
+end entity;
+
+architecture a of ent is
+  signal sig : bit;
+begin
+  inst: entity work.ent_inst
+    port map (
+      complete.first => sig,
+      complete.second => sig,
+      complete.third => sig,
+      incomplete.second => sig);
+end architecture;
+",
+    );
+
+    let diagnostics = builder.analyze();
+    check_diagnostics(
+        diagnostics,
+        vec![
+            Diagnostic::error(
+                code.s1("work.ent_inst"),
+                "No association of element 'first' of port 'incomplete' : in",
+            )
+            .related(code.s1("incomplete"), "Defined here"),
+            Diagnostic::error(
+                code.s1("work.ent_inst"),
+                "No association of element 'third' of port 'incomplete' : in",
+            )
+            .related(code.s1("incomplete"), "Defined here"),
+        ],
+    );
+}
+
+#[test]
+fn output_port_must_be_associated_with_signal() {
+    let mut builder = LibraryBuilder::new();
+    let code = builder.code(
+        "libname",
+        "
+entity ent_inst is
+  port (
+    din : in bit;
+    dout : out bit;
+    dio : inout bit_vector(0 to 1);
+    dbuf : buffer bit);
+end entity;
+
+architecture a of ent_inst is
+begin
+end architecture;
+
+entity ent is
+end entity;
+
+architecture a of ent is
+  constant c : bit := '0';
+  signal sig, buf : bit;
+  signal vec : bit_vector(0 to 1);
+  alias sig_alias is sig;
+begin
+  good: entity work.ent_inst
+    port map (din => not sig, dout => sig_alias, dio(0) => vec(1), dio(1) => sig, dbuf => buf);
+
+  bad: entity work.ent_inst
+    port map (din => c, dout => c, dio => \"00\", dbuf => not sig);
+end architecture;
+",
+    );
+
+    let diagnostics = builder.analyze();
+    check_diagnostics(
+        diagnostics,
+        vec![
+            Diagnostic::error(
+                code.s1("dout => c").s1("c"),
+                "Port 'dout' : out must be associated with a signal",
+            )
+            .related(code.s1("dout"), "Defined here"),
+            Diagnostic::error(
+                code.s1("\"00\""),
+                "Port 'dio' : inout must be associated with a signal",
+            )
+            .related(code.s1("dio"), "Defined here"),
+            Diagnostic::error(
+                code.s1("dbuf => not sig").s1("not sig"),
+                "Port 'dbuf' : buffer must be associated with a signal",
+            )
+            .related(code.s1("dbuf"), "Defined here"),
+        ],
+    );
+}
+
+#[test]
+fn input_port_expression_reading_signal_requires_vhdl2008() {
+    let mut builder = LibraryBuilder::new();
+    builder.set_standard(VHDLStandard::VHDL1993);
+    let code = builder.code(
+        "libname",
+        "
+entity ent_inst is
+  port (
+    a : in bit;
+    b : in bit;
+    c : in bit_vector(0 to 1));
+end entity;
+
+architecture a of ent_inst is
+begin
+end architecture;
+
+entity ent is
+end entity;
+
+architecture a of ent is
+  constant k : bit := '0';
+  signal sig : bit;
+begin
+  inst: entity work.ent_inst
+    port map (a => not sig, b => not k, c => (others => '0'));
+end architecture;
+",
+    );
+
+    let diagnostics = builder.analyze();
+    check_diagnostics(
+        diagnostics,
+        vec![Diagnostic::error(
+            code.s1("not sig"),
+            "Port 'a' : in may only be associated with a signal or a globally static expression before VHDL-2008",
+        )],
+    );
+}
+
+#[test]
+fn ports_of_interface_types_are_checked_abstractly() {
+    let mut builder = LibraryBuilder::new();
+    let code = builder.code(
+        "libname",
+        "
+entity reg is
+  generic (
+    type data_t;
+    function \"+\"(l, r : data_t) return data_t is <>);
+  port (
+    d : in data_t;
+    q : out data_t);
+end entity;
+
+architecture rtl of reg is
+  signal sum : data_t;
+begin
+  sum <= d + d;
+  q <= d * d;
+end architecture;
+",
+    );
+
+    let diagnostics = builder.analyze();
+    check_diagnostics(
+        diagnostics,
+        vec![Diagnostic::error(
+            code.s1("*"),
+            "Found no match for operator \"*\"",
+        )],
+    );
+}
+
+#[test]
+fn ports_of_interface_types_have_the_actual_type_in_instances() {
+    let mut builder = LibraryBuilder::new();
+    let code = builder.code(
+        "libname",
+        "
+entity reg is
+  generic (
+    type data_t;
+    width : natural := 1);
+  port (
+    clk : in bit;
+    d : in data_t;
+    q : out data_t);
+end entity;
+
+architecture rtl of reg is
+begin
+end architecture;
+
+entity top is
+end entity;
+
+architecture a of top is
+  component reg_comp is
+    generic (type data_t);
+    port (d : in data_t);
+  end component;
+
+  signal clk, bit_sig : bit;
+  signal int_d, int_q : integer;
+begin
+  good : entity work.reg generic map (data_t => integer, width => 2) port map (clk, int_d, int_q);
+  bad : entity work.reg generic map (integer) port map (clk, bit_sig, int_q);
+  comp : reg_comp generic map (data_t => bit) port map (d => int_d);
+end architecture;
+",
+    );
+
+    let (root, diagnostics) = builder.get_analyzed_root();
+    check_diagnostics(
+        diagnostics,
+        vec![
+            Diagnostic::error(
+                code.s("bit_sig", 2),
+                "signal 'bit_sig' of type 'BIT' does not match integer type 'INTEGER'",
+            ),
+            Diagnostic::error(
+                code.s("int_d", 3),
+                "signal 'int_d' of integer type 'INTEGER' does not match type 'BIT'",
+            ),
+        ],
+    );
+
+    // The formal of an interface type generic is resolved
+    assert_eq!(
+        root.search_reference_pos(
+            code.source(),
+            code.sa("good : entity work.reg generic map (", "data_t")
+                .start()
+        ),
+        Some(code.s1("data_t").pos())
+    );
+}
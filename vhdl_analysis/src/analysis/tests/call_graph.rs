@@ -0,0 +1,257 @@
+//! This Source Code Form is subject to the terms of the Mozilla Public
+//! License, v. 2.0. If a copy of the MPL was not distributed with this file,
+//! You can obtain one at http://mozilla.org/MPL/2.0/.
+//!
+//! Copyright (c) 2023, Olof Kraigher olof.kraigher@gmail.com
+
+use super::*;
+
+#[test]
+fn call_graph_of_package() {
+    let mut builder = LibraryBuilder::new();
+    let code = builder.code(
+        "libname",
+        "
+package pkg is
+  function add(a, b : natural) return natural;
+  function twice(a : natural) return natural;
+  procedure log;
+end package;
+
+package body pkg is
+  function add(a, b : natural) return natural is
+  begin
+    return a + b;
+  end function;
+
+  function twice(a : natural) return natural is
+    function helper return natural is
+    begin
+      return add(a, a);
+    end function;
+  begin
+    log;
+    return helper;
+  end function;
+
+  procedure log is
+  begin
+  end procedure;
+end package body;
+",
+    );
+
+    let (root, diagnostics) = builder.get_analyzed_root();
+    check_no_diagnostics(&diagnostics);
+
+    let graph = root.call_graph();
+    let twice = root
+        .search_reference(code.source(), code.s1("twice").start())
+        .unwrap();
+    let calls: Vec<_> = graph
+        .calls_from(twice)
+        .iter()
+        .map(|call| (call.callee.designator().to_string(), call.pos.clone()))
+        .collect();
+    assert_eq!(
+        calls,
+        vec![
+            ("log".to_owned(), code.s("log", 2).pos()),
+            ("helper".to_owned(), code.s("helper", 2).pos())
+        ]
+    );
+    assert!(graph.recursive_call(twice).is_none());
+
+    assert_eq!(
+        graph.to_dot(),
+        "\
+digraph calls {
+  \"libname.pkg.add[NATURAL, NATURAL return NATURAL]\";
+  \"libname.pkg.log[]\";
+  \"libname.pkg.twice.helper[return NATURAL]\";
+  \"libname.pkg.twice.helper[return NATURAL]\" -> \"libname.pkg.add[NATURAL, NATURAL return NATURAL]\";
+  \"libname.pkg.twice[NATURAL return NATURAL]\";
+  \"libname.pkg.twice[NATURAL return NATURAL]\" -> \"libname.pkg.log[]\";
+  \"libname.pkg.twice[NATURAL return NATURAL]\" -> \"libname.pkg.twice.helper[return NATURAL]\";
+}"
+    );
+
+    assert_eq!(
+        graph.to_json(),
+        "[\
+{\"name\": \"libname.pkg.add[NATURAL, NATURAL return NATURAL]\", \"calls\": []}, \
+{\"name\": \"libname.pkg.log[]\", \"calls\": []}, \
+{\"name\": \"libname.pkg.twice.helper[return NATURAL]\", \"calls\": [\"libname.pkg.add[NATURAL, NATURAL return NATURAL]\"]}, \
+{\"name\": \"libname.pkg.twice[NATURAL return NATURAL]\", \"calls\": [\"libname.pkg.log[]\", \"libname.pkg.twice.helper[return NATURAL]\"]}\
+]"
+    );
+}
+
+#[test]
+fn recursive_function_used_in_constant() {
+    let mut builder = LibraryBuilder::new();
+    let code = builder.in_declarative_region(
+        "
+  function fact(n : natural) return natural is
+  begin
+    if n = 0 then
+      return 1;
+    else
+      return n * fact(n - 1);
+    end if;
+  end function;
+
+  function is_even(n : natural) return boolean;
+
+  function is_odd(n : natural) return boolean is
+  begin
+    return n /= 0 and is_even(n - 1);
+  end function;
+
+  function is_even(n : natural) return boolean is
+  begin
+    return n = 0 or is_odd(n - 1);
+  end function;
+
+  function log_fact(n : natural) return natural is
+  begin
+    return fact(n) / 2;
+  end function;
+
+  constant c0 : natural := fact(5);
+  constant c1 : boolean := is_even(4);
+  constant c2 : natural := log_fact(3);
+  signal s0 : natural := fact(3);
+",
+    );
+
+    let diagnostics = builder.analyze();
+    check_diagnostics(
+        diagnostics,
+        vec![
+            Diagnostic::warning(
+                code.s1("fact(5)").s1("fact"),
+                "Recursive function 'fact' is used in the value of constant 'c0'",
+            )
+            .related(code.s("fact", 2), "Recursion through this call"),
+            Diagnostic::warning(
+                code.s1("is_even(4)").s1("is_even"),
+                "Recursive function 'is_even' is used in the value of constant 'c1'",
+            )
+            .related(code.s("is_odd", 2), "Recursion through this call"),
+            Diagnostic::warning(
+                code.s1("log_fact(3)").s1("log_fact"),
+                "Function 'log_fact' used in the value of constant 'c2' calls recursive function 'fact'",
+            )
+            .related(code.s("fact", 2), "Recursion through this call"),
+        ],
+    );
+}
+
+#[test]
+fn recursion_warnings_can_be_disabled() {
+    let mut builder = LibraryBuilder::new();
+    builder.in_declarative_region(
+        "
+  function fact(n : natural) return natural is
+  begin
+    if n = 0 then
+      return 1;
+    end if;
+    return n * fact(n - 1);
+  end function;
+
+  constant c0 : natural := fact(5);
+",
+    );
+
+    let (mut root, diagnostics) = builder.get_analyzed_root();
+    assert_eq!(diagnostics.len(), 1);
+
+    root.set_warn_recursion(false);
+    let mut diagnostics = Vec::new();
+    root.analyze(&mut diagnostics);
+    check_no_diagnostics(&diagnostics);
+}
+
+#[test]
+fn incoming_and_outgoing_calls() {
+    let mut builder = LibraryBuilder::new();
+    let code = builder.code(
+        "libname",
+        "
+package pkg is
+  function inc(a : natural) return natural;
+  function twice(a : natural) return natural;
+end package;
+
+package body pkg is
+  function inc(a : natural) return natural is
+  begin
+    return a + 1;
+  end function;
+
+  function twice(a : natural) return natural is
+  begin
+    return inc(inc(a));
+  end function;
+end package body;
+
+use work.pkg.all;
+
+entity ent is
+end entity;
+
+architecture a of ent is
+  signal s, t : natural;
+begin
+  main: process
+    variable v : natural := inc(0);
+  begin
+    s <= twice(v);
+    wait;
+  end process;
+
+  t <= inc(1);
+end architecture;
+",
+    );
+
+    let (root, diagnostics) = builder.get_analyzed_root();
+    check_no_diagnostics(&diagnostics);
+
+    let graph = root.call_graph();
+    let described =
+        |calls: Vec<(EntRef, Vec<crate::SrcPos>)>| -> Vec<(String, Vec<crate::SrcPos>)> {
+            calls
+                .into_iter()
+                .map(|(ent, positions)| (ent.describe(), positions))
+                .collect()
+        };
+
+    let inc = root
+        .search_reference(code.source(), code.s1("inc").start())
+        .unwrap();
+    let twice = root
+        .search_reference(code.source(), code.s1("twice").start())
+        .unwrap();
+
+    assert_eq!(
+        described(graph.incoming_calls(inc)),
+        vec![
+            (
+                twice.describe(),
+                vec![code.s("inc", 3).pos(), code.s("inc", 4).pos()]
+            ),
+            ("process 'main'".to_owned(), vec![code.s("inc", 5).pos()]),
+            ("architecture 'a'".to_owned(), vec![code.s("inc", 6).pos()]),
+        ]
+    );
+    assert_eq!(
+        described(graph.outgoing_calls(twice)),
+        vec![(
+            inc.describe(),
+            vec![code.s("inc", 3).pos(), code.s("inc", 4).pos()]
+        )]
+    );
+}
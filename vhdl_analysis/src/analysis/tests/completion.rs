@@ -0,0 +1,331 @@
+//! This Source Code Form is subject to the terms of the Mozilla Public
+//! License, v. 2.0. If a copy of the MPL was not distributed with this file,
+//! You can obtain one at http://mozilla.org/MPL/2.0/.
+//!
+//! Copyright (c) 2023, Olof Kraigher olof.kraigher@gmail.com
+
+use super::*;
+use crate::data::SrcPos;
+use crate::syntax::{ArgumentPosition, ClauseKind, CompletionContext};
+
+fn completion_names(
+    root: &DesignRoot,
+    clause: ClauseKind,
+    prefix: &[&str],
+    partial: &str,
+) -> Vec<String> {
+    let context = CompletionContext {
+        clause,
+        prefix: prefix.iter().map(|name| root.symbol_utf8(name)).collect(),
+        partial: partial.to_owned(),
+        argument: None,
+        attribute_prefix: None,
+        loop_keyword: None,
+    };
+    root.list_completion_options(&context)
+        .into_iter()
+        .map(|option| option.name())
+        .collect()
+}
+
+fn argument_completion_names(
+    root: &DesignRoot,
+    subprogram: &str,
+    index: usize,
+    formal: Option<&str>,
+    partial: &str,
+) -> Vec<String> {
+    let context = CompletionContext {
+        clause: ClauseKind::Argument,
+        prefix: Vec::new(),
+        partial: partial.to_owned(),
+        argument: Some(ArgumentPosition {
+            subprogram: root.symbol_utf8(subprogram),
+            index,
+            formal: formal.map(|name| root.symbol_utf8(name)),
+        }),
+        attribute_prefix: None,
+        loop_keyword: None,
+    };
+    root.list_completion_options(&context)
+        .into_iter()
+        .map(|option| option.name())
+        .collect()
+}
+
+#[test]
+fn completes_library_names() {
+    let mut builder = LibraryBuilder::new();
+    builder.add_std_logic_1164();
+    let (root, _) = builder.get_analyzed_root();
+
+    assert_eq!(
+        completion_names(&root, ClauseKind::Library, &[], "ie"),
+        vec!["ieee"]
+    );
+    assert_eq!(
+        completion_names(&root, ClauseKind::Use, &[], "IE"),
+        vec!["ieee"]
+    );
+}
+
+#[test]
+fn completes_packages_of_library_with_all_suffix() {
+    let mut builder = LibraryBuilder::new();
+    builder.add_std_logic_1164();
+    builder.code(
+        "libname",
+        "
+package pkg is
+end package;
+
+entity ent is
+end entity;
+
+context ctx is
+end context;
+",
+    );
+    let (root, _) = builder.get_analyzed_root();
+
+    assert_eq!(
+        completion_names(&root, ClauseKind::Use, &["ieee"], "std_l"),
+        vec!["std_logic_1164", "std_logic_1164.all"]
+    );
+    assert_eq!(
+        completion_names(&root, ClauseKind::Use, &["libname"], ""),
+        vec!["pkg", "pkg.all"]
+    );
+    assert_eq!(
+        completion_names(&root, ClauseKind::Context, &["libname"], ""),
+        vec!["ctx"]
+    );
+}
+
+#[test]
+fn completes_declarations_of_package() {
+    let mut builder = LibraryBuilder::new();
+    builder.code(
+        "libname",
+        "
+package pkg is
+  constant c_width : natural := 8;
+  type rec_t is record
+    field : natural;
+  end record;
+  function width_of(value : natural) return natural;
+end package;
+",
+    );
+    let (root, _) = builder.get_analyzed_root();
+
+    assert_eq!(
+        completion_names(&root, ClauseKind::Use, &["libname", "pkg"], ""),
+        vec!["all", "c_width", "rec_t", "width_of"]
+    );
+    assert_eq!(
+        completion_names(&root, ClauseKind::Use, &["libname", "pkg"], "W"),
+        vec!["width_of"]
+    );
+    assert_eq!(
+        completion_names(&root, ClauseKind::Use, &["libname", "missing"], ""),
+        Vec::<String>::new()
+    );
+}
+
+#[test]
+fn completes_enum_literals_in_argument_positions() {
+    let mut builder = LibraryBuilder::new();
+    builder.code(
+        "libname",
+        "
+package pkg is
+  type color_t is (red, green, blue);
+  procedure paint(value : natural; color : color_t);
+end package;
+",
+    );
+    let (root, _) = builder.get_analyzed_root();
+
+    assert_eq!(
+        argument_completion_names(&root, "file_open", 2, None, ""),
+        vec!["APPEND_MODE", "READ_MODE", "WRITE_MODE"]
+    );
+    assert_eq!(
+        argument_completion_names(&root, "FILE_OPEN", 0, Some("open_kind"), "wr"),
+        vec!["WRITE_MODE"]
+    );
+    assert_eq!(
+        argument_completion_names(&root, "paint", 1, None, ""),
+        vec!["blue", "green", "red"]
+    );
+    assert_eq!(
+        argument_completion_names(&root, "paint", 0, None, ""),
+        Vec::<String>::new()
+    );
+}
+
+fn attribute_completion_names(root: &DesignRoot, prefix: SrcPos, partial: &str) -> Vec<String> {
+    let context = CompletionContext {
+        clause: ClauseKind::Attribute,
+        prefix: Vec::new(),
+        partial: partial.to_owned(),
+        argument: None,
+        attribute_prefix: Some(prefix),
+        loop_keyword: None,
+    };
+    root.list_completion_options(&context)
+        .into_iter()
+        .map(|option| option.name())
+        .collect()
+}
+
+#[test]
+fn completes_attributes_applicable_to_prefix() {
+    let mut builder = LibraryBuilder::new();
+    let code = builder.in_declarative_region(
+        "
+type state_t is (idle, busy);
+type arr_t is array (natural range <>) of bit;
+signal sig : bit;
+signal arr_sig : arr_t(0 to 3);
+constant state : state_t := idle;
+constant len : natural := arr_sig'length + state_t'pos(state) + boolean'pos(sig'event);
+",
+    );
+    let (root, _) = builder.get_analyzed_root();
+
+    assert_eq!(
+        attribute_completion_names(&root, code.s1("sig'event").s1("sig").pos(), "last_"),
+        vec!["last_active", "last_event", "last_value"]
+    );
+    assert_eq!(
+        attribute_completion_names(&root, code.s1("arr_sig'length").s1("arr_sig").pos(), "r"),
+        vec!["range", "reverse_range", "right"]
+    );
+    assert_eq!(
+        attribute_completion_names(&root, code.s1("state_t'pos").s1("state_t").pos(), "p"),
+        vec!["path_name", "pos", "pred"]
+    );
+    assert_eq!(
+        attribute_completion_names(&root, code.s1("state)").s1("state").pos(), "s"),
+        vec!["simple_name", "subtype"]
+    );
+}
+
+#[test]
+fn finds_predefined_attribute_at_cursor() {
+    let mut builder = LibraryBuilder::new();
+    let code = builder.in_declarative_region(
+        "
+type arr_t is array (natural range <>) of bit;
+signal arr_sig : arr_t(0 to 3);
+constant len : natural := arr_sig'length + natural'high;
+",
+    );
+    let (root, _) = builder.get_analyzed_root();
+
+    let attribute = root
+        .predefined_attribute_at(code.source(), code.s1("length").start())
+        .unwrap();
+    assert_eq!(attribute.name, "length");
+    assert_eq!(attribute.signature, "A'length[(n)]");
+
+    let attribute = root
+        .predefined_attribute_at(code.source(), code.s1("high").start())
+        .unwrap();
+    assert_eq!(attribute.signature, "T'high");
+
+    assert_eq!(
+        root.predefined_attribute_at(code.source(), code.s1("arr_sig'").start()),
+        None
+    );
+}
+
+fn loop_label_completion_names(
+    root: &DesignRoot,
+    clause: ClauseKind,
+    keyword: SrcPos,
+    partial: &str,
+) -> Vec<String> {
+    let context = CompletionContext {
+        clause,
+        prefix: Vec::new(),
+        partial: partial.to_owned(),
+        argument: None,
+        attribute_prefix: None,
+        loop_keyword: Some(keyword),
+    };
+    root.list_completion_options(&context)
+        .into_iter()
+        .map(|option| option.name())
+        .collect()
+}
+
+#[test]
+fn completes_labels_of_enclosing_loops() {
+    let mut builder = LibraryBuilder::new();
+    let code = builder.in_declarative_region(
+        "
+procedure proc is
+begin
+  outer : for i in 0 to 3 loop
+    other : while false loop
+    end loop;
+    if i > 1 then
+      inner : loop
+        exit inner;
+      end loop;
+    end if;
+    loop
+      next outer;
+    end loop;
+  end loop outer;
+  exit;
+end procedure;
+",
+    );
+    let (root, _) = builder.get_analyzed_root();
+
+    assert_eq!(
+        loop_label_completion_names(
+            &root,
+            ClauseKind::LoopLabel,
+            code.s1("exit inner").s1("exit").pos(),
+            ""
+        ),
+        vec!["inner", "outer"]
+    );
+    assert_eq!(
+        loop_label_completion_names(
+            &root,
+            ClauseKind::LoopLabel,
+            code.s1("exit inner").s1("exit").pos(),
+            "o"
+        ),
+        vec!["outer"]
+    );
+    // Loops without a label are skipped
+    assert_eq!(
+        loop_label_completion_names(&root, ClauseKind::LoopLabel, code.s1("next").pos(), ""),
+        vec!["outer"]
+    );
+    assert_eq!(
+        loop_label_completion_names(
+            &root,
+            ClauseKind::EndLoopLabel,
+            code.s1("loop outer").s1("loop").pos(),
+            ""
+        ),
+        vec!["outer"]
+    );
+    assert_eq!(
+        loop_label_completion_names(
+            &root,
+            ClauseKind::LoopLabel,
+            code.s1("exit;").s1("exit").pos(),
+            ""
+        ),
+        Vec::<String>::new()
+    );
+}
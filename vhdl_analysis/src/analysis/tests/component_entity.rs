@@ -0,0 +1,290 @@
+//! This Source Code Form is subject to the terms of the Mozilla Public
+//! License, v. 2.0. If a copy of the MPL was not distributed with this file,
+//! You can obtain one at http://mozilla.org/MPL/2.0/.
+//!
+//! Copyright (c) 2023, Olof Kraigher olof.kraigher@gmail.com
+
+use super::*;
+
+#[test]
+fn component_matching_entity() {
+    let mut builder = LibraryBuilder::new();
+    builder.code(
+        "libname",
+        "
+entity ent is
+  generic (width : natural := 8);
+  port (clk : in bit; data : out bit_vector(width-1 downto 0));
+end entity;
+
+entity top is
+end entity;
+
+architecture a of top is
+  component ent is
+    generic (width : natural := 8);
+    port (clk : in bit; data : out bit_vector(width-1 downto 0));
+  end component;
+begin
+end architecture;
+",
+    );
+
+    let diagnostics = builder.analyze();
+    check_no_diagnostics(&diagnostics);
+}
+
+#[test]
+fn component_drifted_from_entity() {
+    let mut builder = LibraryBuilder::new();
+    let code = builder.code(
+        "libname",
+        "
+entity ent is
+  generic (width : natural := 8);
+  port (clk : in bit; data : out bit_vector(width-1 downto 0); valid : out bit);
+end entity;
+
+entity top is
+end entity;
+
+architecture a of top is
+  component ent is
+    generic (width : integer);
+    port (clk : in boolean; data : in bit_vector(width-1 downto 0); rst : in bit);
+  end component;
+begin
+end architecture;
+",
+    );
+
+    let diagnostics = builder.analyze();
+    check_diagnostics(
+        diagnostics,
+        vec![
+            Diagnostic::warning(
+                code.s("width", 3),
+                "Generic 'width' has a default value in entity 'ent' but not in component 'ent'",
+            )
+            .related(code.s1("width"), "generic 'width' declared here"),
+            Diagnostic::warning(
+                code.s("clk", 2),
+                "Port 'clk' has type 'BOOLEAN' in component 'ent' but type 'BIT' in entity 'ent'",
+            )
+            .related(code.s1("clk"), "port 'clk' declared here"),
+            Diagnostic::warning(
+                code.s("data", 2),
+                "Port 'data' has mode 'in' in component 'ent' but mode 'out' in entity 'ent'",
+            )
+            .related(code.s1("data"), "port 'data' declared here"),
+            Diagnostic::warning(
+                code.sa("component ", "ent"),
+                "Missing port 'valid' of entity 'ent' in component 'ent'",
+            )
+            .related(code.s1("valid"), "port 'valid' declared here"),
+            Diagnostic::warning(
+                code.s1("rst"),
+                "Port 'rst' of component 'ent' does not exist in entity 'ent'",
+            )
+            .related(code.sa("entity ", "ent"), "Entity declared here"),
+        ],
+    );
+}
+
+#[test]
+fn component_ports_in_different_order() {
+    let mut builder = LibraryBuilder::new();
+    let code = builder.code(
+        "libname",
+        "
+entity ent is
+  port (clk : in bit; rst : in bit);
+end entity;
+
+entity top is
+end entity;
+
+architecture a of top is
+  component ent is
+    port (rst : in bit; clk : in bit);
+  end component;
+begin
+end architecture;
+",
+    );
+
+    let diagnostics = builder.analyze();
+    check_diagnostics(
+        diagnostics,
+        vec![Diagnostic::warning(
+            code.sa("component ", "ent"),
+            "The ports of component 'ent' are not declared in the same order as in entity 'ent'",
+        )
+        .related(code.sa("entity ", "ent"), "Entity declared here")],
+    );
+}
+
+#[test]
+fn component_without_entity_is_not_checked() {
+    let mut builder = LibraryBuilder::new();
+    builder.code(
+        "libname",
+        "
+entity top is
+end entity;
+
+architecture a of top is
+  component missing is
+    port (clk : in bit);
+  end component;
+begin
+end architecture;
+",
+    );
+
+    let diagnostics = builder.analyze();
+    check_no_diagnostics(&diagnostics);
+}
+
+#[test]
+fn component_of_other_library_checked_against_default_binding() {
+    let mut builder = LibraryBuilder::new();
+    let code = builder.code(
+        "components",
+        "
+package comps is
+  component ent is
+    port (clk : in bit; rst : in bit);
+  end component;
+end package;
+",
+    );
+    let rtl = builder.code(
+        "rtl",
+        "
+entity ent is
+  port (clk : in bit);
+end entity;
+
+library components;
+use components.comps.all;
+
+entity top is
+end entity;
+
+architecture a of top is
+  signal clk, rst : bit;
+begin
+  inst: ent port map (clk, rst);
+end architecture;
+",
+    );
+
+    let diagnostics = builder.analyze();
+    check_diagnostics(
+        diagnostics,
+        vec![Diagnostic::warning(
+            code.s1("rst"),
+            "Port 'rst' of component 'ent' does not exist in entity 'ent'",
+        )
+        .related(rtl.sa("entity ", "ent"), "Entity declared here")],
+    );
+}
+
+#[test]
+fn configured_instance_is_not_checked_against_default_binding() {
+    let mut builder = LibraryBuilder::new();
+    builder.code(
+        "components",
+        "
+package comps is
+  component ent is
+    port (clk : in bit; rst : in bit);
+  end component;
+end package;
+",
+    );
+    builder.code(
+        "rtl",
+        "
+entity ent is
+  port (clk : in bit);
+end entity;
+
+entity ent_with_reset is
+  port (clk : in bit; rst : in bit);
+end entity;
+
+library components;
+use components.comps.all;
+
+entity top is
+end entity;
+
+architecture a of top is
+  signal clk, rst : bit;
+  for inst : ent use entity work.ent_with_reset;
+begin
+  inst: ent port map (clk, rst);
+end architecture;
+",
+    );
+
+    let diagnostics = builder.analyze();
+    check_no_diagnostics(&diagnostics);
+}
+
+#[test]
+fn sync_component_with_entity() {
+    let mut builder = LibraryBuilder::new();
+    let code = builder.code(
+        "libname",
+        "
+entity ent is
+  generic (width : natural := 8);
+  port (clk : in bit; valid : out bit);
+end entity;
+
+entity top is
+end entity;
+
+architecture a of top is
+  component ent is
+    port (clk : in bit);
+  end component;
+begin
+end architecture;
+",
+    );
+
+    let (root, _) = builder.get_analyzed_root();
+    let (_, component) = root
+        .item_at_cursor(code.source(), code.sa("component ", "ent").start())
+        .unwrap();
+
+    assert_eq!(
+        root.sync_component_with_entity(component),
+        Some((
+            code.s1("component ent is")
+                .s1("component")
+                .pos()
+                .combine(&code.s1("end component;")),
+            "\
+component ent
+    generic (
+      width : natural := 8
+    );
+    port (
+      clk : in bit;
+      valid : out bit
+    );
+  end component;"
+                .to_owned()
+        ))
+    );
+
+    let (_, entity) = root
+        .item_at_cursor(code.source(), code.sa("entity ", "ent").start())
+        .unwrap();
+    assert_eq!(root.sync_component_with_entity(entity), None);
+}
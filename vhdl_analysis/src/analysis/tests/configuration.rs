@@ -0,0 +1,316 @@
+//! This Source Code Form is subject to the terms of the Mozilla Public
+//! License, v. 2.0. If a copy of the MPL was not distributed with this file,
+//! You can obtain one at http://mozilla.org/MPL/2.0/.
+//!
+//! Copyright (c) 2023, Olof Kraigher olof.kraigher@gmail.com
+
+use super::*;
+
+const DESIGN: &str = "
+entity leaf is
+end entity;
+
+architecture rtl of leaf is
+begin
+end architecture;
+
+configuration leaf_cfg of leaf is
+  for rtl
+  end for;
+end configuration;
+
+entity top is
+end entity;
+
+architecture rtl of top is
+  component leaf is
+  end component;
+begin
+  inst0 : component leaf;
+
+  blk : block
+  begin
+    inst1 : component leaf;
+  end block;
+
+  gen : for i in 0 to 1 generate
+    inst2 : component leaf;
+  end generate;
+
+  sel : if alt1: true generate
+    inst3 : component leaf;
+  else alt2: generate
+    inst4 : component leaf;
+  end generate;
+
+  proc : process
+  begin
+    wait;
+  end process;
+end architecture;
+";
+
+#[test]
+fn good_nested_configuration() {
+    let mut builder = LibraryBuilder::new();
+    builder.code(
+        "libname",
+        &format!(
+            "{DESIGN}
+configuration cfg of top is
+  for rtl
+    for inst0 : leaf
+      use entity work.leaf(rtl);
+    end for;
+    for blk
+      for inst1 : leaf
+        use configuration work.leaf_cfg;
+      end for;
+    end for;
+    for gen(0)
+      for all : leaf
+        use open;
+      end for;
+    end for;
+    for sel(alt1)
+      for inst3 : leaf
+        for rtl
+        end for;
+      end for;
+    end for;
+    for sel(alt2)
+      for others : leaf
+        use entity work.leaf;
+      end for;
+    end for;
+  end for;
+end configuration;
+"
+        ),
+    );
+    let diagnostics = builder.analyze();
+    check_no_diagnostics(&diagnostics);
+}
+
+#[test]
+fn configuration_labels_must_match_the_architecture() {
+    let mut builder = LibraryBuilder::new();
+    let code = builder.code(
+        "libname",
+        &format!(
+            "{DESIGN}
+configuration cfg of top is
+  for rtl
+    for missing_blk
+    end for;
+    for proc
+    end for;
+    for blk
+      for inst0 : leaf
+      end for;
+    end for;
+    for proc : leaf
+    end for;
+    for inst0 : leaf
+      use entity work.leaf(missing_arch);
+    end for;
+  end for;
+end configuration;
+
+configuration bad_arch of top is
+  for missing_arch
+  end for;
+end configuration;
+"
+        ),
+    );
+    let diagnostics = builder.analyze();
+    check_diagnostics(
+        diagnostics,
+        vec![
+            Diagnostic::error(
+                code.s1("for missing_blk").s1("missing_blk"),
+                "No block or generate statement with label 'missing_blk'",
+            ),
+            Diagnostic::error(
+                code.s("for proc", 1).s1("proc"),
+                "Expected block or generate label, got process 'proc'",
+            ),
+            Diagnostic::error(
+                code.s("for inst0", 1).s1("inst0"),
+                "No component instance with label 'inst0'",
+            ),
+            Diagnostic::error(
+                code.s1("for proc : leaf").s1("proc"),
+                "Expected component instance label, got process 'proc'",
+            ),
+            Diagnostic::error(
+                code.s("missing_arch", 1),
+                "No architecture 'missing_arch' for entity 'libname.leaf'",
+            )
+            .related(
+                code.s1("architecture rtl of leaf").s1("rtl"),
+                "Architecture 'rtl' declared here",
+            ),
+            Diagnostic::error(
+                code.s("missing_arch", 2),
+                "No architecture 'missing_arch' for entity 'libname.top'",
+            )
+            .related(
+                code.s1("architecture rtl of top").s1("rtl"),
+                "Architecture 'rtl' declared here",
+            ),
+        ],
+    );
+}
+
+#[test]
+fn verification_units_cannot_be_bound_to_open_instance() {
+    let mut builder = LibraryBuilder::new();
+    let code = builder.code(
+        "libname",
+        &format!(
+            "{DESIGN}
+configuration cfg of top is
+  for rtl
+    for inst0 : leaf
+      use open;
+      use vunit checker;
+    end for;
+  end for;
+end configuration;
+"
+        ),
+    );
+    let diagnostics = builder.analyze();
+    check_diagnostics(
+        diagnostics,
+        vec![Diagnostic::error(
+            code.s1("checker"),
+            "Verification units cannot be bound to an open component instance",
+        )],
+    );
+}
+
+#[test]
+fn default_binding_configures_the_entity_of_the_component() {
+    let mut builder = LibraryBuilder::new();
+    let code = builder.code(
+        "libname",
+        &format!(
+            "{DESIGN}
+configuration cfg of top is
+  for rtl
+    for inst0 : leaf
+      for missing_arch
+      end for;
+    end for;
+  end for;
+end configuration;
+"
+        ),
+    );
+    let diagnostics = builder.analyze();
+    check_diagnostics(
+        diagnostics,
+        vec![Diagnostic::error(
+            code.s1("missing_arch"),
+            "No architecture 'missing_arch' for entity 'libname.leaf'",
+        )
+        .related(
+            code.s1("architecture rtl of leaf").s1("rtl"),
+            "Architecture 'rtl' declared here",
+        )],
+    );
+}
+
+#[test]
+fn search_block_configuration_label() {
+    check_search_reference(
+        "
+entity ent is
+end entity;
+
+architecture rtl of ent is
+begin
+  decl : block
+  begin
+  end block;
+end architecture;
+
+configuration cfg of ent is
+  for rtl
+    for decl
+    end for;
+  end for;
+end configuration;
+",
+    );
+}
+
+#[test]
+fn search_generate_alternative_label() {
+    check_search_reference(
+        "
+entity ent is
+end entity;
+
+architecture rtl of ent is
+begin
+  gen : if decl: true generate
+  end generate;
+end architecture;
+
+configuration cfg of ent is
+  for rtl
+    for gen(decl)
+    end for;
+  end for;
+end configuration;
+",
+    );
+}
+
+#[test]
+fn search_component_configuration_instance_label() {
+    check_search_reference(
+        "
+entity ent is
+end entity;
+
+architecture rtl of ent is
+  component comp is
+  end component;
+begin
+  decl : component comp;
+end architecture;
+
+configuration cfg of ent is
+  for rtl
+    for decl : comp
+      use open;
+    end for;
+  end for;
+end configuration;
+",
+    );
+}
+
+#[test]
+fn search_configured_architecture() {
+    check_search_reference(
+        "
+entity ent is
+end entity;
+
+architecture decl of ent is
+begin
+end architecture;
+
+configuration cfg of ent is
+  for decl
+  end for;
+end configuration;
+",
+    );
+}
@@ -0,0 +1,296 @@
+//! This Source Code Form is subject to the terms of the Mozilla Public
+//! License, v. 2.0. If a copy of the MPL was not distributed with this file,
+//! You can obtain one at http://mozilla.org/MPL/2.0/.
+//!
+//! Copyright (c) 2023, Olof Kraigher olof.kraigher@gmail.com
+
+use super::*;
+use crate::config::{GenericOverrides, GenericValue};
+
+#[test]
+fn evaluates_constant_tables_built_from_other_constants() {
+    let mut builder = LibraryBuilder::new();
+    let code = builder.code(
+        "libname",
+        "
+package cfg_pkg is
+  constant num_channels : natural := 2 * 2;
+end package;
+
+use work.cfg_pkg.all;
+
+package tables_pkg is
+  type channel_t is record
+    enabled : boolean;
+    width : natural;
+  end record;
+  type channel_table_t is array (0 to num_channels - 1) of channel_t;
+  type width_table_t is array (natural range <>) of natural;
+
+  constant default_channel : channel_t := (enabled => FALSE, width => 8);
+  constant channels : channel_table_t := (1 => (true, 16), others => default_channel);
+  constant widths : width_table_t := (channels(1).width, channels(2).width + 1);
+  constant name : string := \"ch\";
+end package;
+",
+    );
+
+    let (root, diagnostics) = builder.get_analyzed_root();
+    check_no_diagnostics(&diagnostics);
+
+    let value_of = |name: &str| {
+        let ent = root
+            .search_reference(code.source(), code.sa("constant ", name).start())
+            .unwrap();
+        root.constant_value(ent).map(|value| value.to_string())
+    };
+
+    assert_eq!(value_of("num_channels"), Some("4".to_owned()));
+    assert_eq!(
+        value_of("channels"),
+        Some(
+            "((enabled => FALSE, width => 8), (enabled => TRUE, width => 16), \
+             (enabled => FALSE, width => 8), (enabled => FALSE, width => 8))"
+                .to_owned()
+        )
+    );
+    assert_eq!(value_of("widths"), Some("(16, 9)".to_owned()));
+    // Literals are not repeated on hover
+    assert_eq!(value_of("name"), None);
+
+    let ent = root
+        .search_reference(code.source(), code.sa("constant ", "widths").start())
+        .unwrap();
+    assert_eq!(
+        root.format_declaration(ent),
+        Some(
+            "constant widths : width_table_t := (channels(1).width, channels(2).width + 1);\n\
+             -- Value: (16, 9)"
+                .to_owned()
+        )
+    );
+}
+
+#[test]
+fn evaluates_predefined_operators_on_physical_boolean_and_string_values() {
+    let mut builder = LibraryBuilder::new();
+    let code = builder.code(
+        "libname",
+        "
+package pkg is
+  type distance_t is range 0 to 1000000
+    units
+      mm;
+      cm = 10 mm;
+      m = 100 cm;
+    end units;
+
+  constant width : natural := 8;
+  constant length : distance_t := 2 m + 5 cm;
+  constant half : distance_t := length / 2;
+  constant ratio : integer := length / 1 cm;
+  constant period : time := 2.5 ns * 2;
+  constant wide : boolean := width > 4 and not (width = 16);
+  constant prefix : string := \"ab\";
+  constant full : string := prefix & 'c' & \"de\";
+  constant same : boolean := prefix = \"ab\";
+end package;
+",
+    );
+
+    let (root, diagnostics) = builder.get_analyzed_root();
+    check_no_diagnostics(&diagnostics);
+
+    let value_of = |name: &str| {
+        let ent = root
+            .search_reference(code.source(), code.sa("constant ", name).start())
+            .unwrap();
+        root.constant_value(ent).map(|value| value.to_string())
+    };
+
+    assert_eq!(value_of("length"), Some("2050 mm".to_owned()));
+    assert_eq!(value_of("half"), Some("1025 mm".to_owned()));
+    assert_eq!(value_of("ratio"), Some("205".to_owned()));
+    assert_eq!(value_of("period"), Some("5000000 fs".to_owned()));
+    assert_eq!(value_of("wide"), Some("true".to_owned()));
+    assert_eq!(value_of("full"), Some("\"abcde\"".to_owned()));
+    assert_eq!(value_of("same"), Some("true".to_owned()));
+
+    // Other analyses may evaluate expressions that are not constant declarations
+    let expr = Code::new("2 ** 4 - 1 = 15 and 7 mod (-3) < 0").expr();
+    assert_eq!(
+        root.evaluate_expression(&expr.item)
+            .map(|value| value.to_string()),
+        Some("true".to_owned())
+    );
+}
+
+#[test]
+fn index_outside_of_constant_table() {
+    let mut builder = LibraryBuilder::new();
+    let code = builder.code(
+        "libname",
+        "
+package pkg is
+  type table_t is array (natural range <>) of integer;
+  constant size : natural := 3;
+  constant table : table_t := (10, 20, 30);
+  constant rtable : table_t(7 downto 5) := (others => size);
+  constant good : integer := table(size - 1) + rtable(5);
+  constant bad : integer := table(size);
+  constant bad2 : integer := rtable(8);
+end package;
+",
+    );
+
+    let diagnostics = builder.analyze();
+    check_diagnostics(
+        diagnostics,
+        vec![
+            Diagnostic::error(
+                code.s1("table(size)").s1("size"),
+                "Index 3 is outside of the range 0 to 2 of constant 'table'",
+            ),
+            Diagnostic::error(
+                code.s1("rtable(8)").s1("8"),
+                "Index 8 is outside of the range 7 downto 5 of constant 'rtable'",
+            ),
+        ],
+    );
+}
+
+#[test]
+fn evaluates_top_level_constants_with_generic_overrides() {
+    let mut builder = LibraryBuilder::new();
+    let code = builder.code(
+        "libname",
+        "
+entity top is
+  generic (
+    data_width : natural := 8;
+    depth : natural := 4);
+end entity;
+
+architecture a of top is
+  constant bus_width : natural := 2 * data_width;
+  constant last : natural := depth - 1;
+begin
+end architecture;
+",
+    );
+
+    let (mut root, diagnostics) = builder.get_analyzed_root();
+    check_no_diagnostics(&diagnostics);
+
+    let value_of = |root: &DesignRoot, name: &str| {
+        let ent = root
+            .search_reference(code.source(), code.sa("constant ", name).start())
+            .unwrap();
+        root.constant_value(ent).map(|value| value.to_string())
+    };
+    // Only the generics of top level entities in the generics file are known
+    assert_eq!(value_of(&root, "bus_width"), None);
+
+    let mut overrides = GenericOverrides::default();
+    overrides.set("libname", "TOP", "Data_Width", GenericValue::Integer(32));
+    root.set_generic_overrides(overrides);
+    let mut diagnostics = Vec::new();
+    root.analyze(&mut diagnostics);
+    check_no_diagnostics(&diagnostics);
+
+    assert_eq!(value_of(&root, "bus_width"), Some("64".to_owned()));
+    assert_eq!(value_of(&root, "last"), Some("3".to_owned()));
+
+    let data_width = root
+        .search_reference(code.source(), code.s1("data_width").start())
+        .unwrap();
+    assert_eq!(
+        root.constant_value(data_width)
+            .map(|value| value.to_string()),
+        Some("32".to_owned())
+    );
+}
+
+#[test]
+fn port_widths_use_the_generic_map_of_the_instance() {
+    let mut builder = LibraryBuilder::new();
+    let code = builder.code(
+        "libname",
+        "
+entity child is
+  generic (width : natural := 8);
+  port (d : in bit_vector(width - 1 downto 0));
+end entity;
+
+architecture a of child is
+begin
+end architecture;
+
+entity top is
+end entity;
+
+architecture a of top is
+  constant narrow : natural := 2 * 2;
+  signal s4 : bit_vector(3 downto 0);
+  signal s8 : bit_vector(7 downto 0);
+begin
+  inst0: entity work.child generic map (width => narrow) port map (d => s4);
+  inst1: entity work.child generic map (4) port map (d => s8);
+  inst2: entity work.child port map (s8);
+  inst3: entity work.child port map (d => s4);
+end architecture;
+",
+    );
+
+    let diagnostics = builder.analyze();
+    check_diagnostics(
+        diagnostics,
+        vec![
+            Diagnostic::error(
+                code.s("s8", 2),
+                "Signal 's8' of width 8 is associated with port 'd' of width 4",
+            ),
+            Diagnostic::error(
+                code.s("s4", 3),
+                "Signal 's4' of width 4 is associated with port 'd' of width 8",
+            ),
+        ],
+    );
+}
+
+#[test]
+fn generic_without_default_needs_an_actual_in_the_instance() {
+    let mut builder = LibraryBuilder::new();
+    let code = builder.code(
+        "libname",
+        "
+entity child is
+  generic (width : natural);
+  port (d : in bit_vector(width - 1 downto 0));
+end entity;
+
+architecture a of child is
+begin
+end architecture;
+
+entity top is
+end entity;
+
+architecture a of top is
+  signal s4 : bit_vector(3 downto 0);
+begin
+  inst0: entity work.child generic map (width => 4) port map (d => s4);
+  inst1: entity work.child port map (d => s4);
+end architecture;
+",
+    );
+
+    let diagnostics = builder.analyze();
+    check_diagnostics(
+        diagnostics,
+        vec![
+            Diagnostic::error(code.s("work.child", 2), "No association of generic 'width'")
+                .related(code.s1("width"), "Defined here"),
+        ],
+    );
+}
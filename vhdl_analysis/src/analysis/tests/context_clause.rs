@@ -935,7 +935,7 @@ end package body;
 -- Configuration context clause reference
 use work.pkg.all;
 configuration cfg of ename1 is
-for rtl
+for a
 end for;
 end configuration;
 
@@ -1038,3 +1038,68 @@ end package;
 ",
     );
 }
+
+#[test]
+fn missing_unit_of_work_library_found_within_other_library() {
+    let mut builder = LibraryBuilder::new();
+    let pkg = builder.code(
+        "lib2",
+        "
+package pkg is
+end package;",
+    );
+    let code = builder.code(
+        "lib1",
+        "
+use work.pkg;
+
+entity ent is
+end entity;",
+    );
+
+    let diagnostics = builder.analyze();
+    check_diagnostics(
+        diagnostics,
+        vec![Diagnostic::error(
+            code.s1("pkg"),
+            "No primary unit 'pkg' within library 'lib1'",
+        )
+        .related(
+            pkg.s1("pkg"),
+            "Found within library 'lib2' while work denotes library 'lib1' that this file is compiled into",
+        )],
+    );
+}
+
+#[test]
+fn work_denotes_the_library_of_the_file() {
+    let mut builder = LibraryBuilder::new();
+    let code = builder.code(
+        "lib1",
+        "
+package pkg is
+end package;
+
+use work.pkg;
+library lib1;
+use lib1.pkg;
+
+entity ent is
+end entity;",
+    );
+
+    let (root, diagnostics) = builder.get_analyzed_root();
+    check_no_diagnostics(&diagnostics);
+
+    let libraries = root.work_libraries_at(code.source(), code.s1("work").start());
+    assert_eq!(
+        libraries
+            .iter()
+            .map(|name| name.name_utf8())
+            .collect::<Vec<_>>(),
+        vec!["lib1".to_owned()]
+    );
+    assert!(root
+        .work_libraries_at(code.source(), code.s("lib1", 2).start())
+        .is_empty());
+}
@@ -0,0 +1,111 @@
+//! This Source Code Form is subject to the terms of the Mozilla Public
+//! License, v. 2.0. If a copy of the MPL was not distributed with this file,
+//! You can obtain one at http://mozilla.org/MPL/2.0/.
+//!
+//! Copyright (c) 2023, Olof Kraigher olof.kraigher@gmail.com
+
+use super::*;
+use crate::data::Severity;
+
+fn analyze_dead_generate(builder: &LibraryBuilder, severity: Option<Severity>) -> Vec<Diagnostic> {
+    let (mut root, _) = builder.get_analyzed_root();
+    root.set_dead_generate(severity);
+    let mut diagnostics = Vec::new();
+    root.analyze(&mut diagnostics);
+    diagnostics
+}
+
+fn vendor_code(builder: &mut LibraryBuilder) -> Code {
+    builder.code(
+        "libname",
+        "
+package pkg is
+  constant target : string := \"XILINX\";
+end package;
+
+use work.pkg.all;
+
+entity ent is
+  generic (width : natural);
+end entity;
+
+architecture a of ent is
+  signal s1, s2, s3, s4, s5 : bit;
+begin
+  gen_xilinx: if target = \"XILINX\" generate
+    s1 <= xilinx_sig;
+  end generate;
+
+  gen_intel: if target = \"INTEL\" generate
+    s2 <= intel_sig;
+  elsif target = \"XILINX\" generate
+    s3 <= other_xilinx_sig;
+  else generate
+    s4 <= else_sig;
+  end generate;
+
+  gen_unknown: if width = 0 generate
+    s5 <= unknown_sig;
+  end generate;
+end architecture;
+",
+    )
+}
+
+#[test]
+fn diagnostics_in_branches_that_are_never_selected_are_downgraded() {
+    let mut builder = LibraryBuilder::new();
+    let code = vendor_code(&mut builder);
+
+    check_diagnostics(
+        analyze_dead_generate(&builder, Some(Severity::Hint)),
+        vec![
+            Diagnostic::error(code.s1("xilinx_sig"), "No declaration of 'xilinx_sig'"),
+            Diagnostic::new(
+                code.s1("intel_sig"),
+                "No declaration of 'intel_sig'",
+                Severity::Hint,
+            ),
+            Diagnostic::error(
+                code.s1("other_xilinx_sig"),
+                "No declaration of 'other_xilinx_sig'",
+            ),
+            Diagnostic::new(
+                code.s1("else_sig"),
+                "No declaration of 'else_sig'",
+                Severity::Hint,
+            ),
+            Diagnostic::error(code.s1("unknown_sig"), "No declaration of 'unknown_sig'"),
+        ],
+    );
+}
+
+#[test]
+fn diagnostics_in_branches_that_are_never_selected_can_be_hidden() {
+    let mut builder = LibraryBuilder::new();
+    let code = vendor_code(&mut builder);
+
+    check_diagnostics(
+        analyze_dead_generate(&builder, None),
+        vec![
+            Diagnostic::error(code.s1("xilinx_sig"), "No declaration of 'xilinx_sig'"),
+            Diagnostic::error(
+                code.s1("other_xilinx_sig"),
+                "No declaration of 'other_xilinx_sig'",
+            ),
+            Diagnostic::error(code.s1("unknown_sig"), "No declaration of 'unknown_sig'"),
+        ],
+    );
+}
+
+#[test]
+fn diagnostics_in_branches_are_kept_when_not_configured() {
+    let mut builder = LibraryBuilder::new();
+    vendor_code(&mut builder);
+
+    let diagnostics = builder.analyze();
+    assert_eq!(diagnostics.len(), 5);
+    assert!(diagnostics
+        .iter()
+        .all(|diagnostic| diagnostic.severity == Severity::Error));
+}
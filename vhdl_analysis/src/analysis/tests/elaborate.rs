@@ -0,0 +1,314 @@
+//! This Source Code Form is subject to the terms of the Mozilla Public
+//! License, v. 2.0. If a copy of the MPL was not distributed with this file,
+//! You can obtain one at http://mozilla.org/MPL/2.0/.
+//!
+//! Copyright (c) 2023, Olof Kraigher olof.kraigher@gmail.com
+
+use super::*;
+use crate::analysis::ElaboratedInstance;
+use crate::config::{GenericOverrides, GenericValue};
+
+/// One line per instance indented by its depth with the entity, architecture and generics
+fn hierarchy(instance: &ElaboratedInstance) -> Vec<String> {
+    fn visit(instance: &ElaboratedInstance, depth: usize, lines: &mut Vec<String>) {
+        let generics: Vec<_> = instance
+            .generics
+            .iter()
+            .map(|(name, value)| format!("{name}={value}"))
+            .collect();
+        lines.push(format!(
+            "{}{}: {}.{}({}) {}",
+            "  ".repeat(depth),
+            instance.name,
+            instance.library_name,
+            instance.entity_name,
+            instance
+                .architecture_name
+                .as_ref()
+                .map(|name| name.to_string())
+                .unwrap_or_default(),
+            generics.join(", ")
+        ));
+        for child in instance.children.iter() {
+            visit(child, depth + 1, lines);
+        }
+    }
+    let mut lines = Vec::new();
+    visit(instance, 0, &mut lines);
+    lines
+}
+
+#[test]
+fn expands_generate_statements_with_generics_of_each_instance() {
+    let mut builder = LibraryBuilder::new();
+    builder.code(
+        "libname",
+        "
+entity leaf is
+  generic (width : natural := 1);
+end entity;
+
+architecture rtl of leaf is
+begin
+end architecture;
+
+entity top is
+  generic (
+    num : natural := 2;
+    use_extra : boolean := false;
+    mode : natural := 0);
+end entity;
+
+architecture rtl of top is
+  constant base : natural := 4 * num;
+begin
+  gen_lanes : for i in 0 to num - 1 generate
+    lane : entity work.leaf generic map (width => base + i);
+  end generate;
+
+  gen_extra : if use_extra generate
+    extra : entity work.leaf generic map (16);
+  else generate
+    none : entity work.leaf;
+  end generate;
+
+  gen_mode : case mode generate
+    when 0 =>
+      zero : entity work.leaf(rtl) generic map (width => 32);
+    when 1 to 3 =>
+      small : entity work.leaf;
+    when others =>
+  end generate;
+end architecture;
+",
+    );
+
+    let (mut root, diagnostics) = builder.get_analyzed_root();
+    check_no_diagnostics(&diagnostics);
+
+    let elaboration = root.elaborate("top").unwrap();
+    check_no_diagnostics(&elaboration.diagnostics);
+    assert_eq!(
+        hierarchy(&elaboration.top),
+        vec![
+            "top: libname.top(rtl) num=2, use_extra=FALSE, mode=0",
+            "  gen_lanes(0).lane: libname.leaf(rtl) width=8",
+            "  gen_lanes(1).lane: libname.leaf(rtl) width=9",
+            "  gen_extra.none: libname.leaf(rtl) width=1",
+            "  gen_mode.zero: libname.leaf(rtl) width=32",
+        ]
+    );
+
+    let mut overrides = GenericOverrides::default();
+    overrides.set("libname", "top", "num", GenericValue::Integer(1));
+    overrides.set("libname", "top", "use_extra", GenericValue::Boolean(true));
+    overrides.set("libname", "top", "mode", GenericValue::Integer(2));
+    root.set_generic_overrides(overrides);
+
+    let elaboration = root.elaborate("libname.top(rtl)").unwrap();
+    check_no_diagnostics(&elaboration.diagnostics);
+    assert_eq!(
+        hierarchy(&elaboration.top),
+        vec![
+            "top: libname.top(rtl) num=1, use_extra=true, mode=2",
+            "  gen_lanes(0).lane: libname.leaf(rtl) width=4",
+            "  gen_extra.extra: libname.leaf(rtl) width=16",
+            "  gen_mode.small: libname.leaf(rtl) width=1",
+        ]
+    );
+}
+
+#[test]
+fn binds_components_by_configuration_or_default() {
+    let mut builder = LibraryBuilder::new();
+    builder.code(
+        "libname",
+        "
+entity leaf is
+  generic (width : natural := 1);
+end entity;
+
+architecture rtl of leaf is
+begin
+end architecture;
+
+architecture sim of leaf is
+begin
+end architecture;
+
+entity top is
+end entity;
+
+architecture rtl of top is
+  component leaf is
+    generic (width : natural := 1);
+  end component;
+
+  component missing is
+  end component;
+
+  for configured : leaf use entity work.leaf(rtl);
+begin
+  configured : leaf generic map (width => 3);
+  defaulted : component leaf;
+
+  blk : block is
+  begin
+    unbound : component missing;
+  end block;
+end architecture;
+",
+    );
+
+    let (root, diagnostics) = builder.get_analyzed_root();
+    check_no_diagnostics(&diagnostics);
+
+    let elaboration = root.elaborate("top").unwrap();
+    assert_eq!(
+        hierarchy(&elaboration.top),
+        vec![
+            "top: libname.top(rtl) ",
+            "  configured: libname.leaf(rtl) width=3",
+            "  defaulted: libname.leaf(sim) width=1",
+        ]
+    );
+    assert_eq!(elaboration.diagnostics.len(), 1);
+    assert_eq!(
+        elaboration.diagnostics[0].message,
+        "No entity 'missing' in library 'libname' is bound to the instance"
+    );
+}
+
+#[test]
+fn top_level_must_be_an_entity_of_a_single_library() {
+    let mut builder = LibraryBuilder::new();
+    builder.code(
+        "lib1",
+        "
+entity ent is
+end entity;
+",
+    );
+    builder.code(
+        "lib2",
+        "
+entity ent is
+end entity;
+",
+    );
+
+    let (root, diagnostics) = builder.get_analyzed_root();
+    check_no_diagnostics(&diagnostics);
+
+    assert_eq!(
+        root.elaborate("ent").err(),
+        Some("Entity 'ent' exists in libraries lib1, lib2".to_owned())
+    );
+    assert_eq!(
+        root.elaborate("missing").err(),
+        Some("No entity 'missing'".to_owned())
+    );
+    let elaboration = root.elaborate("lib2.ent").unwrap();
+    assert_eq!(hierarchy(&elaboration.top), vec!["ent: lib2.ent() "]);
+}
+
+#[test]
+fn exports_hierarchy_as_text_and_json() {
+    let mut builder = LibraryBuilder::new();
+    let code = builder.code(
+        "libname",
+        "
+entity leaf is
+  generic (name : string := \"a\"\"b\");
+end entity;
+
+entity top is
+end entity;
+
+architecture rtl of top is
+begin
+  inst : entity work.leaf;
+end architecture;
+",
+    );
+
+    let (root, diagnostics) = builder.get_analyzed_root();
+    check_no_diagnostics(&diagnostics);
+
+    let file_name = code.source().file_name().to_string_lossy().to_string();
+    let elaboration = root.elaborate("top").unwrap();
+    assert_eq!(
+        elaboration.to_text(),
+        format!(
+            "top : libname.top(rtl) at {file_name}:6\n  \
+             inst : libname.leaf generic map (name => \"a\"b\") at {file_name}:11\n"
+        )
+    );
+    assert_eq!(
+        elaboration.to_json(),
+        format!(
+            "{{\"name\": \"top\", \"library\": \"libname\", \"entity\": \"top\", \
+             \"architecture\": \"rtl\", \"generics\": {{}}, \"file\": \"{file_name}\", \"line\": 6, \
+             \"children\": [{{\"name\": \"inst\", \"library\": \"libname\", \"entity\": \"leaf\", \
+             \"architecture\": null, \"generics\": {{\"name\": \"\\\"a\\\"b\\\"\"}}, \
+             \"file\": \"{file_name}\", \"line\": 11, \"children\": []}}]}}"
+        )
+    );
+}
+
+#[test]
+fn exports_netlist_with_nets_connected_through_port_maps() {
+    let mut builder = LibraryBuilder::new();
+    builder.code(
+        "libname",
+        "
+entity buf is
+  generic (width : natural := 1);
+  port (a : in bit; y : out bit);
+end entity;
+
+entity top is
+  port (i : in bit; o : out bit);
+end entity;
+
+architecture rtl of top is
+  signal s : bit;
+begin
+  first : entity work.buf
+    generic map (width => 2)
+    port map (a => i, y => s);
+
+  second : entity work.buf
+    port map (s, o);
+end architecture;
+",
+    );
+
+    let (root, diagnostics) = builder.get_analyzed_root();
+    check_no_diagnostics(&diagnostics);
+
+    let elaboration = root.elaborate("top").unwrap();
+    assert_eq!(
+        elaboration.to_netlist_json(),
+        "{\"creator\": \"vhdl_lang\", \"modules\": {\
+         \"top\": {\"attributes\": {\"top\": 1}, \
+         \"ports\": {\"i\": {\"direction\": \"input\", \"bits\": [2]}, \
+         \"o\": {\"direction\": \"output\", \"bits\": [3]}}, \
+         \"cells\": {\
+         \"first\": {\"type\": \"top.first\", \"parameters\": {\"width\": \"2\"}, \
+         \"port_directions\": {\"a\": \"input\", \"y\": \"output\"}, \
+         \"connections\": {\"a\": [2], \"y\": [4]}}, \
+         \"second\": {\"type\": \"top.second\", \"parameters\": {\"width\": \"1\"}, \
+         \"port_directions\": {\"a\": \"input\", \"y\": \"output\"}, \
+         \"connections\": {\"a\": [4], \"y\": [3]}}}, \
+         \"netnames\": {\"i\": {\"bits\": [2]}, \"o\": {\"bits\": [3]}, \"s\": {\"bits\": [4]}}}, \
+         \"top.first\": {\"attributes\": {}, \
+         \"ports\": {\"a\": {\"direction\": \"input\", \"bits\": [2]}, \
+         \"y\": {\"direction\": \"output\", \"bits\": [4]}}, \"cells\": {}, \
+         \"netnames\": {\"a\": {\"bits\": [2]}, \"y\": {\"bits\": [4]}}}, \
+         \"top.second\": {\"attributes\": {}, \
+         \"ports\": {\"a\": {\"direction\": \"input\", \"bits\": [4]}, \
+         \"y\": {\"direction\": \"output\", \"bits\": [3]}}, \"cells\": {}, \
+         \"netnames\": {\"a\": {\"bits\": [4]}, \"y\": {\"bits\": [3]}}}}}"
+    );
+}
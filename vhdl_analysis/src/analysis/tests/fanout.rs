@@ -0,0 +1,153 @@
+//! This Source Code Form is subject to the terms of the Mozilla Public
+//! License, v. 2.0. If a copy of the MPL was not distributed with this file,
+//! You can obtain one at http://mozilla.org/MPL/2.0/.
+//!
+//! Copyright (c) 2023, Olof Kraigher olof.kraigher@gmail.com
+
+use super::*;
+
+fn fanout_code(builder: &mut LibraryBuilder) -> Code {
+    builder.code(
+        "libname",
+        "
+entity sub is
+  port (
+    d : in bit;
+    q : out bit
+  );
+end entity;
+
+architecture a of sub is
+begin
+  q <= d;
+end architecture;
+
+entity ent is
+  port (
+    clk : in bit;
+    output : out bit
+  );
+end entity;
+
+architecture a of ent is
+  signal x, y : bit;
+  alias y_alias : bit is y;
+begin
+  main: process (clk)
+  begin
+    if clk'event and clk = '1' then
+      x <= not x;
+    end if;
+  end process;
+
+  y_alias <= x;
+  output <= x and y;
+
+  inst: entity work.sub
+    port map (
+      d => x,
+      q => y
+    );
+end architecture;
+",
+    )
+}
+
+#[test]
+fn counts_drivers_and_loads_of_signals_within_architecture() {
+    let mut builder = LibraryBuilder::new();
+    fanout_code(&mut builder);
+    let (root, diagnostics) = builder.get_analyzed_root();
+    check_no_diagnostics(&diagnostics);
+
+    let architectures: Vec<_> = root
+        .signal_fanout()
+        .into_iter()
+        .map(|fanout| {
+            let signals: Vec<_> = fanout
+                .signals
+                .iter()
+                .map(|signal| {
+                    (
+                        signal.signal.designator().to_string(),
+                        signal.drivers,
+                        signal.loads,
+                    )
+                })
+                .collect();
+            (fanout.architecture.describe(), signals)
+        })
+        .collect();
+
+    assert_eq!(
+        architectures,
+        vec![
+            (
+                "architecture 'a'".to_owned(),
+                vec![("q".to_owned(), 1, 0), ("d".to_owned(), 0, 1)]
+            ),
+            (
+                "architecture 'a'".to_owned(),
+                vec![
+                    ("x".to_owned(), 1, 4),
+                    ("clk".to_owned(), 0, 1),
+                    ("y".to_owned(), 2, 1),
+                    ("output".to_owned(), 1, 0),
+                ]
+            ),
+        ]
+    );
+}
+
+#[test]
+fn fanout_of_signal_and_port() {
+    let mut builder = LibraryBuilder::new();
+    let code = fanout_code(&mut builder);
+    let (root, diagnostics) = builder.get_analyzed_root();
+    check_no_diagnostics(&diagnostics);
+
+    let fanout_of = |decl: &str| -> Vec<(String, usize, usize)> {
+        let (_, signal) = root
+            .item_at_cursor(code.source(), code.s1(decl).start())
+            .unwrap();
+        root.fanout_of(signal)
+            .into_iter()
+            .map(|(architecture, fanout)| (architecture.describe(), fanout.drivers, fanout.loads))
+            .collect()
+    };
+
+    assert_eq!(
+        fanout_of("x, y : bit"),
+        vec![("architecture 'a'".to_owned(), 1, 4)]
+    );
+    assert_eq!(
+        fanout_of("clk : in bit"),
+        vec![("architecture 'a'".to_owned(), 0, 1)]
+    );
+}
+
+#[test]
+fn signals_with_more_loads_or_drivers_than_the_limits() {
+    let mut builder = LibraryBuilder::new();
+    let code = fanout_code(&mut builder);
+    let (mut root, _) = builder.get_analyzed_root();
+    root.set_fanout_limits(Some(3), Some(1));
+    let mut diagnostics = Vec::new();
+    root.analyze(&mut diagnostics);
+
+    check_diagnostics(
+        diagnostics,
+        vec![
+            Diagnostic::warning(
+                code.s1("x, y : bit").s1("x"),
+                "Signal 'x' has 4 loads within architecture 'a' which is more than the limit of 3",
+            )
+            .with_code("signal-fanout"),
+            Diagnostic::warning(
+                code.s1("x, y : bit").s1("y"),
+                "Signal 'y' has 2 drivers within architecture 'a' which is more than the limit of 1",
+            )
+            .with_code("signal-drivers"),
+        ],
+    );
+}
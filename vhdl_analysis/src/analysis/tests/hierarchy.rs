@@ -81,6 +81,7 @@ package body pkg is
     function fun0(arg : natural) return natural is
         variable v0 : natural;
     begin
+      return 0;
     end function;
 end package body;
       ",
@@ -330,8 +331,12 @@ architecture a of ent is
 begin
   process
   begin
-    exit;
-    next;
+    if true then
+      exit;
+    end if;
+    if true then
+      next;
+    end if;
 
     loop
         exit;
@@ -405,3 +410,49 @@ end architecture;
         ],
     );
 }
+
+#[test]
+fn exit_and_next_label_must_be_a_loop() {
+    let mut builder = LibraryBuilder::new();
+    let code = builder.code(
+        "libname",
+        "
+entity ent is
+end entity;
+
+architecture a of ent is
+begin
+  main: process
+  begin
+    l0: loop
+        cond: if true then
+            exit main when true;
+            next cond;
+        end if;
+    end loop;
+  end process;
+end architecture;
+      ",
+    );
+
+    let (root, diagnostics) = builder.get_analyzed_root();
+    check_diagnostics(
+        diagnostics,
+        vec![
+            Diagnostic::error(
+                code.sa("exit ", "main"),
+                "Expected loop label, got process 'main'",
+            ),
+            Diagnostic::error(
+                code.sa("next ", "cond"),
+                "Expected loop label, got if 'cond'",
+            ),
+        ],
+    );
+
+    // The label is still referenced for navigation
+    assert_eq!(
+        root.search_reference_pos(code.source(), code.sa("next ", "cond").start()),
+        Some(code.s1("cond").pos())
+    );
+}
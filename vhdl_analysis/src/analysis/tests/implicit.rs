@@ -266,3 +266,79 @@ end package;
 ",
     );
 }
+
+#[test]
+fn resolves_textio_and_file_open_calls() {
+    let mut builder = LibraryBuilder::new();
+    builder.code(
+        "lib",
+        "
+use std.textio.all;
+
+package pkg is
+end package;
+
+package body pkg is
+  procedure proc is
+    file f : text;
+    file f2 : text open write_mode is \"out.txt\";
+    variable l : line;
+    variable status : file_open_status;
+    variable kind : file_open_kind;
+    variable good : boolean;
+    variable i : integer;
+    variable r : real;
+    variable t : time;
+    variable s : string(1 to 3);
+    variable b : bit_vector(0 to 3);
+  begin
+    file_open(f, \"foo.txt\");
+    file_open(f, \"foo.txt\", write_mode);
+    file_open(f, \"foo.txt\", open_kind => append_mode);
+    file_open(status, f, \"foo.txt\");
+    file_open(status, f, \"foo.txt\", read_mode);
+    file_open(status => status, f => f, external_name => \"foo.txt\", open_kind => read_mode);
+    assert status = open_ok or status = status_error or status = name_error or status = mode_error;
+    kind := file_mode(f);
+    i := file_size(f);
+    file_rewind(f);
+    readline(f, l);
+    read(l, i, good);
+    read(l, r);
+    read(l, t, good);
+    sread(l, s, i);
+    hread(l, b, good);
+    oread(l, b);
+    bread(l, b);
+    write(l, i);
+    write(l, i, right, 5);
+    write(l, r, left, 10, 3);
+    write(l, r, \"%f\");
+    write(l, t, right, 0, ns);
+    write(l, true);
+    write(l, 'c', left);
+    write(l, s);
+    write(l, string'(\"abc\"));
+    swrite(l, \"abc\");
+    hwrite(l, b, right, 4);
+    owrite(l, b);
+    bwrite(l, b);
+    writeline(f2, l);
+    writeline(output, l);
+    tee(output, l);
+    deallocate(l);
+    file_close(f);
+    report \"msg\" severity note;
+    report \"msg\" severity warning;
+    report \"msg\" severity error;
+    report \"msg\" severity failure;
+    assert false report to_string(warning) severity failure;
+    assert severity_level'pos(failure) = 3;
+    assert justify(\"a\", left, 3) = \"a  \";
+  end procedure;
+end package body;
+",
+    );
+    let diagnostics = builder.analyze();
+    check_no_diagnostics(&diagnostics);
+}
@@ -10,6 +10,7 @@ use crate::analysis::EntityId;
 use crate::ast::search::*;
 use crate::ast::Reference;
 use crate::data::SrcPos;
+use crate::syntax::test::CodeBuilder;
 use fnv::FnvHashSet;
 use pretty_assertions::assert_eq;
 
@@ -86,6 +87,33 @@ end architecture;
     check_incremental_analysis(builder, vec![]);
 }
 
+#[test]
+fn incremental_analysis_of_entity_declarations_used_in_architecture() {
+    let mut builder = LibraryBuilder::new();
+    builder.code(
+        "libname",
+        "
+entity ent is
+  generic (width : natural := 8);
+  subtype word_t is bit_vector(width-1 downto 0);
+  constant c_zero : word_t := (others => '0');
+end entity;
+",
+    );
+
+    builder.code(
+        "libname",
+        "
+architecture a of ent is
+  signal s : word_t := c_zero;
+begin
+end architecture;
+",
+    );
+
+    check_incremental_analysis(builder, vec![]);
+}
+
 #[test]
 fn incremental_analysis_of_package_and_body() {
     let mut builder = LibraryBuilder::new();
@@ -158,7 +186,7 @@ end architecture;
         "libname",
         "
 configuration cfg of ent is
-for rtl
+for a
 end for;
 end configuration;
 ",
@@ -258,6 +286,96 @@ end package body;
     check_incremental_analysis(builder, vec![]);
 }
 
+#[test]
+fn whitespace_and_comment_changes_do_not_reanalyze_users() {
+    let mut builder = LibraryBuilder::new();
+    let pkg = builder.code(
+        "libname",
+        "
+package pkg is
+  constant const : natural := 0;
+end package;
+",
+    );
+
+    let user = builder.code(
+        "libname",
+        "
+use work.pkg.const;
+
+package pkg2 is
+  constant const2 : natural := const;
+end package;
+",
+    );
+
+    let (mut root, diagnostics) = builder.get_analyzed_root();
+    check_no_diagnostics(&diagnostics);
+
+    let libname = root.symbol_utf8("libname");
+    let user_id = UnitId::package(&libname, &root.symbol_utf8("pkg2"));
+    let user_decl = root
+        .search_reference(user.source(), user.s1("const2").start())
+        .unwrap()
+        .id();
+    // Keep the analysis of the user alive to compare with later
+    let user_arena = root
+        .get_unit(&user_id)
+        .unwrap()
+        .unit
+        .get()
+        .unwrap()
+        .result()
+        .arena
+        .clone();
+
+    let code_builder = CodeBuilder {
+        symbols: builder.symbols(),
+    };
+    let update = |root: &mut DesignRoot, contents: &str| {
+        let code = code_builder.code_with_file_name(pkg.source().file_name(), contents);
+        root.remove_source(libname.clone(), code.source());
+        root.add_design_file(libname.clone(), code.design_file());
+        let mut diagnostics = Vec::new();
+        root.analyze(&mut diagnostics);
+        check_no_diagnostics(&diagnostics);
+        code
+    };
+
+    let pkg = update(
+        &mut root,
+        "
+-- Only whitespace and comments differ
+package pkg is
+
+  constant const : natural := 0; -- Zero
+end package;
+",
+    );
+    assert!(std::ptr::eq(
+        user_arena.get(user_decl),
+        root.get_ent(user_decl)
+    ));
+    assert_eq!(
+        root.search_reference(user.source(), user.s1("pkg.const").s1("const").start())
+            .and_then(|ent| ent.decl_pos().cloned()),
+        Some(pkg.s1("const :").s1("const").pos())
+    );
+
+    update(
+        &mut root,
+        "
+package pkg is
+  constant const : natural := 1;
+end package;
+",
+    );
+    assert!(!std::ptr::eq(
+        user_arena.get(user_decl),
+        root.get_ent(user_decl)
+    ));
+}
+
 fn check_incremental_analysis(builder: LibraryBuilder, expected_diagnostics: Vec<Diagnostic>) {
     let symbols = builder.symbols();
     let codes = builder.take_code();
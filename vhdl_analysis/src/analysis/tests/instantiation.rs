@@ -0,0 +1,146 @@
+//! This Source Code Form is subject to the terms of the Mozilla Public
+//! License, v. 2.0. If a copy of the MPL was not distributed with this file,
+//! You can obtain one at http://mozilla.org/MPL/2.0/.
+//!
+//! Copyright (c) 2023, Olof Kraigher olof.kraigher@gmail.com
+
+use super::*;
+use crate::analysis::InstantiationForm;
+
+#[test]
+fn instantiation_text_of_entity() {
+    let mut builder = LibraryBuilder::new();
+    let code = builder.code(
+        "libname",
+        "
+entity ent is
+  generic (
+    width : natural := 8
+  );
+  port (
+    clk : in bit;
+    data : out bit_vector(width - 1 downto 0)
+  );
+end entity;
+",
+    );
+
+    let (root, diagnostics) = builder.get_analyzed_root();
+    check_no_diagnostics(&diagnostics);
+
+    let (_, entity) = root
+        .item_at_cursor(code.source(), code.sa("entity ", "ent").start())
+        .unwrap();
+
+    assert_eq!(
+        root.instantiation_text(entity, InstantiationForm::Entity)
+            .unwrap(),
+        "\
+ent_inst : entity libname.ent
+  generic map (
+    width => width
+  )
+  port map (
+    clk  => clk,
+    data => data
+  );"
+    );
+
+    assert_eq!(
+        root.instantiation_text(entity, InstantiationForm::Component)
+            .unwrap(),
+        "\
+component ent
+  generic (
+    width : natural := 8
+  );
+  port (
+    clk : in bit;
+    data : out bit_vector(width - 1 downto 0)
+  );
+end component;
+
+ent_inst : component ent
+  generic map (
+    width => width
+  )
+  port map (
+    clk  => clk,
+    data => data
+  );"
+    );
+}
+
+#[test]
+fn instantiation_text_without_interface() {
+    let mut builder = LibraryBuilder::new();
+    let code = builder.code(
+        "libname",
+        "
+entity ent is
+end entity;
+
+architecture a of ent is
+begin
+end architecture;
+",
+    );
+
+    let (root, _) = builder.get_analyzed_root();
+
+    let (_, entity) = root
+        .item_at_cursor(code.source(), code.sa("entity ", "ent").start())
+        .unwrap();
+    assert_eq!(
+        root.instantiation_text(entity, InstantiationForm::Entity),
+        Some("ent_inst : entity libname.ent;".to_owned())
+    );
+
+    let (_, architecture) = root
+        .item_at_cursor(code.source(), code.s1("a of").start())
+        .unwrap();
+    assert_eq!(
+        root.instantiation_text(architecture, InstantiationForm::Entity),
+        None
+    );
+}
+
+#[test]
+fn instantiation_snippet_has_placeholders_for_label_and_actuals() {
+    let mut builder = LibraryBuilder::new();
+    let code = builder.code(
+        "libname",
+        "
+entity ent is
+  generic (
+    width : natural := 8
+  );
+  port (
+    clk : in bit;
+    data : out bit_vector(width - 1 downto 0)
+  );
+end entity;
+",
+    );
+
+    let (root, diagnostics) = builder.get_analyzed_root();
+    check_no_diagnostics(&diagnostics);
+
+    let (_, entity) = root
+        .item_at_cursor(code.source(), code.sa("entity ", "ent").start())
+        .unwrap();
+
+    assert_eq!(
+        root.instantiation_snippet(entity, InstantiationForm::Entity)
+            .unwrap(),
+        "\
+${1:ent_inst} : entity libname.ent
+  generic map (
+    width => ${2:width}
+  )
+  port map (
+    clk  => ${3:clk},
+    data => ${4:data}
+  );$0"
+    );
+}
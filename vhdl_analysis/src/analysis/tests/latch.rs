@@ -0,0 +1,90 @@
+//! This Source Code Form is subject to the terms of the Mozilla Public
+//! License, v. 2.0. If a copy of the MPL was not distributed with this file,
+//! You can obtain one at http://mozilla.org/MPL/2.0/.
+//!
+//! Copyright (c) 2023, Olof Kraigher olof.kraigher@gmail.com
+
+use super::*;
+
+#[test]
+fn signal_not_assigned_in_all_branches_infers_latch() {
+    let mut builder = LibraryBuilder::new();
+    let code = builder.code(
+        "libname",
+        "
+entity ent is
+  port (sel : in bit; a, b : in bit; x, y, z : out bit);
+end entity;
+
+architecture a of ent is
+  type state_t is (idle, busy, done);
+  signal state : state_t;
+begin
+  process (sel, a, b, state)
+  begin
+    z <= '0';
+    if sel = '1' then
+      x <= a;
+      y <= a;
+      z <= a;
+    elsif a = '1' then
+      y <= b;
+    else
+      y <= '0';
+    end if;
+
+    case state is
+      when idle => x <= b;
+      when busy => x <= a;
+      when others => null;
+    end case;
+  end process;
+end architecture;
+",
+    );
+
+    let diagnostics = builder.analyze();
+    check_diagnostics(
+        diagnostics,
+        vec![Diagnostic::warning(
+            code.s1("x <= a;"),
+            "Signal 'x' is not assigned in all branches of a process without a clock edge which infers a latch",
+        )],
+    );
+}
+
+#[test]
+fn complete_and_clocked_processes_do_not_infer_latches() {
+    let mut builder = LibraryBuilder::new();
+    builder.code(
+        "libname",
+        "
+entity ent is
+  port (clk, sel : in bit; a : in bit; x, y : out bit);
+end entity;
+
+architecture a of ent is
+begin
+  process (sel, a)
+  begin
+    case sel is
+      when '0' => x <= a;
+      when '1' => x <= not a;
+    end case;
+  end process;
+
+  process (clk)
+  begin
+    if rising_edge(clk) then
+      if sel = '1' then
+        y <= a;
+      end if;
+    end if;
+  end process;
+end architecture;
+",
+    );
+
+    let diagnostics = builder.analyze();
+    check_no_diagnostics(&diagnostics);
+}
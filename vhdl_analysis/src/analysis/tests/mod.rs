@@ -6,22 +6,41 @@
 
 mod assignment_typecheck;
 mod association_formal;
+mod call_graph;
 mod circular_dependencies;
+mod completion;
+mod component_entity;
+mod configuration;
+mod constant_value;
 mod context_clause;
+mod dead_generate;
 mod deferred_constant;
+mod elaborate;
+mod fanout;
 mod hierarchy;
 mod homographs;
 mod implicit;
 mod incomplete_type;
 mod incremental_analysis;
+mod instantiation;
+mod latch;
+mod multiple_drivers;
 mod package_instance;
+mod parameter_mode;
+mod port_order;
 mod protected_type;
+mod pure_function;
+mod reset_value;
 mod resolves_design_units;
 mod resolves_names;
 mod resolves_type_mark;
 mod sensitivity_list;
+mod signal_type;
+mod staticness;
 mod subprogram_arguments;
+mod synthesis;
 mod typecheck_expression;
+mod unused;
 mod util;
 mod visibility;
 
@@ -39,6 +58,7 @@ use super::named_entity::*;
 use super::region::*;
 use super::DesignRoot;
 use super::EntRef;
+use super::Partial;
 
 pub(super) struct TestSetup<'a> {
     builder: RefCell<LibraryBuilder>,
@@ -62,6 +82,11 @@ impl<'a> TestSetup<'a> {
         }
     }
 
+    pub fn explain_overloads(mut self) -> Self {
+        self.root.set_explain_overloads(true);
+        self
+    }
+
     pub fn ctx(&'a self) -> AnalyzeContext<'a> {
         let ctx = AnalyzeContext::new(
             &self.root,
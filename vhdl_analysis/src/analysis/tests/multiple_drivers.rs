@@ -0,0 +1,251 @@
+//! This Source Code Form is subject to the terms of the Mozilla Public
+//! License, v. 2.0. If a copy of the MPL was not distributed with this file,
+//! You can obtain one at http://mozilla.org/MPL/2.0/.
+//!
+//! Copyright (c) 2023, Olof Kraigher olof.kraigher@gmail.com
+
+use super::*;
+
+#[test]
+fn unresolved_signal_driven_by_process_and_concurrent_assignment() {
+    let mut builder = LibraryBuilder::new();
+    let code = builder.code(
+        "libname",
+        "
+entity ent is
+end entity;
+
+architecture a of ent is
+  signal cnt : natural;
+  signal flag : boolean;
+begin
+  cnt <= 0;
+
+  process
+  begin
+    cnt <= 1;
+    cnt <= 2;
+    wait;
+  end process;
+
+  process
+    variable flag : boolean;
+  begin
+    flag := true;
+    wait;
+  end process;
+
+  blk : block
+  begin
+    flag <= false;
+  end block;
+
+  flag <= true;
+end architecture;
+",
+    );
+
+    let diagnostics = builder.analyze();
+    check_diagnostics(
+        diagnostics,
+        vec![
+            Diagnostic::error(
+                code.s1("cnt <= 1").s1("cnt"),
+                "Signal 'cnt' of an unresolved type has multiple drivers",
+            )
+            .related(code.s1("cnt <= 0").s1("cnt"), "Also driven here"),
+            Diagnostic::error(
+                code.s1("cnt <= 2").s1("cnt"),
+                "Signal 'cnt' of an unresolved type has multiple drivers",
+            )
+            .related(code.s1("cnt <= 0").s1("cnt"), "Also driven here"),
+            Diagnostic::error(
+                code.s1("flag <= true").s1("flag"),
+                "Signal 'flag' of an unresolved type has multiple drivers",
+            )
+            .related(code.s1("flag <= false").s1("flag"), "Also driven here"),
+        ],
+    );
+}
+
+#[test]
+fn different_parts_of_unresolved_signal_may_have_separate_drivers() {
+    let mut builder = LibraryBuilder::new();
+    builder.code(
+        "libname",
+        "
+entity ent is
+end entity;
+
+architecture a of ent is
+  type rec_t is record
+    field0, field1 : natural;
+  end record;
+
+  signal rec : rec_t;
+  signal vec : bit_vector(0 to 7);
+  signal idx : natural;
+begin
+  rec.field0 <= 0;
+  rec.field1 <= 1;
+
+  vec(0) <= '0';
+  vec(1 to 3) <= \"000\";
+  vec(7 downto 4) <= \"0000\";
+
+  process
+  begin
+    if idx = 0 then
+      vec(idx) <= '1';
+    end if;
+    wait;
+  end process;
+end architecture;
+",
+    );
+
+    let diagnostics = builder.analyze();
+    check_no_diagnostics(&diagnostics);
+}
+
+#[test]
+fn overlapping_parts_of_unresolved_signal_have_multiple_drivers() {
+    let mut builder = LibraryBuilder::new();
+    let code = builder.code(
+        "libname",
+        "
+entity ent is
+end entity;
+
+architecture a of ent is
+  signal vec : bit_vector(0 to 7);
+begin
+  vec(0 to 3) <= \"0000\";
+  vec(3) <= '1';
+end architecture;
+",
+    );
+
+    let diagnostics = builder.analyze();
+    check_diagnostics(
+        diagnostics,
+        vec![Diagnostic::error(
+            code.s1("vec(3) <= '1'").s1("vec(3)"),
+            "Signal 'vec' of an unresolved type has multiple drivers",
+        )
+        .related(code.s1("vec(0 to 3)"), "Also driven here")],
+    );
+}
+
+#[test]
+fn drivers_in_different_generate_alternatives_do_not_conflict() {
+    let mut builder = LibraryBuilder::new();
+    let code = builder.code(
+        "libname",
+        "
+entity ent is
+  generic (sel : natural);
+end entity;
+
+architecture a of ent is
+  signal cnt : natural;
+begin
+  gen_if : if sel = 0 generate
+    cnt <= 0;
+  elsif sel = 1 generate
+    cnt <= 1;
+  else generate
+    gen_case : case sel generate
+      when 2 =>
+        cnt <= 2;
+      when others =>
+        cnt <= 3;
+    end generate;
+  end generate;
+
+  gen_for : for i in 0 to 1 generate
+    cnt <= 4;
+  end generate;
+end architecture;
+",
+    );
+
+    let diagnostics = builder.analyze();
+    check_diagnostics(
+        diagnostics,
+        vec![Diagnostic::error(
+            code.s1("cnt <= 4").s1("cnt"),
+            "Signal 'cnt' of an unresolved type has multiple drivers",
+        )
+        .related(code.s1("cnt <= 0").s1("cnt"), "Also driven here")],
+    );
+}
+
+#[test]
+fn resolved_signals_may_have_multiple_drivers() {
+    let mut builder = LibraryBuilder::new();
+    builder.add_std_logic_1164();
+    builder.code(
+        "libname",
+        "
+library ieee;
+use ieee.std_logic_1164.all;
+
+entity ent is
+end entity;
+
+architecture a of ent is
+  type rec_t is record
+    bit0 : std_logic;
+  end record;
+
+  signal sl : std_logic;
+  signal slv : std_logic_vector(3 downto 0);
+  signal rec : rec_t;
+  signal sul : resolved std_ulogic;
+begin
+  sl <= '0';
+  sl <= 'Z';
+  slv <= (others => '0');
+  slv <= (others => 'Z');
+  rec.bit0 <= '0';
+  rec.bit0 <= 'Z';
+  sul <= '0';
+  sul <= 'Z';
+end architecture;
+",
+    );
+
+    let diagnostics = builder.analyze();
+    check_no_diagnostics(&diagnostics);
+}
+
+#[test]
+fn alias_drives_the_aliased_signal() {
+    let mut builder = LibraryBuilder::new();
+    let code = builder.code(
+        "libname",
+        "
+entity ent is
+end entity;
+
+architecture a of ent is
+  signal cnt : natural;
+  alias cnt_alias is cnt;
+begin
+  cnt <= 0;
+  cnt_alias <= 1;
+end architecture;
+",
+    );
+
+    let diagnostics = builder.analyze();
+    check_diagnostics(
+        diagnostics,
+        vec![Diagnostic::error(
+            code.s1("cnt_alias <= 1").s1("cnt_alias"),
+            "Signal 'cnt' of an unresolved type has multiple drivers",
+        )
+        .related(code.s1("cnt <= 0").s1("cnt"), "Also driven here")],
+    );
+}
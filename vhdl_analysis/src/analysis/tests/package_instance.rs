@@ -608,3 +608,71 @@ end package;
         vec![code.s("sub_t", 1).pos(), code.s("sub_t", 3).pos(),]
     );
 }
+
+#[test]
+fn expand_package_instance() {
+    let mut builder = LibraryBuilder::new();
+    let code = builder.code(
+        "libname",
+        "
+package gpkg is
+  generic (
+    type data_t;
+    width : natural;
+    function to_string(value : data_t) return string
+  );
+
+  type arr_t is array (0 to width - 1) of data_t;
+  subtype idx_t is natural range 0 to width;
+  constant size : natural := width * 2;
+  function image(value : arr_t) return string;
+end package;
+
+package body gpkg is
+  function image(value : arr_t) return string is
+  begin
+    return to_string(value(0));
+  end;
+end package body;
+
+package util is
+  function to_str(value : integer) return string;
+end package;
+
+package body util is
+  function to_str(value : integer) return string is
+  begin
+    return integer'image(value);
+  end;
+end package body;
+
+use work.util.to_str;
+package ipkg is new work.gpkg generic map (integer, width => 4 + 4, to_string => to_str);
+",
+    );
+
+    let (root, diagnostics) = builder.get_analyzed_root();
+    check_no_diagnostics(&diagnostics);
+
+    let instance = root
+        .search_reference(code.source(), code.s1("ipkg").start())
+        .unwrap();
+    assert_eq!(
+        root.expand_package_instance(instance).unwrap(),
+        "\
+-- Expanded from generic package work.gpkg
+package ipkg is
+  type arr_t is array (0 to (4 + 4) - 1) of integer;
+  subtype idx_t is natural range 0 to (4 + 4);
+  constant size : natural := (4 + 4) * 2;
+  function image(
+    constant value : in arr_t
+  ) return string;
+end package;"
+    );
+
+    let generic = root
+        .search_reference(code.source(), code.s1("gpkg").start())
+        .unwrap();
+    assert_eq!(root.expand_package_instance(generic), None);
+}
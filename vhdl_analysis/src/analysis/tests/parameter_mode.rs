@@ -0,0 +1,134 @@
+//! This Source Code Form is subject to the terms of the Mozilla Public
+//! License, v. 2.0. If a copy of the MPL was not distributed with this file,
+//! You can obtain one at http://mozilla.org/MPL/2.0/.
+//!
+//! Copyright (c) 2023, Olof Kraigher olof.kraigher@gmail.com
+
+use super::*;
+use crate::config::VHDLStandard;
+
+#[test]
+fn in_parameter_cannot_be_associated_with_out_formal() {
+    let mut builder = LibraryBuilder::new();
+    let code = builder.in_declarative_region(
+        "
+procedure set(variable value : out natural) is
+begin
+  value := 0;
+end procedure;
+
+procedure proc(variable arg : in natural; variable other : inout natural) is
+begin
+  set(arg);
+  set(value => other);
+end procedure;
+",
+    );
+
+    let diagnostics = builder.analyze();
+    check_diagnostics(
+        diagnostics,
+        vec![Diagnostic::error(
+            code.s1("set(arg)").s1("arg"),
+            "Cannot associate parameter 'arg' of mode in with formal 'value' of mode out",
+        )],
+    );
+}
+
+#[test]
+fn parameter_class_must_match_formal_class() {
+    let mut builder = LibraryBuilder::new();
+    let code = builder.in_declarative_region(
+        "
+procedure update(variable value : inout natural) is
+begin
+  value := value + 1;
+end procedure;
+
+procedure drive(signal value : out natural) is
+begin
+  value <= 0;
+end procedure;
+
+procedure proc(signal sig : inout natural; variable var : inout natural) is
+begin
+  update(sig);
+  drive(var);
+  update(var);
+  drive(sig);
+end procedure;
+",
+    );
+
+    let diagnostics = builder.analyze();
+    check_diagnostics(
+        diagnostics,
+        vec![
+            Diagnostic::error(
+                code.s1("update(sig)").s1("sig"),
+                "Cannot associate signal parameter 'sig' with variable formal 'value'",
+            ),
+            Diagnostic::error(
+                code.s1("drive(var)").s1("var"),
+                "Cannot associate variable parameter 'var' with signal formal 'value'",
+            ),
+        ],
+    );
+}
+
+#[test]
+fn out_parameter_cannot_be_read_before_vhdl2008() {
+    let mut builder = LibraryBuilder::new();
+    builder.set_standard(VHDLStandard::VHDL1993);
+    let code = builder.in_declarative_region(
+        "
+type arr_t is array (natural range <>) of natural;
+
+procedure clear(variable value : out arr_t) is
+begin
+  for i in value'range loop
+    value(i) := 0;
+  end loop;
+end procedure;
+
+procedure proc(variable arr : out arr_t; variable count : out natural) is
+begin
+  clear(arr);
+  count := arr'length;
+  count := count + arr(0);
+end procedure;
+",
+    );
+
+    let diagnostics = builder.analyze();
+    check_diagnostics(
+        diagnostics,
+        vec![
+            Diagnostic::error(
+                code.s1("count + arr(0)").s1("count"),
+                "Cannot read parameter 'count' of mode out before VHDL-2008",
+            ),
+            Diagnostic::error(
+                code.s1("arr(0)").s1("arr"),
+                "Cannot read parameter 'arr' of mode out before VHDL-2008",
+            ),
+        ],
+    );
+}
+
+#[test]
+fn out_parameter_can_be_read_in_vhdl2008() {
+    let mut builder = LibraryBuilder::new();
+    builder.in_declarative_region(
+        "
+procedure proc(variable count : out natural) is
+begin
+  count := 0;
+  count := count + 1;
+end procedure;
+",
+    );
+
+    let diagnostics = builder.analyze();
+    check_no_diagnostics(&diagnostics);
+}
@@ -0,0 +1,132 @@
+//! This Source Code Form is subject to the terms of the Mozilla Public
+//! License, v. 2.0. If a copy of the MPL was not distributed with this file,
+//! You can obtain one at http://mozilla.org/MPL/2.0/.
+//!
+//! Copyright (c) 2023, Olof Kraigher olof.kraigher@gmail.com
+
+use super::*;
+use crate::data::TextEdit;
+
+#[test]
+fn group_ports_by_mode_names_positional_associations() {
+    let mut builder = LibraryBuilder::new();
+    let ent = builder.code(
+        "libname",
+        "
+entity ent is
+  port (
+    valid : out bit;
+    clk : in bit;
+    data : out bit;
+    rst : in bit
+  );
+end entity;
+",
+    );
+    let top = builder.code(
+        "libname",
+        "
+entity top is
+end entity;
+
+architecture a of top is
+  signal clk, rst, valid, data : bit;
+begin
+  inst0: entity work.ent port map (valid, clk, data => data, rst => rst);
+  gen: if true generate
+    inst1: entity work.ent port map (valid => valid, clk => clk, data => data, rst => rst);
+  end generate;
+end architecture;
+",
+    );
+
+    let (root, diagnostics) = builder.get_analyzed_root();
+    check_no_diagnostics(&diagnostics);
+
+    let (_, entity) = root
+        .item_at_cursor(ent.source(), ent.sa("entity ", "ent").start())
+        .unwrap();
+
+    assert_eq!(
+        root.group_ports_by_mode(entity),
+        Some(vec![
+            TextEdit::replace(
+                ent.s1("(").pos().combine(&ent.s1(")")),
+                "\
+(
+    clk : in bit;
+    rst : in bit;
+    valid : out bit;
+    data : out bit
+  )"
+            ),
+            TextEdit::replace(
+                top.s1("(valid, clk").s1("valid").pos().pos_at_beginning(),
+                "valid => "
+            ),
+            TextEdit::replace(
+                top.s1("(valid, clk").s1("clk").pos().pos_at_beginning(),
+                "clk => "
+            ),
+        ])
+    );
+
+    // Already grouped
+    assert_eq!(root.reorder_ports(entity, &[0, 1, 2, 3]), None);
+    // Not a reordering of the ports
+    assert_eq!(root.reorder_ports(entity, &[0, 0, 1, 2]), None);
+}
+
+#[test]
+fn reordering_ports_keeps_comments_and_declarations_of_several_ports() {
+    let mut builder = LibraryBuilder::new();
+    let ent = builder.code(
+        "libname",
+        "
+entity ent is
+  port (
+    -- Handshake
+    valid : out bit; -- high when data is valid
+    clk, rst : in bit;
+    data : out bit
+  );
+end entity;
+",
+    );
+
+    let (root, diagnostics) = builder.get_analyzed_root();
+    check_no_diagnostics(&diagnostics);
+
+    let (_, entity) = root
+        .item_at_cursor(ent.source(), ent.sa("entity ", "ent").start())
+        .unwrap();
+
+    assert_eq!(
+        root.group_ports_by_mode(entity),
+        Some(vec![TextEdit::replace(
+            ent.s1("(").pos().combine(&ent.s1(")")),
+            "\
+(
+    clk, rst : in bit;
+    -- Handshake
+    valid : out bit; -- high when data is valid
+    data : out bit
+  )"
+        )])
+    );
+
+    assert_eq!(
+        root.reorder_ports(entity, &[1, 0, 3, 2]),
+        Some(vec![TextEdit::replace(
+            ent.s1("(").pos().combine(&ent.s1(")")),
+            "\
+(
+    clk : in bit;
+    -- Handshake
+    valid : out bit; -- high when data is valid
+    data : out bit;
+    rst : in bit
+  )"
+        )])
+    );
+}
@@ -31,8 +31,14 @@ end package body;
     check_diagnostics(
         diagnostics,
         vec![
-            Diagnostic::error(&code.s1("a1"), "Missing body for protected type 'a1'"),
-            Diagnostic::error(&code.s1("b1"), "Missing body for protected type 'b1'"),
+            Diagnostic::error(&code.s1("a1"), "Missing body for protected type 'a1'").related(
+                code.s1("type a1 is protected\nend protected;"),
+                "Declared here",
+            ),
+            Diagnostic::error(&code.s1("b1"), "Missing body for protected type 'b1'").related(
+                code.s1("type b1 is protected\nend protected;"),
+                "Declared here",
+            ),
         ],
     );
 }
@@ -67,7 +73,10 @@ end package body;
         vec![
             Diagnostic::error(&code.s1("a1"), "No declaration of protected type 'a1'"),
             Diagnostic::error(&code.s1("b1"), "No declaration of protected type 'b1'"),
-            Diagnostic::error(&code.s("b1", 2), "Missing body for protected type 'b1'"),
+            Diagnostic::error(&code.s("b1", 2), "Missing body for protected type 'b1'").related(
+                code.s1("type b1 is protected\nend protected;"),
+                "Declared here",
+            ),
         ],
     );
 }
@@ -0,0 +1,155 @@
+//! This Source Code Form is subject to the terms of the Mozilla Public
+//! License, v. 2.0. If a copy of the MPL was not distributed with this file,
+//! You can obtain one at http://mozilla.org/MPL/2.0/.
+//!
+//! Copyright (c) 2023, Olof Kraigher olof.kraigher@gmail.com
+
+use super::*;
+
+#[test]
+fn pure_function_cannot_reference_objects_declared_outside() {
+    let mut builder = LibraryBuilder::new();
+    let code = builder.code(
+        "libname",
+        "
+use std.textio.all;
+
+entity ent is
+end entity;
+
+architecture a of ent is
+  signal sig : natural;
+  shared variable shared_var : natural;
+  constant const : natural := 0;
+  alias sig_alias is sig;
+
+  function get return natural is
+    variable local : natural := const;
+  begin
+    if sig'event then
+      local := sig_alias;
+    end if;
+    local := local + boolean'pos(endfile(output));
+    return sig + shared_var + local;
+  end function;
+begin
+end architecture;
+",
+    );
+
+    let diagnostics = builder.analyze();
+    check_diagnostics(
+        diagnostics,
+        vec![
+            Diagnostic::error(
+                code.s1("sig'event").s1("sig"),
+                "Pure function 'get' cannot reference signal 'sig' declared outside of it",
+            ),
+            Diagnostic::error(
+                code.s1("local := sig_alias").s1("sig_alias"),
+                "Pure function 'get' cannot reference object alias 'sig_alias' declared outside of it",
+            ),
+            Diagnostic::error(
+                code.s1("output"),
+                "Pure function 'get' cannot reference file 'OUTPUT' declared outside of it",
+            ),
+            Diagnostic::error(
+                code.s1("return sig").s1("sig"),
+                "Pure function 'get' cannot reference signal 'sig' declared outside of it",
+            ),
+            Diagnostic::error(
+                code.s1("+ shared_var").s1("shared_var"),
+                "Pure function 'get' cannot reference shared variable 'shared_var' declared outside of it",
+            ),
+        ],
+    );
+}
+
+#[test]
+fn pure_function_cannot_call_impure_function() {
+    let mut builder = LibraryBuilder::new();
+    let code = builder.code(
+        "libname",
+        "
+package pkg is
+  impure function impure_fun return natural;
+  function pure_fun return natural;
+  function pure_time return delay_length;
+end package;
+
+package body pkg is
+  impure function impure_fun return natural is
+  begin
+    return 0;
+  end function;
+
+  function pure_fun return natural is
+  begin
+    return impure_fun;
+  end function;
+
+  function pure_time return delay_length is
+  begin
+    return now;
+  end function;
+end package body;
+",
+    );
+
+    let diagnostics = builder.analyze();
+    check_diagnostics(
+        diagnostics,
+        vec![
+            Diagnostic::error(
+                code.s1("return impure_fun").s1("impure_fun"),
+                "Pure function 'pure_fun' cannot call impure function 'impure_fun'",
+            ),
+            Diagnostic::error(
+                code.s1("return now").s1("now"),
+                "Pure function 'pure_time' cannot call impure function 'NOW'",
+            ),
+        ],
+    );
+}
+
+#[test]
+fn impure_functions_and_procedures_may_reference_objects_declared_outside() {
+    let mut builder = LibraryBuilder::new();
+    builder.code(
+        "libname",
+        "
+entity ent is
+end entity;
+
+architecture a of ent is
+  signal sig : natural;
+
+  impure function get return natural is
+  begin
+    return sig;
+  end function;
+
+  procedure proc(variable value : out natural) is
+  begin
+    value := sig + get;
+  end procedure;
+
+  function pure_fun(arg : natural) return natural is
+    variable local : natural;
+
+    procedure nested(variable value : inout natural) is
+    begin
+      value := value + local + arg;
+    end procedure;
+  begin
+    nested(value => local);
+    return local;
+  end function;
+begin
+end architecture;
+",
+    );
+
+    let diagnostics = builder.analyze();
+    check_no_diagnostics(&diagnostics);
+}
@@ -0,0 +1,139 @@
+//! This Source Code Form is subject to the terms of the Mozilla Public
+//! License, v. 2.0. If a copy of the MPL was not distributed with this file,
+//! You can obtain one at http://mozilla.org/MPL/2.0/.
+//!
+//! Copyright (c) 2023, Olof Kraigher olof.kraigher@gmail.com
+
+use super::*;
+
+#[test]
+fn asynchronous_reset_value_differs_from_initial_value() {
+    let mut builder = LibraryBuilder::new();
+    builder.add_std_logic_1164();
+    let code = builder.code(
+        "libname",
+        "
+library ieee;
+use ieee.std_logic_1164.all;
+
+entity ent is
+  port (clk, rst : in std_logic);
+end entity;
+
+architecture a of ent is
+  signal cnt : std_logic_vector(3 downto 0) := (others => '1');
+  signal valid : std_logic := '0';
+begin
+  process (clk, rst)
+  begin
+    if rst = '1' then
+      cnt <= (others => '0');
+      valid <= '0';
+    elsif rising_edge(clk) then
+      cnt <= cnt;
+      valid <= '1';
+    end if;
+  end process;
+end architecture;
+",
+    );
+
+    let diagnostics = builder.analyze();
+    check_diagnostics(
+        diagnostics,
+        vec![Diagnostic::warning(
+            code.s1("(others => '0')"),
+            "Reset value of signal 'cnt' differs from its initial value",
+        )
+        .related(code.s1("(others => '1')"), "Initial value declared here")],
+    );
+}
+
+#[test]
+fn synchronous_reset_value_differs_from_initial_value() {
+    let mut builder = LibraryBuilder::new();
+    builder.add_std_logic_1164();
+    let code = builder.code(
+        "libname",
+        "
+library ieee;
+use ieee.std_logic_1164.all;
+
+entity ent is
+  port (clk, rst : in std_logic);
+end entity;
+
+architecture a of ent is
+  type state_t is (idle, busy);
+  signal state : state_t := busy;
+  signal data : std_logic_vector(3 downto 0) := x\"0\";
+  signal flags : std_logic_vector(3 downto 0) := \"0001\";
+begin
+  process (clk)
+  begin
+    if clk'event and clk = '1' then
+      if rst = '1' then
+        state <= idle;
+        data <= (others => '0');
+        flags <= (others => '0');
+      else
+        state <= busy;
+      end if;
+    end if;
+  end process;
+end architecture;
+",
+    );
+
+    let diagnostics = builder.analyze();
+    check_diagnostics(
+        diagnostics,
+        vec![
+            Diagnostic::warning(
+                code.s1("state <= idle").s1("idle"),
+                "Reset value of signal 'state' differs from its initial value",
+            )
+            .related(code.s1(":= busy").s1("busy"), "Initial value declared here"),
+            Diagnostic::warning(
+                code.s("(others => '0')", 2),
+                "Reset value of signal 'flags' differs from its initial value",
+            )
+            .related(code.s1("\"0001\""), "Initial value declared here"),
+        ],
+    );
+}
+
+#[test]
+fn enable_is_not_a_reset() {
+    let mut builder = LibraryBuilder::new();
+    builder.add_std_logic_1164();
+    builder.code(
+        "libname",
+        "
+library ieee;
+use ieee.std_logic_1164.all;
+
+entity ent is
+  port (clk, en, d : in std_logic);
+end entity;
+
+architecture a of ent is
+  signal q : std_logic := '0';
+begin
+  process (clk)
+  begin
+    if rising_edge(clk) then
+      if en = '1' then
+        q <= d;
+      else
+        q <= '1';
+      end if;
+    end if;
+  end process;
+end architecture;
+",
+    );
+
+    let diagnostics = builder.analyze();
+    check_no_diagnostics(&diagnostics);
+}
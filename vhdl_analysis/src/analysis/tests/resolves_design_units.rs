@@ -21,6 +21,10 @@ end configuration;
 
 entity ent is
 end entity;
+
+architecture rtl of ent is
+begin
+end architecture;
 ",
     );
 
@@ -64,6 +68,10 @@ fn good_configurations() {
 entity ent is
 end entity;
 
+architecture rtl of ent is
+begin
+end architecture;
+
 configuration cfg_good1 of ent is
 for rtl
 end for;
@@ -122,6 +130,10 @@ fn search_reference_from_configuration_to_entity() {
 entity decl is
 end entity;
 
+architecture rtl of decl is
+begin
+end architecture;
+
 configuration cfg_good1 of decl is
 for rtl
 end for;
@@ -356,12 +368,19 @@ fn search_configuration_instance() {
 entity ent is
 end entity;
 
+architecture a of ent is
+begin
+end architecture;
+
 configuration decl of ent is
   for a
   end for;
 end configuration;
 
-architecture a of ent is
+entity top is
+end entity;
+
+architecture a of top is
 begin
   inst : configuration work.decl;
 end architecture;
@@ -539,3 +558,105 @@ end architecture;
         Some(&code.s1("empty").pos())
     );
 }
+
+#[test]
+fn find_library_references() {
+    let mut builder = LibraryBuilder::new();
+    builder.code(
+        "lib2",
+        "
+package pkg is
+    constant c : natural := 0;
+end package;
+
+use work.pkg;
+
+package user is
+    constant d : natural := work.pkg.c;
+end package;
+",
+    );
+
+    let code = builder.code(
+        "libname",
+        "
+library lib2;
+use lib2.pkg.all;
+
+entity ent is
+end entity;
+
+architecture a of ent is
+    constant e : natural := LIB2.pkg.c;
+begin
+end architecture;
+",
+    );
+
+    let (root, diagnostics) = builder.get_analyzed_root();
+    check_no_diagnostics(&diagnostics);
+
+    assert_eq_unordered(
+        &root
+            .find_library_references(&root.symbol_utf8("lib2"))
+            .unwrap(),
+        &[
+            code.s1("lib2").pos(),
+            code.s("lib2", 2).pos(),
+            code.s1("LIB2").pos(),
+        ],
+    );
+    assert!(root
+        .find_library_references(&root.symbol_utf8("missing"))
+        .is_none());
+}
+
+#[test]
+fn compile_order_puts_dependencies_first() {
+    let mut builder = LibraryBuilder::new();
+    let top = builder.code(
+        "libname",
+        "
+library lib2;
+use work.pkg.all;
+
+entity top is
+end entity;
+
+architecture a of top is
+    constant d : natural := c;
+begin
+    inst: entity lib2.sub;
+end architecture;
+",
+    );
+    let pkg = builder.code(
+        "libname",
+        "
+package pkg is
+    constant c : natural := 0;
+end package;
+",
+    );
+    let sub = builder.code(
+        "lib2",
+        "
+entity sub is
+end entity;
+",
+    );
+
+    let (root, diagnostics) = builder.get_analyzed_root();
+    check_no_diagnostics(&diagnostics);
+
+    let order = root.compile_order();
+    let position = |code: &Code| {
+        order
+            .iter()
+            .position(|(_, source)| source == code.source())
+            .unwrap()
+    };
+    assert!(position(&pkg) < position(&top));
+    assert!(position(&sub) < position(&top));
+    assert_eq!(order[position(&sub)].0, root.symbol_utf8("lib2"));
+}
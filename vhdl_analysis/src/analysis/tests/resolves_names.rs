@@ -5,6 +5,9 @@
 // Copyright (c) 2019, Olof Kraigher olof.kraigher@gmail.com
 
 use super::*;
+use crate::analysis::EntPositions;
+use crate::ast::search::ItemKind;
+use crate::data::TextEdit;
 use pretty_assertions::assert_eq;
 
 #[test]
@@ -325,6 +328,7 @@ package body pkg is
      constant c5 : natural := missing'val(0);
      constant c6 : boolean := boolean'val(missing);
   begin
+    return 0;
   end;
 
 end package body;
@@ -365,6 +369,7 @@ package body pkg is
      constant c5 : string := decl'simple_name;
      constant c6 : boolean := boolean'val(decl);
   begin
+    return 0;
   end;
 
 end package body;
@@ -517,7 +522,7 @@ package body pkg is
          proc2(i); -- Index is defined
          missing;
 
-         exit missing;
+         exit missing when i = 0;
          next missing;
        end loop;
        
@@ -572,7 +577,7 @@ package body pkg is
     decl := decl;
     decl := decl when decl = 0 else decl;
     with decl select
-      decl := decl when decl,
+      decl := decl when 0,
               decl when others;
 
     -- Procedure call
@@ -605,9 +610,9 @@ package body pkg is
 
     -- Case
     case decl is
-      when decl =>
+      when 0 =>
         proc(decl);
-      when decl to decl =>
+      when 1 to 2 =>
         proc(decl);
     end case;
 
@@ -631,9 +636,13 @@ end entity;
 
 architecture a of ent is
 begin
-  main : process(missing) is
+  waits : process is
   begin
     wait on missing until missing = 0 ns for missing;
+  end process;
+
+  main : process(missing) is
+  begin
     missing <= missing after missing;
     missing <= force missing;
     missing <= release;
@@ -657,14 +666,18 @@ end entity;
 architecture a of ent is
   signal decl : time;
 begin
-  main : process (decl) is
+  waits : process is
   begin
     wait on decl until decl = 0 ns for decl;
+  end process;
+
+  main : process (decl) is
+  begin
     decl <= decl after decl;
     decl <= force decl;
     decl <= release;
     with decl select
-       decl <= decl when decl,
+       decl <= decl when 0 ns,
                decl when others;
   end process;
 end architecture;
@@ -867,12 +880,17 @@ architecture a of ent is
   begin
   end;
 
-  signal decl : natural := 0;
+  function resolve(values : integer_vector) return integer is
+  begin
+    return values(values'left);
+  end;
+
+  signal decl : resolve natural := 0;
 begin
   decl <= decl;
   decl <= decl when decl = 0 else decl;
   with decl select
-     decl <= decl when decl,
+     decl <= decl when 0,
              decl when others;
   proc(decl);
   assert decl = 0 report decl'instance_name severity severity_level'val(decl);
@@ -881,6 +899,38 @@ end architecture;
     );
 }
 
+#[test]
+fn search_names_in_choices() {
+    check_search_reference(
+        "
+entity ent is
+end entity;
+
+architecture a of ent is
+  constant decl : natural := 0;
+  signal sig : natural;
+begin
+  with sig select
+     sig <= decl when decl,
+            decl when others;
+
+  main : process is
+    variable var : natural;
+  begin
+    case var is
+      when decl =>
+        var := decl;
+      when decl + 1 to decl + 2 =>
+        var := decl;
+      when others =>
+        null;
+    end case;
+  end process;
+end architecture;
+",
+    );
+}
+
 #[test]
 fn search_for_loop_index() {
     check_search_reference(
@@ -1132,6 +1182,7 @@ package body pkg is
 
   function subpgm(arg: sub_type2) return sub_type2 is
   begin
+    return 0;
   end;
 
   alias alias1 is subpgm[integer return integer];
@@ -1155,10 +1206,12 @@ end package;
 package body pkg is
   function subpgm(arg: natural) return natural is
   begin
+    return 0;
   end;
 
   function subpgm(arg: boolean) return boolean is
   begin
+    return false;
   end;
 
   alias alias1 is subpgm[boolean return boolean];
@@ -1880,7 +1933,9 @@ end architecture;
         vec![Diagnostic::error(
             code.sa("work.ent1(", "a3"),
             "No architecture 'a3' for entity 'libname.ent1'",
-        )],
+        )
+        .related(code.s("a1", 1), "Architecture 'a1' declared here")
+        .related(code.s("a2", 1), "Architecture 'a2' declared here")],
     );
 
     assert_eq_unordered(
@@ -1904,6 +1959,48 @@ end architecture;
     );
 }
 
+#[test]
+fn find_architecture_references_in_other_library() {
+    let mut builder = LibraryBuilder::new();
+    let lib_code = builder.code(
+        "lib",
+        "
+entity ent1 is
+end entity;
+
+architecture rtl of ent1 is
+begin
+end architecture;
+      ",
+    );
+    let code = builder.code(
+        "libname",
+        "
+library lib;
+
+entity ent2 is
+end entity;
+
+architecture a of ent2 is
+begin
+    inst : entity lib.ent1(rtl);
+end architecture;
+      ",
+    );
+
+    let (root, diagnostics) = builder.get_analyzed_root();
+    check_no_diagnostics(&diagnostics);
+
+    assert_eq!(
+        root.search_reference_pos(code.source(), code.s1("rtl").start()),
+        Some(lib_code.s1("rtl").pos())
+    );
+    assert_eq_unordered(
+        &root.find_all_references_pos(&lib_code.s1("rtl").pos()),
+        &[lib_code.s1("rtl").pos(), code.s1("rtl").pos()],
+    );
+}
+
 #[test]
 fn find_end_identifier_references_of_declarations() {
     for name in [
@@ -1935,6 +2032,7 @@ architecture a1 of ent1 is
 
     function fun1 return integer is
     begin
+      return 0;
     end function fun1;
 
     procedure proc1 is
@@ -1953,7 +2051,7 @@ package body pkg is
 end package body pkg;
 
 configuration cfg1 of ent1 is
-  for rtl(0)
+  for a1
   end for;
 end configuration cfg1;
 
@@ -2020,7 +2118,7 @@ begin
     end if if0;
 
     loop0: for i in 0 to 1 loop
-      next loop0;
+      next loop0 when i = 0;
       exit loop0;
     end loop loop0;
 
@@ -2033,3 +2131,615 @@ end architecture;
         );
     }
 }
+
+#[test]
+fn resolves_range_attribute_of_subprogram_parameter() {
+    let mut builder = LibraryBuilder::new();
+    builder.add_std_logic_1164();
+    let code = builder.code(
+        "libname",
+        "
+library ieee;
+use ieee.std_logic_1164.all;
+
+package pkg is
+end package;
+
+package body pkg is
+  procedure proc(data : std_logic_vector; other : std_logic_vector(data'range)) is
+    variable tmp : std_logic_vector(data'range);
+    variable rev : std_logic_vector(data'reverse_range);
+    variable idx : integer range data'range(1);
+    variable copy : data'subtype;
+  begin
+  end procedure;
+end package body;
+",
+    );
+
+    let (root, diagnostics) = builder.get_analyzed_root();
+    check_no_diagnostics(&diagnostics);
+
+    let decl_pos = code.sa("proc(", "data").pos();
+    for occurence in 2..=6 {
+        assert_eq!(
+            root.search_reference_pos(code.source(), code.s("data", occurence).start()),
+            Some(decl_pos.clone())
+        );
+    }
+}
+
+#[test]
+fn type_attributes_of_subprogram_parameter_in_expressions() {
+    let mut builder = LibraryBuilder::new();
+    let code = builder.code(
+        "libname",
+        "
+package pkg is
+end package;
+
+package body pkg is
+  function reverse(arg : integer_vector) return integer_vector is
+    variable result : arg'subtype;
+    variable elem : arg'element := arg'element'low;
+  begin
+    for i in arg'range loop
+      elem := arg'element(arg(i));
+      result(arg'high - i + arg'low) := elem;
+      report arg'element'image(elem);
+    end loop;
+    return result;
+  end function;
+end package body;
+",
+    );
+
+    let (root, diagnostics) = builder.get_analyzed_root();
+    check_no_diagnostics(&diagnostics);
+
+    let decl_pos = code.sa("reverse(", "arg").pos();
+    for occurence in 2..=9 {
+        assert_eq!(
+            root.search_reference_pos(code.source(), code.s("arg", occurence).start()),
+            Some(decl_pos.clone())
+        );
+    }
+}
+
+#[test]
+fn element_attribute_type_conversion_is_checked() {
+    let mut builder = LibraryBuilder::new();
+    let code = builder.in_declarative_region(
+        "
+signal vec : integer_vector(0 to 1);
+signal bad : vec'element := vec'element('a');
+signal good : vec'element := vec'element(1.0);
+",
+    );
+
+    let diagnostics = builder.analyze();
+    check_diagnostics(
+        diagnostics,
+        vec![Diagnostic::error(
+            code.s1("'a'"),
+            "type 'CHARACTER' cannot be converted to integer type 'INTEGER'",
+        )],
+    );
+}
+
+#[test]
+fn range_attribute_of_scalar_subprogram_parameter() {
+    let mut builder = LibraryBuilder::new();
+    builder.add_std_logic_1164();
+    let code = builder.code(
+        "libname",
+        "
+library ieee;
+use ieee.std_logic_1164.all;
+
+package pkg is
+end package;
+
+package body pkg is
+  procedure proc(n : natural) is
+    variable tmp : std_logic_vector(n'range);
+  begin
+  end procedure;
+end package body;
+",
+    );
+
+    let diagnostics = builder.analyze();
+    check_diagnostics(
+        diagnostics,
+        vec![Diagnostic::error(
+            code.s1("n'range").s1("n"),
+            "constant 'n' cannot be prefix of range attribute, array type or object is required",
+        )],
+    );
+}
+
+#[test]
+fn attributes_of_two_dimensional_array_with_dimension_argument() {
+    let mut builder = LibraryBuilder::new();
+    builder.code(
+        "libname",
+        "
+entity ent is
+end entity;
+
+architecture a of ent is
+  type mem_t is array (natural range 0 to 15, character range 'a' to 'h') of bit;
+  signal mem : mem_t;
+  constant rows : natural := mem'length(1);
+  constant cols : natural := mem'length(2);
+  constant last_col : character := mem'high(2);
+  constant up : boolean := mem'ascending(2);
+begin
+  process
+  begin
+    for col in mem'range(2) loop
+      mem(0, col) <= '0';
+    end loop;
+    for col in mem'reverse_range(1 + 1) loop
+      mem(rows - 1, col) <= '1';
+    end loop;
+    wait;
+  end process;
+end architecture;
+",
+    );
+
+    let diagnostics = builder.analyze();
+    check_no_diagnostics(&diagnostics);
+}
+
+#[test]
+fn dimension_argument_out_of_range() {
+    let mut builder = LibraryBuilder::new();
+    let code = builder.code(
+        "libname",
+        "
+entity ent is
+end entity;
+
+architecture a of ent is
+  type mem_t is array (natural range 0 to 15, character range 'a' to 'h') of bit;
+  signal mem : mem_t;
+  constant len : natural := mem'length(3);
+begin
+  process
+  begin
+    for col in mem'range(0) loop
+    end loop;
+    wait;
+  end process;
+end architecture;
+",
+    );
+
+    let diagnostics = builder.analyze();
+    check_diagnostics(
+        diagnostics,
+        vec![
+            Diagnostic::error(
+                code.s1("3"),
+                "Index 3 out of range for array with 2 dimensions, expected 1 to 2",
+            ),
+            Diagnostic::error(
+                code.s1("(0)").s1("0"),
+                "Index 0 out of range for array with 2 dimensions, expected 1 to 2",
+            ),
+        ],
+    );
+}
+
+#[test]
+fn entity_declarations_are_visible_in_architecture_of_other_file() {
+    let mut builder = LibraryBuilder::new();
+    builder.add_std_logic_1164();
+    let ent = builder.code(
+        "libname",
+        "
+library ieee;
+use ieee.std_logic_1164.all;
+
+entity ent is
+  generic (width : natural := 8);
+  port (data : in std_logic_vector(width-1 downto 0));
+  subtype word_t is std_logic_vector(width-1 downto 0);
+  alias word_alias_t is word_t;
+  constant c_zero : word_t := (others => '0');
+begin
+  assert data /= c_zero;
+end entity;
+",
+    );
+    let arch = builder.code(
+        "libname",
+        "
+architecture a of ent is
+  signal s : word_alias_t := c_zero;
+  signal t : word_t;
+begin
+  t <= s;
+  assert s = c_zero;
+end architecture;
+",
+    );
+
+    let (root, diagnostics) = builder.get_analyzed_root();
+    check_no_diagnostics(&diagnostics);
+
+    for name in ["word_t", "word_alias_t", "c_zero"] {
+        assert_eq!(
+            root.search_reference_pos(arch.source(), arch.s1(name).start()),
+            Some(ent.s1(name).pos()),
+            "{name}"
+        );
+    }
+
+    let c_zero = root
+        .search_reference(ent.source(), ent.s1("c_zero").start())
+        .unwrap();
+    assert_eq!(
+        root.find_all_references(c_zero),
+        vec![
+            arch.s("c_zero", 1).pos(),
+            arch.s("c_zero", 2).pos(),
+            ent.s("c_zero", 1).pos(),
+            ent.s("c_zero", 2).pos(),
+        ]
+    );
+}
+
+#[test]
+fn entity_declarations_cannot_be_redeclared_in_architecture() {
+    let mut builder = LibraryBuilder::new();
+    let ent = builder.code(
+        "libname",
+        "
+entity ent is
+  generic (width : natural := 8);
+  constant c_zero : natural := 0;
+end entity;
+",
+    );
+    let arch = builder.code(
+        "libname",
+        "
+architecture a of ent is
+  constant c_zero : natural := 1;
+  signal width : bit;
+begin
+end architecture;
+",
+    );
+
+    let diagnostics = builder.analyze();
+    check_diagnostics(
+        diagnostics,
+        duplicate_in_two_files(&ent, &arch, &["c_zero", "width"]),
+    );
+}
+
+#[test]
+fn find_all_references_until_deadline() {
+    let mut builder = LibraryBuilder::new();
+    let code = builder.code(
+        "libname",
+        "
+package pkg is
+  constant c0 : natural := 0;
+  constant c1 : natural := c0;
+end package;
+",
+    );
+
+    let (root, diagnostics) = builder.get_analyzed_root();
+    check_no_diagnostics(&diagnostics);
+    let ent = root
+        .search_reference(code.source(), code.s1("c0").start())
+        .unwrap();
+
+    let deadline = std::time::Instant::now() + std::time::Duration::from_secs(60);
+    assert_eq!(
+        root.find_all_references_until(ent, deadline),
+        Partial {
+            result: vec![code.s("c0", 1).pos(), code.s("c0", 2).pos()],
+            incomplete: false,
+        }
+    );
+
+    let passed = std::time::Instant::now();
+    assert_eq!(
+        root.find_all_references_until(ent, passed),
+        Partial {
+            result: vec![],
+            incomplete: true,
+        }
+    );
+}
+
+#[test]
+fn implicit_signals_of_signal_attributes() {
+    let mut builder = LibraryBuilder::new();
+    let code = builder.code(
+        "libname",
+        "
+entity ent is
+end entity;
+
+architecture a of ent is
+  signal req : bit;
+  signal changed : boolean;
+
+  procedure monitor(signal s : in bit) is
+  begin
+    wait on s'transaction;
+    wait until s'event;
+  end procedure;
+begin
+  process (req'transaction, req'stable(5 ns))
+  begin
+    changed <= req'delayed(1 ns)'event or req'quiet'stable;
+    req'delayed <= '1';
+  end process;
+
+  monitor(req);
+end architecture;
+",
+    );
+
+    let diagnostics = builder.analyze();
+    check_diagnostics(
+        diagnostics,
+        vec![
+            Diagnostic::error(
+                code.s1("wait on s'transaction").s1("s"),
+                "'transaction attribute is not allowed on formal signal parameter 's' of a subprogram",
+            ),
+            Diagnostic::error(
+                code.s1("req'delayed <=").s1("req'delayed"),
+                "implicit signal 'delayed may not be the target of an assignment",
+            ),
+        ],
+    );
+}
+
+#[test]
+fn item_at_cursor_has_the_kind_of_node_and_resolved_entity() {
+    let mut builder = LibraryBuilder::new();
+    let code = builder.in_declarative_region(
+        "
+signal sig : natural := missing;
+procedure proc is
+begin
+end procedure proc;
+constant c : natural := sig;
+",
+    );
+
+    let (root, diagnostics) = builder.get_analyzed_root();
+    assert_eq!(diagnostics.len(), 1);
+
+    let decl = code.sa("signal ", "sig");
+    let item = root.item_at(code.source(), decl.start()).unwrap();
+    assert_eq!(item.pos, decl.pos());
+    assert_eq!(item.kind, ItemKind::Declaration);
+    assert_eq!(item.ent.unwrap().decl_pos(), Some(&decl.pos()));
+
+    let item = root
+        .item_at(code.source(), code.sa("natural := ", "sig").start())
+        .unwrap();
+    assert_eq!(item.pos, code.sa("natural := ", "sig").pos());
+    assert_eq!(item.kind, ItemKind::Reference);
+    assert_eq!(item.ent.unwrap().decl_pos(), Some(&decl.pos()));
+
+    let item = root
+        .item_at(code.source(), code.sa("end procedure ", "proc").start())
+        .unwrap();
+    assert_eq!(item.kind, ItemKind::Reference);
+    assert_eq!(
+        item.ent.unwrap().decl_pos(),
+        Some(&code.sa("procedure ", "proc").pos())
+    );
+
+    let item = root
+        .item_at(code.source(), code.s1("missing").start())
+        .unwrap();
+    assert_eq!(item.pos, code.s1("missing").pos());
+    assert_eq!(item.kind, ItemKind::Reference);
+    assert!(item.ent.is_none());
+}
+
+#[test]
+fn items_in_source_are_resolved_and_ordered() {
+    let mut builder = LibraryBuilder::new();
+    let code = builder.in_declarative_region(
+        "
+signal sig : natural := missing;
+constant c : natural := sig;
+",
+    );
+
+    let (root, _) = builder.get_analyzed_root();
+    let items = root.items_in(code.source());
+    assert!(items
+        .windows(2)
+        .all(|pair| pair[0].pos.start() < pair[1].pos.start()));
+    assert!(items.iter().all(|item| item.ent.is_some()));
+    assert!(!items
+        .iter()
+        .any(|item| item.pos == code.s1("missing").pos()));
+
+    let decl = code.sa("signal ", "sig").pos();
+    let kinds: Vec<_> = items
+        .iter()
+        .filter(|item| item.ent.unwrap().decl_pos() == Some(&decl))
+        .map(|item| (item.pos.clone(), item.kind))
+        .collect();
+    assert_eq!(
+        kinds,
+        vec![
+            (decl.clone(), ItemKind::Declaration),
+            (code.sa("natural := ", "sig").pos(), ItemKind::Reference)
+        ]
+    );
+}
+
+#[test]
+fn positional_actuals_have_the_formals_of_calls_and_instances() {
+    let mut builder = LibraryBuilder::new();
+    let code = builder.code(
+        "libname",
+        "
+entity sub is
+  generic (width : natural);
+  port (clk : bit; data : bit);
+end entity;
+
+architecture a of sub is
+begin
+end architecture;
+
+entity top is
+end entity;
+
+architecture a of top is
+  signal clk, d : bit;
+  function f(x : natural; y : natural) return natural is
+  begin
+    return x;
+  end function;
+  constant c : natural := f(1, y => 2);
+begin
+  inst : entity work.sub generic map (8) port map (clk, data => d);
+end architecture;",
+    );
+
+    let (root, diagnostics) = builder.get_analyzed_root();
+    check_no_diagnostics(&diagnostics);
+
+    let formals: Vec<_> = root
+        .positional_formals(code.source())
+        .into_iter()
+        .map(|(actual, formal)| (actual, formal.decl_pos().cloned()))
+        .collect();
+    assert_eq!(
+        formals,
+        vec![
+            (code.sa("f(", "1").pos(), Some(code.s1("x :").s1("x").pos())),
+            (code.sa("map (", "8").pos(), Some(code.s1("width").pos())),
+            (
+                code.sa("port map (", "clk").pos(),
+                Some(code.s1("clk :").s1("clk").pos())
+            ),
+        ]
+    );
+}
+
+#[test]
+fn positional_associations_of_instance_at_cursor_become_named() {
+    let mut builder = LibraryBuilder::new();
+    let code = builder.code(
+        "libname",
+        "
+entity sub is
+  generic (width : natural);
+  port (clk : bit; data : bit);
+end entity;
+
+architecture a of sub is
+begin
+end architecture;
+
+entity top is
+end entity;
+
+architecture a of top is
+  signal clk, d : bit;
+begin
+  inst1 : entity work.sub generic map (8) port map (clk, d);
+  inst2 : entity work.sub
+    generic map (4)
+    port map (
+      clk,
+      data => d
+    );
+end architecture;",
+    );
+
+    let (root, diagnostics) = builder.get_analyzed_root();
+    check_no_diagnostics(&diagnostics);
+
+    let edits = root
+        .named_associations(code.source(), code.s1("generic map (4)").start())
+        .unwrap();
+    assert_eq!(
+        edits,
+        vec![
+            TextEdit::replace(code.sa("map (", "4").pos().pos_at_beginning(), "width => "),
+            TextEdit::replace(
+                code.s1("      clk,").s1("clk").pos().pos_at_beginning(),
+                "clk => "
+            ),
+        ]
+    );
+
+    assert_eq!(
+        root.named_associations(code.source(), code.s1("end architecture;").start()),
+        None
+    );
+}
+
+#[test]
+fn positions_of_entities_with_separate_declaration_and_body() {
+    let mut builder = LibraryBuilder::new();
+    let code = builder.code(
+        "libname",
+        "
+package pkg is
+  function myfun return natural;
+  constant c0 : natural := 0;
+end package;
+
+package body pkg is
+  function myfun return natural is
+  begin
+    return c0;
+  end function;
+end package body;
+",
+    );
+
+    let (root, diagnostics) = builder.get_analyzed_root();
+    check_no_diagnostics(&diagnostics);
+
+    let positions = |code: Code| {
+        let ent = root.search_reference(code.source(), code.start()).unwrap();
+        root.positions_of(ent).unwrap()
+    };
+
+    let package = EntPositions {
+        declaration: code.s("pkg", 1).pos(),
+        definition: code.s("pkg", 2).pos(),
+    };
+    assert_eq!(positions(code.s("pkg", 1)), package);
+    assert_eq!(positions(code.s("pkg", 2)), package);
+
+    let function = EntPositions {
+        declaration: code.s("myfun", 1).pos(),
+        definition: code.s("myfun", 2).pos(),
+    };
+    assert_eq!(positions(code.s("myfun", 1)), function);
+    assert_eq!(positions(code.s("myfun", 2)), function);
+
+    assert_eq!(
+        positions(code.s("c0", 2)),
+        EntPositions {
+            declaration: code.s("c0", 1).pos(),
+            definition: code.s("c0", 1).pos(),
+        }
+    );
+}
@@ -0,0 +1,391 @@
+//! This Source Code Form is subject to the terms of the Mozilla Public
+//! License, v. 2.0. If a copy of the MPL was not distributed with this file,
+//! You can obtain one at http://mozilla.org/MPL/2.0/.
+//!
+//! Copyright (c) 2023, Olof Kraigher olof.kraigher@gmail.com
+
+use super::*;
+use crate::config::VHDLStandard;
+use crate::data::TextEdit;
+
+#[test]
+fn must_be_object_name() {
+    let mut builder = LibraryBuilder::new();
+    let code = builder.code(
+        "libname",
+        "
+package pkg is
+end package;
+
+package body pkg is
+  procedure proc(signal good : in bit) is
+  begin
+    wait on good;
+    wait on proc;
+  end;
+end package body;
+
+        ",
+    );
+    let (_, diagnostics) = builder.get_analyzed_root();
+    check_diagnostics(
+        diagnostics,
+        vec![Diagnostic::error(
+            code.s1("wait on proc").s1("proc"),
+            "procedure proc[BIT] is not a signal and cannot be in a sensitivity list",
+        )],
+    )
+}
+
+#[test]
+fn must_be_signal_name() {
+    let mut builder = LibraryBuilder::new();
+    let code = builder.code(
+        "libname",
+        "
+package pkg is
+end package;
+
+package body pkg is
+  constant c0 : bit := '0';
+  procedure proc(signal good : in bit) is
+  begin
+    wait on good;
+    wait on c0;
+  end;
+end package body;
+
+        ",
+    );
+    let (_, diagnostics) = builder.get_analyzed_root();
+    check_diagnostics(
+        diagnostics,
+        vec![Diagnostic::error(
+            code.s1("wait on c0").s1("c0"),
+            "constant 'c0' is not a signal and cannot be in a sensitivity list",
+        )],
+    )
+}
+
+#[test]
+fn must_not_be_output() {
+    let mut builder = LibraryBuilder::new();
+    let code = builder.code(
+        "libname",
+        "
+package pkg is
+end package;
+
+package body pkg is
+  procedure proc(signal bad : out bit) is
+  begin
+    wait on bad;
+  end;
+end package body;
+
+        ",
+    );
+    let (_, diagnostics) = builder.get_analyzed_root();
+    check_diagnostics(
+        diagnostics,
+        vec![Diagnostic::error(
+            code.s1("wait on bad").s1("bad"),
+            "interface signal 'bad' of mode out cannot be in a sensitivity list",
+        )],
+    )
+}
+
+#[test]
+fn may_be_output_port() {
+    let mut builder = LibraryBuilder::new();
+    builder.code(
+        "libname",
+        "
+entity ent is
+  port (oport : out bit);
+end entity;
+
+architecture a of ent is
+begin
+  main: process (oport)
+  begin
+  end process main;
+end architecture;
+
+        ",
+    );
+    let (_, diagnostics) = builder.get_analyzed_root();
+    check_no_diagnostics(&diagnostics);
+}
+
+#[test]
+fn concurrent_procedure_call_without_signal_inputs_is_executed_once() {
+    let mut builder = LibraryBuilder::new();
+    let code = builder.code(
+        "libname",
+        "
+entity ent is
+end entity;
+
+architecture a of ent is
+  procedure check(value : natural) is
+  begin
+  end procedure;
+
+  constant c0 : natural := 0;
+begin
+  check(c0);
+end architecture;
+",
+    );
+    let (_, diagnostics) = builder.get_analyzed_root();
+    check_diagnostics(
+        diagnostics,
+        vec![Diagnostic::warning(
+            code.s1("check(c0)"),
+            "Concurrent call to procedure check[NATURAL] is only executed once since no signal is associated with a formal of mode in or inout",
+        )],
+    )
+}
+
+#[test]
+fn concurrent_procedure_call_is_sensitive_to_signal_inputs() {
+    let mut builder = LibraryBuilder::new();
+    builder.code(
+        "libname",
+        "
+entity ent is
+end entity;
+
+architecture a of ent is
+  procedure check(value : natural) is
+  begin
+  end procedure;
+
+  procedure check_signal(signal value : in natural) is
+  begin
+  end procedure;
+
+  procedure clock_gen(signal clk : out bit; period : time) is
+  begin
+    loop
+      clk <= '0', '1' after period / 2;
+      wait for period;
+    end loop;
+  end procedure;
+
+  signal sig : natural;
+  signal clk : bit;
+begin
+  check(sig + 1);
+  check_signal(sig);
+  check_signal(value => sig);
+  clock_gen(clk, 10 ns);
+end architecture;
+",
+    );
+    let (_, diagnostics) = builder.get_analyzed_root();
+    check_no_diagnostics(&diagnostics);
+}
+
+#[test]
+fn signal_read_in_process_must_be_in_sensitivity_list() {
+    let mut builder = LibraryBuilder::new();
+    let code = builder.code(
+        "libname",
+        "
+entity ent is
+end entity;
+
+architecture a of ent is
+signal a, b, c, unused : bit;
+begin
+  main: process (a, unused)
+  begin
+    c <= a and b;
+  end process;
+end architecture;
+        ",
+    );
+
+    let diagnostics = builder.analyze();
+    check_diagnostics(
+        diagnostics,
+        vec![
+            Diagnostic::warning(
+                code.s1("a and b").s1("b"),
+                "Signal 'b' is read within the process but is missing from the sensitivity list",
+            )
+            .unsafe_fix(
+                "Add 'b' to the sensitivity list",
+                vec![TextEdit::replace(
+                    code.s1("a, unused").pos().pos_at_end(),
+                    ", b",
+                )],
+            )
+            .unsafe_fix(
+                "Replace the sensitivity list with 'all'",
+                vec![TextEdit::replace(code.s1("a, unused"), "all")],
+            ),
+            Diagnostic::hint(
+                code.s1("(a, unused)").s1("unused"),
+                "Signal 'unused' is in the sensitivity list but is never read within the process",
+            ),
+        ],
+    );
+}
+
+#[test]
+fn sensitivity_list_is_not_replaced_by_all_before_vhdl_2008() {
+    let mut builder = LibraryBuilder::new();
+    builder.set_standard(VHDLStandard::VHDL1993);
+    let code = builder.code(
+        "libname",
+        "
+entity ent is
+end entity;
+
+architecture a of ent is
+signal a, b, c : bit;
+begin
+  main: process (a)
+  begin
+    c <= a and b;
+  end process;
+end architecture;
+        ",
+    );
+
+    check_diagnostics(
+        builder.analyze(),
+        vec![Diagnostic::warning(
+            code.s1("a and b").s1("b"),
+            "Signal 'b' is read within the process but is missing from the sensitivity list",
+        )
+        .unsafe_fix(
+            "Add 'b' to the sensitivity list",
+            vec![TextEdit::replace(
+                code.s1("(a)").s1("a").pos().pos_at_end(),
+                ", b",
+            )],
+        )],
+    );
+}
+
+#[test]
+fn attribute_prefixes_and_output_actuals_are_not_reads() {
+    let mut builder = LibraryBuilder::new();
+    builder.code(
+        "libname",
+        "
+entity ent is
+end entity;
+
+architecture a of ent is
+signal a, c : bit;
+signal arr : bit_vector(0 to 3);
+
+procedure get(signal value : out bit) is
+begin
+end procedure;
+begin
+  main: process (a)
+  begin
+    if arr'length > 2 and a'event then
+      get(c);
+    end if;
+  end process;
+end architecture;
+        ",
+    );
+
+    let diagnostics = builder.analyze();
+    check_no_diagnostics(&diagnostics);
+}
+
+#[test]
+fn clocked_process_only_needs_clock_and_asynchronous_reset() {
+    let mut builder = LibraryBuilder::new();
+    builder.code(
+        "libname",
+        "
+entity ent is
+end entity;
+
+architecture a of ent is
+signal clk, rst, d, q : bit;
+begin
+  main: process (clk, rst)
+  begin
+    if rst = '1' then
+      q <= '0';
+    elsif clk'event and clk = '1' then
+      q <= d;
+    end if;
+  end process;
+end architecture;
+        ",
+    );
+
+    let diagnostics = builder.analyze();
+    check_no_diagnostics(&diagnostics);
+}
+
+#[test]
+fn process_with_sensitivity_list_must_not_wait() {
+    let mut builder = LibraryBuilder::new();
+    let code = builder.code(
+        "libname",
+        "
+entity ent is
+end entity;
+
+architecture a of ent is
+  signal clk : bit;
+begin
+  main: process (clk)
+    procedure proc is
+    begin
+      wait for 1 ns;
+    end procedure;
+  begin
+    wait on clk;
+    if clk = '1' then
+      for i in 0 to 1 loop
+        wait until clk = '0';
+      end loop;
+    end if;
+  end process;
+
+  other: process (all)
+  begin
+    wait;
+  end process;
+
+  waits: process
+  begin
+    wait on clk;
+  end process;
+end architecture;
+        ",
+    );
+
+    let diagnostics = builder.analyze();
+    check_diagnostics(
+        diagnostics,
+        vec![
+            Diagnostic::error(
+                code.s1("wait on clk;"),
+                "Wait statement is not allowed in a process with a sensitivity list",
+            ),
+            Diagnostic::error(
+                code.s1("wait until clk = '0';"),
+                "Wait statement is not allowed in a process with a sensitivity list",
+            ),
+            Diagnostic::error(
+                code.s1("wait;"),
+                "Wait statement is not allowed in a process with a sensitivity list",
+            ),
+        ],
+    );
+}
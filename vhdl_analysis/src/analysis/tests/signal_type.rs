@@ -0,0 +1,196 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this file,
+// You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) 2023, Olof Kraigher olof.kraigher@gmail.com
+
+use super::*;
+use crate::config::VHDLStandard;
+
+#[test]
+fn signal_cannot_be_of_access_or_file_type() {
+    let mut builder = LibraryBuilder::new();
+    let code = builder.in_declarative_region(
+        "
+type ptr_t is access integer;
+type file_t is file of character;
+signal good : integer;
+signal bad1 : ptr_t;
+signal bad2 : file_t;
+        ",
+    );
+
+    let diagnostics = builder.analyze();
+    check_diagnostics(
+        diagnostics,
+        vec![
+            Diagnostic::error(
+                code.sa("bad1 : ", "ptr_t"),
+                "Signal 'bad1' cannot be of access type 'ptr_t'",
+            ),
+            Diagnostic::error(
+                code.sa("bad2 : ", "file_t"),
+                "Signal 'bad2' cannot be of file type 'file_t'",
+            ),
+        ],
+    );
+}
+
+#[test]
+fn signal_cannot_have_access_subelement() {
+    let mut builder = LibraryBuilder::new();
+    let code = builder.in_declarative_region(
+        "
+type ptr_t is access integer;
+subtype sub_ptr_t is ptr_t;
+type rec_t is record
+  value : integer;
+  ptr : sub_ptr_t;
+end record;
+type rec_arr_t is array (natural range <>) of rec_t;
+signal bad1 : rec_t;
+signal bad2 : rec_arr_t(0 to 1);
+        ",
+    );
+
+    let diagnostics = builder.analyze();
+    check_diagnostics(
+        diagnostics,
+        vec![
+            Diagnostic::error(
+                code.sa("bad1 : ", "rec_t"),
+                "Signal 'bad1' cannot have subelement 'bad1.ptr' of access type 'ptr_t'",
+            ),
+            Diagnostic::error(
+                code.sa("bad2 : ", "rec_arr_t"),
+                "Signal 'bad2' cannot have subelement 'bad2'element.ptr' of access type 'ptr_t'",
+            ),
+        ],
+    );
+}
+
+#[test]
+fn port_and_signal_parameter_cannot_be_of_protected_type() {
+    let mut builder = LibraryBuilder::new();
+    let code = builder.code(
+        "libname",
+        "
+package pkg is
+  type prot_t is protected
+  end protected;
+
+  procedure proc(signal bad1 : prot_t);
+end package;
+
+package body pkg is
+  type prot_t is protected body
+  end protected body;
+
+  procedure proc(signal bad1 : prot_t) is
+  begin
+  end procedure;
+end package body;
+
+use work.pkg.all;
+
+entity ent is
+  port (
+    bad2 : in prot_t
+  );
+end entity;
+        ",
+    );
+
+    let diagnostics = builder.analyze();
+    check_diagnostics(
+        diagnostics,
+        vec![
+            Diagnostic::error(
+                code.s("bad1 : prot_t", 1).s1("prot_t"),
+                "Signal parameter 'bad1' cannot be of protected type 'prot_t'",
+            ),
+            Diagnostic::error(
+                code.s("bad1 : prot_t", 2).s1("prot_t"),
+                "Signal parameter 'bad1' cannot be of protected type 'prot_t'",
+            ),
+            Diagnostic::error(
+                code.sa("bad2 : in ", "prot_t"),
+                "Port 'bad2' cannot be of protected type 'prot_t'",
+            ),
+        ],
+    );
+}
+
+#[test]
+fn composite_cannot_have_file_or_protected_elements() {
+    let mut builder = LibraryBuilder::new();
+    let code = builder.code(
+        "libname",
+        "
+package pkg is
+  type prot_t is protected
+  end protected;
+  type file_t is file of character;
+
+  type rec_t is record
+    f : file_t;
+    p : prot_t;
+  end record;
+  type file_arr_t is array (natural range <>) of file_t;
+  type prot_arr_t is array (natural range <>) of prot_t;
+end package;
+
+package body pkg is
+  type prot_t is protected body
+  end protected body;
+end package body;
+        ",
+    );
+
+    let diagnostics = builder.analyze();
+    check_diagnostics(
+        diagnostics,
+        vec![
+            Diagnostic::error(
+                code.sa("f : ", "file_t"),
+                "Record element 'f' cannot be of file type 'file_t'",
+            ),
+            Diagnostic::error(
+                code.sa("p : ", "prot_t"),
+                "Record element 'p' cannot be of protected type 'prot_t'",
+            ),
+            Diagnostic::error(
+                code.sa("natural range <>) of ", "file_t"),
+                "Array element cannot be of file type 'file_t'",
+            ),
+            Diagnostic::error(
+                code.sa("natural range <>) of ", "prot_t"),
+                "Array element cannot be of protected type 'prot_t'",
+            ),
+        ],
+    );
+}
+
+#[test]
+fn composite_may_have_protected_elements_in_vhdl2019() {
+    let mut builder = LibraryBuilder::new();
+    builder.set_standard(VHDLStandard::VHDL2019);
+    builder.code(
+        "libname",
+        "
+package pkg is
+  type prot_t is protected
+  end protected;
+  type prot_arr_t is array (natural range <>) of prot_t;
+end package;
+
+package body pkg is
+  type prot_t is protected body
+  end protected body;
+end package body;
+        ",
+    );
+
+    let diagnostics = builder.analyze();
+    check_no_diagnostics(&diagnostics);
+}
@@ -0,0 +1,204 @@
+//! This Source Code Form is subject to the terms of the Mozilla Public
+//! License, v. 2.0. If a copy of the MPL was not distributed with this file,
+//! You can obtain one at http://mozilla.org/MPL/2.0/.
+//!
+//! Copyright (c) 2023, Olof Kraigher olof.kraigher@gmail.com
+
+use super::*;
+
+#[test]
+fn case_choices_must_be_locally_static() {
+    let mut builder = LibraryBuilder::new();
+    let code = builder.code(
+        "libname",
+        "
+entity ent is
+  generic (g : natural);
+end entity;
+
+architecture a of ent is
+  type state_t is (idle, busy, done);
+  constant c_one : natural := 1;
+
+  function user_fun return natural is
+  begin
+    return 3;
+  end function;
+begin
+  main : process is
+    variable var : natural;
+    variable state : state_t;
+  begin
+    case var is
+      when 0 | c_one =>
+      when c_one + 1 to 2 * c_one + 1 =>
+      when var =>
+      when g =>
+      when user_fun =>
+      when 10 to var =>
+      when others =>
+    end case;
+
+    case state is
+      when idle =>
+      when state_t'succ(idle) | done =>
+    end case;
+  end process;
+end architecture;
+",
+    );
+
+    check_diagnostics(
+        builder.analyze(),
+        vec![
+            Diagnostic::error(
+                code.sa("when ", "var"),
+                "Choice must be locally static, variable 'var' is not",
+            ),
+            Diagnostic::error(
+                code.sa("when ", "g"),
+                "Choice must be locally static, constant 'g' is not",
+            ),
+            Diagnostic::error(
+                code.s("user_fun", 2),
+                "Choice must be locally static, function 'user_fun' is not",
+            ),
+            Diagnostic::error(
+                code.s1("10 to var"),
+                "Choice must be locally static, variable 'var' is not",
+            ),
+        ],
+    );
+}
+
+#[test]
+fn generate_statements_must_be_globally_static() {
+    let mut builder = LibraryBuilder::new();
+    let code = builder.code(
+        "libname",
+        "
+entity ent is
+  generic (g : natural);
+end entity;
+
+architecture a of ent is
+  signal sig : natural;
+begin
+  gen_if_good: if g = 0 generate
+  end generate;
+
+  gen_if_bad: if sig = 0 generate
+  end generate;
+
+  gen_for_good: for i in 0 to g - 1 generate
+    gen_nested: if i = 0 generate
+    end generate;
+  end generate;
+
+  gen_for_bad: for i in 0 to sig generate
+  end generate;
+
+  gen_case_good: case g generate
+    when 0 =>
+    when others =>
+  end generate;
+
+  gen_case_bad: case sig generate
+    when g =>
+    when others =>
+  end generate;
+end architecture;
+",
+    );
+
+    check_diagnostics(
+        builder.analyze(),
+        vec![
+            Diagnostic::error(
+                code.s1("sig = 0"),
+                "Condition of an if generate statement must be globally static, signal 'sig' is not",
+            ),
+            Diagnostic::error(
+                code.s1("0 to sig"),
+                "Range of a for generate statement must be globally static, signal 'sig' is not",
+            ),
+            Diagnostic::error(
+                code.s1("case sig").s1("sig"),
+                "Expression of a case generate statement must be globally static, signal 'sig' is not",
+            ),
+            Diagnostic::error(
+                code.sa("when ", "g"),
+                "Choice must be locally static, constant 'g' is not",
+            ),
+        ],
+    );
+}
+
+#[test]
+fn port_index_constraints_must_be_globally_static() {
+    let mut builder = LibraryBuilder::new();
+    let code = builder.code(
+        "libname",
+        "
+entity ent is
+  generic (width : natural);
+  port (
+    len : in natural;
+    good : in bit_vector(width - 1 downto 0);
+    bad : in bit_vector(len - 1 downto 0)
+  );
+end entity;
+",
+    );
+
+    check_diagnostics(
+        builder.analyze(),
+        vec![Diagnostic::error(
+            code.s1("len - 1 downto 0"),
+            "Index constraint of a port must be globally static, signal 'len' is not",
+        )],
+    );
+}
+
+#[test]
+fn type_definition_ranges_must_be_locally_static() {
+    let mut builder = LibraryBuilder::new();
+    let code = builder.code(
+        "libname",
+        "
+package pkg is
+  constant c_max : natural := 10;
+  constant c_deferred : natural;
+  type good_t is range 0 to c_max;
+  type bad_t is range 0 to c_deferred;
+  type bad_real_t is range 0.0 to real(c_deferred);
+  type bad_phys_t is range 0 to c_deferred
+    units
+      primary;
+    end units;
+end package;
+
+package body pkg is
+  constant c_deferred : natural := 5;
+end package body;
+",
+    );
+
+    check_diagnostics(
+        builder.analyze(),
+        vec![
+            Diagnostic::error(
+                code.s1("0 to c_deferred"),
+                "Range of a type definition must be locally static, deferred constant 'c_deferred' is not",
+            ),
+            Diagnostic::error(
+                code.s1("0.0 to real(c_deferred)"),
+                "Range of a type definition must be locally static, deferred constant 'c_deferred' is not",
+            ),
+            Diagnostic::error(
+                code.s("0 to c_deferred", 2),
+                "Range of a type definition must be locally static, deferred constant 'c_deferred' is not",
+            ),
+        ],
+    );
+}
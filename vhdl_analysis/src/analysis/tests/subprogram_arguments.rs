@@ -13,6 +13,7 @@ fn wrong_number_of_arguments() {
         "
 function subpgm(arg: natural) return natural
 is begin
+  return 0;
 end;
 
 signal good : natural := subpgm(0);
@@ -42,6 +43,7 @@ end entity;
 architecture a of ent is
     function subpgm(arg: natural) return natural
     is begin
+      return 0;
     end;
 
     procedure theproc(arg: natural)
@@ -66,6 +68,10 @@ end architecture;
                 code.s("subpgm", 1),
                 "function subpgm[NATURAL return NATURAL] is not a procedure",
             ),
+            Diagnostic::warning(
+                code.s1("theproc(0)"),
+                "Concurrent call to procedure theproc[NATURAL] is only executed once since no signal is associated with a formal of mode in or inout",
+            ),
             Diagnostic::error(
                 code.s("thesig", 2),
                 "signal 'thesig' of array type 'INTEGER_VECTOR' is not a procedure",
@@ -81,10 +87,12 @@ fn resolve_overloaded_subprogram_by_return_type() {
         "
 function subpgm(arg: natural) return character
 is begin
+  return 'a';
 end;
 
 function subpgm(arg: natural) return natural
 is begin
+  return 0;
 end;
 
 
@@ -143,10 +151,12 @@ fn resolve_overloaded_subprogram_by_argument() {
         "
 function subpgm(arg: character) return natural
 is begin
+  return 0;
 end;
 
 function subpgm(arg: natural) return natural
 is begin
+  return 0;
 end;
 
 
@@ -174,6 +184,7 @@ fn subprogram_argument_not_associated() {
         "
 function subpgm(arg1: natural; arg2: character) return natural
 is begin
+  return 0;
 end;
 
 signal bad : natural := subpgm(0);
@@ -202,6 +213,7 @@ fn subprogram_extra_argument_not_associated() {
         "
 function subpgm(arg1: natural) return natural
 is begin
+  return 0;
 end;
 
 signal bad : natural := subpgm(1111, 2222);
@@ -0,0 +1,196 @@
+//! This Source Code Form is subject to the terms of the Mozilla Public
+//! License, v. 2.0. If a copy of the MPL was not distributed with this file,
+//! You can obtain one at http://mozilla.org/MPL/2.0/.
+//!
+//! Copyright (c) 2023, Olof Kraigher olof.kraigher@gmail.com
+
+use super::*;
+use fnv::FnvHashSet;
+
+fn analyze_synthesis(builder: &LibraryBuilder, libraries: &[&str]) -> Vec<Diagnostic> {
+    let (mut root, _) = builder.get_analyzed_root();
+    let libraries: FnvHashSet<_> = libraries
+        .iter()
+        .map(|name| root.symbol_utf8(name))
+        .collect();
+    root.set_synthesis_libraries(libraries);
+    let mut diagnostics = Vec::new();
+    root.analyze(&mut diagnostics);
+    diagnostics
+}
+
+#[test]
+fn constructs_that_are_not_synthesizable() {
+    let mut builder = LibraryBuilder::new();
+    let code = builder.code(
+        "rtl",
+        "
+package pkg is
+  type ptr_t is access integer;
+  type int_file_t is file of integer;
+  shared variable counter : integer;
+end package;
+
+use work.pkg.all;
+
+entity ent is
+  port (clk : in bit; din : in natural; dout : out natural);
+end entity;
+
+architecture a of ent is
+  file log_file : int_file_t;
+begin
+  process
+  begin
+    wait until clk = '1' for 10 ns;
+    dout <= din / 3 + din / 4 + din mod 6;
+  end process;
+end architecture;
+",
+    );
+
+    check_diagnostics(
+        analyze_synthesis(&builder, &["rtl"]),
+        vec![
+            Diagnostic::warning(code.s1("ptr_t"), "Access type 'ptr_t' is not synthesizable")
+                .with_code("synthesis-access-type"),
+            Diagnostic::warning(
+                code.s1("int_file_t"),
+                "File type 'int_file_t' is not synthesizable",
+            )
+            .with_code("synthesis-file"),
+            Diagnostic::warning(
+                code.s1("counter"),
+                "Shared variable 'counter' is not synthesizable",
+            )
+            .with_code("synthesis-shared-variable"),
+            Diagnostic::warning(code.s1("log_file"), "File 'log_file' is not synthesizable")
+                .with_code("synthesis-file"),
+            Diagnostic::warning(
+                code.s1("wait until clk = '1' for 10 ns;"),
+                "Wait statement with a timeout is not synthesizable",
+            )
+            .with_code("synthesis-wait-for"),
+            Diagnostic::warning(
+                code.s1("din / 3"),
+                "Operator '/' by 3 which is not a power of two is not synthesizable",
+            )
+            .with_code("synthesis-division"),
+            Diagnostic::warning(
+                code.s1("din mod 6"),
+                "Operator 'mod' by 6 which is not a power of two is not synthesizable",
+            )
+            .with_code("synthesis-division"),
+        ],
+    );
+
+    // Only libraries configured for synthesis are checked
+    check_no_diagnostics(&analyze_synthesis(&builder, &[]));
+}
+
+#[test]
+fn registers_relying_on_initial_value() {
+    let mut builder = LibraryBuilder::new();
+    let code = builder.code(
+        "rtl",
+        "
+entity ent is
+  port (clk : in bit; rst : in bit; dout : out bit_vector(0 to 2));
+end entity;
+
+architecture a of ent is
+  signal with_reset : bit := '0';
+  signal without_reset : bit := '0';
+  signal combinational : bit := '0';
+begin
+  process (clk, rst)
+  begin
+    if rst = '1' then
+      with_reset <= '0';
+    elsif rising_edge(clk) then
+      with_reset <= not with_reset;
+      without_reset <= not without_reset;
+    end if;
+  end process;
+
+  combinational <= with_reset;
+  dout <= (with_reset, without_reset, combinational);
+end architecture;
+",
+    );
+
+    check_diagnostics(
+        analyze_synthesis(&builder, &["rtl"]),
+        vec![Diagnostic::warning(
+            code.s("'0'", 2),
+            "Register 'without_reset' relies on its initial value since it is never reset",
+        )
+        .related(
+            code.s("without_reset", 2),
+            "Assigned in a clocked process here",
+        )
+        .with_code("synthesis-initial-value")],
+    );
+}
+
+#[test]
+fn std_ulogic_comparison_with_metalogical_value() {
+    let mut builder = LibraryBuilder::new();
+    builder.add_std_logic_1164();
+    let code = builder.code(
+        "rtl",
+        "
+library ieee;
+use ieee.std_logic_1164.all;
+
+entity ent is
+end entity;
+
+architecture a of ent is
+    signal sl, sl2 : std_logic;
+    signal ch : character;
+begin
+    main : process
+        variable v : boolean;
+    begin
+        if sl = 'X' then
+        elsif sl2 = '1' and not (sl /= '-') then
+        end if;
+
+        if ch = 'X' then
+        end if;
+
+        v := true when sl = 'U' else false;
+        assert sl /= 'X';
+        wait;
+    end process;
+
+    assert sl2 /= 'U';
+end architecture;
+",
+    );
+
+    check_diagnostics(
+        analyze_synthesis(&builder, &["rtl"]),
+        vec![
+            Diagnostic::warning(
+                code.s1("sl = 'X'"),
+                "Comparison with metalogical value 'X' is always false in synthesis",
+            )
+            .with_code("synthesis-metalogical-comparison"),
+            Diagnostic::warning(
+                code.s1("(sl /= '-')"),
+                "Comparison with metalogical value '-' is always true in synthesis",
+            )
+            .with_code("synthesis-metalogical-comparison"),
+            Diagnostic::warning(
+                code.s1("sl = 'U'"),
+                "Comparison with metalogical value 'U' is always false in synthesis",
+            )
+            .with_code("synthesis-metalogical-comparison"),
+        ],
+    );
+
+    // Test benches and libraries such as ieee are not checked
+    check_no_diagnostics(&analyze_synthesis(&builder, &[]));
+}
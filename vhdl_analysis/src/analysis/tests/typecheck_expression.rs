@@ -5,6 +5,7 @@
 // Copyright (c) 2019, Olof Kraigher olof.kraigher@gmail.com
 
 use super::*;
+use crate::config::VHDLStandard;
 
 #[test]
 fn test_integer_literal_expression_typecheck() {
@@ -481,7 +482,6 @@ constant good : natural := thefun(0)(0);
     check_no_diagnostics(&diagnostics);
 }
 
-#[ignore = "Does not work yet"]
 #[test]
 fn function_result_can_be_indexed_no_arg() {
     let mut builder = LibraryBuilder::new();
@@ -500,6 +500,54 @@ constant good : natural := thefun(0);
     check_no_diagnostics(&diagnostics);
 }
 
+#[test]
+fn function_result_can_be_sliced_and_selected_no_arg() {
+    let mut builder = LibraryBuilder::new();
+    let code = builder.in_declarative_region(
+        "
+type rec_t is record
+  field : integer_vector(0 to 1);
+end record;
+
+function thefun return integer_vector;
+function thefun return integer_vector is
+begin
+   return (0, 1);
+end;
+
+function getrec return rec_t is
+begin
+   return (field => (0, 1));
+end;
+
+constant good1 : integer_vector := thefun(0 to 0);
+constant good2 : natural := getrec.field(1);
+constant bad : natural := thefun(0)(0);
+        ",
+    );
+
+    let (root, diagnostics) = builder.get_analyzed_root();
+    check_diagnostics(
+        diagnostics,
+        vec![Diagnostic::error(
+            code.s1("thefun(0)"),
+            "Expression of integer type 'INTEGER' cannot be indexed",
+        )],
+    );
+    assert_eq!(
+        root.search_reference_pos(code.source(), code.s1("thefun(0 to 0)").start()),
+        Some(code.s("thefun", 2).pos())
+    );
+    assert_eq!(
+        root.search_reference_pos(code.source(), code.s1("getrec.field").start()),
+        Some(code.s1("getrec").pos())
+    );
+    assert_eq!(
+        root.search_reference_pos(code.source(), code.s1("thefun(0)").start()),
+        Some(code.s("thefun", 2).pos())
+    );
+}
+
 #[test]
 fn test_type_conversion() {
     let mut builder = LibraryBuilder::new();
@@ -1160,8 +1208,42 @@ constant bad2 : character := - 'a';
                 "integer type 'INTEGER' does not match type 'CHARACTER'",
             ),
             Diagnostic::error(
-                code.s1("character := - 'a'").s1("-"),
-                "Found no match for operator \"-\"",
+                code.s1("character := - 'a'").s1("'a'"),
+                "expression with type 'CHARACTER' is not a valid operand of operator \"-\"",
+            ),
+        ],
+    );
+}
+
+#[test]
+fn operator_mismatch_is_reported_at_operand() {
+    let mut builder = LibraryBuilder::new();
+    let code = builder.in_declarative_region(
+        "
+type rec_t is record
+  field : natural;
+end record;
+
+function \"*\"(left, right : rec_t) return rec_t;
+
+constant i0 : integer := 0;
+constant r0 : rec_t := (field => 0);
+constant bad1 : integer := i0 + (i0 + true);
+constant bad2 : rec_t := r0 * 'c';
+        ",
+    );
+
+    let diagnostics = builder.analyze();
+    check_diagnostics(
+        diagnostics,
+        vec![
+            Diagnostic::error(
+                code.s1("i0 + true").s1("true"),
+                "'true' does not match integer type 'INTEGER'",
+            ),
+            Diagnostic::error(
+                code.s1("'c'"),
+                "character literal does not match record type 'rec_t'",
             ),
         ],
     );
@@ -1397,6 +1479,123 @@ end;
     );
 }
 
+#[test]
+fn function_can_reach_end_without_return() {
+    let mut builder = LibraryBuilder::new();
+    let code = builder.in_declarative_region(
+        "
+function good1(arg : boolean) return integer is
+begin
+  if arg then
+    return 1;
+  else
+    return 0;
+  end if;
+end;
+
+function good2(arg : boolean) return integer is
+begin
+  loop
+    if arg then
+      return 1;
+    end if;
+  end loop;
+end;
+
+function good3(arg : boolean) return integer is
+begin
+  case arg is
+    when true => return 1;
+    when false => return 0;
+  end case;
+end;
+
+function bad1(arg : boolean) return integer is
+begin
+  if arg then
+    return 1;
+  end if;
+end;
+
+function bad2(arg : boolean) return integer is
+begin
+  outer: loop
+    loop
+      exit outer when arg;
+    end loop;
+  end loop;
+end;
+
+function bad3(arg : boolean) return integer is
+begin
+  for i in 0 to 1 loop
+    return i;
+  end loop;
+end;
+",
+    );
+
+    let diagnostics = builder.analyze();
+    check_diagnostics(
+        diagnostics,
+        vec![
+            Diagnostic::warning(
+                code.s1("bad1"),
+                "Function 'bad1' can reach the end without returning a value",
+            ),
+            Diagnostic::warning(
+                code.s1("bad2"),
+                "Function 'bad2' can reach the end without returning a value",
+            ),
+            Diagnostic::warning(
+                code.s1("bad3"),
+                "Function 'bad3' can reach the end without returning a value",
+            ),
+        ],
+    );
+}
+
+#[test]
+fn statements_after_transfer_of_control_are_unreachable() {
+    let mut builder = LibraryBuilder::new();
+    let code = builder.in_declarative_region(
+        "
+procedure proc(arg : boolean) is
+  variable count : natural := 0;
+begin
+  for i in 0 to 1 loop
+    next when arg;
+    count := count + 1;
+    exit;
+    count := count + 2;
+  end loop;
+
+  if arg then
+    return;
+  else
+    return;
+  end if;
+  count := count + 3;
+end;
+",
+    );
+
+    let diagnostics = builder.analyze();
+    check_diagnostics(
+        diagnostics,
+        vec![
+            Diagnostic::warning(code.s1("count := count + 2;"), "Unreachable statement").related(
+                code.s1("exit;"),
+                "Execution does not continue after this statement",
+            ),
+            Diagnostic::warning(code.s1("count := count + 3;"), "Unreachable statement").related(
+                code.s1("if arg then\n    return;\n  else\n    return;\n  end if;"),
+                "Execution does not continue after this statement",
+            ),
+        ],
+    );
+}
+
 #[test]
 fn typecheck_report_statement() {
     let mut builder = LibraryBuilder::new();
@@ -1565,6 +1764,65 @@ end;
     );
 }
 
+#[test]
+fn ambiguous_expression_with_single_qq_conversion() {
+    let mut builder = LibraryBuilder::new();
+    let code = builder.in_declarative_region(
+        "
+type typ1_t is (alpha, beta);
+type typ2_t is (alpha, beta);
+
+function \"??\"(val : typ2_t) return boolean is
+begin
+  return true;
+end function;
+
+procedure wrapper is
+begin
+   assert alpha;
+end;
+",
+    );
+
+    let (root, diagnostics) = builder.get_analyzed_root();
+    check_no_diagnostics(&diagnostics);
+
+    let decl = root
+        .search_reference(code.source(), code.s1("assert alpha").s1("alpha").start())
+        .unwrap();
+
+    assert_eq!(
+        decl.decl_pos().unwrap(),
+        &code.s1("typ2_t is (alpha").s1("alpha").pos(),
+    );
+}
+
+#[test]
+fn implicit_qq_conversion_requires_vhdl_2008() {
+    let mut builder = LibraryBuilder::new();
+    builder.set_standard(VHDLStandard::VHDL1993);
+    let code = builder.in_declarative_region(
+        "
+signal sig : bit;
+
+procedure wrapper is
+begin
+   assert sig;
+   assert sig = '1';
+end;
+",
+    );
+
+    let diagnostics = builder.analyze();
+    check_diagnostics(
+        diagnostics,
+        vec![Diagnostic::error(
+            code.s1("assert sig;").s1("sig"),
+            "type 'BIT' cannot be implictly converted to type 'BOOLEAN'. Implicit condition conversion requires VHDL-2008.",
+        )],
+    );
+}
+
 #[test]
 fn ambiguous_qq_conversion_no_candidates() {
     let mut builder = LibraryBuilder::new();
@@ -1865,3 +2123,330 @@ end package;
     let diagnostics = builder.analyze();
     check_no_diagnostics(&diagnostics);
 }
+
+#[test]
+fn matching_case_statement() {
+    let mut builder = LibraryBuilder::new();
+    builder.add_std_logic_1164();
+    let code = builder.code(
+        "libname",
+        "
+library ieee;
+use ieee.std_logic_1164.all;
+
+entity ent is
+end entity;
+
+architecture a of ent is
+    signal vec : std_logic_vector(1 downto 0);
+    signal sl : std_logic;
+    signal bv : bit_vector(1 downto 0);
+    signal int : integer;
+begin
+    main : process
+    begin
+        case? vec is
+            when \"1-\" => null;
+            when \"0L\" => null;
+            when \"-1\" => null;
+            when others => null;
+        end case?;
+
+        case? sl is
+            when '1' => null;
+            when 'H' => null;
+            when others => null;
+        end case?;
+
+        case? bv is
+            when \"10\" => null;
+            when others => null;
+        end case?;
+
+        case? int is
+            when others => null;
+        end case?;
+
+        case? std_logic_vector'(\"0-\") is
+            when others => null;
+        end case?;
+        wait;
+    end process;
+end architecture;
+",
+    );
+
+    let diagnostics = builder.analyze();
+    check_diagnostics(
+        diagnostics,
+        vec![
+            Diagnostic::error(
+                code.s1("\"-1\""),
+                "Choice overlaps with a previous choice of the matching case statement",
+            )
+            .related(code.s1("\"1-\""), "Previous choice"),
+            Diagnostic::error(
+                code.s1("'H'"),
+                "Choice overlaps with a previous choice of the matching case statement",
+            )
+            .related(code.s1("when '1'").s1("'1'"), "Previous choice"),
+            Diagnostic::error(
+                code.s1("std_logic_vector'(\"0-\")"),
+                "Expression of a matching case statement may not contain '-'",
+            ),
+            Diagnostic::error(
+                code.s1("case? int").s1("int"),
+                "Expression of a matching case statement must be of type BIT, STD_ULOGIC or a one-dimensional array of these, got integer type 'INTEGER'",
+            ),
+        ],
+    );
+}
+
+#[test]
+fn case_statement_choices() {
+    let mut builder = LibraryBuilder::new();
+    let code = builder.code(
+        "libname",
+        "
+entity ent is
+end entity;
+
+architecture a of ent is
+    type state_t is (idle, load, run, stop, flush);
+    subtype active_t is state_t range load to stop;
+    signal state : state_t;
+    signal active : active_t;
+    signal int : integer;
+    signal ch : character;
+    signal busy : boolean;
+begin
+    main : process
+    begin
+        case state is
+            when idle => null;
+            when load to run => null;
+            when run => null;
+            when idle => null;
+        end case;
+
+        case int is
+            when 0 to 7 => null;
+            when 8 | 9 => null;
+            when 5 to 10 => null;
+            when others => null;
+        end case;
+
+        case active is
+            when load => null;
+        end case;
+
+        case state is
+            when others => null;
+        end case;
+
+        case ch is
+            when 'a' => null;
+        end case;
+        wait;
+    end process;
+
+    with state select
+        busy <= false when idle | flush,
+                true when load to stop;
+end architecture;
+",
+    );
+
+    let diagnostics = builder.analyze();
+    check_diagnostics(
+        diagnostics,
+        vec![
+            Diagnostic::error(
+                code.s1("when run").s1("run"),
+                "Choice overlaps with a previous choice",
+            )
+            .related(code.s1("load to run"), "Previous choice"),
+            Diagnostic::error(code.s("when idle", 2).s1("idle"), "Duplicate choice idle")
+                .related(code.s1("when idle").s1("idle"), "Previous choice"),
+            Diagnostic::error(
+                code.s1("case state").s1("state"),
+                "Missing choices for type 'state_t': stop, flush",
+            ),
+            Diagnostic::error(
+                code.s1("5 to 10"),
+                "Choice overlaps with a previous choice",
+            )
+            .related(code.s1("0 to 7"), "Previous choice"),
+            Diagnostic::error(
+                code.s1("case ch").s1("ch"),
+                "Missing choices for type 'CHARACTER': NUL, SOH, STX, ETX, EOT, ENQ, ACK, BEL, BS, HT and 245 more",
+            ),
+        ],
+    );
+}
+
+#[test]
+fn std_ulogic_comparison_with_character_outside_enumeration() {
+    let mut builder = LibraryBuilder::new();
+    builder.add_std_logic_1164();
+    let code = builder.code(
+        "libname",
+        "
+library ieee;
+use ieee.std_logic_1164.all;
+
+entity ent is
+end entity;
+
+architecture a of ent is
+    signal sl : std_logic;
+begin
+    main : process
+    begin
+        if sl = 'a' then
+        end if;
+        if 'x' /= sl then
+        end if;
+        wait;
+    end process;
+end architecture;
+",
+    );
+
+    let diagnostics = builder.analyze();
+    check_diagnostics(
+        diagnostics,
+        vec![
+            Diagnostic::error(
+                code.s1("'a'"),
+                "Character literal 'a' is not a literal of subtype 'STD_LOGIC'",
+            ),
+            Diagnostic::error(
+                code.s1("'x'"),
+                "Character literal 'x' is not a literal of subtype 'STD_LOGIC'",
+            ),
+        ],
+    );
+}
+
+#[test]
+fn record_aggregate_with_multiple_choices() {
+    let mut builder = LibraryBuilder::new();
+    let code = builder.in_declarative_region(
+        "
+type rec_t is record
+    f1 : character;
+    f2 : integer;
+    f3 : integer;
+end record;
+
+constant good : rec_t := (f2 | f3 => 0, f1 => 'a');
+constant bad1 : rec_t := (f2 | f3 => 'b', f1 => 'a');
+constant bad2 : rec_t := (f1 | f2 => 0, f3 => 0);
+constant bad3 : rec_t := (f1 | f4 => 'a', f2 | f3 => 0);
+        ",
+    );
+
+    let diagnostics = builder.analyze();
+    check_diagnostics(
+        diagnostics,
+        vec![
+            Diagnostic::error(
+                code.s1("'b'"),
+                "character literal does not match integer type 'INTEGER'",
+            ),
+            Diagnostic::error(
+                code.s1("f1 | f2"),
+                "Elements of record 'rec_t' associated together are not of the same type",
+            )
+            .related(code.s1("f1"), "Element 'f1' has type 'CHARACTER'")
+            .related(code.s1("f2"), "Element 'f2' has integer type 'INTEGER'"),
+            Diagnostic::error(
+                code.s1("f4"),
+                "No declaration of 'f4' within record type 'rec_t'",
+            ),
+        ],
+    );
+}
+
+#[test]
+fn typecheck_nested_aggregates_with_others() {
+    let mut builder = LibraryBuilder::new();
+    let code = builder.in_declarative_region(
+        "
+type word_t is array (0 to 7) of bit;
+type rec_t is record
+    valid : bit;
+    data : word_t;
+end record;
+type rec_arr_t is array (0 to 3) of rec_t;
+type bus_t is record
+    words : rec_arr_t;
+    first, last : word_t;
+end record;
+
+constant good1 : rec_arr_t := (others => (valid => '0', data => (others => '0')));
+constant good2 : bus_t := (words => (others => (valid => '1', data => (others => '0'))), first | last => (others => '1'));
+constant good3 : bus_t := (words => (others => ('0', (others => '1'))), others => (others => '0'));
+constant bad1 : rec_arr_t := (others => (valid => '0', data => (others => 2)));
+constant bad2 : bus_t := (words => (others => ('0', (others => '1'))), first | last => (others => 3));
+        ",
+    );
+
+    let diagnostics = builder.analyze();
+    check_diagnostics(
+        diagnostics,
+        vec![
+            Diagnostic::error(
+                code.s1("(others => 2)").s1("2"),
+                "integer literal does not match type 'BIT'",
+            ),
+            Diagnostic::error(
+                code.s1("(others => 3)").s1("3"),
+                "integer literal does not match type 'BIT'",
+            ),
+        ],
+    );
+}
+
+#[test]
+fn ambiguous_string_literal_lists_candidate_types() {
+    let mut builder = LibraryBuilder::new();
+    let code = builder.in_declarative_region(
+        "
+type char_vec_t is array (natural range <>) of character;
+type int_vec_t is array (natural range <>) of integer;
+
+procedure proc is
+begin
+  case \"01\" is
+    when others => null;
+  end case;
+  case char_vec_t'(\"01\") is
+    when others => null;
+  end case;
+end procedure;
+        ",
+    );
+
+    let diagnostics = builder.analyze();
+    assert_eq!(diagnostics.len(), 1);
+    let diagnostic = &diagnostics[0];
+    assert_eq!(diagnostic.pos, code.s1("\"01\"").pos());
+    assert_eq!(
+        diagnostic.message,
+        "Ambiguous expression. You can use a qualified expression type'(expr) to disambiguate."
+    );
+
+    // Only one-dimensional arrays of enumeration types may be the type of a string literal
+    let candidates: Vec<_> = diagnostic
+        .related
+        .iter()
+        .map(|(_, message)| message.as_str())
+        .collect();
+    assert!(candidates.contains(&"Might be array type 'STRING'"));
+    assert!(candidates.contains(&"Might be array type 'BIT_VECTOR'"));
+    assert!(candidates.contains(&"Might be array type 'char_vec_t'"));
+    assert!(!candidates.contains(&"Might be array type 'INTEGER_VECTOR'"));
+    assert!(!candidates.contains(&"Might be array type 'int_vec_t'"));
+}
@@ -0,0 +1,150 @@
+//! This Source Code Form is subject to the terms of the Mozilla Public
+//! License, v. 2.0. If a copy of the MPL was not distributed with this file,
+//! You can obtain one at http://mozilla.org/MPL/2.0/.
+//!
+//! Copyright (c) 2023, Olof Kraigher olof.kraigher@gmail.com
+
+use super::*;
+use crate::data::Severity;
+
+fn analyze_unused(builder: &LibraryBuilder, severity: Severity) -> Vec<Diagnostic> {
+    let (mut root, _) = builder.get_analyzed_root();
+    root.set_unused_declarations(Some(severity));
+    let mut diagnostics = Vec::new();
+    root.analyze(&mut diagnostics);
+    diagnostics
+}
+
+#[test]
+fn local_declarations_that_are_never_used() {
+    let mut builder = LibraryBuilder::new();
+    let code = builder.code(
+        "libname",
+        "
+package pkg is
+  constant c_public : natural := 0;
+end package;
+
+entity ent is
+  port (clk : in bit; unused_port : in bit);
+end entity;
+
+architecture a of ent is
+  type state_t is (idle, busy);
+  type unused_t is (first, last);
+  signal state : state_t;
+  signal unused_sig : bit;
+  function used_fun(arg : natural) return natural;
+  function used_fun(arg : natural) return natural is
+  begin
+    return arg;
+  end function;
+  procedure unused_proc(arg : natural) is
+    variable unused_var : natural;
+  begin
+  end procedure;
+begin
+  process
+    variable count : natural;
+  begin
+    count := used_fun(count);
+    if state = idle then
+      state <= busy;
+    end if;
+    wait on clk;
+  end process;
+end architecture;
+",
+    );
+
+    check_diagnostics(
+        analyze_unused(&builder, Severity::Hint),
+        vec![
+            Diagnostic::hint(code.s1("unused_t"), "Type 'unused_t' is never used")
+                .related(code.s1("type unused_t is (first, last);"), "Declared here")
+                .with_code("unused-declaration"),
+            Diagnostic::hint(code.s1("unused_sig"), "Signal 'unused_sig' is never used")
+                .related(code.s1("signal unused_sig : bit;"), "Declared here")
+                .with_code("unused-declaration"),
+            Diagnostic::hint(
+                code.s1("unused_proc"),
+                "Procedure 'unused_proc' is never used",
+            )
+            .related(
+                code.s1("procedure unused_proc(arg : natural)"),
+                "Declared here",
+            )
+            .with_code("unused-declaration"),
+            Diagnostic::hint(code.s1("unused_var"), "Variable 'unused_var' is never used")
+                .related(code.s1("variable unused_var : natural;"), "Declared here")
+                .with_code("unused-declaration"),
+        ],
+    );
+
+    check_diagnostics(
+        analyze_unused(&builder, Severity::Warning),
+        vec![
+            Diagnostic::warning(code.s1("unused_t"), "Type 'unused_t' is never used")
+                .related(code.s1("type unused_t is (first, last);"), "Declared here")
+                .with_code("unused-declaration"),
+            Diagnostic::warning(code.s1("unused_sig"), "Signal 'unused_sig' is never used")
+                .related(code.s1("signal unused_sig : bit;"), "Declared here")
+                .with_code("unused-declaration"),
+            Diagnostic::warning(
+                code.s1("unused_proc"),
+                "Procedure 'unused_proc' is never used",
+            )
+            .related(
+                code.s1("procedure unused_proc(arg : natural)"),
+                "Declared here",
+            )
+            .with_code("unused-declaration"),
+            Diagnostic::warning(code.s1("unused_var"), "Variable 'unused_var' is never used")
+                .related(code.s1("variable unused_var : natural;"), "Declared here")
+                .with_code("unused-declaration"),
+        ],
+    );
+}
+
+#[test]
+fn signals_that_are_only_assigned_or_only_read() {
+    let mut builder = LibraryBuilder::new();
+    let code = builder.code(
+        "libname",
+        "
+entity ent is
+  port (clk : in bit; dout : out bit);
+end entity;
+
+architecture a of ent is
+  signal dead : bit;
+  signal undriven : bit;
+  signal with_default : bit := '1';
+  signal mem : bit_vector(0 to 3);
+  signal idx : natural;
+  signal both : bit;
+begin
+  dead <= clk;
+  mem(idx) <= with_default;
+  both <= undriven;
+  dout <= both and mem(0);
+  idx <= 0;
+end architecture;
+",
+    );
+
+    check_diagnostics(
+        analyze_unused(&builder, Severity::Hint),
+        vec![
+            Diagnostic::hint(code.s1("dead"), "Signal 'dead' is assigned but never read")
+                .related(code.s("dead", 2), "Assigned here")
+                .with_code("signal-never-read"),
+            Diagnostic::hint(
+                code.s1("undriven"),
+                "Signal 'undriven' is read but never assigned",
+            )
+            .related(code.s("undriven", 2), "Read here")
+            .with_code("signal-never-assigned"),
+        ],
+    );
+}
@@ -5,6 +5,7 @@
 // Copyright (c) 2019, Olof Kraigher olof.kraigher@gmail.com
 
 use crate::analysis::DesignRoot;
+use crate::config::VHDLStandard;
 use crate::data::*;
 use crate::syntax::test::*;
 use crate::syntax::Symbols;
@@ -15,6 +16,7 @@ use std::sync::Arc;
 pub struct LibraryBuilder {
     code_builder: CodeBuilder,
     libraries: HashMap<Symbol, Vec<Code>>,
+    standard: VHDLStandard,
 }
 
 impl LibraryBuilder {
@@ -22,9 +24,14 @@ impl LibraryBuilder {
         LibraryBuilder {
             code_builder: CodeBuilder::new(),
             libraries: HashMap::default(),
+            standard: VHDLStandard::default(),
         }
     }
 
+    pub fn set_standard(&mut self, standard: VHDLStandard) {
+        self.standard = standard;
+    }
+
     fn add_code(&mut self, library_name: &str, code: Code) {
         let library_name = self.code_builder.symbol(library_name);
         match self.libraries.entry(library_name) {
@@ -71,6 +78,7 @@ end architecture;"
 
     pub fn get_analyzed_root(&self) -> (DesignRoot, Vec<Diagnostic>) {
         let mut root = DesignRoot::new(self.code_builder.symbols.clone());
+        root.set_standard(self.standard);
         let mut diagnostics = Vec::new();
 
         add_standard_library(self.symbols(), &mut root);
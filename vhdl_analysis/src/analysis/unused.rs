@@ -0,0 +1,221 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this file,
+// You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) 2023, Olof Kraigher olof.kraigher@gmail.com
+
+//! Find signals, variables, constants, types and subprograms that are never referenced
+//! and signals that are only assigned or only read
+//! Only declarations which are not visible outside of an architecture or a subprogram
+//! body are considered since other declarations may be used by code outside the project
+
+use super::component::capitalize;
+use super::named_entity::*;
+use super::root::DesignRoot;
+use crate::ast::search::*;
+use crate::ast::*;
+use crate::data::*;
+use fnv::{FnvHashMap, FnvHashSet};
+
+/// The diagnostic codes which allow clients to filter each kind separately
+const UNUSED_DECLARATION: &str = "unused-declaration";
+const SIGNAL_NEVER_READ: &str = "signal-never-read";
+const SIGNAL_NEVER_ASSIGNED: &str = "signal-never-assigned";
+
+#[derive(Default)]
+pub(super) struct FindUnused {
+    declared: Vec<EntityId>,
+    referenced: FnvHashSet<EntityId>,
+    /// The first position where each object is read
+    reads: FnvHashMap<EntityId, SrcPos>,
+    /// The first position where each object is assigned
+    writes: FnvHashMap<EntityId, SrcPos>,
+    /// The names of assignment targets which are not reads
+    targets: FnvHashSet<SrcPos>,
+}
+
+impl FindUnused {
+    fn write(&mut self, pos: &SrcPos, name: &Name, is_target: bool) {
+        if let Some((pos, id)) = base_name(pos, name) {
+            self.writes.entry(id).or_insert_with(|| pos.clone());
+            if is_target {
+                self.targets.insert(pos.clone());
+            }
+        }
+    }
+}
+
+impl Searcher for FindUnused {
+    fn search_pos_with_ref(&mut self, pos: &SrcPos, reference: &mut Reference) -> SearchState {
+        if let Some(id) = reference {
+            self.referenced.insert(*id);
+            if !self.targets.contains(pos) {
+                self.reads.entry(*id).or_insert_with(|| pos.clone());
+            }
+        }
+        NotFinished
+    }
+
+    fn search_target(&mut self, target: &WithPos<Target>) -> SearchState {
+        match target.item {
+            Target::Name(ref name) => self.write(&target.pos, name, true),
+            Target::Aggregate(ref assocs) => {
+                for assoc in assocs.iter() {
+                    let (ElementAssociation::Positional(expr) | ElementAssociation::Named(_, expr)) =
+                        assoc;
+                    if let Expression::Name(ref name) = expr.item {
+                        self.write(&expr.pos, name, true);
+                    }
+                }
+            }
+        }
+        NotFinished
+    }
+
+    /// The direction of an actual is not known, it may both be read and assigned
+    fn search_association(&mut self, assoc: &AssociationElement) -> SearchState {
+        if let ActualPart::Expression(Expression::Name(ref name)) = assoc.actual.item {
+            self.write(&assoc.actual.pos, name, false);
+        }
+        NotFinished
+    }
+
+    fn search_decl(&mut self, decl: FoundDeclaration) -> SearchState {
+        match decl {
+            FoundDeclaration::Object(_)
+            | FoundDeclaration::Type(_)
+            | FoundDeclaration::Function(_)
+            | FoundDeclaration::Procedure(_) => {
+                self.declared.extend(decl.ent_id());
+            }
+            // An object alias may both be read and assigned
+            FoundDeclaration::Alias(ref alias) => {
+                self.write(&alias.name.pos, &alias.name.item, false);
+            }
+            _ => {}
+        }
+        NotFinished
+    }
+}
+
+impl FindUnused {
+    /// Report the local declarations that are never referenced and the local signals
+    /// that are only assigned or only read with the severity
+    pub fn finish(
+        self,
+        root: &DesignRoot,
+        severity: Severity,
+        diagnostics: &mut dyn DiagnosticHandler,
+    ) {
+        // A reference to a subprogram body also uses its declaration
+        let declarations: Vec<_> = self
+            .referenced
+            .iter()
+            .filter_map(|id| match root.get_ent(*id).related {
+                Related::DeclaredBy(decl) => Some(decl.id()),
+                _ => None,
+            })
+            .collect();
+        let mut referenced = self.referenced;
+        referenced.extend(declarations);
+
+        for id in self.declared.iter() {
+            let ent = root.get_ent(*id);
+            // A subprogram body or the full declaration of a deferred constant
+            // is reported at its declaration
+            if matches!(ent.related, Related::DeclaredBy(_))
+                || !is_checked_kind(ent)
+                || !is_local(ent)
+            {
+                continue;
+            }
+            let decl_pos = if let Some(decl_pos) = ent.decl_pos() {
+                decl_pos
+            } else {
+                continue;
+            };
+
+            if !referenced.contains(id) {
+                let mut diagnostic = Diagnostic::new(
+                    decl_pos,
+                    format!(
+                        "{} '{}' is never used",
+                        capitalize(ent.kind().describe()),
+                        ent.designator()
+                    ),
+                    severity,
+                )
+                .with_code(UNUSED_DECLARATION);
+                if let Some(decl_span) = ent.decl_span() {
+                    diagnostic.add_related(decl_span, "Declared here");
+                }
+                diagnostics.push(diagnostic);
+                continue;
+            }
+
+            let object = match ent.kind() {
+                AnyEntKind::Object(object) if object.class == ObjectClass::Signal => object,
+                _ => continue,
+            };
+            match (self.reads.get(id), self.writes.get(id)) {
+                (None, Some(write_pos)) => diagnostics.push(
+                    Diagnostic::new(
+                        decl_pos,
+                        format!("Signal '{}' is assigned but never read", ent.designator()),
+                        severity,
+                    )
+                    .related(write_pos, "Assigned here")
+                    .with_code(SIGNAL_NEVER_READ),
+                ),
+                // A signal with an initial value that is never assigned is used as a constant
+                (Some(read_pos), None) if !object.has_default => diagnostics.push(
+                    Diagnostic::new(
+                        decl_pos,
+                        format!("Signal '{}' is read but never assigned", ent.designator()),
+                        severity,
+                    )
+                    .related(read_pos, "Read here")
+                    .with_code(SIGNAL_NEVER_ASSIGNED),
+                ),
+                _ => {}
+            }
+        }
+    }
+}
+
+/// The object denoted by the prefix of a name such as `sig` in `sig(0).elem`
+/// and the position of the prefix
+fn base_name<'n>(pos: &'n SrcPos, name: &'n Name) -> Option<(&'n SrcPos, EntityId)> {
+    match name {
+        Name::Designator(designator) => Some((pos, designator.reference?)),
+        Name::Selected(prefix, _) | Name::Slice(prefix, _) => base_name(&prefix.pos, &prefix.item),
+        Name::CallOrIndexed(call) => base_name(&call.name.pos, &call.name.item),
+        _ => None,
+    }
+}
+
+fn is_checked_kind(ent: EntRef) -> bool {
+    match ent.kind() {
+        // Interface objects are part of the signature of their parent
+        AnyEntKind::Object(object) => object.iface.is_none(),
+        AnyEntKind::Type(_) => true,
+        AnyEntKind::Overloaded(Overloaded::Subprogram(_) | Overloaded::SubprogramDecl(_)) => true,
+        _ => false,
+    }
+}
+
+/// Declared within an architecture or a subprogram body
+fn is_local(ent: EntRef) -> bool {
+    let mut parent = ent.parent;
+    while let Some(ent) = parent {
+        if matches!(
+            ent.kind(),
+            AnyEntKind::Design(Design::Architecture(..))
+                | AnyEntKind::Overloaded(Overloaded::Subprogram(_))
+        ) {
+            return true;
+        }
+        parent = ent.parent;
+    }
+    false
+}
@@ -0,0 +1,1546 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this file,
+// You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) 2018, Olof Kraigher olof.kraigher@gmail.com
+
+//! Configuration of the design hierarchy and other settings
+
+use crate::data::*;
+use crate::format::{FormatOptions, KeywordCase};
+use fnv::FnvHashMap;
+use std::env;
+use std::fs::File;
+use std::io;
+use std::io::prelude::*;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use toml::Value;
+
+/// The revision of the VHDL language standard to analyze against
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug, Default)]
+pub enum VHDLStandard {
+    VHDL1993,
+    #[default]
+    VHDL2008,
+    VHDL2019,
+}
+
+impl FromStr for VHDLStandard {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "1993" | "93" => Ok(VHDLStandard::VHDL1993),
+            "2008" | "08" => Ok(VHDLStandard::VHDL2008),
+            "2019" | "19" => Ok(VHDLStandard::VHDL2019),
+            _ => Err(format!("Unsupported VHDL standard '{s}'")),
+        }
+    }
+}
+
+/// The kind of code within a library or file which selects the lints that apply to it
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum LibraryKind {
+    /// Code for synthesis
+    Rtl,
+    /// Code that is only simulated such as test benches
+    Testbench,
+}
+
+impl FromStr for LibraryKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "rtl" => Ok(LibraryKind::Rtl),
+            "testbench" => Ok(LibraryKind::Testbench),
+            _ => Err(format!("Unsupported library kind '{s}'")),
+        }
+    }
+}
+
+/// The lints whose severity can be configured for each kind of code
+const KIND_LINTS: [&str; 2] = ["unused", "synthesis"];
+
+#[derive(Clone, PartialEq, Eq, Default, Debug)]
+pub struct Config {
+    // A map from library name to file name
+    libraries: FnvHashMap<String, LibraryConfig>,
+    // The VHDL standard, uses the default when not configured
+    standard: Option<VHDLStandard>,
+    // Warn about recursive subprograms used in constant values, enabled when not configured
+    warn_recursion: Option<bool>,
+    // The severity of unused local declarations where None disables them, hints when not configured
+    unused_declarations: Option<Option<Severity>>,
+    // The severity of constructs that are not synthesizable within RTL code, warnings when not configured
+    synthesis_checks: Option<Option<Severity>>,
+    // The severity of diagnostics within if generate branches that are never selected
+    // where None hides them, kept unchanged when not configured
+    dead_generate: Option<Option<Severity>>,
+    // Warn about signals with more loads or drivers within an architecture, no limit when not configured
+    max_loads: Option<usize>,
+    max_drivers: Option<usize>,
+    // The severity of lints within code of a kind, overrides the severity of the project
+    kind_lints: FnvHashMap<LibraryKind, FnvHashMap<String, Option<Severity>>>,
+    // Explain why each candidate of an unresolved call was rejected, disabled when not configured
+    explain_overloads: Option<bool>,
+    // File with the generic values of top level entities
+    generics_file: Option<PathBuf>,
+    // The encoding of source files, detected when not configured
+    encoding: Option<SourceEncoding>,
+    // Patterns of files whose encoding differs from the encoding of the project
+    file_encodings: Vec<(String, SourceEncoding)>,
+    // Patterns of files whose kind differs from the kind of their library
+    file_kinds: Vec<(String, LibraryKind)>,
+    // The settings of the formatter, the defaults of the formatter are used when not configured
+    indent_width: Option<usize>,
+    keyword_case: Option<KeywordCase>,
+    align_declarations: Option<bool>,
+    // The configuration files of projects whose libraries are read-only dependencies
+    dependencies: Vec<PathBuf>,
+}
+
+#[derive(Clone, PartialEq, Eq, Default, Debug)]
+pub struct LibraryConfig {
+    name: String,
+    patterns: Vec<String>,
+    // Diagnostics within the files of a third party library are not reported
+    is_third_party: bool,
+    // Whether the library contains RTL or test bench code
+    kind: Option<LibraryKind>,
+}
+
+impl LibraryConfig {
+    /// Return a vector of file names
+    /// Only include files that exists
+    /// Files that do not exist produce a warning message
+    pub fn file_names(&self, messages: &mut dyn MessageHandler) -> Vec<PathBuf> {
+        fn as_abspath(file_path: &Path) -> Result<PathBuf, Message> {
+            match dunce::canonicalize(file_path) {
+                Ok(file_path) => Ok(file_path),
+                Err(err) => Err(Message::error(format!(
+                    "Could not create absolute path {}: {:?}",
+                    file_path.to_string_lossy(),
+                    err
+                ))),
+            }
+        }
+
+        let mut result = Vec::new();
+        for pattern in self.patterns.iter() {
+            let stripped_pattern = if cfg!(windows) {
+                pattern.strip_prefix("\\\\?\\").unwrap_or(pattern.as_str())
+            } else {
+                pattern.as_str()
+            };
+
+            if is_literal(stripped_pattern) {
+                let file_path = Path::new(pattern);
+
+                if file_path.exists() {
+                    match as_abspath(file_path) {
+                        Ok(abs_path) => {
+                            result.push(abs_path);
+                        }
+                        Err(msg) => {
+                            messages.push(msg);
+                        }
+                    };
+                } else {
+                    messages.push(Message::warning(format! {"File {pattern} does not exist"}));
+                }
+            } else {
+                match glob::glob(stripped_pattern) {
+                    Ok(paths) => {
+                        let mut empty_pattern = true;
+
+                        for file_path_or_error in paths {
+                            empty_pattern = false;
+                            match file_path_or_error {
+                                Ok(file_path) => {
+                                    match as_abspath(&file_path) {
+                                        Ok(abs_path) => {
+                                            result.push(abs_path);
+                                        }
+                                        Err(msg) => {
+                                            messages.push(msg);
+                                        }
+                                    };
+                                }
+                                Err(err) => {
+                                    messages.push(Message::error(err.to_string()));
+                                }
+                            }
+                        }
+
+                        if empty_pattern {
+                            messages.push(Message::warning(format!(
+                                "Pattern '{stripped_pattern}' did not match any file"
+                            )));
+                        }
+                    }
+                    Err(err) => {
+                        messages.push(Message::error(format!("Invalid pattern '{pattern}' {err}")));
+                    }
+                }
+            }
+        }
+        Self::remove_duplicates(result)
+    }
+
+    /// Remove duplicate file names from the result
+    fn remove_duplicates(file_names: Vec<PathBuf>) -> Vec<PathBuf> {
+        let mut result = Vec::with_capacity(file_names.len());
+        let mut fileset = std::collections::HashSet::new();
+
+        for file_name in file_names.into_iter() {
+            if fileset.insert(file_name.clone()) {
+                result.push(file_name);
+            }
+        }
+        result
+    }
+
+    /// Returns the name of the library
+    pub fn name(&self) -> &str {
+        self.name.as_str()
+    }
+
+    /// Returns true if the library is third party code such as vendor IP
+    /// whose diagnostics should not be reported
+    pub fn is_third_party(&self) -> bool {
+        self.is_third_party
+    }
+
+    /// Returns whether the library contains RTL or test bench code if configured
+    pub fn kind(&self) -> Option<LibraryKind> {
+        self.kind
+    }
+}
+
+/// The value of a generic in a generics file
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum GenericValue {
+    Integer(i64),
+    Boolean(bool),
+    String(String),
+}
+
+/// The generic values of top level entities that select a build configuration
+///
+/// The generics file has one table per entity:
+/// ```toml
+/// [lib.top]
+/// data_width = 16
+/// use_fifo = true
+/// ```
+#[derive(Clone, PartialEq, Eq, Default, Debug)]
+pub struct GenericOverrides {
+    // (library name, entity name) => generic name => value, all names in lower case
+    entities: FnvHashMap<(String, String), FnvHashMap<String, GenericValue>>,
+}
+
+impl FromStr for GenericOverrides {
+    type Err = String;
+
+    fn from_str(string: &str) -> Result<GenericOverrides, String> {
+        let file = string.parse::<Value>().map_err(|err| err.to_string())?;
+        let libraries = file.as_table().ok_or("generics file must be a table")?;
+        let mut overrides = GenericOverrides::default();
+
+        for (library_name, entities) in libraries.iter() {
+            let entities = entities
+                .as_table()
+                .ok_or_else(|| format!("library {library_name} must be a table"))?;
+            for (entity_name, generics) in entities.iter() {
+                let generics = generics.as_table().ok_or_else(|| {
+                    format!("entity {library_name}.{entity_name} must be a table")
+                })?;
+                for (name, value) in generics.iter() {
+                    let value = match value {
+                        Value::Integer(value) => GenericValue::Integer(*value),
+                        Value::Boolean(value) => GenericValue::Boolean(*value),
+                        Value::String(value) => GenericValue::String(value.clone()),
+                        _ => {
+                            return Err(format!(
+                                "generic {library_name}.{entity_name}.{name} must be an integer, boolean or string"
+                            ))
+                        }
+                    };
+                    overrides.set(library_name, entity_name, name, value);
+                }
+            }
+        }
+        Ok(overrides)
+    }
+}
+
+impl GenericOverrides {
+    pub fn read_file_path(file_name: &Path) -> io::Result<GenericOverrides> {
+        let mut file = File::open(file_name)?;
+        let mut contents = String::new();
+        file.read_to_string(&mut contents)?;
+
+        contents.parse().map_err(io::Error::other)
+    }
+
+    /// Set the value of a generic of a top level entity
+    pub fn set(
+        &mut self,
+        library_name: &str,
+        entity_name: &str,
+        generic_name: &str,
+        value: GenericValue,
+    ) {
+        self.entities
+            .entry((library_name.to_lowercase(), entity_name.to_lowercase()))
+            .or_default()
+            .insert(generic_name.to_lowercase(), value);
+    }
+
+    /// The generic values of an entity by their lower case name
+    pub fn get(
+        &self,
+        library_name: &str,
+        entity_name: &str,
+    ) -> Option<&FnvHashMap<String, GenericValue>> {
+        self.entities
+            .get(&(library_name.to_lowercase(), entity_name.to_lowercase()))
+    }
+
+    /// The library and entity names of the top level entities
+    pub fn entities(&self) -> impl Iterator<Item = &(String, String)> {
+        self.entities.keys()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entities.is_empty()
+    }
+}
+
+impl Config {
+    pub fn from_str(string: &str, parent: &Path) -> Result<Config, String> {
+        let config = string.parse::<Value>().map_err(|err| err.to_string())?;
+
+        let standard = if let Some(standard) = config.get("standard") {
+            Some(
+                standard
+                    .as_str()
+                    .ok_or("standard must be a string")?
+                    .parse()?,
+            )
+        } else {
+            None
+        };
+
+        let generics_file = if let Some(file) = config.get("generics_file") {
+            Some(parent.join(file.as_str().ok_or("generics_file must be a string")?))
+        } else {
+            None
+        };
+
+        let encoding = if let Some(encoding) = config.get("encoding") {
+            Some(
+                encoding
+                    .as_str()
+                    .ok_or("encoding must be a string")?
+                    .parse()?,
+            )
+        } else {
+            None
+        };
+
+        let libraries = config
+            .get("libraries")
+            .ok_or("missing field libraries")?
+            .as_table()
+            .ok_or("libraries must be a table")?;
+
+        let mut result = Config {
+            libraries: parse_libraries(libraries, parent)?,
+            standard,
+            generics_file,
+            encoding,
+            ..Config::default()
+        };
+
+        if let Some(lint) = config.get("lint") {
+            result.read_lint(lint.as_table().ok_or("lint must be a table")?)?;
+        }
+        if let Some(debug) = config.get("debug") {
+            result.read_debug(debug.as_table().ok_or("debug must be a table")?)?;
+        }
+        if let Some(format) = config.get("format") {
+            result.read_format(format.as_table().ok_or("format must be a table")?)?;
+        }
+        if let Some(encodings) = config.get("file_encodings") {
+            let encodings = encodings
+                .as_table()
+                .ok_or("file_encodings must be a table")?;
+            result.file_encodings = parse_file_patterns(encodings, parent, "encoding")?;
+        }
+        if let Some(kinds) = config.get("file_kinds") {
+            let kinds = kinds.as_table().ok_or("file_kinds must be a table")?;
+            result.file_kinds = parse_file_patterns(kinds, parent, "kind")?;
+        }
+        if let Some(files) = config.get("dependencies") {
+            let files = files.as_array().ok_or("dependencies must be an array")?;
+            for file in files.iter() {
+                let file = file
+                    .as_str()
+                    .ok_or("dependencies must be an array of strings")?;
+                result.dependencies.push(parent.join(file));
+            }
+        }
+
+        Ok(result)
+    }
+
+    /// Read the settings of the lint table
+    fn read_lint(&mut self, lint: &toml::Table) -> Result<(), String> {
+        if let Some(recursion) = lint.get("recursion") {
+            self.warn_recursion = Some(
+                recursion
+                    .as_bool()
+                    .ok_or("lint.recursion must be a boolean")?,
+            );
+        }
+        self.unused_declarations = parse_lint_severity_of(lint, "unused")?;
+        self.synthesis_checks = parse_lint_severity_of(lint, "synthesis")?;
+        self.dead_generate = parse_lint_severity_of(lint, "dead_generate")?;
+        self.max_loads = parse_lint_limit(lint, "max_loads")?;
+        self.max_drivers = parse_lint_limit(lint, "max_drivers")?;
+
+        for kind_name in ["rtl", "testbench"] {
+            if let Some(lints) = lint.get(kind_name) {
+                let lints = lints
+                    .as_table()
+                    .ok_or_else(|| format!("lint.{kind_name} must be a table"))?;
+                let mut severities = FnvHashMap::default();
+                for (name, severity) in lints.iter() {
+                    if !KIND_LINTS.contains(&name.as_str()) {
+                        return Err(format!("Unsupported lint '{name}' for {kind_name}"));
+                    }
+                    let severity = severity
+                        .as_str()
+                        .ok_or_else(|| format!("lint.{kind_name}.{name} must be a string"))?;
+                    severities.insert(name.to_owned(), parse_lint_severity(severity)?);
+                }
+                self.kind_lints.insert(kind_name.parse()?, severities);
+            }
+        }
+        Ok(())
+    }
+
+    /// Read the settings of the debug table
+    fn read_debug(&mut self, debug: &toml::Table) -> Result<(), String> {
+        if let Some(overloads) = debug.get("overload_resolution") {
+            self.explain_overloads = Some(
+                overloads
+                    .as_bool()
+                    .ok_or("debug.overload_resolution must be a boolean")?,
+            );
+        }
+        Ok(())
+    }
+
+    /// Read the settings of the format table
+    fn read_format(&mut self, format: &toml::Table) -> Result<(), String> {
+        if let Some(width) = format.get("indent_width") {
+            let width = width
+                .as_integer()
+                .ok_or("format.indent_width must be an integer")?;
+            self.indent_width = Some(
+                usize::try_from(width).map_err(|_| "format.indent_width must not be negative")?,
+            );
+        }
+        if let Some(case) = format.get("keyword_case") {
+            self.keyword_case = Some(
+                case.as_str()
+                    .ok_or("format.keyword_case must be a string")?
+                    .parse()?,
+            );
+        }
+        if let Some(align) = format.get("align_declarations") {
+            self.align_declarations = Some(
+                align
+                    .as_bool()
+                    .ok_or("format.align_declarations must be a boolean")?,
+            );
+        }
+        Ok(())
+    }
+
+    pub fn read_file_path(file_name: &Path) -> io::Result<Config> {
+        let mut file = File::open(file_name)?;
+        let mut contents = String::new();
+        file.read_to_string(&mut contents)?;
+
+        let parent = file_name.parent().unwrap();
+
+        Config::from_str(&contents, parent).map_err(|msg| io::Error::new(io::ErrorKind::Other, msg))
+    }
+
+    pub fn get_library<'a>(&'a self, name: &str) -> Option<&'a LibraryConfig> {
+        self.libraries.get(name)
+    }
+
+    pub fn iter_libraries(&self) -> impl Iterator<Item = &LibraryConfig> {
+        self.libraries.values()
+    }
+
+    /// The configured VHDL standard
+    pub fn standard(&self) -> VHDLStandard {
+        self.standard.unwrap_or_default()
+    }
+
+    /// Warn about recursive subprograms used in constant values
+    pub fn warn_recursion(&self) -> bool {
+        self.warn_recursion.unwrap_or(true)
+    }
+
+    /// The severity of local declarations that are never used, not reported when None
+    pub fn unused_declarations(&self) -> Option<Severity> {
+        self.unused_declarations.unwrap_or(Some(Severity::Hint))
+    }
+
+    /// The severity of constructs that are not synthesizable within RTL code,
+    /// not reported when None
+    pub fn synthesis_checks(&self) -> Option<Severity> {
+        self.synthesis_checks.unwrap_or(Some(Severity::Warning))
+    }
+
+    /// The severity of diagnostics within if generate branches whose conditions evaluate
+    /// to false, None when they are kept unchanged and Some(None) when they are hidden
+    pub fn dead_generate(&self) -> Option<Option<Severity>> {
+        self.dead_generate
+    }
+
+    /// The largest number of processes, concurrent statements and instances that may read
+    /// a signal within an architecture, not checked when None
+    pub fn max_loads(&self) -> Option<usize> {
+        self.max_loads
+    }
+
+    /// The largest number of processes, concurrent assignments and instances that may drive
+    /// a signal within an architecture, not checked when None
+    pub fn max_drivers(&self) -> Option<usize> {
+        self.max_drivers
+    }
+
+    /// The severity of a lint such as `unused` or `synthesis` within code of the kind,
+    /// not reported when None. Synthesis checks only apply to RTL code unless configured
+    /// for another kind
+    pub fn lint_severity(&self, kind: Option<LibraryKind>, lint: &str) -> Option<Severity> {
+        if let Some(severity) = kind
+            .and_then(|kind| self.kind_lints.get(&kind))
+            .and_then(|lints| lints.get(lint))
+        {
+            return *severity;
+        }
+        match lint {
+            "unused" => self.unused_declarations(),
+            "synthesis" if kind == Some(LibraryKind::Rtl) => self.synthesis_checks(),
+            _ => None,
+        }
+    }
+
+    /// The kind of a source file when it matches a file pattern
+    pub fn kind_of(&self, file_name: &Path) -> Option<LibraryKind> {
+        self.file_kinds
+            .iter()
+            .rev()
+            .find(|(pattern, _)| {
+                glob::Pattern::new(pattern).is_ok_and(|pattern| pattern.matches_path(file_name))
+            })
+            .map(|(_, kind)| *kind)
+    }
+
+    /// Explain why each candidate of an unresolved call was rejected
+    pub fn explain_overloads(&self) -> bool {
+        self.explain_overloads.unwrap_or(false)
+    }
+
+    /// The file with the generic values of top level entities
+    pub fn generics_file(&self) -> Option<&Path> {
+        self.generics_file.as_deref()
+    }
+
+    /// The encoding of a source file, a matching file pattern takes precedence
+    /// over the encoding of the project
+    pub fn encoding_of(&self, file_name: &Path) -> SourceEncoding {
+        self.file_encodings
+            .iter()
+            .rev()
+            .find(|(pattern, _)| {
+                glob::Pattern::new(pattern).is_ok_and(|pattern| pattern.matches_path(file_name))
+            })
+            .map(|(_, encoding)| *encoding)
+            .or(self.encoding)
+            .unwrap_or_default()
+    }
+
+    /// The options of the formatter where each setting that is not configured has its default
+    pub fn format_options(&self) -> FormatOptions {
+        let default = FormatOptions::default();
+        FormatOptions {
+            indent_width: self.indent_width.unwrap_or(default.indent_width),
+            keyword_case: self.keyword_case.unwrap_or(default.keyword_case),
+            align_declarations: self
+                .align_declarations
+                .unwrap_or(default.align_declarations),
+        }
+    }
+
+    /// Append another config to self
+    ///
+    /// In case of conflict the appended config takes precedence
+    pub fn append(&mut self, config: &Config, messages: &mut dyn MessageHandler) {
+        if config.standard.is_some() {
+            self.standard = config.standard;
+        }
+
+        if config.warn_recursion.is_some() {
+            self.warn_recursion = config.warn_recursion;
+        }
+
+        if config.unused_declarations.is_some() {
+            self.unused_declarations = config.unused_declarations;
+        }
+
+        if config.synthesis_checks.is_some() {
+            self.synthesis_checks = config.synthesis_checks;
+        }
+
+        if config.dead_generate.is_some() {
+            self.dead_generate = config.dead_generate;
+        }
+
+        if config.max_loads.is_some() {
+            self.max_loads = config.max_loads;
+        }
+
+        if config.max_drivers.is_some() {
+            self.max_drivers = config.max_drivers;
+        }
+
+        for (kind, lints) in config.kind_lints.iter() {
+            self.kind_lints.entry(*kind).or_default().extend(
+                lints
+                    .iter()
+                    .map(|(name, severity)| (name.clone(), *severity)),
+            );
+        }
+
+        if config.explain_overloads.is_some() {
+            self.explain_overloads = config.explain_overloads;
+        }
+
+        if config.generics_file.is_some() {
+            self.generics_file = config.generics_file.clone();
+        }
+
+        if config.encoding.is_some() {
+            self.encoding = config.encoding;
+        }
+
+        self.file_encodings
+            .extend(config.file_encodings.iter().cloned());
+        self.file_kinds.extend(config.file_kinds.iter().cloned());
+
+        if config.indent_width.is_some() {
+            self.indent_width = config.indent_width;
+        }
+
+        if config.keyword_case.is_some() {
+            self.keyword_case = config.keyword_case;
+        }
+
+        if config.align_declarations.is_some() {
+            self.align_declarations = config.align_declarations;
+        }
+
+        for library in config.iter_libraries() {
+            if let Some(parent_library) = self.libraries.get_mut(&library.name) {
+                *parent_library = library.clone();
+
+                messages.push(Message::warning(format!(
+                    "Re-defined library {}",
+                    &library.name
+                )));
+            } else {
+                self.libraries.insert(
+                    library.name.clone(),
+                    LibraryConfig {
+                        name: library.name.clone(),
+                        patterns: library.patterns.clone(),
+                        is_third_party: library.is_third_party,
+                        kind: library.kind,
+                    },
+                );
+            }
+        }
+
+        for file_name in config.dependencies.iter() {
+            self.load_dependency(file_name, messages);
+        }
+    }
+
+    /// Add the libraries of the project of a configuration file as third party libraries such that
+    /// they are analyzed for navigation but not reported or changed. Libraries that are already
+    /// defined take precedence and each project is only loaded once.
+    fn load_dependency(&mut self, file_name: &Path, messages: &mut dyn MessageHandler) {
+        let file_name = dunce::canonicalize(file_name).unwrap_or_else(|_| file_name.to_owned());
+        if self.dependencies.contains(&file_name) {
+            return;
+        }
+        self.dependencies.push(file_name.clone());
+
+        let config = match Config::read_file_path(&file_name) {
+            Ok(config) => config,
+            Err(ref err) => {
+                messages.push(Message::error(format!(
+                    "Error while loading dependency configuration file {}: {err}",
+                    file_name.to_string_lossy()
+                )));
+                return;
+            }
+        };
+        messages.push(Message::log(format!(
+            "Loaded dependency configuration file: {}",
+            file_name.to_string_lossy()
+        )));
+
+        for library in config.iter_libraries() {
+            if self.libraries.contains_key(&library.name) {
+                messages.push(Message::warning(format!(
+                    "Library {} of dependency {} is already defined",
+                    &library.name,
+                    file_name.to_string_lossy()
+                )));
+            } else {
+                self.libraries.insert(
+                    library.name.clone(),
+                    LibraryConfig {
+                        is_third_party: true,
+                        ..library.clone()
+                    },
+                );
+            }
+        }
+
+        for dependency in config.dependencies.iter() {
+            self.load_dependency(dependency, messages);
+        }
+    }
+
+    /// Load configuration file from installation folder
+    fn load_installed_config(&mut self, messages: &mut dyn MessageHandler) {
+        let search_paths = [
+            "../vhdl_libraries",
+            "../../vhdl_libraries",
+            "/usr/lib/rust_hdl/vhdl_libraries",
+        ];
+
+        for dir in search_paths.into_iter() {
+            let mut file_name = PathBuf::from(dir);
+            // Expand a relative path
+            if !file_name.is_absolute() {
+                let exe_path = env::current_exe().expect("Executable path needed");
+                let exe_folder = exe_path.parent().expect("Executable folder must exist");
+                file_name = exe_folder.join(file_name)
+            }
+            file_name.push("vhdl_ls.toml");
+            if file_name.exists() {
+                self.load_installed_config_file(&file_name, messages);
+                return;
+            }
+        }
+
+        // Panic if we did not yet find the installed libraries
+        panic!(
+            "Couldn't find installed libraries at {}.",
+            search_paths.join(", ")
+        );
+    }
+
+    /// Load and append the configuration file of the installed libraries
+    /// The installed libraries are only analyzed for navigation and never reported
+    fn load_installed_config_file(&mut self, file_name: &Path, messages: &mut dyn MessageHandler) {
+        if let Some(mut config) = Self::read_config(file_name, "Installation", messages) {
+            for library in config.libraries.values_mut() {
+                library.is_third_party = true;
+            }
+            self.append(&config, messages);
+        }
+    }
+
+    /// Load configuration file from home folder
+    fn load_home_config(&mut self, messages: &mut dyn MessageHandler) {
+        if let Some(home_dir) = dirs::home_dir() {
+            let file_name = home_dir.join(".vhdl_ls.toml");
+
+            if !file_name.exists() {
+                return;
+            }
+
+            self.load_config(&file_name, "HOME folder", messages);
+        }
+    }
+
+    /// Load configuration file from environment
+    fn load_env_config(&mut self, env_name: &str, messages: &mut dyn MessageHandler) {
+        if let Some(file_name) = std::env::var_os(env_name) {
+            self.load_config(Path::new(&file_name), env_name, messages);
+        };
+    }
+
+    /// Load and append configuration file
+    fn load_config(&mut self, file_name: &Path, desc: &str, messages: &mut dyn MessageHandler) {
+        if let Some(env_config) = Self::read_config(file_name, desc, messages) {
+            self.append(&env_config, messages);
+        }
+    }
+
+    /// Read a configuration file and report whether it could be loaded
+    fn read_config(
+        file_name: &Path,
+        desc: &str,
+        messages: &mut dyn MessageHandler,
+    ) -> Option<Config> {
+        match Config::read_file_path(file_name) {
+            Ok(config) => {
+                messages.push(Message::log(format!(
+                    "Loaded {} configuration file: {}",
+                    desc,
+                    file_name.to_string_lossy()
+                )));
+                Some(config)
+            }
+            Err(ref err) => {
+                messages.push(Message::error(format!(
+                    "Error while loading {desc} configuration file: {err} "
+                )));
+                None
+            }
+        }
+    }
+
+    /// Find the ranges of the library name within the text of a configuration file
+    /// Used to rename a library in the configuration file
+    pub fn library_name_ranges(contents: &str, name: &str) -> Vec<Range> {
+        let mut ranges = Vec::new();
+        let mut in_libraries = false;
+
+        for (lineno, line) in contents.lines().enumerate() {
+            let indent = line.len() - line.trim_start().len();
+            let stmt = &line[indent..];
+
+            let key_offset = if let Some(header) = stmt.strip_prefix('[') {
+                let header = header.trim_start();
+                in_libraries = header.trim_end().trim_end_matches(']').trim_end() == "libraries";
+                header
+                    .strip_prefix("libraries")
+                    .and_then(|rest| rest.trim_start().strip_prefix('.'))
+                    .map(|rest| line.len() - rest.trim_start().len())
+            } else if in_libraries {
+                Some(indent)
+            } else {
+                None
+            };
+
+            if let Some(range) =
+                key_offset.and_then(|offset| library_key_range(lineno, line, offset, name))
+            {
+                ranges.push(range);
+            }
+        }
+
+        ranges
+    }
+
+    /// Find the ranges of the string literals within the text of a configuration file that
+    /// are equal to the file name, excluding the quotes
+    /// Used to update the file lists of libraries when a file is renamed
+    pub fn file_name_ranges(contents: &str, file_name: &str) -> Vec<Range> {
+        let mut ranges = Vec::new();
+
+        for (lineno, line) in contents.lines().enumerate() {
+            let mut rest = line;
+            while let Some(start) = rest.find(['"', '\'']) {
+                let quote = rest[start..].chars().next().unwrap();
+                let literal = &rest[start + 1..];
+                let len = if let Some(len) = literal.find(quote) {
+                    len
+                } else {
+                    break;
+                };
+
+                if &literal[..len] == file_name {
+                    let offset = line.len() - literal.len();
+                    let character = line[..offset].chars().count() as u32;
+                    ranges.push(Range::new(
+                        Position::new(lineno as u32, character),
+                        Position::new(lineno as u32, character + file_name.chars().count() as u32),
+                    ));
+                }
+                rest = &literal[len + 1..];
+            }
+        }
+
+        ranges
+    }
+
+    /// Load all external configuration
+    pub fn load_external_config(&mut self, messages: &mut dyn MessageHandler) {
+        self.load_installed_config(messages);
+        self.load_home_config(messages);
+        self.load_env_config("VHDL_LS_CONFIG", messages);
+    }
+}
+
+/// The range of the library name when it is the key starting at byte offset `offset` of the line
+fn library_key_range(lineno: usize, line: &str, offset: usize, name: &str) -> Option<Range> {
+    let key = &line[offset..];
+    let (start, key) = match key.strip_prefix(['"', '\'']) {
+        Some(unquoted) => (offset + 1, unquoted),
+        None => (offset, key),
+    };
+
+    let candidate = key.get(..name.len())?;
+    let rest = &key[name.len()..];
+    let terminated = rest
+        .chars()
+        .next()
+        .is_none_or(|chr| matches!(chr, '.' | '=' | ']' | '"' | '\'') || chr.is_whitespace());
+
+    if candidate.eq_ignore_ascii_case(name) && terminated {
+        let character = line[..start].chars().count() as u32;
+        Some(Range::new(
+            Position::new(lineno as u32, character),
+            Position::new(lineno as u32, character + name.chars().count() as u32),
+        ))
+    } else {
+        None
+    }
+}
+
+/// The severity of a lint where 'off' disables it
+fn parse_lint_severity(value: &str) -> Result<Option<Severity>, String> {
+    match value {
+        "off" => Ok(None),
+        "hint" => Ok(Some(Severity::Hint)),
+        "info" => Ok(Some(Severity::Info)),
+        "warning" => Ok(Some(Severity::Warning)),
+        "error" => Ok(Some(Severity::Error)),
+        _ => Err(format!("Unsupported lint severity '{value}'")),
+    }
+}
+
+/// The severity of a lint of the lint table, None when not configured
+fn parse_lint_severity_of(
+    lint: &toml::Table,
+    name: &str,
+) -> Result<Option<Option<Severity>>, String> {
+    if let Some(severity) = lint.get(name) {
+        let severity = severity
+            .as_str()
+            .ok_or_else(|| format!("lint.{name} must be a string"))?;
+        Ok(Some(parse_lint_severity(severity)?))
+    } else {
+        Ok(None)
+    }
+}
+
+/// Parse the libraries table where file names are relative to the parent folder
+fn parse_libraries(
+    libs: &toml::Table,
+    parent: &Path,
+) -> Result<FnvHashMap<String, LibraryConfig>, String> {
+    let mut libraries = FnvHashMap::default();
+    for (name, lib) in libs.iter() {
+        let file_arr = lib
+            .get("files")
+            .ok_or_else(|| format!("missing field files for library {name}"))?
+            .as_array()
+            .ok_or_else(|| format!("files for library {name} is not array"))?;
+
+        let mut patterns = Vec::new();
+        for file in file_arr.iter() {
+            let file = file
+                .as_str()
+                .ok_or_else(|| format!("not a string {file}"))?;
+            patterns.push(path_string(&parent.join(file))?);
+        }
+
+        let is_third_party = if let Some(is_third_party) = lib.get("is_third_party") {
+            is_third_party
+                .as_bool()
+                .ok_or_else(|| format!("is_third_party for library {name} is not a boolean"))?
+        } else {
+            false
+        };
+
+        let kind = if let Some(kind) = lib.get("kind") {
+            Some(
+                kind.as_str()
+                    .ok_or_else(|| format!("kind for library {name} is not a string"))?
+                    .parse()?,
+            )
+        } else {
+            None
+        };
+
+        libraries.insert(
+            name.to_owned(),
+            LibraryConfig {
+                name: name.to_owned(),
+                patterns,
+                is_third_party,
+                kind,
+            },
+        );
+    }
+    Ok(libraries)
+}
+
+/// Parse a table from file patterns relative to the parent folder to a setting of the files
+fn parse_file_patterns<T: FromStr<Err = String>>(
+    table: &toml::Table,
+    parent: &Path,
+    setting: &str,
+) -> Result<Vec<(String, T)>, String> {
+    let mut result = Vec::new();
+    for (file, value) in table.iter() {
+        let value = value
+            .as_str()
+            .ok_or_else(|| format!("{setting} of {file} must be a string"))?
+            .parse()?;
+        result.push((path_string(&parent.join(file))?, value));
+    }
+    Ok(result)
+}
+
+fn path_string(path: &Path) -> Result<String, String> {
+    path.to_str()
+        .map(|path| path.to_owned())
+        .ok_or_else(|| format!("Could not convert {path:?} to string"))
+}
+
+fn parse_lint_limit(lint: &toml::Table, name: &str) -> Result<Option<usize>, String> {
+    if let Some(limit) = lint.get(name) {
+        let limit = limit
+            .as_integer()
+            .and_then(|limit| usize::try_from(limit).ok())
+            .ok_or_else(|| format!("lint.{name} must be a non-negative integer"))?;
+        Ok(Some(limit))
+    } else {
+        Ok(None)
+    }
+}
+
+/// Returns true if the pattern is a plain file name and not a glob pattern
+fn is_literal(pattern: &str) -> bool {
+    for chr in pattern.chars() {
+        match chr {
+            '?' | '*' | '[' => {
+                return false;
+            }
+            _ => {}
+        }
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    /// Utility function to create an empty file in parent folder
+    fn touch(parent: &Path, file_name: &str) -> PathBuf {
+        let path = parent.join(file_name);
+        File::create(&path).expect("Assume file can be created");
+        path
+    }
+
+    fn abspath(path: &Path) -> PathBuf {
+        dunce::canonicalize(path).unwrap()
+    }
+
+    fn abspaths(paths: &[PathBuf]) -> Vec<PathBuf> {
+        paths.iter().map(|path| abspath(path)).collect()
+    }
+
+    fn assert_files_eq(got: &[PathBuf], expected: &[PathBuf]) {
+        assert_eq!(got, abspaths(expected).as_slice());
+    }
+
+    #[test]
+    fn test_is_literal() {
+        assert!(is_literal("file.vhd"));
+        assert!(!is_literal("file*.vhd"));
+        assert!(!is_literal("file?.vhd"));
+        assert!(!is_literal("file[ab].vhd"));
+    }
+
+    #[test]
+    fn test_library_name_ranges() {
+        let contents = "
+[libraries]
+lib1.files = ['pkg1.vhd']
+LIB1.is_third_party = true
+lib12.files = ['pkg12.vhd']
+\"lib1\".files = ['pkg1.vhd']
+
+[libraries.lib1]
+files = ['lib1.vhd']
+
+[ libraries . 'lib1' ]
+lib1 = 'not a library'
+";
+
+        let range = |line, character| {
+            Range::new(
+                Position::new(line, character),
+                Position::new(line, character + 4),
+            )
+        };
+
+        assert_eq!(
+            Config::library_name_ranges(contents, "lib1"),
+            vec![
+                range(2, 0),
+                range(3, 0),
+                range(5, 1),
+                range(7, 11),
+                range(10, 15),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_file_name_ranges() {
+        let contents = "
+[libraries]
+lib1.files = ['src/pkg.vhd', \"src/pkg.vhd\", 'src/pkg.vhdl']
+lib2.files = [
+  'src/*.vhd',
+  'src/pkg.vhd',
+]
+";
+
+        let range = |line, character| {
+            Range::new(
+                Position::new(line, character),
+                Position::new(line, character + 11),
+            )
+        };
+
+        assert_eq!(
+            Config::file_name_ranges(contents, "src/pkg.vhd"),
+            vec![range(2, 15), range(2, 30), range(5, 3)]
+        );
+    }
+
+    #[test]
+    fn config_from_str() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let parent = tempdir.path();
+
+        let tempdir2 = tempfile::tempdir().unwrap();
+        let absolute_path = abspath(tempdir2.path());
+        let absolute_vhd = touch(&absolute_path, "absolute.vhd");
+
+        let config = Config::from_str(
+            &format!(
+                "
+[libraries]
+lib2.files = [
+  'pkg2.vhd',
+  '{}'
+]
+lib1.files = [
+  'pkg1.vhd',
+  'tb_ent.vhd'
+]
+",
+                absolute_vhd.to_str().unwrap()
+            ),
+            parent,
+        )
+        .unwrap();
+        let mut libraries: Vec<&str> = config.iter_libraries().map(|lib| lib.name()).collect();
+        libraries.sort_unstable();
+        assert_eq!(libraries, &["lib1", "lib2"]);
+
+        let lib1 = config.get_library("lib1").unwrap();
+        let lib2 = config.get_library("lib2").unwrap();
+
+        let pkg1_path = touch(parent, "pkg1.vhd");
+        let pkg2_path = touch(parent, "pkg2.vhd");
+        let tb_ent_path = touch(parent, "tb_ent.vhd");
+
+        let mut messages = vec![];
+        assert_files_eq(&lib1.file_names(&mut messages), &[pkg1_path, tb_ent_path]);
+        assert_files_eq(&lib2.file_names(&mut messages), &[pkg2_path, absolute_vhd]);
+        assert_eq!(messages, vec![]);
+    }
+
+    #[test]
+    fn lint_settings_from_str() {
+        let config = Config::from_str(
+            "
+[libraries]
+lib.files = []
+
+[lint]
+unused = 'warning'
+synthesis = 'off'
+dead_generate = 'hint'
+max_loads = 16
+max_drivers = 1
+
+[lint.testbench]
+unused = 'off'
+",
+            Path::new(""),
+        )
+        .unwrap();
+
+        assert_eq!(config.unused_declarations(), Some(Severity::Warning));
+        assert_eq!(config.synthesis_checks(), None);
+        assert_eq!(config.dead_generate(), Some(Some(Severity::Hint)));
+        assert_eq!(config.max_loads(), Some(16));
+        assert_eq!(config.max_drivers(), Some(1));
+        assert_eq!(
+            config.lint_severity(Some(LibraryKind::Testbench), "unused"),
+            None
+        );
+        assert_eq!(
+            config.lint_severity(Some(LibraryKind::Rtl), "unused"),
+            Some(Severity::Warning)
+        );
+
+        assert_eq!(
+            Config::from_str(
+                "
+[libraries]
+lib.files = []
+
+[lint]
+max_loads = -1
+",
+                Path::new(""),
+            ),
+            Err("lint.max_loads must be a non-negative integer".to_owned())
+        );
+    }
+
+    #[test]
+    fn test_append_config() {
+        let parent0 = Path::new("parent_folder0");
+        let config0 = Config::from_str(
+            "
+[libraries]
+lib1.files = [
+  'pkg1.vhd',
+]
+lib2.files = [
+  'pkg2.vhd'
+]
+",
+            parent0,
+        )
+        .unwrap();
+
+        let parent1 = Path::new("parent_folder1");
+        let config1 = Config::from_str(
+            "
+[libraries]
+lib2.files = [
+  'ent.vhd'
+]
+lib3.files = [
+  'pkg3.vhd',
+]
+",
+            parent1,
+        )
+        .unwrap();
+
+        let expected_parent = Path::new("");
+        let expected_config = Config::from_str(
+            &format!(
+                "
+[libraries]
+lib1.files = [
+  '{pkg1}',
+]
+lib2.files = [
+  '{ent}'
+]
+lib3.files = [
+  '{pkg3}',
+]
+",
+                pkg1 = parent0.join("pkg1.vhd").to_str().unwrap(),
+                ent = parent1.join("ent.vhd").to_str().unwrap(),
+                pkg3 = parent1.join("pkg3.vhd").to_str().unwrap()
+            ),
+            expected_parent,
+        )
+        .unwrap();
+
+        let mut merged_config = config0;
+        merged_config.append(&config1, &mut Vec::new());
+        assert_eq!(merged_config, expected_config);
+    }
+
+    #[test]
+    fn installed_libraries_are_third_party() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let parent = tempdir.path();
+        let installed = parent.join("installed.toml");
+        std::fs::write(
+            &installed,
+            "
+[libraries]
+ieee.files = ['ieee/*.vhd']
+vital.files = ['vital/*.vhd']
+",
+        )
+        .unwrap();
+        let user = parent.join("vhdl_ls.toml");
+        std::fs::write(
+            &user,
+            "
+[libraries]
+lib.files = ['lib/*.vhd']
+",
+        )
+        .unwrap();
+
+        let mut config = Config::default();
+        let mut messages = Vec::new();
+        config.load_installed_config_file(&installed, &mut messages);
+        config.load_config(&user, "Workspace", &mut messages);
+        assert!(messages
+            .iter()
+            .all(|message| message.message_type == MessageType::Log));
+
+        assert!(config.get_library("ieee").unwrap().is_third_party());
+        assert!(config.get_library("vital").unwrap().is_third_party());
+        assert!(!config.get_library("lib").unwrap().is_third_party());
+    }
+
+    /// Write the configuration files of a project with two dependencies where
+    /// the first dependency also depends on the second
+    fn write_dependency_projects(parent: &Path) -> PathBuf {
+        std::fs::create_dir(parent.join("ip")).unwrap();
+        std::fs::create_dir(parent.join("base")).unwrap();
+        std::fs::write(
+            parent.join("ip").join("vhdl_ls.toml"),
+            "
+dependencies = ['../base/vhdl_ls.toml']
+
+[libraries]
+ip.files = ['*.vhd']
+",
+        )
+        .unwrap();
+        std::fs::write(
+            parent.join("base").join("vhdl_ls.toml"),
+            "
+dependencies = ['../ip/vhdl_ls.toml']
+
+[libraries]
+base.files = ['*.vhd']
+lib.files = ['*.vhd']
+",
+        )
+        .unwrap();
+        let user = parent.join("vhdl_ls.toml");
+        std::fs::write(
+            &user,
+            "
+dependencies = ['ip/vhdl_ls.toml', 'base/vhdl_ls.toml']
+
+[libraries]
+lib.files = ['lib/*.vhd']
+",
+        )
+        .unwrap();
+        user
+    }
+
+    #[test]
+    fn dependencies_are_loaded_recursively_as_third_party() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let user = write_dependency_projects(tempdir.path());
+
+        let mut config = Config::default();
+        let mut messages = Vec::new();
+        config.load_config(&user, "Workspace", &mut messages);
+
+        assert!(config.get_library("ip").unwrap().is_third_party());
+        // Only listed by the dependency of a dependency
+        assert!(config.get_library("base").unwrap().is_third_party());
+        assert!(!config.get_library("lib").unwrap().is_third_party());
+    }
+
+    #[test]
+    fn dependencies_are_loaded_once() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let user = write_dependency_projects(tempdir.path());
+
+        let mut config = Config::default();
+        let mut messages = Vec::new();
+        config.load_config(&user, "Workspace", &mut messages);
+
+        let loaded: Vec<_> = messages
+            .iter()
+            .filter(|message| {
+                message
+                    .message
+                    .starts_with("Loaded dependency configuration file")
+            })
+            .collect();
+        assert_eq!(loaded.len(), 2);
+        assert_eq!(config.dependencies.len(), 2);
+    }
+
+    #[test]
+    fn warning_on_library_of_dependency_that_is_already_defined() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let user = write_dependency_projects(tempdir.path());
+
+        let mut config = Config::default();
+        let mut messages = Vec::new();
+        config.load_config(&user, "Workspace", &mut messages);
+
+        let base = dunce::canonicalize(tempdir.path().join("base").join("vhdl_ls.toml")).unwrap();
+        let warnings: Vec<_> = messages
+            .iter()
+            .filter(|message| message.message_type == MessageType::Warning)
+            .collect();
+        assert_eq!(
+            warnings,
+            vec![&Message::warning(format!(
+                "Library lib of dependency {} is already defined",
+                base.to_string_lossy()
+            ))]
+        );
+        // The library of the project takes precedence
+        assert_eq!(
+            config.get_library("lib").unwrap().patterns,
+            vec![tempdir
+                .path()
+                .join("lib")
+                .join("*.vhd")
+                .to_string_lossy()
+                .to_string()]
+        );
+    }
+
+    #[test]
+    fn test_warning_on_missing_file() {
+        let parent = Path::new("parent_folder");
+        let config = Config::from_str(
+            "
+[libraries]
+lib.files = [
+  'missing.vhd'
+]
+",
+            parent,
+        )
+        .unwrap();
+
+        let mut messages = vec![];
+        let file_names = config.get_library("lib").unwrap().file_names(&mut messages);
+        assert_files_eq(&file_names, &[]);
+        assert_eq!(
+            messages,
+            vec![Message::warning(format!(
+                "File {} does not exist",
+                parent.join("missing.vhd").to_str().unwrap()
+            ))]
+        );
+    }
+
+    #[test]
+    fn test_file_wildcard_pattern() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let parent = tempdir.path();
+        let config = Config::from_str(
+            "
+[libraries]
+lib.files = [
+  '*.vhd'
+]
+",
+            parent,
+        )
+        .unwrap();
+
+        let file1 = touch(parent, "file1.vhd");
+        let file2 = touch(parent, "file2.vhd");
+
+        let mut messages = vec![];
+        let file_names = config.get_library("lib").unwrap().file_names(&mut messages);
+        assert_files_eq(&file_names, &[file1, file2]);
+        assert_eq!(messages, vec![]);
+    }
+
+    #[test]
+    fn test_file_wildcard_pattern_removes_duplicates() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let parent = tempdir.path();
+        let config = Config::from_str(
+            "
+[libraries]
+lib.files = [
+  '*.vhd',
+  'file*.vhd'
+]
+",
+            parent,
+        )
+        .unwrap();
+
+        let file1 = touch(parent, "file1.vhd");
+        let file2 = touch(parent, "file2.vhd");
+
+        let mut messages = vec![];
+        let file_names = config.get_library("lib").unwrap().file_names(&mut messages);
+        assert_files_eq(&file_names, &[file1, file2]);
+        assert_eq!(messages, vec![]);
+    }
+    #[test]
+    fn test_warning_on_emtpy_glob_pattern() {
+        let parent = Path::new("parent_folder");
+        let config = Config::from_str(
+            "
+[libraries]
+lib.files = [
+  'missing*.vhd'
+]
+",
+            parent,
+        )
+        .unwrap();
+
+        let mut messages = vec![];
+        let file_names = config.get_library("lib").unwrap().file_names(&mut messages);
+        assert_files_eq(&file_names, &[]);
+        assert_eq!(
+            messages,
+            vec![Message::warning(format!(
+                "Pattern '{}' did not match any file",
+                parent.join("missing*.vhd").to_str().unwrap()
+            ))]
+        );
+    }
+}
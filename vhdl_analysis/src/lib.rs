@@ -0,0 +1,39 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this file,
+// You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) 2018, Olof Kraigher olof.kraigher@gmail.com
+
+//! The semantic analysis of VHDL, projects and their configuration on top of vhdl_syntax
+#![allow(clippy::upper_case_acronyms)]
+// False positives with unconditional loops
+// allow for now
+#![allow(clippy::vec_init_then_push)]
+
+#[macro_use]
+extern crate vhdl_syntax;
+
+#[macro_use]
+mod analysis;
+mod config;
+mod project;
+
+use vhdl_syntax::{ast, data, format, syntax};
+
+pub use crate::config::{Config, GenericOverrides, GenericValue, LibraryKind, VHDLStandard};
+pub use vhdl_syntax::{
+    select_fixes, Diagnostic, Fix, ItemKind, Latin1String, Message, MessageHandler, MessagePrinter,
+    MessageType, NullDiagnostics, NullMessages, Position, Range, Severity, Source, SourceFixes,
+    SrcPos, TextEdit,
+};
+
+pub use crate::analysis::{
+    AnyEnt, AnyEntKind, ArchitectureFanout, AttributePrefix, Call, CallGraph, CancellationToken,
+    CompletionItem, Concurrent, ConstantValue, Design, ElaboratedInstance, Elaboration,
+    EntHierarchy, EntPositions, EntRef, EntityId, InstantiationForm, ItemAt, Object,
+    ObjectInterface, Overloaded, Partial, PredefinedAttribute, SignalFanout, Type,
+    PREDEFINED_ATTRIBUTES,
+};
+pub use crate::project::{
+    ExtractedDesignUnit, InterfaceChange, LintTarget, Project, SourceFile, UnitChange, UnitDiff,
+};
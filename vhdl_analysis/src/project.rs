@@ -0,0 +1,1888 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this file,
+// You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) 2018, Olof Kraigher olof.kraigher@gmail.com
+
+mod diff;
+
+pub use diff::{InterfaceChange, UnitChange, UnitDiff};
+
+use crate::analysis::{
+    AnyEnt, AnyEntKind, CancellationToken, CompletionItem, ConstantValue, Design, DesignRoot,
+    EntPositions, EntRef, InstantiationForm, ItemAt, Partial, PredefinedAttribute,
+};
+use crate::ast::ancestry::{find_ancestry, Breadcrumb};
+use crate::ast::statement_regions::{find_statement_regions, StatementRegion};
+use crate::ast::test_cases::{find_test_cases, TestCase};
+use crate::ast::{
+    AnyDesignUnit, AnySecondaryUnit, DesignFile, Designator, Expression, HasIdent, HasPrimaryIdent,
+};
+use crate::config::{Config, GenericOverrides, LibraryKind};
+use crate::format::{format_on_type, format_range, format_source, FormatOptions};
+use crate::syntax::{FileMetrics, SignalMetrics, VHDLParser};
+use crate::{data::*, ArchitectureFanout, CallGraph, Elaboration, EntHierarchy, SignalFanout};
+use fnv::{FnvHashMap, FnvHashSet};
+use std::collections::hash_map::Entry;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+/// The files that a lint profile is run over
+pub enum LintTarget {
+    /// The files of a library
+    Library(String),
+    /// The files of RTL or test bench code
+    Kind(LibraryKind),
+}
+
+pub struct Project {
+    parser: VHDLParser,
+    root: DesignRoot,
+    files: FnvHashMap<PathBuf, SourceFile>,
+    empty_libraries: FnvHashSet<Symbol>,
+    // Libraries such as vendor IP whose diagnostics are not reported
+    third_party_libraries: FnvHashSet<Symbol>,
+    // Whether each configured file contains RTL or test bench code
+    file_kinds: FnvHashMap<PathBuf, LibraryKind>,
+    // The severity of each configurable lint within code of a kind
+    lint_severities: FnvHashMap<(Option<LibraryKind>, &'static str), Option<Severity>>,
+    format_options: FormatOptions,
+}
+
+impl Project {
+    pub fn new() -> Project {
+        let parser = VHDLParser::default();
+        Project {
+            root: DesignRoot::new(parser.symbols.clone()),
+            files: FnvHashMap::default(),
+            empty_libraries: FnvHashSet::default(),
+            third_party_libraries: FnvHashSet::default(),
+            file_kinds: FnvHashMap::default(),
+            lint_severities: FnvHashMap::default(),
+            format_options: FormatOptions::default(),
+            parser,
+        }
+    }
+
+    /// Create instance from given configuration.
+    /// Files referred by configuration are parsed into corresponding libraries.
+    pub fn from_config(config: &Config, messages: &mut dyn MessageHandler) -> Project {
+        let mut project = Project::new();
+        project.root.set_standard(config.standard());
+        project.root.set_warn_recursion(config.warn_recursion());
+        project.configure_lints(config);
+        project.format_options = config.format_options();
+        project
+            .root
+            .set_explain_overloads(config.explain_overloads());
+        project.load_generic_overrides(config, messages);
+
+        let files = project.load_files_from_config(config, messages);
+        project.parse_and_add_files(files, config, messages);
+
+        project
+    }
+
+    /// Replace active project configuration.
+    /// The design state is reset, new files are added and parsed. Existing source files will be
+    /// kept and parsed from in-memory source (required for incremental document updates).
+    pub fn update_config(&mut self, config: &Config, messages: &mut dyn MessageHandler) {
+        self.parser = VHDLParser::default();
+        self.root = DesignRoot::new(self.parser.symbols.clone());
+        self.root.set_standard(config.standard());
+        self.root.set_warn_recursion(config.warn_recursion());
+        self.configure_lints(config);
+        self.format_options = config.format_options();
+        self.root.set_explain_overloads(config.explain_overloads());
+        self.load_generic_overrides(config, messages);
+
+        // Reset library associations for known files,
+        // all project files are added to the corresponding libraries later on.
+        self.files
+            .values_mut()
+            .for_each(|source_file| source_file.library_names.clear());
+
+        // Files might already be part of self.files, these have to be parsed
+        // from in-memory source. New files can be parsed as usual.
+        let (known_files, new_files) = self
+            .load_files_from_config(config, messages)
+            .into_iter()
+            .partition(|(file_name, _library_names)| self.files.contains_key(file_name));
+
+        for (file_name, library_names) in known_files {
+            if let Some(source_file) = self.files.get_mut(&file_name) {
+                source_file.parser_diagnostics.clear();
+                source_file.library_names = library_names;
+                source_file.design_file = self
+                    .parser
+                    .parse_design_source(&source_file.source, &mut source_file.parser_diagnostics);
+            }
+        }
+
+        self.parse_and_add_files(new_files, config, messages);
+    }
+
+    /// Lints run with the severity of the project and each report then takes
+    /// the severity configured for the kind of its file
+    fn configure_lints(&mut self, config: &Config) {
+        self.lint_severities.clear();
+        for kind in [None, Some(LibraryKind::Rtl), Some(LibraryKind::Testbench)] {
+            for lint in ["unused", "synthesis"] {
+                self.lint_severities
+                    .insert((kind, lint), config.lint_severity(kind, lint));
+            }
+        }
+
+        let unused = self
+            .lint_severities
+            .iter()
+            .any(|((_, lint), severity)| *lint == "unused" && severity.is_some());
+        self.root
+            .set_unused_declarations(unused.then_some(Severity::Hint));
+
+        if let Some(severity) = config.dead_generate() {
+            self.root.set_dead_generate(severity);
+        }
+        self.root
+            .set_fanout_limits(config.max_loads(), config.max_drivers());
+    }
+
+    /// Read the generic values of top level entities from the configured generics file
+    fn load_generic_overrides(&mut self, config: &Config, messages: &mut dyn MessageHandler) {
+        if let Some(file_name) = config.generics_file() {
+            match GenericOverrides::read_file_path(file_name) {
+                Ok(overrides) => self.root.set_generic_overrides(overrides),
+                Err(err) => messages.push(Message::file_error(err.to_string(), file_name)),
+            }
+        }
+    }
+
+    /// Set the generic values of top level entities used when evaluating constants
+    pub fn set_generic_overrides(&mut self, overrides: GenericOverrides) {
+        self.root.set_generic_overrides(overrides);
+    }
+
+    fn load_files_from_config(
+        &mut self,
+        config: &Config,
+        messages: &mut dyn MessageHandler,
+    ) -> FnvHashMap<PathBuf, FnvHashSet<Symbol>> {
+        let mut files: FnvHashMap<PathBuf, FnvHashSet<Symbol>> = FnvHashMap::default();
+        self.empty_libraries.clear();
+        self.third_party_libraries.clear();
+        self.file_kinds.clear();
+        let mut library_kinds = FnvHashMap::default();
+
+        for library in config.iter_libraries() {
+            let library_name =
+                Latin1String::from_utf8(library.name()).expect("Library name not latin-1 encoded");
+            let library_name = self.parser.symbol(&library_name);
+
+            if library.is_third_party() {
+                self.third_party_libraries.insert(library_name.clone());
+            }
+            if let Some(kind) = library.kind() {
+                library_kinds.insert(library_name.clone(), kind);
+            }
+
+            let mut empty_library = true;
+            for file_name in library.file_names(messages) {
+                empty_library = false;
+
+                match files.entry(file_name.clone()) {
+                    Entry::Occupied(mut entry) => {
+                        entry.get_mut().insert(library_name.clone());
+                    }
+                    Entry::Vacant(entry) => {
+                        let mut set = FnvHashSet::default();
+                        set.insert(library_name.clone());
+                        entry.insert(set);
+                    }
+                }
+            }
+
+            if empty_library {
+                self.empty_libraries.insert(library_name);
+            }
+        }
+
+        // A file pattern takes precedence over the kind of the libraries of a file
+        // and a file of both an RTL and a test bench library is RTL code
+        let mut synthesis_libraries = FnvHashSet::default();
+        for (file_name, library_names) in files.iter() {
+            let kind = config.kind_of(file_name).or_else(|| {
+                let kinds: FnvHashSet<_> = library_names
+                    .iter()
+                    .filter_map(|library_name| library_kinds.get(library_name).copied())
+                    .collect();
+                if kinds.contains(&LibraryKind::Rtl) {
+                    Some(LibraryKind::Rtl)
+                } else {
+                    kinds.into_iter().next()
+                }
+            });
+            if let Some(kind) = kind {
+                self.file_kinds.insert(file_name.clone(), kind);
+            }
+            if config.lint_severity(kind, "synthesis").is_some() {
+                synthesis_libraries.extend(library_names.iter().cloned());
+            }
+        }
+        self.root.set_synthesis_libraries(synthesis_libraries);
+        files
+    }
+
+    fn parse_and_add_files(
+        &mut self,
+        files_to_parse: FnvHashMap<PathBuf, FnvHashSet<Symbol>>,
+        config: &Config,
+        messages: &mut dyn MessageHandler,
+    ) {
+        use rayon::prelude::*;
+
+        let parsed: Vec<_> = files_to_parse
+            .into_par_iter()
+            .map_init(
+                || &self.parser,
+                |parser, (file_name, library_names)| {
+                    let mut diagnostics = Vec::new();
+                    let result = parser.parse_design_file(
+                        &file_name,
+                        config.encoding_of(&file_name),
+                        &mut diagnostics,
+                    );
+                    (file_name, library_names, diagnostics, result)
+                },
+            )
+            .collect();
+
+        for (file_name, library_names, parser_diagnostics, result) in parsed.into_iter() {
+            let (source, design_file) = match result {
+                Ok(result) => result,
+                Err(err) => {
+                    messages.push(Message::file_error(err.to_string(), &file_name));
+                    continue;
+                }
+            };
+
+            self.files.insert(
+                source.file_name().to_owned(),
+                SourceFile {
+                    source,
+                    library_names,
+                    parser_diagnostics,
+                    design_file,
+                },
+            );
+        }
+    }
+
+    pub fn library_mapping_of(&self, source: &Source) -> Vec<Symbol> {
+        let file = if let Some(file) = self.files.get(source.file_name()) {
+            file
+        } else {
+            return Vec::new();
+        };
+        let mut libs: Vec<_> = file.library_names.iter().cloned().collect();
+        libs.sort_by_key(|lib| lib.name_utf8());
+        libs
+    }
+
+    pub fn get_source(&self, file_name: &Path) -> Option<Source> {
+        self.files.get(file_name).map(|file| file.source.clone())
+    }
+
+    /// Returns true when parsing the source reported any error
+    pub fn has_parse_errors(&self, source: &Source) -> bool {
+        self.files.get(source.file_name()).is_some_and(|file| {
+            file.parser_diagnostics
+                .iter()
+                .any(|diagnostic| diagnostic.severity == Severity::Error)
+        })
+    }
+
+    pub fn update_source(&mut self, source: &Source) {
+        let mut source_file = {
+            if let Some(mut source_file) = self.files.remove(source.file_name()) {
+                // File is already part of the project
+                for library_name in source_file.library_names.iter() {
+                    self.root.remove_source(library_name.clone(), source);
+                }
+                source_file.source = source.clone();
+                source_file
+            } else {
+                // File is not part of the project
+                // @TODO use config wildcards to map to library
+
+                // Add unmapped files to an anonymous library work
+                // To still get some semantic analysis for unmapped files
+                let mut library_names = FnvHashSet::default();
+                library_names.insert(self.root.symbol_utf8("work"));
+
+                SourceFile {
+                    source: source.clone(),
+                    library_names,
+                    parser_diagnostics: vec![],
+                    design_file: DesignFile::default(),
+                }
+            }
+        };
+        source_file.parser_diagnostics.clear();
+        source_file.design_file = self
+            .parser
+            .parse_design_source(source, &mut source_file.parser_diagnostics);
+        self.files
+            .insert(source.file_name().to_owned(), source_file);
+    }
+
+    /// Move a file to a new file name keeping the libraries it belongs to
+    /// Returns false when the file is not part of the project
+    pub fn rename_file(&mut self, old_file_name: &Path, new_file_name: &Path) -> bool {
+        let source_file = if let Some(source_file) = self.files.remove(old_file_name) {
+            source_file
+        } else {
+            return false;
+        };
+        for library_name in source_file.library_names.iter() {
+            self.root
+                .remove_source(library_name.clone(), &source_file.source);
+        }
+
+        let text = {
+            let contents = source_file.source.contents();
+            contents.text_in(Range::new(contents.start(), contents.end()))
+        };
+        let source = Source::inline(new_file_name, &text);
+        self.files.insert(
+            new_file_name.to_owned(),
+            SourceFile {
+                source: source.clone(),
+                library_names: source_file.library_names,
+                parser_diagnostics: vec![],
+                design_file: DesignFile::default(),
+            },
+        );
+        self.update_source(&source);
+        true
+    }
+
+    /// Add a source that was extracted from the file `origin` to the same libraries as `origin`
+    pub fn add_extracted_source(&mut self, source: &Source, origin: &Path) {
+        if !self.files.contains_key(source.file_name()) {
+            if let Some(library_names) = self
+                .files
+                .get(origin)
+                .map(|file| file.library_names.clone())
+            {
+                self.files.insert(
+                    source.file_name().to_owned(),
+                    SourceFile {
+                        source: source.clone(),
+                        library_names,
+                        parser_diagnostics: vec![],
+                        design_file: DesignFile::default(),
+                    },
+                );
+            }
+        }
+        self.update_source(source);
+    }
+
+    /// Apply the text edits of one or more source files of the project and analyze the result
+    /// Either all edits are applied or none when some edit is not valid
+    pub fn apply_edits(&mut self, edits: &[TextEdit]) -> Result<Vec<Diagnostic>, String> {
+        let mut by_file: FnvHashMap<&Path, Vec<&TextEdit>> = FnvHashMap::default();
+        for edit in edits.iter() {
+            let file_name = edit.pos.source.file_name();
+            if !self.files.contains_key(file_name) {
+                return Err(format!(
+                    "{} is not part of the project",
+                    file_name.to_string_lossy()
+                ));
+            }
+            by_file.entry(file_name).or_default().push(edit);
+        }
+
+        let mut changes = Vec::with_capacity(by_file.len());
+        for (file_name, edits) in by_file.into_iter() {
+            if edits_overlap_any(&edits) {
+                return Err(format!(
+                    "Overlapping edits of {}",
+                    file_name.to_string_lossy()
+                ));
+            }
+            let source = self.files[file_name].source.clone();
+            let text = {
+                let contents = source.contents();
+                contents.text_in(Range::new(contents.start(), contents.end()))
+            };
+            let edits: Vec<TextEdit> = edits.into_iter().cloned().collect();
+            changes.push((source, apply_edits(&text, &edits)));
+        }
+
+        for (source, text) in changes {
+            source.change(None, &text);
+            self.update_source(&source);
+        }
+        Ok(self.analyse())
+    }
+
+    pub fn analyse(&mut self) -> Vec<Diagnostic> {
+        self.analyse_cancellable(&CancellationToken::new())
+            .unwrap_or_default()
+    }
+
+    /// Analyse the project until the token is cancelled, None when cancelled
+    /// such as when the sources are changed again while being analysed
+    pub fn analyse_cancellable(&mut self, token: &CancellationToken) -> Option<Vec<Diagnostic>> {
+        let mut diagnostics = Vec::new();
+
+        for source_file in self.files.values_mut() {
+            let design_file = source_file.take_design_file();
+            // Avoid cloning design files for single library
+            let mut design_files = multiply(design_file, source_file.library_names.len());
+
+            for library_name in source_file.library_names.iter() {
+                let design_file = design_files.pop().unwrap();
+                self.root.add_design_file(library_name.clone(), design_file);
+            }
+
+            for diagnostic in source_file.parser_diagnostics.iter().cloned() {
+                diagnostics.push(diagnostic);
+            }
+        }
+
+        for library_name in self.empty_libraries.iter() {
+            self.root.ensure_library(library_name.clone());
+        }
+
+        if !self.root.analyze_cancellable(token, &mut diagnostics) {
+            return None;
+        }
+
+        // Third party files are still analyzed to support navigation into them
+        // but their diagnostics are only reported when they are also mapped to another library
+        let third_party_files: FnvHashSet<_> = self
+            .files
+            .iter()
+            .filter(|(_, source_file)| self.is_third_party(source_file))
+            .map(|(file_name, _)| file_name.as_path())
+            .collect();
+
+        if !third_party_files.is_empty() {
+            diagnostics.retain(|diagnostic| {
+                !third_party_files.contains(diagnostic.pos.source.file_name())
+            });
+        }
+
+        if !self.lint_severities.is_empty() {
+            diagnostics = diagnostics
+                .into_iter()
+                .filter_map(|mut diagnostic| {
+                    if let Some(lint) = diagnostic.code.and_then(lint_of_code) {
+                        let kind = self.kind_of(diagnostic.pos.source.file_name());
+                        diagnostic.severity =
+                            self.lint_severities.get(&(kind, lint)).copied()??;
+                    }
+                    Some(diagnostic)
+                })
+                .collect();
+        }
+        Some(diagnostics)
+    }
+
+    /// Run the lints of a profile over the files of a library or of a kind regardless of the
+    /// lints configured for these files and return their diagnostics
+    /// The profiles `rtl` and `testbench` run the lints with the severities configured for
+    /// that kind of code and the profile `synthesis` only runs the synthesis checks of RTL code
+    pub fn run_lint_profile(
+        &mut self,
+        profile: &str,
+        target: &LintTarget,
+    ) -> Result<Vec<Diagnostic>, String> {
+        let (unused, synthesis) = match profile {
+            "synthesis" => (
+                None,
+                self.lint_severity(Some(LibraryKind::Rtl), "synthesis"),
+            ),
+            _ => {
+                let kind = Some(profile.parse::<LibraryKind>()?);
+                (
+                    self.lint_severity(kind, "unused"),
+                    self.lint_severity(kind, "synthesis"),
+                )
+            }
+        };
+
+        let files: FnvHashSet<PathBuf> = self
+            .files
+            .iter()
+            .filter(|(file_name, source_file)| match target {
+                LintTarget::Library(name) => source_file
+                    .library_names
+                    .iter()
+                    .any(|library_name| library_name.name_utf8().eq_ignore_ascii_case(name)),
+                LintTarget::Kind(kind) => self.kind_of(file_name) == Some(*kind),
+            })
+            .map(|(file_name, _)| file_name.clone())
+            .collect();
+        if files.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        // The lints search the analyzed design units
+        self.analyse();
+        let mut diagnostics = self.root.run_lints(unused, synthesis);
+        diagnostics.retain(|diagnostic| files.contains(diagnostic.pos.source.file_name()));
+        Ok(diagnostics)
+    }
+
+    /// The severity of a lint within code of a kind, the defaults apply when not configured
+    fn lint_severity(&self, kind: Option<LibraryKind>, lint: &'static str) -> Option<Severity> {
+        match self.lint_severities.get(&(kind, lint)) {
+            Some(severity) => *severity,
+            None => Config::default().lint_severity(kind, lint),
+        }
+    }
+
+    /// Whether a configured file contains RTL or test bench code, a matching file
+    /// pattern takes precedence over the kind of its library
+    pub fn kind_of(&self, file_name: &Path) -> Option<LibraryKind> {
+        self.file_kinds.get(file_name).copied()
+    }
+
+    fn is_third_party(&self, source_file: &SourceFile) -> bool {
+        !source_file.library_names.is_empty()
+            && source_file
+                .library_names
+                .iter()
+                .all(|library_name| self.third_party_libraries.contains(library_name))
+    }
+
+    /// Search for reference at position
+    /// Character offset on a line in a document (zero-based). Assuming that the line is
+    /// represented as a string, the `character` value represents the gap between the
+    /// `character` and `character + 1`.
+    ///
+    /// If the character value is greater than the line length it defaults back to the
+    /// line length.
+    pub fn find_definition<'a>(&'a self, source: &Source, cursor: Position) -> Option<EntRef<'a>> {
+        let ent = self.root.search_reference(source, cursor)?;
+        self.root.find_definition_of(ent)
+    }
+
+    pub fn find_declaration<'a>(&'a self, source: &Source, cursor: Position) -> Option<EntRef<'a>> {
+        let ent = self.root.search_reference(source, cursor)?;
+        Some(ent.declaration())
+    }
+
+    /// The positions of the declaration and the definition of a named entity such as
+    /// a public symbol, they differ when the entity has a separate body
+    pub fn positions_of(&self, ent: EntRef) -> Option<EntPositions> {
+        self.root.positions_of(ent)
+    }
+
+    pub fn item_at_cursor<'a>(
+        &'a self,
+        source: &Source,
+        cursor: Position,
+    ) -> Option<(SrcPos, EntRef<'a>)> {
+        self.root.item_at_cursor(source, cursor)
+    }
+
+    /// Format a source with the options of the configuration
+    pub fn format(&self, source: &Source) -> Result<String, Diagnostic> {
+        format_source(source, &self.format_options)
+    }
+
+    /// The edits formatting the lines within a range of a source
+    pub fn format_range(&self, source: &Source, range: Range) -> Result<Vec<TextEdit>, Diagnostic> {
+        format_range(source, &self.format_options, range)
+    }
+
+    /// The edits after typing the end of a construct at the cursor
+    pub fn format_on_type(
+        &self,
+        source: &Source,
+        cursor: Position,
+    ) -> Result<Vec<TextEdit>, Diagnostic> {
+        format_on_type(source, &self.format_options, cursor)
+    }
+
+    /// The token at the cursor with the kind of its syntax node and the entity it denotes
+    /// such that integrations need not search the syntax tree themselves
+    pub fn item_at<'a>(&'a self, source: &Source, cursor: Position) -> Option<ItemAt<'a>> {
+        self.root.item_at(source, cursor)
+    }
+
+    /// The formals associated with the positional actuals of calls and instances in a source
+    pub fn positional_formals<'a>(&'a self, source: &Source) -> Vec<(SrcPos, EntRef<'a>)> {
+        self.root.positional_formals(source)
+    }
+
+    /// The resolved declarations and names of a source ordered by their position
+    pub fn items_in<'a>(&'a self, source: &Source) -> Vec<ItemAt<'a>> {
+        self.root.items_in(source)
+    }
+
+    // Find symbols that are public such as primary design units and their interfaces
+    pub fn public_symbols<'a>(&'a self) -> Box<dyn Iterator<Item = EntRef<'a>> + 'a> {
+        self.root.public_symbols()
+    }
+
+    // Find symbols that are public such as primary design units and their interfaces
+    pub fn document_symbols<'a>(
+        &'a self,
+        library_name: &Symbol,
+        source: &Source,
+    ) -> Vec<EntHierarchy<'a>> {
+        self.root.document_symbols(library_name, source)
+    }
+
+    pub fn find_implementation<'a>(&'a self, source: &Source, cursor: Position) -> Vec<EntRef<'a>> {
+        if let Some(ent) = self.find_declaration(source, cursor) {
+            self.root.find_implementation(ent)
+        } else {
+            Vec::default()
+        }
+    }
+
+    /// Search for the declaration at decl_pos and format it
+    pub fn format_declaration(&self, ent: &AnyEnt) -> Option<String> {
+        self.root.format_declaration(ent)
+    }
+
+    /// The libraries that the work library alias at the cursor denotes
+    pub fn work_libraries_at(&self, source: &Source, cursor: Position) -> Vec<Symbol> {
+        self.root.work_libraries_at(source, cursor)
+    }
+
+    /// Evaluate a static expression built from literals, predefined operators and the
+    /// constants of the analyzed design
+    pub fn evaluate_expression(&self, expr: &Expression) -> Option<ConstantValue> {
+        self.root.evaluate_expression(expr)
+    }
+
+    /// Search for all references to the declaration at decl_pos
+    pub fn find_all_references(&self, ent: &AnyEnt) -> Vec<SrcPos> {
+        self.root.find_all_references(ent)
+    }
+
+    /// Search for all references to the declaration until the deadline has passed
+    /// The result is marked as incomplete when the search was stopped early
+    pub fn find_all_references_until(
+        &self,
+        ent: &AnyEnt,
+        deadline: Instant,
+    ) -> Partial<Vec<SrcPos>> {
+        self.root.find_all_references_until(ent, deadline)
+    }
+
+    /// Edits that change the positional associations of the instance at the cursor
+    /// into named associations
+    pub fn named_associations(&self, source: &Source, cursor: Position) -> Option<Vec<TextEdit>> {
+        self.root.named_associations(source, cursor)
+    }
+
+    /// The entity and components which are bound to `ent` by default binding
+    pub fn bound_by_default<'a>(&'a self, ent: EntRef<'a>) -> Vec<EntRef<'a>> {
+        self.root.bound_by_default(ent)
+    }
+
+    /// Search for all references to library `old_name` that must change to rename it to `new_name`
+    /// An error message is returned when the library cannot be renamed
+    pub fn rename_library(&self, old_name: &str, new_name: &str) -> Result<Vec<SrcPos>, String> {
+        if !is_basic_identifier(new_name) {
+            return Err(format!("'{new_name}' is not a valid library name"));
+        }
+
+        for name in [old_name, new_name] {
+            if name.eq_ignore_ascii_case("work") {
+                return Err("The work library cannot be renamed".to_owned());
+            }
+        }
+
+        let new_sym = self.root.symbol_utf8(new_name);
+        if self.root.find_library_references(&new_sym).is_some() {
+            return Err(format!("Library '{new_name}' already exists"));
+        }
+
+        self.root
+            .find_library_references(&self.root.symbol_utf8(old_name))
+            .ok_or_else(|| format!("No such library '{old_name}'"))
+    }
+
+    /// The names of the architectures of an entity and the positions where they are declared
+    pub fn architectures_of(&self, entity: &AnyEnt) -> Vec<(Symbol, SrcPos)> {
+        match (entity.library_name(), entity.designator()) {
+            (Some(library_name), Designator::Identifier(entity_name)) => {
+                self.root.architectures_of(library_name, entity_name)
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    /// Extract the design unit at the cursor into a new file next to the source file
+    /// The design unit is moved together with its context clause
+    /// Returns None when the design unit is the only one in the file
+    pub fn extract_design_unit(
+        &self,
+        source: &Source,
+        cursor: Position,
+    ) -> Option<ExtractedDesignUnit> {
+        let design_file = self
+            .parser
+            .parse_design_source(source, &mut NullDiagnostics);
+
+        if design_file.design_units.len() < 2 {
+            return None;
+        }
+
+        let (unit, span) = design_file
+            .design_units
+            .iter()
+            .zip(design_file.unit_spans.iter())
+            .find(|(_, span)| span.start() <= cursor && cursor <= span.end())?;
+
+        let extension = source
+            .file_name()
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .unwrap_or("vhd");
+        let file_name = source
+            .file_name()
+            .with_file_name(format!("{}.{extension}", design_unit_file_stem(unit)));
+
+        if file_name.exists() || self.files.contains_key(&file_name) {
+            return None;
+        }
+
+        let contents = source.contents();
+        let mut end = span.end();
+        let rest_of_line = contents
+            .get_line(end.line as usize)
+            .map(|line| {
+                line.chars()
+                    .skip(end.character as usize)
+                    .collect::<String>()
+            })
+            .unwrap_or_default();
+        if rest_of_line.trim().is_empty() {
+            end = if (end.line as usize) + 1 < contents.num_lines() {
+                Position::new(end.line + 1, 0)
+            } else {
+                contents.end()
+            };
+        }
+
+        Some(ExtractedDesignUnit {
+            file_name,
+            contents: format!("{}\n", contents.text_in(span.range())),
+            range: Range::new(span.start(), end),
+        })
+    }
+
+    /// Rewrite the component declaration at the cursor to match the generics and ports
+    /// of the entity of the same name, returns None when they already agree
+    pub fn sync_component(&self, source: &Source, cursor: Position) -> Option<(SrcPos, String)> {
+        let (_, ent) = self.root.item_at_cursor(source, cursor)?;
+        self.root.sync_component_with_entity(ent)
+    }
+
+    /// Group the ports of the entity at the cursor by mode
+    /// Returns the edits of the entity and its instances, or None when the ports are already grouped
+    pub fn group_ports(&self, source: &Source, cursor: Position) -> Option<Vec<TextEdit>> {
+        let (_, ent) = self.root.item_at_cursor(source, cursor)?;
+        self.root.group_ports_by_mode(ent)
+    }
+
+    /// Find the VUnit and OSVVM test cases in the testbench architectures of the source
+    pub fn test_cases(&self, source: &Source) -> Vec<TestCase> {
+        let design_file = self
+            .parser
+            .parse_design_source(source, &mut NullDiagnostics);
+        find_test_cases(&design_file)
+    }
+
+    /// Find the concurrent statements of the entities and architectures of the source
+    pub fn statement_regions(&self, source: &Source) -> Vec<StatementRegion> {
+        let design_file = self
+            .parser
+            .parse_design_source(source, &mut NullDiagnostics);
+        find_statement_regions(&design_file)
+    }
+
+    /// The text of an instance of the entity in the library with all generics and ports
+    /// associated by name, such that it can be pasted into an architecture
+    pub fn instantiation_text(
+        &self,
+        library_name: &str,
+        entity_name: &str,
+        form: InstantiationForm,
+    ) -> Option<String> {
+        let entity = self.root.get_design_entity(
+            &self.root.symbol_utf8(library_name),
+            &self.root.symbol_utf8(entity_name),
+        )?;
+        self.root.instantiation_text(entity.into(), form)
+    }
+
+    /// The instantiation text as a snippet with placeholders for the label and the actuals
+    pub fn instantiation_snippet(
+        &self,
+        library_name: &str,
+        entity_name: &str,
+        form: InstantiationForm,
+    ) -> Option<String> {
+        let entity = self.root.get_design_entity(
+            &self.root.symbol_utf8(library_name),
+            &self.root.symbol_utf8(entity_name),
+        )?;
+        self.root.instantiation_snippet(entity.into(), form)
+    }
+
+    /// The declarations of the generic package instance at the cursor with the actuals
+    /// of the generic map substituted for the generics
+    pub fn expand_package_instance(&self, source: &Source, cursor: Position) -> Option<String> {
+        let instance = self.find_declaration(source, cursor)?;
+        self.root.expand_package_instance(instance)
+    }
+
+    /// The predefined attribute whose designator is at the cursor such as `length` in `arr'length`
+    pub fn predefined_attribute_at(
+        &self,
+        source: &Source,
+        cursor: Position,
+    ) -> Option<&'static PredefinedAttribute> {
+        self.root.predefined_attribute_at(source, cursor)
+    }
+
+    /// Find the constructs enclosing the cursor such as design units, processes and if statements
+    pub fn breadcrumbs(&self, source: &Source, cursor: Position) -> Vec<Breadcrumb> {
+        let design_file = self
+            .parser
+            .parse_design_source(source, &mut NullDiagnostics);
+        find_ancestry(&design_file, cursor)
+    }
+
+    /// List the names that complete the library, package or declaration name being typed
+    /// at the cursor in a library clause, use clause or context reference
+    /// or the enumeration literal being typed as the argument of a call
+    pub fn list_completion_options(
+        &self,
+        source: &Source,
+        cursor: Position,
+    ) -> Vec<CompletionItem<'_>> {
+        let mut context = if let Some(context) = self.parser.completion_context(source, cursor) {
+            context
+        } else {
+            return Vec::new();
+        };
+
+        // The work library is the library of the source itself
+        if let Some(first) = context.prefix.first_mut() {
+            if first.name_utf8().eq_ignore_ascii_case("work") {
+                if let Some(library_name) = self.library_mapping_of(source).into_iter().next() {
+                    *first = library_name;
+                }
+            }
+        }
+
+        self.root.list_completion_options(&context)
+    }
+
+    /// The graph of calls between subprograms, requires that the project has been analyzed
+    pub fn call_graph(&self) -> CallGraph<'_> {
+        self.root.call_graph()
+    }
+
+    /// The library and file name of each source file in an order that they can be compiled,
+    /// requires that the project has been analyzed
+    pub fn compile_order(&self) -> Vec<(String, PathBuf)> {
+        self.root
+            .compile_order()
+            .into_iter()
+            .map(|(library_name, source)| (library_name.name_utf8(), source.file_name().to_owned()))
+            .collect()
+    }
+
+    /// The hierarchy of instances below a top level entity written as `lib.ent(arch)`
+    /// where the library and the architecture are optional, requires that the project has been analyzed
+    pub fn elaborate(&self, top: &str) -> Result<Elaboration, String> {
+        self.root.elaborate(top)
+    }
+
+    /// The number of drivers and loads of the signals used within each architecture,
+    /// requires that the project has been analyzed
+    pub fn signal_fanout(&self) -> Vec<ArchitectureFanout<'_>> {
+        self.root.signal_fanout()
+    }
+
+    /// The number of drivers and loads of a signal within each architecture where it is visible
+    pub fn fanout_of(&self, signal: EntRef) -> Vec<(EntRef<'_>, SignalFanout<'_>)> {
+        self.root.fanout_of(signal)
+    }
+
+    /// Size metrics of each source file sorted by file name
+    /// The architectures include the drivers and loads of their signals when the project has been analyzed
+    pub fn metrics(&self) -> Vec<FileMetrics> {
+        let mut metrics: Vec<_> = self
+            .files
+            .values()
+            .map(|source_file| {
+                let mut metrics = self.parser.metrics(&source_file.source);
+                metrics.testbench =
+                    self.kind_of(source_file.source.file_name()) == Some(LibraryKind::Testbench);
+                metrics
+            })
+            .collect();
+        metrics.sort_by(|x, y| x.file_name.cmp(&y.file_name));
+
+        for fanout in self.root.signal_fanout() {
+            let architecture = fanout.architecture;
+            let (file_name, entity) = match (architecture.decl_pos(), architecture.kind()) {
+                (Some(pos), AnyEntKind::Design(Design::Architecture(entity))) => {
+                    (pos.source.file_name(), entity)
+                }
+                _ => continue,
+            };
+            let name = format!("{} of {}", architecture.designator(), entity.designator());
+            let unit = metrics
+                .iter_mut()
+                .filter(|file| file.file_name == file_name)
+                .flat_map(|file| file.units.iter_mut())
+                .find(|unit| unit.kind == "architecture" && unit.name.eq_ignore_ascii_case(&name));
+            if let Some(unit) = unit {
+                unit.signals = fanout
+                    .signals
+                    .iter()
+                    .map(|signal| SignalMetrics {
+                        name: signal.signal.designator().to_string(),
+                        drivers: signal.drivers,
+                        loads: signal.loads,
+                    })
+                    .collect();
+            }
+        }
+        metrics
+    }
+
+    /// Get source positions that are not resolved to a declaration
+    /// This is used for development to test where the language server is blind
+    pub fn find_all_unresolved(&self) -> (usize, Vec<SrcPos>) {
+        self.root.find_all_unresolved()
+    }
+
+    pub fn files(&self) -> impl Iterator<Item = &SourceFile> {
+        self.files.values()
+    }
+}
+
+/// The name of the file that a design unit is extracted to without extension
+fn design_unit_file_stem(unit: &AnyDesignUnit) -> String {
+    match unit {
+        AnyDesignUnit::Primary(unit) => unit.ident().item.name_utf8(),
+        AnyDesignUnit::Secondary(AnySecondaryUnit::Architecture(unit)) => format!(
+            "{}_{}",
+            unit.primary_ident().item.name_utf8(),
+            unit.ident().item.name_utf8()
+        ),
+        AnyDesignUnit::Secondary(AnySecondaryUnit::PackageBody(unit)) => {
+            format!("{}_body", unit.ident().item.name_utf8())
+        }
+    }
+}
+
+/// LRM 15.4.2 Basic identifiers
+fn is_basic_identifier(name: &str) -> bool {
+    name.starts_with(|chr: char| chr.is_ascii_alphabetic())
+        && !name.ends_with('_')
+        && !name.contains("__")
+        && name
+            .chars()
+            .all(|chr| chr.is_ascii_alphanumeric() || chr == '_')
+}
+
+/// The configurable lint that reports a diagnostic code
+fn lint_of_code(code: &str) -> Option<&'static str> {
+    match code {
+        "unused-declaration" | "signal-never-read" | "signal-never-assigned" => Some("unused"),
+        _ if code.starts_with("synthesis-") => Some("synthesis"),
+        _ => None,
+    }
+}
+
+/// Multiply clonable value by cloning
+/// Avoid clone for n=1
+fn multiply<T: Clone>(value: T, n: usize) -> Vec<T> {
+    if n == 0 {
+        vec![]
+    } else if n == 1 {
+        vec![value]
+    } else {
+        let mut res = Vec::with_capacity(n);
+        for _ in 0..n - 1 {
+            res.push(value.clone());
+        }
+        res.push(value);
+        res
+    }
+}
+
+impl Default for Project {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A design unit extracted from a file into a new file
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct ExtractedDesignUnit {
+    /// The name of the new file
+    pub file_name: PathBuf,
+    /// The contents of the new file
+    pub contents: String,
+    /// The range of the design unit to remove from the original file
+    pub range: Range,
+}
+
+pub struct SourceFile {
+    library_names: FnvHashSet<Symbol>,
+    source: Source,
+    design_file: DesignFile,
+    parser_diagnostics: Vec<Diagnostic>,
+}
+
+impl SourceFile {
+    fn take_design_file(&mut self) -> DesignFile {
+        std::mem::take(&mut self.design_file)
+    }
+
+    pub fn num_lines(&self) -> usize {
+        self.source.contents().num_lines()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::syntax::test::check_no_diagnostics;
+
+    /// Test that an empty library is created
+    /// Thus test case was added when fixing a bug
+    /// Where a library with no files was never added
+    #[test]
+    fn test_empty_library_is_defined() {
+        let root = tempfile::tempdir().unwrap();
+        let vhdl_file_path = root.path().join("file.vhd");
+        std::fs::write(
+            vhdl_file_path,
+            "
+library missing;
+
+entity ent is
+end entity;
+        ",
+        )
+        .unwrap();
+
+        let config_str = "
+[libraries]
+missing.files = []
+lib.files = ['file.vhd']
+        ";
+
+        let config = Config::from_str(config_str, root.path()).unwrap();
+        let mut messages = Vec::new();
+        let mut project = Project::from_config(&config, &mut messages);
+        assert_eq!(messages, vec![]);
+        check_no_diagnostics(&project.analyse());
+    }
+
+    #[test]
+    fn unmapped_libraries_are_analyzed() {
+        let mut messages = Vec::new();
+        let mut project = Project::from_config(&Config::default(), &mut messages);
+        assert_eq!(messages, vec![]);
+        let diagnostics = project.analyse();
+        check_no_diagnostics(&diagnostics);
+
+        let root = tempfile::tempdir().unwrap();
+        let vhdl_file_path = root.path().join("file.vhd");
+        std::fs::write(
+            &vhdl_file_path,
+            "
+entity ent is
+end ent;
+
+architecture rtl of ent is
+begin
+end architecture;
+
+architecture rtl of ent is
+begin
+end architecture;
+",
+        )
+        .unwrap();
+        let source = Source::from_latin1_file(&vhdl_file_path).unwrap();
+
+        project.update_source(&source);
+        let diagnostics = project.analyse();
+        assert_eq!(diagnostics.len(), 1);
+        let diag = diagnostics.first().unwrap();
+        assert_eq!(diag.message, "Duplicate architecture 'rtl' of entity 'ent'")
+    }
+
+    #[test]
+    fn diagnostics_of_third_party_libraries_are_suppressed() {
+        let mut project = Project::new();
+        let vendor = Source::inline(
+            Path::new("vendor.vhd"),
+            "
+entity vendor_ent is
+end entity;
+
+architecture rtl of vendor_ent is
+  signal sig : missing_type;
+begin
+end architecture;
+",
+        );
+        let user = Source::inline(
+            Path::new("user.vhd"),
+            "
+library vendor;
+
+entity user_ent is
+end entity;
+
+architecture rtl of user_ent is
+begin
+  inst: entity vendor.vendor_ent;
+  inst2: entity work.missing_ent;
+end architecture;
+",
+        );
+        project.update_source(&vendor);
+        project.update_source(&user);
+
+        let vendor_lib = project.root.symbol_utf8("vendor");
+        let vendor_file = project.files.get_mut(vendor.file_name()).unwrap();
+        vendor_file.library_names = FnvHashSet::from_iter([vendor_lib.clone()]);
+
+        let diagnostics = project.analyse();
+        assert_eq!(diagnostics.len(), 2);
+
+        project.third_party_libraries.insert(vendor_lib);
+        project.update_source(&vendor);
+        project.update_source(&user);
+        let diagnostics = project.analyse();
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].pos.source, user);
+
+        // Third party files are still analyzed such that navigation into them works
+        let ent = project
+            .find_declaration(&user, Position::new(8, 22))
+            .unwrap();
+        assert_eq!(ent.decl_pos().unwrap().source, vendor);
+    }
+
+    #[test]
+    fn installed_pure_functions_calling_now_are_not_reported() {
+        // The VITAL primitives call the impure function NOW from pure functions
+        let mut project = Project::new();
+        let standard = Source::inline(
+            Path::new("standard.vhd"),
+            &Latin1String::new(include_bytes!("../../vhdl_libraries/std/standard.vhd")).to_string(),
+        );
+        let vital = Source::inline(
+            Path::new("prmtvs_b.vhd"),
+            "
+package vital_primitives is
+  function GlitchMinTime (constant Time1, Time2 : in time) return time;
+end package;
+
+package body vital_primitives is
+  function GlitchMinTime (constant Time1, Time2 : in time) return time is
+  begin
+    if Time1 >= NOW then
+      return Time1;
+    else
+      return Time2;
+    end if;
+  end function;
+end package body;
+",
+        );
+        project.update_source(&standard);
+        project.update_source(&vital);
+
+        let std_lib = project.root.symbol_utf8("std");
+        let vital_lib = project.root.symbol_utf8("vital");
+        project
+            .files
+            .get_mut(standard.file_name())
+            .unwrap()
+            .library_names = FnvHashSet::from_iter([std_lib.clone()]);
+        project
+            .files
+            .get_mut(vital.file_name())
+            .unwrap()
+            .library_names = FnvHashSet::from_iter([vital_lib.clone()]);
+
+        let diagnostics = project.analyse();
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(
+            diagnostics[0].message,
+            "Pure function 'GlitchMinTime' cannot call impure function 'NOW'"
+        );
+
+        project.third_party_libraries = FnvHashSet::from_iter([std_lib, vital_lib]);
+        project.update_source(&standard);
+        project.update_source(&vital);
+        assert_eq!(project.analyse(), vec![]);
+    }
+
+    #[test]
+    fn cancelled_analysis_is_done_again() {
+        let mut project = Project::new();
+        let code = Source::inline(
+            Path::new("code.vhd"),
+            "
+entity ent is
+end entity;
+
+architecture rtl of ent is
+  signal sig : missing_type;
+begin
+end architecture;
+",
+        );
+        project.update_source(&code);
+
+        let token = CancellationToken::new();
+        token.cancel();
+        assert_eq!(project.analyse_cancellable(&token), None);
+
+        // The design units which were not completely analyzed are analyzed again
+        let diagnostics = project.analyse();
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].pos.source, code);
+    }
+
+    #[test]
+    fn lints_take_the_severity_of_the_kind_of_their_file() {
+        let mut project = Project::new();
+        let rtl = Source::inline(
+            Path::new("rtl.vhd"),
+            "
+package rtl_pkg is
+  shared variable rtl_var : natural;
+end package;
+
+entity rtl_ent is
+end entity;
+
+architecture a of rtl_ent is
+  signal rtl_sig : bit;
+begin
+end architecture;
+",
+        );
+        let tb = Source::inline(
+            Path::new("tb.vhd"),
+            "
+package tb_pkg is
+  shared variable tb_var : natural;
+end package;
+
+entity tb_ent is
+end entity;
+
+architecture a of tb_ent is
+  signal tb_sig : bit;
+begin
+end architecture;
+",
+        );
+        let standard = Source::inline(
+            Path::new("standard.vhd"),
+            &Latin1String::new(include_bytes!("../../vhdl_libraries/std/standard.vhd")).to_string(),
+        );
+        project.update_source(&standard);
+        let std_lib = project.root.symbol_utf8("std");
+        project
+            .files
+            .get_mut(standard.file_name())
+            .unwrap()
+            .library_names = FnvHashSet::from_iter([std_lib]);
+        project.update_source(&standard);
+        project.update_source(&rtl);
+        project.update_source(&tb);
+        project
+            .file_kinds
+            .insert(rtl.file_name().to_owned(), LibraryKind::Rtl);
+        project
+            .file_kinds
+            .insert(tb.file_name().to_owned(), LibraryKind::Testbench);
+        project.configure_lints(&Config::default());
+        let work = project.root.symbol_utf8("work");
+        project
+            .root
+            .set_synthesis_libraries(FnvHashSet::from_iter([work]));
+
+        let messages = |project: &mut Project| {
+            let mut messages: Vec<_> = project
+                .analyse()
+                .into_iter()
+                .map(|diagnostic| (diagnostic.severity, diagnostic.message))
+                .collect();
+            messages.sort_by(|left, right| left.1.cmp(&right.1));
+            messages
+        };
+
+        // Synthesis checks only apply to RTL code
+        assert_eq!(
+            messages(&mut project),
+            vec![
+                (
+                    Severity::Warning,
+                    "Shared variable 'rtl_var' is not synthesizable".to_owned()
+                ),
+                (Severity::Hint, "Signal 'rtl_sig' is never used".to_owned()),
+                (Severity::Hint, "Signal 'tb_sig' is never used".to_owned()),
+            ]
+        );
+
+        project
+            .lint_severities
+            .insert((Some(LibraryKind::Testbench), "unused"), None);
+        project
+            .lint_severities
+            .insert((Some(LibraryKind::Rtl), "unused"), Some(Severity::Error));
+        project.update_source(&rtl);
+        project.update_source(&tb);
+        assert_eq!(
+            messages(&mut project),
+            vec![
+                (
+                    Severity::Warning,
+                    "Shared variable 'rtl_var' is not synthesizable".to_owned()
+                ),
+                (Severity::Error, "Signal 'rtl_sig' is never used".to_owned()),
+            ]
+        );
+    }
+
+    #[test]
+    fn lint_profiles_run_regardless_of_the_configured_lints() {
+        let mut project = Project::new();
+        let model = Source::inline(
+            Path::new("model.vhd"),
+            "
+package model_pkg is
+  shared variable model_var : natural;
+end package;
+
+entity model_ent is
+end entity;
+
+architecture a of model_ent is
+  signal model_sig : bit;
+begin
+end architecture;
+",
+        );
+        let other = Source::inline(
+            Path::new("other.vhd"),
+            "
+package other_pkg is
+  shared variable other_var : natural;
+end package;
+",
+        );
+        let standard = Source::inline(
+            Path::new("standard.vhd"),
+            &Latin1String::new(include_bytes!("../../vhdl_libraries/std/standard.vhd")).to_string(),
+        );
+        project.update_source(&standard);
+        let std_lib = project.root.symbol_utf8("std");
+        project
+            .files
+            .get_mut(standard.file_name())
+            .unwrap()
+            .library_names = FnvHashSet::from_iter([std_lib]);
+        project.update_source(&standard);
+        project.update_source(&model);
+        project.update_source(&other);
+        project
+            .file_kinds
+            .insert(model.file_name().to_owned(), LibraryKind::Testbench);
+        project.configure_lints(&Config::default());
+
+        let messages = |diagnostics: Result<Vec<Diagnostic>, String>| {
+            let mut messages: Vec<_> = diagnostics
+                .unwrap()
+                .into_iter()
+                .map(|diagnostic| (diagnostic.severity, diagnostic.message))
+                .collect();
+            messages.sort_by(|left, right| left.1.cmp(&right.1));
+            messages
+        };
+
+        // The synthesis checks are not configured for test bench code
+        assert!(!project
+            .analyse()
+            .iter()
+            .any(|diagnostic| diagnostic.message.contains("not synthesizable")));
+
+        assert_eq!(
+            messages(project.run_lint_profile("rtl", &LintTarget::Kind(LibraryKind::Testbench))),
+            vec![
+                (
+                    Severity::Warning,
+                    "Shared variable 'model_var' is not synthesizable".to_owned()
+                ),
+                (
+                    Severity::Hint,
+                    "Signal 'model_sig' is never used".to_owned()
+                ),
+            ]
+        );
+        assert_eq!(
+            messages(
+                project.run_lint_profile("synthesis", &LintTarget::Library("work".to_owned()))
+            ),
+            vec![
+                (
+                    Severity::Warning,
+                    "Shared variable 'model_var' is not synthesizable".to_owned()
+                ),
+                (
+                    Severity::Warning,
+                    "Shared variable 'other_var' is not synthesizable".to_owned()
+                ),
+            ]
+        );
+        assert_eq!(
+            messages(project.run_lint_profile("testbench", &LintTarget::Library("lib".to_owned()))),
+            vec![]
+        );
+        assert_eq!(
+            project.run_lint_profile("style", &LintTarget::Kind(LibraryKind::Rtl)),
+            Err("Unsupported library kind 'style'".to_owned())
+        );
+    }
+
+    #[test]
+    fn extract_design_unit() {
+        let root = tempfile::tempdir().unwrap();
+        let file_name = root.path().join("file.vhd");
+        let code = "\
+library ieee;
+use ieee.std_logic_1164.all;
+
+package pkg is
+end package;
+
+entity ent is
+end entity;
+";
+        let source = Source::inline(&file_name, code);
+        let mut project = Project::new();
+        project.update_source(&source);
+
+        assert_eq!(
+            project.extract_design_unit(&source, Position::new(3, 9)),
+            Some(ExtractedDesignUnit {
+                file_name: root.path().join("pkg.vhd"),
+                contents: "\
+library ieee;
+use ieee.std_logic_1164.all;
+
+package pkg is
+end package;
+"
+                .to_owned(),
+                range: Range::new(Position::new(0, 0), Position::new(5, 0)),
+            })
+        );
+        assert_eq!(
+            project
+                .extract_design_unit(&source, Position::new(6, 0))
+                .map(|unit| unit.file_name),
+            Some(root.path().join("ent.vhd"))
+        );
+
+        let single = Source::inline(
+            &root.path().join("single.vhd"),
+            "entity ent2 is\nend entity;\n",
+        );
+        project.update_source(&single);
+        assert_eq!(
+            project.extract_design_unit(&single, Position::new(0, 0)),
+            None
+        );
+
+        let extracted = Source::inline(
+            &root.path().join("pkg.vhd"),
+            "package pkg is\nend package;\n",
+        );
+        project.add_extracted_source(&extracted, &file_name);
+        assert_eq!(
+            project.library_mapping_of(&extracted),
+            project.library_mapping_of(&source)
+        );
+    }
+
+    #[test]
+    fn rename_file_keeps_library_mapping() {
+        let root = tempfile::tempdir().unwrap();
+        let old_file_name = root.path().join("old.vhd");
+        let new_file_name = root.path().join("rtl").join("new.vhd");
+        let mut project = Project::new();
+        project.update_source(&Source::inline(
+            &old_file_name,
+            "package pkg is\nend package;\n",
+        ));
+        project.update_source(&Source::inline(
+            &root.path().join("user.vhd"),
+            "use work.pkg.all;\nentity ent is\nend entity;\n",
+        ));
+        check_no_diagnostics(&project.analyse());
+
+        assert!(project.rename_file(&old_file_name, &new_file_name));
+        assert!(!project.rename_file(&old_file_name, &new_file_name));
+        assert!(project.get_source(&old_file_name).is_none());
+
+        let source = project.get_source(&new_file_name).unwrap();
+        assert_eq!(
+            project
+                .library_mapping_of(&source)
+                .iter()
+                .map(|lib| lib.name_utf8())
+                .collect::<Vec<_>>(),
+            vec!["work".to_owned()]
+        );
+        check_no_diagnostics(&project.analyse());
+    }
+
+    #[test]
+    fn rename_library_errors() {
+        let mut messages = Vec::new();
+        let mut project = Project::from_config(&Config::default(), &mut messages);
+        check_no_diagnostics(&project.analyse());
+
+        assert_eq!(
+            project.rename_library("lib", "2lib"),
+            Err("'2lib' is not a valid library name".to_owned())
+        );
+        assert_eq!(
+            project.rename_library("lib", "new__lib"),
+            Err("'new__lib' is not a valid library name".to_owned())
+        );
+        assert_eq!(
+            project.rename_library("work", "lib"),
+            Err("The work library cannot be renamed".to_owned())
+        );
+        assert_eq!(
+            project.rename_library("lib", "new_lib"),
+            Err("No such library 'lib'".to_owned())
+        );
+    }
+
+    /// Test that the same file can be added to several libraries
+    #[test]
+    fn test_same_file_in_multiple_libraries() {
+        let root = tempfile::tempdir().unwrap();
+        let vhdl_file_path1 = root.path().join("file.vhd");
+        std::fs::write(
+            vhdl_file_path1,
+            "
+package pkg is
+end package;
+        ",
+        )
+        .unwrap();
+
+        let vhdl_file_path2 = root.path().join("use_file.vhd");
+        std::fs::write(
+            vhdl_file_path2,
+            "
+library lib1;
+use lib1.pkg.all;
+
+package use_pkg1 is
+end package;
+
+library lib2;
+use lib2.pkg.all;
+
+package use_pkg2 is
+end package;
+        ",
+        )
+        .unwrap();
+
+        let config_str = "
+[libraries]
+lib1.files = ['file.vhd']
+lib2.files = ['file.vhd']
+use_lib.files = ['use_file.vhd']
+        ";
+
+        let config = Config::from_str(config_str, root.path()).unwrap();
+        let mut messages = Vec::new();
+        let mut project = Project::from_config(&config, &mut messages);
+        assert_eq!(messages, vec![]);
+        check_no_diagnostics(&project.analyse());
+    }
+
+    #[test]
+    fn apply_edits_of_several_files() {
+        let root = tempfile::tempdir().unwrap();
+        let pkg_file_name = root.path().join("pkg.vhd");
+        let ent_file_name = root.path().join("ent.vhd");
+        let mut project = Project::new();
+        project.update_source(&Source::inline(
+            &pkg_file_name,
+            "package pkg is\nend package;\n",
+        ));
+        project.update_source(&Source::inline(
+            &ent_file_name,
+            "use work.pkg.all;\nentity ent is\nend entity;\n",
+        ));
+        check_no_diagnostics(&project.analyse());
+
+        let pkg = project.get_source(&pkg_file_name).unwrap();
+        let ent = project.get_source(&ent_file_name).unwrap();
+        let text = |source: &Source| {
+            let contents = source.contents();
+            contents.text_in(Range::new(contents.start(), contents.end()))
+        };
+
+        // Nothing is applied when some edit is not valid
+        let outside = Source::inline(&root.path().join("other.vhd"), "");
+        assert_eq!(
+            project.apply_edits(&[
+                TextEdit::replace(
+                    pkg.pos(Position::new(0, 8), Position::new(0, 11)),
+                    "util_pkg"
+                ),
+                TextEdit::remove(outside.pos(Position::new(0, 0), Position::new(0, 0))),
+            ]),
+            Err(format!(
+                "{} is not part of the project",
+                root.path().join("other.vhd").to_string_lossy()
+            ))
+        );
+        assert_eq!(
+            project.apply_edits(&[
+                TextEdit::replace(
+                    pkg.pos(Position::new(0, 8), Position::new(0, 11)),
+                    "util_pkg"
+                ),
+                TextEdit::replace(pkg.pos(Position::new(0, 10), Position::new(0, 14)), "util"),
+            ]),
+            Err(format!(
+                "Overlapping edits of {}",
+                pkg_file_name.to_string_lossy()
+            ))
+        );
+        assert_eq!(text(&pkg), "package pkg is\nend package;\n");
+
+        // Renaming the package in only one of the files would leave the use clause unresolved
+        let diagnostics = project
+            .apply_edits(&[
+                TextEdit::replace(
+                    pkg.pos(Position::new(0, 8), Position::new(0, 11)),
+                    "util_pkg",
+                ),
+                TextEdit::replace(
+                    ent.pos(Position::new(0, 9), Position::new(0, 12)),
+                    "util_pkg",
+                ),
+            ])
+            .unwrap();
+        check_no_diagnostics(&diagnostics);
+        assert_eq!(
+            text(&project.get_source(&pkg_file_name).unwrap()),
+            "package util_pkg is\nend package;\n"
+        );
+        assert_eq!(
+            text(&project.get_source(&ent_file_name).unwrap()),
+            "use work.util_pkg.all;\nentity ent is\nend entity;\n"
+        );
+    }
+
+    fn update(project: &mut Project, source: &mut Source, contents: &str) {
+        std::fs::write(std::path::Path::new(source.file_name()), contents).unwrap();
+        *source = Source::from_latin1_file(source.file_name()).unwrap();
+        project.update_source(source);
+    }
+
+    /// Test that the same file can be added to several libraries
+    #[test]
+    fn test_re_analyze_after_update() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let root = dunce::canonicalize(tempdir.path()).unwrap();
+
+        let path1 = root.join("file1.vhd");
+        let path2 = root.join("file2.vhd");
+        std::fs::write(
+            &path1,
+            "
+package pkg is
+end package;
+        ",
+        )
+        .unwrap();
+        let mut source1 = Source::from_latin1_file(&path1).unwrap();
+
+        std::fs::write(
+            &path2,
+            "
+library lib1;
+use lib1.pkg.all;
+
+package pkg is
+end package;
+        ",
+        )
+        .unwrap();
+        let mut source2 = Source::from_latin1_file(&path2).unwrap();
+
+        let config_str = "
+[libraries]
+lib1.files = ['file1.vhd']
+lib2.files = ['file2.vhd']
+        ";
+
+        let config = Config::from_str(config_str, &root).unwrap();
+        let mut messages = Vec::new();
+        let mut project = Project::from_config(&config, &mut messages);
+        assert_eq!(messages, vec![]);
+        check_no_diagnostics(&project.analyse());
+
+        // Add syntax error
+        update(
+            &mut project,
+            &mut source1,
+            "
+package is
+        ",
+        );
+        let diagnostics = project.analyse();
+        assert_eq!(diagnostics.len(), 3);
+        // Syntax error comes first
+        assert_eq!(diagnostics[0].pos.source, source1);
+        assert_eq!(diagnostics[1].pos.source, source1);
+        assert_eq!(diagnostics[2].pos.source, source2);
+
+        // Make it good again
+        update(
+            &mut project,
+            &mut source1,
+            "
+package pkg is
+end package;
+        ",
+        );
+        check_no_diagnostics(&project.analyse());
+
+        // Add analysis error
+        update(
+            &mut project,
+            &mut source2,
+            "
+package pkg is
+end package;
+
+package pkg is
+end package;
+        ",
+        );
+        let diagnostics = project.analyse();
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].pos.source, source2);
+
+        // Make it good again
+        update(
+            &mut project,
+            &mut source2,
+            "
+package pkg is
+end package;
+        ",
+        );
+        check_no_diagnostics(&project.analyse());
+    }
+
+    /// Test that the configuration can be updated
+    #[test]
+    fn test_config_update() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let root = dunce::canonicalize(tempdir.path()).unwrap();
+
+        let path1 = root.join("file1.vhd");
+        let path2 = root.join("file2.vhd");
+        std::fs::write(
+            &path1,
+            "
+library unkown;
+use unkown.pkg.all;
+
+package pkg is
+end package;
+        ",
+        )
+        .unwrap();
+        let source1 = Source::from_latin1_file(&path1).unwrap();
+
+        std::fs::write(
+            &path2,
+            "
+library unkown;
+use unkown.pkg.all;
+
+package pkg is
+end package;
+        ",
+        )
+        .unwrap();
+        let source2 = Source::from_latin1_file(&path2).unwrap();
+
+        let config_str1 = "
+[libraries]
+lib.files = ['file1.vhd']
+        ";
+        let config1 = Config::from_str(config_str1, &root).unwrap();
+
+        let config_str2 = "
+[libraries]
+lib.files = ['file2.vhd']
+        ";
+        let config2 = Config::from_str(config_str2, &root).unwrap();
+
+        let mut messages = Vec::new();
+        let mut project = Project::from_config(&config1, &mut messages);
+        assert_eq!(messages, vec![]);
+
+        // Invalid library should only be reported in source1
+        let diagnostics = project.analyse();
+        assert_eq!(diagnostics.len(), 2);
+        assert_eq!(diagnostics[0].pos.source, source1); // No such library
+        assert_eq!(diagnostics[1].pos.source, source1); // No declaration
+
+        // Change configuration file
+        project.update_config(&config2, &mut messages);
+        assert_eq!(messages, vec![]);
+
+        // Invalid library should only be reported in source2
+        let diagnostics = project.analyse();
+        assert_eq!(diagnostics.len(), 2);
+        assert_eq!(diagnostics[0].pos.source, source2); // No such library
+        assert_eq!(diagnostics[1].pos.source, source2); // No declaration
+    }
+}
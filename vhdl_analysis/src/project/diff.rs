@@ -0,0 +1,339 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this file,
+// You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) 2023, Olof Kraigher olof.kraigher@gmail.com
+
+//! Compare the design units of two projects, such as the same project
+//! before and after switching a git branch
+
+use super::Project;
+use crate::ast::*;
+use crate::data::*;
+use fnv::FnvHashMap;
+
+/// A design unit that differs between two projects
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct UnitDiff {
+    pub library_name: String,
+    /// The kind and name of the design unit such as `architecture 'rtl' of 'foo'`
+    pub unit: String,
+    pub change: UnitChange,
+}
+
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub enum UnitChange {
+    Added,
+    Removed,
+    /// The source of the design unit has changed, ignoring whitespace and comments
+    /// Changes to generics, ports and package declarations are listed separately
+    Changed(Vec<InterfaceChange>),
+}
+
+/// A change to a generic, a port or a declaration in a package
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub enum InterfaceChange {
+    Added(String),
+    Removed(String),
+    Changed { before: String, after: String },
+}
+
+#[derive(Clone)]
+struct UnitSummary {
+    /// The fingerprint of the tokens which ignores whitespace and comments
+    fingerprint: u64,
+    /// The name and the formatted declaration of each interface item
+    interface: Vec<(String, String)>,
+}
+
+impl Project {
+    /// Compare the design units of this project with those of another project
+    /// The differences are sorted by library and design unit
+    pub fn compare(&self, other: &Project) -> Vec<UnitDiff> {
+        let before = self.unit_summaries();
+        let after = other.unit_summaries();
+        let mut diffs = Vec::new();
+
+        for ((library_name, unit), old) in before.iter() {
+            let change = match after.get(&(library_name.clone(), unit.clone())) {
+                None => UnitChange::Removed,
+                Some(new) if new.fingerprint != old.fingerprint => {
+                    UnitChange::Changed(interface_changes(&old.interface, &new.interface))
+                }
+                Some(_) => continue,
+            };
+            diffs.push(UnitDiff {
+                library_name: library_name.clone(),
+                unit: unit.clone(),
+                change,
+            });
+        }
+
+        for (library_name, unit) in after.keys() {
+            if !before.contains_key(&(library_name.clone(), unit.clone())) {
+                diffs.push(UnitDiff {
+                    library_name: library_name.clone(),
+                    unit: unit.clone(),
+                    change: UnitChange::Added,
+                });
+            }
+        }
+
+        diffs.sort_by(|a, b| (&a.library_name, &a.unit).cmp(&(&b.library_name, &b.unit)));
+        diffs
+    }
+
+    fn unit_summaries(&self) -> FnvHashMap<(String, String), UnitSummary> {
+        let mut summaries = FnvHashMap::default();
+
+        for source_file in self.files.values() {
+            let design_file = self
+                .parser
+                .parse_design_source(&source_file.source, &mut NullDiagnostics);
+
+            for (unit, fingerprint) in design_file
+                .design_units
+                .iter()
+                .zip(design_file.unit_fingerprints.iter())
+            {
+                let summary = UnitSummary {
+                    fingerprint: *fingerprint,
+                    interface: unit_interface(unit),
+                };
+
+                for library_name in source_file.library_names.iter() {
+                    summaries.insert(
+                        (library_name.name_utf8(), describe_unit(unit)),
+                        summary.clone(),
+                    );
+                }
+            }
+        }
+
+        summaries
+    }
+}
+
+fn interface_changes(
+    before: &[(String, String)],
+    after: &[(String, String)],
+) -> Vec<InterfaceChange> {
+    let mut changes = Vec::new();
+
+    for (name, old) in before.iter() {
+        match after.iter().find(|(other, _)| other == name) {
+            None => changes.push(InterfaceChange::Removed(old.clone())),
+            Some((_, new)) if new != old => changes.push(InterfaceChange::Changed {
+                before: old.clone(),
+                after: new.clone(),
+            }),
+            Some(_) => {}
+        }
+    }
+
+    for (name, new) in after.iter() {
+        if !before.iter().any(|(other, _)| other == name) {
+            changes.push(InterfaceChange::Added(new.clone()));
+        }
+    }
+
+    changes
+}
+
+fn lowercase_name(ident: &Ident) -> String {
+    ident.item.name_utf8().to_lowercase()
+}
+
+fn describe_unit(unit: &AnyDesignUnit) -> String {
+    match unit {
+        AnyDesignUnit::Primary(primary) => {
+            let kind = match primary {
+                AnyPrimaryUnit::Entity(..) => "entity",
+                AnyPrimaryUnit::Configuration(..) => "configuration",
+                AnyPrimaryUnit::Package(..) => "package",
+                AnyPrimaryUnit::PackageInstance(..) => "package instance",
+                AnyPrimaryUnit::Context(..) => "context",
+            };
+            format!("{kind} '{}'", lowercase_name(primary.ident()))
+        }
+        AnyDesignUnit::Secondary(AnySecondaryUnit::Architecture(unit)) => format!(
+            "architecture '{}' of '{}'",
+            lowercase_name(unit.ident()),
+            lowercase_name(unit.primary_ident())
+        ),
+        AnyDesignUnit::Secondary(AnySecondaryUnit::PackageBody(unit)) => {
+            format!("package body '{}'", lowercase_name(unit.ident()))
+        }
+    }
+}
+
+/// The generics and ports of an entity or the declarations of a package
+fn unit_interface(unit: &AnyDesignUnit) -> Vec<(String, String)> {
+    match unit {
+        AnyDesignUnit::Primary(AnyPrimaryUnit::Entity(entity)) => entity
+            .generic_clause
+            .iter()
+            .chain(entity.port_clause.iter())
+            .flatten()
+            .map(|decl| (interface_name(decl), decl.to_string()))
+            .collect(),
+        AnyDesignUnit::Primary(AnyPrimaryUnit::Package(package)) => package
+            .decl
+            .iter()
+            .filter_map(declaration_summary)
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+fn interface_name(decl: &InterfaceDeclaration) -> String {
+    match decl {
+        InterfaceDeclaration::Object(object) => object.ident.to_string(),
+        InterfaceDeclaration::File(file) => file.ident.to_string(),
+        InterfaceDeclaration::Type(ident) => ident.to_string(),
+        InterfaceDeclaration::Package(package) => package.ident.to_string(),
+        // Subprograms may be overloaded
+        InterfaceDeclaration::Subprogram(..) => decl.to_string(),
+    }
+    .to_lowercase()
+}
+
+fn declaration_summary(decl: &Declaration) -> Option<(String, String)> {
+    let (name, text) = match decl {
+        Declaration::Object(object) => (object.ident.to_string(), object.to_string()),
+        Declaration::File(file) => (file.ident.to_string(), file.to_string()),
+        Declaration::Type(typ) => (typ.ident.to_string(), typ.to_string()),
+        Declaration::Component(component) => (component.ident.to_string(), component.to_string()),
+        Declaration::Attribute(Attribute::Declaration(attribute)) => {
+            (attribute.ident.to_string(), attribute.to_string())
+        }
+        Declaration::Alias(alias) => (alias.designator.to_string(), alias.to_string()),
+        Declaration::Package(package) => (package.ident.to_string(), package.to_string()),
+        // Subprograms may be overloaded
+        Declaration::SubprogramDeclaration(subprogram) => {
+            (subprogram.to_string(), subprogram.to_string())
+        }
+        Declaration::Attribute(Attribute::Specification(..))
+        | Declaration::SubprogramBody(..)
+        | Declaration::Use(..)
+        | Declaration::Configuration(..) => return None,
+    };
+    Some((name.to_lowercase(), text))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    fn project(code: &str) -> Project {
+        let mut project = Project::new();
+        project.update_source(&Source::inline(Path::new("file.vhd"), code));
+        project
+    }
+
+    #[test]
+    fn compare_projects() {
+        let before = project(
+            "
+entity ent is
+  generic (width : natural := 8);
+  port (clk : in bit; data : out bit);
+end entity;
+
+architecture rtl of ent is
+begin
+  assert false report \"a -- b\";
+end architecture;
+
+package pkg is
+  constant c : natural := 0;
+end package;
+",
+        );
+
+        let after = project(
+            "
+entity ent is
+  generic (width : natural := 16);
+  port (clk : in bit; valid : out bit);
+end entity;
+
+-- Only the comments and whitespace have changed
+architecture rtl of ent is
+begin
+  /* a block
+     comment */
+  assert false report \"a -- b\";
+end   architecture;
+
+package pkg2 is
+end package;
+",
+        );
+
+        assert_eq!(
+            before.compare(&after),
+            vec![
+                UnitDiff {
+                    library_name: "work".to_owned(),
+                    unit: "entity 'ent'".to_owned(),
+                    change: UnitChange::Changed(vec![
+                        InterfaceChange::Changed {
+                            before: "width : natural := 8".to_owned(),
+                            after: "width : natural := 16".to_owned(),
+                        },
+                        InterfaceChange::Removed("data : out bit".to_owned()),
+                        InterfaceChange::Added("valid : out bit".to_owned()),
+                    ]),
+                },
+                UnitDiff {
+                    library_name: "work".to_owned(),
+                    unit: "package 'pkg'".to_owned(),
+                    change: UnitChange::Removed,
+                },
+                UnitDiff {
+                    library_name: "work".to_owned(),
+                    unit: "package 'pkg2'".to_owned(),
+                    change: UnitChange::Added,
+                },
+            ]
+        );
+        assert_eq!(after.compare(&after), vec![]);
+    }
+
+    #[test]
+    fn changes_after_double_dash_within_string_are_found() {
+        let before = project(
+            "
+entity ent is
+end entity;
+
+architecture rtl of ent is
+begin
+  assert false report \"a -- b\";
+end architecture;
+",
+        );
+        let after = project(
+            "
+entity ent is
+end entity;
+
+architecture rtl of ent is
+begin
+  assert false report \"a -- c\";
+end architecture;
+",
+        );
+
+        assert_eq!(
+            before.compare(&after),
+            vec![UnitDiff {
+                library_name: "work".to_owned(),
+                unit: "architecture 'rtl' of 'ent'".to_owned(),
+                change: UnitChange::Changed(vec![]),
+            }]
+        );
+    }
+}
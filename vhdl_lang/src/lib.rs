@@ -3,29 +3,25 @@
 // You can obtain one at http://mozilla.org/MPL/2.0/.
 //
 // Copyright (c) 2018, Olof Kraigher olof.kraigher@gmail.com
-#![allow(clippy::upper_case_acronyms)]
-// False positives with unconditional loops
-// allow for now
-#![allow(clippy::vec_init_then_push)]
 
-#[macro_use]
-pub mod ast;
-#[macro_use]
-mod analysis;
-mod config;
-mod data;
-mod project;
-mod syntax;
+//! Re-exports the parser of vhdl_syntax and the analysis of vhdl_analysis
+//! Tools that only need the parser can depend on vhdl_syntax directly
 
-pub use crate::config::Config;
-pub use crate::data::{
-    Diagnostic, Latin1String, Message, MessageHandler, MessagePrinter, MessageType,
-    NullDiagnostics, NullMessages, Position, Range, Severity, Source, SrcPos,
+pub use vhdl_syntax::{ast, format};
+
+pub use vhdl_syntax::{
+    select_fixes, ArgumentPosition, ClauseKind, CompletionContext, Diagnostic, FileMetrics, Fix,
+    ItemKind, Latin1String, Message, MessageHandler, MessagePrinter, MessageType, NullDiagnostics,
+    NullMessages, ParserResult, Position, Range, Severity, SignalMetrics, Source, SourceFixes,
+    SrcPos, TextEdit, UnitMetrics, VHDLParser,
 };
 
-pub use crate::analysis::{
-    AnyEnt, AnyEntKind, Concurrent, Design, EntHierarchy, EntRef, EntityId, Object, Overloaded,
-    Type,
+#[cfg(feature = "analysis")]
+pub use vhdl_analysis::{
+    AnyEnt, AnyEntKind, ArchitectureFanout, AttributePrefix, Call, CallGraph, CancellationToken,
+    CompletionItem, Concurrent, Config, ConstantValue, Design, ElaboratedInstance, Elaboration,
+    EntHierarchy, EntPositions, EntRef, EntityId, ExtractedDesignUnit, GenericOverrides,
+    GenericValue, InstantiationForm, InterfaceChange, ItemAt, LibraryKind, LintTarget, Object,
+    ObjectInterface, Overloaded, Partial, PredefinedAttribute, Project, SignalFanout, SourceFile,
+    Type, UnitChange, UnitDiff, VHDLStandard, PREDEFINED_ATTRIBUTES,
 };
-pub use crate::project::{Project, SourceFile};
-pub use crate::syntax::{ParserResult, VHDLParser};
@@ -12,7 +12,10 @@
 use clap::Parser;
 use std::path::Path;
 use std::time::SystemTime;
-use vhdl_lang::{Config, Diagnostic, MessagePrinter, NullMessages, Project, Severity};
+use vhdl_lang::{
+    select_fixes, Config, Diagnostic, InstantiationForm, Latin1String, MessagePrinter,
+    NullMessages, Project, Severity,
+};
 
 /// Run vhdl analysis
 #[derive(Parser, Debug)]
@@ -47,6 +50,64 @@ struct Args {
     /// This is used for development to test where the language server is blind
     #[arg(long)]
     count_unresolved: bool,
+
+    /// Print the graph of calls between subprograms in the given format
+    #[arg(long, value_enum)]
+    call_graph: Option<CallGraphFormat>,
+
+    /// Print the hierarchy of instances below a top level entity written as lib.ent(arch)
+    /// where the library and the architecture are optional
+    #[arg(long, value_name = "TOP")]
+    hierarchy: Option<String>,
+
+    /// The format of the hierarchy printed by --hierarchy, where netlist is the JSON format
+    /// of yosys with the ports and signals connected between the instances
+    #[arg(long, value_enum, default_value_t = HierarchyFormat::Text, requires = "hierarchy")]
+    hierarchy_format: HierarchyFormat,
+
+    /// Print an instance of the entity written as lib.ent with all generics and ports
+    /// associated by name
+    #[arg(long, value_name = "LIB.ENT")]
+    instantiate: Option<String>,
+
+    /// Include a component declaration and instantiate the component with --instantiate
+    #[arg(long, requires = "instantiate")]
+    component: bool,
+
+    /// Print the size metrics of each file and design unit as JSON
+    #[arg(long)]
+    metrics: bool,
+
+    /// Print the number of assertions and reports of each architecture and flag the
+    /// architectures of test bench code without assertions
+    #[arg(long)]
+    assertions: bool,
+
+    /// Apply the automatic fixes of the diagnostics to the source files
+    #[arg(long)]
+    fix: bool,
+
+    /// Print the changes that --fix would make as a diff without changing any file
+    #[arg(long, requires = "fix")]
+    dry_run: bool,
+
+    /// Also apply fixes that may change the meaning of the code such as adding a signal to
+    /// the sensitivity list of a process
+    #[arg(long, requires = "fix")]
+    unsafe_fixes: bool,
+}
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug)]
+enum CallGraphFormat {
+    Dot,
+    Json,
+}
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug)]
+enum HierarchyFormat {
+    Text,
+    Json,
+    Netlist,
 }
 
 fn main() {
@@ -119,10 +180,132 @@ fn main() {
         }
     }
 
+    if let Some(format) = args.call_graph {
+        let call_graph = project.call_graph();
+        match format {
+            CallGraphFormat::Dot => println!("{}", call_graph.to_dot()),
+            CallGraphFormat::Json => println!("{}", call_graph.to_json()),
+        }
+    }
+
+    if let Some(ref top) = args.hierarchy {
+        match project.elaborate(top) {
+            Ok(elaboration) => {
+                show_diagnostics(&elaboration.diagnostics);
+                match args.hierarchy_format {
+                    HierarchyFormat::Text => print!("{}", elaboration.to_text()),
+                    HierarchyFormat::Json => println!("{}", elaboration.to_json()),
+                    HierarchyFormat::Netlist => println!("{}", elaboration.to_netlist_json()),
+                }
+            }
+            Err(err) => println!("{err}"),
+        }
+    }
+
+    if let Some(ref name) = args.instantiate {
+        let form = if args.component {
+            InstantiationForm::Component
+        } else {
+            InstantiationForm::Entity
+        };
+        match name.split_once('.') {
+            Some((library_name, entity_name)) => {
+                match project.instantiation_text(library_name, entity_name, form) {
+                    Some(text) => println!("{text}"),
+                    None => println!("No entity '{entity_name}' in library '{library_name}'"),
+                }
+            }
+            None => println!("Expected the entity to be written as lib.ent, got '{name}'"),
+        }
+    }
+
+    if args.metrics {
+        let metrics: Vec<_> = project
+            .metrics()
+            .iter()
+            .map(|metrics| metrics.to_json())
+            .collect();
+        println!("[{}]", metrics.join(",\n "));
+    }
+
+    if args.assertions {
+        let metrics = project.metrics();
+        let mut architectures = 0;
+        let mut unchecked = 0;
+        for file in metrics.iter() {
+            for unit in file.units_of_kind("architecture") {
+                architectures += 1;
+                let flag = if file.testbench && unit.assertions == 0 {
+                    unchecked += 1;
+                    " (test bench without assertions)"
+                } else {
+                    ""
+                };
+                println!(
+                    "{}: {}: {} assertions, {} reports{flag}",
+                    file.file_name.to_string_lossy(),
+                    unit.name,
+                    unit.assertions,
+                    unit.reports
+                );
+            }
+        }
+        println!("{architectures} architectures, {unchecked} test benches without assertions");
+    }
+
+    if args.fix {
+        apply_fixes(&diagnostics, args.dry_run, args.unsafe_fixes);
+    }
+
     // Exit without running Drop on entire allocated AST
     std::process::exit(0);
 }
 
+fn apply_fixes(diagnostics: &[Diagnostic], dry_run: bool, unsafe_fixes: bool) {
+    let mut num_fixes = 0;
+    let mut num_files = 0;
+
+    for source_fixes in select_fixes(diagnostics, unsafe_fixes) {
+        let file_name = source_fixes.source.file_name();
+        let text = match std::fs::read(file_name) {
+            Ok(bytes) => Latin1String::from_vec(bytes).to_string(),
+            Err(err) => {
+                println!("Could not read {}: {err}", file_name.to_string_lossy());
+                continue;
+            }
+        };
+
+        if dry_run {
+            print!("{}", source_fixes.diff(&text));
+        } else {
+            let fixed = match Latin1String::from_utf8(&source_fixes.apply(&text)) {
+                Ok(fixed) => fixed,
+                Err(err) => {
+                    println!(
+                        "Could not fix {}: {}",
+                        file_name.to_string_lossy(),
+                        err.message()
+                    );
+                    continue;
+                }
+            };
+            if let Err(err) = std::fs::write(file_name, fixed.bytes) {
+                println!("Could not write {}: {err}", file_name.to_string_lossy());
+                continue;
+            }
+        }
+
+        num_fixes += source_fixes.fixes.len();
+        num_files += 1;
+    }
+
+    if dry_run {
+        println!("Would apply {num_fixes} fixes in {num_files} files");
+    } else {
+        println!("Applied {num_fixes} fixes in {num_files} files");
+    }
+}
+
 fn show_diagnostics(diagnostics: &[Diagnostic]) {
     for diagnostic in diagnostics {
         println!("{}", diagnostic.show());
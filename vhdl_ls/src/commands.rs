@@ -0,0 +1,187 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this file,
+// You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) 2023, Olof Kraigher olof.kraigher@gmail.com
+
+//! The commands of the server that clients run with `workspace/executeCommand`
+//! The registry describes the arguments and the result of each command such that
+//! clients other than the VS Code extension can invoke them
+//! The custom requests such as `vhdl_ls/breadcrumbs` are kept for the clients that already
+//! send them and are answered by the same handlers as the commands of the same name
+
+/// Command to add a file created by moving a design unit to the project
+pub const ADD_EXTRACTED_FILE_COMMAND: &str = "vhdl_ls.addExtractedFile";
+
+/// Command returning the text of an instance of an entity with all generics and ports
+/// associated by name, such that an editor can put it on the clipboard
+pub const INSTANTIATION_TEXT_COMMAND: &str = "vhdl_ls.instantiationText";
+
+/// Command inserting an instance of an entity at a location chosen by the client
+pub const INSERT_INSTANTIATION_COMMAND: &str = "vhdl_ls.insertInstantiation";
+
+/// Command returning the hierarchy of instances below a top level entity
+pub const HIERARCHY_COMMAND: &str = "vhdl_ls.hierarchy";
+
+/// Command returning the files of the project in an order that they can be compiled
+pub const COMPILE_ORDER_COMMAND: &str = "vhdl_ls.compileOrder";
+
+/// Command returning the diagnostics of the project, optionally filtered
+pub const PROJECT_DIAGNOSTICS_COMMAND: &str = "vhdl_ls.projectDiagnostics";
+
+/// Command running the lints of a profile over the files of a library or of a kind of code
+pub const RUN_LINT_PROFILE_COMMAND: &str = "vhdl_ls.runLintProfile";
+
+/// Command returning the constructs enclosing a position, outermost first
+pub const BREADCRUMBS_COMMAND: &str = "vhdl_ls.breadcrumbs";
+
+/// Command returning the declarations of the generic package instance at a position
+pub const EXPAND_PACKAGE_INSTANCE_COMMAND: &str = "vhdl_ls.expandPackageInstance";
+
+/// Command returning the VUnit and OSVVM test cases of a file
+pub const LIST_TESTS_COMMAND: &str = "vhdl_ls.listTests";
+
+/// Command returning the regions of the processes and other concurrent statements of a file
+pub const STATEMENT_REGIONS_COMMAND: &str = "vhdl_ls.statementRegions";
+
+/// Command returning all diagnostics of a file including those suppressed
+/// by the maximum number of diagnostics per file
+pub const FILE_DIAGNOSTICS_COMMAND: &str = "vhdl_ls.fileDiagnostics";
+
+/// Command returning this registry
+pub const LIST_COMMANDS_COMMAND: &str = "vhdl_ls.listCommands";
+
+/// The name, the arguments and the result of a command
+pub struct CommandSpec {
+    pub name: &'static str,
+    pub description: &'static str,
+    /// The name and the JSON type of each argument in order, optional arguments end with `?`
+    pub arguments: &'static [(&'static str, &'static str)],
+    /// The JSON type of the result
+    pub result: &'static str,
+}
+
+pub const COMMANDS: &[CommandSpec] = &[
+    CommandSpec {
+        name: ADD_EXTRACTED_FILE_COMMAND,
+        description:
+            "Add a file created by moving a design unit to the library of the original file",
+        arguments: &[
+            ("uri", "string"),
+            ("contents", "string"),
+            ("originUri", "string"),
+        ],
+        result: "null",
+    },
+    CommandSpec {
+        name: INSTANTIATION_TEXT_COMMAND,
+        description: "The text of an instance of an entity, the form is 'entity' or 'component'",
+        arguments: &[
+            ("library", "string"),
+            ("entity", "string"),
+            ("form?", "string"),
+            ("snippet?", "boolean"),
+        ],
+        result: "string",
+    },
+    CommandSpec {
+        name: INSERT_INSTANTIATION_COMMAND,
+        description: "Insert an instance of an entity at a position, the form is as for the instantiation text",
+        arguments: &[
+            ("library", "string"),
+            ("entity", "string"),
+            ("uri", "string"),
+            ("position", "object"),
+            ("form?", "string"),
+        ],
+        result: "null",
+    },
+    CommandSpec {
+        name: HIERARCHY_COMMAND,
+        description: "The hierarchy of instances below a top level entity written as lib.ent(arch)",
+        arguments: &[("top", "string")],
+        result: "object",
+    },
+    CommandSpec {
+        name: COMPILE_ORDER_COMMAND,
+        description: "The library and uri of each file such that dependencies are compiled first",
+        arguments: &[],
+        result: "array",
+    },
+    CommandSpec {
+        name: PROJECT_DIAGNOSTICS_COMMAND,
+        description: "The diagnostics of the project filtered by severity, code and library",
+        arguments: &[("filter?", "object")],
+        result: "object",
+    },
+    CommandSpec {
+        name: RUN_LINT_PROFILE_COMMAND,
+        description: "The diagnostics of the lints of the profile 'rtl', 'testbench' or 'synthesis' within the files of a library or a kind, the target is either { library } or { kind }",
+        arguments: &[("profile", "string"), ("target", "object")],
+        result: "array",
+    },
+    CommandSpec {
+        name: BREADCRUMBS_COMMAND,
+        description: "The constructs enclosing a position, outermost first",
+        arguments: &[("uri", "string"), ("position", "object")],
+        result: "array",
+    },
+    CommandSpec {
+        name: EXPAND_PACKAGE_INSTANCE_COMMAND,
+        description: "The declarations of the generic package instance at a position with the actuals substituted for the generics",
+        arguments: &[("uri", "string"), ("position", "object")],
+        result: "string",
+    },
+    CommandSpec {
+        name: LIST_TESTS_COMMAND,
+        description: "The VUnit and OSVVM test cases of a file",
+        arguments: &[("uri", "string")],
+        result: "array",
+    },
+    CommandSpec {
+        name: STATEMENT_REGIONS_COMMAND,
+        description: "The kind, label and range of the processes and other concurrent statements of a file",
+        arguments: &[("uri", "string")],
+        result: "array",
+    },
+    CommandSpec {
+        name: FILE_DIAGNOSTICS_COMMAND,
+        description: "All diagnostics of a file including those beyond the maximum number per file",
+        arguments: &[("uri", "string")],
+        result: "array",
+    },
+    CommandSpec {
+        name: LIST_COMMANDS_COMMAND,
+        description: "The commands of the server with their arguments and result",
+        arguments: &[],
+        result: "array",
+    },
+];
+
+/// The names of the commands advertised in the capabilities of the server
+pub fn command_names() -> Vec<String> {
+    COMMANDS.iter().map(|spec| spec.name.to_owned()).collect()
+}
+
+/// The registry as JSON
+pub fn list_commands() -> serde_json::Value {
+    COMMANDS
+        .iter()
+        .map(|spec| {
+            serde_json::json!({
+                "name": spec.name,
+                "description": spec.description,
+                "arguments": spec
+                    .arguments
+                    .iter()
+                    .map(|(name, typ)| serde_json::json!({
+                        "name": name.trim_end_matches('?'),
+                        "type": typ,
+                        "optional": name.ends_with('?'),
+                    }))
+                    .collect::<Vec<_>>(),
+                "result": spec.result,
+            })
+        })
+        .collect()
+}
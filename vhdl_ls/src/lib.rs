@@ -8,6 +8,7 @@
 #[macro_use]
 extern crate log;
 
+mod commands;
 mod rpc_channel;
 mod stdio_server;
 mod vhdl_server;
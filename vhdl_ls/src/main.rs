@@ -5,6 +5,7 @@
 // Copyright (c) 2018, Olof Kraigher olof.kraigher@gmail.com
 
 use clap::Parser;
+use std::time::Duration;
 use vhdl_ls::VHDLServerSettings;
 
 #[derive(Parser)]
@@ -18,6 +19,21 @@ struct Args {
     /// This will silence all window/showMessage and only use window/logMessage
     #[arg(long, default_value_t = false)]
     silent: bool,
+
+    /// Time budget in milliseconds for expensive requests such as references, rename and
+    /// workspace symbols after which the results found so far are returned, 0 disables the budget
+    #[arg(long, default_value_t = 2000)]
+    request_timeout: u64,
+
+    /// Maximum number of diagnostics published for a file, the remaining diagnostics are
+    /// summarized and available through the vhdl_ls/fileDiagnostics request, 0 disables the limit
+    #[arg(long, default_value_t = 1000)]
+    max_diagnostics_per_file: usize,
+
+    /// Go to definition goes to the declaration of a subprogram, package or protected type
+    /// instead of its body, go to declaration always goes to the declaration
+    #[arg(long, default_value_t = false)]
+    prefer_declaration: bool,
 }
 
 fn main() {
@@ -28,5 +44,10 @@ fn main() {
     vhdl_ls::start(VHDLServerSettings {
         no_lint: args.no_lint,
         silent: args.silent,
+        request_timeout: (args.request_timeout > 0)
+            .then(|| Duration::from_millis(args.request_timeout)),
+        max_diagnostics_per_file: (args.max_diagnostics_per_file > 0)
+            .then_some(args.max_diagnostics_per_file),
+        prefer_declaration: args.prefer_declaration,
     });
 }
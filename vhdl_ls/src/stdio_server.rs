@@ -8,26 +8,67 @@
 //! It also contains the main event loop for handling incoming messages from the LSP client and
 //! dispatching them to the appropriate server methods.
 
-use lsp_server::{Connection, ExtractError, Request, RequestId};
+use crossbeam_channel::{Receiver, Sender};
+use lsp_server::{Connection, ExtractError, Message, Request, RequestId};
+use lsp_types::notification::Notification as _;
+use lsp_types::request::Request as _;
 use lsp_types::{notification, request, InitializeParams};
 use serde_json::Value;
 
+use std::thread::JoinHandle;
 use std::{cell::RefCell, rc::Rc};
 
 use crate::rpc_channel::{RpcChannel, SharedRpcChannel};
-use crate::vhdl_server::VHDLServer;
-use crate::vhdl_server::VHDLServerSettings;
+use crate::vhdl_server::{AnalysisCancellation, VHDLServerSettings};
+use crate::vhdl_server::{
+    Breadcrumbs, ExpandPackageInstance, FileDiagnostics, ListTests, ProjectDiagnostics,
+    StatementRegions, VHDLServer,
+};
 
 /// Set up the IO channel for `stdio` and start the VHDL language server.
 pub fn start(settings: VHDLServerSettings) {
-    let (connection, io_threads) = Connection::stdio();
+    let (mut connection, io_threads) = Connection::stdio();
+    let (sender, receiver) = crossbeam_channel::unbounded();
+    let incoming = std::mem::replace(&mut connection.receiver, receiver);
     let connection_rpc = Rc::new(ConnectionRpcChannel::new(connection));
     let rpc = SharedRpcChannel::new(connection_rpc.clone());
     let mut server = VHDLServer::new_settings(rpc, settings);
+    let forwarder = forward_messages(incoming, sender, server.analysis_cancellation());
     connection_rpc.handle_initialization(&mut server);
     connection_rpc.main_event_loop(server);
 
     io_threads.join().unwrap();
+    forwarder.join().unwrap();
+}
+
+/// Forward the messages of the client to the main event loop and cancel the ongoing
+/// analysis when a document changes since the analysis of the previous version is superseded
+fn forward_messages(
+    incoming: Receiver<Message>,
+    sender: Sender<Message>,
+    cancellation: AnalysisCancellation,
+) -> JoinHandle<()> {
+    std::thread::spawn(move || {
+        for message in incoming {
+            if let Message::Notification(notification) = &message {
+                if changes_documents(&notification.method) {
+                    cancellation.cancel();
+                }
+            }
+            if sender.send(message).is_err() {
+                break;
+            }
+        }
+    })
+}
+
+fn changes_documents(method: &str) -> bool {
+    matches!(
+        method,
+        notification::DidChangeTextDocument::METHOD
+            | notification::DidOpenTextDocument::METHOD
+            | notification::DidChangeWatchedFiles::METHOD
+    )
 }
 
 /// Wrapper for Connection implementing RpcChannel + Clone
@@ -81,19 +122,47 @@ impl ConnectionRpcChannel {
     }
 
     /// Main event loop handling incoming messages from the client.
+    /// All messages that have arrived are handled as a batch in the order they arrived where
+    /// the analysis is done once before the requests are answered in order of priority.
+    /// Requests are answered before a notification that arrived after them is handled such
+    /// that they see the documents as the client did when sending them.
+    /// Without requests to answer the analysis is cancelled when the documents change again.
     fn main_event_loop(&self, mut server: VHDLServer) {
         info!("Language server initialized, waiting for messages ...");
         while let Ok(message) = self.connection.receiver.recv() {
-            trace!("Received message: {:?}", message);
-            match message {
-                lsp_server::Message::Request(request) => self.handle_request(&mut server, request),
-                lsp_server::Message::Notification(notification) => {
-                    self.handle_notification(&mut server, notification);
-                }
-                lsp_server::Message::Response(response) => {
-                    self.handle_response(&mut server, response)
-                }
-            };
+            let mut requests = Vec::new();
+
+            server.defer_diagnostics();
+            for message in std::iter::once(message).chain(self.connection.receiver.try_iter()) {
+                trace!("Received message: {:?}", message);
+                match message {
+                    Message::Request(request) => requests.push(request),
+                    Message::Notification(notification) => {
+                        if !requests.is_empty() {
+                            server.flush_diagnostics();
+                            self.answer_requests(&mut server, &mut requests);
+                            server.defer_diagnostics();
+                        }
+                        self.handle_notification(&mut server, notification);
+                    }
+                    Message::Response(response) => self.handle_response(&mut server, response),
+                };
+            }
+            if requests.is_empty() {
+                server.flush_diagnostics_cancellable();
+            } else {
+                server.flush_diagnostics();
+                self.answer_requests(&mut server, &mut requests);
+            }
+        }
+    }
+
+    /// Answer the requests in order of priority
+    fn answer_requests(&self, server: &mut VHDLServer, requests: &mut Vec<Request>) {
+        // The sort is stable so requests of the same priority keep their order
+        requests.sort_by_key(|request| request_priority(&request.method));
+        for request in requests.drain(..) {
+            self.handle_request(server, request);
         }
     }
 
@@ -179,6 +248,54 @@ impl ConnectionRpcChannel {
             }
             Err(request) => request,
         };
+        let request = match extract::<request::Formatting>(request) {
+            Ok((id, params)) => {
+                let result = server.document_formatting(&params);
+                self.send_response(lsp_server::Response::new_ok(id, result));
+                return;
+            }
+            Err(request) => request,
+        };
+        let request = match extract::<request::RangeFormatting>(request) {
+            Ok((id, params)) => {
+                let result = server.document_range_formatting(&params);
+                self.send_response(lsp_server::Response::new_ok(id, result));
+                return;
+            }
+            Err(request) => request,
+        };
+        let request = match extract::<request::OnTypeFormatting>(request) {
+            Ok((id, params)) => {
+                let result = server.document_on_type_formatting(&params);
+                self.send_response(lsp_server::Response::new_ok(id, result));
+                return;
+            }
+            Err(request) => request,
+        };
+        let request = match extract::<request::InlayHintRequest>(request) {
+            Ok((id, params)) => {
+                let result = server.inlay_hints(&params);
+                self.send_response(lsp_server::Response::new_ok(id, result));
+                return;
+            }
+            Err(request) => request,
+        };
+        let request = match extract::<request::SemanticTokensFullRequest>(request) {
+            Ok((id, params)) => {
+                let result = server.semantic_tokens_full(&params);
+                self.send_response(lsp_server::Response::new_ok(id, result));
+                return;
+            }
+            Err(request) => request,
+        };
+        let request = match extract::<request::SemanticTokensRangeRequest>(request) {
+            Ok((id, params)) => {
+                let result = server.semantic_tokens_range(&params);
+                self.send_response(lsp_server::Response::new_ok(id, result));
+                return;
+            }
+            Err(request) => request,
+        };
         let request = match extract::<request::HoverRequest>(request) {
             Ok((id, params)) => {
                 let result = server.text_document_hover(&params.text_document_position_params);
@@ -195,6 +312,110 @@ impl ConnectionRpcChannel {
             }
             Err(request) => request,
         };
+        let request = match extract::<request::CallHierarchyPrepare>(request) {
+            Ok((id, params)) => {
+                let result = server.prepare_call_hierarchy(&params);
+                self.send_response(lsp_server::Response::new_ok(id, result));
+                return;
+            }
+            Err(request) => request,
+        };
+        let request = match extract::<request::CallHierarchyIncomingCalls>(request) {
+            Ok((id, params)) => {
+                let result = server.call_hierarchy_incoming_calls(&params);
+                self.send_response(lsp_server::Response::new_ok(id, result));
+                return;
+            }
+            Err(request) => request,
+        };
+        let request = match extract::<request::CallHierarchyOutgoingCalls>(request) {
+            Ok((id, params)) => {
+                let result = server.call_hierarchy_outgoing_calls(&params);
+                self.send_response(lsp_server::Response::new_ok(id, result));
+                return;
+            }
+            Err(request) => request,
+        };
+        let request = match extract::<request::Completion>(request) {
+            Ok((id, params)) => {
+                let result = server.request_completion(&params);
+                self.send_response(lsp_server::Response::new_ok(id, result));
+                return;
+            }
+            Err(request) => request,
+        };
+        let request = match extract::<request::CodeActionRequest>(request) {
+            Ok((id, params)) => {
+                let result = server.text_document_code_action(&params);
+                self.send_response(lsp_server::Response::new_ok(id, result));
+                return;
+            }
+            Err(request) => request,
+        };
+        let request = match extract::<request::WillRenameFiles>(request) {
+            Ok((id, params)) => {
+                let result = server.workspace_will_rename_files(&params);
+                self.send_response(lsp_server::Response::new_ok(id, result));
+                return;
+            }
+            Err(request) => request,
+        };
+        let request = match extract::<request::ExecuteCommand>(request) {
+            Ok((id, params)) => {
+                let result = server.workspace_execute_command(&params);
+                self.send_response(lsp_server::Response::new_ok(id, result));
+                return;
+            }
+            Err(request) => request,
+        };
+        let request = match extract::<Breadcrumbs>(request) {
+            Ok((id, params)) => {
+                let result = server.breadcrumbs(&params);
+                self.send_response(lsp_server::Response::new_ok(id, result));
+                return;
+            }
+            Err(request) => request,
+        };
+        let request = match extract::<ExpandPackageInstance>(request) {
+            Ok((id, params)) => {
+                let result = server.expand_package_instance(&params);
+                self.send_response(lsp_server::Response::new_ok(id, result));
+                return;
+            }
+            Err(request) => request,
+        };
+        let request = match extract::<ListTests>(request) {
+            Ok((id, params)) => {
+                let result = server.list_tests(&params);
+                self.send_response(lsp_server::Response::new_ok(id, result));
+                return;
+            }
+            Err(request) => request,
+        };
+        let request = match extract::<StatementRegions>(request) {
+            Ok((id, params)) => {
+                let result = server.statement_regions(&params);
+                self.send_response(lsp_server::Response::new_ok(id, result));
+                return;
+            }
+            Err(request) => request,
+        };
+        let request = match extract::<ProjectDiagnostics>(request) {
+            Ok((id, params)) => {
+                let result = server.project_diagnostics(&params);
+                self.send_response(lsp_server::Response::new_ok(id, result));
+                return;
+            }
+            Err(request) => request,
+        };
+        let request = match extract::<FileDiagnostics>(request) {
+            Ok((id, params)) => {
+                let result = server.file_diagnostics(&params);
+                self.send_response(lsp_server::Response::new_ok(id, result));
+                return;
+            }
+            Err(request) => request,
+        };
         let request = match extract::<request::Shutdown>(request) {
             Ok((id, _params)) => {
                 server.shutdown_server();
@@ -240,6 +461,11 @@ impl ConnectionRpcChannel {
             Ok(params) => return server.text_document_did_open_notification(&params),
             Err(notification) => notification,
         };
+        // textDocument/didClose
+        let notification = match extract::<notification::DidCloseTextDocument>(notification) {
+            Ok(params) => return server.text_document_did_close_notification(&params),
+            Err(notification) => notification,
+        };
         // workspace.didChangeWatchedFiles
         let notification = match extract::<notification::DidChangeWatchedFiles>(notification) {
             Ok(params) => return server.workspace_did_change_watched_files(&params),
@@ -263,3 +489,191 @@ impl ConnectionRpcChannel {
         // outgoing requests do not require confirmation by the client.
     }
 }
+
+/// Requests that the user is typically waiting on are answered first
+/// while requests that search the whole project are answered last
+fn request_priority(method: &str) -> usize {
+    match method {
+        request::GotoDeclaration::METHOD
+        | request::GotoDefinition::METHOD
+        | request::GotoImplementation::METHOD
+        | request::HoverRequest::METHOD
+        | request::Completion::METHOD
+        | request::DocumentSymbolRequest::METHOD
+        | request::Formatting::METHOD
+        | request::RangeFormatting::METHOD
+        | request::OnTypeFormatting::METHOD
+        | request::InlayHintRequest::METHOD
+        | request::SemanticTokensFullRequest::METHOD
+        | request::SemanticTokensRangeRequest::METHOD
+        | Breadcrumbs::METHOD
+        | ExpandPackageInstance::METHOD
+        | FileDiagnostics::METHOD
+        | ProjectDiagnostics::METHOD
+        | ListTests::METHOD
+        | StatementRegions::METHOD => 0,
+        request::References::METHOD
+        | request::CallHierarchyIncomingCalls::METHOD
+        | request::CallHierarchyOutgoingCalls::METHOD
+        | request::Rename::METHOD
+        | request::WorkspaceSymbolRequest::METHOD => 2,
+        _ => 1,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lsp_types::*;
+
+    #[test]
+    fn requests_are_answered_before_later_changes() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let root_path = tempdir.path().canonicalize().unwrap();
+        let root_uri = Url::from_file_path(&root_path).unwrap();
+        let file_url = Url::from_file_path(root_path.join("ent.vhd")).unwrap();
+
+        let (server_connection, client_connection) = Connection::memory();
+        let Connection { sender, receiver } = client_connection;
+        let send_request = |id: i32, method: &str, params: Value| {
+            sender
+                .send(Request::new(RequestId::from(id), method.to_owned(), params).into())
+                .unwrap();
+        };
+        let send_notification = |method: &str, params: Value| {
+            sender
+                .send(lsp_server::Notification::new(method.to_owned(), params).into())
+                .unwrap();
+        };
+
+        #[allow(deprecated)]
+        let initialize_params = InitializeParams {
+            root_uri: Some(root_uri),
+            ..Default::default()
+        };
+        send_request(
+            0,
+            request::Initialize::METHOD,
+            serde_json::to_value(initialize_params).unwrap(),
+        );
+        send_notification(notification::Initialized::METHOD, serde_json::json!({}));
+        send_notification(
+            notification::DidOpenTextDocument::METHOD,
+            serde_json::to_value(DidOpenTextDocumentParams {
+                text_document: TextDocumentItem {
+                    uri: file_url.clone(),
+                    language_id: "vhdl".to_owned(),
+                    version: 0,
+                    text: "entity ent is\nend entity;\n".to_owned(),
+                },
+            })
+            .unwrap(),
+        );
+        send_request(
+            1,
+            request::DocumentSymbolRequest::METHOD,
+            serde_json::to_value(DocumentSymbolParams {
+                text_document: TextDocumentIdentifier {
+                    uri: file_url.clone(),
+                },
+                work_done_progress_params: Default::default(),
+                partial_result_params: Default::default(),
+            })
+            .unwrap(),
+        );
+        send_notification(
+            notification::DidChangeTextDocument::METHOD,
+            serde_json::to_value(DidChangeTextDocumentParams {
+                text_document: VersionedTextDocumentIdentifier {
+                    uri: file_url,
+                    version: 1,
+                },
+                content_changes: vec![TextDocumentContentChangeEvent {
+                    range: None,
+                    range_length: None,
+                    text: "entity changed is\nend entity;\n".to_owned(),
+                }],
+            })
+            .unwrap(),
+        );
+        send_request(2, request::Shutdown::METHOD, Value::Null);
+        drop(sender);
+
+        let connection_rpc = Rc::new(ConnectionRpcChannel::new(server_connection));
+        let rpc = SharedRpcChannel::new(connection_rpc.clone());
+        let mut server = VHDLServer::new_external_config(rpc, false);
+        connection_rpc.handle_initialization(&mut server);
+        connection_rpc.main_event_loop(server);
+
+        let symbols = receiver
+            .try_iter()
+            .find_map(|message| match message {
+                Message::Response(response) if response.id == RequestId::from(1) => {
+                    response.response_result.ok()
+                }
+                _ => None,
+            })
+            .unwrap()
+            .to_string();
+        assert!(symbols.contains("'ent'"), "{symbols}");
+        assert!(!symbols.contains("changed"), "{symbols}");
+    }
+
+    #[test]
+    fn changes_cancel_the_analysis() {
+        let cancellation = AnalysisCancellation::default();
+        let (sender, incoming) = crossbeam_channel::unbounded();
+        let (forwarded, receiver) = crossbeam_channel::unbounded();
+        let forwarder = forward_messages(incoming, forwarded, cancellation.clone());
+
+        sender
+            .send(
+                Request::new(
+                    RequestId::from(1),
+                    request::HoverRequest::METHOD.to_owned(),
+                    Value::Null,
+                )
+                .into(),
+            )
+            .unwrap();
+        assert!(matches!(receiver.recv(), Ok(Message::Request(_))));
+        assert!(!cancellation.token().is_cancelled());
+
+        sender
+            .send(
+                lsp_server::Notification::new(
+                    notification::DidChangeTextDocument::METHOD.to_owned(),
+                    Value::Null,
+                )
+                .into(),
+            )
+            .unwrap();
+        assert!(matches!(receiver.recv(), Ok(Message::Notification(_))));
+        assert!(cancellation.token().is_cancelled());
+
+        drop(sender);
+        forwarder.join().unwrap();
+    }
+
+    #[test]
+    fn navigation_is_answered_before_searches() {
+        let mut methods = vec![
+            request::References::METHOD,
+            request::Shutdown::METHOD,
+            request::GotoDefinition::METHOD,
+            request::WorkspaceSymbolRequest::METHOD,
+            request::HoverRequest::METHOD,
+        ];
+        methods.sort_by_key(|method| request_priority(method));
+        assert_eq!(
+            methods,
+            vec![
+                request::GotoDefinition::METHOD,
+                request::HoverRequest::METHOD,
+                request::Shutdown::METHOD,
+                request::References::METHOD,
+                request::WorkspaceSymbolRequest::METHOD,
+            ]
+        );
+    }
+}
@@ -11,18 +11,144 @@ use std::collections::hash_map::Entry;
 use std::collections::HashMap;
 use vhdl_lang::ast::{Designator, ObjectClass};
 
+use crate::commands::*;
 use crate::rpc_channel::SharedRpcChannel;
 use std::io;
 use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 use vhdl_lang::{
-    AnyEntKind, Concurrent, Config, Diagnostic, EntHierarchy, EntRef, Message, MessageHandler,
-    Object, Overloaded, Project, Severity, Source, SrcPos, Type,
+    AnyEnt, AnyEntKind, CancellationToken, CompletionItem, Concurrent, Config, Diagnostic,
+    EntHierarchy, Fix, InstantiationForm, ItemKind, LintTarget, Message, MessageHandler, Object,
+    ObjectInterface, Overloaded, Partial, Project, Severity, Source, SrcPos, Type,
 };
 
+/// The types of semantic tokens, the index of a type is its number in the encoded tokens.
+/// VHDL specific types are used where the standard types have no counterpart.
+const SEMANTIC_TOKEN_TYPES: &[SemanticTokenType] = &[
+    SemanticTokenType::NAMESPACE,
+    SemanticTokenType::new("entity"),
+    SemanticTokenType::new("component"),
+    SemanticTokenType::TYPE,
+    SemanticTokenType::ENUM_MEMBER,
+    SemanticTokenType::FUNCTION,
+    SemanticTokenType::new("signal"),
+    SemanticTokenType::VARIABLE,
+    SemanticTokenType::new("constant"),
+    SemanticTokenType::new("generic"),
+    SemanticTokenType::new("port"),
+    SemanticTokenType::PARAMETER,
+    SemanticTokenType::PROPERTY,
+    SemanticTokenType::new("label"),
+];
+
+const SEMANTIC_TOKEN_MODIFIERS: &[SemanticTokenModifier] = &[SemanticTokenModifier::DECLARATION];
+
+/// Custom request for the constructs enclosing a position, outermost first
+/// Editors may render the result as breadcrumbs such as `entity foo > architecture rtl > process p`
+pub enum Breadcrumbs {}
+
+impl request::Request for Breadcrumbs {
+    type Params = TextDocumentPositionParams;
+    type Result = Vec<SymbolInformation>;
+    const METHOD: &'static str = "vhdl_ls/breadcrumbs";
+}
+
+/// Custom request for the declarations of the generic package instance at a position
+/// with the actuals of the generic map substituted for the generics
+/// Editors may show the result as a read-only document
+pub enum ExpandPackageInstance {}
+
+impl request::Request for ExpandPackageInstance {
+    type Params = TextDocumentPositionParams;
+    type Result = Option<String>;
+    const METHOD: &'static str = "vhdl_ls/expandPackageInstance";
+}
+
+/// Custom request for the VUnit and OSVVM test cases of a file
+/// Editors may show code lenses that run the test cases with the simulator of the user
+pub enum ListTests {}
+
+impl request::Request for ListTests {
+    type Params = TextDocumentIdentifier;
+    type Result = Vec<serde_json::Value>;
+    const METHOD: &'static str = "vhdl/listTests";
+}
+
+/// Custom request for the regions of the processes and other concurrent statements of a file
+/// Coverage tools may map the coverage data of a simulator onto the ranges of the statements
+pub enum StatementRegions {}
+
+impl request::Request for StatementRegions {
+    type Params = TextDocumentIdentifier;
+    type Result = Vec<serde_json::Value>;
+    const METHOD: &'static str = "vhdl_ls/statementRegions";
+}
+
+/// Custom request for all diagnostics of a file including those suppressed
+/// by the maximum number of diagnostics per file
+pub enum FileDiagnostics {}
+
+impl request::Request for FileDiagnostics {
+    type Params = TextDocumentIdentifier;
+    type Result = Vec<lsp_types::Diagnostic>;
+    const METHOD: &'static str = "vhdl_ls/fileDiagnostics";
+}
+
+/// Custom request for the diagnostics of all files of the project including those not open in the editor
+/// The optional parameters `severity`, `code` and `library` filter the diagnostics
+/// and `start` and `limit` select a page of them
+pub enum ProjectDiagnostics {}
+
+impl request::Request for ProjectDiagnostics {
+    type Params = serde_json::Value;
+    type Result = serde_json::Value;
+    const METHOD: &'static str = "vhdl/projectDiagnostics";
+}
+
 #[derive(Default, Clone)]
 pub struct VHDLServerSettings {
     pub no_lint: bool,
     pub silent: bool,
+    /// Time budget of expensive requests such as references, rename and workspace symbols
+    /// The results found so far are returned when the budget is exceeded
+    pub request_timeout: Option<Duration>,
+    /// The maximum number of diagnostics published for a file, the rest are summarized
+    pub max_diagnostics_per_file: Option<usize>,
+    /// Go to definition goes to the declaration of a subprogram, package or protected type
+    /// instead of its body
+    pub prefer_declaration: bool,
+}
+
+/// The document symbols and the completions of the declarations of a version of a file
+/// that is shown while the current version has syntax errors
+#[derive(Default)]
+struct LastGood {
+    symbols: Vec<DocumentSymbol>,
+    completions: Vec<lsp_types::CompletionItem>,
+}
+
+/// The cancellation token of the analysis of the changes handled since the last call of
+/// `VHDLServer::defer_diagnostics`, shared with the thread receiving the messages of the client
+/// such that the analysis is stopped when the documents change again
+#[derive(Default, Clone)]
+pub struct AnalysisCancellation {
+    token: Arc<Mutex<CancellationToken>>,
+}
+
+impl AnalysisCancellation {
+    /// Stop the analysis of the changes that were handled so far
+    pub fn cancel(&self) {
+        self.token.lock().unwrap().cancel();
+    }
+
+    pub(crate) fn token(&self) -> CancellationToken {
+        self.token.lock().unwrap().clone()
+    }
+
+    fn renew(&self) {
+        *self.token.lock().unwrap() = CancellationToken::new();
+    }
 }
 
 pub struct VHDLServer {
@@ -31,7 +157,14 @@ pub struct VHDLServer {
     // To have well defined unit tests that are not affected by environment
     use_external_config: bool,
     project: Project,
-    files_with_notifications: FnvHashMap<Url, ()>,
+    // All diagnostics of the files with published diagnostics
+    files_with_notifications: FnvHashMap<Url, Vec<lsp_types::Diagnostic>>,
+    // The last analyzed version without syntax errors of each open file
+    last_good: FnvHashMap<Url, LastGood>,
+    // Analysis is postponed until flush_diagnostics while true
+    defer_diagnostics: bool,
+    analysis_cancellation: AnalysisCancellation,
+    diagnostics_outdated: bool,
     init_params: Option<InitializeParams>,
     config_file: Option<PathBuf>,
 }
@@ -44,19 +177,30 @@ impl VHDLServer {
             use_external_config: true,
             project: Project::new(),
             files_with_notifications: FnvHashMap::default(),
+            last_good: FnvHashMap::default(),
+            defer_diagnostics: false,
+            analysis_cancellation: AnalysisCancellation::default(),
+            diagnostics_outdated: false,
             init_params: None,
             config_file: None,
         }
     }
 
     #[cfg(test)]
-    fn new_external_config(rpc: SharedRpcChannel, use_external_config: bool) -> VHDLServer {
+    pub(crate) fn new_external_config(
+        rpc: SharedRpcChannel,
+        use_external_config: bool,
+    ) -> VHDLServer {
         VHDLServer {
             rpc,
             settings: Default::default(),
             use_external_config,
             project: Project::new(),
             files_with_notifications: FnvHashMap::default(),
+            last_good: FnvHashMap::default(),
+            defer_diagnostics: false,
+            analysis_cancellation: AnalysisCancellation::default(),
+            diagnostics_outdated: false,
             init_params: None,
             config_file: None,
         }
@@ -121,6 +265,7 @@ impl VHDLServer {
             definition_provider: Some(OneOf::Left(true)),
             hover_provider: Some(HoverProviderCapability::Simple(true)),
             references_provider: Some(OneOf::Left(true)),
+            call_hierarchy_provider: Some(CallHierarchyServerCapability::Simple(true)),
             implementation_provider: Some(ImplementationProviderCapability::Simple(true)),
             rename_provider: Some(OneOf::Right(RenameOptions {
                 prepare_provider: Some(true),
@@ -128,6 +273,53 @@ impl VHDLServer {
             })),
             workspace_symbol_provider: Some(OneOf::Left(true)),
             document_symbol_provider: Some(OneOf::Left(true)),
+            document_formatting_provider: Some(OneOf::Left(true)),
+            inlay_hint_provider: Some(OneOf::Left(true)),
+            semantic_tokens_provider: Some(
+                SemanticTokensOptions {
+                    legend: SemanticTokensLegend {
+                        token_types: SEMANTIC_TOKEN_TYPES.to_vec(),
+                        token_modifiers: SEMANTIC_TOKEN_MODIFIERS.to_vec(),
+                    },
+                    range: Some(true),
+                    full: Some(SemanticTokensFullOptions::Bool(true)),
+                    work_done_progress_options: Default::default(),
+                }
+                .into(),
+            ),
+            document_range_formatting_provider: Some(OneOf::Left(true)),
+            document_on_type_formatting_provider: Some(DocumentOnTypeFormattingOptions {
+                first_trigger_character: ";".to_owned(),
+                // The last character of `end`
+                more_trigger_character: Some(vec!["d".to_owned()]),
+            }),
+            code_action_provider: Some(CodeActionProviderCapability::Simple(true)),
+            completion_provider: Some(CompletionOptions {
+                trigger_characters: Some(vec![".".to_owned(), "(".to_owned(), ",".to_owned()]),
+                ..Default::default()
+            }),
+            execute_command_provider: Some(ExecuteCommandOptions {
+                commands: command_names(),
+                work_done_progress_options: Default::default(),
+            }),
+            workspace: Some(WorkspaceServerCapabilities {
+                workspace_folders: None,
+                file_operations: Some(WorkspaceFileOperationsServerCapabilities {
+                    will_rename: Some(FileOperationRegistrationOptions {
+                        filters: vec![FileOperationFilter {
+                            scheme: Some("file".to_owned()),
+                            pattern: FileOperationPattern {
+                                glob: "**/*.{vhd,vhdl}".to_owned(),
+                                matches: Some(FileOperationPatternKind::File),
+                                options: Some(FileOperationPatternOptions {
+                                    ignore_case: Some(true),
+                                }),
+                            },
+                        }],
+                    }),
+                    ..Default::default()
+                }),
+            }),
             ..Default::default()
         };
 
@@ -218,6 +410,7 @@ impl VHDLServer {
     pub fn text_document_did_open_notification(&mut self, params: &DidOpenTextDocumentParams) {
         let TextDocumentItem { uri, text, .. } = &params.text_document;
         let file_name = uri_to_file_name(uri);
+        self.last_good.entry(uri.clone()).or_default();
         if let Some(source) = self.project.get_source(&file_name) {
             source.change(None, text);
             self.project.update_source(&source);
@@ -233,6 +426,10 @@ impl VHDLServer {
         }
     }
 
+    pub fn text_document_did_close_notification(&mut self, params: &DidCloseTextDocumentParams) {
+        self.last_good.remove(&params.text_document.uri);
+    }
+
     pub fn workspace_did_change_watched_files(&mut self, params: &DidChangeWatchedFilesParams) {
         if let Some(config_file) = &self.config_file {
             let config_file_has_changed = params
@@ -252,6 +449,68 @@ impl VHDLServer {
         }
     }
 
+    /// Move renamed files to their new file name in the project before the client renames them
+    /// The returned edit updates the file lists of the configuration file that name the files
+    /// explicitly, files matched by glob patterns are left to the patterns
+    pub fn workspace_will_rename_files(
+        &mut self,
+        params: &RenameFilesParams,
+    ) -> Option<WorkspaceEdit> {
+        let config = self.config_file.as_ref().and_then(|config_file| {
+            Some((
+                config_file.parent()?.to_owned(),
+                std::fs::read_to_string(config_file).ok()?,
+            ))
+        });
+
+        let mut edits = Vec::new();
+        for rename in params.files.iter() {
+            let (old_file_name, new_file_name) =
+                match (Url::parse(&rename.old_uri), Url::parse(&rename.new_uri)) {
+                    (Ok(old_uri), Ok(new_uri)) => {
+                        (uri_to_file_name(&old_uri), uri_to_file_name(&new_uri))
+                    }
+                    _ => continue,
+                };
+            if !self.project.rename_file(&old_file_name, &new_file_name) {
+                continue;
+            }
+
+            if let Some((parent, contents)) = &config {
+                // Files are listed either relative to the configuration file or absolute
+                let mut names = vec![(old_file_name.clone(), new_file_name.clone())];
+                if let (Ok(old_relative), Ok(new_relative)) = (
+                    old_file_name.strip_prefix(parent),
+                    new_file_name.strip_prefix(parent),
+                ) {
+                    names.push((old_relative.to_owned(), new_relative.to_owned()));
+                }
+                for (old_name, new_name) in names {
+                    let new_text = new_name.to_string_lossy().into_owned();
+                    edits.extend(
+                        Config::file_name_ranges(contents, &old_name.to_string_lossy())
+                            .into_iter()
+                            .map(|range| TextEdit {
+                                range: to_lsp_range(range),
+                                new_text: new_text.clone(),
+                            }),
+                    );
+                }
+            }
+        }
+        self.publish_diagnostics();
+
+        if edits.is_empty() {
+            return None;
+        }
+        let mut changes: HashMap<Url, Vec<TextEdit>> = Default::default();
+        changes.insert(file_name_to_uri(self.config_file.as_ref()?), edits);
+        Some(WorkspaceEdit {
+            changes: Some(changes),
+            ..Default::default()
+        })
+    }
+
     fn client_supports_related_information(&self) -> bool {
         let try_fun = || {
             self.init_params
@@ -294,11 +553,58 @@ impl VHDLServer {
         try_fun().unwrap_or(false)
     }
 
+    /// Postpone analysis after changes until `flush_diagnostics` is called
+    /// Such that a burst of changes is analyzed once
+    pub fn defer_diagnostics(&mut self) {
+        self.defer_diagnostics = true;
+        self.analysis_cancellation.renew();
+    }
+
+    /// Analyze and publish diagnostics if there were changes since `defer_diagnostics`
+    pub fn flush_diagnostics(&mut self) {
+        self.defer_diagnostics = false;
+        if std::mem::take(&mut self.diagnostics_outdated) {
+            self.analyse_and_publish(&CancellationToken::new());
+        }
+    }
+
+    /// Like `flush_diagnostics` but the analysis is stopped when it is cancelled through
+    /// `analysis_cancellation`, the diagnostics are then analyzed by the next flush
+    pub fn flush_diagnostics_cancellable(&mut self) {
+        self.defer_diagnostics = false;
+        if std::mem::take(&mut self.diagnostics_outdated) {
+            let token = self.analysis_cancellation.token();
+            if !self.analyse_and_publish(&token) {
+                self.diagnostics_outdated = true;
+            }
+        }
+    }
+
+    /// Cancels the analysis of the deferred changes
+    pub fn analysis_cancellation(&self) -> AnalysisCancellation {
+        self.analysis_cancellation.clone()
+    }
+
     fn publish_diagnostics(&mut self) {
-        let diagnostics = self.project.analyse();
+        if self.defer_diagnostics {
+            self.diagnostics_outdated = true;
+            return;
+        }
+
+        self.analyse_and_publish(&CancellationToken::new());
+    }
+
+    /// False when the analysis was cancelled before any diagnostics were published
+    fn analyse_and_publish(&mut self, token: &CancellationToken) -> bool {
+        let diagnostics = if let Some(diagnostics) = self.project.analyse_cancellable(token) {
+            diagnostics
+        } else {
+            return false;
+        };
+        self.update_last_good();
 
         if self.settings.no_lint {
-            return;
+            return true;
         }
 
         let supports_related_information = self.client_supports_related_information();
@@ -319,14 +625,18 @@ impl VHDLServer {
 
             let publish_diagnostics = PublishDiagnosticsParams {
                 uri: file_uri.clone(),
-                diagnostics: lsp_diagnostics,
+                diagnostics: cap_diagnostics(
+                    &lsp_diagnostics,
+                    self.settings.max_diagnostics_per_file,
+                ),
                 version: None,
             };
 
             self.rpc
                 .send_notification("textDocument/publishDiagnostics", publish_diagnostics);
 
-            self.files_with_notifications.insert(file_uri.clone(), ());
+            self.files_with_notifications
+                .insert(file_uri.clone(), lsp_diagnostics);
         }
 
         for (file_uri, _) in files_with_notifications.drain() {
@@ -342,6 +652,93 @@ impl VHDLServer {
                     .send_notification("textDocument/publishDiagnostics", publish_diagnostics);
             }
         }
+        true
+    }
+
+    /// All diagnostics of the file including those that were not published
+    pub fn file_diagnostics(&self, params: &TextDocumentIdentifier) -> Vec<lsp_types::Diagnostic> {
+        self.files_with_notifications
+            .get(&params.uri)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// The diagnostics of all files ordered by file and position with the total number that matched the filters
+    /// A diagnostic matches the severity filter when it is at least as severe
+    pub fn project_diagnostics(&self, params: &serde_json::Value) -> serde_json::Value {
+        let severity = params
+            .get("severity")
+            .and_then(|value| serde_json::from_value::<DiagnosticSeverity>(value.clone()).ok());
+        let code = params
+            .get("code")
+            .and_then(|value| serde_json::from_value::<NumberOrString>(value.clone()).ok());
+        let library = params.get("library").and_then(|value| value.as_str());
+        let start = params
+            .get("start")
+            .and_then(|value| value.as_u64())
+            .unwrap_or(0) as usize;
+        let limit = params
+            .get("limit")
+            .and_then(|value| value.as_u64())
+            .map_or(usize::MAX, |limit| limit as usize);
+
+        let mut uris: Vec<_> = self.files_with_notifications.keys().collect();
+        uris.sort();
+
+        let mut matching = Vec::new();
+        for uri in uris {
+            let libraries: Vec<String> = self
+                .project
+                .get_source(&uri_to_file_name(uri))
+                .map(|source| {
+                    self.project
+                        .library_mapping_of(&source)
+                        .iter()
+                        .map(|name| name.name_utf8())
+                        .collect()
+                })
+                .unwrap_or_default();
+
+            if let Some(library) = library {
+                if !libraries
+                    .iter()
+                    .any(|name| name.eq_ignore_ascii_case(library))
+                {
+                    continue;
+                }
+            }
+
+            for diagnostic in self.files_with_notifications[uri].iter() {
+                if severity.is_some_and(|severity| {
+                    diagnostic.severity.unwrap_or(DiagnosticSeverity::HINT) > severity
+                }) {
+                    continue;
+                }
+                if code.is_some() && diagnostic.code != code {
+                    continue;
+                }
+                matching.push((uri, libraries.clone(), diagnostic));
+            }
+        }
+
+        let total = matching.len();
+        let diagnostics: Vec<_> = matching
+            .into_iter()
+            .skip(start)
+            .take(limit)
+            .map(|(uri, libraries, diagnostic)| {
+                serde_json::json!({
+                    "uri": uri,
+                    "libraries": libraries,
+                    "diagnostic": diagnostic,
+                })
+            })
+            .collect();
+
+        serde_json::json!({
+            "diagnostics": diagnostics,
+            "total": total,
+        })
     }
 
     pub fn text_document_declaration(
@@ -368,8 +765,13 @@ impl VHDLServer {
 
         let ent = self
             .project
-            .find_definition(&source, from_lsp_pos(params.position))?;
-        Some(srcpos_to_location(ent.decl_pos()?))
+            .find_declaration(&source, from_lsp_pos(params.position))?;
+        let positions = self.project.positions_of(ent)?;
+        if self.settings.prefer_declaration {
+            Some(srcpos_to_location(&positions.declaration))
+        } else {
+            Some(srcpos_to_location(&positions.definition))
+        }
     }
 
     pub fn text_document_implementation(
@@ -423,9 +825,59 @@ impl VHDLServer {
             from_lsp_pos(params.text_document_position.position),
         )?;
 
+        let references = if let AnyEntKind::Library = ent.kind() {
+            match self
+                .project
+                .rename_library(&ent.designator().to_string(), &params.new_name)
+            {
+                Ok(references) => references,
+                Err(err) => {
+                    self.message(Message::error(err));
+                    return None;
+                }
+            }
+        } else {
+            // Components bound by default to an entity must keep the name of the entity
+            let mut result = Vec::new();
+            for ent in std::iter::once(ent).chain(self.project.bound_by_default(ent)) {
+                let references = self.find_all_references(ent);
+                if references.incomplete {
+                    // Renaming only some of the references would break the design
+                    self.message(Message::error(format!(
+                        "Searching for references of {} timed out, nothing was renamed",
+                        ent.describe()
+                    )));
+                    return None;
+                }
+                result.extend(references.result);
+            }
+            result.sort();
+            result.dedup();
+            result
+        };
+
         let mut changes: HashMap<Url, Vec<TextEdit>> = Default::default();
 
-        for srcpos in self.project.find_all_references(ent) {
+        if let AnyEntKind::Library = ent.kind() {
+            // The library mapping of the configuration file must also be renamed
+            if let Some(config_file) = &self.config_file {
+                if let Ok(contents) = std::fs::read_to_string(config_file) {
+                    let edits =
+                        Config::library_name_ranges(&contents, &ent.designator().to_string())
+                            .into_iter()
+                            .map(|range| TextEdit {
+                                range: to_lsp_range(range),
+                                new_text: params.new_name.clone(),
+                            });
+                    changes
+                        .entry(file_name_to_uri(config_file))
+                        .or_default()
+                        .extend(edits);
+                }
+            }
+        }
+
+        for srcpos in references {
             let loc = srcpos_to_location(&srcpos);
             changes.entry(loc.uri).or_default().push(TextEdit {
                 range: loc.range,
@@ -445,9 +897,15 @@ impl VHDLServer {
     ) -> Option<WorkspaceSymbolResponse> {
         let trunc_limit = 200;
         let query = params.query.to_ascii_lowercase();
+        let deadline = self.deadline();
+        let mut incomplete = false;
         let mut symbols: Vec<_> = self
             .project
             .public_symbols()
+            .take_while(|_| {
+                incomplete = deadline.is_some_and(|deadline| Instant::now() >= deadline);
+                !incomplete
+            })
             .filter_map(|ent| match ent.designator() {
                 Designator::Identifier(_) | Designator::Character(_) => {
                     Some((ent, ent.designator().to_string().to_ascii_lowercase()))
@@ -456,6 +914,9 @@ impl VHDLServer {
                 Designator::Anonymous(_) => None,
             })
             .collect();
+        if incomplete {
+            self.warn_incomplete("workspace symbols");
+        }
         symbols.sort_by(|(_, n1), (_, n2)| n1.cmp(n2));
         Some(WorkspaceSymbolResponse::Nested(
             symbols
@@ -480,532 +941,2766 @@ impl VHDLServer {
         ))
     }
 
-    pub fn document_symbol(&self, params: &DocumentSymbolParams) -> Option<DocumentSymbolResponse> {
+    /// Format a whole document, it is left unchanged when it cannot be tokenized
+    pub fn document_formatting(
+        &mut self,
+        params: &DocumentFormattingParams,
+    ) -> Option<Vec<TextEdit>> {
+        let file_name = uri_to_file_name(&params.text_document.uri);
+        let source = self.project.get_source(&file_name)?;
+        match self.project.format(&source) {
+            Ok(formatted) => {
+                let contents = source.contents();
+                let range = vhdl_lang::Range::new(contents.start(), contents.end());
+                if contents.text_in(range) == formatted {
+                    Some(Vec::new())
+                } else {
+                    Some(vec![TextEdit {
+                        range: to_lsp_range(range),
+                        new_text: formatted,
+                    }])
+                }
+            }
+            Err(diagnostic) => {
+                self.message(Message::warning(format!(
+                    "Could not format {}: {}",
+                    file_name.to_string_lossy(),
+                    diagnostic.message
+                )));
+                None
+            }
+        }
+    }
+
+    /// Format the lines within a range of a document
+    pub fn document_range_formatting(
+        &mut self,
+        params: &DocumentRangeFormattingParams,
+    ) -> Option<Vec<TextEdit>> {
+        let file_name = uri_to_file_name(&params.text_document.uri);
+        let source = self.project.get_source(&file_name)?;
+        match self
+            .project
+            .format_range(&source, from_lsp_range(params.range))
+        {
+            Ok(edits) => Some(to_lsp_edits(edits)),
+            Err(diagnostic) => {
+                self.message(Message::warning(format!(
+                    "Could not format {}: {}",
+                    file_name.to_string_lossy(),
+                    diagnostic.message
+                )));
+                None
+            }
+        }
+    }
+
+    /// Re-indent the construct ended by the `end` or `;` typed at the cursor and insert its
+    /// missing closing keyword and label. Code which cannot be tokenized is left unchanged
+    /// without a message as it is often incomplete while typing.
+    pub fn document_on_type_formatting(
+        &mut self,
+        params: &DocumentOnTypeFormattingParams,
+    ) -> Option<Vec<TextEdit>> {
+        let position = &params.text_document_position;
+        let source = self
+            .project
+            .get_source(&uri_to_file_name(&position.text_document.uri))?;
+        self.project
+            .format_on_type(&source, from_lsp_pos(position.position))
+            .ok()
+            .map(to_lsp_edits)
+    }
+
+    /// Show the name of the formal before each positional actual of a call or instance
+    pub fn inlay_hints(&self, params: &InlayHintParams) -> Option<Vec<InlayHint>> {
         let source = self
             .project
             .get_source(&uri_to_file_name(&params.text_document.uri))?;
+        let range = from_lsp_range(params.range);
+        Some(
+            self.project
+                .positional_formals(&source)
+                .into_iter()
+                .filter(|(actual, _)| {
+                    let start = actual.start();
+                    range.start <= start && start < range.end
+                })
+                .map(|(actual, formal)| InlayHint {
+                    position: to_lsp_pos(actual.start()),
+                    label: InlayHintLabel::String(format!("{} =>", formal.designator())),
+                    kind: Some(InlayHintKind::PARAMETER),
+                    text_edits: None,
+                    tooltip: None,
+                    padding_left: None,
+                    padding_right: Some(true),
+                    data: None,
+                })
+                .collect(),
+        )
+    }
 
-        // Some files are mapped to multiple libraries, only use the first library for document symbols
-        let library_name = self
+    /// Classify the resolved names of a document by the kind of entity they denote
+    pub fn semantic_tokens_full(
+        &self,
+        params: &SemanticTokensParams,
+    ) -> Option<SemanticTokensResult> {
+        let source = self
             .project
-            .library_mapping_of(&source)
-            .into_iter()
-            .next()?;
+            .get_source(&uri_to_file_name(&params.text_document.uri))?;
+        Some(self.semantic_tokens(&source, None).into())
+    }
 
-        if self.client_has_hierarchical_document_symbol_support() {
-            fn to_document_symbol(
-                EntHierarchy { ent, children }: EntHierarchy,
-            ) -> Option<DocumentSymbol> {
-                let decl_pos = ent.decl_pos()?;
-                #[allow(deprecated)]
-                Some(DocumentSymbol {
-                    name: ent.describe(),
-                    kind: to_symbol_kind(ent.kind()),
-                    tags: None,
-                    detail: None,
-                    selection_range: to_lsp_range(decl_pos.range),
-                    range: to_lsp_range(decl_pos.range),
-                    children: if !children.is_empty() {
-                        Some(
-                            children
-                                .into_iter()
-                                .filter_map(to_document_symbol)
-                                .collect(),
-                        )
-                    } else {
-                        None
-                    },
-                    deprecated: None,
-                })
+    pub fn semantic_tokens_range(
+        &self,
+        params: &SemanticTokensRangeParams,
+    ) -> Option<SemanticTokensRangeResult> {
+        let source = self
+            .project
+            .get_source(&uri_to_file_name(&params.text_document.uri))?;
+        Some(
+            self.semantic_tokens(&source, Some(from_lsp_range(params.range)))
+                .into(),
+        )
+    }
+
+    fn semantic_tokens(&self, source: &Source, range: Option<vhdl_lang::Range>) -> SemanticTokens {
+        let mut data = Vec::new();
+        let mut prev = vhdl_lang::Position::default();
+        for item in self.project.items_in(source) {
+            let pos = item.pos.range();
+            // Tokens cannot span lines
+            if pos.start.line != pos.end.line {
+                continue;
+            }
+            if let Some(range) = range {
+                if pos.end <= range.start || pos.start >= range.end {
+                    continue;
+                }
             }
+            let token_type = match item.ent.and_then(semantic_token_type) {
+                Some(token_type) => token_type,
+                None => continue,
+            };
+            let token_type = SEMANTIC_TOKEN_TYPES
+                .iter()
+                .position(|typ| *typ == token_type)
+                .unwrap() as u32;
+
+            let delta_line = pos.start.line - prev.line;
+            data.push(SemanticToken {
+                delta_line,
+                delta_start: if delta_line == 0 {
+                    pos.start.character - prev.character
+                } else {
+                    pos.start.character
+                },
+                length: pos.end.character - pos.start.character,
+                token_type,
+                token_modifiers_bitset: u32::from(item.kind == ItemKind::Declaration),
+            });
+            prev = pos.start;
+        }
+        SemanticTokens {
+            result_id: None,
+            data,
+        }
+    }
 
-            Some(DocumentSymbolResponse::Nested(
-                self.project
-                    .document_symbols(&library_name, &source)
-                    .into_iter()
-                    .filter_map(to_document_symbol)
-                    .collect(),
-            ))
+    /// Keep the document symbols and completions of the open files without syntax errors
+    fn update_last_good(&mut self) {
+        let mut last_good = std::mem::take(&mut self.last_good);
+        for (uri, last) in last_good.iter_mut() {
+            if let Some(source) = self.project.get_source(&uri_to_file_name(uri)) {
+                if !self.project.has_parse_errors(&source) {
+                    let hierarchy = self.document_hierarchy(&source).unwrap_or_default();
+                    let mut completions = Vec::new();
+                    for unit in hierarchy.iter() {
+                        add_declaration_completions(unit, &mut completions);
+                    }
+                    *last = LastGood {
+                        symbols: hierarchy
+                            .into_iter()
+                            .filter_map(to_document_symbol)
+                            .collect(),
+                        completions,
+                    };
+                }
+            }
+        }
+        self.last_good = last_good;
+    }
+
+    fn document_hierarchy(&self, source: &Source) -> Option<Vec<EntHierarchy<'_>>> {
+        // Some files are mapped to multiple libraries, only use the first library for document symbols
+        let library_name = self.project.library_mapping_of(source).into_iter().next()?;
+        Some(self.project.document_symbols(&library_name, source))
+    }
+
+    pub fn document_symbol(&self, params: &DocumentSymbolParams) -> Option<DocumentSymbolResponse> {
+        let uri = &params.text_document.uri;
+        let source = self.project.get_source(&uri_to_file_name(uri))?;
+
+        // Design units that fail to parse during an edit are lost from the analysis,
+        // keep showing all symbols of the last version that parsed such that the ranges
+        // of the symbols are consistent
+        let last_good = self
+            .last_good
+            .get(uri)
+            .filter(|last| self.project.has_parse_errors(&source) && !last.symbols.is_empty());
+        let symbols: Vec<DocumentSymbol> = if let Some(last) = last_good {
+            last.symbols.clone()
+        } else {
+            self.document_hierarchy(&source)?
+                .into_iter()
+                .filter_map(to_document_symbol)
+                .collect()
+        };
+
+        if self.client_has_hierarchical_document_symbol_support() {
+            Some(DocumentSymbolResponse::Nested(symbols))
         } else {
-            fn to_symbol_information(ent: EntRef) -> Option<SymbolInformation> {
-                let decl_pos = ent.decl_pos()?;
+            fn to_symbol_information(
+                uri: &Url,
+                symbol: DocumentSymbol,
+                container_name: Option<String>,
+                result: &mut Vec<SymbolInformation>,
+            ) {
                 #[allow(deprecated)]
-                Some(SymbolInformation {
-                    name: ent.describe(),
-                    kind: to_symbol_kind(ent.kind()),
+                result.push(SymbolInformation {
+                    name: symbol.name.clone(),
+                    kind: symbol.kind,
                     tags: None,
-                    location: srcpos_to_location(decl_pos),
+                    location: Location::new(uri.clone(), symbol.range),
                     deprecated: None,
-                    container_name: ent.parent_in_same_source().map(|ent| ent.describe()),
-                })
+                    container_name,
+                });
+                for child in symbol.children.into_iter().flatten() {
+                    to_symbol_information(uri, child, Some(symbol.name.clone()), result);
+                }
             }
 
-            Some(DocumentSymbolResponse::Flat(
-                self.project
-                    .document_symbols(&library_name, &source)
-                    .into_iter()
-                    .flat_map(|ent| ent.into_flat())
-                    .filter_map(to_symbol_information)
-                    .collect(),
-            ))
+            let mut result = Vec::new();
+            for symbol in symbols {
+                to_symbol_information(uri, symbol, None, &mut result);
+            }
+            Some(DocumentSymbolResponse::Flat(result))
         }
     }
 
-    pub fn text_document_hover(&mut self, params: &TextDocumentPositionParams) -> Option<Hover> {
+    /// Complete library, package and declaration names in library clauses, use clauses
+    /// and context references as well as enumeration literals in arguments of calls
+    /// and predefined attributes after the tick of an attribute name
+    pub fn request_completion(&self, params: &CompletionParams) -> CompletionList {
+        let position = &params.text_document_position;
         let source = self
             .project
-            .get_source(&uri_to_file_name(&params.text_document.uri))?;
-        let ent = self
-            .project
-            .find_declaration(&source, from_lsp_pos(params.position))?;
+            .get_source(&uri_to_file_name(&position.text_document.uri));
+        let mut items: Vec<lsp_types::CompletionItem> = source
+            .as_ref()
+            .map(|source| {
+                self.project
+                    .list_completion_options(source, from_lsp_pos(position.position))
+            })
+            .unwrap_or_default()
+            .into_iter()
+            .map(|option| {
+                let (kind, detail) = match option {
+                    CompletionItem::Simple(ent) => {
+                        (to_completion_kind(ent.kind()), Some(ent.describe()))
+                    }
+                    CompletionItem::AllOf(ent) => (
+                        CompletionItemKind::MODULE,
+                        Some(format!("All declarations of {}", ent.describe())),
+                    ),
+                    CompletionItem::All => (CompletionItemKind::KEYWORD, None),
+                    CompletionItem::Attribute(attribute) => (
+                        CompletionItemKind::PROPERTY,
+                        Some(attribute.signature.to_owned()),
+                    ),
+                };
+                let documentation = if let CompletionItem::Attribute(attribute) = option {
+                    Some(Documentation::String(attribute.documentation.to_owned()))
+                } else {
+                    None
+                };
+                lsp_types::CompletionItem {
+                    label: option.name(),
+                    kind: Some(kind),
+                    detail,
+                    documentation,
+                    ..Default::default()
+                }
+            })
+            .collect();
 
-        let value = self.project.format_declaration(ent)?;
+        // Declarations of design units that fail to parse during an edit are lost from
+        // the analysis, complete them from the last version that parsed
+        let has_parse_errors = source
+            .as_ref()
+            .is_some_and(|source| self.project.has_parse_errors(source));
+        if has_parse_errors {
+            if let Some(last) = self.last_good.get(&position.text_document.uri) {
+                for completion in last.completions.iter() {
+                    if !items.iter().any(|item| item.label == completion.label) {
+                        items.push(completion.clone());
+                    }
+                }
+            }
+        }
 
-        Some(Hover {
-            contents: HoverContents::Markup(MarkupContent {
-                kind: MarkupKind::Markdown,
-                value: format!("```vhdl\n{value}\n```"),
-            }),
-            range: None,
-        })
+        CompletionList {
+            is_incomplete: false,
+            items,
+        }
     }
 
-    pub fn text_document_references(&mut self, params: &ReferenceParams) -> Vec<Location> {
-        let ent = self
+    pub fn breadcrumbs(&self, params: &TextDocumentPositionParams) -> Vec<SymbolInformation> {
+        let breadcrumbs = if let Some(source) = self
             .project
-            .get_source(&uri_to_file_name(
-                &params.text_document_position.text_document.uri,
-            ))
-            .and_then(|source| {
-                self.project.find_declaration(
-                    &source,
-                    from_lsp_pos(params.text_document_position.position),
-                )
-            });
-
-        if let Some(ent) = ent {
+            .get_source(&uri_to_file_name(&params.text_document.uri))
+        {
             self.project
-                .find_all_references(ent)
-                .iter()
-                .map(srcpos_to_location)
-                .collect()
+                .breadcrumbs(&source, from_lsp_pos(params.position))
         } else {
-            Vec::new()
+            return Vec::new();
+        };
+
+        let mut symbols: Vec<SymbolInformation> = Vec::with_capacity(breadcrumbs.len());
+        for breadcrumb in breadcrumbs {
+            #[allow(deprecated)]
+            symbols.push(SymbolInformation {
+                container_name: symbols.last().map(|parent| parent.name.clone()),
+                name: breadcrumb.name,
+                kind: SymbolKind::NAMESPACE,
+                tags: None,
+                location: srcpos_to_location(&breadcrumb.pos),
+                deprecated: None,
+            });
         }
+        symbols
     }
 
-    fn message_filter(&self) -> MessageFilter {
-        MessageFilter {
-            silent: self.settings.silent,
-            rpc: self.rpc.clone(),
-        }
+    pub fn expand_package_instance(&self, params: &TextDocumentPositionParams) -> Option<String> {
+        let source = self
+            .project
+            .get_source(&uri_to_file_name(&params.text_document.uri))?;
+        self.project
+            .expand_package_instance(&source, from_lsp_pos(params.position))
     }
 
-    fn message(&self, msg: Message) {
-        self.message_filter().push(msg);
+    /// The test cases of the file with the name, framework, testbench and library of each
+    /// The full name is the VUnit test name such as `lib.tb_uart.send one byte`
+    pub fn list_tests(&self, params: &TextDocumentIdentifier) -> Vec<serde_json::Value> {
+        let source = if let Some(source) = self.project.get_source(&uri_to_file_name(&params.uri)) {
+            source
+        } else {
+            return Vec::new();
+        };
+
+        let library_name = self
+            .project
+            .library_mapping_of(&source)
+            .into_iter()
+            .next()
+            .map(|name| name.name_utf8());
+
+        self.project
+            .test_cases(&source)
+            .into_iter()
+            .map(|test_case| {
+                let testbench = test_case.testbench.name_utf8();
+                let full_name = match library_name {
+                    Some(ref library_name) => {
+                        format!("{library_name}.{testbench}.{}", test_case.name)
+                    }
+                    None => format!("{testbench}.{}", test_case.name),
+                };
+                serde_json::json!({
+                    "name": test_case.name,
+                    "fullName": full_name,
+                    "framework": test_case.framework.describe(),
+                    "testbench": testbench,
+                    "library": library_name,
+                    "location": srcpos_to_location(&test_case.pos),
+                })
+            })
+            .collect()
     }
-}
 
-struct MessageFilter {
-    silent: bool,
-    rpc: SharedRpcChannel,
-}
+    /// The concurrent statements of the file with the kind, label and range of each
+    /// The hierarchy lists the labels of the enclosing statements and generate alternatives
+    pub fn statement_regions(&self, params: &TextDocumentIdentifier) -> Vec<serde_json::Value> {
+        let source = if let Some(source) = self.project.get_source(&uri_to_file_name(&params.uri)) {
+            source
+        } else {
+            return Vec::new();
+        };
 
-impl MessageHandler for MessageFilter {
-    fn push(&mut self, msg: Message) {
-        if !self.silent
-            && matches!(
-                msg.message_type,
-                vhdl_lang::MessageType::Warning | vhdl_lang::MessageType::Error
-            )
+        self.project
+            .statement_regions(&source)
+            .into_iter()
+            .map(|region| {
+                serde_json::json!({
+                    "kind": region.kind,
+                    "label": region.label.map(|label| label.name_utf8()),
+                    "hierarchy": region
+                        .hierarchy
+                        .iter()
+                        .map(|label| label.name_utf8())
+                        .collect::<Vec<_>>(),
+                    "entity": region.entity.name_utf8(),
+                    "architecture": region.architecture.map(|name| name.name_utf8()),
+                    "location": srcpos_to_location(&region.pos),
+                })
+            })
+            .collect()
+    }
+
+    pub fn text_document_hover(&mut self, params: &TextDocumentPositionParams) -> Option<Hover> {
+        let source = self
+            .project
+            .get_source(&uri_to_file_name(&params.text_document.uri))?;
+
+        if let Some(attribute) = self
+            .project
+            .predefined_attribute_at(&source, from_lsp_pos(params.position))
         {
-            self.rpc.send_notification(
-                "window/showMessage",
-                ShowMessageParams {
-                    typ: to_lsp_message_type(&msg.message_type),
-                    message: msg.message.clone(),
-                },
-            );
+            return Some(Hover {
+                contents: HoverContents::Markup(MarkupContent {
+                    kind: MarkupKind::Markdown,
+                    value: format!(
+                        "```vhdl\n{}\n```\n{}",
+                        attribute.signature, attribute.documentation
+                    ),
+                }),
+                range: None,
+            });
         }
 
-        self.rpc.send_notification(
-            "window/logMessage",
-            LogMessageParams {
-                typ: to_lsp_message_type(&msg.message_type),
-                message: msg.message,
-            },
-        );
-    }
-}
+        let ent = self
+            .project
+            .find_declaration(&source, from_lsp_pos(params.position))?;
 
-fn to_lsp_message_type(message_type: &vhdl_lang::MessageType) -> MessageType {
-    match message_type {
-        vhdl_lang::MessageType::Error => MessageType::ERROR,
-        vhdl_lang::MessageType::Warning => MessageType::WARNING,
-        vhdl_lang::MessageType::Info => MessageType::INFO,
-        vhdl_lang::MessageType::Log => MessageType::LOG,
-    }
-}
+        let value = self.project.format_declaration(ent)?;
+        let mut value = format!("```vhdl\n{value}\n```");
 
-fn srcpos_to_location(pos: &SrcPos) -> Location {
-    let uri = file_name_to_uri(pos.source.file_name());
-    Location {
-        uri,
-        range: to_lsp_range(pos.range()),
-    }
-}
+        for library_name in self
+            .project
+            .work_libraries_at(&source, from_lsp_pos(params.position))
+        {
+            value.push_str(&format!(
+                "\n- work denotes library '{library_name}' that this file is compiled into"
+            ));
+        }
 
-fn from_lsp_pos(position: lsp_types::Position) -> vhdl_lang::Position {
-    vhdl_lang::Position {
-        line: position.line,
-        character: position.character,
+        if matches!(ent.kind(), AnyEntKind::Object(object) if object.class == ObjectClass::Signal) {
+            for (architecture, fanout) in self.project.fanout_of(ent) {
+                value.push_str(&format!(
+                    "\n- {} drivers and {} loads within {}",
+                    fanout.drivers,
+                    fanout.loads,
+                    architecture.describe()
+                ));
+            }
+        }
+
+        Some(Hover {
+            contents: HoverContents::Markup(MarkupContent {
+                kind: MarkupKind::Markdown,
+                value,
+            }),
+            range: None,
+        })
     }
-}
 
-fn to_lsp_pos(position: vhdl_lang::Position) -> lsp_types::Position {
-    lsp_types::Position {
-        line: position.line,
-        character: position.character,
+    pub fn text_document_references(&mut self, params: &ReferenceParams) -> Vec<Location> {
+        let ent = self
+            .project
+            .get_source(&uri_to_file_name(
+                &params.text_document_position.text_document.uri,
+            ))
+            .and_then(|source| {
+                self.project.find_declaration(
+                    &source,
+                    from_lsp_pos(params.text_document_position.position),
+                )
+            });
+
+        if let Some(ent) = ent {
+            let references = self.find_all_references(ent);
+            if references.incomplete {
+                self.warn_incomplete(&format!("references of {}", ent.describe()));
+            }
+            references.result.iter().map(srcpos_to_location).collect()
+        } else {
+            Vec::new()
+        }
     }
-}
 
-fn to_lsp_range(range: vhdl_lang::Range) -> lsp_types::Range {
-    lsp_types::Range {
-        start: to_lsp_pos(range.start),
-        end: to_lsp_pos(range.end),
+    /// The subprogram at the cursor as the root of a call hierarchy
+    pub fn prepare_call_hierarchy(
+        &mut self,
+        params: &CallHierarchyPrepareParams,
+    ) -> Option<Vec<CallHierarchyItem>> {
+        let ent = self.call_hierarchy_subprogram(
+            &params.text_document_position_params.text_document.uri,
+            params.text_document_position_params.position,
+        )?;
+        Some(vec![call_hierarchy_item(ent)?])
     }
-}
 
-fn from_lsp_range(range: lsp_types::Range) -> vhdl_lang::Range {
-    vhdl_lang::Range {
-        start: from_lsp_pos(range.start),
-        end: from_lsp_pos(range.end),
+    /// The subprograms, processes and other concurrent statements that call a subprogram
+    pub fn call_hierarchy_incoming_calls(
+        &mut self,
+        params: &CallHierarchyIncomingCallsParams,
+    ) -> Option<Vec<CallHierarchyIncomingCall>> {
+        let ent =
+            self.call_hierarchy_subprogram(&params.item.uri, params.item.selection_range.start)?;
+        let calls = self.project.call_graph().incoming_calls(ent);
+        Some(
+            calls
+                .into_iter()
+                .filter_map(|(caller, positions)| {
+                    Some(CallHierarchyIncomingCall {
+                        from: call_hierarchy_item(caller)?,
+                        from_ranges: positions
+                            .iter()
+                            .map(|pos| to_lsp_range(pos.range()))
+                            .collect(),
+                    })
+                })
+                .collect(),
+        )
     }
-}
 
-fn diagnostics_by_uri(diagnostics: Vec<Diagnostic>) -> FnvHashMap<Url, Vec<Diagnostic>> {
-    let mut map: FnvHashMap<Url, Vec<Diagnostic>> = FnvHashMap::default();
+    /// The subprograms called from the body of a subprogram
+    pub fn call_hierarchy_outgoing_calls(
+        &mut self,
+        params: &CallHierarchyOutgoingCallsParams,
+    ) -> Option<Vec<CallHierarchyOutgoingCall>> {
+        let ent =
+            self.call_hierarchy_subprogram(&params.item.uri, params.item.selection_range.start)?;
+        let calls = self.project.call_graph().outgoing_calls(ent);
+        Some(
+            calls
+                .into_iter()
+                .filter_map(|(callee, positions)| {
+                    Some(CallHierarchyOutgoingCall {
+                        to: call_hierarchy_item(callee)?,
+                        from_ranges: positions
+                            .iter()
+                            .map(|pos| to_lsp_range(pos.range()))
+                            .collect(),
+                    })
+                })
+                .collect(),
+        )
+    }
 
-    for diagnostic in diagnostics {
-        let uri = file_name_to_uri(diagnostic.pos.source.file_name());
-        match map.entry(uri) {
-            Entry::Occupied(mut entry) => entry.get_mut().push(diagnostic),
-            Entry::Vacant(entry) => {
-                let vec = vec![diagnostic];
-                entry.insert(vec);
-            }
+    /// The declaration of the subprogram at a position, a subprogram with a separate
+    /// declaration is represented by the declaration
+    fn call_hierarchy_subprogram(&self, uri: &Url, position: Position) -> Option<&AnyEnt<'_>> {
+        let source = self.project.get_source(&uri_to_file_name(uri))?;
+        let ent = self
+            .project
+            .find_declaration(&source, from_lsp_pos(position))?
+            .declaration();
+        if ent.is_subprogram() || ent.is_subprogram_decl() {
+            Some(ent)
+        } else {
+            None
         }
     }
 
-    map
-}
+    /// Offer the fixes of the diagnostics at the cursor, to group the ports of the entity
+    /// at the cursor, to name the positional associations of the instance at the cursor
+    /// and to move the design unit at the cursor to a file of its own
+    pub fn text_document_code_action(
+        &mut self,
+        params: &CodeActionParams,
+    ) -> Option<CodeActionResponse> {
+        let uri = &params.text_document.uri;
+        let source = self.project.get_source(&uri_to_file_name(uri))?;
+        let cursor = from_lsp_pos(params.range.start);
+
+        let actions: CodeActionResponse = params
+            .context
+            .diagnostics
+            .iter()
+            .flat_map(diagnostic_fixes)
+            .chain(
+                [
+                    self.sync_component_action(uri, &source, cursor),
+                    self.group_ports_action(&source, cursor),
+                    self.named_associations_action(&source, cursor),
+                    self.extract_design_unit_action(uri, &source, cursor),
+                ]
+                .into_iter()
+                .flatten(),
+            )
+            .map(CodeActionOrCommand::CodeAction)
+            .collect();
 
-fn flatten_related(diagnostics: Vec<Diagnostic>) -> Vec<Diagnostic> {
-    let mut flat_diagnostics = Vec::new();
-    for mut diagnostic in diagnostics {
-        flat_diagnostics.extend(diagnostic.drain_related());
-        flat_diagnostics.push(diagnostic);
+        if actions.is_empty() {
+            None
+        } else {
+            Some(actions)
+        }
     }
-    flat_diagnostics
-}
-
-fn file_name_to_uri(file_name: &Path) -> Url {
-    // @TODO return error to client
-    Url::from_file_path(file_name).unwrap()
-}
 
-fn uri_to_file_name(uri: &Url) -> PathBuf {
-    // @TODO return error to client
-    uri.to_file_path().unwrap()
-}
+    fn sync_component_action(
+        &self,
+        uri: &Url,
+        source: &Source,
+        cursor: vhdl_lang::Position,
+    ) -> Option<CodeAction> {
+        let (pos, new_text) = self.project.sync_component(source, cursor)?;
+
+        let mut changes = HashMap::new();
+        changes.insert(
+            uri.clone(),
+            vec![TextEdit {
+                range: to_lsp_range(pos.range()),
+                new_text,
+            }],
+        );
 
-fn to_lsp_diagnostic(diagnostic: Diagnostic) -> lsp_types::Diagnostic {
-    let severity = match diagnostic.severity {
-        Severity::Error => DiagnosticSeverity::ERROR,
-        Severity::Warning => DiagnosticSeverity::WARNING,
-        Severity::Info => DiagnosticSeverity::INFORMATION,
-        Severity::Hint => DiagnosticSeverity::HINT,
-    };
+        Some(CodeAction {
+            title: "Sync component with entity".to_owned(),
+            kind: Some(CodeActionKind::QUICKFIX),
+            edit: Some(WorkspaceEdit {
+                changes: Some(changes),
+                ..Default::default()
+            }),
+            ..Default::default()
+        })
+    }
 
-    let related_information = if !diagnostic.related.is_empty() {
-        let mut related_information = Vec::new();
-        for (pos, msg) in diagnostic.related {
-            let uri = file_name_to_uri(pos.source.file_name());
-            related_information.push(DiagnosticRelatedInformation {
-                location: Location {
-                    uri: uri.to_owned(),
-                    range: to_lsp_range(pos.range()),
-                },
-                message: msg,
+    fn group_ports_action(
+        &self,
+        source: &Source,
+        cursor: vhdl_lang::Position,
+    ) -> Option<CodeAction> {
+        let edits = self.project.group_ports(source, cursor)?;
+        Some(CodeAction {
+            kind: Some(CodeActionKind::REFACTOR_REWRITE),
+            ..to_code_action(Fix {
+                title: "Group ports by mode".to_owned(),
+                edits,
+                is_safe: true,
             })
-        }
-        Some(related_information)
-    } else {
-        None
-    };
+        })
+    }
 
-    lsp_types::Diagnostic {
-        range: to_lsp_range(diagnostic.pos.range()),
-        severity: Some(severity),
-        code: None,
-        source: Some("vhdl ls".to_owned()),
-        message: diagnostic.message,
-        related_information,
-        ..Default::default()
+    fn named_associations_action(
+        &self,
+        source: &Source,
+        cursor: vhdl_lang::Position,
+    ) -> Option<CodeAction> {
+        let edits = self.project.named_associations(source, cursor)?;
+        Some(CodeAction {
+            kind: Some(CodeActionKind::REFACTOR_REWRITE),
+            ..to_code_action(Fix {
+                title: "Convert to named associations".to_owned(),
+                edits,
+                is_safe: true,
+            })
+        })
     }
-}
 
-fn overloaded_kind(overloaded: &Overloaded) -> SymbolKind {
-    match overloaded {
-        Overloaded::SubprogramDecl(_) => SymbolKind::FUNCTION,
-        Overloaded::Subprogram(_) => SymbolKind::FUNCTION,
-        Overloaded::InterfaceSubprogram(_) => SymbolKind::FUNCTION,
-        Overloaded::EnumLiteral(_) => SymbolKind::ENUM_MEMBER,
-        Overloaded::Alias(o) => overloaded_kind(o.kind()),
+    fn extract_design_unit_action(
+        &self,
+        uri: &Url,
+        source: &Source,
+        cursor: vhdl_lang::Position,
+    ) -> Option<CodeAction> {
+        let extracted = self.project.extract_design_unit(source, cursor)?;
+
+        let new_uri = file_name_to_uri(&extracted.file_name);
+        let file_name = extracted.file_name.file_name()?.to_string_lossy();
+        let title = format!("Move design unit to '{file_name}'");
+
+        let edit = WorkspaceEdit {
+            document_changes: Some(DocumentChanges::Operations(vec![
+                DocumentChangeOperation::Op(ResourceOp::Create(CreateFile {
+                    uri: new_uri.clone(),
+                    options: None,
+                    annotation_id: None,
+                })),
+                DocumentChangeOperation::Edit(TextDocumentEdit {
+                    text_document: OptionalVersionedTextDocumentIdentifier {
+                        uri: new_uri.clone(),
+                        version: None,
+                    },
+                    edits: vec![OneOf::Left(TextEdit {
+                        range: lsp_types::Range::default(),
+                        new_text: extracted.contents.clone(),
+                    })],
+                }),
+                DocumentChangeOperation::Edit(TextDocumentEdit {
+                    text_document: OptionalVersionedTextDocumentIdentifier {
+                        uri: uri.clone(),
+                        version: None,
+                    },
+                    edits: vec![OneOf::Left(TextEdit {
+                        range: to_lsp_range(extracted.range),
+                        new_text: String::new(),
+                    })],
+                }),
+            ])),
+            ..Default::default()
+        };
+
+        // The command is executed after the edit is applied to add the new file to the project
+        let command = Command {
+            title: title.clone(),
+            command: ADD_EXTRACTED_FILE_COMMAND.to_owned(),
+            arguments: Some(vec![
+                serde_json::to_value(&new_uri).unwrap(),
+                serde_json::to_value(&extracted.contents).unwrap(),
+                serde_json::to_value(uri).unwrap(),
+            ]),
+        };
+
+        Some(CodeAction {
+            title,
+            kind: Some(CodeActionKind::REFACTOR_EXTRACT),
+            edit: Some(edit),
+            command: Some(command),
+            ..Default::default()
+        })
     }
-}
 
-fn object_kind(object: &Object) -> SymbolKind {
-    if matches!(object.subtype.type_mark().kind(), Type::Protected(..)) {
-        SymbolKind::OBJECT
-    } else if object.iface.is_some() {
-        SymbolKind::INTERFACE
-    } else {
-        object_class_kind(object.class)
+    pub fn workspace_execute_command(
+        &mut self,
+        params: &ExecuteCommandParams,
+    ) -> Option<serde_json::Value> {
+        match params.command.as_str() {
+            ADD_EXTRACTED_FILE_COMMAND => {
+                self.add_extracted_file(&params.arguments);
+                None
+            }
+            INSTANTIATION_TEXT_COMMAND => self
+                .instantiation_text(&params.arguments)
+                .map(serde_json::Value::String),
+            INSERT_INSTANTIATION_COMMAND => {
+                self.insert_instantiation(&params.arguments);
+                None
+            }
+            HIERARCHY_COMMAND => self.hierarchy(&params.arguments),
+            COMPILE_ORDER_COMMAND => Some(self.compile_order()),
+            PROJECT_DIAGNOSTICS_COMMAND => {
+                Some(self.project_diagnostics(
+                    params.arguments.first().unwrap_or(&serde_json::Value::Null),
+                ))
+            }
+            RUN_LINT_PROFILE_COMMAND => self.run_lint_profile(&params.arguments),
+            BREADCRUMBS_COMMAND => {
+                self.position_command(params, |server, position| server.breadcrumbs(position))
+            }
+            EXPAND_PACKAGE_INSTANCE_COMMAND => self.position_command(params, |server, position| {
+                server.expand_package_instance(position)
+            }),
+            LIST_TESTS_COMMAND => {
+                self.document_command(params, |server, document| server.list_tests(document))
+            }
+            STATEMENT_REGIONS_COMMAND => self.document_command(params, |server, document| {
+                server.statement_regions(document)
+            }),
+            FILE_DIAGNOSTICS_COMMAND => {
+                self.document_command(params, |server, document| server.file_diagnostics(document))
+            }
+            LIST_COMMANDS_COMMAND => Some(list_commands()),
+            _ => {
+                self.message(Message::error(format!(
+                    "Unknown command '{}'",
+                    params.command
+                )));
+                None
+            }
+        }
+    }
+
+    /// Run a command whose argument is the uri of a document as the custom request of the same name
+    fn document_command<T: serde::Serialize>(
+        &self,
+        params: &ExecuteCommandParams,
+        handler: impl Fn(&Self, &TextDocumentIdentifier) -> T,
+    ) -> Option<serde_json::Value> {
+        let document = match params.arguments.as_slice() {
+            [uri] => serde_json::from_value::<Url>(uri.clone()).ok(),
+            _ => None,
+        };
+        if let Some(uri) = document {
+            serde_json::to_value(handler(self, &TextDocumentIdentifier { uri })).ok()
+        } else {
+            self.message(Message::error(format!(
+                "Invalid arguments to command '{}'",
+                params.command
+            )));
+            None
+        }
+    }
+
+    /// Run a command whose arguments are the uri of a document and a position within it
+    /// as the custom request of the same name
+    fn position_command<T: serde::Serialize>(
+        &self,
+        params: &ExecuteCommandParams,
+        handler: impl Fn(&Self, &TextDocumentPositionParams) -> T,
+    ) -> Option<serde_json::Value> {
+        let position = match params.arguments.as_slice() {
+            [uri, position] => serde_json::from_value::<Url>(uri.clone())
+                .ok()
+                .zip(serde_json::from_value::<Position>(position.clone()).ok()),
+            _ => None,
+        };
+        if let Some((uri, position)) = position {
+            let params = TextDocumentPositionParams {
+                text_document: TextDocumentIdentifier { uri },
+                position,
+            };
+            serde_json::to_value(handler(self, &params)).ok()
+        } else {
+            self.message(Message::error(format!(
+                "Invalid arguments to command '{}'",
+                params.command
+            )));
+            None
+        }
+    }
+
+    /// The arguments are the name of the lint profile and the target which is either
+    /// `{ "library": name }` or `{ "kind": "rtl" | "testbench" }`
+    /// The result has the uri and the diagnostic of each report ordered by file
+    fn run_lint_profile(&mut self, arguments: &[serde_json::Value]) -> Option<serde_json::Value> {
+        let parsed = match arguments {
+            [profile, target] => profile.as_str().zip(
+                if let Some(library) = target.get("library").and_then(|value| value.as_str()) {
+                    Some(LintTarget::Library(library.to_owned()))
+                } else {
+                    target
+                        .get("kind")
+                        .and_then(|value| value.as_str())
+                        .and_then(|kind| kind.parse().ok())
+                        .map(LintTarget::Kind)
+                },
+            ),
+            _ => None,
+        };
+        let (profile, target) = if let Some(parsed) = parsed {
+            parsed
+        } else {
+            self.message(Message::error(format!(
+                "Invalid arguments to command '{RUN_LINT_PROFILE_COMMAND}'"
+            )));
+            return None;
+        };
+
+        let diagnostics = match self.project.run_lint_profile(profile, &target) {
+            Ok(diagnostics) => diagnostics,
+            Err(err) => {
+                self.message(Message::error(err));
+                return None;
+            }
+        };
+        let mut by_uri: Vec<_> = diagnostics_by_uri(diagnostics).into_iter().collect();
+        by_uri.sort_by(|(left, _), (right, _)| left.cmp(right));
+        Some(
+            by_uri
+                .into_iter()
+                .flat_map(|(uri, diagnostics)| {
+                    diagnostics.into_iter().map(move |diagnostic| {
+                        serde_json::json!({
+                            "uri": uri,
+                            "diagnostic": to_lsp_diagnostic(diagnostic),
+                        })
+                    })
+                })
+                .collect(),
+        )
+    }
+
+    fn add_extracted_file(&mut self, arguments: &[serde_json::Value]) {
+        fn parse_arguments(arguments: &[serde_json::Value]) -> Option<(Url, String, Url)> {
+            match arguments {
+                [new_uri, contents, origin_uri] => Some((
+                    serde_json::from_value(new_uri.clone()).ok()?,
+                    serde_json::from_value(contents.clone()).ok()?,
+                    serde_json::from_value(origin_uri.clone()).ok()?,
+                )),
+                _ => None,
+            }
+        }
+
+        if let Some((new_uri, contents, origin_uri)) = parse_arguments(arguments) {
+            let source = Source::inline(&uri_to_file_name(&new_uri), &contents);
+            self.project
+                .add_extracted_source(&source, &uri_to_file_name(&origin_uri));
+            self.publish_diagnostics();
+        } else {
+            self.message(Message::error(format!(
+                "Invalid arguments to command '{ADD_EXTRACTED_FILE_COMMAND}'"
+            )));
+        }
+    }
+
+    /// The arguments are the library name, the entity name, optionally the form
+    /// which is either "entity" for a direct instantiation or "component" and optionally
+    /// whether the text is a snippet with placeholders for the label and the actuals
+    fn instantiation_text(&self, arguments: &[serde_json::Value]) -> Option<String> {
+        let parsed = match arguments {
+            [library_name, entity_name] => Some((library_name, entity_name, None, None)),
+            [library_name, entity_name, form] => {
+                Some((library_name, entity_name, Some(form), None))
+            }
+            [library_name, entity_name, form, snippet] => {
+                Some((library_name, entity_name, Some(form), Some(snippet)))
+            }
+            _ => None,
+        }
+        .and_then(|(library_name, entity_name, form, snippet)| {
+            let snippet = match snippet {
+                Some(snippet) => snippet.as_bool()?,
+                None => false,
+            };
+            Some((
+                parse_instantiated_entity(library_name, entity_name, form)?,
+                snippet,
+            ))
+        });
+
+        let ((library_name, entity_name, form), snippet) = if let Some(parsed) = parsed {
+            parsed
+        } else {
+            self.message(Message::error(format!(
+                "Invalid arguments to command '{INSTANTIATION_TEXT_COMMAND}'"
+            )));
+            return None;
+        };
+
+        let text = if snippet {
+            self.project
+                .instantiation_snippet(&library_name, &entity_name, form)
+        } else {
+            self.project
+                .instantiation_text(&library_name, &entity_name, form)
+        };
+        if text.is_none() {
+            self.message(Message::error(format!(
+                "No entity '{entity_name}' in library '{library_name}'"
+            )));
+        }
+        text
+    }
+
+    /// The arguments are the library name, the entity name, the uri and the position
+    /// where the instance is inserted and optionally the form as for the instantiation text
+    /// The client is asked to apply the edit since only it knows the target location
+    fn insert_instantiation(&self, arguments: &[serde_json::Value]) {
+        let parsed = match arguments {
+            [library_name, entity_name, uri, position] => {
+                Some((library_name, entity_name, uri, position, None))
+            }
+            [library_name, entity_name, uri, position, form] => {
+                Some((library_name, entity_name, uri, position, Some(form)))
+            }
+            _ => None,
+        }
+        .and_then(|(library_name, entity_name, uri, position, form)| {
+            Some((
+                parse_instantiated_entity(library_name, entity_name, form)?,
+                serde_json::from_value::<Url>(uri.clone()).ok()?,
+                serde_json::from_value::<Position>(position.clone()).ok()?,
+            ))
+        });
+
+        let ((library_name, entity_name, form), uri, position) = if let Some(parsed) = parsed {
+            parsed
+        } else {
+            self.message(Message::error(format!(
+                "Invalid arguments to command '{INSERT_INSTANTIATION_COMMAND}'"
+            )));
+            return;
+        };
+
+        let text = if let Some(text) =
+            self.project
+                .instantiation_text(&library_name, &entity_name, form)
+        {
+            text
+        } else {
+            self.message(Message::error(format!(
+                "No entity '{entity_name}' in library '{library_name}'"
+            )));
+            return;
+        };
+
+        // Indent the lines that follow to the column of the insertion
+        let indent = " ".repeat(position.character as usize);
+        let new_text = text.replace('\n', &format!("\n{indent}"));
+
+        let mut changes = HashMap::new();
+        changes.insert(
+            uri,
+            vec![TextEdit {
+                range: Range::new(position, position),
+                new_text,
+            }],
+        );
+        self.rpc.send_request(
+            "workspace/applyEdit",
+            ApplyWorkspaceEditParams {
+                label: Some(format!("Instantiate {library_name}.{entity_name}")),
+                edit: WorkspaceEdit {
+                    changes: Some(changes),
+                    ..Default::default()
+                },
+            },
+        );
+    }
+
+    /// The argument is the top level entity written as `lib.ent(arch)`
+    fn hierarchy(&self, arguments: &[serde_json::Value]) -> Option<serde_json::Value> {
+        let top = match arguments {
+            [top] => top.as_str(),
+            _ => None,
+        };
+        let top = if let Some(top) = top {
+            top
+        } else {
+            self.message(Message::error(format!(
+                "Invalid arguments to command '{HIERARCHY_COMMAND}'"
+            )));
+            return None;
+        };
+
+        match self.project.elaborate(top) {
+            Ok(elaboration) => serde_json::from_str(&elaboration.to_json()).ok(),
+            Err(err) => {
+                self.message(Message::error(err));
+                None
+            }
+        }
+    }
+
+    fn compile_order(&self) -> serde_json::Value {
+        self.project
+            .compile_order()
+            .into_iter()
+            .map(|(library_name, file_name)| {
+                serde_json::json!({
+                    "library": library_name,
+                    "uri": file_name_to_uri(&file_name),
+                })
+            })
+            .collect()
+    }
+
+    /// The time at which an expensive request should return what it has found so far
+    fn deadline(&self) -> Option<Instant> {
+        self.settings
+            .request_timeout
+            .map(|timeout| Instant::now() + timeout)
+    }
+
+    fn find_all_references(&self, ent: &AnyEnt) -> Partial<Vec<SrcPos>> {
+        if let Some(deadline) = self.deadline() {
+            self.project.find_all_references_until(ent, deadline)
+        } else {
+            Partial {
+                result: self.project.find_all_references(ent),
+                incomplete: false,
+            }
+        }
+    }
+
+    fn warn_incomplete(&self, what: &str) {
+        self.message(Message::warning(format!(
+            "Searching for {what} timed out, the results are incomplete"
+        )));
+    }
+
+    fn message_filter(&self) -> MessageFilter {
+        MessageFilter {
+            silent: self.settings.silent,
+            rpc: self.rpc.clone(),
+        }
+    }
+
+    fn message(&self, msg: Message) {
+        self.message_filter().push(msg);
     }
 }
-fn object_class_kind(class: ObjectClass) -> SymbolKind {
-    match class {
-        ObjectClass::Signal => SymbolKind::EVENT,
-        ObjectClass::Constant => SymbolKind::CONSTANT,
-        ObjectClass::Variable => SymbolKind::VARIABLE,
-        ObjectClass::SharedVariable => SymbolKind::VARIABLE,
+
+struct MessageFilter {
+    silent: bool,
+    rpc: SharedRpcChannel,
+}
+
+impl MessageHandler for MessageFilter {
+    fn push(&mut self, msg: Message) {
+        if !self.silent
+            && matches!(
+                msg.message_type,
+                vhdl_lang::MessageType::Warning | vhdl_lang::MessageType::Error
+            )
+        {
+            self.rpc.send_notification(
+                "window/showMessage",
+                ShowMessageParams {
+                    typ: to_lsp_message_type(&msg.message_type),
+                    message: msg.message.clone(),
+                },
+            );
+        }
+
+        self.rpc.send_notification(
+            "window/logMessage",
+            LogMessageParams {
+                typ: to_lsp_message_type(&msg.message_type),
+                message: msg.message,
+            },
+        );
     }
 }
 
-fn type_kind(t: &Type) -> SymbolKind {
-    match t {
-        vhdl_lang::Type::Array { .. } => SymbolKind::ARRAY,
-        vhdl_lang::Type::Enum(_) => SymbolKind::ENUM,
-        vhdl_lang::Type::Integer => SymbolKind::NUMBER,
-        vhdl_lang::Type::Real => SymbolKind::NUMBER,
-        vhdl_lang::Type::Physical => SymbolKind::NUMBER,
-        vhdl_lang::Type::Access(_) => SymbolKind::ENUM,
-        vhdl_lang::Type::Record(_) => SymbolKind::STRUCT,
-        vhdl_lang::Type::Incomplete => SymbolKind::NULL,
-        vhdl_lang::Type::Subtype(t) => type_kind(t.type_mark().kind()),
-        vhdl_lang::Type::Protected(_, _) => SymbolKind::CLASS,
-        vhdl_lang::Type::File => SymbolKind::FILE,
-        vhdl_lang::Type::Interface => SymbolKind::TYPE_PARAMETER,
-        vhdl_lang::Type::Alias(t) => type_kind(t.kind()),
-        vhdl_lang::Type::Universal(_) => SymbolKind::NUMBER,
+fn to_lsp_message_type(message_type: &vhdl_lang::MessageType) -> MessageType {
+    match message_type {
+        vhdl_lang::MessageType::Error => MessageType::ERROR,
+        vhdl_lang::MessageType::Warning => MessageType::WARNING,
+        vhdl_lang::MessageType::Info => MessageType::INFO,
+        vhdl_lang::MessageType::Log => MessageType::LOG,
     }
 }
 
-fn to_symbol_kind(kind: &AnyEntKind) -> SymbolKind {
-    match kind {
-        AnyEntKind::ExternalAlias { class, .. } => object_class_kind(ObjectClass::from(*class)),
-        AnyEntKind::ObjectAlias { base_object, .. } => object_kind(base_object.object()),
-        AnyEntKind::Object(o) => object_kind(o),
-        AnyEntKind::LoopParameter(_) => SymbolKind::CONSTANT,
-        AnyEntKind::PhysicalLiteral(_) => SymbolKind::CONSTANT,
-        AnyEntKind::DeferredConstant(_) => SymbolKind::CONSTANT,
-        AnyEntKind::File { .. } => SymbolKind::FILE,
-        AnyEntKind::InterfaceFile { .. } => SymbolKind::INTERFACE,
-        AnyEntKind::Component(_) => SymbolKind::CLASS,
-        AnyEntKind::Attribute(_) => SymbolKind::PROPERTY,
-        AnyEntKind::Overloaded(o) => overloaded_kind(o),
-        AnyEntKind::Type(t) => type_kind(t),
-        AnyEntKind::ElementDeclaration(_) => SymbolKind::FIELD,
-        AnyEntKind::Sequential(_) => SymbolKind::NAMESPACE,
-        AnyEntKind::Concurrent(Some(Concurrent::Instance)) => SymbolKind::MODULE,
-        AnyEntKind::Concurrent(_) => SymbolKind::NAMESPACE,
-        AnyEntKind::Library => SymbolKind::NAMESPACE,
-        AnyEntKind::Design(d) => match d {
-            vhdl_lang::Design::Entity(_, _) => SymbolKind::MODULE,
-            vhdl_lang::Design::Architecture(_) => SymbolKind::MODULE,
-            vhdl_lang::Design::Configuration => SymbolKind::MODULE,
-            vhdl_lang::Design::Package(_, _) => SymbolKind::PACKAGE,
-            vhdl_lang::Design::PackageBody => SymbolKind::PACKAGE,
-            vhdl_lang::Design::UninstPackage(_, _) => SymbolKind::PACKAGE,
-            vhdl_lang::Design::PackageInstance(_) => SymbolKind::PACKAGE,
-            vhdl_lang::Design::Context(_) => SymbolKind::NAMESPACE,
-        },
+fn srcpos_to_location(pos: &SrcPos) -> Location {
+    let uri = file_name_to_uri(pos.source.file_name());
+    Location {
+        uri,
+        range: to_lsp_range(pos.range()),
+    }
+}
+
+fn from_lsp_pos(position: lsp_types::Position) -> vhdl_lang::Position {
+    vhdl_lang::Position {
+        line: position.line,
+        character: position.character,
+    }
+}
+
+fn to_lsp_pos(position: vhdl_lang::Position) -> lsp_types::Position {
+    lsp_types::Position {
+        line: position.line,
+        character: position.character,
     }
 }
 
-#[cfg(test)]
-mod tests {
-    use std::rc::Rc;
+fn to_lsp_range(range: vhdl_lang::Range) -> lsp_types::Range {
+    lsp_types::Range {
+        start: to_lsp_pos(range.start),
+        end: to_lsp_pos(range.end),
+    }
+}
+
+fn from_lsp_range(range: lsp_types::Range) -> vhdl_lang::Range {
+    vhdl_lang::Range {
+        start: from_lsp_pos(range.start),
+        end: from_lsp_pos(range.end),
+    }
+}
+
+fn diagnostics_by_uri(diagnostics: Vec<Diagnostic>) -> FnvHashMap<Url, Vec<Diagnostic>> {
+    let mut map: FnvHashMap<Url, Vec<Diagnostic>> = FnvHashMap::default();
+
+    for diagnostic in diagnostics {
+        let uri = file_name_to_uri(diagnostic.pos.source.file_name());
+        match map.entry(uri) {
+            Entry::Occupied(mut entry) => entry.get_mut().push(diagnostic),
+            Entry::Vacant(entry) => {
+                let vec = vec![diagnostic];
+                entry.insert(vec);
+            }
+        }
+    }
+
+    map
+}
+
+/// Keep at most the maximum number of diagnostics with errors before warnings
+/// and add a summary of the suppressed diagnostics at the start of the file
+fn cap_diagnostics(
+    diagnostics: &[lsp_types::Diagnostic],
+    max: Option<usize>,
+) -> Vec<lsp_types::Diagnostic> {
+    let max = match max {
+        Some(max) if diagnostics.len() > max => max,
+        _ => return diagnostics.to_vec(),
+    };
+
+    let mut capped = diagnostics.to_vec();
+    capped.sort_by_key(|diagnostic| diagnostic.severity.unwrap_or(DiagnosticSeverity::HINT));
+    capped.truncate(max);
+    capped.push(lsp_types::Diagnostic {
+        range: lsp_types::Range::default(),
+        severity: Some(DiagnosticSeverity::INFORMATION),
+        source: Some("vhdl ls".to_owned()),
+        message: format!("{} further diagnostics suppressed", diagnostics.len() - max),
+        ..Default::default()
+    });
+    capped
+}
+
+fn flatten_related(diagnostics: Vec<Diagnostic>) -> Vec<Diagnostic> {
+    let mut flat_diagnostics = Vec::new();
+    for mut diagnostic in diagnostics {
+        flat_diagnostics.extend(diagnostic.drain_related());
+        flat_diagnostics.push(diagnostic);
+    }
+    flat_diagnostics
+}
+
+fn file_name_to_uri(file_name: &Path) -> Url {
+    // @TODO return error to client
+    Url::from_file_path(file_name).unwrap()
+}
+
+fn uri_to_file_name(uri: &Url) -> PathBuf {
+    // @TODO return error to client
+    uri.to_file_path().unwrap()
+}
+
+fn to_lsp_diagnostic(diagnostic: Diagnostic) -> lsp_types::Diagnostic {
+    let severity = match diagnostic.severity {
+        Severity::Error => DiagnosticSeverity::ERROR,
+        Severity::Warning => DiagnosticSeverity::WARNING,
+        Severity::Info => DiagnosticSeverity::INFORMATION,
+        Severity::Hint => DiagnosticSeverity::HINT,
+    };
+
+    let related_information = if !diagnostic.related.is_empty() {
+        let mut related_information = Vec::new();
+        for (pos, msg) in diagnostic.related {
+            let uri = file_name_to_uri(pos.source.file_name());
+            related_information.push(DiagnosticRelatedInformation {
+                location: Location {
+                    uri: uri.to_owned(),
+                    range: to_lsp_range(pos.range()),
+                },
+                message: msg,
+            })
+        }
+        Some(related_information)
+    } else {
+        None
+    };
+
+    // The fixes are returned by the client in the context of a code action request
+    let data = if !diagnostic.fixes.is_empty() {
+        let actions: Vec<CodeAction> = diagnostic.fixes.into_iter().map(to_code_action).collect();
+        serde_json::to_value(actions).ok()
+    } else {
+        None
+    };
+
+    lsp_types::Diagnostic {
+        range: to_lsp_range(diagnostic.pos.range()),
+        severity: Some(severity),
+        code: diagnostic
+            .code
+            .map(|code| NumberOrString::String(code.to_owned())),
+        source: Some("vhdl ls".to_owned()),
+        message: diagnostic.message,
+        related_information,
+        data,
+        ..Default::default()
+    }
+}
+
+fn to_lsp_edits(edits: Vec<vhdl_lang::TextEdit>) -> Vec<TextEdit> {
+    edits
+        .into_iter()
+        .map(|edit| TextEdit {
+            range: to_lsp_range(edit.pos.range()),
+            new_text: edit.new_text,
+        })
+        .collect()
+}
+
+/// The library name, the entity name and the optional form of an instantiation
+fn parse_instantiated_entity(
+    library_name: &serde_json::Value,
+    entity_name: &serde_json::Value,
+    form: Option<&serde_json::Value>,
+) -> Option<(String, String, InstantiationForm)> {
+    let form = match form.map(|form| form.as_str()) {
+        None | Some(Some("entity")) => InstantiationForm::Entity,
+        Some(Some("component")) => InstantiationForm::Component,
+        _ => return None,
+    };
+    Some((
+        library_name.as_str()?.to_owned(),
+        entity_name.as_str()?.to_owned(),
+        form,
+    ))
+}
+
+fn to_code_action(fix: Fix) -> CodeAction {
+    let mut changes: HashMap<Url, Vec<TextEdit>> = HashMap::new();
+    for edit in fix.edits {
+        changes
+            .entry(file_name_to_uri(edit.pos.source.file_name()))
+            .or_default()
+            .push(TextEdit {
+                range: to_lsp_range(edit.pos.range()),
+                new_text: edit.new_text,
+            });
+    }
+
+    CodeAction {
+        title: fix.title,
+        kind: Some(CodeActionKind::QUICKFIX),
+        edit: Some(WorkspaceEdit {
+            changes: Some(changes),
+            ..Default::default()
+        }),
+        ..Default::default()
+    }
+}
+
+/// The quick fixes stored in the data of a published diagnostic
+fn diagnostic_fixes(diagnostic: &lsp_types::Diagnostic) -> Vec<CodeAction> {
+    let actions = diagnostic
+        .data
+        .clone()
+        .and_then(|data| serde_json::from_value::<Vec<CodeAction>>(data).ok())
+        .unwrap_or_default();
+
+    actions
+        .into_iter()
+        .map(|action| CodeAction {
+            diagnostics: Some(vec![lsp_types::Diagnostic {
+                data: None,
+                ..diagnostic.clone()
+            }]),
+            ..action
+        })
+        .collect()
+}
+
+fn overloaded_kind(overloaded: &Overloaded) -> SymbolKind {
+    match overloaded {
+        Overloaded::SubprogramDecl(_) => SymbolKind::FUNCTION,
+        Overloaded::Subprogram(_) => SymbolKind::FUNCTION,
+        Overloaded::InterfaceSubprogram(_) => SymbolKind::FUNCTION,
+        Overloaded::EnumLiteral(_) => SymbolKind::ENUM_MEMBER,
+        Overloaded::Alias(o) => overloaded_kind(o.kind()),
+    }
+}
+
+fn object_kind(object: &Object) -> SymbolKind {
+    if matches!(object.subtype.type_mark().kind(), Type::Protected(..)) {
+        SymbolKind::OBJECT
+    } else if object.iface.is_some() {
+        SymbolKind::INTERFACE
+    } else {
+        object_class_kind(object.class)
+    }
+}
+fn object_class_kind(class: ObjectClass) -> SymbolKind {
+    match class {
+        ObjectClass::Signal => SymbolKind::EVENT,
+        ObjectClass::Constant => SymbolKind::CONSTANT,
+        ObjectClass::Variable => SymbolKind::VARIABLE,
+        ObjectClass::SharedVariable => SymbolKind::VARIABLE,
+    }
+}
+
+fn type_kind(t: &Type) -> SymbolKind {
+    match t {
+        vhdl_lang::Type::Array { .. } => SymbolKind::ARRAY,
+        vhdl_lang::Type::Enum(_) => SymbolKind::ENUM,
+        vhdl_lang::Type::Integer => SymbolKind::NUMBER,
+        vhdl_lang::Type::Real => SymbolKind::NUMBER,
+        vhdl_lang::Type::Physical => SymbolKind::NUMBER,
+        vhdl_lang::Type::Access(_) => SymbolKind::ENUM,
+        vhdl_lang::Type::Record(_) => SymbolKind::STRUCT,
+        vhdl_lang::Type::Incomplete => SymbolKind::NULL,
+        vhdl_lang::Type::Subtype(t) => type_kind(t.type_mark().kind()),
+        vhdl_lang::Type::Protected(_, _) => SymbolKind::CLASS,
+        vhdl_lang::Type::File => SymbolKind::FILE,
+        vhdl_lang::Type::Interface => SymbolKind::TYPE_PARAMETER,
+        vhdl_lang::Type::Alias(t) => type_kind(t.kind()),
+        vhdl_lang::Type::Universal(_) => SymbolKind::NUMBER,
+    }
+}
+
+fn to_document_symbol(EntHierarchy { ent, children }: EntHierarchy) -> Option<DocumentSymbol> {
+    let decl_pos = ent.decl_pos()?;
+    #[allow(deprecated)]
+    Some(DocumentSymbol {
+        name: ent.describe(),
+        kind: to_symbol_kind(ent.kind()),
+        tags: None,
+        detail: None,
+        selection_range: to_lsp_range(decl_pos.range),
+        range: to_lsp_range(decl_pos.range),
+        children: if !children.is_empty() {
+            Some(
+                children
+                    .into_iter()
+                    .filter_map(to_document_symbol)
+                    .collect(),
+            )
+        } else {
+            None
+        },
+        deprecated: None,
+    })
+}
+
+/// Completions of the named declarations of a design unit and its nested regions
+fn add_declaration_completions(
+    EntHierarchy { ent, children }: &EntHierarchy,
+    completions: &mut Vec<lsp_types::CompletionItem>,
+) {
+    if let Designator::Identifier(_) = ent.designator() {
+        completions.push(lsp_types::CompletionItem {
+            label: ent.designator().to_string(),
+            kind: Some(to_completion_kind(ent.kind())),
+            detail: Some(ent.describe()),
+            ..Default::default()
+        });
+    }
+    for child in children.iter() {
+        add_declaration_completions(child, completions);
+    }
+}
+
+fn to_completion_kind(kind: &AnyEntKind) -> CompletionItemKind {
+    match kind {
+        AnyEntKind::Library | AnyEntKind::Design(_) => CompletionItemKind::MODULE,
+        AnyEntKind::Type(_) => CompletionItemKind::CLASS,
+        AnyEntKind::Overloaded(Overloaded::EnumLiteral(_)) => CompletionItemKind::ENUM_MEMBER,
+        AnyEntKind::Overloaded(_) => CompletionItemKind::FUNCTION,
+        AnyEntKind::Object(object) if object.class == ObjectClass::Constant => {
+            CompletionItemKind::CONSTANT
+        }
+        AnyEntKind::Object(_) => CompletionItemKind::VARIABLE,
+        _ => CompletionItemKind::TEXT,
+    }
+}
+
+/// The type of semantic token of a name of an entity, operators and character literals
+/// are not classified since editors already highlight them
+fn semantic_token_type(ent: &AnyEnt) -> Option<SemanticTokenType> {
+    if !matches!(ent.designator(), Designator::Identifier(_)) {
+        return None;
+    }
+    fn object_class_type(class: ObjectClass) -> SemanticTokenType {
+        match class {
+            ObjectClass::Signal => SemanticTokenType::new("signal"),
+            ObjectClass::Constant => SemanticTokenType::new("constant"),
+            ObjectClass::Variable | ObjectClass::SharedVariable => SemanticTokenType::VARIABLE,
+        }
+    }
+    fn object_type(object: &Object) -> SemanticTokenType {
+        match object.iface {
+            Some(ObjectInterface::Generic) => SemanticTokenType::new("generic"),
+            Some(ObjectInterface::Port(_)) => SemanticTokenType::new("port"),
+            Some(ObjectInterface::Parameter(_)) => SemanticTokenType::PARAMETER,
+            None => object_class_type(object.class),
+        }
+    }
+    fn overloaded_type(overloaded: &Overloaded) -> SemanticTokenType {
+        match overloaded {
+            Overloaded::EnumLiteral(_) => SemanticTokenType::ENUM_MEMBER,
+            Overloaded::Alias(aliased) => overloaded_type(aliased.kind()),
+            _ => SemanticTokenType::FUNCTION,
+        }
+    }
+
+    let token_type = match ent.kind() {
+        AnyEntKind::ExternalAlias { class, .. } => object_class_type(ObjectClass::from(*class)),
+        AnyEntKind::ObjectAlias { base_object, .. } => object_type(base_object.object()),
+        AnyEntKind::Object(object) => object_type(object),
+        AnyEntKind::LoopParameter(_)
+        | AnyEntKind::PhysicalLiteral(_)
+        | AnyEntKind::DeferredConstant(_) => SemanticTokenType::new("constant"),
+        AnyEntKind::File(_) => SemanticTokenType::VARIABLE,
+        AnyEntKind::InterfaceFile(_) => SemanticTokenType::PARAMETER,
+        AnyEntKind::Component(_) => SemanticTokenType::new("component"),
+        AnyEntKind::Attribute(_) | AnyEntKind::ElementDeclaration(_) => SemanticTokenType::PROPERTY,
+        AnyEntKind::Overloaded(overloaded) => overloaded_type(overloaded),
+        AnyEntKind::Type(_) => SemanticTokenType::TYPE,
+        AnyEntKind::Concurrent(_) | AnyEntKind::Sequential(_) => SemanticTokenType::new("label"),
+        AnyEntKind::Library => SemanticTokenType::NAMESPACE,
+        AnyEntKind::Design(design) => match design {
+            vhdl_lang::Design::Entity(..)
+            | vhdl_lang::Design::Architecture(_)
+            | vhdl_lang::Design::Configuration => SemanticTokenType::new("entity"),
+            _ => SemanticTokenType::NAMESPACE,
+        },
+    };
+    Some(token_type)
+}
+
+fn call_hierarchy_item(ent: &AnyEnt) -> Option<CallHierarchyItem> {
+    let decl_pos = ent.decl_pos()?;
+    Some(CallHierarchyItem {
+        name: ent.designator().to_string(),
+        kind: to_symbol_kind(ent.kind()),
+        tags: None,
+        detail: ent.signature().map(|signature| signature.describe()),
+        uri: file_name_to_uri(decl_pos.source.file_name()),
+        range: to_lsp_range(decl_pos.range()),
+        selection_range: to_lsp_range(decl_pos.range()),
+        data: None,
+    })
+}
+
+fn to_symbol_kind(kind: &AnyEntKind) -> SymbolKind {
+    match kind {
+        AnyEntKind::ExternalAlias { class, .. } => object_class_kind(ObjectClass::from(*class)),
+        AnyEntKind::ObjectAlias { base_object, .. } => object_kind(base_object.object()),
+        AnyEntKind::Object(o) => object_kind(o),
+        AnyEntKind::LoopParameter(_) => SymbolKind::CONSTANT,
+        AnyEntKind::PhysicalLiteral(_) => SymbolKind::CONSTANT,
+        AnyEntKind::DeferredConstant(_) => SymbolKind::CONSTANT,
+        AnyEntKind::File { .. } => SymbolKind::FILE,
+        AnyEntKind::InterfaceFile { .. } => SymbolKind::INTERFACE,
+        AnyEntKind::Component(_) => SymbolKind::CLASS,
+        AnyEntKind::Attribute(_) => SymbolKind::PROPERTY,
+        AnyEntKind::Overloaded(o) => overloaded_kind(o),
+        AnyEntKind::Type(t) => type_kind(t),
+        AnyEntKind::ElementDeclaration(_) => SymbolKind::FIELD,
+        AnyEntKind::Sequential(_) => SymbolKind::NAMESPACE,
+        AnyEntKind::Concurrent(Some(Concurrent::Instance)) => SymbolKind::MODULE,
+        AnyEntKind::Concurrent(_) => SymbolKind::NAMESPACE,
+        AnyEntKind::Library => SymbolKind::NAMESPACE,
+        AnyEntKind::Design(d) => match d {
+            vhdl_lang::Design::Entity(_, _) => SymbolKind::MODULE,
+            vhdl_lang::Design::Architecture(_) => SymbolKind::MODULE,
+            vhdl_lang::Design::Configuration => SymbolKind::MODULE,
+            vhdl_lang::Design::Package(_, _) => SymbolKind::PACKAGE,
+            vhdl_lang::Design::PackageBody => SymbolKind::PACKAGE,
+            vhdl_lang::Design::UninstPackage(_, _) => SymbolKind::PACKAGE,
+            vhdl_lang::Design::PackageInstance(_) => SymbolKind::PACKAGE,
+            vhdl_lang::Design::Context(_) => SymbolKind::NAMESPACE,
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+
+    use super::*;
+    use crate::rpc_channel::test_support::*;
+
+    fn initialize_server(server: &mut VHDLServer, root_uri: Url) {
+        let capabilities = ClientCapabilities::default();
+
+        #[allow(deprecated)]
+        let initialize_params = InitializeParams {
+            process_id: None,
+            root_path: None,
+            root_uri: Some(root_uri),
+            initialization_options: None,
+            capabilities,
+            trace: None,
+            workspace_folders: None,
+            client_info: None,
+            locale: None,
+        };
+
+        server.initialize_request(initialize_params);
+        server.initialized_notification();
+    }
+
+    fn temp_root_uri() -> (tempfile::TempDir, Url) {
+        let tempdir = tempfile::tempdir().unwrap();
+        let root_uri = Url::from_file_path(tempdir.path().canonicalize().unwrap()).unwrap();
+        (tempdir, root_uri)
+    }
+
+    fn expect_loaded_config_messages(mock: &RpcMock, config_uri: &Url) {
+        let file_name = config_uri
+            .to_file_path()
+            .unwrap()
+            .to_str()
+            .unwrap()
+            .to_owned();
+        mock.expect_message_contains(format!(
+            "Loaded workspace root configuration file: {file_name}"
+        ));
+    }
+
+    fn expect_missing_config_messages(mock: &RpcMock) {
+        mock.expect_error_contains("Library mapping is unknown due to missing vhdl_ls.toml config file in the workspace root path");
+        mock.expect_warning_contains(
+            "Without library mapping semantic analysis might be incorrect",
+        );
+    }
+
+    /// Create RpcMock and VHDLServer
+    fn setup_server() -> (Rc<RpcMock>, VHDLServer) {
+        let mock = Rc::new(RpcMock::new());
+        let server = VHDLServer::new_external_config(SharedRpcChannel::new(mock.clone()), false);
+        (mock, server)
+    }
+
+    #[test]
+    fn initialize() {
+        let (mock, mut server) = setup_server();
+        let (_tempdir, root_uri) = temp_root_uri();
+        expect_missing_config_messages(&mock);
+        initialize_server(&mut server, root_uri);
+    }
+
+    #[test]
+    fn did_open_no_diagnostics() {
+        let (mock, mut server) = setup_server();
+        let (_tempdir, root_uri) = temp_root_uri();
+        expect_missing_config_messages(&mock);
+        initialize_server(&mut server, root_uri.clone());
+
+        let file_url = root_uri.join("ent.vhd").unwrap();
+        let code = "
+entity ent is
+end entity ent;
+"
+        .to_owned();
+
+        let did_open = DidOpenTextDocumentParams {
+            text_document: TextDocumentItem {
+                uri: file_url,
+                language_id: "vhdl".to_owned(),
+                version: 0,
+                text: code,
+            },
+        };
+
+        mock.expect_warning_contains("is not part of the project");
+
+        server.text_document_did_open_notification(&did_open);
+    }
+
+    #[test]
+    fn did_open_with_diagnostics_and_change_without() {
+        let (mock, mut server) = setup_server();
+
+        let (_tempdir, root_uri) = temp_root_uri();
+        expect_missing_config_messages(&mock);
+        initialize_server(&mut server, root_uri.clone());
+
+        let file_url = root_uri.join("ent.vhd").unwrap();
+        let code = "
+entity ent is
+end entity ent2;
+"
+        .to_owned();
+
+        let did_open = DidOpenTextDocumentParams {
+            text_document: TextDocumentItem {
+                uri: file_url.clone(),
+                language_id: "vhdl".to_owned(),
+                version: 0,
+                text: code,
+            },
+        };
+
+        let publish_diagnostics = PublishDiagnosticsParams {
+            uri: file_url.clone(),
+            diagnostics: vec![end_identifier_mismatch(&file_url)],
+            version: None,
+        };
+
+        mock.expect_warning_contains("is not part of the project");
+
+        mock.expect_notification("textDocument/publishDiagnostics", publish_diagnostics);
+        server.text_document_did_open_notification(&did_open);
+
+        let code = "
+entity ent is
+end entity ent;
+"
+        .to_owned();
+
+        let did_change = DidChangeTextDocumentParams {
+            text_document: VersionedTextDocumentIdentifier {
+                uri: file_url.clone(),
+                version: 1,
+            },
+            content_changes: vec![TextDocumentContentChangeEvent {
+                range: None,
+                range_length: None,
+                text: code,
+            }],
+        };
+
+        let publish_diagnostics = PublishDiagnosticsParams {
+            uri: file_url,
+            diagnostics: vec![],
+            version: None,
+        };
+
+        mock.expect_notification("textDocument/publishDiagnostics", publish_diagnostics);
+        server.text_document_did_change_notification(&did_change);
+    }
+
+    fn end_identifier_mismatch_fix(file_url: &Url) -> CodeAction {
+        let mut changes = HashMap::new();
+        changes.insert(
+            file_url.clone(),
+            vec![TextEdit {
+                range: Range {
+                    start: lsp_types::Position {
+                        line: 2,
+                        character: "end entity ".len() as u32,
+                    },
+                    end: lsp_types::Position {
+                        line: 2,
+                        character: "end entity ent2".len() as u32,
+                    },
+                },
+                new_text: "ent".to_owned(),
+            }],
+        );
+
+        CodeAction {
+            title: "Replace with 'ent'".to_owned(),
+            kind: Some(CodeActionKind::QUICKFIX),
+            edit: Some(WorkspaceEdit {
+                changes: Some(changes),
+                ..Default::default()
+            }),
+            ..Default::default()
+        }
+    }
+
+    fn end_identifier_mismatch(file_url: &Url) -> lsp_types::Diagnostic {
+        lsp_types::Diagnostic {
+            range: Range {
+                start: lsp_types::Position {
+                    line: 2,
+                    character: "end entity ".len() as u32,
+                },
+                end: lsp_types::Position {
+                    line: 2,
+                    character: "end entity ent2".len() as u32,
+                },
+            },
+            code: None,
+            severity: Some(DiagnosticSeverity::ERROR),
+            source: Some("vhdl ls".to_owned()),
+            message: "End identifier mismatch, expected ent".to_owned(),
+            data: Some(serde_json::to_value(vec![end_identifier_mismatch_fix(file_url)]).unwrap()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn quick_fix_from_diagnostic() {
+        let (mock, mut server) = setup_server();
+        let (_tempdir, root_uri) = temp_root_uri();
+        expect_missing_config_messages(&mock);
+        initialize_server(&mut server, root_uri.clone());
+
+        let file_url = root_uri.join("ent.vhd").unwrap();
+        let diagnostic = end_identifier_mismatch(&file_url);
+        mock.expect_warning_contains("is not part of the project");
+        mock.expect_notification(
+            "textDocument/publishDiagnostics",
+            PublishDiagnosticsParams {
+                uri: file_url.clone(),
+                diagnostics: vec![diagnostic.clone()],
+                version: None,
+            },
+        );
+        open_file(
+            &mut server,
+            &file_url,
+            "
+entity ent is
+end entity ent2;
+",
+        );
+
+        let actions = server.text_document_code_action(&CodeActionParams {
+            text_document: TextDocumentIdentifier {
+                uri: file_url.clone(),
+            },
+            range: diagnostic.range,
+            context: CodeActionContext {
+                diagnostics: vec![diagnostic.clone()],
+                only: None,
+                trigger_kind: None,
+            },
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        });
+
+        assert_eq!(
+            actions,
+            Some(vec![CodeActionOrCommand::CodeAction(CodeAction {
+                diagnostics: Some(vec![lsp_types::Diagnostic {
+                    data: None,
+                    ..diagnostic
+                }]),
+                ..end_identifier_mismatch_fix(&file_url)
+            })])
+        );
+    }
+
+    #[test]
+    fn document_symbols_kept_during_syntax_errors() {
+        let (mock, mut server) = setup_server();
+        let (_tempdir, root_uri) = temp_root_uri();
+        expect_missing_config_messages(&mock);
+        initialize_server(&mut server, root_uri.clone());
+
+        let file_url = root_uri.join("pkg.vhd").unwrap();
+        let did_open = DidOpenTextDocumentParams {
+            text_document: TextDocumentItem {
+                uri: file_url.clone(),
+                language_id: "vhdl".to_owned(),
+                version: 0,
+                text: "
+package pkg is
+end package;
+
+package pkg2 is
+end package;
+"
+                .to_owned(),
+            },
+        };
+        mock.expect_warning_contains("is not part of the project");
+        server.text_document_did_open_notification(&did_open);
+
+        let params = DocumentSymbolParams {
+            text_document: TextDocumentIdentifier {
+                uri: file_url.clone(),
+            },
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        };
+        let symbol_names = |response: Option<DocumentSymbolResponse>| match response {
+            Some(DocumentSymbolResponse::Flat(symbols)) => symbols
+                .into_iter()
+                .map(|symbol| symbol.name)
+                .collect::<Vec<_>>(),
+            _ => panic!("Expected flat document symbols"),
+        };
+        let last_good = server.document_symbol(&params);
+        assert_eq!(
+            symbol_names(last_good.clone()),
+            vec!["package 'pkg'", "package 'pkg2'"]
+        );
+
+        // Remove the end of the second package and move the first one
+        let did_change = DidChangeTextDocumentParams {
+            text_document: VersionedTextDocumentIdentifier {
+                uri: file_url.clone(),
+                version: 1,
+            },
+            content_changes: vec![TextDocumentContentChangeEvent {
+                range: None,
+                range_length: None,
+                text: "
+
+package pkg is
+end package;
+
+package pkg2 is
+"
+                .to_owned(),
+            }],
+        };
+        mock.expect_notification_contains("textDocument/publishDiagnostics", "Unexpected EOF");
+        server.text_document_did_change_notification(&did_change);
+
+        // The symbols of the last good version are not mixed with those of the current one
+        assert_eq!(server.document_symbol(&params), last_good);
+    }
+
+    #[test]
+    fn completions_kept_during_syntax_errors_until_closed() {
+        let (mock, mut server) = setup_server();
+        let (_tempdir, root_uri) = temp_root_uri();
+        expect_missing_config_messages(&mock);
+        initialize_server(&mut server, root_uri.clone());
+
+        let file_url = root_uri.join("pkg.vhd").unwrap();
+        mock.expect_warning_contains("is not part of the project");
+        open_file(
+            &mut server,
+            &file_url,
+            "\
+package pkg is
+  component my_comp is
+  end component;
+end package;
+",
+        );
+
+        let did_change = DidChangeTextDocumentParams {
+            text_document: VersionedTextDocumentIdentifier {
+                uri: file_url.clone(),
+                version: 1,
+            },
+            content_changes: vec![TextDocumentContentChangeEvent {
+                range: None,
+                range_length: None,
+                text: "\
+package pkg is
+  component my_comp is
+  end component;
+  constant
+"
+                .to_owned(),
+            }],
+        };
+        mock.expect_notification_contains("textDocument/publishDiagnostics", "Unexpected EOF");
+        server.text_document_did_change_notification(&did_change);
+
+        let params = CompletionParams {
+            text_document_position: TextDocumentPositionParams {
+                text_document: TextDocumentIdentifier {
+                    uri: file_url.clone(),
+                },
+                position: Position::new(2, 0),
+            },
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+            context: None,
+        };
+        let labels = |server: &VHDLServer| {
+            server
+                .request_completion(&params)
+                .items
+                .into_iter()
+                .map(|item| item.label)
+                .collect::<Vec<_>>()
+        };
+        assert!(labels(&server).contains(&"my_comp".to_owned()));
+
+        server.text_document_did_close_notification(&DidCloseTextDocumentParams {
+            text_document: TextDocumentIdentifier { uri: file_url },
+        });
+        assert!(!labels(&server).contains(&"my_comp".to_owned()));
+    }
+
+    fn open_file(server: &mut VHDLServer, uri: &Url, text: &str) {
+        let did_open = DidOpenTextDocumentParams {
+            text_document: TextDocumentItem {
+                uri: uri.clone(),
+                language_id: "vhdl".to_owned(),
+                version: 0,
+                text: text.to_owned(),
+            },
+        };
+        server.text_document_did_open_notification(&did_open);
+    }
+
+    #[test]
+    fn formats_whole_document() {
+        let (mock, mut server) = setup_server();
+        let (_tempdir, root_uri) = temp_root_uri();
+        expect_missing_config_messages(&mock);
+        initialize_server(&mut server, root_uri.clone());
+
+        let file_url = root_uri.join("ent.vhd").unwrap();
+        mock.expect_warning_contains("is not part of the project");
+        open_file(
+            &mut server,
+            &file_url,
+            "package pkg is\n-- comment\nend package;",
+        );
+
+        let params = DocumentFormattingParams {
+            text_document: TextDocumentIdentifier {
+                uri: file_url.clone(),
+            },
+            options: Default::default(),
+            work_done_progress_params: Default::default(),
+        };
+        let edits = server.document_formatting(&params).unwrap();
+        assert_eq!(
+            edits,
+            vec![TextEdit {
+                range: lsp_types::Range::new(
+                    lsp_types::Position::new(0, 0),
+                    lsp_types::Position::new(2, 12)
+                ),
+                new_text: "package pkg is\n  -- comment\nend package;\n".to_owned(),
+            }]
+        );
+    }
+
+    #[test]
+    fn formats_range_and_on_type() {
+        let (mock, mut server) = setup_server();
+        let (_tempdir, root_uri) = temp_root_uri();
+        expect_missing_config_messages(&mock);
+        initialize_server(&mut server, root_uri.clone());
+
+        let file_url = root_uri.join("ent.vhd").unwrap();
+        mock.expect_warning_contains("is not part of the project");
+        open_file(
+            &mut server,
+            &file_url,
+            "package pkg is\n-- comment\n-- other\nend;",
+        );
+
+        let params = DocumentRangeFormattingParams {
+            text_document: TextDocumentIdentifier {
+                uri: file_url.clone(),
+            },
+            range: lsp_types::Range::new(
+                lsp_types::Position::new(1, 0),
+                lsp_types::Position::new(2, 0),
+            ),
+            options: Default::default(),
+            work_done_progress_params: Default::default(),
+        };
+        assert_eq!(
+            server.document_range_formatting(&params).unwrap(),
+            vec![TextEdit {
+                range: lsp_types::Range::new(
+                    lsp_types::Position::new(1, 0),
+                    lsp_types::Position::new(1, 10)
+                ),
+                new_text: "  -- comment".to_owned(),
+            }]
+        );
+
+        let params = DocumentOnTypeFormattingParams {
+            text_document_position: TextDocumentPositionParams {
+                text_document: TextDocumentIdentifier {
+                    uri: file_url.clone(),
+                },
+                position: lsp_types::Position::new(3, 4),
+            },
+            ch: ";".to_owned(),
+            options: Default::default(),
+        };
+        assert_eq!(
+            server.document_on_type_formatting(&params).unwrap(),
+            vec![
+                TextEdit {
+                    range: lsp_types::Range::new(
+                        lsp_types::Position::new(1, 0),
+                        lsp_types::Position::new(1, 0)
+                    ),
+                    new_text: "  ".to_owned(),
+                },
+                TextEdit {
+                    range: lsp_types::Range::new(
+                        lsp_types::Position::new(2, 0),
+                        lsp_types::Position::new(2, 0)
+                    ),
+                    new_text: "  ".to_owned(),
+                },
+                TextEdit {
+                    range: lsp_types::Range::new(
+                        lsp_types::Position::new(3, 3),
+                        lsp_types::Position::new(3, 3)
+                    ),
+                    new_text: " pkg".to_owned(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn goto_definition_prefers_body_or_declaration() {
+        let (mock, mut server) = setup_server();
+        let (_tempdir, root_uri) = temp_root_uri();
+        expect_missing_config_messages(&mock);
+        initialize_server(&mut server, root_uri.clone());
+
+        let file_url = root_uri.join("pkg.vhd").unwrap();
+        mock.expect_warning_contains("is not part of the project");
+        open_file(
+            &mut server,
+            &file_url,
+            "\
+package pkg is
+  type prot is protected
+  end protected;
+end package;
+
+package body pkg is
+  type prot is protected body
+  end protected body;
+end package body;",
+        );
+
+        let params = TextDocumentPositionParams {
+            text_document: TextDocumentIdentifier {
+                uri: file_url.clone(),
+            },
+            position: lsp_types::Position::new(1, 8),
+        };
+        let location = |line| Location {
+            uri: file_url.clone(),
+            range: lsp_types::Range::new(
+                lsp_types::Position::new(line, 7),
+                lsp_types::Position::new(line, 11),
+            ),
+        };
+
+        assert_eq!(server.text_document_definition(&params), Some(location(6)));
+        assert_eq!(server.text_document_declaration(&params), Some(location(1)));
+
+        server.settings.prefer_declaration = true;
+        assert_eq!(server.text_document_definition(&params), Some(location(1)));
+    }
+
+    #[test]
+    fn inlay_hints_show_formals_of_positional_actuals() {
+        let (mock, mut server) = setup_server();
+        let (_tempdir, root_uri) = temp_root_uri();
+        expect_missing_config_messages(&mock);
+        initialize_server(&mut server, root_uri.clone());
+
+        let file_url = root_uri.join("pkg.vhd").unwrap();
+        mock.expect_warning_contains("is not part of the project");
+        open_file(
+            &mut server,
+            &file_url,
+            "\
+package pkg is
+  type prot is protected
+  end protected;
+  procedure proc(variable a : inout prot; variable b : inout prot);
+end package;
+
+package body pkg is
+  type prot is protected body
+  end protected body;
+  procedure proc(variable a : inout prot; variable b : inout prot) is
+  begin
+    proc(a, b => b);
+  end procedure;
+end package body;",
+        );
+
+        let params = InlayHintParams {
+            work_done_progress_params: Default::default(),
+            text_document: TextDocumentIdentifier {
+                uri: file_url.clone(),
+            },
+            range: lsp_types::Range::new(
+                lsp_types::Position::new(0, 0),
+                lsp_types::Position::new(13, 0),
+            ),
+        };
+        let hints: Vec<_> = server
+            .inlay_hints(&params)
+            .unwrap()
+            .into_iter()
+            .map(|hint| match hint.label {
+                InlayHintLabel::String(label) => (hint.position, label, hint.kind),
+                InlayHintLabel::LabelParts(_) => panic!("Expected a string label"),
+            })
+            .collect();
+        assert_eq!(
+            hints,
+            vec![(
+                lsp_types::Position::new(11, 9),
+                "a =>".to_owned(),
+                Some(InlayHintKind::PARAMETER)
+            )]
+        );
+    }
+
+    #[test]
+    fn semantic_tokens_classify_resolved_names() {
+        let (mock, mut server) = setup_server();
+        let (_tempdir, root_uri) = temp_root_uri();
+        expect_missing_config_messages(&mock);
+        initialize_server(&mut server, root_uri.clone());
+
+        let file_url = root_uri.join("ent.vhd").unwrap();
+        mock.expect_warning_contains("is not part of the project");
+        open_file(
+            &mut server,
+            &file_url,
+            "\
+entity ent is
+end entity;
+architecture a of ent is
+  component comp is
+  end component;
+begin
+  inst : comp;
+end architecture;",
+        );
+
+        let token = |delta_line, delta_start, length, token_type, declaration| SemanticToken {
+            delta_line,
+            delta_start,
+            length,
+            token_type,
+            token_modifiers_bitset: u32::from(declaration),
+        };
+        let text_document = TextDocumentIdentifier {
+            uri: file_url.clone(),
+        };
+
+        let params = SemanticTokensParams {
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+            text_document: text_document.clone(),
+        };
+        assert_eq!(
+            server.semantic_tokens_full(&params),
+            Some(SemanticTokensResult::Tokens(SemanticTokens {
+                result_id: None,
+                data: vec![
+                    token(0, 7, 3, 1, true),
+                    token(2, 13, 1, 1, true),
+                    token(0, 5, 3, 1, false),
+                    token(1, 12, 4, 2, true),
+                    token(3, 2, 4, 13, true),
+                    token(0, 7, 4, 2, false),
+                ]
+            }))
+        );
+
+        let params = SemanticTokensRangeParams {
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+            text_document,
+            range: lsp_types::Range::new(
+                lsp_types::Position::new(6, 0),
+                lsp_types::Position::new(7, 0),
+            ),
+        };
+        assert_eq!(
+            server.semantic_tokens_range(&params),
+            Some(SemanticTokensRangeResult::Tokens(SemanticTokens {
+                result_id: None,
+                data: vec![token(6, 2, 4, 13, true), token(0, 7, 4, 2, false),]
+            }))
+        );
+    }
+
+    #[test]
+    fn deferred_diagnostics_are_published_once() {
+        let (mock, mut server) = setup_server();
+        let (_tempdir, root_uri) = temp_root_uri();
+        expect_missing_config_messages(&mock);
+        initialize_server(&mut server, root_uri.clone());
+
+        let file_url = root_uri.join("ent.vhd").unwrap();
+        server.defer_diagnostics();
+        mock.expect_warning_contains("is not part of the project");
+        open_file(&mut server, &file_url, "entity ent is end entity;");
+
+        let did_change = DidChangeTextDocumentParams {
+            text_document: VersionedTextDocumentIdentifier {
+                uri: file_url.clone(),
+                version: 1,
+            },
+            content_changes: vec![TextDocumentContentChangeEvent {
+                range: None,
+                range_length: None,
+                text: "entity ent is".to_owned(),
+            }],
+        };
+        server.text_document_did_change_notification(&did_change);
+
+        mock.expect_notification_contains("textDocument/publishDiagnostics", "Unexpected EOF");
+        server.flush_diagnostics();
+        // Nothing has changed since the last flush
+        server.flush_diagnostics();
+    }
+
+    #[test]
+    fn cancelled_analysis_is_published_by_next_flush() {
+        let (mock, mut server) = setup_server();
+        let (_tempdir, root_uri) = temp_root_uri();
+        expect_missing_config_messages(&mock);
+        initialize_server(&mut server, root_uri.clone());
+
+        let file_url = root_uri.join("ent.vhd").unwrap();
+        server.defer_diagnostics();
+        mock.expect_warning_contains("is not part of the project");
+        open_file(&mut server, &file_url, "entity ent is");
+
+        // The thread receiving messages cancels when the documents change again
+        server.analysis_cancellation().cancel();
+        server.flush_diagnostics_cancellable();
 
-    use super::*;
-    use crate::rpc_channel::test_support::*;
+        server.defer_diagnostics();
+        mock.expect_notification_contains("textDocument/publishDiagnostics", "Unexpected EOF");
+        server.flush_diagnostics_cancellable();
+    }
 
-    fn initialize_server(server: &mut VHDLServer, root_uri: Url) {
-        let capabilities = ClientCapabilities::default();
+    #[test]
+    fn will_rename_files_moves_file_in_project() {
+        let (mock, mut server) = setup_server();
+        let (_tempdir, root_uri) = temp_root_uri();
+        expect_missing_config_messages(&mock);
+        initialize_server(&mut server, root_uri.clone());
 
-        #[allow(deprecated)]
-        let initialize_params = InitializeParams {
-            process_id: None,
-            root_path: None,
-            root_uri: Some(root_uri),
-            initialization_options: None,
-            capabilities,
-            trace: None,
-            workspace_folders: None,
-            client_info: None,
-            locale: None,
-        };
+        let old_url = root_uri.join("ent.vhd").unwrap();
+        let new_url = root_uri.join("rtl/ent.vhd").unwrap();
+        mock.expect_warning_contains("is not part of the project");
+        open_file(&mut server, &old_url, "entity ent is end entity;");
 
-        server.initialize_request(initialize_params);
-        server.initialized_notification();
+        let params = RenameFilesParams {
+            files: vec![FileRename {
+                old_uri: old_url.to_string(),
+                new_uri: new_url.to_string(),
+            }],
+        };
+        // Without a configuration file there is nothing to edit
+        assert_eq!(server.workspace_will_rename_files(&params), None);
+        assert!(server
+            .project
+            .get_source(&uri_to_file_name(&old_url))
+            .is_none());
+        assert!(server
+            .project
+            .get_source(&uri_to_file_name(&new_url))
+            .is_some());
     }
 
-    fn temp_root_uri() -> (tempfile::TempDir, Url) {
-        let tempdir = tempfile::tempdir().unwrap();
-        let root_uri = Url::from_file_path(tempdir.path().canonicalize().unwrap()).unwrap();
-        (tempdir, root_uri)
-    }
+    #[test]
+    fn project_diagnostics_are_filtered_and_paged() {
+        let (mock, mut server) = setup_server();
+        let (_tempdir, root_uri) = temp_root_uri();
+        expect_missing_config_messages(&mock);
+        initialize_server(&mut server, root_uri.clone());
 
-    fn expect_loaded_config_messages(mock: &RpcMock, config_uri: &Url) {
-        let file_name = config_uri
-            .to_file_path()
-            .unwrap()
-            .to_str()
+        let file_url = root_uri.join("ent.vhd").unwrap();
+        mock.expect_warning_contains("is not part of the project");
+        mock.expect_notification_contains("textDocument/publishDiagnostics", "Unexpected EOF");
+        open_file(&mut server, &file_url, "entity ent is");
+
+        let result = server.project_diagnostics(&serde_json::json!({}));
+        assert_eq!(result["total"], 1);
+        assert_eq!(result["diagnostics"][0]["uri"], file_url.to_string());
+        assert!(result["diagnostics"][0]["diagnostic"]["message"]
+            .as_str()
             .unwrap()
-            .to_owned();
-        mock.expect_message_contains(format!(
-            "Loaded workspace root configuration file: {file_name}"
-        ));
+            .contains("Unexpected EOF"));
+
+        // Errors are at least as severe as warnings
+        let result = server.project_diagnostics(&serde_json::json!({ "severity": 2 }));
+        assert_eq!(result["total"], 1);
+
+        // The file is not part of any library
+        let result = server.project_diagnostics(&serde_json::json!({ "library": "lib" }));
+        assert_eq!(result["total"], 0);
+
+        let result = server.project_diagnostics(&serde_json::json!({ "start": 1, "limit": 10 }));
+        assert_eq!(result["total"], 1);
+        assert_eq!(result["diagnostics"], serde_json::json!([]));
     }
 
-    fn expect_missing_config_messages(mock: &RpcMock) {
-        mock.expect_error_contains("Library mapping is unknown due to missing vhdl_ls.toml config file in the workspace root path");
+    #[test]
+    fn references_are_incomplete_after_timeout() {
+        let (mock, mut server) = setup_server();
+        let (_tempdir, root_uri) = temp_root_uri();
+        expect_missing_config_messages(&mock);
+        initialize_server(&mut server, root_uri.clone());
+        server.settings.request_timeout = Some(Duration::ZERO);
+
+        let file_url = root_uri.join("ent.vhd").unwrap();
+        mock.expect_warning_contains("is not part of the project");
+        open_file(
+            &mut server,
+            &file_url,
+            "
+entity ent is
+end entity;
+
+architecture rtl of ent is
+begin
+end architecture;
+",
+        );
+
+        let text_document_position = TextDocumentPositionParams {
+            text_document: TextDocumentIdentifier {
+                uri: file_url.clone(),
+            },
+            position: lsp_types::Position {
+                line: 1,
+                character: 7,
+            },
+        };
+
         mock.expect_warning_contains(
-            "Without library mapping semantic analysis might be incorrect",
+            "Searching for references of entity 'ent' timed out, the results are incomplete",
         );
-    }
+        let references = server.text_document_references(&ReferenceParams {
+            text_document_position: text_document_position.clone(),
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+            context: ReferenceContext {
+                include_declaration: true,
+            },
+        });
+        assert_eq!(references, vec![]);
 
-    /// Create RpcMock and VHDLServer
-    fn setup_server() -> (Rc<RpcMock>, VHDLServer) {
-        let mock = Rc::new(RpcMock::new());
-        let server = VHDLServer::new_external_config(SharedRpcChannel::new(mock.clone()), false);
-        (mock, server)
+        mock.expect_error_contains(
+            "Searching for references of entity 'ent' timed out, nothing was renamed",
+        );
+        let edit = server.rename(&RenameParams {
+            text_document_position,
+            new_name: "ent2".to_owned(),
+            work_done_progress_params: Default::default(),
+        });
+        assert_eq!(edit, None);
     }
 
     #[test]
-    fn initialize() {
+    fn rename_entity_renames_components_bound_by_default() {
         let (mock, mut server) = setup_server();
         let (_tempdir, root_uri) = temp_root_uri();
         expect_missing_config_messages(&mock);
-        initialize_server(&mut server, root_uri);
+        initialize_server(&mut server, root_uri.clone());
+
+        let ent_url = root_uri.join("ent.vhd").unwrap();
+        mock.expect_warning_contains("is not part of the project");
+        open_file(&mut server, &ent_url, "entity ent is\nend entity ent;\n");
+
+        let top_url = root_uri.join("top.vhd").unwrap();
+        mock.expect_warning_contains("is not part of the project");
+        open_file(
+            &mut server,
+            &top_url,
+            "\
+entity top is
+end entity;
+
+architecture rtl of top is
+  component ent is
+  end component ent;
+begin
+  inst: ent;
+end architecture;
+",
+        );
+
+        let edit = server
+            .rename(&RenameParams {
+                text_document_position: TextDocumentPositionParams {
+                    text_document: TextDocumentIdentifier {
+                        uri: ent_url.clone(),
+                    },
+                    position: lsp_types::Position {
+                        line: 0,
+                        character: 7,
+                    },
+                },
+                new_name: "ent2".to_owned(),
+                work_done_progress_params: Default::default(),
+            })
+            .unwrap();
+
+        let lines = |uri: &Url| {
+            let mut lines: Vec<_> = edit.changes.as_ref().unwrap()[uri]
+                .iter()
+                .map(|edit| (edit.range.start.line, edit.new_text.as_str()))
+                .collect();
+            lines.sort();
+            lines
+        };
+        assert_eq!(lines(&ent_url), vec![(0, "ent2"), (1, "ent2")]);
+        assert_eq!(lines(&top_url), vec![(4, "ent2"), (5, "ent2"), (7, "ent2")]);
     }
 
     #[test]
-    fn did_open_no_diagnostics() {
+    fn execute_commands_of_the_registry() {
         let (mock, mut server) = setup_server();
         let (_tempdir, root_uri) = temp_root_uri();
         expect_missing_config_messages(&mock);
         initialize_server(&mut server, root_uri.clone());
 
-        let file_url = root_uri.join("ent.vhd").unwrap();
-        let code = "
-entity ent is
-end entity ent;
-"
-        .to_owned();
+        let sub_url = root_uri.join("z_sub.vhd").unwrap();
+        mock.expect_warning_contains("is not part of the project");
+        open_file(&mut server, &sub_url, "entity sub is\nend entity;\n");
 
-        let did_open = DidOpenTextDocumentParams {
-            text_document: TextDocumentItem {
-                uri: file_url,
-                language_id: "vhdl".to_owned(),
-                version: 0,
-                text: code,
-            },
+        let top_url = root_uri.join("a_top.vhd").unwrap();
+        mock.expect_warning_contains("is not part of the project");
+        mock.expect_notification_contains("textDocument/publishDiagnostics", "is never used");
+        open_file(
+            &mut server,
+            &top_url,
+            "\
+entity top is
+end entity;
+
+architecture rtl of top is
+  procedure unused_proc is
+  begin
+  end procedure;
+begin
+  inst: entity work.sub;
+end architecture;
+",
+        );
+
+        let mut execute = |command: &str, arguments: Vec<serde_json::Value>| {
+            server.workspace_execute_command(&ExecuteCommandParams {
+                command: command.to_owned(),
+                arguments,
+                work_done_progress_params: Default::default(),
+            })
         };
 
-        mock.expect_warning_contains("is not part of the project");
+        let commands = execute(LIST_COMMANDS_COMMAND, vec![]).unwrap();
+        assert_eq!(commands.as_array().unwrap().len(), COMMANDS.len());
+        assert_eq!(commands[1]["arguments"][2]["name"], "form");
+        assert_eq!(commands[1]["arguments"][2]["optional"], true);
+
+        let order = execute(COMPILE_ORDER_COMMAND, vec![]).unwrap();
+        assert_eq!(
+            order,
+            serde_json::json!([
+                { "library": "work", "uri": sub_url },
+                { "library": "work", "uri": top_url },
+            ])
+        );
 
-        server.text_document_did_open_notification(&did_open);
+        let hierarchy = execute(HIERARCHY_COMMAND, vec![serde_json::json!("work.top")]).unwrap();
+        assert_eq!(hierarchy["children"][0]["name"], "inst");
+
+        let lints = execute(
+            RUN_LINT_PROFILE_COMMAND,
+            vec![
+                serde_json::json!("testbench"),
+                serde_json::json!({ "library": "work" }),
+            ],
+        )
+        .unwrap();
+        assert_eq!(lints.as_array().unwrap().len(), 1);
+        assert_eq!(lints[0]["uri"], top_url.to_string());
+        assert_eq!(
+            lints[0]["diagnostic"]["message"],
+            "Procedure 'unused_proc' is never used"
+        );
+
+        let position = serde_json::json!({ "line": 7, "character": 2 });
+        let breadcrumbs = execute(
+            BREADCRUMBS_COMMAND,
+            vec![serde_json::json!(top_url), position],
+        )
+        .unwrap();
+        assert_eq!(breadcrumbs[0]["name"], "entity top");
+
+        let regions = execute(STATEMENT_REGIONS_COMMAND, vec![serde_json::json!(top_url)]).unwrap();
+        assert_eq!(regions[0]["label"], "inst");
+
+        let diagnostics =
+            execute(FILE_DIAGNOSTICS_COMMAND, vec![serde_json::json!(top_url)]).unwrap();
+        assert!(diagnostics
+            .as_array()
+            .unwrap()
+            .iter()
+            .any(|diagnostic| diagnostic["message"] == "Procedure 'unused_proc' is never used"));
     }
 
     #[test]
-    fn did_open_with_diagnostics_and_change_without() {
+    fn insert_instantiation_asks_client_to_apply_edit() {
         let (mock, mut server) = setup_server();
+        let (_tempdir, root_uri) = temp_root_uri();
+        expect_missing_config_messages(&mock);
+        initialize_server(&mut server, root_uri.clone());
+
+        let sub_url = root_uri.join("sub.vhd").unwrap();
+        mock.expect_warning_contains("is not part of the project");
+        // There is no standard library to declare the type of the port
+        mock.expect_notification_contains("textDocument/publishDiagnostics", "No declaration");
+        open_file(
+            &mut server,
+            &sub_url,
+            "entity sub is\n  port (\n    clk : in bit\n  );\nend entity;\n",
+        );
+
+        let snippet = server.workspace_execute_command(&ExecuteCommandParams {
+            command: INSTANTIATION_TEXT_COMMAND.to_owned(),
+            arguments: vec![
+                serde_json::json!("work"),
+                serde_json::json!("sub"),
+                serde_json::json!("entity"),
+                serde_json::json!(true),
+            ],
+            work_done_progress_params: Default::default(),
+        });
+        assert_eq!(
+            snippet,
+            Some(serde_json::json!(
+                "${1:sub_inst} : entity work.sub\n  port map (\n    clk => ${2:clk}\n  );$0"
+            ))
+        );
+
+        let top_url = root_uri.join("top.vhd").unwrap();
+        let position = Position::new(4, 2);
+        let mut changes = HashMap::new();
+        changes.insert(
+            top_url.clone(),
+            vec![TextEdit {
+                range: Range::new(position, position),
+                new_text: "\
+sub_inst : entity work.sub
+    port map (
+      clk => clk
+    );"
+                .to_owned(),
+            }],
+        );
+        mock.expect_request(
+            "workspace/applyEdit",
+            ApplyWorkspaceEditParams {
+                label: Some("Instantiate work.sub".to_owned()),
+                edit: WorkspaceEdit {
+                    changes: Some(changes),
+                    ..Default::default()
+                },
+            },
+        );
+        server.workspace_execute_command(&ExecuteCommandParams {
+            command: INSERT_INSTANTIATION_COMMAND.to_owned(),
+            arguments: vec![
+                serde_json::json!("work"),
+                serde_json::json!("sub"),
+                serde_json::to_value(&top_url).unwrap(),
+                serde_json::to_value(position).unwrap(),
+            ],
+            work_done_progress_params: Default::default(),
+        });
+    }
 
+    #[test]
+    fn call_hierarchy_of_procedure() {
+        let (mock, mut server) = setup_server();
         let (_tempdir, root_uri) = temp_root_uri();
         expect_missing_config_messages(&mock);
         initialize_server(&mut server, root_uri.clone());
 
         let file_url = root_uri.join("ent.vhd").unwrap();
-        let code = "
+        mock.expect_warning_contains("is not part of the project");
+        open_file(
+            &mut server,
+            &file_url,
+            "\
 entity ent is
-end entity ent2;
-"
-        .to_owned();
+end entity;
 
-        let did_open = DidOpenTextDocumentParams {
-            text_document: TextDocumentItem {
-                uri: file_url.clone(),
-                language_id: "vhdl".to_owned(),
-                version: 0,
-                text: code,
-            },
-        };
+architecture a of ent is
+  procedure leaf is
+  begin
+  end procedure;
 
-        let publish_diagnostics = PublishDiagnosticsParams {
-            uri: file_url.clone(),
-            diagnostics: vec![lsp_types::Diagnostic {
-                range: Range {
-                    start: lsp_types::Position {
-                        line: 2,
-                        character: "end entity ".len() as u32,
-                    },
-                    end: lsp_types::Position {
-                        line: 2,
-                        character: "end entity ent2".len() as u32,
+  procedure mid is
+  begin
+    leaf;
+  end procedure;
+begin
+  main: process
+  begin
+    mid;
+    wait;
+  end process;
+end architecture;
+",
+        );
+
+        let items = server
+            .prepare_call_hierarchy(&CallHierarchyPrepareParams {
+                text_document_position_params: TextDocumentPositionParams {
+                    text_document: TextDocumentIdentifier {
+                        uri: file_url.clone(),
                     },
+                    position: Position::new(15, 4),
                 },
-                code: None,
-                severity: Some(DiagnosticSeverity::ERROR),
-                source: Some("vhdl ls".to_owned()),
-                message: "End identifier mismatch, expected ent".to_owned(),
-                ..Default::default()
-            }],
-            version: None,
-        };
+                work_done_progress_params: Default::default(),
+            })
+            .unwrap();
+        assert_eq!(items.len(), 1);
+        let item = items[0].clone();
+        assert_eq!(item.name, "mid");
+        assert_eq!(
+            item.selection_range,
+            Range::new(Position::new(8, 12), Position::new(8, 15))
+        );
 
-        mock.expect_warning_contains("is not part of the project");
+        let incoming = server
+            .call_hierarchy_incoming_calls(&CallHierarchyIncomingCallsParams {
+                item: item.clone(),
+                work_done_progress_params: Default::default(),
+                partial_result_params: Default::default(),
+            })
+            .unwrap();
+        assert_eq!(incoming.len(), 1);
+        assert_eq!(incoming[0].from.name, "main");
+        assert_eq!(
+            incoming[0].from_ranges,
+            vec![Range::new(Position::new(15, 4), Position::new(15, 7))]
+        );
 
-        mock.expect_notification("textDocument/publishDiagnostics", publish_diagnostics);
-        server.text_document_did_open_notification(&did_open);
+        let outgoing = server
+            .call_hierarchy_outgoing_calls(&CallHierarchyOutgoingCallsParams {
+                item,
+                work_done_progress_params: Default::default(),
+                partial_result_params: Default::default(),
+            })
+            .unwrap();
+        assert_eq!(outgoing.len(), 1);
+        assert_eq!(outgoing[0].to.name, "leaf");
+        assert_eq!(
+            outgoing[0].from_ranges,
+            vec![Range::new(Position::new(10, 4), Position::new(10, 8))]
+        );
+    }
 
-        let code = "
-entity ent is
-end entity ent;
-"
-        .to_owned();
+    #[test]
+    fn completion_in_use_clause() {
+        let (mock, mut server) = setup_server();
+        let (_tempdir, root_uri) = temp_root_uri();
+        expect_missing_config_messages(&mock);
+        initialize_server(&mut server, root_uri.clone());
 
-        let did_change = DidChangeTextDocumentParams {
-            text_document: VersionedTextDocumentIdentifier {
-                uri: file_url.clone(),
-                version: 1,
-            },
-            content_changes: vec![TextDocumentContentChangeEvent {
-                range: None,
-                range_length: None,
-                text: code,
-            }],
-        };
+        let pkg_url = root_uri.join("pkg.vhd").unwrap();
+        mock.expect_warning_contains("is not part of the project");
+        open_file(&mut server, &pkg_url, "package pkg is\nend package;");
 
-        let publish_diagnostics = PublishDiagnosticsParams {
-            uri: file_url,
-            diagnostics: vec![],
-            version: None,
-        };
+        let ent_url = root_uri.join("ent.vhd").unwrap();
+        mock.expect_warning_contains("is not part of the project");
+        mock.expect_notification_contains("textDocument/publishDiagnostics", "Unexpected EOF");
+        open_file(&mut server, &ent_url, "use work.pk");
+
+        let completions = server.request_completion(&CompletionParams {
+            text_document_position: TextDocumentPositionParams {
+                text_document: TextDocumentIdentifier { uri: ent_url },
+                position: lsp_types::Position {
+                    line: 0,
+                    character: 11,
+                },
+            },
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+            context: None,
+        });
 
-        mock.expect_notification("textDocument/publishDiagnostics", publish_diagnostics);
-        server.text_document_did_change_notification(&did_change);
+        assert_eq!(
+            completions
+                .items
+                .into_iter()
+                .map(|item| item.label)
+                .collect::<Vec<_>>(),
+            vec!["pkg", "pkg.all"]
+        );
     }
 
     fn write_file(root_uri: &Url, file_name: impl AsRef<str>, contents: impl AsRef<str>) -> Url {
@@ -1328,4 +4023,33 @@ lib.files = [
             }],
         });
     }
+
+    #[test]
+    fn cap_diagnostics_keeps_errors_and_summarizes_the_rest() {
+        let diagnostic = |line: u32, severity: DiagnosticSeverity| lsp_types::Diagnostic {
+            range: lsp_types::Range::new(Position::new(line, 0), Position::new(line, 1)),
+            severity: Some(severity),
+            message: format!("line {line}"),
+            ..Default::default()
+        };
+        let diagnostics = vec![
+            diagnostic(0, DiagnosticSeverity::WARNING),
+            diagnostic(1, DiagnosticSeverity::ERROR),
+            diagnostic(2, DiagnosticSeverity::WARNING),
+            diagnostic(3, DiagnosticSeverity::ERROR),
+        ];
+
+        assert_eq!(cap_diagnostics(&diagnostics, None), diagnostics);
+        assert_eq!(cap_diagnostics(&diagnostics, Some(4)), diagnostics);
+
+        let capped = cap_diagnostics(&diagnostics, Some(2));
+        assert_eq!(
+            capped
+                .iter()
+                .map(|diagnostic| diagnostic.message.as_str())
+                .collect::<Vec<_>>(),
+            vec!["line 1", "line 3", "2 further diagnostics suppressed"]
+        );
+        assert_eq!(capped[2].severity, Some(DiagnosticSeverity::INFORMATION));
+    }
 }
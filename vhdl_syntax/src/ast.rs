@@ -18,15 +18,18 @@ mod any_design_unit;
 #[macro_use]
 pub mod search;
 
+pub mod ancestry;
+pub mod statement_regions;
+pub mod test_cases;
+
 pub use self::display::*;
-pub(crate) use self::util::*;
-pub(crate) use any_design_unit::*;
+pub use self::util::*;
+pub use any_design_unit::*;
 
-use crate::analysis::EntityId;
 use crate::data::*;
 
 /// LRM 15.8 Bit string literals
-#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+#[derive(PartialEq, Eq, Hash, Copy, Clone, Debug)]
 pub enum BaseSpecifier {
     B,
     O,
@@ -246,7 +249,7 @@ pub enum AbstractLiteral {
 }
 
 /// LRM 15.8 Bit string literals
-#[derive(PartialEq, Eq, Debug, Clone)]
+#[derive(PartialEq, Eq, Hash, Debug, Clone)]
 pub struct BitString {
     pub length: Option<u32>,
     pub base: BaseSpecifier,
@@ -421,6 +424,34 @@ pub enum Designator {
     Anonymous(usize),
 }
 
+/// The id of a named entity created during analysis
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub struct EntityId {
+    pub(crate) id: usize,
+}
+
+impl EntityId {
+    // Using 64-bits we can create 5 * 10**9 ids per second for 100 years before wrapping
+    #[allow(clippy::new_without_default)]
+    pub fn undefined() -> Self {
+        EntityId { id: usize::MAX }
+    }
+
+    /// An id from the raw value given by the arena of the analysis
+    pub fn from_raw(id: usize) -> Self {
+        EntityId { id }
+    }
+
+    pub fn raw(&self) -> usize {
+        self.id
+    }
+}
+
+/// This trait is implemented for Ast-nodes which declare named entities
+pub trait HasEntityId {
+    fn ent_id(&self) -> Option<EntityId>;
+}
+
 pub type Reference = Option<EntityId>;
 
 /// An item which has a reference to a declaration
@@ -592,6 +623,8 @@ pub struct TypeDeclaration {
     pub ident: WithDecl<Ident>,
     pub def: TypeDefinition,
     pub end_ident_pos: Option<SrcPos>,
+    /// The source span from `type` or `subtype` to the final semi colon
+    pub pos: SrcPos,
 }
 
 /// LRM 6.4.2 Object Declarations
@@ -610,12 +643,23 @@ pub enum InterfaceType {
     Parameter,
 }
 
+/// LRM 6.4.2.3 Signal declarations
+/// The kind of a guarded signal
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum SignalKind {
+    Register,
+    Bus,
+}
+
 #[derive(PartialEq, Debug, Clone)]
 pub struct ObjectDeclaration {
     pub class: ObjectClass,
     pub ident: WithDecl<Ident>,
     pub subtype_indication: SubtypeIndication,
+    pub signal_kind: Option<SignalKind>,
     pub expression: Option<WithPos<Expression>>,
+    /// The source span from the object class to the final semi colon
+    pub pos: SrcPos,
 }
 
 #[derive(PartialEq, Debug, Clone)]
@@ -637,6 +681,8 @@ pub enum SubprogramDesignator {
 pub struct ProcedureSpecification {
     pub designator: WithDecl<WithPos<SubprogramDesignator>>,
     pub parameter_list: Vec<InterfaceDeclaration>,
+    /// The source span from `procedure` to the end of the parameter list
+    pub pos: SrcPos,
 }
 
 /// LRM 4.2 Subprogram declaration
@@ -646,6 +692,8 @@ pub struct FunctionSpecification {
     pub designator: WithDecl<WithPos<SubprogramDesignator>>,
     pub parameter_list: Vec<InterfaceDeclaration>,
     pub return_type: WithPos<TypeMark>,
+    /// The source span from `function` or its purity to the return type
+    pub pos: SrcPos,
 }
 
 /// LRM 4.3 Subprogram bodies
@@ -684,6 +732,7 @@ pub struct InterfaceObjectDeclaration {
     pub ident: WithDecl<Ident>,
     pub mode: Mode,
     pub subtype_indication: SubtypeIndication,
+    pub signal_kind: Option<SignalKind>,
     pub expression: Option<WithPos<Expression>>,
 }
 
@@ -740,6 +789,8 @@ pub struct ComponentDeclaration {
     pub generic_list: Vec<InterfaceDeclaration>,
     pub port_list: Vec<InterfaceDeclaration>,
     pub end_ident_pos: Option<SrcPos>,
+    /// The source span from `component` to the final semi colon
+    pub pos: SrcPos,
 }
 
 #[derive(PartialEq, Debug, Clone)]
@@ -1050,6 +1101,8 @@ pub struct ForGenerateStatement {
 #[derive(PartialEq, Debug, Clone)]
 pub struct IfGenerateStatement {
     pub conds: Conditionals<GenerateBody>,
+    /// The position of the else keyword, the else branch extends from it to the end
+    pub else_pos: Option<SrcPos>,
     pub end_label_pos: Option<SrcPos>,
 }
 #[derive(PartialEq, Debug, Clone)]
@@ -1123,9 +1176,9 @@ pub struct PackageInstantiation {
 }
 
 /// LRM 7.3 Configuration specification
-#[derive(PartialEq, Eq, Debug, Clone)]
+#[derive(PartialEq, Debug, Clone)]
 pub enum InstantiationList {
-    Labels(Vec<Ident>),
+    Labels(Vec<WithRef<Ident>>),
     Others,
     All,
 }
@@ -1133,7 +1186,7 @@ pub enum InstantiationList {
 /// LRM 7.3.2 Binding indication
 #[derive(PartialEq, Debug, Clone)]
 pub enum EntityAspect {
-    Entity(WithPos<SelectedName>, Option<Ident>),
+    Entity(WithPos<SelectedName>, Option<WithRef<Ident>>),
     Configuration(WithPos<SelectedName>),
     Open,
 }
@@ -1293,4 +1346,9 @@ pub enum AnyDesignUnit {
 #[derive(PartialEq, Debug, Clone, Default)]
 pub struct DesignFile {
     pub design_units: Vec<AnyDesignUnit>,
+    /// The source span of each design unit including its context clause
+    pub unit_spans: Vec<SrcPos>,
+    /// A fingerprint of the tokens of each design unit including its context clause
+    /// Units with the same fingerprint only differ in whitespace and comments
+    pub unit_fingerprints: Vec<u64>,
 }
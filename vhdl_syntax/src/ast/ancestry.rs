@@ -0,0 +1,249 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this file,
+// You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) 2023, Olof Kraigher olof.kraigher@gmail.com
+
+//! Find the chain of constructs enclosing a position, such as
+//! `entity foo > architecture rtl > process p_ctrl > if > case`
+
+use super::*;
+
+/// A construct that encloses a position
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct Breadcrumb {
+    /// The kind of construct followed by its name or label if it has one
+    pub name: String,
+    pub pos: SrcPos,
+}
+
+/// Find the constructs enclosing the cursor, outermost first
+pub fn find_ancestry(design_file: &DesignFile, cursor: Position) -> Vec<Breadcrumb> {
+    let mut ancestry = Vec::new();
+
+    for (unit, span) in design_file
+        .design_units
+        .iter()
+        .zip(design_file.unit_spans.iter())
+    {
+        if !contains(span, cursor) {
+            continue;
+        }
+
+        let mut push = |kind: &str, ident: &Ident| {
+            ancestry.push(Breadcrumb {
+                name: format!("{kind} {}", ident.item),
+                pos: span.clone(),
+            })
+        };
+
+        match unit {
+            AnyDesignUnit::Primary(primary) => match primary {
+                AnyPrimaryUnit::Entity(unit) => {
+                    push("entity", unit.ident());
+                    concurrent_ancestry(&unit.statements, cursor, &mut ancestry);
+                }
+                AnyPrimaryUnit::Configuration(unit) => push("configuration", unit.ident()),
+                AnyPrimaryUnit::Package(unit) => push("package", unit.ident()),
+                AnyPrimaryUnit::PackageInstance(unit) => push("package", unit.ident()),
+                AnyPrimaryUnit::Context(unit) => push("context", unit.ident()),
+            },
+            AnyDesignUnit::Secondary(secondary) => match secondary {
+                AnySecondaryUnit::Architecture(unit) => {
+                    push("entity", unit.primary_ident());
+                    push("architecture", unit.ident());
+                    concurrent_ancestry(&unit.statements, cursor, &mut ancestry);
+                }
+                AnySecondaryUnit::PackageBody(unit) => push("package body", unit.ident()),
+            },
+        }
+        break;
+    }
+
+    ancestry
+}
+
+fn contains(pos: &SrcPos, cursor: Position) -> bool {
+    pos.start() <= cursor && cursor <= pos.end()
+}
+
+/// The position of a statement including its label
+pub(super) fn statement_pos<T>(label: &WithDecl<Option<Ident>>, statement: &WithPos<T>) -> SrcPos {
+    if let Some(label) = &label.tree {
+        label.pos.combine(&statement.pos)
+    } else {
+        statement.pos.clone()
+    }
+}
+
+fn breadcrumb(kind: &str, label: &WithDecl<Option<Ident>>, pos: SrcPos) -> Breadcrumb {
+    let name = if let Some(label) = &label.tree {
+        format!("{kind} {}", label.item)
+    } else {
+        kind.to_owned()
+    };
+    Breadcrumb { name, pos }
+}
+
+fn concurrent_ancestry(
+    statements: &[LabeledConcurrentStatement],
+    cursor: Position,
+    ancestry: &mut Vec<Breadcrumb>,
+) {
+    for statement in statements {
+        let pos = statement_pos(&statement.label, &statement.statement);
+        if !contains(&pos, cursor) {
+            continue;
+        }
+
+        if matches!(
+            statement.statement.item,
+            ConcurrentStatement::ProcedureCall(..)
+                | ConcurrentStatement::Assert(..)
+                | ConcurrentStatement::Assignment(..)
+        ) {
+            return;
+        }
+        ancestry.push(breadcrumb(
+            statement.statement.item.describe(),
+            &statement.label,
+            pos,
+        ));
+
+        match statement.statement.item {
+            ConcurrentStatement::Process(ref process) => {
+                sequential_ancestry(&process.statements, cursor, ancestry);
+            }
+            ConcurrentStatement::Block(ref block) => {
+                concurrent_ancestry(&block.statements, cursor, ancestry);
+            }
+            ConcurrentStatement::ForGenerate(ref gen) => {
+                concurrent_ancestry(&gen.body.statements, cursor, ancestry);
+            }
+            ConcurrentStatement::IfGenerate(ref gen) => {
+                for body in gen
+                    .conds
+                    .conditionals
+                    .iter()
+                    .map(|cond| &cond.item)
+                    .chain(gen.conds.else_item.iter())
+                {
+                    concurrent_ancestry(&body.statements, cursor, ancestry);
+                }
+            }
+            ConcurrentStatement::CaseGenerate(ref gen) => {
+                for alternative in gen.sels.alternatives.iter() {
+                    concurrent_ancestry(&alternative.item.statements, cursor, ancestry);
+                }
+            }
+            _ => {}
+        }
+        return;
+    }
+}
+
+fn sequential_ancestry(
+    statements: &[LabeledSequentialStatement],
+    cursor: Position,
+    ancestry: &mut Vec<Breadcrumb>,
+) {
+    for statement in statements {
+        let pos = statement_pos(&statement.label, &statement.statement);
+        if !contains(&pos, cursor) {
+            continue;
+        }
+
+        match statement.statement.item {
+            SequentialStatement::If(ref ifstmt) => {
+                ancestry.push(breadcrumb("if", &statement.label, pos));
+                for stmts in ifstmt
+                    .conds
+                    .conditionals
+                    .iter()
+                    .map(|cond| &cond.item)
+                    .chain(ifstmt.conds.else_item.iter())
+                {
+                    sequential_ancestry(stmts, cursor, ancestry);
+                }
+            }
+            SequentialStatement::Case(ref case_stmt) => {
+                ancestry.push(breadcrumb("case", &statement.label, pos));
+                for alternative in case_stmt.alternatives.iter() {
+                    sequential_ancestry(&alternative.item, cursor, ancestry);
+                }
+            }
+            SequentialStatement::Loop(ref loop_stmt) => {
+                let kind = match loop_stmt.iteration_scheme {
+                    Some(IterationScheme::For(..)) => "for loop",
+                    Some(IterationScheme::While(..)) => "while loop",
+                    None => "loop",
+                };
+                ancestry.push(breadcrumb(kind, &statement.label, pos));
+                sequential_ancestry(&loop_stmt.statements, cursor, ancestry);
+            }
+            _ => {}
+        }
+        return;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::syntax::test::Code;
+
+    fn ancestry_at(code: &Code, substr: &str) -> Vec<String> {
+        find_ancestry(&code.design_file(), code.s1(substr).start())
+            .into_iter()
+            .map(|breadcrumb| breadcrumb.name)
+            .collect()
+    }
+
+    #[test]
+    fn finds_enclosing_constructs() {
+        let code = Code::new(
+            "
+package pkg is
+  constant c : natural := 0;
+end package;
+
+architecture rtl of foo is
+begin
+  p_ctrl: process
+  begin
+    if true then
+      case sel is
+        when others =>
+          null;
+      end case;
+    end if;
+  end process;
+
+  gen: for i in 0 to 1 generate
+    assert false;
+  end generate;
+end architecture;
+",
+        );
+
+        assert_eq!(ancestry_at(&code, "constant"), vec!["package pkg"]);
+        assert_eq!(
+            ancestry_at(&code, "null"),
+            vec![
+                "entity foo",
+                "architecture rtl",
+                "process p_ctrl",
+                "if",
+                "case"
+            ]
+        );
+        assert_eq!(
+            ancestry_at(&code, "assert"),
+            vec!["entity foo", "architecture rtl", "for generate gen"]
+        );
+        assert_eq!(
+            ancestry_at(&code, "begin"),
+            vec!["entity foo", "architecture rtl"]
+        );
+    }
+}
@@ -161,6 +161,12 @@ macro_rules! delegate_any {
     };
 }
 
+impl HasEntityId for AnyPrimaryUnit {
+    fn ent_id(&self) -> Option<EntityId> {
+        delegate_primary!(self, unit, unit.ident.decl)
+    }
+}
+
 impl AnyKind {
     pub fn describe(&self) -> &str {
         match self {
@@ -739,6 +739,15 @@ impl Display for ObjectClass {
     }
 }
 
+impl Display for SignalKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        match self {
+            SignalKind::Register => write!(f, "register"),
+            SignalKind::Bus => write!(f, "bus"),
+        }
+    }
+}
+
 impl Display for ObjectDeclaration {
     fn fmt(&self, f: &mut Formatter<'_>) -> Result {
         write!(
@@ -746,6 +755,9 @@ impl Display for ObjectDeclaration {
             "{} {} : {}",
             self.class, self.ident, self.subtype_indication,
         )?;
+        if let Some(signal_kind) = self.signal_kind {
+            write!(f, " {signal_kind}")?;
+        }
         match self.expression {
             Some(ref expr) => write!(f, " := {expr};"),
             None => write!(f, ";"),
@@ -890,6 +902,9 @@ impl Display for InterfaceObjectDeclaration {
                 )?;
             }
         }
+        if let Some(signal_kind) = self.signal_kind {
+            write!(f, " {signal_kind}")?;
+        }
         match self.expression {
             Some(ref expr) => write!(f, " := {expr}"),
             None => Ok(()),
@@ -1725,6 +1740,12 @@ end units;",
         assert_format("signal foo : natural;", Code::object_decl);
     }
 
+    #[test]
+    fn test_object_declaration_guarded_signal() {
+        assert_format("signal foo : natural register;", Code::object_decl);
+        assert_format("signal foo : natural bus := 0;", Code::object_decl);
+    }
+
     #[test]
     fn test_object_declaration_variable() {
         assert_format("variable foo : natural;", Code::object_decl);
@@ -1861,6 +1882,11 @@ end units;",
         assert_format("foo : in natural := bar(0)", Code::port);
     }
 
+    #[test]
+    fn test_interface_declaration_object_bus() {
+        assert_format("signal foo : in std_logic bus", Code::parameter);
+    }
+
     #[test]
     fn test_interface_declaration_file() {
         assert_format("file foo : text", Code::parameter);
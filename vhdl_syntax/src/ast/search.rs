@@ -9,10 +9,6 @@
 #![allow(clippy::unneeded_field_pattern)]
 
 use super::*;
-use crate::analysis::DesignRoot;
-use crate::analysis::EntRef;
-pub use crate::analysis::HasEntityId;
-use crate::analysis::Related;
 
 #[must_use]
 pub enum SearchResult {
@@ -98,6 +94,52 @@ pub trait Searcher {
     fn search_with_pos(&mut self, _pos: &SrcPos) -> SearchState {
         NotFinished
     }
+
+    /// Search a function call or an indexed name before its prefix and parameters
+    fn search_call_or_indexed(&mut self, _call: &CallOrIndexed) -> SearchState {
+        NotFinished
+    }
+
+    /// Search an instance before its unit name and its generic and port maps
+    fn search_instance(&mut self, _instance: &InstantiationStatement) -> SearchState {
+        NotFinished
+    }
+
+    /// Search the target of an assignment before its name
+    fn search_target(&mut self, _target: &WithPos<Target>) -> SearchState {
+        NotFinished
+    }
+
+    /// Search an association of a port map, generic map or call before its formal and actual
+    fn search_association(&mut self, _assoc: &AssociationElement) -> SearchState {
+        NotFinished
+    }
+
+    /// Search an attribute name before its prefix and argument
+    fn search_attribute(&mut self, _attr: &AttributeName) -> SearchState {
+        NotFinished
+    }
+
+    /// Search a sequential statement before its parts
+    fn search_sequential_statement(
+        &mut self,
+        _statement: &LabeledSequentialStatement,
+    ) -> SearchState {
+        NotFinished
+    }
+
+    /// Search a concurrent statement before its parts
+    fn search_concurrent_statement(
+        &mut self,
+        _statement: &LabeledConcurrentStatement,
+    ) -> SearchState {
+        NotFinished
+    }
+
+    /// Search an expression before its operands
+    fn search_expression(&mut self, _pos: &SrcPos, _expr: &Expression) -> SearchState {
+        NotFinished
+    }
     fn search_source(&mut self, _source: &Source) -> SearchState {
         NotFinished
     }
@@ -252,6 +294,7 @@ impl Search for WithPos<Choice> {
 
 impl Search for WithPos<Target> {
     fn search(&mut self, searcher: &mut impl Searcher) -> SearchResult {
+        return_if_finished!(searcher.search_target(self));
         match self.item {
             Target::Name(ref mut name) => search_pos_name(&mut self.pos, name, searcher),
             Target::Aggregate(ref mut assocs) => assocs.search(searcher),
@@ -269,6 +312,7 @@ impl Search for LabeledSequentialStatement {
                 ))
                 .or_not_found());
         }
+        return_if_finished!(searcher.search_sequential_statement(self));
         match self.statement.item {
             SequentialStatement::Return(ref mut ret) => {
                 let ReturnStatement { ref mut expression } = ret;
@@ -423,6 +467,7 @@ impl Search for GenerateBody {
 
 impl Search for InstantiationStatement {
     fn search(&mut self, searcher: &mut impl Searcher) -> SearchResult {
+        return_if_finished!(searcher.search_instance(self));
         match self.unit {
             InstantiatedUnit::Entity(ref mut ent_name, ref mut architecture_name) => {
                 return_if_found!(ent_name.search(searcher));
@@ -468,9 +513,20 @@ impl Search for LabeledConcurrentStatement {
                 ))
                 .or_not_found());
         }
+        return_if_finished!(searcher.search_concurrent_statement(self));
         match self.statement.item {
             ConcurrentStatement::Block(ref mut block) => {
                 // @TODO guard condition
+                let BlockHeader {
+                    generic_clause,
+                    generic_map,
+                    port_clause,
+                    port_map,
+                } = &mut block.header;
+                return_if_found!(generic_clause.search(searcher));
+                return_if_found!(generic_map.search(searcher));
+                return_if_found!(port_clause.search(searcher));
+                return_if_found!(port_map.search(searcher));
                 return_if_found!(block.decl.search(searcher));
                 return_if_found!(block.statements.search(searcher));
             }
@@ -599,6 +655,7 @@ fn search_pos_name(
         }
         Name::CallOrIndexed(ref mut fcall) => fcall.search(searcher),
         Name::Attribute(ref mut attr) => {
+            return_if_finished!(searcher.search_attribute(attr));
             // @TODO more
             let AttributeName { name, expr, .. } = attr.as_mut();
             return_if_found!(name.search(searcher));
@@ -694,6 +751,7 @@ impl Search for RangeConstraint {
 
 impl Search for AttributeName {
     fn search(&mut self, searcher: &mut impl Searcher) -> SearchResult {
+        return_if_finished!(searcher.search_attribute(self));
         // @TODO more
         let AttributeName { name, .. } = self;
         name.search(searcher)
@@ -822,6 +880,7 @@ fn search_pos_expr(
     searcher: &mut impl Searcher,
 ) -> SearchResult {
     return_if_finished!(searcher.search_with_pos(pos));
+    return_if_finished!(searcher.search_expression(pos, expr));
     match expr {
         Expression::Binary(ref mut op, ref mut left, ref mut right) => {
             return_if_found!(searcher
@@ -881,23 +940,27 @@ impl Search for QualifiedExpression {
 
 impl Search for AssociationElement {
     fn search(&mut self, searcher: &mut impl Searcher) -> SearchResult {
+        return_if_finished!(searcher.search_association(self));
         let AssociationElement { formal, actual } = self;
         if let Some(formal) = formal {
             return_if_found!(search_pos_name(&mut formal.pos, &mut formal.item, searcher));
         }
+        actual.search(searcher)
+    }
+}
 
-        match actual.item {
-            ActualPart::Expression(ref mut expr) => {
-                return_if_found!(search_pos_expr(&mut actual.pos, expr, searcher));
-            }
-            ActualPart::Open => {}
+impl Search for WithPos<ActualPart> {
+    fn search(&mut self, searcher: &mut impl Searcher) -> SearchResult {
+        match self.item {
+            ActualPart::Expression(ref mut expr) => search_pos_expr(&mut self.pos, expr, searcher),
+            ActualPart::Open => NotFound,
         }
-        NotFound
     }
 }
 
 impl Search for CallOrIndexed {
     fn search(&mut self, searcher: &mut impl Searcher) -> SearchResult {
+        return_if_finished!(searcher.search_call_or_indexed(self));
         let CallOrIndexed { name, parameters } = self;
         return_if_found!(name.search(searcher));
         return_if_found!(parameters.search(searcher));
@@ -1034,6 +1097,7 @@ impl Search for Declaration {
                     generic_list,
                     port_list,
                     end_ident_pos: _,
+                    pos: _,
                 } = component;
                 return_if_found!(generic_list.search(searcher));
                 return_if_found!(port_list.search(searcher));
@@ -1278,7 +1342,57 @@ impl Search for ConfigurationDeclaration {
         return_if_found!(searcher
             .search_decl(FoundDeclaration::Configuration(self))
             .or_not_found());
-        self.entity_name.search(searcher)
+        return_if_found!(self.entity_name.search(searcher));
+        self.block_config.search(searcher)
+    }
+}
+
+impl Search for BlockConfiguration {
+    fn search(&mut self, searcher: &mut impl Searcher) -> SearchResult {
+        return_if_found!(self.block_spec.search(searcher));
+        self.items.search(searcher)
+    }
+}
+
+impl Search for ConfigurationItem {
+    fn search(&mut self, searcher: &mut impl Searcher) -> SearchResult {
+        match self {
+            ConfigurationItem::Block(block_config) => block_config.search(searcher),
+            ConfigurationItem::Component(component_config) => component_config.search(searcher),
+        }
+    }
+}
+
+impl Search for ComponentConfiguration {
+    fn search(&mut self, searcher: &mut impl Searcher) -> SearchResult {
+        if let InstantiationList::Labels(ref mut labels) = self.spec.instantiation_list {
+            for label in labels.iter_mut() {
+                return_if_found!(searcher
+                    .search_pos_with_ref(&label.item.pos, &mut label.reference)
+                    .or_not_found());
+            }
+        }
+        return_if_found!(self.spec.component_name.search(searcher));
+        if let Some(ref mut bind_ind) = self.bind_ind {
+            match bind_ind.entity_aspect {
+                Some(EntityAspect::Entity(ref mut entity_name, ref mut architecture_name)) => {
+                    return_if_found!(entity_name.search(searcher));
+                    if let Some(ref mut architecture_name) = architecture_name {
+                        return_if_found!(searcher
+                            .search_pos_with_ref(
+                                &architecture_name.item.pos,
+                                &mut architecture_name.reference
+                            )
+                            .or_not_found());
+                    }
+                }
+                Some(EntityAspect::Configuration(ref mut config_name)) => {
+                    return_if_found!(config_name.search(searcher));
+                }
+                Some(EntityAspect::Open) | None => {}
+            }
+        }
+        self.block_config.search(searcher)
     }
 }
 
@@ -1307,10 +1421,21 @@ impl Search for CaseStatement {
 }
 
 // Search for reference to declaration/definition at cursor
+/// The kind of syntax node of the item at a cursor
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ItemKind {
+    /// The designator of a declaration
+    Declaration,
+    /// A name referring to a declaration, including a designator repeated after the end
+    /// of a declaration
+    Reference,
+}
+
 pub struct ItemAtCursor {
     source: Source,
     cursor: Position,
-    pub result: Option<(SrcPos, EntityId)>,
+    /// The reference is none for a name that was not resolved
+    pub result: Option<(SrcPos, ItemKind, Reference)>,
 }
 
 impl ItemAtCursor {
@@ -1328,14 +1453,27 @@ impl ItemAtCursor {
         pos.start() <= self.cursor && self.cursor <= pos.end()
     }
 
-    fn search_decl_pos(&mut self, pos: &SrcPos, decl: &FoundDeclaration) -> SearchState {
+    /// An item without a reference does not finish the search such that a resolved item
+    /// at the same position is preferred
+    fn found(&mut self, pos: &SrcPos, kind: ItemKind, reference: Reference) -> SearchState {
+        if reference.is_some() || self.result.is_none() {
+            self.result = Some((pos.clone(), kind, reference));
+        }
+        if reference.is_some() {
+            Finished(Found)
+        } else {
+            Finished(NotFound)
+        }
+    }
+
+    fn search_decl_pos(
+        &mut self,
+        pos: &SrcPos,
+        kind: ItemKind,
+        decl: &FoundDeclaration,
+    ) -> SearchState {
         if self.is_inside(pos) {
-            if let Some(id) = decl.ent_id() {
-                self.result = Some((pos.clone(), id));
-                Finished(Found)
-            } else {
-                Finished(NotFound)
-            }
+            self.found(pos, kind, decl.ent_id())
         } else {
             NotFinished
         }
@@ -1355,12 +1493,12 @@ impl Searcher for ItemAtCursor {
 
     fn search_decl(&mut self, decl: FoundDeclaration) -> SearchState {
         let pos = decl.pos();
-        if let Finished(res) = self.search_decl_pos(pos, &decl) {
+        if let Finished(res) = self.search_decl_pos(pos, ItemKind::Declaration, &decl) {
             return Finished(res);
         }
 
         if let Some(end_pos) = decl.end_ident_pos() {
-            self.search_decl_pos(end_pos, &decl)
+            self.search_decl_pos(end_pos, ItemKind::Reference, &decl)
         } else {
             NotFinished
         }
@@ -1368,12 +1506,7 @@ impl Searcher for ItemAtCursor {
 
     fn search_pos_with_ref(&mut self, pos: &SrcPos, reference: &mut Reference) -> SearchState {
         if self.is_inside(pos) {
-            if let Some(id) = reference {
-                self.result = Some((pos.clone(), *id));
-                Finished(Found)
-            } else {
-                Finished(NotFound)
-            }
+            self.found(pos, ItemKind::Reference, *reference)
         } else {
             NotFinished
         }
@@ -1389,196 +1522,8 @@ impl Searcher for ItemAtCursor {
     }
 }
 
-// Search for reference to declaration/definition at cursor
-pub struct FindEnt<'a, T: Fn(EntRef<'a>) -> bool> {
-    root: &'a DesignRoot,
-    cond: T,
-    pub result: Option<EntRef<'a>>,
-}
-
-impl<'a, T: Fn(EntRef<'a>) -> bool> FindEnt<'a, T> {
-    pub fn new(root: &'a DesignRoot, cond: T) -> FindEnt<'a, T> {
-        FindEnt {
-            root,
-            cond,
-            result: None,
-        }
-    }
-}
-
-impl<'a, T: Fn(EntRef<'a>) -> bool> Searcher for FindEnt<'a, T> {
-    fn search_decl(&mut self, decl: FoundDeclaration) -> SearchState {
-        if let Some(id) = decl.ent_id() {
-            let ent = self.root.get_ent(id);
-            if (self.cond)(ent) {
-                self.result = Some(ent);
-                return SearchState::Finished(SearchResult::Found);
-            }
-        }
-
-        SearchState::NotFinished
-    }
-}
-
-pub struct FindAllEnt<'a, T: FnMut(EntRef<'a>) -> bool> {
-    root: &'a DesignRoot,
-    cond: T,
-    pub result: Vec<EntRef<'a>>,
-}
-
-impl<'a, T: FnMut(EntRef<'a>) -> bool> FindAllEnt<'a, T> {
-    pub fn new(root: &'a DesignRoot, cond: T) -> FindAllEnt<'a, T> {
-        FindAllEnt {
-            root,
-            cond,
-            result: Vec::default(),
-        }
-    }
-}
-
-impl<'a, T: FnMut(EntRef<'a>) -> bool> Searcher for FindAllEnt<'a, T> {
-    fn search_decl(&mut self, decl: FoundDeclaration) -> SearchState {
-        if let Some(id) = decl.ent_id() {
-            let ent = self.root.get_ent(id);
-            if (self.cond)(ent) {
-                self.result.push(ent);
-            }
-        }
-
-        SearchState::NotFinished
-    }
-}
-
-// Search for a declaration/definition and format it
-pub struct FormatDeclaration<'a> {
-    ent: EntRef<'a>,
-    pub result: Option<String>,
-}
-
-impl<'a> FormatDeclaration<'a> {
-    pub fn new(ent: EntRef<'a>) -> FormatDeclaration<'a> {
-        FormatDeclaration { ent, result: None }
-    }
-}
-
-impl<'a> Searcher for FormatDeclaration<'a> {
-    fn search_decl(&mut self, decl: FoundDeclaration) -> SearchState {
-        let id = if let Some(id) = decl.ent_id() {
-            id
-        } else {
-            return NotFinished;
-        };
-
-        if is_implicit_of(self.ent, id) {
-            // Implicit
-            self.result = Some(format!(
-                "-- {}\n\n-- Implicitly defined by:\n{}\n",
-                self.ent.describe(),
-                decl,
-            ));
-            return Finished(Found);
-        } else if self.ent.id() == id {
-            // Explicit
-            self.result = Some(decl.to_string());
-            return Finished(Found);
-        }
-        NotFinished
-    }
-}
-
-// Search for all references to declaration/definition
-pub struct FindAllReferences<'a> {
-    root: &'a DesignRoot,
-    ent: EntRef<'a>,
-    pub references: Vec<SrcPos>,
-}
-
-fn is_instance_of(ent: EntRef, other: EntRef) -> bool {
-    if let Related::InstanceOf(ent) = ent.related {
-        if ent.id() == other.id() {
-            return true;
-        }
-
-        if is_instance_of(ent, other) {
-            return true;
-        }
-    }
-
-    false
-}
-
-fn is_declared_by(ent: EntRef, other: EntRef) -> bool {
-    if let Related::DeclaredBy(ent) = ent.related {
-        if ent.id() == other.id() {
-            return true;
-        }
-    }
-
-    false
-}
-
-fn is_implicit_of(ent: EntRef, id: EntityId) -> bool {
-    match ent.related {
-        Related::ImplicitOf(ent) => ent.id() == id,
-        Related::InstanceOf(ent) => is_implicit_of(ent, id),
-        Related::None => false,
-        Related::DeclaredBy(_) => false,
-    }
-}
-
-fn is_reference(ent: EntRef, other: EntRef) -> bool {
-    if ent.id() == other.id() {
-        return true;
-    }
-
-    if is_instance_of(ent, other) || is_instance_of(other, ent) {
-        return true;
-    }
-
-    if is_declared_by(ent, other) || is_declared_by(other, ent) {
-        return true;
-    }
-
-    false
-}
-impl<'a> FindAllReferences<'a> {
-    pub fn new(root: &'a DesignRoot, ent: EntRef<'a>) -> FindAllReferences<'a> {
-        FindAllReferences {
-            root,
-            ent,
-            references: Vec::new(),
-        }
-    }
-}
-
-impl<'a> Searcher for FindAllReferences<'a> {
-    fn search_decl(&mut self, decl: FoundDeclaration) -> SearchState {
-        if let Some(id) = decl.ent_id() {
-            let other = self.root.get_ent(id);
-
-            if is_reference(self.ent, other) {
-                self.references.push(decl.pos().clone());
-                if let Some(pos) = decl.end_ident_pos() {
-                    self.references.push(pos.clone());
-                }
-            }
-        }
-        NotFinished
-    }
-
-    fn search_pos_with_ref(&mut self, pos: &SrcPos, reference: &mut Reference) -> SearchState {
-        if let Some(id) = reference.as_ref() {
-            let other = self.root.get_ent(*id);
-            if is_reference(self.ent, other) {
-                self.references.push(pos.clone());
-            }
-        };
-        NotFinished
-    }
-}
-
 impl<'a> FoundDeclaration<'a> {
-    fn end_ident_pos(&self) -> Option<&SrcPos> {
+    pub fn end_ident_pos(&self) -> Option<&SrcPos> {
         match self {
             FoundDeclaration::InterfaceObject(_) => None,
             FoundDeclaration::ForIndex(..) => None,
@@ -1647,6 +1592,42 @@ impl<'a> HasEntityId for FoundDeclaration<'a> {
     }
 }
 
+impl<'a> FoundDeclaration<'a> {
+    /// The declared entity id slot, used to clear ids from a previous analysis
+    fn decl_mut(self) -> &'a mut Option<EntityId> {
+        match self {
+            FoundDeclaration::InterfaceObject(value) => &mut value.ident.decl,
+            FoundDeclaration::ForIndex(ident, _) => &mut ident.decl,
+            FoundDeclaration::ForGenerateIndex(_, value) => &mut value.index_name.decl,
+            FoundDeclaration::Function(value) => &mut value.designator.decl,
+            FoundDeclaration::Procedure(value) => &mut value.designator.decl,
+            FoundDeclaration::Object(value) => &mut value.ident.decl,
+            FoundDeclaration::ElementDeclaration(elem) => &mut elem.ident.decl,
+            FoundDeclaration::EnumerationLiteral(_, elem) => &mut elem.decl,
+            FoundDeclaration::File(value) => &mut value.ident.decl,
+            FoundDeclaration::Type(value) => &mut value.ident.decl,
+            FoundDeclaration::InterfaceType(value) => &mut value.decl,
+            FoundDeclaration::InterfacePackage(value) => &mut value.ident.decl,
+            FoundDeclaration::InterfaceFile(value) => &mut value.ident.decl,
+            FoundDeclaration::PhysicalTypePrimary(value) => &mut value.decl,
+            FoundDeclaration::PhysicalTypeSecondary(value, _) => &mut value.decl,
+            FoundDeclaration::Component(value) => &mut value.ident.decl,
+            FoundDeclaration::Attribute(value) => &mut value.ident.decl,
+            FoundDeclaration::Alias(value) => &mut value.designator.decl,
+            FoundDeclaration::Package(value) => &mut value.ident.decl,
+            FoundDeclaration::PackageBody(value) => &mut value.ident.decl,
+            FoundDeclaration::PackageInstance(value) => &mut value.ident.decl,
+            FoundDeclaration::Configuration(value) => &mut value.ident.decl,
+            FoundDeclaration::Entity(value) => &mut value.ident.decl,
+            FoundDeclaration::Architecture(value) => &mut value.ident.decl,
+            FoundDeclaration::Context(value) => &mut value.ident.decl,
+            FoundDeclaration::GenerateBody(value) => &mut value.decl,
+            FoundDeclaration::ConcurrentStatement(_, value) => value,
+            FoundDeclaration::SequentialStatement(_, value) => value,
+        }
+    }
+}
+
 impl<'a> HasSrcPos for FoundDeclaration<'a> {
     fn pos(&self) -> &SrcPos {
         match self {
@@ -1777,6 +1758,160 @@ impl std::fmt::Display for FoundDeclaration<'_> {
     }
 }
 
+/// All resolved declarations and names of a source
+pub struct FindAllItems {
+    source: Source,
+    pub items: Vec<(SrcPos, ItemKind, EntityId)>,
+}
+
+impl FindAllItems {
+    pub fn new(source: &Source) -> FindAllItems {
+        FindAllItems {
+            source: source.clone(),
+            items: Vec::new(),
+        }
+    }
+}
+
+impl Searcher for FindAllItems {
+    fn search_decl(&mut self, decl: FoundDeclaration) -> SearchState {
+        if let Some(id) = decl.ent_id() {
+            self.items
+                .push((decl.pos().clone(), ItemKind::Declaration, id));
+            if let Some(end_pos) = decl.end_ident_pos() {
+                self.items.push((end_pos.clone(), ItemKind::Reference, id));
+            }
+        }
+        NotFinished
+    }
+
+    fn search_pos_with_ref(&mut self, pos: &SrcPos, reference: &mut Reference) -> SearchState {
+        if let Some(id) = reference {
+            self.items.push((pos.clone(), ItemKind::Reference, *id));
+        }
+        NotFinished
+    }
+
+    fn search_source(&mut self, source: &Source) -> SearchState {
+        if source == &self.source {
+            NotFinished
+        } else {
+            Finished(NotFound)
+        }
+    }
+}
+
+/// The actuals of the positional associations of calls and instances in a source with the
+/// subprogram, entity or component whose formals they are associated with
+pub struct FindPositionalActuals {
+    // All sources when not set
+    source: Option<Source>,
+    // Only the instance at the cursor when set
+    cursor: Option<Position>,
+    // Only the port maps of the instances of the entity when set
+    entity: Option<EntityId>,
+    pub result: Vec<(EntityId, InterfaceType, Vec<SrcPos>)>,
+}
+
+impl FindPositionalActuals {
+    pub fn new(source: &Source) -> FindPositionalActuals {
+        FindPositionalActuals {
+            source: Some(source.clone()),
+            cursor: None,
+            entity: None,
+            result: Vec::new(),
+        }
+    }
+
+    /// Find the positional actuals of the instance at the cursor only
+    pub fn instance_at(source: &Source, cursor: Position) -> FindPositionalActuals {
+        FindPositionalActuals {
+            source: Some(source.clone()),
+            cursor: Some(cursor),
+            entity: None,
+            result: Vec::new(),
+        }
+    }
+
+    /// Find the positional actuals of the port maps of the instances of an entity in all sources
+    /// Instances of components are bound by name and are not included
+    pub fn port_maps_of(entity: EntityId) -> FindPositionalActuals {
+        FindPositionalActuals {
+            source: None,
+            cursor: None,
+            entity: Some(entity),
+            result: Vec::new(),
+        }
+    }
+
+    fn add(&mut self, reference: Reference, typ: InterfaceType, elems: &[AssociationElement]) {
+        // Positional associations precede the named associations
+        let actuals: Vec<_> = elems
+            .iter()
+            .take_while(|elem| elem.formal.is_none())
+            .map(|elem| elem.actual.pos.clone())
+            .collect();
+        if let Some(id) = reference {
+            if !actuals.is_empty() {
+                self.result.push((id, typ, actuals));
+            }
+        }
+    }
+}
+
+impl Searcher for FindPositionalActuals {
+    fn search_call_or_indexed(&mut self, call: &CallOrIndexed) -> SearchState {
+        if self.cursor.is_none() && self.entity.is_none() {
+            self.add(
+                call.name.item.get_suffix_reference(),
+                InterfaceType::Parameter,
+                &call.parameters,
+            );
+        }
+        NotFinished
+    }
+
+    fn search_instance(&mut self, instance: &InstantiationStatement) -> SearchState {
+        let name = match instance.unit {
+            InstantiatedUnit::Entity(ref name, _) | InstantiatedUnit::Component(ref name) => name,
+            InstantiatedUnit::Configuration(_) => return NotFinished,
+        };
+        if let Some(cursor) = self.cursor {
+            // From the name of the unit to the last actual
+            let end = instance
+                .port_map
+                .last()
+                .or(instance.generic_map.last())
+                .map(|assoc| assoc.actual.pos.end())
+                .unwrap_or_else(|| name.pos.end());
+            if cursor < name.pos.start() || end < cursor {
+                return NotFinished;
+            }
+        }
+        let reference = match name.item {
+            SelectedName::Designator(ref designator) => designator.reference,
+            SelectedName::Selected(_, ref suffix) => suffix.item.reference,
+        };
+        if let Some(entity) = self.entity {
+            if matches!(instance.unit, InstantiatedUnit::Entity(..)) && reference == Some(entity) {
+                self.add(reference, InterfaceType::Port, &instance.port_map);
+            }
+            return NotFinished;
+        }
+        self.add(reference, InterfaceType::Generic, &instance.generic_map);
+        self.add(reference, InterfaceType::Port, &instance.port_map);
+        NotFinished
+    }
+
+    fn search_source(&mut self, source: &Source) -> SearchState {
+        if self.source.as_ref().is_none_or(|own| own == source) {
+            NotFinished
+        } else {
+            Finished(NotFound)
+        }
+    }
+}
+
 #[derive(Default)]
 pub struct FindAllUnresolved {
     pub count: usize,
@@ -1801,13 +1936,20 @@ pub fn clear_references(tree: &mut impl Search) {
             *reference = None;
             NotFinished
         }
+
+        fn search_decl(&mut self, decl: FoundDeclaration) -> SearchState {
+            // A unit whose analysis stops early would otherwise keep ids
+            // into the arena of its previous analysis
+            *decl.decl_mut() = None;
+            NotFinished
+        }
     }
 
     let mut searcher = ReferenceClearer;
     let _ = tree.search(&mut searcher);
 }
 
-#[cfg(test)]
+#[cfg(any(test, feature = "test-util"))]
 pub fn check_no_unresolved(tree: &mut impl Search) {
     #[derive(Default)]
     struct CheckNoUnresolved;
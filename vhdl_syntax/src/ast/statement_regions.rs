@@ -0,0 +1,223 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this file,
+// You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) 2023, Olof Kraigher olof.kraigher@gmail.com
+
+//! Find the source regions of the concurrent statements of entities and architectures
+//! such that tools can map coverage data of a simulator back onto the source
+
+use super::ancestry::statement_pos;
+use super::*;
+
+impl ConcurrentStatement {
+    /// The kind of statement such as `process` or `for generate`
+    pub fn describe(&self) -> &'static str {
+        match self {
+            ConcurrentStatement::ProcedureCall(..) => "procedure call",
+            ConcurrentStatement::Block(..) => "block",
+            ConcurrentStatement::Process(..) => "process",
+            ConcurrentStatement::Assert(..) => "assert",
+            ConcurrentStatement::Assignment(..) => "assignment",
+            ConcurrentStatement::Instance(..) => "instance",
+            ConcurrentStatement::ForGenerate(..) => "for generate",
+            ConcurrentStatement::IfGenerate(..) => "if generate",
+            ConcurrentStatement::CaseGenerate(..) => "case generate",
+        }
+    }
+}
+
+/// The region of a concurrent statement
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct StatementRegion {
+    pub kind: &'static str,
+    pub label: Option<Symbol>,
+    /// The labels of the enclosing blocks, generate statements and generate alternatives, outermost first
+    pub hierarchy: Vec<Symbol>,
+    pub entity: Symbol,
+    /// The architecture of the statement or None for statements of an entity
+    pub architecture: Option<Symbol>,
+    /// The statement including its label
+    pub pos: SrcPos,
+}
+
+/// Find the concurrent statements of the design file, an enclosing statement before the statements within it
+pub fn find_statement_regions(design_file: &DesignFile) -> Vec<StatementRegion> {
+    let mut regions = Vec::new();
+    for unit in design_file.design_units.iter() {
+        let (entity, architecture, statements) = match unit {
+            AnyDesignUnit::Primary(AnyPrimaryUnit::Entity(entity)) => {
+                (&entity.ident().item, None, &entity.statements)
+            }
+            AnyDesignUnit::Secondary(AnySecondaryUnit::Architecture(architecture)) => (
+                &architecture.primary_ident().item,
+                Some(&architecture.ident().item),
+                &architecture.statements,
+            ),
+            _ => continue,
+        };
+
+        let mut finder = StatementRegionFinder {
+            entity,
+            architecture,
+            hierarchy: Vec::new(),
+            regions: &mut regions,
+        };
+        finder.concurrent_statements(statements);
+    }
+    regions
+}
+
+struct StatementRegionFinder<'a> {
+    entity: &'a Symbol,
+    architecture: Option<&'a Symbol>,
+    hierarchy: Vec<Symbol>,
+    regions: &'a mut Vec<StatementRegion>,
+}
+
+impl<'a> StatementRegionFinder<'a> {
+    fn concurrent_statements(&mut self, statements: &[LabeledConcurrentStatement]) {
+        for statement in statements {
+            let label = statement
+                .label
+                .tree
+                .as_ref()
+                .map(|label| label.item.clone());
+            self.regions.push(StatementRegion {
+                kind: statement.statement.item.describe(),
+                label: label.clone(),
+                hierarchy: self.hierarchy.clone(),
+                entity: self.entity.clone(),
+                architecture: self.architecture.cloned(),
+                pos: statement_pos(&statement.label, &statement.statement),
+            });
+
+            let depth = self.hierarchy.len();
+            self.hierarchy.extend(label);
+            match statement.statement.item {
+                ConcurrentStatement::Block(ref block) => {
+                    self.concurrent_statements(&block.statements);
+                }
+                ConcurrentStatement::ForGenerate(ref gen) => {
+                    self.generate_body(&gen.body);
+                }
+                ConcurrentStatement::IfGenerate(ref gen) => {
+                    for body in gen
+                        .conds
+                        .conditionals
+                        .iter()
+                        .map(|cond| &cond.item)
+                        .chain(gen.conds.else_item.iter())
+                    {
+                        self.generate_body(body);
+                    }
+                }
+                ConcurrentStatement::CaseGenerate(ref gen) => {
+                    for alternative in gen.sels.alternatives.iter() {
+                        self.generate_body(&alternative.item);
+                    }
+                }
+                _ => {}
+            }
+            self.hierarchy.truncate(depth);
+        }
+    }
+
+    fn generate_body(&mut self, body: &GenerateBody) {
+        let depth = self.hierarchy.len();
+        if let Some(ref label) = body.alternative_label {
+            self.hierarchy.push(label.tree.item.clone());
+        }
+        self.concurrent_statements(&body.statements);
+        self.hierarchy.truncate(depth);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::syntax::test::Code;
+
+    fn describe(region: &StatementRegion) -> (String, &'static str, Vec<String>) {
+        (
+            region
+                .label
+                .as_ref()
+                .map(|label| label.name_utf8())
+                .unwrap_or_default(),
+            region.kind,
+            region
+                .hierarchy
+                .iter()
+                .map(|label| label.name_utf8())
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn finds_nested_statements_with_hierarchy() {
+        let code = Code::new(
+            "
+architecture rtl of foo is
+begin
+  p_ctrl: process
+  begin
+  end process;
+
+  gen: for i in 0 to 1 generate
+    inst: entity work.bar;
+  end generate;
+
+  sel: if alt1: true generate
+    sig <= '0';
+  else alt2: generate
+    blk: block
+    begin
+      assert false;
+    end block;
+  end generate;
+end architecture;
+",
+        );
+
+        let regions = find_statement_regions(&code.design_file());
+        assert_eq!(
+            regions.iter().map(describe).collect::<Vec<_>>(),
+            vec![
+                ("p_ctrl".to_owned(), "process", vec![]),
+                ("gen".to_owned(), "for generate", vec![]),
+                ("inst".to_owned(), "instance", vec!["gen".to_owned()]),
+                ("sel".to_owned(), "if generate", vec![]),
+                (
+                    "".to_owned(),
+                    "assignment",
+                    vec!["sel".to_owned(), "alt1".to_owned()]
+                ),
+                (
+                    "blk".to_owned(),
+                    "block",
+                    vec!["sel".to_owned(), "alt2".to_owned()]
+                ),
+                (
+                    "".to_owned(),
+                    "assert",
+                    vec!["sel".to_owned(), "alt2".to_owned(), "blk".to_owned()]
+                ),
+            ]
+        );
+        assert_eq!(
+            regions[0].pos,
+            code.s1("p_ctrl")
+                .pos()
+                .combine(&code.s1("end process;").pos())
+        );
+        assert_eq!(regions[0].entity.name_utf8(), "foo");
+        assert_eq!(
+            regions[0]
+                .architecture
+                .as_ref()
+                .map(|arch| arch.name_utf8()),
+            Some("rtl".to_owned())
+        );
+    }
+}
@@ -0,0 +1,256 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this file,
+// You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) 2023, Olof Kraigher olof.kraigher@gmail.com
+
+//! Find the test cases of testbench architectures such that an editor can offer to run them
+//! VUnit test cases are the conditions `run("name")` of the test suite loop
+//! OSVVM test cases are named by `SetTestName("name")` or `SetAlertLogName("name")`
+
+use super::*;
+
+/// The test framework of a test case
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum TestFramework {
+    VUnit,
+    Osvvm,
+}
+
+impl TestFramework {
+    pub fn describe(&self) -> &'static str {
+        match self {
+            TestFramework::VUnit => "vunit",
+            TestFramework::Osvvm => "osvvm",
+        }
+    }
+}
+
+/// A test case of a testbench
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct TestCase {
+    pub name: String,
+    pub framework: TestFramework,
+    /// The entity of the testbench architecture
+    pub testbench: Symbol,
+    /// The call that names the test case
+    pub pos: SrcPos,
+}
+
+/// Find the test cases in the architectures of the design file in the order they appear
+pub fn find_test_cases(design_file: &DesignFile) -> Vec<TestCase> {
+    let mut test_cases = Vec::new();
+    for unit in design_file.design_units.iter() {
+        if let AnyDesignUnit::Secondary(AnySecondaryUnit::Architecture(architecture)) = unit {
+            let mut finder = TestCaseFinder {
+                testbench: &architecture.entity_name.item.item,
+                test_cases: &mut test_cases,
+            };
+            finder.concurrent_statements(&architecture.statements);
+        }
+    }
+    test_cases
+}
+
+struct TestCaseFinder<'a> {
+    testbench: &'a Symbol,
+    test_cases: &'a mut Vec<TestCase>,
+}
+
+impl<'a> TestCaseFinder<'a> {
+    fn push(&mut self, name: String, framework: TestFramework, pos: &SrcPos) {
+        self.test_cases.push(TestCase {
+            name,
+            framework,
+            testbench: self.testbench.clone(),
+            pos: pos.clone(),
+        });
+    }
+
+    fn concurrent_statements(&mut self, statements: &[LabeledConcurrentStatement]) {
+        for statement in statements {
+            match statement.statement.item {
+                ConcurrentStatement::Process(ref process) => {
+                    self.sequential_statements(&process.statements);
+                }
+                ConcurrentStatement::Block(ref block) => {
+                    self.concurrent_statements(&block.statements);
+                }
+                ConcurrentStatement::ForGenerate(ref gen) => {
+                    self.concurrent_statements(&gen.body.statements);
+                }
+                ConcurrentStatement::IfGenerate(ref gen) => {
+                    for body in gen
+                        .conds
+                        .conditionals
+                        .iter()
+                        .map(|cond| &cond.item)
+                        .chain(gen.conds.else_item.iter())
+                    {
+                        self.concurrent_statements(&body.statements);
+                    }
+                }
+                ConcurrentStatement::CaseGenerate(ref gen) => {
+                    for alternative in gen.sels.alternatives.iter() {
+                        self.concurrent_statements(&alternative.item.statements);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn sequential_statements(&mut self, statements: &[LabeledSequentialStatement]) {
+        for statement in statements {
+            match statement.statement.item {
+                SequentialStatement::If(ref ifstmt) => {
+                    for cond in ifstmt.conds.conditionals.iter() {
+                        if let Expression::Name(ref name) = cond.condition.item {
+                            if let Name::CallOrIndexed(ref call) = name.as_ref() {
+                                if let Some(test_name) = string_argument_of(call, &["run"]) {
+                                    self.push(test_name, TestFramework::VUnit, &cond.condition.pos);
+                                }
+                            }
+                        }
+                        self.sequential_statements(&cond.item);
+                    }
+                    if let Some(ref stmts) = ifstmt.conds.else_item {
+                        self.sequential_statements(stmts);
+                    }
+                }
+                SequentialStatement::Case(ref case_stmt) => {
+                    for alternative in case_stmt.alternatives.iter() {
+                        self.sequential_statements(&alternative.item);
+                    }
+                }
+                SequentialStatement::Loop(ref loop_stmt) => {
+                    self.sequential_statements(&loop_stmt.statements);
+                }
+                SequentialStatement::ProcedureCall(ref call) => {
+                    if let Some(test_name) =
+                        string_argument_of(&call.item, &["settestname", "setalertlogname"])
+                    {
+                        self.push(test_name, TestFramework::Osvvm, &call.pos);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+/// The string literal of a call such as `run("name")` to a subprogram with one of the names
+fn string_argument_of(call: &CallOrIndexed, names: &[&str]) -> Option<String> {
+    let designator = match call.name.item {
+        Name::Designator(ref designator) => &designator.item,
+        Name::Selected(_, ref designator) => &designator.item.item,
+        _ => return None,
+    };
+    let is_named = matches!(designator, Designator::Identifier(sym)
+        if names.iter().any(|name| sym.name_utf8().eq_ignore_ascii_case(name)));
+    if !is_named {
+        return None;
+    }
+
+    match call.parameters.as_slice() {
+        [AssociationElement {
+            formal: None,
+            actual,
+        }] => match actual.item {
+            ActualPart::Expression(Expression::Literal(Literal::String(ref value))) => {
+                Some(value.to_string())
+            }
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::syntax::test::Code;
+
+    fn test_names(code: &Code) -> Vec<(String, TestFramework, String)> {
+        find_test_cases(&code.design_file())
+            .into_iter()
+            .map(|test_case| {
+                (
+                    test_case.name,
+                    test_case.framework,
+                    test_case.testbench.name_utf8(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn finds_vunit_test_cases() {
+        let code = Code::new(
+            "
+architecture tb of tb_uart is
+begin
+  main: process
+  begin
+    test_runner_setup(runner, runner_cfg);
+    while test_suite loop
+      if run(\"send one byte\") then
+        null;
+      elsif run(\"receive\") then
+        null;
+      elsif is_set(\"other\") then
+        null;
+      end if;
+    end loop;
+    test_runner_cleanup(runner);
+  end process;
+end architecture;
+",
+        );
+
+        assert_eq!(
+            test_names(&code),
+            vec![
+                (
+                    "send one byte".to_owned(),
+                    TestFramework::VUnit,
+                    "tb_uart".to_owned()
+                ),
+                (
+                    "receive".to_owned(),
+                    TestFramework::VUnit,
+                    "tb_uart".to_owned()
+                ),
+            ]
+        );
+        assert_eq!(
+            find_test_cases(&code.design_file())[0].pos,
+            code.s1("run(\"send one byte\")").pos()
+        );
+    }
+
+    #[test]
+    fn finds_osvvm_test_cases() {
+        let code = Code::new(
+            "
+architecture SendGet of TestCtrl is
+begin
+  ControlProc: process
+  begin
+    SetTestName(\"TbUart_SendGet\");
+    wait;
+  end process;
+end architecture;
+",
+        );
+
+        assert_eq!(
+            test_names(&code),
+            vec![(
+                "TbUart_SendGet".to_owned(),
+                TestFramework::Osvvm,
+                "TestCtrl".to_owned()
+            )]
+        );
+    }
+}
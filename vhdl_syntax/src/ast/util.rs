@@ -6,7 +6,6 @@
 
 //! Name conversions
 use super::*;
-use crate::analysis::{Concurrent, Sequential};
 use crate::data::*;
 
 impl From<WithPos<SelectedName>> for WithPos<Name> {
@@ -484,22 +483,28 @@ impl SubprogramDeclaration {
             SubprogramDeclaration::Procedure(ref mut procedure) => &mut procedure.designator.decl,
         }
     }
+
+    pub fn set_decl_id(&mut self, id: EntityId) {
+        match self {
+            SubprogramDeclaration::Function(f) => f.designator.decl = Some(id),
+            SubprogramDeclaration::Procedure(p) => p.designator.decl = Some(id),
+        }
+    }
 }
 
-impl ConcurrentStatement {
-    pub fn label_typ(&self) -> Option<Concurrent> {
-        use ConcurrentStatement::*;
+impl ObjectClass {
+    pub fn describe(&self) -> &str {
+        use ObjectClass::*;
         match self {
-            ProcedureCall(_) => None,
-            Block(_) => Some(Concurrent::Block),
-            Process(_) => Some(Concurrent::Process),
-            Assert(_) => None,
-            Assignment(_) => None,
-            Instance(_) => Some(Concurrent::Instance),
-            ForGenerate(_) | IfGenerate(_) | CaseGenerate(_) => Some(Concurrent::Generate),
+            Constant => "constant",
+            Variable => "variable",
+            Signal => "signal",
+            SharedVariable => "shared variable",
         }
     }
+}
 
+impl ConcurrentStatement {
     pub fn end_label_pos(&self) -> Option<&SrcPos> {
         use ConcurrentStatement::*;
 
@@ -515,34 +520,9 @@ impl ConcurrentStatement {
             CaseGenerate(value) => value.end_label_pos.as_ref(),
         }
     }
-
-    pub fn can_have_label(&self) -> bool {
-        self.label_typ().is_some()
-    }
 }
 
 impl SequentialStatement {
-    pub fn label_typ(&self) -> Option<Sequential> {
-        use SequentialStatement::*;
-        match self {
-            Wait(_) => None,
-            Assert(_) => None,
-            Report(_) => None,
-            VariableAssignment(_) => None,
-            SignalAssignment(_) => None,
-            SignalForceAssignment(_) => None,
-            SignalReleaseAssignment(_) => None,
-            ProcedureCall(_) => None,
-            If(_) => Some(Sequential::If),
-            Case(_) => Some(Sequential::Case),
-            Loop(_) => Some(Sequential::Loop),
-            Next(_) => None,
-            Exit(_) => None,
-            Return(_) => None,
-            Null => None,
-        }
-    }
-
     pub fn end_label_pos(&self) -> Option<&SrcPos> {
         use SequentialStatement::*;
         match self {
@@ -563,8 +543,81 @@ impl SequentialStatement {
             Null => None,
         }
     }
+}
+
+impl BaseSpecifier {
+    /// Returns whether this base specifier represents a signed value
+    /// (i.e. `SX` for signed hexadecimal) or an unsigned value
+    /// (i.e. `UX` or `X` for unsigned hexadecimal)
+    pub fn is_signed(&self) -> bool {
+        match self {
+            BaseSpecifier::SX | BaseSpecifier::SO | BaseSpecifier::SB => true,
+            BaseSpecifier::B
+            | BaseSpecifier::UB
+            | BaseSpecifier::O
+            | BaseSpecifier::UO
+            | BaseSpecifier::X
+            | BaseSpecifier::UX
+            | BaseSpecifier::D => false,
+        }
+    }
 
-    pub fn can_have_label(&self) -> bool {
-        self.label_typ().is_some()
+    /// Get the digits that are obtained by replacing `byte` with the
+    /// appropriate sequence of characters as defined in the standard (section 15.8).
+    ///
+    /// # Special Cases
+    /// If the base specifier is `D`, i.e. decimal, return the byte itself (wrapped as array)
+    ///
+    /// # Example
+    /// ```
+    /// use vhdl_syntax::ast::BaseSpecifier;
+    ///
+    /// let digits: Vec<u8> = BaseSpecifier::UX.get_extended_digits(b'C');
+    /// assert_eq!(digits, Vec::from("1100"));
+    ///
+    /// let digits: Vec<u8> = BaseSpecifier::O.get_extended_digits(b'F');
+    /// assert_eq!(digits, Vec::from("FFF"))
+    /// ```
+    pub fn get_extended_digits(&self, byte: u8) -> Vec<u8> {
+        match self {
+            // For O, UO and SO, the values 1-7 are replaced.
+            // All other values are left as-is.
+            BaseSpecifier::O | BaseSpecifier::UO | BaseSpecifier::SO => match byte {
+                b'0' => Vec::from("000"),
+                b'1' => Vec::from("001"),
+                b'2' => Vec::from("010"),
+                b'3' => Vec::from("011"),
+                b'4' => Vec::from("100"),
+                b'5' => Vec::from("101"),
+                b'6' => Vec::from("110"),
+                b'7' => Vec::from("111"),
+                _ => vec![byte; 3],
+            },
+            // For U, UX and SX, the values 1-9 and A-F are replaced.
+            // All other values are left as-is.
+            BaseSpecifier::X | BaseSpecifier::UX | BaseSpecifier::SX => match byte {
+                b'0' => Vec::from("0000"),
+                b'1' => Vec::from("0001"),
+                b'2' => Vec::from("0010"),
+                b'3' => Vec::from("0011"),
+                b'4' => Vec::from("0100"),
+                b'5' => Vec::from("0101"),
+                b'6' => Vec::from("0110"),
+                b'7' => Vec::from("0111"),
+                b'8' => Vec::from("1000"),
+                b'9' => Vec::from("1001"),
+                b'A' | b'a' => Vec::from("1010"),
+                b'B' | b'b' => Vec::from("1011"),
+                b'C' | b'c' => Vec::from("1100"),
+                b'D' | b'd' => Vec::from("1101"),
+                b'E' | b'e' => Vec::from("1110"),
+                b'F' | b'f' => Vec::from("1111"),
+                _ => vec![byte; 4],
+            },
+            // Binary values are simply the values left as they are.
+            BaseSpecifier::B | BaseSpecifier::UB | BaseSpecifier::SB | BaseSpecifier::D => {
+                vec![byte]
+            }
+        }
     }
 }
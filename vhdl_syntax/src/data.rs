@@ -7,6 +7,7 @@
 
 mod contents;
 mod diagnostic;
+mod fix;
 mod latin_1;
 mod message;
 mod source;
@@ -14,6 +15,7 @@ mod symbol_table;
 
 pub use contents::*;
 pub use diagnostic::*;
+pub use fix::*;
 pub use latin_1::*;
 pub use message::*;
 pub use source::*;
@@ -10,6 +10,56 @@ use std::fs::File;
 use std::io;
 use std::io::prelude::Read;
 use std::path::Path;
+use std::str::FromStr;
+
+/// The character encoding of a source file
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum SourceEncoding {
+    /// UTF-8 when the file starts with a byte order mark or is valid UTF-8, otherwise Latin-1
+    #[default]
+    Detect,
+    Utf8,
+    Latin1,
+}
+
+impl FromStr for SourceEncoding {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "auto" | "detect" => Ok(SourceEncoding::Detect),
+            "utf-8" | "utf8" => Ok(SourceEncoding::Utf8),
+            "latin-1" | "latin1" | "iso-8859-1" => Ok(SourceEncoding::Latin1),
+            _ => Err(format!("Unsupported source encoding '{s}'")),
+        }
+    }
+}
+
+const UTF8_BOM: &[u8] = &[0xef, 0xbb, 0xbf];
+
+/// Decode the bytes of a source file into text
+/// Invalid UTF-8 sequences of a file configured as UTF-8 are replaced by U+FFFD
+/// such that each one still occupies a single character position
+pub fn decode_source(bytes: Vec<u8>, encoding: SourceEncoding) -> String {
+    match encoding {
+        SourceEncoding::Detect => {
+            if bytes.starts_with(UTF8_BOM) {
+                decode_source(bytes, SourceEncoding::Utf8)
+            } else {
+                match String::from_utf8(bytes) {
+                    Ok(text) => text,
+                    // Vendor files with high-bit characters are typically Latin-1
+                    Err(err) => decode_source(err.into_bytes(), SourceEncoding::Latin1),
+                }
+            }
+        }
+        SourceEncoding::Utf8 => {
+            let bytes = bytes.strip_prefix(UTF8_BOM).unwrap_or(&bytes);
+            String::from_utf8_lossy(bytes).into_owned()
+        }
+        SourceEncoding::Latin1 => Latin1String::from_vec(bytes).to_string(),
+    }
+}
 
 pub struct Contents {
     lines: Vec<String>,
@@ -17,14 +67,17 @@ pub struct Contents {
 
 impl Contents {
     pub fn from_latin1_file(file_name: &Path) -> io::Result<Contents> {
+        Contents::from_file(file_name, SourceEncoding::Latin1)
+    }
+
+    pub fn from_file(file_name: &Path, encoding: SourceEncoding) -> io::Result<Contents> {
         let mut file = File::open(file_name)?;
         let mut bytes = Vec::new();
         file.read_to_end(&mut bytes)?;
-        Ok(Contents::from_str(
-            &Latin1String::from_vec(bytes).to_string(),
-        ))
+        Ok(Contents::from_str(&decode_source(bytes, encoding)))
     }
 
+    #[allow(clippy::should_implement_trait)]
     pub fn from_str(code: &str) -> Contents {
         Contents {
             lines: split_lines(code),
@@ -48,13 +101,20 @@ impl Contents {
         Position { line, character }
     }
 
-    #[cfg(test)]
+    #[cfg(any(test, feature = "test-util"))]
     pub fn range(&self) -> Range {
         Range::new(self.start(), self.end())
     }
 
-    #[cfg(test)]
+    #[cfg(any(test, feature = "test-util"))]
     pub fn crop(&self, range: Range) -> Contents {
+        Contents {
+            lines: split_lines(&self.text_in(range)),
+        }
+    }
+
+    /// The text within the range
+    pub fn text_in(&self, range: Range) -> String {
         let mut reader = ContentReader::new(self);
         reader.seek_pos(range.start);
 
@@ -62,12 +122,11 @@ impl Contents {
         while reader.pos() < range.end {
             if let Some(chr) = reader.pop_char() {
                 result.push(chr);
+            } else {
+                break;
             }
         }
-
-        Contents {
-            lines: split_lines(&result),
-        }
+        result
     }
 
     pub fn num_lines(&self) -> usize {
@@ -270,7 +329,7 @@ impl<'a> ContentReader<'a> {
         Ok(self.peek()?.map(Latin1String::lowercase))
     }
 
-    #[cfg(test)]
+    #[cfg(any(test, feature = "test-util"))]
     pub fn matches(&mut self, substr: &str) -> bool {
         let mut lookahead = self.clone();
         for exp in substr.chars() {
@@ -306,7 +365,6 @@ impl<'a> ContentReader<'a> {
         self.state.pos()
     }
 
-    #[cfg(test)]
     pub fn seek_pos(&mut self, pos: Position) {
         self.state = ReaderState {
             pos: Position {
@@ -346,6 +404,50 @@ mod tests {
         ContentReader::new(contents)
     }
 
+    #[test]
+    fn decode_source_detects_encoding() {
+        let utf8 = "-- Gr\u{fc}\u{df}e \u{2192}\n".as_bytes().to_vec();
+        assert_eq!(
+            decode_source(utf8.clone(), SourceEncoding::Detect),
+            "-- Gr\u{fc}\u{df}e \u{2192}\n"
+        );
+
+        let mut with_bom = UTF8_BOM.to_vec();
+        with_bom.extend_from_slice(&utf8);
+        assert_eq!(
+            decode_source(with_bom, SourceEncoding::Detect),
+            "-- Gr\u{fc}\u{df}e \u{2192}\n"
+        );
+
+        let latin1 = b"-- Gr\xfc\xdfe\n".to_vec();
+        assert_eq!(
+            decode_source(latin1.clone(), SourceEncoding::Detect),
+            "-- Gr\u{fc}\u{df}e\n"
+        );
+        assert_eq!(
+            decode_source(latin1, SourceEncoding::Utf8),
+            "-- Gr\u{fffd}\u{fffd}e\n"
+        );
+        assert_eq!(
+            decode_source(b"Gr\xc3\xbc".to_vec(), SourceEncoding::Latin1),
+            "Gr\u{c3}\u{bc}"
+        );
+    }
+
+    #[test]
+    fn latin1_identifier_positions_after_decoding() {
+        let contents = Contents::from_str(&decode_source(
+            b"signal \xe4 : bit;".to_vec(),
+            SourceEncoding::Detect,
+        ));
+        let mut reader = reader(&contents);
+        for _ in 0.."signal ".len() {
+            reader.skip();
+        }
+        assert_eq!(reader.pop(), Ok(Some(0xe4)));
+        assert_eq!(reader.pos(), Position::new(0, 8));
+    }
+
     #[test]
     fn pop_latin1_ok() {
         let contents = new("hi");
@@ -15,6 +15,36 @@ pub enum Severity {
     Error,
 }
 
+/// A replacement of the text at a position
+#[derive(PartialEq, Debug, Clone, Eq, Hash)]
+pub struct TextEdit {
+    pub pos: SrcPos,
+    pub new_text: String,
+}
+
+impl TextEdit {
+    pub fn replace(item: impl AsRef<SrcPos>, new_text: impl Into<String>) -> TextEdit {
+        TextEdit {
+            pos: item.as_ref().clone(),
+            new_text: new_text.into(),
+        }
+    }
+
+    pub fn remove(item: impl AsRef<SrcPos>) -> TextEdit {
+        Self::replace(item, "")
+    }
+}
+
+/// An automatic fix of the problem reported by a diagnostic
+#[derive(PartialEq, Debug, Clone, Eq, Hash)]
+pub struct Fix {
+    /// A short description such as `Replace with 'foo'`
+    pub title: String,
+    pub edits: Vec<TextEdit>,
+    /// A safe fix keeps the meaning of the code such that it can be applied without review
+    pub is_safe: bool,
+}
+
 #[must_use]
 #[derive(PartialEq, Debug, Clone, Eq, Hash)]
 pub struct Diagnostic {
@@ -22,6 +52,9 @@ pub struct Diagnostic {
     pub message: String,
     pub severity: Severity,
     pub related: Vec<(SrcPos, String)>,
+    pub fixes: Vec<Fix>,
+    /// Identifies the kind of a lint such that clients can filter it
+    pub code: Option<&'static str>,
 }
 
 impl Diagnostic {
@@ -31,6 +64,8 @@ impl Diagnostic {
             message: msg.into(),
             severity,
             related: vec![],
+            fixes: vec![],
+            code: None,
         }
     }
 
@@ -56,6 +91,8 @@ impl Diagnostic {
             pos: self.pos,
             severity: self.severity,
             related: vec![],
+            fixes: self.fixes,
+            code: self.code,
         }
     }
 
@@ -77,6 +114,33 @@ impl Diagnostic {
         diagnostic
     }
 
+    pub fn fix(self, title: impl Into<String>, edits: Vec<TextEdit>) -> Diagnostic {
+        let mut diagnostic = self;
+        diagnostic.fixes.push(Fix {
+            title: title.into(),
+            edits,
+            is_safe: true,
+        });
+        diagnostic
+    }
+
+    /// A fix that may change the meaning of the code and should be reviewed
+    pub fn unsafe_fix(self, title: impl Into<String>, edits: Vec<TextEdit>) -> Diagnostic {
+        let mut diagnostic = self;
+        diagnostic.fixes.push(Fix {
+            title: title.into(),
+            edits,
+            is_safe: false,
+        });
+        diagnostic
+    }
+
+    pub fn with_code(self, code: &'static str) -> Diagnostic {
+        let mut diagnostic = self;
+        diagnostic.code = Some(code);
+        diagnostic
+    }
+
     pub fn add_related(&mut self, item: impl AsRef<SrcPos>, message: impl Into<String>) {
         self.related
             .push((item.as_ref().to_owned(), message.into()));
@@ -167,10 +231,10 @@ impl DiagnosticHandler for NullDiagnostics {
     }
 }
 
-#[cfg(test)]
+#[cfg(any(test, feature = "test-util"))]
 pub struct NoDiagnostics;
 
-#[cfg(test)]
+#[cfg(any(test, feature = "test-util"))]
 impl DiagnosticHandler for NoDiagnostics {
     fn push(&mut self, diagnostic: crate::Diagnostic) {
         panic!("{}", diagnostic.show())
@@ -0,0 +1,322 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this file,
+// You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) 2023, Olof Kraigher olof.kraigher@gmail.com
+
+//! Apply the fixes of diagnostics to the text of their source files
+
+use super::{Diagnostic, Fix, Position, Range, Source, TextEdit};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+/// The fixes to apply to a single source file
+pub struct SourceFixes {
+    pub source: Source,
+    pub fixes: Vec<Fix>,
+}
+
+/// Select the fixes of the diagnostics that can be applied together
+/// The first safe fix of each diagnostic is used unless it overlaps with an already selected fix
+/// Fixes that may change the meaning of the code are only used with `unsafe_fixes`
+/// Fixes that edit several files are not applied automatically
+pub fn select_fixes(diagnostics: &[Diagnostic], unsafe_fixes: bool) -> Vec<SourceFixes> {
+    let mut by_file: BTreeMap<PathBuf, SourceFixes> = BTreeMap::new();
+
+    for fix in diagnostics.iter().filter_map(|diagnostic| {
+        diagnostic
+            .fixes
+            .iter()
+            .find(|fix| fix.is_safe || unsafe_fixes)
+    }) {
+        let source = if let Some(edit) = fix.edits.first() {
+            &edit.pos.source
+        } else {
+            continue;
+        };
+
+        if fix.edits.iter().any(|edit| &edit.pos.source != source) {
+            continue;
+        }
+
+        let file_name = source.file_name().to_owned();
+        let selected = by_file.entry(file_name).or_insert_with(|| SourceFixes {
+            source: source.clone(),
+            fixes: Vec::new(),
+        });
+
+        let overlaps = selected.edits().any(|other| {
+            fix.edits
+                .iter()
+                .any(|edit| edits_overlap(&edit.pos.range(), &other.pos.range()))
+        });
+
+        if !overlaps {
+            selected.fixes.push(fix.clone());
+        }
+    }
+
+    by_file.into_values().collect()
+}
+
+fn edits_overlap(left: &Range, right: &Range) -> bool {
+    left.start == right.start || (left.start < right.end && right.start < left.end)
+}
+
+impl SourceFixes {
+    fn edits(&self) -> impl Iterator<Item = &TextEdit> {
+        self.fixes.iter().flat_map(|fix| fix.edits.iter())
+    }
+
+    fn byte_edits<'e>(&'e self, text: &str) -> Vec<(usize, usize, &'e str)> {
+        byte_edits(text, self.edits())
+    }
+
+    /// Apply the fixes to the text of the source file
+    /// The text is expected to be the one that was analyzed, its line endings are kept
+    pub fn apply(&self, text: &str) -> String {
+        splice(text, 0, &self.byte_edits(text))
+    }
+
+    /// A unified diff of the lines changed by the fixes
+    pub fn diff(&self, text: &str) -> String {
+        let starts = line_starts(text);
+        let line_of = |offset: usize| starts.partition_point(|start| *start <= offset) - 1;
+        let line_end = |line: usize| starts.get(line + 1).copied().unwrap_or(text.len());
+
+        let file_name = self.source.file_name().to_string_lossy();
+        let mut diff = format!("--- {file_name}\n+++ {file_name}\n");
+
+        let edits = self.byte_edits(text);
+        let mut delta: isize = 0;
+        let mut idx = 0;
+        while idx < edits.len() {
+            // Group the edits that change the same lines into one hunk
+            let first_line = line_of(edits[idx].0);
+            let mut last_line = line_of(edits[idx].1);
+            let mut end = idx + 1;
+            while end < edits.len() && line_of(edits[end].0) <= last_line {
+                last_line = last_line.max(line_of(edits[end].1));
+                end += 1;
+            }
+
+            let hunk_start = starts[first_line];
+            let before = &text[hunk_start..line_end(last_line)];
+            let after = splice(before, hunk_start, &edits[idx..end]);
+            let old_lines: Vec<_> = before.lines().collect();
+            let new_lines: Vec<_> = after.lines().collect();
+
+            diff.push_str(&format!(
+                "@@ -{},{} +{},{} @@\n",
+                first_line + 1,
+                old_lines.len(),
+                first_line as isize + 1 + delta,
+                new_lines.len()
+            ));
+            for line in old_lines.iter() {
+                diff.push_str(&format!("-{}\n", line.trim_end_matches('\r')));
+            }
+            for line in new_lines.iter() {
+                diff.push_str(&format!("+{}\n", line.trim_end_matches('\r')));
+            }
+
+            delta += new_lines.len() as isize - old_lines.len() as isize;
+            idx = end;
+        }
+
+        diff
+    }
+}
+
+/// Apply edits that do not overlap to the text of a single source file, its line endings are kept
+pub fn apply_edits(text: &str, edits: &[TextEdit]) -> String {
+    splice(text, 0, &byte_edits(text, edits.iter()))
+}
+
+/// True if some of the edits of a single source file replace the same text
+pub fn edits_overlap_any(edits: &[&TextEdit]) -> bool {
+    edits.iter().enumerate().any(|(idx, edit)| {
+        edits[..idx]
+            .iter()
+            .any(|other| edits_overlap(&edit.pos.range(), &other.pos.range()))
+    })
+}
+
+/// The edits as byte offsets into the text sorted by their start
+fn byte_edits<'e>(
+    text: &str,
+    edits: impl Iterator<Item = &'e TextEdit>,
+) -> Vec<(usize, usize, &'e str)> {
+    let starts = line_starts(text);
+    let mut edits: Vec<_> = edits
+        .map(|edit| {
+            let range = edit.pos.range();
+            (
+                byte_offset(text, &starts, range.start),
+                byte_offset(text, &starts, range.end),
+                edit.new_text.as_str(),
+            )
+        })
+        .collect();
+    edits.sort_by_key(|(start, end, _)| (*start, *end));
+    edits
+}
+
+/// Replace the ranges of text starting at the offset with the new text of the edits
+fn splice(text: &str, offset: usize, edits: &[(usize, usize, &str)]) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut last = 0;
+    for (start, end, new_text) in edits.iter() {
+        let start = start - offset;
+        let end = end - offset;
+        result.push_str(&text[last..start]);
+        result.push_str(new_text);
+        last = end;
+    }
+    result.push_str(&text[last..]);
+    result
+}
+
+/// The byte offset where each line starts using the same line endings as the parser
+fn line_starts(text: &str) -> Vec<usize> {
+    let bytes = text.as_bytes();
+    let mut starts = vec![0];
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\r' if bytes.get(i + 1) == Some(&b'\n') => i += 2,
+            b'\r' | b'\n' => i += 1,
+            _ => {
+                i += 1;
+                continue;
+            }
+        }
+        starts.push(i);
+    }
+    starts
+}
+
+/// Positions count characters in utf-16 code units within the line
+fn byte_offset(text: &str, starts: &[usize], pos: Position) -> usize {
+    let line_start = if let Some(start) = starts.get(pos.line as usize) {
+        *start
+    } else {
+        return text.len();
+    };
+
+    let mut character = 0;
+    for (offset, chr) in text[line_start..].char_indices() {
+        if character >= pos.character as usize || chr == '\r' || chr == '\n' {
+            return line_start + offset;
+        }
+        character += chr.len_utf16();
+    }
+    text.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::syntax::test::Code;
+
+    #[test]
+    fn apply_fixes_keeping_line_endings() {
+        let text = "entity ent is\r\nend entity ent2;\r\n";
+        let code = Code::new(text);
+        let diagnostics = vec![Diagnostic::error(code.s1("ent2"), "mismatch").fix(
+            "Replace with 'ent'",
+            vec![TextEdit::replace(code.s1("ent2"), "ent")],
+        )];
+
+        let fixes = select_fixes(&diagnostics, false);
+        assert_eq!(fixes.len(), 1);
+        assert_eq!(fixes[0].apply(text), "entity ent is\r\nend entity ent;\r\n");
+        assert_eq!(
+            fixes[0].diff(text),
+            format!(
+                "--- {0}\n+++ {0}\n@@ -2,1 +2,1 @@\n-end entity ent2;\n+end entity ent;\n",
+                code.source().file_name().to_string_lossy()
+            )
+        );
+    }
+
+    #[test]
+    fn unsafe_fixes_are_only_applied_when_requested() {
+        let text = "\
+process (a)
+begin
+  c <= a and b;
+end process;
+";
+        let code = Code::new(text);
+        let diagnostics = vec![
+            Diagnostic::warning(code.s1("b;").s1("b"), "missing").unsafe_fix(
+                "Add 'b' to the sensitivity list",
+                vec![TextEdit::replace(code.s1("a").pos().pos_at_end(), ", b")],
+            ),
+        ];
+
+        assert!(select_fixes(&diagnostics, false).is_empty());
+
+        let fixes = select_fixes(&diagnostics, true);
+        assert_eq!(fixes.len(), 1);
+        assert_eq!(
+            fixes[0].apply(text),
+            "\
+process (a, b)
+begin
+  c <= a and b;
+end process;
+"
+        );
+    }
+
+    #[test]
+    fn overlapping_fixes_are_skipped() {
+        let text = "\
+entity ent is
+end entity ent2;
+
+architecture a of ent is
+begin
+end architecture b;
+";
+        let code = Code::new(text);
+        let diagnostics = vec![
+            Diagnostic::error(code.s1("ent2"), "mismatch").fix(
+                "Replace with 'ent'",
+                vec![TextEdit::replace(code.s1("ent2"), "ent")],
+            ),
+            Diagnostic::error(code.s1("ent2"), "mismatch")
+                .fix("Remove", vec![TextEdit::remove(code.s1("ent2"))]),
+            Diagnostic::error(code.s1("b;").s1("b"), "mismatch").fix(
+                "Replace with 'a'",
+                vec![TextEdit::replace(code.s1("b;").s1("b"), "a")],
+            ),
+        ];
+
+        let fixes = select_fixes(&diagnostics, false);
+        assert_eq!(fixes.len(), 1);
+        assert_eq!(fixes[0].fixes.len(), 2);
+        assert_eq!(
+            fixes[0].apply(text),
+            "\
+entity ent is
+end entity ent;
+
+architecture a of ent is
+begin
+end architecture a;
+"
+        );
+        assert_eq!(
+            fixes[0].diff(text),
+            format!(
+                "--- {0}\n+++ {0}\n\
+                 @@ -2,1 +2,1 @@\n-end entity ent2;\n+end entity ent;\n\
+                 @@ -6,1 +6,1 @@\n-end architecture b;\n+end architecture a;\n",
+                code.source().file_name().to_string_lossy()
+            )
+        );
+    }
+}
@@ -112,7 +112,7 @@ impl Latin1String {
         }
     }
 
-    #[cfg(test)]
+    #[cfg(any(test, feature = "test-util"))]
     pub fn from_utf8_unchecked(string: &str) -> Latin1String {
         Self::from_utf8(string).unwrap()
     }
@@ -4,7 +4,7 @@
 //
 // Copyright (c) 2018, Olof Kraigher olof.kraigher@gmail.com
 
-use super::contents::Contents;
+use super::contents::{Contents, SourceEncoding};
 use parking_lot::{RwLock, RwLockReadGuard};
 use std::cmp::{max, min};
 use std::collections::hash_map::DefaultHasher;
@@ -70,15 +70,15 @@ impl UniqueSource {
         }
     }
 
-    fn from_latin1_file(file_name: &Path) -> io::Result<Self> {
-        let contents = Contents::from_latin1_file(file_name)?;
+    fn from_file(file_name: &Path, encoding: SourceEncoding) -> io::Result<Self> {
+        let contents = Contents::from_file(file_name, encoding)?;
         Ok(Self {
             file_id: FileId::new(file_name),
             contents: RwLock::new(contents),
         })
     }
 
-    #[cfg(test)]
+    #[cfg(any(test, feature = "test-util"))]
     pub fn from_contents(file_name: &Path, contents: Contents) -> UniqueSource {
         Self {
             file_id: FileId::new(file_name),
@@ -140,12 +140,16 @@ impl Source {
     }
 
     pub fn from_latin1_file(file_name: &Path) -> io::Result<Source> {
+        Source::from_file(file_name, SourceEncoding::Latin1)
+    }
+
+    pub fn from_file(file_name: &Path, encoding: SourceEncoding) -> io::Result<Source> {
         Ok(Source {
-            source: Arc::new(UniqueSource::from_latin1_file(file_name)?),
+            source: Arc::new(UniqueSource::from_file(file_name, encoding)?),
         })
     }
 
-    #[cfg(test)]
+    #[cfg(any(test, feature = "test-util"))]
     pub fn from_contents(file_name: &Path, contents: Contents) -> Source {
         Source {
             source: Arc::new(UniqueSource::from_contents(file_name, contents)),
@@ -88,7 +88,7 @@ impl SymbolTable {
         self.insert(&name)
     }
 
-    #[cfg(test)]
+    #[cfg(any(test, feature = "test-util"))]
     pub fn insert_extended_utf8(&self, name: &str) -> Symbol {
         let name = Latin1String::from_utf8_unchecked(name);
         self.insert_extended(&name)
@@ -0,0 +1,934 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this file,
+// You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) 2023, Olof Kraigher olof.kraigher@gmail.com
+
+//! Format VHDL code from its tokens such that comments, line breaks and the case of
+//! identifiers are kept. Lines are indented by the nesting of the constructs they belong to,
+//! the spaces between the tokens of a line are collapsed and the colons of consecutive
+//! declarations are aligned.
+
+use crate::data::*;
+use crate::syntax::tokens::{Kind, Symbols, Tokenizer, Value};
+use std::str::FromStr;
+
+/// The case of keywords in formatted code
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum KeywordCase {
+    #[default]
+    Preserve,
+    Lower,
+    Upper,
+}
+
+impl FromStr for KeywordCase {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "preserve" => Ok(KeywordCase::Preserve),
+            "lower" => Ok(KeywordCase::Lower),
+            "upper" => Ok(KeywordCase::Upper),
+            _ => Err(format!("Unsupported keyword case '{s}'")),
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct FormatOptions {
+    /// The number of spaces of each level of indentation
+    pub indent_width: usize,
+    pub keyword_case: KeywordCase,
+    /// Align the colons of consecutive port, generic and object declarations
+    pub align_declarations: bool,
+}
+
+impl Default for FormatOptions {
+    fn default() -> FormatOptions {
+        FormatOptions {
+            indent_width: 2,
+            keyword_case: KeywordCase::Preserve,
+            align_declarations: true,
+        }
+    }
+}
+
+/// Format the contents of a source, fails when the source cannot be tokenized
+pub fn format_source(source: &Source, options: &FormatOptions) -> Result<String, Diagnostic> {
+    format(source, options).map(|(text, _)| text)
+}
+
+/// The edits formatting the lines within a range of a source, only lines which are
+/// changed by formatting the whole source are replaced
+pub fn format_range(
+    source: &Source,
+    options: &FormatOptions,
+    range: Range,
+) -> Result<Vec<TextEdit>, Diagnostic> {
+    let (text, _) = format(source, options)?;
+    let contents = source.contents();
+
+    // A range ending at the start of a line does not include that line
+    let last_line = if range.end.character == 0 && range.end.line > range.start.line {
+        range.end.line - 1
+    } else {
+        range.end.line
+    };
+
+    let mut edits = Vec::new();
+    for (lineno, new_line) in text.lines().enumerate() {
+        let lineno = lineno as u32;
+        if lineno < range.start.line || lineno > last_line {
+            continue;
+        }
+        let old_line = line_text(&contents, lineno);
+        if old_line != new_line {
+            edits.push(TextEdit {
+                pos: SrcPos::new(
+                    source.clone(),
+                    Range::new(
+                        Position::new(lineno, 0),
+                        Position::new(lineno, utf16_len(old_line)),
+                    ),
+                ),
+                new_text: new_line.to_owned(),
+            });
+        }
+    }
+    Ok(edits)
+}
+
+/// The edits after typing the `end` or the `;` of the end of a construct at the cursor.
+/// The lines of the construct are re-indented and after a `;` the closing keyword
+/// and label which are missing such as in `end process name;` are inserted.
+pub fn format_on_type(
+    source: &Source,
+    options: &FormatOptions,
+    cursor: Position,
+) -> Result<Vec<TextEdit>, Diagnostic> {
+    let (text, ends) = format(source, options)?;
+    let contents = source.contents();
+
+    let end = match ends
+        .iter()
+        .find(|end| end.range.end == cursor || end.semicolon.is_some_and(|semi| semi.end == cursor))
+    {
+        Some(end) => end,
+        None => return Ok(Vec::new()),
+    };
+
+    let edit = |range: Range, new_text: String| TextEdit {
+        pos: SrcPos::new(source.clone(), range),
+        new_text,
+    };
+
+    let mut edits = Vec::new();
+    let formatted: Vec<&str> = text.lines().collect();
+    for lineno in end.start_line..=end.range.end.line {
+        let new_line = formatted.get(lineno as usize).copied().unwrap_or_default();
+        if new_line.is_empty() {
+            continue;
+        }
+        let old_line = line_text(&contents, lineno);
+        let old_indent = &old_line[..old_line.len() - old_line.trim_start().len()];
+        let new_indent = &new_line[..new_line.len() - new_line.trim_start().len()];
+        if old_indent != new_indent {
+            edits.push(edit(
+                Range::new(
+                    Position::new(lineno, 0),
+                    Position::new(lineno, utf16_len(old_indent)),
+                ),
+                new_indent.to_owned(),
+            ));
+        }
+    }
+
+    if end.semicolon.is_some_and(|semi| semi.end == cursor) {
+        let mut missing = String::new();
+        if let Some(closing) = end.closing.filter(|_| !end.has_keyword) {
+            missing.push(' ');
+            missing.push_str(closing);
+        }
+        if let Some(name) = end.name.as_ref().filter(|_| !end.has_name) {
+            missing.push(' ');
+            missing.push_str(name);
+        }
+        if !missing.is_empty() {
+            edits.push(edit(Range::new(end.last, end.last), missing));
+        }
+    }
+    Ok(edits)
+}
+
+fn line_text(contents: &Contents, lineno: u32) -> &str {
+    contents
+        .get_line(lineno as usize)
+        .unwrap_or_default()
+        .trim_end_matches(['\n', '\r'])
+}
+
+fn utf16_len(text: &str) -> u32 {
+    text.chars().map(|chr| chr.len_utf16() as u32).sum()
+}
+
+/// The formatted text and the ends of the constructs within it
+fn format(source: &Source, options: &FormatOptions) -> Result<(String, Vec<End>), Diagnostic> {
+    let items = items(source, options.keyword_case)?;
+
+    let mut indenter = Indenter::default();
+    let mut lines: Vec<Line> = Vec::new();
+    let mut prev_end: Option<Position> = None;
+
+    for (idx, item) in items.iter().enumerate() {
+        let start = item.range.start;
+        match prev_end {
+            Some(end) if end.line == start.line => {
+                let line = lines.last_mut().unwrap();
+                if start.character > end.character {
+                    line.text.push(' ');
+                }
+            }
+            _ => {
+                let blank_lines = match prev_end {
+                    Some(end) => start.line - end.line - 1,
+                    None => start.line,
+                };
+                for _ in 0..blank_lines {
+                    lines.push(Line::default());
+                }
+                // A line starting with a comment is indented as the statements around it
+                let (indent, alignment) = indenter.line_start(item.kind);
+                lines.push(Line {
+                    indent,
+                    text: String::new(),
+                    alignment: item.kind.and(alignment),
+                    colon: None,
+                });
+            }
+        }
+
+        let line = lines.last_mut().unwrap();
+        if let Some(kind) = item.kind {
+            if kind == Kind::Colon && line.colon.is_none() {
+                line.colon = Some(line.text.len());
+            }
+            let next_kind = items[idx + 1..].iter().find_map(|item| item.kind);
+            indenter.token(kind, item, next_kind, line.indent);
+        }
+        line.text.push_str(&item.text);
+        prev_end = Some(item.range.end);
+    }
+
+    if options.align_declarations {
+        align_colons(&mut lines);
+    }
+
+    let mut result = String::new();
+    for line in lines.iter() {
+        if !line.text.is_empty() {
+            result.push_str(&" ".repeat(line.indent * options.indent_width));
+            result.push_str(&line.text);
+        }
+        result.push('\n');
+    }
+    Ok((result, indenter.ends))
+}
+
+/// A token or a comment with its text as it is formatted
+struct Item {
+    range: Range,
+    text: String,
+    /// None for a comment
+    kind: Option<Kind>,
+}
+
+fn items(source: &Source, keyword_case: KeywordCase) -> Result<Vec<Item>, Diagnostic> {
+    let symbols = Symbols::default();
+    let contents = source.contents();
+    let mut tokenizer = Tokenizer::new(&symbols, source, ContentReader::new(&contents));
+
+    let comment = |range: Range| Item {
+        range,
+        text: contents.text_in(range),
+        kind: None,
+    };
+
+    let mut items = Vec::new();
+    while let Some(token) = tokenizer.pop()? {
+        let range = token.pos.range();
+        let mut text = contents.text_in(range);
+        if token.value == Value::NoValue {
+            match keyword_case {
+                KeywordCase::Preserve => {}
+                KeywordCase::Lower => text = text.to_lowercase(),
+                KeywordCase::Upper => text = text.to_uppercase(),
+            }
+        }
+
+        let trailing = match token.comments {
+            Some(comments) => {
+                items.extend(comments.leading.iter().map(|c| comment(c.range)));
+                comments.trailing.map(|c| comment(c.range))
+            }
+            None => None,
+        };
+        items.push(Item {
+            range,
+            text,
+            kind: Some(token.kind),
+        });
+        items.extend(trailing);
+    }
+    for final_comment in tokenizer.get_final_comments().unwrap_or_default() {
+        items.push(comment(final_comment.range));
+    }
+    Ok(items)
+}
+
+/// The kind of declarations whose colons are aligned
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum Alignment {
+    Interface,
+    Object,
+}
+
+#[derive(Default)]
+struct Line {
+    /// The level of indentation
+    indent: usize,
+    text: String,
+    alignment: Option<Alignment>,
+    /// The offset of the first colon within the text
+    colon: Option<usize>,
+}
+
+/// Align the colons of consecutive declarations of the same kind and indentation
+fn align_colons(lines: &mut [Line]) {
+    let alignable = |line: &Line| line.alignment.zip(line.colon).map(|(kind, _)| kind);
+
+    let mut start = 0;
+    while start < lines.len() {
+        let alignment = match alignable(&lines[start]) {
+            Some(alignment) => alignment,
+            None => {
+                start += 1;
+                continue;
+            }
+        };
+        let indent = lines[start].indent;
+        let end = start
+            + lines[start..]
+                .iter()
+                .take_while(|line| alignable(line) == Some(alignment) && line.indent == indent)
+                .count();
+
+        let group = &mut lines[start..end];
+        let width = group
+            .iter()
+            .map(|line| line.text[..line.colon.unwrap()].trim_end().len())
+            .max()
+            .unwrap_or(0);
+        for line in group.iter_mut() {
+            let colon = line.colon.unwrap();
+            let name = line.text[..colon].trim_end();
+            let rest = line.text[colon + 1..].trim_start();
+            line.text = if rest.is_empty() {
+                format!("{name:width$} :")
+            } else {
+                format!("{name:width$} : {rest}")
+            };
+            line.colon = Some(width + 1);
+        }
+        start = end;
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum FrameKind {
+    /// A construct with a declarative part which may be followed by `begin`
+    Declarative {
+        begun: bool,
+    },
+    Statements,
+    /// A case statement whose alternatives are indented below it
+    Case,
+    /// A configuration declaration whose block and component configurations end with `end for`
+    Configuration,
+}
+
+impl FrameKind {
+    fn width(&self) -> usize {
+        match self {
+            FrameKind::Case => 2,
+            _ => 1,
+        }
+    }
+}
+
+/// A construct which is ended by `end`
+struct Frame {
+    kind: FrameKind,
+    /// The line of the statement which starts the construct
+    start_line: u32,
+    /// The label or designator which may be repeated after `end`
+    name: Option<String>,
+    /// The keyword which must follow `end` such as `process`, None when it is optional
+    closing: Option<&'static str>,
+}
+
+/// The `end` of a construct
+struct End {
+    range: Range,
+    start_line: u32,
+    name: Option<String>,
+    closing: Option<&'static str>,
+    /// There is a keyword after `end`
+    has_keyword: bool,
+    /// There is a label or designator after `end`
+    has_name: bool,
+    /// The end of the last token before the semicolon
+    last: Position,
+    semicolon: Option<Range>,
+}
+
+struct Paren {
+    /// The indentation of the line of the opening parenthesis
+    indent: usize,
+    /// A generic or port clause
+    interface: bool,
+}
+
+/// The nesting of constructs at the current token
+#[derive(Default)]
+struct Indenter {
+    frames: Vec<Frame>,
+    parens: Vec<Paren>,
+    ends: Vec<End>,
+    /// The first keyword of the current statement after its label
+    first: Option<Kind>,
+    /// The line of the first token of the current statement
+    start_line: u32,
+    label: Option<String>,
+    /// The designator of a declaration such as an entity, subprogram or type
+    name: Option<String>,
+    /// The current statement has tokens such that a line continuing it is indented once more
+    in_statement: bool,
+    labeled: bool,
+    /// Within the end of a construct such as `end loop`
+    in_end: bool,
+    prev: Option<Kind>,
+}
+
+impl Indenter {
+    fn depth(&self) -> usize {
+        self.frames.iter().map(|frame| frame.kind.width()).sum()
+    }
+
+    fn top(&self) -> Option<FrameKind> {
+        self.frames.last().map(|frame| frame.kind)
+    }
+
+    /// The indentation of a line starting with a token of the kind
+    fn line_start(&self, kind: Option<Kind>) -> (usize, Option<Alignment>) {
+        if let Some(paren) = self.parens.last() {
+            return if kind == Some(Kind::RightPar) {
+                (paren.indent, None)
+            } else {
+                let alignment = (paren.interface
+                    && matches!(
+                        kind,
+                        Some(Kind::Identifier | Kind::Signal | Kind::Constant | Kind::Variable)
+                    ))
+                .then_some(Alignment::Interface);
+                (paren.indent + 1, alignment)
+            };
+        }
+
+        let depth = self.depth();
+        match kind {
+            Some(Kind::End) => (
+                depth.saturating_sub(self.top().map_or(0, |f| f.width())),
+                None,
+            ),
+            Some(Kind::Begin) => match self.top() {
+                Some(FrameKind::Declarative { begun: false }) => (depth.saturating_sub(1), None),
+                _ => (depth, None),
+            },
+            Some(Kind::For) if self.top() == Some(FrameKind::Configuration) => (depth, None),
+            // Such as the `is` of a subprogram after its return type
+            Some(Kind::Is) => (depth, None),
+            _ if self.in_statement => (depth + 1, None),
+            Some(Kind::Elsif | Kind::Else) => (depth.saturating_sub(1), None),
+            Some(Kind::When) if self.top() == Some(FrameKind::Case) => {
+                (depth.saturating_sub(1), None)
+            }
+            Some(Kind::Signal | Kind::Constant | Kind::Variable | Kind::Shared) => {
+                (depth, Some(Alignment::Object))
+            }
+            _ => (depth, None),
+        }
+    }
+
+    fn start_statement(&mut self) {
+        self.first = None;
+        self.in_statement = false;
+        self.labeled = false;
+        self.in_end = false;
+        self.label = None;
+        self.name = None;
+    }
+
+    fn push(&mut self, kind: FrameKind, closing: Option<&'static str>) {
+        self.frames.push(Frame {
+            kind,
+            start_line: self.start_line,
+            name: self.label.clone().or_else(|| self.name.clone()),
+            closing,
+        });
+        self.start_statement();
+    }
+
+    /// A keyword, label or designator after `end`
+    fn end_token(&mut self, kind: Kind, item: &Item) {
+        if let Some(end) = self.ends.last_mut() {
+            if matches!(
+                kind,
+                Kind::Identifier | Kind::StringLiteral | Kind::Character
+            ) {
+                end.has_name = true;
+            } else {
+                end.has_keyword = true;
+            }
+            end.last = item.range.end;
+        }
+    }
+
+    fn token(&mut self, kind: Kind, item: &Item, next: Option<Kind>, line_indent: usize) {
+        let prev = self.prev.replace(kind);
+        if !self.in_statement && self.parens.is_empty() {
+            self.start_line = item.range.start.line;
+        }
+        match kind {
+            Kind::LeftPar => {
+                self.parens.push(Paren {
+                    indent: line_indent,
+                    interface: matches!(prev, Some(Kind::Generic | Kind::Port)),
+                });
+                self.in_statement = true;
+                return;
+            }
+            Kind::RightPar => {
+                self.parens.pop();
+                return;
+            }
+            _ if !self.parens.is_empty() => return,
+            Kind::SemiColon => {
+                if self.in_end {
+                    if let Some(end) = self.ends.last_mut() {
+                        end.semicolon = Some(item.range);
+                    }
+                }
+                self.start_statement();
+                return;
+            }
+            _ if self.in_end => {
+                self.end_token(kind, item);
+                return;
+            }
+            _ => {}
+        }
+
+        let is_label = !self.labeled
+            && self.first.is_none()
+            && (kind == Kind::Identifier && next == Some(Kind::Colon)
+                || kind == Kind::Colon && prev == Some(Kind::Identifier));
+        if kind == Kind::Colon && is_label {
+            self.labeled = true;
+        } else if is_label {
+            self.label = Some(item.text.clone());
+        }
+        let is_prefix = matches!(kind, Kind::Postponed | Kind::Pure | Kind::Impure)
+            || kind == Kind::Body && prev == Some(Kind::Protected);
+        if kind == Kind::Identifier && self.first.is_none() && !is_label {
+            // The name of a component follows the keyword which started the construct
+            if let Some(frame) = self.frames.last_mut() {
+                if frame.closing == Some("component") && frame.name.is_none() {
+                    frame.name = Some(item.text.clone());
+                }
+            }
+        }
+        if self.name.is_none()
+            && matches!(kind, Kind::Identifier | Kind::StringLiteral)
+            && matches!(
+                self.first,
+                Some(
+                    Kind::Entity
+                        | Kind::Architecture
+                        | Kind::Package
+                        | Kind::Context
+                        | Kind::Configuration
+                        | Kind::Function
+                        | Kind::Procedure
+                        | Kind::Type
+                )
+            )
+        {
+            self.name = Some(item.text.clone());
+        }
+        if self.first.is_none() && !is_label && !is_prefix {
+            self.first = Some(kind);
+        }
+        self.in_statement = true;
+
+        match kind {
+            Kind::End => {
+                let frame = self.frames.pop();
+                self.ends.push(End {
+                    range: item.range,
+                    start_line: frame
+                        .as_ref()
+                        .map_or(item.range.start.line, |frame| frame.start_line),
+                    closing: frame.as_ref().and_then(|frame| frame.closing),
+                    name: frame.and_then(|frame| frame.name),
+                    has_keyword: false,
+                    has_name: false,
+                    last: item.range.end,
+                    semicolon: None,
+                });
+                self.in_end = true;
+            }
+            Kind::Is if next != Some(Kind::New) => match self.first {
+                Some(
+                    Kind::Entity
+                    | Kind::Architecture
+                    | Kind::Package
+                    | Kind::Context
+                    | Kind::Function
+                    | Kind::Procedure,
+                ) => self.push(FrameKind::Declarative { begun: false }, None),
+                Some(Kind::Process) => {
+                    self.push(FrameKind::Declarative { begun: false }, Some("process"));
+                }
+                Some(Kind::Block) => {
+                    self.push(FrameKind::Declarative { begun: false }, Some("block"));
+                }
+                Some(Kind::Case) => self.push(FrameKind::Case, Some("case")),
+                Some(Kind::Configuration) => self.push(FrameKind::Configuration, None),
+                _ => {}
+            },
+            Kind::Component if self.first == Some(Kind::Component) && !self.labeled => {
+                self.push(FrameKind::Statements, Some("component"));
+            }
+            Kind::Begin => {
+                match self.frames.last_mut() {
+                    Some(Frame {
+                        kind: FrameKind::Declarative { begun },
+                        ..
+                    }) if !*begun => *begun = true,
+                    _ => {
+                        let closing = match self.first {
+                            Some(Kind::Process) => Some("process"),
+                            Some(Kind::Block) => Some("block"),
+                            _ => None,
+                        };
+                        self.frames.push(Frame {
+                            kind: FrameKind::Statements,
+                            start_line: self.start_line,
+                            name: self.label.clone(),
+                            closing,
+                        });
+                    }
+                }
+                self.start_statement();
+            }
+            Kind::Then => {
+                if self.first == Some(Kind::If) {
+                    self.push(FrameKind::Statements, Some("if"));
+                } else {
+                    self.start_statement();
+                }
+            }
+            Kind::Else if self.first == Some(Kind::Else) => self.start_statement(),
+            Kind::Loop => self.push(FrameKind::Statements, Some("loop")),
+            Kind::Generate => match self.first {
+                Some(Kind::For | Kind::If) => {
+                    self.push(FrameKind::Declarative { begun: false }, Some("generate"));
+                }
+                Some(Kind::Case) => self.push(FrameKind::Case, Some("generate")),
+                _ => self.start_statement(),
+            },
+            Kind::Record => self.push(FrameKind::Statements, Some("record")),
+            Kind::Units => self.push(FrameKind::Statements, Some("units")),
+            Kind::Protected if next == Some(Kind::Body) => {
+                self.push(FrameKind::Statements, Some("protected body"));
+            }
+            Kind::Protected => self.push(FrameKind::Statements, Some("protected")),
+            // A block or component configuration
+            Kind::For if self.top() == Some(FrameKind::Configuration) => {
+                self.push(FrameKind::Configuration, Some("for"));
+            }
+            Kind::RightArrow
+                if self.first == Some(Kind::When) && self.top() == Some(FrameKind::Case) =>
+            {
+                self.start_statement();
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn format(code: &str, options: &FormatOptions) -> String {
+        let source = Source::inline(std::path::Path::new("file.vhd"), code);
+        format_source(&source, options).unwrap()
+    }
+
+    #[test]
+    fn indents_design_units_and_statements() {
+        let code = "
+entity ent is
+port (
+clk : in std_logic;
+   data_out:out std_logic_vector(7 downto 0)
+);
+end entity;
+
+architecture rtl of ent is
+signal   counter : natural;
+  constant max: natural := 10;
+begin
+main : process (clk)
+begin
+if rising_edge(clk) then
+case counter is
+when 0 =>
+counter <= 1;
+when others =>
+null;
+end case;
+elsif counter = max then
+counter <= 0;
+else
+counter <= counter
++ 1;
+end if;
+end process;
+
+gen : for i in 0 to 3 generate
+inst : entity work.sub
+port map (
+a => clk
+);
+end generate;
+end architecture;
+";
+        assert_eq!(
+            format(code, &FormatOptions::default()),
+            "
+entity ent is
+  port (
+    clk      : in std_logic;
+    data_out : out std_logic_vector(7 downto 0)
+  );
+end entity;
+
+architecture rtl of ent is
+  signal counter : natural;
+  constant max   : natural := 10;
+begin
+  main : process (clk)
+  begin
+    if rising_edge(clk) then
+      case counter is
+        when 0 =>
+          counter <= 1;
+        when others =>
+          null;
+      end case;
+    elsif counter = max then
+      counter <= 0;
+    else
+      counter <= counter
+        + 1;
+    end if;
+  end process;
+
+  gen : for i in 0 to 3 generate
+    inst : entity work.sub
+      port map (
+        a => clk
+      );
+  end generate;
+end architecture;
+"
+        );
+    }
+
+    #[test]
+    fn keeps_comments_and_case_of_identifiers() {
+        let code = "\
+-- Header
+PACKAGE Pkg IS   -- trailing
+    -- before the function
+  FUNCTION Get_Value    RETURN Natural;
+    /* block
+       comment */
+END PACKAGE;
+-- final
+";
+        let options = FormatOptions {
+            indent_width: 4,
+            keyword_case: KeywordCase::Lower,
+            align_declarations: true,
+        };
+        assert_eq!(
+            format(code, &options),
+            "\
+-- Header
+package Pkg is -- trailing
+    -- before the function
+    function Get_Value return Natural;
+    /* block
+       comment */
+end package;
+-- final
+"
+        );
+    }
+
+    #[test]
+    fn keyword_case_and_alignment_are_configurable() {
+        let code = "\
+package pkg is
+  constant a : natural := 0;
+  constant long_name : natural := 1;
+end package;
+";
+        let options = FormatOptions {
+            indent_width: 2,
+            keyword_case: KeywordCase::Upper,
+            align_declarations: false,
+        };
+        assert_eq!(
+            format(code, &options),
+            "\
+PACKAGE pkg IS
+  CONSTANT a : natural := 0;
+  CONSTANT long_name : natural := 1;
+END PACKAGE;
+"
+        );
+    }
+
+    fn edits(edits: Vec<TextEdit>) -> Vec<(Range, String)> {
+        edits
+            .into_iter()
+            .map(|edit| (edit.pos.range(), edit.new_text))
+            .collect()
+    }
+
+    fn on_type(code: &str, cursor: Position) -> Vec<(Range, String)> {
+        let source = Source::inline(std::path::Path::new("file.vhd"), code);
+        edits(format_on_type(&source, &FormatOptions::default(), cursor).unwrap())
+    }
+
+    fn range(start: (u32, u32), end: (u32, u32)) -> Range {
+        Range::new(Position::new(start.0, start.1), Position::new(end.0, end.1))
+    }
+
+    #[test]
+    fn formats_lines_within_range() {
+        let code = "\
+package pkg is
+constant a : natural := 0;
+constant b : natural := 0;
+end package;
+";
+        let source = Source::inline(std::path::Path::new("file.vhd"), code);
+        let edits_in_range =
+            format_range(&source, &FormatOptions::default(), range((1, 3), (2, 0))).unwrap();
+        assert_eq!(
+            edits(edits_in_range),
+            vec![(
+                range((1, 0), (1, 26)),
+                "  constant a : natural := 0;".to_owned()
+            )]
+        );
+    }
+
+    #[test]
+    fn inserts_closing_label_after_semicolon() {
+        let code = "\
+architecture rtl of ent is
+begin
+main : process
+begin
+null;
+end;
+end architecture;
+";
+        assert_eq!(
+            on_type(code, Position::new(5, 4)),
+            vec![
+                (range((2, 0), (2, 0)), "  ".to_owned()),
+                (range((3, 0), (3, 0)), "  ".to_owned()),
+                (range((4, 0), (4, 0)), "    ".to_owned()),
+                (range((5, 0), (5, 0)), "  ".to_owned()),
+                (range((5, 3), (5, 3)), " process main".to_owned()),
+            ]
+        );
+    }
+
+    #[test]
+    fn inserts_only_missing_parts_of_the_end() {
+        let code = "\
+entity ent is
+end entity;
+";
+        assert_eq!(
+            on_type(code, Position::new(1, 11)),
+            vec![(range((1, 10), (1, 10)), " ent".to_owned())]
+        );
+
+        let code = "\
+architecture rtl of ent is
+begin
+  lbl : if true generate
+  end generate lbl;
+end architecture;
+";
+        assert_eq!(on_type(code, Position::new(3, 19)), vec![]);
+    }
+
+    #[test]
+    fn reindents_construct_after_typing_end() {
+        let code = "\
+package pkg is
+  type rec_t is record
+  field : natural;
+    end
+end package;
+";
+        assert_eq!(
+            on_type(code, Position::new(3, 7)),
+            vec![
+                (range((2, 0), (2, 2)), "    ".to_owned()),
+                (range((3, 0), (3, 4)), "  ".to_owned()),
+            ]
+        );
+    }
+
+    #[test]
+    fn fails_on_code_that_cannot_be_tokenized() {
+        let source = Source::inline(
+            std::path::Path::new("file.vhd"),
+            "constant c : string := \"a",
+        );
+        assert!(format_source(&source, &FormatOptions::default()).is_err());
+    }
+}
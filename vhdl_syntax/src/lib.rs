@@ -0,0 +1,28 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this file,
+// You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) 2018, Olof Kraigher olof.kraigher@gmail.com
+
+//! The tokenizer, parser and syntax tree of VHDL without the semantic analysis
+#![allow(clippy::upper_case_acronyms)]
+// False positives with unconditional loops
+// allow for now
+#![allow(clippy::vec_init_then_push)]
+
+#[macro_use]
+pub mod ast;
+pub mod data;
+pub mod format;
+pub mod syntax;
+
+pub use crate::ast::search::ItemKind;
+pub use crate::data::{
+    select_fixes, Diagnostic, Fix, Latin1String, Message, MessageHandler, MessagePrinter,
+    MessageType, NullDiagnostics, NullMessages, Position, Range, Severity, Source, SourceFixes,
+    SrcPos, TextEdit,
+};
+pub use crate::syntax::{
+    ArgumentPosition, ClauseKind, CompletionContext, FileMetrics, ParserResult, SignalMetrics,
+    UnitMetrics, VHDLParser,
+};
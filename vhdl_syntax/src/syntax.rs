@@ -5,11 +5,12 @@
 // Copyright (c) 2019, Olof Kraigher olof.kraigher@gmail.com
 
 #[macro_use]
-mod tokens;
+pub mod tokens;
 
 mod alias_declaration;
 mod attributes;
 mod common;
+mod completion;
 mod component_declaration;
 mod concurrent_statement;
 mod configuration;
@@ -18,6 +19,7 @@ mod declarative_part;
 mod design_unit;
 mod expression;
 mod interface_declaration;
+mod metrics;
 mod names;
 mod object_declaration;
 mod parser;
@@ -28,8 +30,11 @@ mod subtype_indication;
 mod type_declaration;
 mod waveform;
 
-#[cfg(test)]
+#[cfg(any(test, feature = "test-util"))]
 pub mod test;
 
+pub use completion::{ArgumentPosition, ClauseKind, CompletionContext};
+pub use interface_declaration::{port_list_text, InterfaceDeclarationText};
+pub use metrics::{FileMetrics, SignalMetrics, UnitMetrics};
 pub use parser::{ParserResult, VHDLParser};
 pub use tokens::Symbols;
@@ -8,6 +8,7 @@ use super::tokens::{Kind, TokenStream};
 use crate::ast::Ident;
 use crate::data::Diagnostic;
 use crate::data::DiagnosticHandler;
+use crate::data::TextEdit;
 use crate::data::WithPos;
 use crate::SrcPos;
 
@@ -40,9 +41,15 @@ pub fn check_end_identifier_mismatch<T: std::fmt::Display + std::cmp::PartialEq>
         if ident.item == end_ident.item {
             return Some(end_ident.pos);
         } else {
-            diagnostics.error(
-                &end_ident.pos,
-                format!("End identifier mismatch, expected {}", ident.item),
+            diagnostics.push(
+                Diagnostic::error(
+                    &end_ident.pos,
+                    format!("End identifier mismatch, expected {}", ident.item),
+                )
+                .fix(
+                    format!("Replace with '{}'", ident.item),
+                    vec![TextEdit::replace(&end_ident.pos, ident.item.to_string())],
+                ),
             );
         }
     }
@@ -59,19 +66,28 @@ pub fn check_label_identifier_mismatch(
             if ident.item == end_ident.item {
                 return Some(end_ident.pos);
             } else {
-                diagnostics.error(
-                    &end_ident.pos,
-                    format!("End label mismatch, expected {}", ident.item),
+                diagnostics.push(
+                    Diagnostic::error(
+                        &end_ident.pos,
+                        format!("End label mismatch, expected {}", ident.item),
+                    )
+                    .fix(
+                        format!("Replace with '{}'", ident.item),
+                        vec![TextEdit::replace(&end_ident.pos, ident.item.to_string())],
+                    ),
                 );
             }
         }
     } else if let Some(end_ident) = end_ident {
-        diagnostics.error(
-            &end_ident.pos,
-            format!(
-                "End label '{}' found for unlabeled statement",
-                end_ident.item
-            ),
+        diagnostics.push(
+            Diagnostic::error(
+                &end_ident.pos,
+                format!(
+                    "End label '{}' found for unlabeled statement",
+                    end_ident.item
+                ),
+            )
+            .fix("Remove end label", vec![TextEdit::remove(&end_ident.pos)]),
         );
     }
     None
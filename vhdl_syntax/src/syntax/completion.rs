@@ -0,0 +1,396 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this file,
+// You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) 2023, Olof Kraigher olof.kraigher@gmail.com
+
+//! Find out which name is being typed at the cursor from the tokens before it
+//! Such that `use ieee.num` can be completed with names from library `ieee`
+//! and `file_open(f, "name", wr` with the literals of `file_open_kind`
+//! and `sig'ev` with the attributes applicable to `sig`
+
+use super::tokens::{Kind, Symbols, Token, Tokenizer, Value};
+use crate::data::*;
+
+/// The clause in which a name is being typed
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum ClauseKind {
+    Library,
+    Use,
+    Context,
+    /// An argument of a subprogram call
+    Argument,
+    /// The designator of an attribute name after the tick
+    Attribute,
+    /// The label of an enclosing loop after `exit` or `next`
+    LoopLabel,
+    /// The label of the loop being closed after `end loop`
+    EndLoopLabel,
+}
+
+/// The argument of a subprogram call being typed
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct ArgumentPosition {
+    /// The name of the called subprogram
+    pub subprogram: Symbol,
+    /// The index of a positional argument
+    pub index: usize,
+    /// The formal of a named argument such as `open_kind` in `file_open(f, name, open_kind => wr`
+    pub formal: Option<Symbol>,
+}
+
+/// A name being typed in a library clause, use clause, context reference, argument of a call,
+/// attribute name or loop label
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct CompletionContext {
+    pub clause: ClauseKind,
+    /// The complete names before the last dot such as `ieee` in `use ieee.num`
+    pub prefix: Vec<Symbol>,
+    /// The part of the name typed before the cursor such as `num` in `use ieee.num`
+    pub partial: String,
+    /// The call when typing an argument
+    pub argument: Option<ArgumentPosition>,
+    /// The last name of the prefix when typing an attribute such as `sig` in `sig'ev`
+    pub attribute_prefix: Option<SrcPos>,
+    /// The `exit`, `next` or `loop` keyword before a loop label
+    pub loop_keyword: Option<SrcPos>,
+}
+
+pub fn completion_context(
+    symbols: &Symbols,
+    source: &Source,
+    cursor: Position,
+) -> Option<CompletionContext> {
+    let contents = source.contents();
+    let mut tokenizer = Tokenizer::new(symbols, source, ContentReader::new(&contents));
+    let mut tokens = Vec::new();
+    loop {
+        match tokenizer.pop() {
+            Ok(Some(token)) if token.pos.start() < cursor => tokens.push(token),
+            Ok(_) => break,
+            Err(_) => {}
+        }
+    }
+
+    // The cursor may be within or at the end of the name being typed
+    let partial = match tokens.last() {
+        Some(Token {
+            kind: Kind::Identifier,
+            value: Value::Identifier(sym),
+            pos,
+            ..
+        }) if cursor <= pos.end() => {
+            let len = cursor.character.saturating_sub(pos.start().character) as usize;
+            let partial = sym.name_utf8().chars().take(len).collect();
+            tokens.pop();
+            partial
+        }
+        _ => String::new(),
+    };
+
+    if tokens.last().is_some_and(|token| token.kind == Kind::Tick) {
+        return match tokens[..tokens.len() - 1].last() {
+            Some(Token {
+                kind: Kind::Identifier,
+                value: Value::Identifier(sym),
+                pos,
+                ..
+            }) => Some(CompletionContext {
+                clause: ClauseKind::Attribute,
+                prefix: vec![sym.clone()],
+                partial,
+                argument: None,
+                attribute_prefix: Some(pos.clone()),
+                loop_keyword: None,
+            }),
+            _ => None,
+        };
+    }
+
+    let loop_label = match tokens.as_slice() {
+        [.., keyword] if matches!(keyword.kind, Kind::Exit | Kind::Next) => {
+            Some((ClauseKind::LoopLabel, keyword))
+        }
+        [.., end, keyword] if end.kind == Kind::End && keyword.kind == Kind::Loop => {
+            Some((ClauseKind::EndLoopLabel, keyword))
+        }
+        _ => None,
+    };
+    if let Some((clause, keyword)) = loop_label {
+        return Some(CompletionContext {
+            clause,
+            prefix: Vec::new(),
+            partial,
+            argument: None,
+            attribute_prefix: None,
+            loop_keyword: Some(keyword.pos.clone()),
+        });
+    }
+
+    let mut prefix = Vec::new();
+    let mut idx = tokens.len();
+    while idx >= 2 && tokens[idx - 1].kind == Kind::Dot {
+        if let Value::Identifier(ref sym) = tokens[idx - 2].value {
+            prefix.push(sym.clone());
+            idx -= 2;
+        } else {
+            return None;
+        }
+    }
+    prefix.reverse();
+
+    if prefix.is_empty() {
+        if let Some(argument) = argument_position(&tokens) {
+            return Some(CompletionContext {
+                clause: ClauseKind::Argument,
+                prefix,
+                partial,
+                argument: Some(argument),
+                attribute_prefix: None,
+                loop_keyword: None,
+            });
+        }
+    }
+
+    // Skip the earlier names of a list such as `use ieee.std_logic_1164.all, ieee.num`
+    let mut keyword = tokens[..idx].last()?.kind;
+    if keyword == Kind::Comma {
+        keyword = tokens[..idx]
+            .iter()
+            .rev()
+            .map(|token| token.kind)
+            .find(|kind| !matches!(kind, Kind::Identifier | Kind::Dot | Kind::Comma | Kind::All))?;
+    }
+
+    let clause = match keyword {
+        Kind::Library if prefix.is_empty() => ClauseKind::Library,
+        Kind::Use => ClauseKind::Use,
+        Kind::Context => ClauseKind::Context,
+        _ => return None,
+    };
+
+    Some(CompletionContext {
+        clause,
+        prefix,
+        partial,
+        argument: None,
+        attribute_prefix: None,
+        loop_keyword: None,
+    })
+}
+
+/// Find the unclosed parenthesis of a call such as `file_open(f, name, ` by going back
+/// from the last token while counting the commas between the arguments
+fn argument_position(tokens: &[Token]) -> Option<ArgumentPosition> {
+    let formal = match tokens {
+        [.., before, formal, arrow]
+            if arrow.kind == Kind::RightArrow
+                && matches!(before.kind, Kind::LeftPar | Kind::Comma) =>
+        {
+            if let Value::Identifier(ref sym) = formal.value {
+                Some(sym.clone())
+            } else {
+                None
+            }
+        }
+        _ => None,
+    };
+
+    let mut depth = 0;
+    let mut index = 0;
+    for (idx, token) in tokens.iter().enumerate().rev() {
+        match token.kind {
+            Kind::RightPar => depth += 1,
+            Kind::LeftPar if depth > 0 => depth -= 1,
+            Kind::LeftPar => {
+                let subprogram = match tokens[..idx].last() {
+                    Some(Token {
+                        kind: Kind::Identifier,
+                        value: Value::Identifier(sym),
+                        ..
+                    }) => sym.clone(),
+                    _ => return None,
+                };
+                return Some(ArgumentPosition {
+                    subprogram,
+                    index,
+                    formal,
+                });
+            }
+            Kind::Comma if depth == 0 => index += 1,
+            Kind::SemiColon
+            | Kind::Is
+            | Kind::Begin
+            | Kind::Then
+            | Kind::Else
+            | Kind::Loop
+            | Kind::Generate => return None,
+            _ => {}
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::syntax::test::Code;
+
+    fn context_at(code: &Code, cursor: Position) -> Option<(ClauseKind, Vec<String>, String)> {
+        completion_context(&code.symbols, code.source(), cursor).map(|context| {
+            (
+                context.clause,
+                context.prefix.iter().map(|sym| sym.name_utf8()).collect(),
+                context.partial,
+            )
+        })
+    }
+
+    #[test]
+    fn partial_name_in_use_clause() {
+        let code = Code::new("use ieee.num");
+        assert_eq!(
+            context_at(&code, code.s1("num").end()),
+            Some((ClauseKind::Use, vec!["ieee".to_owned()], "num".to_owned()))
+        );
+        assert_eq!(
+            context_at(&code, code.s1("nu").end()),
+            Some((ClauseKind::Use, vec!["ieee".to_owned()], "nu".to_owned()))
+        );
+        assert_eq!(
+            context_at(&code, code.s1("ie").end()),
+            Some((ClauseKind::Use, vec![], "ie".to_owned()))
+        );
+    }
+
+    #[test]
+    fn name_after_dot() {
+        let code = Code::new("use ieee.numeric_std.");
+        assert_eq!(
+            context_at(&code, code.end()),
+            Some((
+                ClauseKind::Use,
+                vec!["ieee".to_owned(), "numeric_std".to_owned()],
+                String::new()
+            ))
+        );
+    }
+
+    #[test]
+    fn later_name_of_use_clause_list() {
+        let code = Code::new("use ieee.std_logic_1164.all, ieee.math");
+        assert_eq!(
+            context_at(&code, code.end()),
+            Some((ClauseKind::Use, vec!["ieee".to_owned()], "math".to_owned()))
+        );
+    }
+
+    #[test]
+    fn library_clause_and_context_reference() {
+        let code = Code::new("library ie");
+        assert_eq!(
+            context_at(&code, code.end()),
+            Some((ClauseKind::Library, vec![], "ie".to_owned()))
+        );
+
+        let code = Code::new("context ieee.ieee_");
+        assert_eq!(
+            context_at(&code, code.end()),
+            Some((
+                ClauseKind::Context,
+                vec!["ieee".to_owned()],
+                "ieee_".to_owned()
+            ))
+        );
+    }
+
+    #[test]
+    fn no_context_outside_of_clauses() {
+        let code = Code::new("signal foo : ieee.num");
+        assert_eq!(context_at(&code, code.end()), None);
+
+        let code = Code::new("use ieee.numeric_std.all; ");
+        assert_eq!(context_at(&code, code.end()), None);
+
+        let code = Code::new("foo := (others => ");
+        assert_eq!(context_at(&code, code.end()), None);
+    }
+
+    fn argument_at(code: &Code, cursor: Position) -> Option<(String, usize, Option<String>)> {
+        completion_context(&code.symbols, code.source(), cursor)
+            .and_then(|context| context.argument)
+            .map(|argument| {
+                (
+                    argument.subprogram.name_utf8(),
+                    argument.index,
+                    argument.formal.map(|sym| sym.name_utf8()),
+                )
+            })
+    }
+
+    #[test]
+    fn positional_argument_of_call() {
+        let code = Code::new("file_open(status, f, to_string(x, y), wr");
+        let context = completion_context(&code.symbols, code.source(), code.end()).unwrap();
+        assert_eq!(context.clause, ClauseKind::Argument);
+        assert_eq!(context.partial, "wr");
+        assert_eq!(
+            argument_at(&code, code.end()),
+            Some(("file_open".to_owned(), 3, None))
+        );
+        assert_eq!(
+            argument_at(&code, code.s1("x").end()),
+            Some(("to_string".to_owned(), 0, None))
+        );
+    }
+
+    #[test]
+    fn named_argument_of_call() {
+        let code = Code::new("file_open(f, \"name\", open_kind => ");
+        assert_eq!(
+            argument_at(&code, code.end()),
+            Some(("file_open".to_owned(), 2, Some("open_kind".to_owned())))
+        );
+    }
+
+    #[test]
+    fn attribute_after_tick() {
+        let code = Code::new("foo := bar.sig'ev");
+        let context = completion_context(&code.symbols, code.source(), code.end()).unwrap();
+        assert_eq!(context.clause, ClauseKind::Attribute);
+        assert_eq!(context.partial, "ev");
+        assert_eq!(context.attribute_prefix, Some(code.s1("sig").pos()));
+
+        let code = Code::new("if sig'");
+        assert_eq!(
+            context_at(&code, code.end()),
+            Some((ClauseKind::Attribute, vec!["sig".to_owned()], String::new()))
+        );
+
+        let code = Code::new("foo := 'a");
+        assert_eq!(context_at(&code, code.end()), None);
+    }
+
+    #[test]
+    fn loop_label_after_exit_next_and_end_loop() {
+        let code = Code::new("exit lbl");
+        let context = completion_context(&code.symbols, code.source(), code.end()).unwrap();
+        assert_eq!(context.clause, ClauseKind::LoopLabel);
+        assert_eq!(context.partial, "lbl");
+        assert_eq!(context.loop_keyword, Some(code.s1("exit").pos()));
+
+        let code = Code::new("next ");
+        assert_eq!(
+            context_at(&code, code.end()),
+            Some((ClauseKind::LoopLabel, vec![], String::new()))
+        );
+
+        let code = Code::new("end loop ");
+        let context = completion_context(&code.symbols, code.source(), code.end()).unwrap();
+        assert_eq!(context.clause, ClauseKind::EndLoopLabel);
+        assert_eq!(context.loop_keyword, Some(code.s1("loop").pos()));
+
+        let code = Code::new("exit when ");
+        assert_eq!(context_at(&code, code.end()), None);
+    }
+}
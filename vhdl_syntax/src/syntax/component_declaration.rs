@@ -75,7 +75,7 @@ pub fn parse_component_declaration(
     stream: &TokenStream,
     diagnostics: &mut dyn DiagnosticHandler,
 ) -> ParseResult<ComponentDeclaration> {
-    stream.expect_kind(Component)?;
+    let start_token = stream.expect_kind(Component)?;
     let ident = WithDecl::new(stream.expect_ident()?);
     stream.pop_if_kind(Is);
 
@@ -84,13 +84,14 @@ pub fn parse_component_declaration(
     stream.expect_kind(End)?;
     stream.expect_kind(Component)?;
     let end_ident = stream.pop_optional_ident();
-    stream.expect_kind(SemiColon)?;
+    let semi_token = stream.expect_kind(SemiColon)?;
 
     Ok(ComponentDeclaration {
         end_ident_pos: check_end_identifier_mismatch(&ident.tree, end_ident, diagnostics),
         ident,
         generic_list: generic_list.unwrap_or_default(),
         port_list: port_list.unwrap_or_default(),
+        pos: start_token.pos.combine(&semi_token.pos),
     })
 }
 
@@ -107,15 +108,23 @@ mod tests {
         generic_list: Vec<InterfaceDeclaration>,
         port_list: Vec<InterfaceDeclaration>,
         end_ident_pos: Option<SrcPos>,
+        pos: SrcPos,
     ) -> ComponentDeclaration {
         ComponentDeclaration {
             ident,
             generic_list,
             port_list,
             end_ident_pos,
+            pos,
         }
     }
 
+    fn component_pos(code: &Code) -> SrcPos {
+        code.s1("component")
+            .pos()
+            .combine(&code.s1("end component;").pos())
+    }
+
     #[test]
     fn test_component() {
         let code = Code::new(
@@ -127,7 +136,13 @@ end component;
         let component = code.with_stream_no_diagnostics(parse_component_declaration);
         assert_eq!(
             component,
-            to_component(code.s1("foo").decl_ident(), vec![], vec![], None)
+            to_component(
+                code.s1("foo").decl_ident(),
+                vec![],
+                vec![],
+                None,
+                component_pos(&code)
+            )
         );
 
         let code = Code::new(
@@ -139,7 +154,13 @@ end component;
         let component = code.with_stream_no_diagnostics(parse_component_declaration);
         assert_eq!(
             component,
-            to_component(code.s1("foo").decl_ident(), vec![], vec![], None)
+            to_component(
+                code.s1("foo").decl_ident(),
+                vec![],
+                vec![],
+                None,
+                component_pos(&code)
+            )
         );
 
         let code = Code::new(
@@ -155,7 +176,8 @@ end component foo;
                 code.s1("foo").decl_ident(),
                 vec![],
                 vec![],
-                Some(code.s("foo", 2).pos())
+                Some(code.s("foo", 2).pos()),
+                code.s1("component foo is\nend component foo;").pos()
             )
         );
     }
@@ -178,7 +200,8 @@ end component;
                 code.s1("foo").decl_ident(),
                 vec![code.s1("foo : natural").generic()],
                 vec![],
-                None
+                None,
+                component_pos(&code)
             )
         );
     }
@@ -201,7 +224,8 @@ end component;
                 code.s1("foo").decl_ident(),
                 vec![],
                 vec![code.s1("foo : natural").port()],
-                None
+                None,
+                component_pos(&code)
             )
         );
     }
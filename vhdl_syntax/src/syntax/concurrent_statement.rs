@@ -6,7 +6,7 @@
 
 use super::common::check_label_identifier_mismatch;
 use super::common::ParseResult;
-use super::declarative_part::{is_declarative_part, parse_declarative_part};
+use super::declarative_part::{expect_begin, is_declarative_part, parse_declarative_part};
 use super::expression::parse_aggregate;
 use super::expression::{parse_choices, parse_expression};
 use super::interface_declaration::{parse_generic_interface_list, parse_port_interface_list};
@@ -200,7 +200,7 @@ pub fn parse_process_statement(
 
     stream.pop_if_kind(Is);
     let decl = parse_declarative_part(stream, diagnostics)?;
-    stream.expect_kind(Begin)?;
+    expect_begin(stream, diagnostics)?;
     let statements = parse_labeled_sequential_statements(stream, diagnostics)?;
     stream.expect_kind(End)?;
 
@@ -440,6 +440,7 @@ fn parse_if_generate_statement(
 ) -> ParseResult<IfGenerateStatement> {
     let mut conditionals = Vec::new();
     let else_branch;
+    let mut else_pos = None;
 
     stream.expect_kind(If)?;
     loop {
@@ -469,6 +470,7 @@ fn parse_if_generate_statement(
                 continue;
             },
             Else => {
+                else_pos = Some(end_token.pos.clone());
                 let alternative_label = expect_token!(
                     stream,
                     token,
@@ -498,6 +500,7 @@ fn parse_if_generate_statement(
             conditionals,
             else_item: else_branch,
         },
+        else_pos,
         end_label_pos: check_label_identifier_mismatch(label, end_ident, diagnostics),
     })
 }
@@ -1601,6 +1604,7 @@ end generate;",
                 }],
                 else_item: None,
             },
+            else_pos: None,
             end_label_pos: None,
         };
         let stmt = code.with_stream_no_diagnostics(parse_labeled_concurrent_statement);
@@ -1635,6 +1639,7 @@ end generate;",
                 }],
                 else_item: None,
             },
+            else_pos: None,
             end_label_pos: None,
         };
         let stmt = code.with_stream_no_diagnostics(parse_labeled_concurrent_statement);
@@ -1686,6 +1691,7 @@ end generate;",
                     end_label_pos: None,
                 }),
             },
+            else_pos: Some(code.s1("else").pos()),
             end_label_pos: None,
         };
         let stmt = code.with_stream_no_diagnostics(parse_labeled_concurrent_statement);
@@ -1745,6 +1751,7 @@ end generate;",
                     end_label_pos: None,
                 }),
             },
+            else_pos: Some(code.s1("else").pos()),
             end_label_pos: None,
         };
         let stmt = code.with_stream_no_diagnostics(parse_labeled_concurrent_statement);
@@ -1798,6 +1805,7 @@ end generate;",
                     end_label_pos: None,
                 }),
             },
+            else_pos: Some(code.s1("else").pos()),
             end_label_pos: None,
         };
         let (stmt, diagnostics) = code.with_stream_diagnostics(parse_labeled_concurrent_statement);
@@ -1815,8 +1823,12 @@ end generate;",
                 Diagnostic::error(
                     code.s1("alt2"),
                     "End label 'alt2' found for unlabeled statement"
-                ),
-                Diagnostic::error(code.s1("alt4"), "End label mismatch, expected alt3")
+                )
+                .fix("Remove end label", vec![TextEdit::remove(code.s1("alt2"))]),
+                Diagnostic::error(code.s1("alt4"), "End label mismatch, expected alt3").fix(
+                    "Replace with 'alt3'",
+                    vec![TextEdit::replace(code.s1("alt4"), "alt3")]
+                )
             ]
         );
     }
@@ -1861,6 +1873,7 @@ end generate;",
                     end_label_pos: Some(code.s("alt3", 2).pos()),
                 }),
             },
+            else_pos: Some(code.s1("else").pos()),
             end_label_pos: None,
         };
         let stmt = code.with_stream_no_diagnostics(parse_labeled_concurrent_statement);
@@ -263,10 +263,13 @@ end context ident2;
         let (context, diagnostics) = code.with_stream_diagnostics(parse_context);
         assert_eq!(
             diagnostics,
-            vec![Diagnostic::error(
-                code.s1("ident2"),
-                "End identifier mismatch, expected ident"
-            )]
+            vec![
+                Diagnostic::error(code.s1("ident2"), "End identifier mismatch, expected ident")
+                    .fix(
+                        "Replace with 'ident'",
+                        vec![TextEdit::replace(code.s1("ident2"), "ident")]
+                    )
+            ]
         );
         assert_eq!(
             context,
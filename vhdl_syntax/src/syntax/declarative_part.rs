@@ -64,6 +64,37 @@ fn check_declarative_part(token: &Token, may_end: bool, may_begin: bool) -> Pars
     }
 }
 
+/// Tokens which cannot start a declaration but start a statement, such as when
+/// the `begin` keyword after a declarative part is missing while editing
+fn is_statement_start(stream: &TokenStream, kind: Kind) -> bool {
+    match kind {
+        Process | Assert | Postponed | With | If | Case | Loop | While | Wait | Null | Return
+        | Report | Exit | Next => true,
+        Identifier => {
+            stream.nth_kind_is(1, Colon)
+                || stream.nth_kind_is(1, LTE)
+                || stream.nth_kind_is(1, ColonEq)
+        }
+        _ => false,
+    }
+}
+
+/// Expect the `begin` keyword after a declarative part
+/// A missing `begin` is reported but parsing continues with the statements
+/// such that the declarations are not lost
+pub fn expect_begin(
+    stream: &TokenStream,
+    diagnostics: &mut dyn DiagnosticHandler,
+) -> ParseResult<()> {
+    let token = stream.peek_expect()?;
+    if token.kind == Begin {
+        stream.skip();
+    } else {
+        diagnostics.push(kinds_error(stream.pos_before(token), &[Begin]));
+    }
+    Ok(())
+}
+
 pub fn parse_declarative_part(
     stream: &TokenStream,
     diagnostics: &mut dyn DiagnosticHandler,
@@ -97,6 +128,7 @@ pub fn parse_declarative_part(
     while let Some(token) = stream.peek() {
         match token.kind {
             Begin | End => break,
+            kind if is_statement_start(stream, kind) => break,
             Type | Subtype | Component | Impure | Pure | Function | Procedure | Package | For => {
                 let decl = match token.kind {
                     Type | Subtype => {
@@ -228,7 +260,9 @@ constant x: natural := 5;
                 class: ObjectClass::Constant,
                 ident: code.s1("x").decl_ident(),
                 subtype_indication: code.s1("natural").subtype_indication(),
-                expression: Some(code.s1("5").expr())
+                signal_kind: None,
+                expression: Some(code.s1("5").expr()),
+                pos: code.s1("constant x: natural := 5;").pos(),
             })])
         );
 
@@ -14,7 +14,7 @@ use super::configuration::parse_configuration_declaration;
 use super::context::{
     parse_context, parse_library_clause, parse_use_clause, DeclarationOrReference,
 };
-use super::declarative_part::{parse_declarative_part, parse_package_instantiation};
+use super::declarative_part::{expect_begin, parse_declarative_part, parse_package_instantiation};
 use super::interface_declaration::parse_generic_interface_list;
 use crate::ast::*;
 use crate::data::*;
@@ -67,7 +67,7 @@ pub fn parse_architecture_body(
     stream.expect_kind(Is)?;
 
     let decl = parse_declarative_part(stream, diagnostics)?;
-    stream.expect_kind(Begin)?;
+    expect_begin(stream, diagnostics)?;
 
     let statements = parse_labeled_concurrent_statements(stream, diagnostics)?;
     stream.expect_kind(End)?;
@@ -164,8 +164,17 @@ pub fn parse_design_file(
 ) -> ParseResult<DesignFile> {
     let mut context_clause = vec![];
     let mut design_units = vec![];
+    let mut unit_spans = vec![];
+    let mut unit_fingerprints = vec![];
+    let mut unit_start = None;
+    let mut unit_start_state = stream.state();
 
     while let Some(token) = stream.peek() {
+        if context_clause.is_empty() {
+            unit_start = Some(token.pos.clone());
+            unit_start_state = stream.state();
+        }
+
         try_init_token_kind!(
             token,
             Library => {
@@ -255,6 +264,13 @@ pub fn parse_design_file(
                 }
             }
         );
+
+        if design_units.len() > unit_spans.len() {
+            if let (Some(start), Some(end)) = (unit_start.take(), stream.last()) {
+                unit_spans.push(start.combine_into(&end.pos));
+                unit_fingerprints.push(stream.fingerprint_since(unit_start_state));
+            }
+        }
     }
 
     for context_item in context_clause {
@@ -264,7 +280,11 @@ pub fn parse_design_file(
         ));
     }
 
-    Ok(DesignFile { design_units })
+    Ok(DesignFile {
+        design_units,
+        unit_spans,
+        unit_fingerprints,
+    })
 }
 
 #[cfg(test)]
@@ -272,7 +292,7 @@ mod tests {
     use super::*;
 
     use crate::data::Diagnostic;
-    use crate::syntax::test::{check_diagnostics, check_no_diagnostics, Code};
+    use crate::syntax::test::{check_diagnostics, check_no_diagnostics, Code, CodeBuilder};
 
     fn parse_str(code: &str) -> (Code, DesignFile, Vec<Diagnostic>) {
         let code = Code::new(code);
@@ -294,6 +314,57 @@ mod tests {
         }
     }
 
+    #[test]
+    fn fingerprints_ignore_whitespace_and_comments() {
+        // Symbols are shared such that the same identifiers give the same fingerprint
+        let builder = CodeBuilder::new();
+        let fingerprints = |code: &str| {
+            let code = builder.code(code);
+            let mut diagnostics = vec![];
+            let design_file =
+                code.with_stream(|stream| parse_design_file(stream, &mut diagnostics));
+            check_no_diagnostics(&diagnostics);
+            design_file.unit_fingerprints
+        };
+
+        let original = fingerprints(
+            "
+entity ent is
+end entity;
+
+architecture a of ent is
+begin
+end architecture;
+",
+        );
+        assert_eq!(original.len(), 2);
+        assert_ne!(original[0], original[1]);
+
+        let reformatted = fingerprints(
+            "
+-- A comment
+entity ent is end entity;
+architecture a of ent is
+begin -- Another comment
+end architecture;
+",
+        );
+        assert_eq!(original, reformatted);
+
+        let changed = fingerprints(
+            "
+entity ent is
+end entity;
+
+architecture b of ent is
+begin
+end architecture;
+",
+        );
+        assert_eq!(original[0], changed[0]);
+        assert_ne!(original[1], changed[1]);
+    }
+
     #[test]
     fn parse_empty() {
         let (_, design_file) = parse_ok("");
@@ -583,6 +654,31 @@ end;
         );
     }
 
+    #[test]
+    fn parse_architecture_body_without_begin() {
+        let code = Code::new(
+            "\
+architecture arch_name of myent is
+  signal foo : natural;
+  lbl: foo <= 0;
+end architecture;
+",
+        );
+        let (arch, diagnostics) = code.with_stream_diagnostics(super::parse_architecture_body);
+        assert_eq!(
+            diagnostics,
+            vec![Diagnostic::error(
+                code.s1("foo : natural;").s1(";").pos().pos_at_end(),
+                "Expected 'begin'"
+            )]
+        );
+        assert_eq!(
+            arch.decl,
+            vec![code.s1("signal foo : natural;").declarative_part()[0].clone()]
+        );
+        assert_eq!(arch.statements.len(), 1);
+    }
+
     #[test]
     fn test_package_declaration() {
         let code = Code::new(
@@ -688,11 +784,50 @@ end entity;
                         statements: vec![],
                         end_ident_pos: None
                     }
-                ))]
+                ))],
+                unit_spans: vec![code
+                    .s1("library lib;")
+                    .pos()
+                    .combine(&code.s1("end entity;"))],
+                unit_fingerprints: design_file.unit_fingerprints.clone(),
             }
         );
     }
 
+    #[test]
+    fn design_unit_spans() {
+        let (code, design_file) = parse_ok(
+            "
+package pkg is
+end package;
+
+library ieee;
+use ieee.std_logic_1164.all;
+
+entity ent is
+end entity;
+
+architecture a of ent is
+begin
+end architecture;
+",
+        );
+        assert_eq!(
+            design_file.unit_spans,
+            vec![
+                code.s1("package pkg is")
+                    .pos()
+                    .combine(&code.s1("end package;")),
+                code.s1("library ieee;")
+                    .pos()
+                    .combine(&code.s1("end entity;")),
+                code.s1("architecture a")
+                    .pos()
+                    .combine(&code.s1("end architecture;")),
+            ]
+        );
+    }
+
     #[test]
     fn warning_on_orphan_context_clause() {
         let code = Code::new(
@@ -723,7 +858,9 @@ context lib.ctx;
         assert_eq!(
             design_file,
             DesignFile {
-                design_units: vec![]
+                design_units: vec![],
+                unit_spans: vec![],
+                unit_fingerprints: vec![],
             }
         );
     }
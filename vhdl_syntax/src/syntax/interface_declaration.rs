@@ -114,6 +114,11 @@ fn parse_interface_object_declaration(
     };
 
     let subtype = parse_subtype_indication(stream)?;
+    let signal_kind = if object_class == ObjectClass::Signal && stream.skip_if_kind(Bus) {
+        Some(SignalKind::Bus)
+    } else {
+        None
+    };
     let expr = parse_optional_assignment(stream)?;
 
     // @TODO maybe move this to a semantic check?
@@ -152,6 +157,7 @@ fn parse_interface_object_declaration(
                 class: object_class,
                 ident: ident.into(),
                 subtype_indication: subtype.clone(),
+                signal_kind,
                 expression: expr.clone(),
             })
         })
@@ -355,7 +361,152 @@ pub fn parse_parameter_interface_list(
     parse_interface_list(stream, diagnostics, InterfaceType::Parameter)
 }
 
-#[cfg(test)]
+/// The source text of a declaration of an interface list including its comments
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct InterfaceDeclarationText {
+    /// The comments on the lines before the declaration
+    pub leading_comments: Vec<String>,
+    /// The text of the declaration without the semicolon such as `clk, rst : in bit`
+    pub text: String,
+    /// The identifiers such as `clk` and `rst`
+    pub idents: Vec<String>,
+    /// The text from the colon to the end of the declaration such as `: in bit`
+    pub subtype: String,
+    /// The comments after the declaration on the same line
+    pub trailing_comments: Vec<String>,
+}
+
+/// The source text of the parenthesized port list of an entity
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct PortListText {
+    /// From the opening to the closing parenthesis
+    pub pos: SrcPos,
+    /// The comment after the opening parenthesis on the same line
+    pub opening_comment: Option<String>,
+    pub declarations: Vec<InterfaceDeclarationText>,
+    /// The comments after the last declaration
+    pub closing_comments: Vec<String>,
+}
+
+/// The source text of the port list of the entity starting at the position
+pub fn port_list_text(
+    symbols: &Symbols,
+    source: &Source,
+    entity_start: Position,
+) -> Option<PortListText> {
+    let contents = source.contents();
+    let mut tokenizer = Tokenizer::new(symbols, source, ContentReader::new(&contents));
+    let mut next_token = || loop {
+        match tokenizer.pop() {
+            Ok(token) => return token,
+            Err(_) => continue,
+        }
+    };
+    let comment_text = |comment: &Comment| contents.text_in(comment.range);
+    let leading_comments = |token: &Token| -> Vec<String> {
+        token
+            .comments
+            .iter()
+            .flat_map(|comments| comments.leading.iter().map(comment_text))
+            .collect()
+    };
+    let trailing_comment = |token: &Token| -> Option<String> {
+        token
+            .comments
+            .as_ref()
+            .and_then(|comments| comments.trailing.as_ref().map(comment_text))
+    };
+
+    let port = loop {
+        let token = next_token()?;
+        if token.pos.start() >= entity_start {
+            match token.kind {
+                Port => break token,
+                Begin | End => return None,
+                _ => {}
+            }
+        }
+    };
+
+    let left_par = next_token()?;
+    if left_par.kind != LeftPar || left_par.pos.start() < port.pos.end() {
+        return None;
+    }
+
+    let mut declarations = Vec::new();
+    // The tokens of the current declaration
+    let mut tokens: Vec<Token> = Vec::new();
+    let mut depth = 1;
+    loop {
+        let token = next_token()?;
+        match token.kind {
+            LeftPar => depth += 1,
+            RightPar if depth == 1 => {
+                if let Some(declaration) = declaration_text(&contents, &tokens, None) {
+                    declarations.push(declaration);
+                }
+                return Some(PortListText {
+                    pos: left_par.pos.combine(&token.pos),
+                    opening_comment: trailing_comment(&left_par),
+                    declarations,
+                    closing_comments: leading_comments(&token),
+                });
+            }
+            RightPar => depth -= 1,
+            SemiColon if depth == 1 => {
+                declarations.push(declaration_text(&contents, &tokens, Some(&token))?);
+                tokens.clear();
+                continue;
+            }
+            _ => {}
+        }
+        tokens.push(token);
+    }
+}
+
+/// The text of a declaration from its tokens and the semicolon that ends it
+fn declaration_text(
+    contents: &Contents,
+    tokens: &[Token],
+    semicolon: Option<&Token>,
+) -> Option<InterfaceDeclarationText> {
+    let comment_text = |comment: &Comment| contents.text_in(comment.range);
+    let first = tokens.first()?;
+    let last = tokens.last()?;
+    let colon = tokens.iter().find(|token| token.kind == Colon)?;
+
+    let mut idents = Vec::new();
+    for token in tokens.iter() {
+        if token.pos.start() >= colon.pos.start() {
+            break;
+        }
+        if token.kind == Identifier {
+            idents.push(contents.text_in(token.pos.range()));
+        }
+    }
+
+    Some(InterfaceDeclarationText {
+        leading_comments: first
+            .comments
+            .iter()
+            .flat_map(|comments| comments.leading.iter().map(comment_text))
+            .collect(),
+        text: contents.text_in(crate::data::Range::new(first.pos.start(), last.pos.end())),
+        idents,
+        subtype: contents.text_in(crate::data::Range::new(colon.pos.start(), last.pos.end())),
+        trailing_comments: std::iter::once(last)
+            .chain(semicolon)
+            .filter_map(|token| {
+                token
+                    .comments
+                    .as_ref()
+                    .and_then(|comments| comments.trailing.as_ref().map(comment_text))
+            })
+            .collect(),
+    })
+}
+
+#[cfg(any(test, feature = "test-util"))]
 fn parse_one_interface_declaration(
     stream: &TokenStream,
     list_type: InterfaceType,
@@ -369,17 +520,17 @@ fn parse_one_interface_declaration(
     result
 }
 
-#[cfg(test)]
+#[cfg(any(test, feature = "test-util"))]
 pub fn parse_parameter(stream: &TokenStream) -> ParseResult<InterfaceDeclaration> {
     parse_one_interface_declaration(stream, InterfaceType::Parameter)
 }
 
-#[cfg(test)]
+#[cfg(any(test, feature = "test-util"))]
 pub fn parse_port(stream: &TokenStream) -> ParseResult<InterfaceDeclaration> {
     parse_one_interface_declaration(stream, InterfaceType::Port)
 }
 
-#[cfg(test)]
+#[cfg(any(test, feature = "test-util"))]
 pub fn parse_generic(stream: &TokenStream) -> ParseResult<InterfaceDeclaration> {
     parse_one_interface_declaration(stream, InterfaceType::Generic)
 }
@@ -402,6 +553,7 @@ mod tests {
                     class: ObjectClass::Constant,
                     ident: code.s1("foo").decl_ident(),
                     subtype_indication: code.s1("natural").subtype_indication(),
+                    signal_kind: None,
                     expression: None
                 }),
                 InterfaceDeclaration::Object(InterfaceObjectDeclaration {
@@ -410,6 +562,7 @@ mod tests {
                     class: ObjectClass::Constant,
                     ident: code.s1("bar").decl_ident(),
                     subtype_indication: code.s1("natural").subtype_indication(),
+                    signal_kind: None,
                     expression: None
                 })
             ]
@@ -427,6 +580,7 @@ mod tests {
                 class: ObjectClass::Constant,
                 ident: code.s1("foo").decl_ident(),
                 subtype_indication: code.s1("std_logic").subtype_indication(),
+                signal_kind: None,
                 expression: None
             })
         );
@@ -479,6 +633,7 @@ mod tests {
                 class: ObjectClass::Signal,
                 ident: code.s1("foo").decl_ident(),
                 subtype_indication: code.s1("std_logic").subtype_indication(),
+                signal_kind: None,
                 expression: None
             })
         );
@@ -547,6 +702,7 @@ mod tests {
                 class: ObjectClass::Constant,
                 ident: code.s1("foo").decl_ident(),
                 subtype_indication: code.s1("std_logic").subtype_indication(),
+                signal_kind: None,
                 expression: None
             })
         );
@@ -563,11 +719,29 @@ mod tests {
                 class: ObjectClass::Signal,
                 ident: code.s1("foo").decl_ident(),
                 subtype_indication: code.s1("std_logic").subtype_indication(),
+                signal_kind: None,
                 expression: None
             })
         );
     }
 
+    #[test]
+    fn parses_port_with_bus_signal_kind() {
+        let code = Code::new("foo : out std_logic bus := '0'");
+        assert_eq!(
+            code.with_stream(parse_port),
+            InterfaceDeclaration::Object(InterfaceObjectDeclaration {
+                list_type: InterfaceType::Port,
+                mode: Mode::Out,
+                class: ObjectClass::Signal,
+                ident: code.s1("foo").decl_ident(),
+                subtype_indication: code.s1("std_logic").subtype_indication(),
+                signal_kind: Some(SignalKind::Bus),
+                expression: Some(code.s1("'0'").expr())
+            })
+        );
+    }
+
     #[test]
     fn parse_generic_non_in_mode_error() {
         let code = Code::new("foo : out boolean");
@@ -843,4 +1017,61 @@ package foo is new lib.pkg
             })
         );
     }
+
+    #[test]
+    fn finds_port_list_text() {
+        let code = Code::new(
+            "\
+entity ent is
+  generic (width : natural := 8);
+  port ( -- The ports
+    -- Clock and reset
+    clk, rst : in bit;
+    data : out bit_vector(width-1 downto 0) -- Data
+    -- No more ports
+  );
+end entity;
+",
+        );
+        let list = port_list_text(&code.symbols, code.source(), code.start()).unwrap();
+        assert_eq!(
+            list.pos,
+            code.s1("( -- The")
+                .s1("(")
+                .pos()
+                .combine(&code.s1("  );").s1(")"))
+        );
+        assert_eq!(list.opening_comment, Some("-- The ports".to_owned()));
+        assert_eq!(
+            list.declarations,
+            vec![
+                InterfaceDeclarationText {
+                    leading_comments: vec!["-- Clock and reset".to_owned()],
+                    text: "clk, rst : in bit".to_owned(),
+                    idents: vec!["clk".to_owned(), "rst".to_owned()],
+                    subtype: ": in bit".to_owned(),
+                    trailing_comments: vec![],
+                },
+                InterfaceDeclarationText {
+                    leading_comments: vec![],
+                    text: "data : out bit_vector(width-1 downto 0)".to_owned(),
+                    idents: vec!["data".to_owned()],
+                    subtype: ": out bit_vector(width-1 downto 0)".to_owned(),
+                    trailing_comments: vec!["-- Data".to_owned()],
+                }
+            ]
+        );
+        assert_eq!(list.closing_comments, vec!["-- No more ports".to_owned()]);
+
+        let code = Code::new(
+            "\
+entity ent is
+end entity;
+",
+        );
+        assert_eq!(
+            port_list_text(&code.symbols, code.source(), code.start()),
+            None
+        );
+    }
 }
@@ -0,0 +1,505 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this file,
+// You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) 2023, Olof Kraigher olof.kraigher@gmail.com
+
+//! Size metrics of source files and their design units such as lines of code and comments
+//! Lines are classified from the tokens and statements are counted in the syntax tree
+
+use super::tokens::{Symbols, Tokenizer};
+use crate::ast::*;
+use crate::data::*;
+use std::path::PathBuf;
+
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct FileMetrics {
+    pub file_name: PathBuf,
+    pub lines: usize,
+    /// Lines with at least one token, a line with both code and a comment is counted as code
+    pub code_lines: usize,
+    /// Lines with only comments
+    pub comment_lines: usize,
+    pub blank_lines: usize,
+    pub units: Vec<UnitMetrics>,
+    /// The file contains test bench code, set by the project from the kind of the file
+    pub testbench: bool,
+}
+
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct UnitMetrics {
+    /// The kind of design unit such as `entity` or `package body`
+    pub kind: &'static str,
+    /// The name of the design unit, architectures are named as `rtl of ent`
+    pub name: String,
+    /// The lines of the design unit including its context clause
+    pub lines: usize,
+    pub code_lines: usize,
+    /// The number of concurrent and sequential statements including those of subprograms
+    pub statements: usize,
+    /// The number of concurrent and sequential assertions
+    pub assertions: usize,
+    /// The number of report statements
+    pub reports: usize,
+    pub generics: usize,
+    pub ports: usize,
+    /// The drivers and loads of the signals used within an architecture,
+    /// set by the project after analysis
+    pub signals: Vec<SignalMetrics>,
+}
+
+/// The number of processes, concurrent statements and instances that drive and read a signal
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct SignalMetrics {
+    pub name: String,
+    pub drivers: usize,
+    pub loads: usize,
+}
+
+impl FileMetrics {
+    /// The share of the non-blank lines that only contain comments
+    pub fn comment_ratio(&self) -> f64 {
+        let non_blank = self.code_lines + self.comment_lines;
+        if non_blank == 0 {
+            0.0
+        } else {
+            self.comment_lines as f64 / non_blank as f64
+        }
+    }
+
+    /// The units of a given kind such as `entity`
+    pub fn units_of_kind<'a>(&'a self, kind: &'a str) -> impl Iterator<Item = &'a UnitMetrics> {
+        self.units.iter().filter(move |unit| unit.kind == kind)
+    }
+
+    /// The architectures of test bench code which check nothing since they have no assertions
+    pub fn unchecked_architectures(&self) -> impl Iterator<Item = &UnitMetrics> {
+        self.units_of_kind("architecture")
+            .filter(|unit| self.testbench && unit.assertions == 0)
+    }
+
+    pub fn to_json(&self) -> String {
+        let units: Vec<_> = self.units.iter().map(|unit| unit.to_json()).collect();
+        format!(
+            "{{\"file\": {}, \"lines\": {}, \"code_lines\": {}, \"comment_lines\": {}, \
+             \"blank_lines\": {}, \"comment_ratio\": {:.3}, \"testbench\": {}, \"units\": [{}]}}",
+            quoted(&self.file_name.to_string_lossy()),
+            self.lines,
+            self.code_lines,
+            self.comment_lines,
+            self.blank_lines,
+            self.comment_ratio(),
+            self.testbench,
+            units.join(", ")
+        )
+    }
+}
+
+impl UnitMetrics {
+    pub fn to_json(&self) -> String {
+        let signals = if self.kind == "architecture" {
+            let signals: Vec<_> = self
+                .signals
+                .iter()
+                .map(|signal| {
+                    format!(
+                        "{{\"name\": {}, \"drivers\": {}, \"loads\": {}}}",
+                        quoted(&signal.name),
+                        signal.drivers,
+                        signal.loads
+                    )
+                })
+                .collect();
+            format!(", \"signals\": [{}]", signals.join(", "))
+        } else {
+            String::new()
+        };
+        format!(
+            "{{\"kind\": {}, \"name\": {}, \"lines\": {}, \"code_lines\": {}, \
+             \"statements\": {}, \"assertions\": {}, \"reports\": {}, \"generics\": {}, \
+             \"ports\": {}{signals}}}",
+            quoted(self.kind),
+            quoted(&self.name),
+            self.lines,
+            self.code_lines,
+            self.statements,
+            self.assertions,
+            self.reports,
+            self.generics,
+            self.ports
+        )
+    }
+}
+
+pub fn file_metrics(symbols: &Symbols, source: &Source, design_file: &DesignFile) -> FileMetrics {
+    let contents = source.contents();
+    let num_lines = contents.num_lines();
+    let mut is_code = vec![false; num_lines];
+    let mut is_comment = vec![false; num_lines];
+
+    let mut tokenizer = Tokenizer::new(symbols, source, ContentReader::new(&contents));
+    loop {
+        match tokenizer.pop() {
+            Ok(Some(token)) => {
+                mark_lines(&mut is_code, &token.pos.range());
+                if let Some(comments) = token.comments {
+                    for comment in comments.leading.iter().chain(comments.trailing.iter()) {
+                        mark_lines(&mut is_comment, &comment.range);
+                    }
+                }
+            }
+            Ok(None) => break,
+            Err(_) => {}
+        }
+    }
+    for comment in tokenizer.get_final_comments().unwrap_or_default() {
+        mark_lines(&mut is_comment, &comment.range);
+    }
+
+    let code_lines = is_code.iter().filter(|code| **code).count();
+    let comment_lines = is_code
+        .iter()
+        .zip(is_comment.iter())
+        .filter(|(code, comment)| !**code && **comment)
+        .count();
+
+    let units = design_file
+        .design_units
+        .iter()
+        .zip(design_file.unit_spans.iter())
+        .map(|(unit, span)| {
+            let range = span.range();
+            let lines = range.start.line as usize..=range.end.line as usize;
+            let (kind, name) = unit_kind_and_name(unit);
+            let (generics, ports) = interface_counts(unit);
+            let mut counts = StatementCounts::default();
+            unit_statements(unit, &mut counts);
+
+            UnitMetrics {
+                kind,
+                name,
+                lines: lines.clone().count(),
+                code_lines: is_code
+                    .get(lines)
+                    .map(|lines| lines.iter().filter(|code| **code).count())
+                    .unwrap_or_default(),
+                statements: counts.statements,
+                assertions: counts.assertions,
+                reports: counts.reports,
+                generics,
+                ports,
+                signals: Vec::new(),
+            }
+        })
+        .collect();
+
+    FileMetrics {
+        file_name: source.file_name().to_owned(),
+        lines: num_lines,
+        code_lines,
+        comment_lines,
+        blank_lines: num_lines - code_lines - comment_lines,
+        units,
+        testbench: false,
+    }
+}
+
+fn mark_lines(lines: &mut [bool], range: &crate::data::Range) {
+    for line in range.start.line..=range.end.line {
+        if let Some(line) = lines.get_mut(line as usize) {
+            *line = true;
+        }
+    }
+}
+
+fn unit_kind_and_name(unit: &AnyDesignUnit) -> (&'static str, String) {
+    match unit {
+        AnyDesignUnit::Primary(primary) => {
+            let kind = match primary {
+                AnyPrimaryUnit::Entity(..) => "entity",
+                AnyPrimaryUnit::Configuration(..) => "configuration",
+                AnyPrimaryUnit::Package(..) => "package",
+                AnyPrimaryUnit::PackageInstance(..) => "package instance",
+                AnyPrimaryUnit::Context(..) => "context",
+            };
+            (kind, primary.name().to_string())
+        }
+        AnyDesignUnit::Secondary(AnySecondaryUnit::Architecture(unit)) => (
+            "architecture",
+            format!("{} of {}", unit.name(), unit.primary_name()),
+        ),
+        AnyDesignUnit::Secondary(AnySecondaryUnit::PackageBody(unit)) => {
+            ("package body", unit.name().to_string())
+        }
+    }
+}
+
+fn interface_counts(unit: &AnyDesignUnit) -> (usize, usize) {
+    let len = |list: &Option<Vec<InterfaceDeclaration>>| list.as_ref().map_or(0, Vec::len);
+    match unit {
+        AnyDesignUnit::Primary(AnyPrimaryUnit::Entity(entity)) => {
+            (len(&entity.generic_clause), len(&entity.port_clause))
+        }
+        AnyDesignUnit::Primary(AnyPrimaryUnit::Package(package)) => {
+            (len(&package.generic_clause), 0)
+        }
+        _ => (0, 0),
+    }
+}
+
+#[derive(Default)]
+struct StatementCounts {
+    statements: usize,
+    assertions: usize,
+    reports: usize,
+}
+
+fn unit_statements(unit: &AnyDesignUnit, counts: &mut StatementCounts) {
+    match unit {
+        AnyDesignUnit::Primary(AnyPrimaryUnit::Entity(entity)) => {
+            declaration_statements(&entity.decl, counts);
+            concurrent_statements(&entity.statements, counts);
+        }
+        AnyDesignUnit::Primary(AnyPrimaryUnit::Package(package)) => {
+            declaration_statements(&package.decl, counts);
+        }
+        AnyDesignUnit::Secondary(AnySecondaryUnit::Architecture(architecture)) => {
+            declaration_statements(&architecture.decl, counts);
+            concurrent_statements(&architecture.statements, counts);
+        }
+        AnyDesignUnit::Secondary(AnySecondaryUnit::PackageBody(body)) => {
+            declaration_statements(&body.decl, counts);
+        }
+        AnyDesignUnit::Primary(
+            AnyPrimaryUnit::PackageInstance(..)
+            | AnyPrimaryUnit::Configuration(..)
+            | AnyPrimaryUnit::Context(..),
+        ) => {}
+    }
+}
+
+/// The statements of the subprogram bodies among the declarations
+fn declaration_statements(decls: &[Declaration], counts: &mut StatementCounts) {
+    for decl in decls.iter() {
+        match decl {
+            Declaration::SubprogramBody(body) => {
+                declaration_statements(&body.declarations, counts);
+                sequential_statements(&body.statements, counts);
+            }
+            Declaration::Type(TypeDeclaration {
+                def: TypeDefinition::ProtectedBody(body),
+                ..
+            }) => declaration_statements(&body.decl, counts),
+            _ => {}
+        }
+    }
+}
+
+fn concurrent_statements(statements: &[LabeledConcurrentStatement], counts: &mut StatementCounts) {
+    for statement in statements.iter() {
+        counts.statements += 1;
+        match statement.statement.item {
+            ConcurrentStatement::Process(ref process) => {
+                declaration_statements(&process.decl, counts);
+                sequential_statements(&process.statements, counts);
+            }
+            ConcurrentStatement::Block(ref block) => {
+                declaration_statements(&block.decl, counts);
+                concurrent_statements(&block.statements, counts);
+            }
+            ConcurrentStatement::ForGenerate(ref gen) => generate_statements(&gen.body, counts),
+            ConcurrentStatement::IfGenerate(ref gen) => {
+                for body in gen
+                    .conds
+                    .conditionals
+                    .iter()
+                    .map(|cond| &cond.item)
+                    .chain(gen.conds.else_item.iter())
+                {
+                    generate_statements(body, counts);
+                }
+            }
+            ConcurrentStatement::CaseGenerate(ref gen) => {
+                for alternative in gen.sels.alternatives.iter() {
+                    generate_statements(&alternative.item, counts);
+                }
+            }
+            ConcurrentStatement::Assert(..) => counts.assertions += 1,
+            ConcurrentStatement::ProcedureCall(..)
+            | ConcurrentStatement::Assignment(..)
+            | ConcurrentStatement::Instance(..) => {}
+        }
+    }
+}
+
+fn generate_statements(body: &GenerateBody, counts: &mut StatementCounts) {
+    if let Some(ref decl) = body.decl {
+        declaration_statements(decl, counts);
+    }
+    concurrent_statements(&body.statements, counts);
+}
+
+fn sequential_statements(statements: &[LabeledSequentialStatement], counts: &mut StatementCounts) {
+    for statement in statements.iter() {
+        counts.statements += 1;
+        match statement.statement.item {
+            SequentialStatement::If(ref ifstmt) => {
+                for statements in ifstmt
+                    .conds
+                    .conditionals
+                    .iter()
+                    .map(|cond| &cond.item)
+                    .chain(ifstmt.conds.else_item.iter())
+                {
+                    sequential_statements(statements, counts);
+                }
+            }
+            SequentialStatement::Case(ref case_stmt) => {
+                for alternative in case_stmt.alternatives.iter() {
+                    sequential_statements(&alternative.item, counts);
+                }
+            }
+            SequentialStatement::Loop(ref loop_stmt) => {
+                sequential_statements(&loop_stmt.statements, counts);
+            }
+            SequentialStatement::Assert(..) => counts.assertions += 1,
+            SequentialStatement::Report(..) => counts.reports += 1,
+            _ => {}
+        }
+    }
+}
+
+fn quoted(name: &str) -> String {
+    format!("\"{}\"", name.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::syntax::test::Code;
+
+    fn metrics(code: &Code) -> FileMetrics {
+        file_metrics(&code.symbols, code.source(), &code.design_file())
+    }
+
+    #[test]
+    fn counts_code_comment_and_blank_lines() {
+        let code = Code::new(
+            "\
+-- A comment
+library ieee; -- Trailing comment
+
+/* A multi line
+   comment */
+entity ent is
+end entity;
+-- Final comment",
+        );
+        let metrics = metrics(&code);
+        assert_eq!(metrics.lines, 8);
+        assert_eq!(metrics.code_lines, 3);
+        assert_eq!(metrics.comment_lines, 4);
+        assert_eq!(metrics.blank_lines, 1);
+        assert_eq!(metrics.comment_ratio(), 4.0 / 7.0);
+    }
+
+    #[test]
+    fn counts_unit_sizes_and_statements() {
+        let code = Code::new(
+            "\
+entity ent is
+  generic (width : natural);
+  port (clk : in bit; q : out bit);
+end entity;
+
+architecture rtl of ent is
+  function f return bit is
+  begin
+    return '0';
+  end function;
+begin
+  main : process (clk)
+  begin
+    if clk = '1' then
+      q <= f;
+    else
+      report \"low\";
+    end if;
+  end process;
+
+  gen : for i in 0 to 1 generate
+    assert true;
+  end generate;
+end architecture;
+",
+        );
+        let metrics = metrics(&code);
+        assert_eq!(
+            metrics.units,
+            vec![
+                UnitMetrics {
+                    kind: "entity",
+                    name: "ent".to_owned(),
+                    lines: 4,
+                    code_lines: 4,
+                    statements: 0,
+                    assertions: 0,
+                    reports: 0,
+                    generics: 1,
+                    ports: 2,
+                    signals: Vec::new(),
+                },
+                UnitMetrics {
+                    kind: "architecture",
+                    name: "rtl of ent".to_owned(),
+                    lines: 19,
+                    code_lines: 18,
+                    statements: 7,
+                    assertions: 1,
+                    reports: 1,
+                    generics: 0,
+                    ports: 0,
+                    signals: Vec::new(),
+                }
+            ]
+        );
+        assert_eq!(metrics.units_of_kind("entity").count(), 1);
+        assert_eq!(
+            metrics.units[0].to_json(),
+            "{\"kind\": \"entity\", \"name\": \"ent\", \"lines\": 4, \"code_lines\": 4, \
+             \"statements\": 0, \"assertions\": 0, \"reports\": 0, \"generics\": 1, \
+             \"ports\": 2}"
+        );
+    }
+
+    #[test]
+    fn flags_test_bench_architectures_without_assertions() {
+        let code = Code::new(
+            "\
+architecture checked of tb is
+begin
+  assert false;
+end architecture;
+
+architecture unchecked of tb is
+begin
+  main : process
+  begin
+    report \"done\";
+    wait;
+  end process;
+end architecture;
+",
+        );
+        let mut metrics = metrics(&code);
+        assert_eq!(metrics.unchecked_architectures().count(), 0);
+
+        metrics.testbench = true;
+        let unchecked: Vec<_> = metrics
+            .unchecked_architectures()
+            .map(|unit| unit.name.as_str())
+            .collect();
+        assert_eq!(unchecked, vec!["unchecked of tb"]);
+        assert_eq!(metrics.units[1].reports, 1);
+    }
+}
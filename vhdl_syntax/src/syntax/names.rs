@@ -161,6 +161,13 @@ fn parse_actual_part(stream: &TokenStream) -> ParseResult<WithPos<ActualPart>> {
 }
 
 fn parse_association_element(stream: &TokenStream) -> ParseResult<AssociationElement> {
+    if let Some(token) = stream.pop_if_kind(Others) {
+        // Unlike an aggregate an association list must name each formal
+        return Err(Diagnostic::error(
+            &token.pos,
+            "'others' is not allowed in an association list",
+        ));
+    }
     let actual = parse_actual_part(stream)?;
     if stream.skip_if_kind(RightArrow) {
         Ok(AssociationElement {
@@ -1020,6 +1027,18 @@ mod tests {
         assert_eq!(code.with_stream(parse_association_list), vec![elem1, elem2]);
     }
 
+    #[test]
+    fn test_association_list_others_is_not_allowed() {
+        let code = Code::new("(arg => 0, others => 1)");
+        assert_eq!(
+            code.with_partial_stream(parse_association_list),
+            Err(Diagnostic::error(
+                code.s1("others"),
+                "'others' is not allowed in an association list"
+            ))
+        );
+    }
+
     #[test]
     fn test_external_name_implicit_relative() {
         let code = Code::new("<< signal dut.foo : std_logic >>");
@@ -13,6 +13,16 @@ use super::tokens::{Kind::*, TokenStream};
 use crate::ast::*;
 use crate::data::WithPos;
 
+fn parse_optional_signal_kind(stream: &TokenStream) -> Option<SignalKind> {
+    if stream.skip_if_kind(Register) {
+        Some(SignalKind::Register)
+    } else if stream.skip_if_kind(Bus) {
+        Some(SignalKind::Bus)
+    } else {
+        None
+    }
+}
+
 pub fn parse_optional_assignment(stream: &TokenStream) -> ParseResult<Option<WithPos<Expression>>> {
     if stream.pop_if_kind(ColonEq).is_some() {
         let expr = parse_expression(stream)?;
@@ -26,6 +36,7 @@ fn parse_object_declaration_kind(
     stream: &TokenStream,
     class: ObjectClass,
 ) -> ParseResult<Vec<ObjectDeclaration>> {
+    let start = stream.peek_expect()?;
     match class {
         ObjectClass::Signal => {
             stream.expect_kind(Signal)?;
@@ -45,7 +56,14 @@ fn parse_object_declaration_kind(
     let idents = parse_identifier_list(stream)?;
     stream.expect_kind(Colon)?;
     let subtype = parse_subtype_indication(stream)?;
+    let signal_kind = if class == ObjectClass::Signal {
+        parse_optional_signal_kind(stream)
+    } else {
+        None
+    };
     let opt_expression = parse_optional_assignment(stream)?;
+    let semi_token = stream.expect_kind(SemiColon)?;
+    let pos = start.pos.combine(&semi_token.pos);
 
     Ok(idents
         .into_iter()
@@ -53,7 +71,9 @@ fn parse_object_declaration_kind(
             class,
             ident: ident.into(),
             subtype_indication: subtype.clone(),
+            signal_kind,
             expression: opt_expression.clone(),
+            pos: pos.clone(),
         })
         .collect())
 }
@@ -69,7 +89,6 @@ pub fn parse_object_declaration(stream: &TokenStream) -> ParseResult<Vec<ObjectD
             parse_object_declaration_kind(stream, ObjectClass::SharedVariable)?
         }
     );
-    stream.expect_kind(SemiColon)?;
     Ok(result)
 }
 
@@ -126,7 +145,9 @@ mod tests {
                 class: ObjectClass::Constant,
                 ident: code.s1("foo").decl_ident(),
                 subtype_indication: code.s1("natural").subtype_indication(),
-                expression: None
+                signal_kind: None,
+                expression: None,
+                pos: code.pos(),
             }]
         );
     }
@@ -140,7 +161,38 @@ mod tests {
                 class: ObjectClass::Signal,
                 ident: code.s1("foo").decl_ident(),
                 subtype_indication: code.s1("natural").subtype_indication(),
-                expression: None
+                signal_kind: None,
+                expression: None,
+                pos: code.pos(),
+            }]
+        );
+    }
+
+    #[test]
+    fn parses_guarded_signal() {
+        let code = Code::new("signal foo : natural register;");
+        assert_eq!(
+            code.with_stream(parse_object_declaration),
+            vec![ObjectDeclaration {
+                class: ObjectClass::Signal,
+                ident: code.s1("foo").decl_ident(),
+                subtype_indication: code.s1("natural").subtype_indication(),
+                signal_kind: Some(SignalKind::Register),
+                expression: None,
+                pos: code.pos(),
+            }]
+        );
+
+        let code = Code::new("signal foo : natural bus := 0;");
+        assert_eq!(
+            code.with_stream(parse_object_declaration),
+            vec![ObjectDeclaration {
+                class: ObjectClass::Signal,
+                ident: code.s1("foo").decl_ident(),
+                subtype_indication: code.s1("natural").subtype_indication(),
+                signal_kind: Some(SignalKind::Bus),
+                expression: Some(code.s1("0").expr()),
+                pos: code.pos(),
             }]
         );
     }
@@ -154,7 +206,9 @@ mod tests {
                 class: ObjectClass::Variable,
                 ident: code.s1("foo").decl_ident(),
                 subtype_indication: code.s1("natural").subtype_indication(),
-                expression: None
+                signal_kind: None,
+                expression: None,
+                pos: code.pos(),
             }]
         );
     }
@@ -168,7 +222,9 @@ mod tests {
                 class: ObjectClass::SharedVariable,
                 ident: code.s1("foo").decl_ident(),
                 subtype_indication: code.s1("natural").subtype_indication(),
-                expression: None
+                signal_kind: None,
+                expression: None,
+                pos: code.pos(),
             }]
         );
     }
@@ -224,7 +280,9 @@ mod tests {
                 class: ObjectClass::Constant,
                 ident: code.s1("foo").decl_ident(),
                 subtype_indication: code.s1("natural").subtype_indication(),
-                expression: Some(code.s1("0").expr())
+                signal_kind: None,
+                expression: Some(code.s1("0").expr()),
+                pos: code.pos(),
             }]
         );
     }
@@ -238,13 +296,17 @@ mod tests {
                 class: ObjectClass::Constant,
                 ident: code.s1("foo").decl_ident(),
                 subtype_indication: code.s1("natural").subtype_indication(),
+                signal_kind: None,
                 expression: Some(code.s1("0").expr()),
+                pos: code.pos(),
             },
             ObjectDeclaration {
                 class: ObjectClass::Constant,
                 ident: code.s1("bar").decl_ident(),
                 subtype_indication: code.s1("natural").subtype_indication(),
+                signal_kind: None,
                 expression: Some(code.s1("0").expr()),
+                pos: code.pos(),
             },
         ];
 
@@ -4,7 +4,9 @@
 //
 // Copyright (c) 2018, Olof Kraigher olof.kraigher@gmail.com
 
+use super::completion::{completion_context, CompletionContext};
 use super::design_unit::parse_design_file;
+use super::metrics::{file_metrics, FileMetrics};
 use super::tokens::{Symbols, TokenStream, Tokenizer};
 use crate::ast::DesignFile;
 use crate::data::*;
@@ -41,12 +43,28 @@ impl VHDLParser {
         }
     }
 
+    /// Find the name being typed at the cursor in a library clause, use clause or context reference
+    pub fn completion_context(
+        &self,
+        source: &Source,
+        cursor: Position,
+    ) -> Option<CompletionContext> {
+        completion_context(&self.symbols, source, cursor)
+    }
+
+    /// Count the lines, statements and ports of a source file and its design units
+    pub fn metrics(&self, source: &Source) -> FileMetrics {
+        let design_file = self.parse_design_source(source, &mut NullDiagnostics);
+        file_metrics(&self.symbols, source, &design_file)
+    }
+
     pub fn parse_design_file(
         &self,
         file_name: &Path,
+        encoding: SourceEncoding,
         diagnostics: &mut dyn DiagnosticHandler,
     ) -> ParserResult {
-        let source = Source::from_latin1_file(file_name)?;
+        let source = Source::from_file(file_name, encoding)?;
         let design_file = self.parse_design_source(&source, diagnostics);
         Ok((source, design_file))
     }
@@ -5,7 +5,7 @@
 // Copyright (c) 2018, Olof Kraigher olof.kraigher@gmail.com
 
 use super::common::{check_end_identifier_mismatch, ParseResult};
-use super::declarative_part::parse_declarative_part;
+use super::declarative_part::{expect_begin, parse_declarative_part};
 use super::interface_declaration::parse_parameter_interface_list;
 use super::names::parse_type_mark;
 use super::sequential_statement::parse_labeled_sequential_statements;
@@ -80,6 +80,7 @@ pub fn parse_subprogram_declaration_no_semi(
     stream: &TokenStream,
     diagnostics: &mut dyn DiagnosticHandler,
 ) -> ParseResult<SubprogramDeclaration> {
+    let start = stream.peek_expect()?;
     let (is_function, is_pure) = {
         expect_token!(
             stream,
@@ -110,16 +111,20 @@ pub fn parse_subprogram_declaration_no_semi(
     if is_function {
         stream.expect_kind(Return)?;
         let return_type = parse_type_mark(stream)?;
+        let end = stream.last().unwrap();
         Ok(SubprogramDeclaration::Function(FunctionSpecification {
             pure: is_pure,
             designator: designator.into(),
             parameter_list,
             return_type,
+            pos: start.pos.combine(&end.pos),
         }))
     } else {
+        let end = stream.last().unwrap();
         Ok(SubprogramDeclaration::Procedure(ProcedureSpecification {
             designator: designator.into(),
             parameter_list,
+            pos: start.pos.combine(&end.pos),
         }))
     }
 }
@@ -146,7 +151,7 @@ pub fn parse_subprogram_body(
         }
     };
     let declarations = parse_declarative_part(stream, diagnostics)?;
-    stream.expect_kind(Begin)?;
+    expect_begin(stream, diagnostics)?;
 
     let statements = parse_labeled_sequential_statements(stream, diagnostics)?;
     expect_token!(
@@ -211,6 +216,7 @@ procedure foo;
                     .map_into(SubprogramDesignator::Identifier)
                     .into(),
                 parameter_list: Vec::new(),
+                pos: code.s1("procedure foo").pos(),
             })
         );
     }
@@ -232,7 +238,8 @@ function foo return lib.foo.natural;
                     .map_into(SubprogramDesignator::Identifier)
                     .into(),
                 parameter_list: Vec::new(),
-                return_type: code.s1("lib.foo.natural").type_mark()
+                return_type: code.s1("lib.foo.natural").type_mark(),
+                pos: code.s1("function foo return lib.foo.natural").pos(),
             })
         );
     }
@@ -254,7 +261,8 @@ function \"+\" return lib.foo.natural;
                 }
                 .into(),
                 parameter_list: Vec::new(),
-                return_type: code.s1("lib.foo.natural").type_mark()
+                return_type: code.s1("lib.foo.natural").type_mark(),
+                pos: code.s1("function \"+\" return lib.foo.natural").pos(),
             })
         );
     }
@@ -276,7 +284,8 @@ impure function foo return lib.foo.natural;
                     .map_into(SubprogramDesignator::Identifier)
                     .into(),
                 parameter_list: Vec::new(),
-                return_type: code.s1("lib.foo.natural").type_mark()
+                return_type: code.s1("lib.foo.natural").type_mark(),
+                pos: code.s1("impure function foo return lib.foo.natural").pos(),
             })
         );
     }
@@ -297,7 +306,8 @@ pure function foo return lib.foo.natural;
                     .map_into(SubprogramDesignator::Identifier)
                     .into(),
                 parameter_list: Vec::new(),
-                return_type: code.s1("lib.foo.natural").type_mark()
+                return_type: code.s1("lib.foo.natural").type_mark(),
+                pos: code.s1("pure function foo return lib.foo.natural").pos(),
             })
         );
     }
@@ -317,6 +327,7 @@ procedure foo(foo : natural);
                     .map_into(SubprogramDesignator::Identifier)
                     .into(),
                 parameter_list: vec![code.s1("foo : natural").parameter()],
+                pos: code.s1("procedure foo(foo : natural)").pos(),
             })
         );
     }
@@ -338,7 +349,10 @@ function foo(foo : natural) return lib.foo.natural;
                     .map_into(SubprogramDesignator::Identifier)
                     .into(),
                 parameter_list: vec![code.s1("foo : natural").parameter()],
-                return_type: code.s1("lib.foo.natural").type_mark()
+                return_type: code.s1("lib.foo.natural").type_mark(),
+                pos: code
+                    .s1("function foo(foo : natural) return lib.foo.natural")
+                    .pos(),
             })
         );
     }
@@ -40,6 +40,7 @@ pub struct CodeBuilder {
 }
 
 impl CodeBuilder {
+    #[allow(clippy::new_without_default)]
     pub fn new() -> CodeBuilder {
         CodeBuilder {
             symbols: Arc::new(Symbols::default()),
@@ -644,6 +645,7 @@ impl AsRef<SrcPos> for Code {
     }
 }
 
+#[cfg(test)]
 mod tests {
     use super::*;
 
@@ -11,7 +11,7 @@ use crate::ast::{BaseSpecifier, Ident};
 use crate::data::*;
 
 /// The kind of a Token
-#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
 pub enum Kind {
     // Keywords
     Architecture,
@@ -75,6 +75,8 @@ pub enum Kind {
     Buffer,
     Linkage,
     Signal,
+    Register,
+    Bus,
     Constant,
     Variable,
     File,
@@ -324,6 +326,8 @@ pub fn kind_str(kind: Kind) -> &'static str {
         Buffer => "buffer",
         Linkage => "linkage",
         Signal => "signal",
+        Register => "register",
+        Bus => "bus",
         Constant => "constant",
         Variable => "variable",
         File => "file",
@@ -445,6 +449,27 @@ pub enum Value {
     NoValue,
 }
 
+/// Hash the value of a token such that the same literals and identifiers give the same hash
+/// Identifiers are hashed by name such that the hashes of different symbol tables can be compared
+impl std::hash::Hash for Value {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        std::mem::discriminant(self).hash(state);
+        match self {
+            Value::Identifier(symbol) => symbol.name().bytes.hash(state),
+            Value::String(string) => string.hash(state),
+            Value::BitString(bit_string) => bit_string.hash(state),
+            Value::AbstractLiteral(ast::AbstractLiteral::Integer(value)) => {
+                (0u8, value).hash(state)
+            }
+            Value::AbstractLiteral(ast::AbstractLiteral::Real(value)) => {
+                (1u8, value.to_bits()).hash(state)
+            }
+            Value::Character(chr) => chr.hash(state),
+            Value::NoValue => {}
+        }
+    }
+}
+
 /// A Token
 #[derive(PartialEq, Clone, Debug)]
 pub struct Token {
@@ -1323,6 +1348,8 @@ impl std::default::Default for Symbols {
             ("buffer", Buffer),
             ("linkage", Linkage),
             ("signal", Signal),
+            ("register", Register),
+            ("bus", Bus),
             ("constant", Constant),
             ("variable", Variable),
             ("file", File),
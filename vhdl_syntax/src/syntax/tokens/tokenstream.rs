@@ -4,7 +4,9 @@
 //
 // Copyright (c) 2018, Olof Kraigher olof.kraigher@gmail.com
 
+use fnv::FnvHasher;
 use std::cell::Cell;
+use std::hash::{Hash, Hasher};
 
 use super::tokenizer::Kind::*;
 use super::tokenizer::*;
@@ -67,6 +69,17 @@ impl<'a> TokenStream<'a> {
         self.tokens.get(last_idx)
     }
 
+    /// A fingerprint of the kinds and values of the tokens from a previous state to the current state
+    /// Whitespace, comments and the positions of the tokens do not change the fingerprint
+    pub fn fingerprint_since(&self, state: usize) -> u64 {
+        let mut hasher = FnvHasher::default();
+        for token in self.tokens.get(state..self.get_idx()).unwrap_or_default() {
+            token.kind.hash(&mut hasher);
+            token.value.hash(&mut hasher);
+        }
+        hasher.finish()
+    }
+
     fn eof_error(&self) -> Diagnostic {
         let end = self.tokenizer.source.contents().end();
         Diagnostic::error(
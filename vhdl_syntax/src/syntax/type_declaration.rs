@@ -97,15 +97,16 @@ fn parse_record_type_definition(
 }
 
 pub fn parse_subtype_declaration(stream: &TokenStream) -> ParseResult<TypeDeclaration> {
-    stream.expect_kind(Subtype)?;
+    let start_token = stream.expect_kind(Subtype)?;
     let ident = stream.expect_ident()?;
     stream.expect_kind(Is)?;
     let subtype_indication = parse_subtype_indication(stream)?;
-    stream.expect_kind(SemiColon)?;
+    let semi_token = stream.expect_kind(SemiColon)?;
     Ok(TypeDeclaration {
         ident: ident.into(),
         def: TypeDefinition::Subtype(subtype_indication),
         end_ident_pos: None,
+        pos: start_token.pos.combine(&semi_token.pos),
     })
 }
 
@@ -205,6 +206,7 @@ pub fn parse_type_declaration(
             stream.skip();
         }
     );
+    let start = stream.last().unwrap();
 
     let ident = WithDecl::new(stream.expect_ident()?);
     let mut end_ident_pos = None;
@@ -216,7 +218,8 @@ pub fn parse_type_declaration(
             return Ok(TypeDeclaration {
                 ident,
                 def: TypeDefinition::Incomplete(Reference::default()),
-                end_ident_pos
+                end_ident_pos,
+                pos: start.pos.combine(&token.pos),
             });
         }
     );
@@ -277,10 +280,12 @@ pub fn parse_type_declaration(
         LeftPar => parse_enumeration_type_definition(stream)?
     );
 
+    let end = stream.last().unwrap();
     Ok(TypeDeclaration {
         ident,
         def,
         end_ident_pos,
+        pos: start.pos.combine(&end.pos),
     })
 }
 
@@ -300,6 +305,7 @@ mod tests {
             ident: code.s1("foo").decl_ident(),
             def: TypeDefinition::Numeric(code.s1("0 to 1").range()),
             end_ident_pos: None,
+            pos: code.pos(),
         };
         assert_eq!(
             code.with_stream_no_diagnostics(parse_type_declaration),
@@ -324,6 +330,7 @@ mod tests {
                     .into(),
             ]),
             end_ident_pos: None,
+            pos: code.pos(),
         };
         assert_eq!(
             code.with_stream_no_diagnostics(parse_type_declaration),
@@ -348,6 +355,7 @@ mod tests {
                     .into(),
             ]),
             end_ident_pos: None,
+            pos: code.pos(),
         };
         assert_eq!(
             code.with_stream_no_diagnostics(parse_type_declaration),
@@ -372,6 +380,7 @@ mod tests {
                     .into(),
             ]),
             end_ident_pos: None,
+            pos: code.pos(),
         };
         assert_eq!(
             code.with_stream_no_diagnostics(parse_type_declaration),
@@ -392,6 +401,7 @@ mod tests {
                 code.s1("boolean").subtype_indication(),
             ),
             end_ident_pos: None,
+            pos: code.pos(),
         };
 
         assert_eq!(
@@ -414,6 +424,7 @@ mod tests {
                 code.s1("boolean").subtype_indication(),
             ),
             end_ident_pos: None,
+            pos: code.pos(),
         };
 
         assert_eq!(
@@ -436,6 +447,7 @@ mod tests {
                 code.s1("boolean").subtype_indication(),
             ),
             end_ident_pos: None,
+            pos: code.pos(),
         };
 
         assert_eq!(
@@ -457,6 +469,7 @@ mod tests {
                 code.s1("boolean").subtype_indication(),
             ),
             end_ident_pos: None,
+            pos: code.pos(),
         };
 
         assert_eq!(
@@ -475,6 +488,7 @@ mod tests {
             ident: code.s1("foo").decl_ident(),
             def: TypeDefinition::Array(vec![index], code.s1("boolean").subtype_indication()),
             end_ident_pos: None,
+            pos: code.pos(),
         };
 
         assert_eq!(
@@ -498,6 +512,7 @@ mod tests {
                 code.s1("boolean").subtype_indication(),
             ),
             end_ident_pos: None,
+            pos: code.pos(),
         };
 
         assert_eq!(
@@ -524,6 +539,7 @@ end record;",
             ident: code.s1("foo").decl_ident(),
             def: TypeDefinition::Record(vec![elem_decl]),
             end_ident_pos: None,
+            pos: code.pos(),
         };
 
         assert_eq!(
@@ -561,6 +577,7 @@ end foo;",
             ident: code.s1("foo").decl_ident(),
             def: TypeDefinition::Record(vec![elem_decl0a, elem_decl0b, elem_decl1]),
             end_ident_pos: Some(code.s("foo", 2).pos()),
+            pos: code.pos(),
         };
 
         assert_eq!(
@@ -581,6 +598,7 @@ end foo;",
                     code.s1("integer_vector(2-1 downto 0)").subtype_indication()
                 ),
                 end_ident_pos: None,
+                pos: code.pos(),
             }
         );
     }
@@ -597,6 +615,7 @@ end foo;",
                     code.s1("integer_vector(2-1 downto 0)").subtype_indication()
                 ),
                 end_ident_pos: None,
+                pos: code.pos(),
             }
         );
     }
@@ -611,6 +630,7 @@ end foo;",
                 ident: code.s1("incomplete").decl_ident(),
                 def: TypeDefinition::Incomplete(Reference::default()),
                 end_ident_pos: None,
+                pos: code.pos(),
             }
         );
     }
@@ -625,6 +645,7 @@ end foo;",
                 ident: code.s1("foo").decl_ident(),
                 def: TypeDefinition::File(code.s1("character").type_mark()),
                 end_ident_pos: None,
+                pos: code.pos(),
             }
         );
     }
@@ -633,11 +654,13 @@ end foo;",
         ident: Ident,
         items: Vec<ProtectedTypeDeclarativeItem>,
         end_ident_pos: Option<SrcPos>,
+        pos: SrcPos,
     ) -> TypeDeclaration {
         TypeDeclaration {
             ident: ident.into(),
             def: TypeDefinition::Protected(ProtectedTypeDeclaration { items }),
             end_ident_pos,
+            pos,
         }
     }
 
@@ -651,7 +674,12 @@ end protected;
         );
         assert_eq!(
             code.with_stream_no_diagnostics(parse_type_declaration),
-            protected_decl(code.s1("foo").ident(), vec![], None)
+            protected_decl(
+                code.s1("foo").ident(),
+                vec![],
+                None,
+                code.s1("type").pos().combine(&code.s1("end protected;"))
+            )
         )
     }
 
@@ -665,7 +693,14 @@ end protected foo;
         );
         assert_eq!(
             code.with_stream_no_diagnostics(parse_type_declaration),
-            protected_decl(code.s1("foo").ident(), vec![], Some(code.s("foo", 2).pos()))
+            protected_decl(
+                code.s1("foo").ident(),
+                vec![],
+                Some(code.s("foo", 2).pos()),
+                code.s1("type")
+                    .pos()
+                    .combine(&code.s1("end protected foo;"))
+            )
         )
     }
 
@@ -688,7 +723,12 @@ end protected;
 
         assert_eq!(
             code.with_stream_no_diagnostics(parse_type_declaration),
-            protected_decl(code.s1("foo").ident(), items, None)
+            protected_decl(
+                code.s1("foo").ident(),
+                items,
+                None,
+                code.s1("type").pos().combine(&code.s1("end protected;"))
+            )
         )
     }
 
@@ -720,6 +760,10 @@ end protected body;
                 ident,
                 def: TypeDefinition::ProtectedBody(ProtectedTypeBody { decl }),
                 end_ident_pos: None,
+                pos: code
+                    .s1("type")
+                    .pos()
+                    .combine(&code.s1("end protected body;")),
             }
         )
     }
@@ -744,6 +788,7 @@ end units phys;
                     secondary_units: vec![]
                 }),
                 end_ident_pos: Some(code.s("phys", 2).pos()),
+                pos: code.s1("type").pos().combine(&code.s1("end units phys;")),
             }
         )
     }
@@ -775,6 +820,7 @@ end units;
                     ),]
                 }),
                 end_ident_pos: None,
+                pos: code.s1("type").pos().combine(&code.s1("end units;")),
             }
         )
     }
@@ -806,6 +852,7 @@ end units;
                     ),]
                 }),
                 end_ident_pos: None,
+                pos: code.s1("type").pos().combine(&code.s1("end units;")),
             }
         )
     }